@@ -0,0 +1,129 @@
+//! Criterion benchmarks for the pure formatting and parsing code
+
+use criterion::{BenchmarkId, Criterion, black_box, criterion_group, criterion_main};
+use logseq_mcp_server::fuzzy;
+use logseq_mcp_server::logseq::api::{Block, SearchResult, TodoItem};
+use logseq_mcp_server::logseq::content::{extract_tags, split_marker};
+use logseq_mcp_server::logseq::journal::{DEFAULT_DATE_FORMAT, parse_journal_title};
+use logseq_mcp_server::logseq::page_name::normalize_page_name;
+use logseq_mcp_server::tools::{
+    TodoGroupBy, format_blocks_as_markdown, format_search_results, format_todos,
+};
+
+fn block(uuid: usize, content: String, children: Vec<Block>) -> Block {
+    Block {
+        uuid: format!("00000000-0000-0000-0000-{:012}", uuid),
+        content,
+        page: None,
+        properties: None,
+        children,
+        level: None,
+        format: None,
+    }
+}
+
+/// A page of `roots` top-level blocks, each with `children` children
+fn page_tree(roots: usize, children: usize) -> Vec<Block> {
+    (0..roots)
+        .map(|r| {
+            let kids = (0..children)
+                .map(|c| {
+                    block(
+                        r * 1000 + c,
+                        format!("child {} of {} #tag{}", c, r, c % 20),
+                        vec![],
+                    )
+                })
+                .collect();
+            block(r, format!("Root block {} with [[Some Page]] link", r), kids)
+        })
+        .collect()
+}
+
+fn todos(count: usize) -> Vec<TodoItem> {
+    (0..count)
+        .map(|i| TodoItem {
+            uuid: format!("uuid-{}", i),
+            content: format!("TODO task {} #project{} #[[area {}]]", i, i % 30, i % 7),
+            marker: ["TODO", "DOING", "LATER", "NOW"][i % 4].to_string(),
+            page_name: format!("page {}", i % 200),
+            priority: ["A", "B", "C"].get(i % 5).map(|p| p.to_string()),
+            scheduled: i.is_multiple_of(3).then_some(20240101 + (i % 28) as u32),
+            tags: vec![format!("project{}", i % 30), format!("area {}", i % 7)],
+        })
+        .collect()
+}
+
+fn bench_formatters(c: &mut Criterion) {
+    let mut group = c.benchmark_group("format_blocks_as_markdown");
+    for (roots, children) in [(50, 4), (500, 10)] {
+        let blocks = page_tree(roots, children);
+        group.bench_with_input(
+            BenchmarkId::from_parameter(roots * (children + 1)),
+            &blocks,
+            |b, blocks| b.iter(|| format_blocks_as_markdown(black_box(blocks))),
+        );
+    }
+    group.finish();
+
+    let todos = todos(5000);
+    let mut group = c.benchmark_group("format_todos");
+    for (label, group_by) in [
+        ("marker", TodoGroupBy::Marker),
+        ("page", TodoGroupBy::Page),
+        ("tag", TodoGroupBy::Tag),
+        ("due-week", TodoGroupBy::DueWeek),
+    ] {
+        group.bench_function(label, |b| {
+            b.iter(|| format_todos(black_box(&todos), group_by))
+        });
+    }
+    group.finish();
+
+    let results: Vec<SearchResult> = page_tree(2000, 0)
+        .into_iter()
+        .map(|block| SearchResult {
+            snippet: Some(format!("…{}…", block.content)),
+            page_name: "Some Page".to_string(),
+            breadcrumb: vec!["Parent".to_string()],
+            updated_at: None,
+            block,
+            score: None,
+        })
+        .collect();
+    c.bench_function("format_search_results/2000", |b| {
+        b.iter(|| format_search_results(black_box(&results)))
+    });
+}
+
+fn bench_parsers(c: &mut Criterion) {
+    let content =
+        "DOING [#A] Review #rust and #[[mcp server]] notes for #project-alpha, see [[Page]]";
+    c.bench_function("extract_tags", |b| {
+        b.iter(|| extract_tags(black_box(content)))
+    });
+    c.bench_function("split_marker", |b| {
+        b.iter(|| split_marker(black_box(content)))
+    });
+    c.bench_function("normalize_page_name", |b| {
+        b.iter(|| normalize_page_name(black_box("  Projects /  Alpha   Beta / Notes ")))
+    });
+    c.bench_function("parse_journal_title", |b| {
+        b.iter(|| parse_journal_title(black_box("Jan 31st, 2024"), DEFAULT_DATE_FORMAT))
+    });
+
+    let names: Vec<String> = (0..10_000)
+        .map(|i| format!("Bench Page {:05}", i))
+        .collect();
+    c.bench_function("fuzzy_suggest/10000", |b| {
+        b.iter(|| {
+            fuzzy::suggest(
+                black_box("bench pgae 04321"),
+                names.iter().map(String::as_str),
+            )
+        })
+    });
+}
+
+criterion_group!(benches, bench_formatters, bench_parsers);
+criterion_main!(benches);
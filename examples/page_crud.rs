@@ -0,0 +1,47 @@
+//! Create, read, update, query, and delete a page with the blocking client
+//!
+//! Runs against LogSeq's HTTP API server, configured as for the MCP server:
+//!
+//! ```sh
+//! LOGSEQ_API_TOKEN=your-token cargo run --example page_crud --features blocking
+//! ```
+//!
+//! `LOGSEQ_API_URL` defaults to `http://localhost:12315`. The example makes
+//! a page named after the current time, and deletes it again at the end.
+
+use logseq_mcp_server::logseq::api::{
+    CreatePageOptions, DEFAULT_API_URL, LogSeqClient, edn_string,
+};
+use logseq_mcp_server::logseq::blocking;
+use serde_json::json;
+
+fn main() -> anyhow::Result<()> {
+    let token = std::env::var("LOGSEQ_API_TOKEN")
+        .map_err(|_| anyhow::anyhow!("Set LOGSEQ_API_TOKEN to LogSeq's API token"))?;
+    let url = std::env::var("LOGSEQ_API_URL").unwrap_or_else(|_| DEFAULT_API_URL.to_string());
+    let client = blocking::LogSeqClient::build(LogSeqClient::builder().url(url).token(token))?;
+
+    // Create
+    let name = format!("Client Example {}", chrono::Local::now().format("%H%M%S"));
+    let page = client.create_page(&name, None, CreatePageOptions::default())?;
+    println!("Created page '{}' ({})", name, page.uuid);
+    let block = client.append_block_in_page(&name, "TODO Try the blocking client")?;
+
+    // Update and read back
+    client.update_block(&block.uuid, "DONE Try the blocking client", None)?;
+    for block in client.get_page_blocks_tree(&name)?.iter() {
+        println!("  - {}", block.content);
+    }
+
+    // Query, passing the page name as an EDN string input
+    let done = client.datascript_query(
+        "[:find (count ?b) . :in $ ?name :where [?p :block/name ?name] [?b :block/page ?p] [?b :block/marker \"DONE\"]]",
+        &[json!(edn_string(&name.to_lowercase()))],
+    )?;
+    println!("Done tasks on the page: {}", done);
+
+    // Delete
+    client.delete_page(&name)?;
+    println!("Deleted page '{}'", name);
+    Ok(())
+}
@@ -0,0 +1,31 @@
+//! Deserialize arbitrary JSON into the page and block types, the way the
+//! client decodes `getPage`, `getAllPages`, `getBlock`, and
+//! `getPageBlocksTree` responses. Malformed shapes must surface as errors or
+//! skipped-entry warnings, never panics, and anything that decodes must
+//! survive formatting.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use logseq_mcp_server::logseq::api::{Block, Page, decode_block_tree, decode_entries};
+use logseq_mcp_server::tools::{format_blocks_as_markdown, format_skipped_entries};
+use serde_json::Value;
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(value) = serde_json::from_slice::<Value>(data) else {
+        return;
+    };
+
+    let _ = serde_json::from_value::<Page>(value.clone());
+    if let Ok(pages) = decode_entries::<Page>(value.clone(), "page") {
+        format_skipped_entries(&pages.warnings);
+    }
+
+    if let Ok(block) = serde_json::from_value::<Block>(value.clone()) {
+        format_blocks_as_markdown(std::slice::from_ref(&block));
+    }
+    if let Ok(blocks) = decode_block_tree(value) {
+        format_blocks_as_markdown(&blocks);
+        format_skipped_entries(&blocks.warnings);
+    }
+});
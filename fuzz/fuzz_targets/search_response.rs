@@ -0,0 +1,47 @@
+//! Parse arbitrary JSON as a `logseq.App.search` response and as datascript
+//! content-search rows, then rank and format the hits as the `search` tool
+//! does.
+
+#![no_main]
+
+use chrono::Utc;
+use libfuzzer_sys::fuzz_target;
+use logseq_mcp_server::logseq::api::{
+    SearchHit, SearchResult, parse_app_search, parse_search_rows,
+};
+use logseq_mcp_server::logseq::content::search_snippet;
+use logseq_mcp_server::ranking::{SearchWeights, rank_blocks};
+use logseq_mcp_server::tools::format_search_result;
+use serde_json::Value;
+
+const QUERY: &str = "rust";
+
+/// A hit as the client passes it on once it knows where the block is
+fn located(hit: SearchHit) -> SearchResult {
+    SearchResult {
+        snippet: hit
+            .snippet
+            .or_else(|| search_snippet(&hit.block.content, QUERY)),
+        page_name: hit.page_name.unwrap_or_default(),
+        breadcrumb: Vec::new(),
+        updated_at: None,
+        block: hit.block,
+        score: hit.score,
+    }
+}
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(value) = serde_json::from_slice::<Value>(data) else {
+        return;
+    };
+
+    let hits = parse_app_search(&value, QUERY)
+        .into_iter()
+        .chain(parse_search_rows(&value))
+        .map(located)
+        .collect();
+    let ranked = rank_blocks(QUERY, hits, &SearchWeights::default(), Utc::now());
+    for (i, (result, _)) in ranked.iter().enumerate() {
+        format_search_result(i + 1, result);
+    }
+});
@@ -0,0 +1,14 @@
+//! Build snippets from arbitrary block content and queries, and normalise
+//! arbitrary search-index highlight markup. Both slice strings by byte
+//! offset, so multi-byte and case-folding edge cases matter here.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use logseq_mcp_server::logseq::content::{normalize_search_highlights, search_snippet};
+
+fuzz_target!(|input: (&str, &str)| {
+    let (content, query) = input;
+    search_snippet(content, query);
+    normalize_search_highlights(content);
+});
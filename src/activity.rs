@@ -0,0 +1,119 @@
+//! A trail of agent activity in the user's journal
+//!
+//! When journaling is on, every successful call that changes the graph adds
+//! a one-line summary, such as ``09:30 `create_block` parent: `Meetings`,
+//! content: `Draft agenda` ``, to today's journal page under an "Agent
+//! activity" heading. The heading and its entries live in a managed region
+//! (see [`crate::logseq::region`]), so the user can move them around the
+//! page and keep their own notes beside them. Argument values are shown as
+//! inline code, so page names and tags in them don't become references
+//! from the journal.
+
+use crate::logseq::content::truncate;
+use crate::logseq::markdown::BatchBlock;
+use crate::logseq::region::append_to_region;
+use crate::tools::context::ToolContext;
+use chrono::{Local, NaiveTime};
+use rmcp::model::JsonObject;
+use serde_json::Value;
+
+/// The managed region holding the activity on a journal page
+pub const ACTIVITY_REGION: &str = "agent-activity";
+
+/// The block the entries are added under
+pub const ACTIVITY_HEADING: &str = "Agent activity";
+
+/// Characters of an argument value shown in an entry
+const VALUE_CHARS: usize = 60;
+
+/// Arguments not worth repeating in an entry
+const SKIPPED_ARGUMENTS: &[&str] = &["confirm", "graph"];
+
+/// The journal entry for a call to `tool` with `arguments`, made at `time`
+pub fn summary(tool: &str, arguments: &JsonObject, time: NaiveTime) -> String {
+    let mut line = format!("{} `{}`", time.format("%H:%M"), tool);
+    let details: Vec<String> = arguments
+        .iter()
+        .filter(|(key, _)| !SKIPPED_ARGUMENTS.contains(&key.as_str()))
+        .filter_map(|(key, value)| Some(format!("{}: `{}`", key, short_value(value)?)))
+        .collect();
+    if !details.is_empty() {
+        line.push(' ');
+        line.push_str(&details.join(", "));
+    }
+    line
+}
+
+/// A value shortened to part of one line, or `None` for one that doesn't
+/// fit on a line at all
+fn short_value(value: &Value) -> Option<String> {
+    let text = match value {
+        Value::String(text) => text.lines().next().unwrap_or_default().trim().to_string(),
+        Value::Number(_) | Value::Bool(_) => value.to_string(),
+        Value::Array(items) => format!("{} item(s)", items.len()),
+        Value::Null | Value::Object(_) => return None,
+    };
+    // A backtick would end the inline code early
+    let text = text.replace('`', "'");
+    Some(truncate(&text, VALUE_CHARS))
+}
+
+/// Add a successful call to today's journal, creating the journal page if
+/// it doesn't exist yet. A failure is logged rather than returned, as the
+/// change itself has already been made.
+pub async fn journal(ctx: &ToolContext, tool: &str, arguments: &JsonObject) {
+    let now = Local::now();
+    let title = ctx.today_title().await;
+    let entry = BatchBlock {
+        content: summary(tool, arguments, now.time()),
+        children: Vec::new(),
+    };
+
+    let _guard = ctx.page_locks.lock(&title).await;
+    let written = async {
+        ctx.ensure_journal_page(&title).await?;
+        append_to_region(
+            &ctx.client,
+            &title,
+            ACTIVITY_REGION,
+            ACTIVITY_HEADING,
+            entry,
+        )
+        .await
+    }
+    .await;
+    if let Err(e) = written {
+        tracing::warn!("Failed to add {} to the journal '{}': {}", tool, title, e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_summaries_fit_on_one_line() {
+        let arguments = json!({
+            "page": "Meetings",
+            "content": "Review `main` with [[Alice]]\nSecond line",
+            "properties": {"status": "draft"},
+            "confirm": "abc123",
+            "tags": ["a", "b"],
+            "before": true,
+        });
+        let time = NaiveTime::from_hms_opt(9, 30, 0).unwrap();
+        assert_eq!(
+            summary("create_block", arguments.as_object().unwrap(), time),
+            "09:30 `create_block` before: `true`, content: `Review 'main' with [[Alice]]`, page: `Meetings`, tags: `2 item(s)`"
+        );
+        assert_eq!(
+            summary("reset_write_budget", &JsonObject::new(), time),
+            "09:30 `reset_write_budget`"
+        );
+
+        let long = json!({"content": "word ".repeat(40)});
+        let line = summary("update_block", long.as_object().unwrap(), time);
+        assert!(line.ends_with("word…`"));
+    }
+}
@@ -0,0 +1,157 @@
+//! Append-only audit log of tool calls that change the graph
+//!
+//! When enabled, every call to a tool that can modify the graph (creating,
+//! updating, moving, deleting, restoring) is appended to a JSONL file as one
+//! [`AuditEntry`], whether it succeeded or not. Read-only tools and delete
+//! previews that haven't been confirmed are not recorded.
+
+use chrono::{DateTime, Utc};
+use rmcp::model::JsonObject;
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// File name of the audit log inside the state directory's audit area
+pub const AUDIT_LOG_FILE: &str = "audit.jsonl";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AuditOutcome {
+    Ok,
+    Error,
+}
+
+/// One line of the audit log
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditEntry {
+    pub timestamp: DateTime<Utc>,
+    /// Correlation ID of the tool call, matching its log lines
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub request_id: Option<String>,
+    pub tool: String,
+    pub arguments: JsonObject,
+    pub outcome: AuditOutcome,
+    /// UUIDs reported in the result, such as a newly created block's
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub uuids: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// An open audit log file
+#[derive(Debug)]
+pub struct AuditLog {
+    path: PathBuf,
+    file: Mutex<File>,
+}
+
+impl AuditLog {
+    /// Open `path` for appending, creating it and its directory if needed
+    pub fn open(path: impl Into<PathBuf>) -> io::Result<Self> {
+        let path = path.into();
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+        Ok(Self {
+            path,
+            file: Mutex::new(file),
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Append an entry as a single line
+    pub fn record(&self, entry: &AuditEntry) -> io::Result<()> {
+        let mut line = serde_json::to_vec(entry)?;
+        line.push(b'\n');
+
+        let mut file = self.file.lock().unwrap_or_else(|e| e.into_inner());
+        file.write_all(&line)?;
+        file.flush()
+    }
+}
+
+/// UUIDs appearing in `text`, in order of first appearance
+pub fn mentioned_uuids(text: &str) -> Vec<String> {
+    let bytes = text.as_bytes();
+    let mut uuids: Vec<String> = Vec::new();
+    let mut i = 0;
+    while i + 36 <= bytes.len() {
+        let candidate = &bytes[i..i + 36];
+        let bounded = (i == 0 || !bytes[i - 1].is_ascii_alphanumeric())
+            && bytes.get(i + 36).is_none_or(|b| !b.is_ascii_alphanumeric());
+        if bounded && is_uuid(candidate) {
+            let uuid = String::from_utf8_lossy(candidate).to_lowercase();
+            if !uuids.contains(&uuid) {
+                uuids.push(uuid);
+            }
+            i += 36;
+        } else {
+            i += 1;
+        }
+    }
+    uuids
+}
+
+fn is_uuid(bytes: &[u8]) -> bool {
+    bytes.iter().enumerate().all(|(i, b)| match i {
+        8 | 13 | 18 | 23 => *b == b'-',
+        _ => b.is_ascii_hexdigit(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_record_appends_json_lines() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("audit").join(AUDIT_LOG_FILE);
+
+        for tool in ["create_page", "delete_page"] {
+            let log = AuditLog::open(&path).unwrap();
+            log.record(&AuditEntry {
+                timestamp: Utc::now(),
+                request_id: Some("abc123".into()),
+                tool: tool.into(),
+                arguments: json!({"name": "Projects"}).as_object().cloned().unwrap(),
+                outcome: AuditOutcome::Ok,
+                uuids: vec![],
+                error: None,
+            })
+            .unwrap();
+        }
+
+        let text = fs::read_to_string(&path).unwrap();
+        let entries: Vec<AuditEntry> = text
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[1].tool, "delete_page");
+        assert!(!text.contains("uuids"), "empty fields are omitted");
+    }
+
+    #[test]
+    fn test_mentioned_uuids() {
+        let text = "Created block with UUID: 6650A1B2-0000-4c1d-8e2f-123456789abc\n\
+                    Moved under 6650a1b2-0000-4c1d-8e2f-123456789abc and \
+                    00000000-1111-2222-3333-444444444444.";
+        assert_eq!(
+            mentioned_uuids(text),
+            vec![
+                "6650a1b2-0000-4c1d-8e2f-123456789abc",
+                "00000000-1111-2222-3333-444444444444"
+            ]
+        );
+        assert!(mentioned_uuids("x6650a1b2-0000-4c1d-8e2f-123456789abc").is_empty());
+        assert!(mentioned_uuids("Saved to the trash as 20240101T000000-abcd1234").is_empty());
+    }
+}
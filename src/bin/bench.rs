@@ -0,0 +1,230 @@
+//! Large-graph benchmark harness.
+//!
+//! Generates a synthetic graph, serves it from the in-process mock LogSeq
+//! API in `logseq::mock`, and measures end-to-end tool latencies through the
+//! tool registry (client, cache, and formatters included):
+//!
+//! ```text
+//! cargo run --release --features bench --bin logseq-mcp-bench -- --pages 2000 --blocks 50
+//! ```
+
+use clap::{Arg, Command};
+use logseq_mcp_server::logseq::mock::{MockGraph, MockLogSeq};
+use logseq_mcp_server::tools::context::ToolContext;
+use logseq_mcp_server::tools::registry::ToolRegistry;
+use rmcp::model::{JsonObject, RawContent};
+use serde_json::{Value, json};
+use std::time::{Duration, Instant};
+
+const WORDS: [&str; 12] = [
+    "alpha", "bravo", "charlie", "delta", "echo", "foxtrot", "golf", "hotel", "india", "juliet",
+    "kilo", "lima",
+];
+const MARKERS: [&str; 4] = ["TODO", "DOING", "LATER", "NOW"];
+const PRIORITIES: [&str; 4] = ["A", "B", "C", ""];
+
+/// Generate a synthetic graph, returning it with its number of todos
+fn generate(page_count: usize, blocks_per_page: usize) -> (MockGraph, usize) {
+    let mut graph = MockGraph::new();
+    let mut todos = 0;
+
+    let mut seq = 0usize;
+    for p in 0..page_count {
+        let name = match p % 10 {
+            0 => format!("Projects/Bench {:05}", p),
+            _ => format!("Bench Page {:05}", p),
+        };
+        graph.add_page(&name);
+
+        let mut previous: Option<String> = None;
+        for b in 0..blocks_per_page {
+            seq += 1;
+            let words: Vec<&str> = (0..8).map(|w| WORDS[(seq + w * 7) % WORDS.len()]).collect();
+            let mut content = format!("{} #tag{}", words.join(" "), seq % 50);
+            if seq.is_multiple_of(7) {
+                let priority = match PRIORITIES[seq % PRIORITIES.len()] {
+                    "" => String::new(),
+                    priority => format!("[#{}] ", priority),
+                };
+                content = format!("{} {}{}", MARKERS[seq % MARKERS.len()], priority, content);
+                if seq.is_multiple_of(3) {
+                    content.push_str(&format!("\nSCHEDULED: <2024-01-{:02}>", 1 + seq % 28));
+                }
+                todos += 1;
+            }
+
+            // Nest every fourth block under the previous one for some depth
+            match &previous {
+                Some(parent) if b % 4 == 3 => {
+                    graph.add_block(parent, &content);
+                }
+                _ => previous = Some(graph.add_block(&name, &content)),
+            }
+        }
+    }
+    (graph, todos)
+}
+
+struct Scenario {
+    label: &'static str,
+    tool: &'static str,
+    arguments: Value,
+}
+
+struct Measurement {
+    label: &'static str,
+    samples: Vec<Duration>,
+    output_bytes: usize,
+}
+
+impl Measurement {
+    fn percentile(&self, p: f64) -> Duration {
+        let index = ((self.samples.len() - 1) as f64 * p).round() as usize;
+        self.samples[index]
+    }
+}
+
+async fn run(
+    ctx: &ToolContext,
+    registry: &ToolRegistry,
+    scenario: &Scenario,
+    iterations: usize,
+) -> Result<Measurement, Box<dyn std::error::Error>> {
+    let arguments: Option<JsonObject> = scenario.arguments.as_object().cloned();
+    let mut samples = Vec::with_capacity(iterations);
+    let mut output_bytes = 0;
+
+    // One untimed call so connection setup and cache fills don't skew results
+    registry.call(ctx, scenario.tool, arguments.clone()).await?;
+
+    for _ in 0..iterations {
+        let start = Instant::now();
+        let result = registry.call(ctx, scenario.tool, arguments.clone()).await?;
+        samples.push(start.elapsed());
+
+        output_bytes = result
+            .content
+            .iter()
+            .flatten()
+            .map(|c| match &c.raw {
+                RawContent::Text(text) => text.text.len(),
+                _ => 0,
+            })
+            .sum();
+    }
+    samples.sort();
+
+    Ok(Measurement {
+        label: scenario.label,
+        samples,
+        output_bytes,
+    })
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let matches = Command::new("logseq-mcp-bench")
+        .about("Measure tool latencies against a synthetic large graph")
+        .arg(
+            Arg::new("pages")
+                .long("pages")
+                .value_parser(clap::value_parser!(usize))
+                .default_value("2000")
+                .help("Number of pages in the synthetic graph"),
+        )
+        .arg(
+            Arg::new("blocks")
+                .long("blocks")
+                .value_parser(clap::value_parser!(usize))
+                .default_value("50")
+                .help("Blocks per page"),
+        )
+        .arg(
+            Arg::new("iterations")
+                .long("iterations")
+                .value_parser(clap::value_parser!(u64).range(1..))
+                .default_value("20")
+                .help("Timed calls per scenario"),
+        )
+        .get_matches();
+
+    let pages = *matches.get_one::<usize>("pages").unwrap_or(&2000);
+    let blocks = *matches.get_one::<usize>("blocks").unwrap_or(&50);
+    let iterations = *matches.get_one::<u64>("iterations").unwrap_or(&20) as usize;
+
+    let start = Instant::now();
+    let (graph, todo_count) = generate(pages.max(1), blocks.max(1));
+    let logseq = MockLogSeq::serve(graph).await?;
+    println!(
+        "Generated {} pages × {} blocks ({} todos) in {:.1?}, serving at {}\n",
+        pages,
+        blocks,
+        todo_count,
+        start.elapsed(),
+        logseq.url()
+    );
+
+    let registry = ToolRegistry::builtin();
+    let uncached = ToolContext::new(logseq.client());
+    let cached = ToolContext::with_cache(logseq.client());
+
+    let scenarios = [
+        Scenario {
+            label: "list_pages",
+            tool: "list_pages",
+            arguments: json!({}),
+        },
+        Scenario {
+            label: "search",
+            tool: "search",
+            arguments: json!({"query": "charlie"}),
+        },
+        Scenario {
+            label: "search (page, case-sensitive)",
+            tool: "search",
+            arguments: json!({"query": "charlie", "page": "Bench Page 00001", "case_sensitive": true}),
+        },
+        Scenario {
+            label: "get_page_content",
+            tool: "get_page_content",
+            arguments: json!({"page_name": "Bench Page 00001"}),
+        },
+        Scenario {
+            label: "find_incomplete_todos",
+            tool: "find_incomplete_todos",
+            arguments: json!({"limit": 100}),
+        },
+        Scenario {
+            label: "find_incomplete_todos (page)",
+            tool: "find_incomplete_todos",
+            arguments: json!({"limit": 100, "group_by": "page"}),
+        },
+    ];
+
+    let mut results = Vec::new();
+    for scenario in &scenarios {
+        results.push(run(&uncached, &registry, scenario, iterations).await?);
+    }
+    let warm = Scenario {
+        label: "list_pages (warm cache)",
+        tool: "list_pages",
+        arguments: json!({}),
+    };
+    results.push(run(&cached, &registry, &warm, iterations).await?);
+
+    println!(
+        "{:<30} {:>10} {:>10} {:>10} {:>12}",
+        "scenario", "min", "median", "p95", "output"
+    );
+    for m in &results {
+        println!(
+            "{:<30} {:>10.2?} {:>10.2?} {:>10.2?} {:>10} B",
+            m.label,
+            m.samples[0],
+            m.percentile(0.5),
+            m.percentile(0.95),
+            m.output_bytes
+        );
+    }
+    Ok(())
+}
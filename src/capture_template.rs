@@ -0,0 +1,101 @@
+//! Templates that shape what `capture` writes
+//!
+//! A capture template is configured as a name followed by the template:
+//! `meeting {{time}} {{text}} (via {{source}})`. Its placeholders are
+//! `{{text}}`, the note itself, which every template must use; `{{time}}`,
+//! the local time as `HH:MM`; and `{{source}}`, where the note came from.
+//! The template named `default` applies to every capture that doesn't pick
+//! another, directly or through its workspace.
+
+use crate::logseq::content::fill_template;
+use chrono::{DateTime, Local};
+use std::collections::HashMap;
+
+/// The template used when a capture names none
+pub const DEFAULT_TEMPLATE: &str = "default";
+
+/// Placeholders a capture template can use
+const PLACEHOLDERS: [&str; 3] = ["text", "time", "source"];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CaptureTemplate {
+    pub name: String,
+    pub template: String,
+}
+
+impl CaptureTemplate {
+    /// Parse a name followed by the template, which must use `{{text}}` and
+    /// no unknown placeholders
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let text = text.trim();
+        let Some((name, template)) = text.split_once(char::is_whitespace) else {
+            return Err(format!(
+                "'{}' must be a name followed by the template, such as 'default {{{{time}}}} {{{{text}}}}'",
+                text
+            ));
+        };
+        let template = template.trim();
+        let (_, placeholders) = fill_template(template, &HashMap::new());
+        if let Some(unknown) = placeholders
+            .iter()
+            .find(|p| !PLACEHOLDERS.contains(&p.as_str()))
+        {
+            return Err(format!(
+                "capture template {} has an unknown placeholder {{{{{}}}}}; use {{{{text}}}}, {{{{time}}}}, or {{{{source}}}}",
+                name, unknown
+            ));
+        }
+        if !placeholders.iter().any(|p| p == "text") {
+            return Err(format!(
+                "capture template {} must include {{{{text}}}}, where the note goes",
+                name
+            ));
+        }
+        Ok(Self {
+            name: name.to_string(),
+            template: template.to_string(),
+        })
+    }
+
+    /// The template filled in with a note, where it came from, and when
+    pub fn render(&self, text: &str, source: &str, now: DateTime<Local>) -> String {
+        let variables = HashMap::from([
+            ("text".to_string(), text.to_string()),
+            ("time".to_string(), now.format("%H:%M").to_string()),
+            ("source".to_string(), source.to_string()),
+        ]);
+        fill_template(&self.template, &variables)
+            .0
+            .trim()
+            .to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_templates_parse_and_render() {
+        let template =
+            CaptureTemplate::parse("default  {{time}} {{text}} (via {{source}})").unwrap();
+        assert_eq!(template.name, "default");
+        let now = Local.with_ymd_and_hms(2026, 3, 4, 9, 5, 0).unwrap();
+        assert_eq!(
+            template.render("Call Sam", "Claude", now),
+            "09:05 Call Sam (via Claude)"
+        );
+
+        // LogSeq macros are left alone
+        let template = CaptureTemplate::parse("inbox {{text}} {{embed [[Inbox]]}}").unwrap();
+        assert_eq!(
+            template.render("Note", "agent", now),
+            "Note {{embed [[Inbox]]}}"
+        );
+
+        assert!(CaptureTemplate::parse("default").is_err());
+        assert!(CaptureTemplate::parse("default {{time}} captured").is_err());
+        assert!(CaptureTemplate::parse("default {{text}} {{date}}").is_err());
+    }
+}
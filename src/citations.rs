@@ -0,0 +1,137 @@
+//! Citation markers for blocks an answer draws on
+//!
+//! Retrieved blocks are numbered `[1]`, `[2]`, … and labelled with where
+//! they sit, `[1] Page > Parent > Block`, so an agent can cite them in its
+//! answer. The same block always gets the same marker. Alongside the text,
+//! tools return the markers as a citation map in the result's structured
+//! content, giving each marker's block UUID and `((ref))`.
+
+use crate::logseq::content::{split_marker, strip_property_lines, truncate};
+use serde::Serialize;
+use serde_json::Value;
+
+/// Characters of a block's first line used to label it
+pub const TITLE_CHARS: usize = 60;
+
+/// One cited block
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Citation {
+    pub marker: usize,
+    pub uuid: String,
+    pub page: String,
+    /// First lines of the block's parents, outermost first
+    pub breadcrumb: Vec<String>,
+    /// The start of the block's first line
+    pub title: String,
+    /// A `((ref))` to the block, for citing it in LogSeq
+    pub block_ref: String,
+}
+
+impl Citation {
+    pub fn new(
+        marker: usize,
+        uuid: &str,
+        page: &str,
+        breadcrumb: &[String],
+        content: &str,
+    ) -> Self {
+        Self {
+            marker,
+            uuid: uuid.to_string(),
+            page: page.to_string(),
+            breadcrumb: breadcrumb.to_vec(),
+            title: block_title(content),
+            block_ref: format!("(({}))", uuid),
+        }
+    }
+
+    /// The citation as shown in text: `[1] Page > Parent > Block`
+    pub fn label(&self) -> String {
+        let mut label = format!("[{}] {}", self.marker, self.page);
+        for part in self.breadcrumb.iter().chain([&self.title]) {
+            label.push_str(" > ");
+            label.push_str(part);
+        }
+        label
+    }
+}
+
+/// The blocks cited so far, in marker order
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct Citations {
+    citations: Vec<Citation>,
+}
+
+impl Citations {
+    /// The marker for a block, numbering it if it hasn't been cited yet
+    pub fn cite(&mut self, uuid: &str, page: &str, breadcrumb: &[String], content: &str) -> usize {
+        if let Some(citation) = self.citations.iter().find(|c| c.uuid == uuid) {
+            return citation.marker;
+        }
+        let marker = self.citations.len() + 1;
+        self.citations
+            .push(Citation::new(marker, uuid, page, breadcrumb, content));
+        marker
+    }
+
+    pub fn get(&self, marker: usize) -> Option<&Citation> {
+        marker
+            .checked_sub(1)
+            .and_then(|index| self.citations.get(index))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.citations.is_empty()
+    }
+
+    /// The citation map returned as structured content
+    pub fn to_value(&self) -> Value {
+        serde_json::json!({ "citations": self.citations })
+    }
+}
+
+/// A short label for a block: the start of its first line, without its task
+/// marker or properties
+pub fn block_title(content: &str) -> String {
+    let content = strip_property_lines(content);
+    let first = content.lines().map(str::trim).find(|l| !l.is_empty());
+    let (_, text) = split_marker(first.unwrap_or_default());
+    let text = text.trim();
+    truncate(text, TITLE_CHARS)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_citations_are_numbered_once_per_block() {
+        let mut citations = Citations::default();
+        let crumbs = vec!["Ownership".to_string()];
+        assert_eq!(citations.cite("a", "Rust", &crumbs, "Borrowing rules"), 1);
+        assert_eq!(
+            citations.cite("b", "Go", &[], "TODO Read about GC\nid:: b"),
+            2
+        );
+        assert_eq!(citations.cite("a", "Rust", &crumbs, "Borrowing rules"), 1);
+
+        assert_eq!(
+            citations.get(1).unwrap().label(),
+            "[1] Rust > Ownership > Borrowing rules"
+        );
+        assert_eq!(citations.get(2).unwrap().label(), "[2] Go > Read about GC");
+        assert!(citations.get(0).is_none());
+
+        let map = citations.to_value();
+        assert_eq!(map["citations"][1]["marker"], 2);
+        assert_eq!(map["citations"][1]["block_ref"], "((b))");
+    }
+
+    #[test]
+    fn test_long_titles_are_shortened() {
+        let title = block_title(&"word ".repeat(40));
+        assert_eq!(title.chars().count(), TITLE_CHARS);
+        assert!(title.ends_with("word…"));
+        assert_eq!(block_title(""), "");
+    }
+}
@@ -0,0 +1,598 @@
+//! Reading settings from command-line flags, environment variables, and a
+//! config file
+//!
+//! Every setting can be given as a command-line flag, an environment
+//! variable, or a key in an optional TOML config file named by `--config` or
+//! `LOGSEQ_MCP_CONFIG`, in that order of precedence. Config file keys are the
+//! long names of the flags, e.g. `connect-timeout = 10`. String values in the
+//! file may refer to environment variables as `${VAR}`, which keeps tokens out
+//! of it; `$$` stands for a literal `$`.
+//!
+//! [`Settings`] checks each value as it's read and carries on, so every
+//! problem can be reported at once, each with where the bad value came from.
+
+use clap::ArgMatches;
+use clap::parser::ValueSource;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::time::Duration;
+use thiserror::Error;
+use toml::Spanned;
+
+/// The flag naming the config file
+pub const CONFIG_ARG: &str = "config";
+
+/// The environment variable naming the config file
+pub const CONFIG_ENV: &str = "LOGSEQ_MCP_CONFIG";
+
+/// Shown in place of secrets such as API tokens
+const REDACTED: &str = "<redacted>";
+
+/// Where a setting's value came from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Location {
+    Flag(String),
+    Env(String),
+    /// A key in the config file, at a 1-based line and column
+    File {
+        path: PathBuf,
+        line: usize,
+        column: usize,
+    },
+}
+
+impl fmt::Display for Location {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Flag(arg) => write!(f, "--{}", arg),
+            Self::Env(var) => f.write_str(var),
+            Self::File { path, line, column } => {
+                write!(f, "{}:{}:{}", path.display(), line, column)
+            }
+        }
+    }
+}
+
+#[derive(Debug, Error, PartialEq, Eq)]
+#[error("{location}: {message}")]
+pub struct SettingError {
+    pub location: Location,
+    pub message: String,
+}
+
+/// Every problem found with the settings
+#[derive(Debug, Error, PartialEq, Eq)]
+pub struct ConfigErrors(pub Vec<SettingError>);
+
+impl fmt::Display for ConfigErrors {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Invalid configuration:")?;
+        for error in &self.0 {
+            write!(f, "\n  {}", error)?;
+        }
+        Ok(())
+    }
+}
+
+/// Replace each `${VAR}` in `value` with what `lookup` gives for `VAR`, and
+/// each `$$` with `$`
+pub fn expand_vars(value: &str, lookup: impl Fn(&str) -> Option<String>) -> Result<String, String> {
+    let mut expanded = String::with_capacity(value.len());
+    let mut rest = value;
+    while let Some(start) = rest.find('$') {
+        expanded.push_str(&rest[..start]);
+        rest = &rest[start + 1..];
+        if let Some(after) = rest.strip_prefix('$') {
+            expanded.push('$');
+            rest = after;
+            continue;
+        }
+        let Some((name, after)) = rest.strip_prefix('{').and_then(|r| r.split_once('}')) else {
+            return Err("a '$' must start ${VAR} (write $$ for a literal '$')".to_string());
+        };
+        let valid = !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+        if !valid {
+            return Err(format!("'${{{}}}' is not a valid variable name", name));
+        }
+        match lookup(name) {
+            Some(var) => expanded.push_str(&var),
+            None => return Err(format!("environment variable {} is not set", name)),
+        }
+        rest = after;
+    }
+    expanded.push_str(rest);
+    Ok(expanded)
+}
+
+/// A parsed config file, keeping where each value is so errors can point at
+/// it
+#[derive(Debug)]
+pub struct ConfigFile {
+    path: PathBuf,
+    source: String,
+    values: BTreeMap<String, Spanned<toml::Value>>,
+}
+
+impl ConfigFile {
+    pub fn parse(path: impl Into<PathBuf>, source: String) -> Result<Self, SettingError> {
+        let path = path.into();
+        match toml::from_str(&source) {
+            Ok(values) => Ok(Self {
+                path,
+                source,
+                values,
+            }),
+            Err(e) => {
+                let offset = e.span().map(|span| span.start).unwrap_or_default();
+                let (line, column) = position(&source, offset);
+                Err(SettingError {
+                    location: Location::File { path, line, column },
+                    message: e.message().to_string(),
+                })
+            }
+        }
+    }
+
+    fn location(&self, value: &Spanned<toml::Value>) -> Location {
+        let (line, column) = position(&self.source, value.span().start);
+        Location::File {
+            path: self.path.clone(),
+            line,
+            column,
+        }
+    }
+}
+
+/// The 1-based line and column of a byte offset
+fn position(source: &str, offset: usize) -> (usize, usize) {
+    let before = &source[..offset.min(source.len())];
+    let line_start = before.rfind('\n').map(|i| i + 1).unwrap_or_default();
+    (
+        before.matches('\n').count() + 1,
+        before[line_start..].chars().count() + 1,
+    )
+}
+
+/// A setting as it was given, before it's parsed
+struct Raw {
+    values: Vec<String>,
+    location: Location,
+}
+
+/// Looks up an environment variable
+type EnvLookup<'a> = Box<dyn Fn(&str) -> Option<String> + 'a>;
+
+/// Reads settings from flags, the environment, and the config file, noting
+/// every problem and every value used
+pub struct Settings<'a> {
+    matches: &'a ArgMatches,
+    env: EnvLookup<'a>,
+    file: Option<ConfigFile>,
+    /// File keys that have been read; any others are mistakes
+    used_keys: Vec<String>,
+    effective: BTreeMap<String, (String, Location)>,
+    errors: Vec<SettingError>,
+}
+
+impl<'a> Settings<'a> {
+    /// Read settings from the process environment and the config file it or
+    /// `matches` names
+    pub fn new(matches: &'a ArgMatches) -> Self {
+        Self::with_env(matches, |var| std::env::var(var).ok())
+    }
+
+    pub fn with_env(matches: &'a ArgMatches, env: impl Fn(&str) -> Option<String> + 'a) -> Self {
+        let mut settings = Self {
+            matches,
+            env: Box::new(env),
+            file: None,
+            used_keys: Vec::new(),
+            effective: BTreeMap::new(),
+            errors: Vec::new(),
+        };
+        if let Some(raw) = settings.flag_or_env(CONFIG_ARG, Some(CONFIG_ENV)) {
+            let path = PathBuf::from(raw.values.concat());
+            match std::fs::read_to_string(&path) {
+                Ok(source) => match ConfigFile::parse(&path, source) {
+                    Ok(file) => settings.file = Some(file),
+                    Err(e) => settings.errors.push(e),
+                },
+                Err(e) => settings.errors.push(SettingError {
+                    location: raw.location,
+                    message: format!("could not read config file {}: {}", path.display(), e),
+                }),
+            }
+        }
+        settings
+    }
+
+    /// A setting parsed by `parse`, which explains what's wrong with a bad
+    /// value
+    pub fn get<T>(
+        &mut self,
+        key: &str,
+        var: Option<&str>,
+        parse: impl Fn(&str) -> Result<T, String>,
+    ) -> Option<T> {
+        self.get_shown(key, var, parse, |value| value.to_string())
+    }
+
+    /// A setting that shouldn't be shown in logs, such as an API token
+    pub fn secret(&mut self, key: &str, var: Option<&str>) -> Option<String> {
+        self.get_shown(
+            key,
+            var,
+            |value| Ok(value.to_string()),
+            |_| REDACTED.to_string(),
+        )
+    }
+
+    /// A setting that may be given several times: repeated flags, a
+    /// comma-separated environment variable, or an array in the file
+    pub fn list<T>(
+        &mut self,
+        key: &str,
+        var: Option<&str>,
+        parse: impl Fn(&str) -> Result<T, String>,
+    ) -> Vec<T> {
+        let Some(mut raw) = self.raw(key, var) else {
+            return Vec::new();
+        };
+        if matches!(raw.location, Location::Env(_)) {
+            raw.values = raw
+                .values
+                .iter()
+                .flat_map(|value| value.split(','))
+                .map(|value| value.trim().to_string())
+                .filter(|value| !value.is_empty())
+                .collect();
+        }
+        let mut parsed = Vec::new();
+        for value in &raw.values {
+            match parse(value) {
+                Ok(value) => parsed.push(value),
+                Err(message) => self.errors.push(SettingError {
+                    location: raw.location.clone(),
+                    message,
+                }),
+            }
+        }
+        self.effective
+            .insert(key.to_string(), (raw.values.join(","), raw.location));
+        parsed
+    }
+
+    /// A plain string setting
+    pub fn string(&mut self, key: &str, var: Option<&str>) -> Option<String> {
+        self.get(key, var, |value| Ok(value.to_string()))
+    }
+
+    /// A number of whole seconds, more than zero
+    pub fn seconds(&mut self, key: &str, var: Option<&str>) -> Option<Duration> {
+        self.get(key, var, positive("a positive whole number of seconds"))
+            .map(Duration::from_secs)
+    }
+
+    /// A number of whole milliseconds, more than zero
+    pub fn millis(&mut self, key: &str, var: Option<&str>) -> Option<Duration> {
+        self.get(
+            key,
+            var,
+            positive("a positive whole number of milliseconds"),
+        )
+        .map(Duration::from_millis)
+    }
+
+    /// A whole number more than zero
+    pub fn count<T: FromStr + Default + PartialOrd>(
+        &mut self,
+        key: &str,
+        var: Option<&str>,
+    ) -> Option<T> {
+        self.get(key, var, positive("a positive whole number"))
+    }
+
+    /// An on/off switch: `true`/`1` or `false`/`0`
+    pub fn switch(&mut self, key: &str, var: Option<&str>) -> bool {
+        self.get(key, var, parse_switch).unwrap_or(false)
+    }
+
+    /// Note a problem with a setting that has already been read, such as
+    /// one that conflicts with another
+    pub fn invalid(&mut self, key: &str, message: impl Into<String>) {
+        let location = match self.effective.get(key) {
+            Some((_, location)) => location.clone(),
+            None => Location::Flag(key.to_string()),
+        };
+        self.errors.push(SettingError {
+            location,
+            message: message.into(),
+        });
+    }
+
+    /// Note that a required setting is missing, unless it was given but
+    /// couldn't be read
+    pub fn missing(&mut self, key: &str, var: &str) {
+        if self.effective.contains_key(key) {
+            return;
+        }
+        self.errors.push(SettingError {
+            location: Location::Env(var.to_string()),
+            message: format!("must be set (or `{}` in the config file)", key),
+        });
+    }
+
+    /// Check the config file for keys nothing read, then report every
+    /// problem found. On success the settings in effect are logged at debug
+    /// level, with secrets redacted.
+    pub fn finish(mut self) -> Result<(), ConfigErrors> {
+        if let Some(file) = &self.file {
+            for (key, value) in &file.values {
+                if !self.used_keys.contains(key) {
+                    self.errors.push(SettingError {
+                        location: file.location(value),
+                        message: format!("unknown setting `{}`", key),
+                    });
+                }
+            }
+        }
+        if !self.errors.is_empty() {
+            return Err(ConfigErrors(self.errors));
+        }
+        tracing::debug!("Effective configuration:{}", self.summary());
+        Ok(())
+    }
+
+    /// One `key = value (from location)` line per setting that was given
+    fn summary(&self) -> String {
+        if self.effective.is_empty() {
+            return " all defaults".to_string();
+        }
+        self.effective
+            .iter()
+            .map(|(key, (value, location))| format!("\n  {} = {} (from {})", key, value, location))
+            .collect()
+    }
+
+    fn get_shown<T>(
+        &mut self,
+        key: &str,
+        var: Option<&str>,
+        parse: impl Fn(&str) -> Result<T, String>,
+        show: impl Fn(&str) -> String,
+    ) -> Option<T> {
+        let raw = self.raw(key, var)?;
+        let value = raw.values.concat();
+        self.effective
+            .insert(key.to_string(), (show(&value), raw.location.clone()));
+        match parse(value.trim()) {
+            Ok(value) => Some(value),
+            Err(message) => {
+                self.errors.push(SettingError {
+                    location: raw.location,
+                    message,
+                });
+                None
+            }
+        }
+    }
+
+    /// A setting's values and where they came from, if it was given
+    fn raw(&mut self, key: &str, var: Option<&str>) -> Option<Raw> {
+        self.used_keys.push(key.to_string());
+        if let Some(raw) = self.flag_or_env(key, var) {
+            return Some(raw);
+        }
+        let file = self.file.as_ref()?;
+        let value = file.values.get(key)?;
+        let location = file.location(value);
+        let items = match value.get_ref() {
+            toml::Value::Array(items) => items.iter().collect(),
+            value => vec![value],
+        };
+        let values = items
+            .into_iter()
+            .map(|item| match item {
+                toml::Value::String(s) => expand_vars(s, &self.env),
+                toml::Value::Integer(n) => Ok(n.to_string()),
+                toml::Value::Boolean(b) => Ok(b.to_string()),
+                _ => Err("must be a string, a whole number, or true/false".to_string()),
+            })
+            .collect::<Result<Vec<_>, _>>();
+        match values {
+            Ok(values) => Some(Raw { values, location }),
+            Err(message) => {
+                self.effective
+                    .insert(key.to_string(), ("<invalid>".to_string(), location.clone()));
+                self.errors.push(SettingError { location, message });
+                None
+            }
+        }
+    }
+
+    fn flag_or_env(&self, key: &str, var: Option<&str>) -> Option<Raw> {
+        let from_command_line = self
+            .matches
+            .try_contains_id(key)
+            .is_ok_and(|present| present)
+            && self.matches.value_source(key) == Some(ValueSource::CommandLine);
+        if from_command_line {
+            let mut values: Vec<String> = self
+                .matches
+                .get_raw(key)
+                .map(|values| {
+                    values
+                        .map(|value| value.to_string_lossy().into_owned())
+                        .collect()
+                })
+                .unwrap_or_default();
+            // A flag with an optional value, given without one, turns the
+            // setting on
+            if values.is_empty() {
+                values.push("true".to_string());
+            }
+            return Some(Raw {
+                values,
+                location: Location::Flag(key.to_string()),
+            });
+        }
+        let var = var?;
+        (self.env)(var).map(|value| Raw {
+            values: vec![value],
+            location: Location::Env(var.to_string()),
+        })
+    }
+}
+
+/// Parse a whole number greater than zero, described as `what` if it isn't
+/// one
+fn positive<T: FromStr + Default + PartialOrd>(
+    what: &'static str,
+) -> impl Fn(&str) -> Result<T, String> {
+    move |value| match value.parse::<T>() {
+        Ok(n) if n > T::default() => Ok(n),
+        _ => Err(format!("must be {}, got {:?}", what, value)),
+    }
+}
+
+/// Parse an on/off switch
+pub fn parse_switch(value: &str) -> Result<bool, String> {
+    match value {
+        "1" | "true" => Ok(true),
+        "" | "0" | "false" => Ok(false),
+        _ => Err(format!("must be true or false, got {:?}", value)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::{Arg, Command};
+    use std::collections::HashMap;
+
+    fn command() -> Command {
+        Command::new("test")
+            .arg(Arg::new("config").long("config"))
+            .arg(Arg::new("connect-timeout").long("connect-timeout"))
+            .arg(Arg::new("api-token").long("api-token"))
+            .arg(
+                Arg::new("graph")
+                    .long("graph")
+                    .action(clap::ArgAction::Append),
+            )
+    }
+
+    fn env(vars: &[(&str, &str)]) -> impl Fn(&str) -> Option<String> {
+        let vars: HashMap<String, String> = vars
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        move |var| vars.get(var).cloned()
+    }
+
+    fn write_config(dir: &tempfile::TempDir, source: &str) -> String {
+        let path = dir.path().join("config.toml");
+        std::fs::write(&path, source).unwrap();
+        path.display().to_string()
+    }
+
+    #[test]
+    fn test_expand_vars() {
+        let lookup = env(&[("HOST", "work"), ("TOKEN", "s3cret")]);
+
+        assert_eq!(
+            expand_vars("http://${HOST}:12315", &lookup),
+            Ok("http://work:12315".to_string())
+        );
+        assert_eq!(
+            expand_vars("$${TOKEN} ${TOKEN}", &lookup).unwrap(),
+            "${TOKEN} s3cret"
+        );
+        assert_eq!(
+            expand_vars("${MISSING}", &lookup),
+            Err("environment variable MISSING is not set".to_string())
+        );
+        assert!(expand_vars("$TOKEN", &lookup).is_err());
+        assert!(expand_vars("${bad name}", &lookup).is_err());
+    }
+
+    #[test]
+    fn test_settings_come_from_flags_then_env_then_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = write_config(
+            &dir,
+            "connect-timeout = 7\napi-token = \"${TOKEN}\"\ngraph = [\"work=http://${HOST}:1\"]\n",
+        );
+        let vars = env(&[("TOKEN", "s3cret"), ("HOST", "work"), ("GRAPHS", "a=b")]);
+
+        let matches = command().get_matches_from(["test", "--config", &path]);
+        let mut settings = Settings::with_env(&matches, &vars);
+        assert_eq!(
+            settings.seconds("connect-timeout", Some("TIMEOUT")),
+            Some(Duration::from_secs(7))
+        );
+        assert_eq!(
+            settings.secret("api-token", None).as_deref(),
+            Some("s3cret")
+        );
+        assert_eq!(
+            settings.string("graph", Some("GRAPHS")).as_deref(),
+            Some("a=b")
+        );
+        assert!(!settings.summary().contains("s3cret"));
+        assert_eq!(settings.finish(), Ok(()));
+
+        let matches =
+            command().get_matches_from(["test", "--config", &path, "--connect-timeout", "3"]);
+        let mut settings = Settings::with_env(&matches, &vars);
+        assert_eq!(
+            settings.seconds("connect-timeout", None),
+            Some(Duration::from_secs(3))
+        );
+        assert_eq!(
+            settings.list("graph", None, |s| Ok(s.to_string())),
+            vec!["work=http://work:1"]
+        );
+    }
+
+    #[test]
+    fn test_every_problem_is_reported_with_its_location() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = write_config(
+            &dir,
+            "# Settings\nconnect-timeout = 0\napi-token = \"${UNSET}\"\nwarm-cahce = true\n",
+        );
+        let matches = command().get_matches_from(["test", "--config", &path]);
+        let mut settings = Settings::with_env(&matches, env(&[]));
+        settings.seconds("connect-timeout", None);
+        settings.secret("api-token", None);
+        let at = |line, column| Location::File {
+            path: path.clone().into(),
+            line,
+            column,
+        };
+
+        assert_eq!(
+            settings.finish(),
+            Err(ConfigErrors(vec![
+                SettingError {
+                    location: at(2, 19),
+                    message: "must be a positive whole number of seconds, got \"0\"".to_string(),
+                },
+                SettingError {
+                    location: at(3, 13),
+                    message: "environment variable UNSET is not set".to_string(),
+                },
+                SettingError {
+                    location: at(4, 14),
+                    message: "unknown setting `warm-cahce`".to_string(),
+                },
+            ]))
+        );
+
+        let broken = ConfigFile::parse("broken.toml", "a = 1\nb = = 2\n".to_string()).unwrap_err();
+        assert!(matches!(broken.location, Location::File { line: 2, .. }));
+    }
+}
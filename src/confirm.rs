@@ -0,0 +1,144 @@
+//! Single-use confirmation tokens for destructive tool calls
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use thiserror::Error;
+
+/// How long a confirmation token stays valid after it is issued
+pub const CONFIRMATION_TTL: Duration = Duration::from_secs(300);
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum ConfirmError {
+    #[error(
+        "Unknown or already used confirmation token; call again without confirm to get a new one"
+    )]
+    Unknown,
+    #[error("Confirmation token has expired; call again without confirm to get a new one")]
+    Expired,
+    #[error("Confirmation token was issued for a different operation ({0})")]
+    Mismatch(String),
+}
+
+struct Pending {
+    action: String,
+    expires: Instant,
+}
+
+/// Outstanding confirmation tokens.
+///
+/// Destructive tools first issue a token bound to the exact operation they
+/// previewed (e.g. `delete_page projects/alpha`) and only carry it out when
+/// called again with that token. Tokens are single use and expire after
+/// [`CONFIRMATION_TTL`].
+pub struct Confirmations {
+    pending: Mutex<HashMap<String, Pending>>,
+    ttl: Duration,
+}
+
+impl Default for Confirmations {
+    fn default() -> Self {
+        Self::with_ttl(CONFIRMATION_TTL)
+    }
+}
+
+impl Confirmations {
+    pub fn with_ttl(ttl: Duration) -> Self {
+        Self {
+            pending: Mutex::default(),
+            ttl,
+        }
+    }
+
+    /// Issue a new token for `action`
+    pub fn issue(&self, action: &str) -> String {
+        let token = uuid::Uuid::new_v4().simple().to_string();
+        let now = Instant::now();
+        let mut pending = self.pending.lock().unwrap_or_else(|e| e.into_inner());
+
+        // Forget tokens nobody came back for so the map doesn't grow unbounded
+        pending.retain(|_, p| p.expires > now);
+
+        pending.insert(
+            token.clone(),
+            Pending {
+                action: action.to_string(),
+                expires: now + self.ttl,
+            },
+        );
+        token
+    }
+
+    /// Consume `token`, succeeding only if it was issued for `action` and
+    /// hasn't expired. A token presented for the wrong action stays valid
+    /// for the one it was issued for.
+    pub fn redeem(&self, token: &str, action: &str) -> Result<(), ConfirmError> {
+        let mut pending = self.pending.lock().unwrap_or_else(|e| e.into_inner());
+        let Some(entry) = pending.remove(token.trim()) else {
+            return Err(ConfirmError::Unknown);
+        };
+
+        if entry.expires <= Instant::now() {
+            return Err(ConfirmError::Expired);
+        }
+        if entry.action != action {
+            let issued_for = entry.action.clone();
+            pending.insert(token.trim().to_string(), entry);
+            return Err(ConfirmError::Mismatch(issued_for));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_token_is_single_use() {
+        let confirmations = Confirmations::default();
+        let token = confirmations.issue("delete_block abc");
+
+        assert_eq!(confirmations.redeem(&token, "delete_block abc"), Ok(()));
+        assert_eq!(
+            confirmations.redeem(&token, "delete_block abc"),
+            Err(ConfirmError::Unknown)
+        );
+    }
+
+    #[test]
+    fn test_token_is_bound_to_its_action() {
+        let confirmations = Confirmations::default();
+        let token = confirmations.issue("delete_page alpha");
+
+        assert_eq!(
+            confirmations.redeem(&token, "delete_page beta"),
+            Err(ConfirmError::Mismatch("delete_page alpha".into()))
+        );
+        assert_eq!(confirmations.redeem(&token, "delete_page alpha"), Ok(()));
+    }
+
+    #[test]
+    fn test_expired_tokens_are_rejected_and_pruned() {
+        let confirmations = Confirmations::with_ttl(Duration::ZERO);
+        let token = confirmations.issue("delete_page alpha");
+
+        assert_eq!(
+            confirmations.redeem(&token, "delete_page alpha"),
+            Err(ConfirmError::Expired)
+        );
+
+        confirmations.issue("delete_page beta");
+        confirmations.issue("delete_page gamma");
+        assert_eq!(confirmations.pending.lock().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_unknown_token() {
+        let confirmations = Confirmations::default();
+        assert_eq!(
+            confirmations.redeem("nope", "delete_page alpha"),
+            Err(ConfirmError::Unknown)
+        );
+    }
+}
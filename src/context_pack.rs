@@ -0,0 +1,238 @@
+//! Packing search results into a context window
+//!
+//! An agent answering a question from the graph wants the most relevant
+//! text that fits in the room it has, with enough provenance to cite it.
+//! [`pack`] takes ranked block hits, best first, and adds them as snippets
+//! until the token budget runs out:
+//!
+//! - property lines such as `id::` are left out of each snippet's text
+//! - a block whose text repeats one already packed, such as a quote of it,
+//!   or that is contained in one, is skipped
+//! - the first snippet that doesn't fit is cut short, if there's enough
+//!   room left for it to be useful, and packing stops there
+//!
+//! Tokens are estimated at [`CHARS_PER_TOKEN`] characters each, which is
+//! close enough for English text under common tokenizers.
+
+use crate::citations::Citation;
+use crate::logseq::api::SearchResult;
+use crate::logseq::content::{strip_property_lines, truncate};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+/// Characters counted as one token
+pub const CHARS_PER_TOKEN: usize = 4;
+
+/// The fewest tokens of text worth including from a snippet cut short
+pub const MIN_TRUNCATED_TOKENS: usize = 16;
+
+/// A block's text and where it came from
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Snippet {
+    pub page: String,
+    /// First lines of the block's parents, outermost first
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub breadcrumb: Vec<String>,
+    pub uuid: String,
+    pub updated_at: Option<DateTime<Utc>>,
+    pub score: f64,
+    pub content: String,
+    /// Whether `content` was cut short to fit the budget
+    pub truncated: bool,
+    /// Estimated tokens of the snippet as rendered, provenance included
+    pub tokens: usize,
+}
+
+impl Snippet {
+    /// The snippet as it's given to the agent, numbered for citation
+    pub fn render(&self, number: usize) -> String {
+        format!("{}\n{}", self.heading(number), self.content)
+    }
+
+    fn heading(&self, number: usize) -> String {
+        Citation::new(
+            number,
+            &self.uuid,
+            &self.page,
+            &self.breadcrumb,
+            &self.content,
+        )
+        .label()
+    }
+}
+
+/// The snippets that fit a budget, and what was left out
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ContextPack {
+    pub query: String,
+    pub token_budget: usize,
+    pub tokens_used: usize,
+    pub snippets: Vec<Snippet>,
+    /// Blocks skipped because their text was already in a snippet
+    pub duplicates: usize,
+    /// Relevant blocks that didn't fit the budget
+    pub omitted: usize,
+}
+
+/// Estimated number of tokens in `text`
+pub fn estimate_tokens(text: &str) -> usize {
+    text.chars().count().div_ceil(CHARS_PER_TOKEN)
+}
+
+/// Pack ranked block hits, best first, into at most `token_budget` tokens
+pub fn pack(query: &str, results: Vec<SearchResult>, token_budget: usize) -> ContextPack {
+    let mut pack = ContextPack {
+        query: query.to_string(),
+        token_budget,
+        tokens_used: 0,
+        snippets: Vec::new(),
+        duplicates: 0,
+        omitted: 0,
+    };
+    let mut seen: Vec<String> = Vec::new();
+    let mut full = false;
+
+    for result in results {
+        let content = strip_property_lines(&result.block.content)
+            .trim()
+            .to_string();
+        let normalized = normalize(&content);
+        if normalized.is_empty() {
+            continue;
+        }
+        if seen
+            .iter()
+            .any(|s| s.contains(&normalized) || normalized.contains(s.as_str()))
+        {
+            pack.duplicates += 1;
+            continue;
+        }
+        if full {
+            pack.omitted += 1;
+            continue;
+        }
+
+        let number = pack.snippets.len() + 1;
+        let mut snippet = Snippet {
+            page: result.page_name,
+            breadcrumb: result.breadcrumb,
+            uuid: result.block.uuid,
+            updated_at: result.updated_at,
+            score: result.score.unwrap_or_default(),
+            content,
+            truncated: false,
+            tokens: 0,
+        };
+        snippet.tokens = estimate_tokens(&snippet.render(number));
+        let remaining = token_budget - pack.tokens_used;
+        if snippet.tokens > remaining {
+            full = true;
+            let heading = estimate_tokens(&snippet.heading(number)) + 1;
+            let room = remaining.saturating_sub(heading);
+            if room < MIN_TRUNCATED_TOKENS {
+                pack.omitted += 1;
+                continue;
+            }
+            // Leave a token for the ellipsis
+            snippet.content = truncate(&snippet.content, (room - 1) * CHARS_PER_TOKEN);
+            snippet.truncated = true;
+            snippet.tokens = estimate_tokens(&snippet.render(number));
+        }
+
+        pack.tokens_used += snippet.tokens;
+        seen.push(normalized);
+        pack.snippets.push(snippet);
+    }
+    pack
+}
+
+/// Lower-cased text with runs of whitespace collapsed, and without quote
+/// markers, for spotting repeated text
+fn normalize(content: &str) -> String {
+    content
+        .split_whitespace()
+        .filter(|word| *word != ">")
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::logseq::api::Block;
+
+    fn hit(uuid: &str, content: &str, page_name: &str) -> SearchResult {
+        SearchResult {
+            block: Block {
+                uuid: uuid.to_string(),
+                content: content.to_string(),
+                page: None,
+                properties: None,
+                children: vec![],
+                level: None,
+                format: None,
+            },
+            score: Some(1.0),
+            page_name: page_name.to_string(),
+            breadcrumb: vec![],
+            updated_at: None,
+            snippet: None,
+        }
+    }
+
+    #[test]
+    fn test_pack_skips_repeated_text() {
+        let results = vec![
+            hit("a", "Rust has no garbage collector\nid:: a", "Rust"),
+            hit(
+                "b",
+                "> Rust has no   garbage collector\n[[Rust]] ((a))",
+                "Digest",
+            ),
+            hit("c", "garbage collector", "Rust"),
+            hit("d", "Ownership replaces the garbage collector", "Rust"),
+        ];
+        let pack = pack("garbage collector", results, 1000);
+        let uuids: Vec<&str> = pack.snippets.iter().map(|s| s.uuid.as_str()).collect();
+        assert_eq!(uuids, vec!["a", "d"]);
+        assert_eq!(pack.duplicates, 2);
+        assert_eq!(pack.snippets[0].content, "Rust has no garbage collector");
+        assert_eq!(
+            pack.tokens_used,
+            pack.snippets.iter().map(|s| s.tokens).sum::<usize>()
+        );
+    }
+
+    #[test]
+    fn test_pack_stays_within_budget() {
+        let long = "word ".repeat(200);
+        let results = vec![
+            hit("a", "short note about rust", "Rust"),
+            hit("b", &long, "Rust"),
+            hit("c", "another note about rust", "Rust"),
+        ];
+        let packed = pack("rust", results, 60);
+        assert!(packed.tokens_used <= 60);
+        assert_eq!(packed.snippets.len(), 2);
+        assert!(packed.snippets[1].truncated);
+        assert!(packed.snippets[1].content.ends_with("word…"));
+        assert_eq!(packed.omitted, 1);
+
+        // Too little room left to be worth cutting a snippet short
+        let packed = pack("rust", vec![hit("a", &long, "Rust")], 10);
+        assert!(packed.snippets.is_empty());
+        assert_eq!(packed.omitted, 1);
+    }
+
+    #[test]
+    fn test_rendered_snippets_cite_their_source() {
+        let mut result = hit("a", "Borrowing rules", "Rust");
+        result.breadcrumb = vec!["Ownership".into()];
+        let pack = pack("borrowing", vec![result], 100);
+        assert_eq!(
+            pack.snippets[0].render(1),
+            "[1] Rust > Ownership > Borrowing rules\nBorrowing rules"
+        );
+    }
+}
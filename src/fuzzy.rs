@@ -0,0 +1,110 @@
+//! Approximate string matching used to suggest alternatives for mistyped names
+
+/// Maximum number of suggestions returned by `suggest`
+pub const MAX_SUGGESTIONS: usize = 5;
+
+/// Shortest candidate suggested for appearing inside the query, so that
+/// names like `a` or `to` aren't offered for every query containing them
+const MIN_CONTAINED_CHARS: usize = 3;
+
+/// Levenshtein edit distance between two strings, counted in characters
+pub fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    let mut current = vec![0; b.len() + 1];
+
+    for (i, ca) in a.iter().enumerate() {
+        current[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != cb);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        std::mem::swap(&mut previous, &mut current);
+    }
+
+    previous[b.len()]
+}
+
+/// Return up to `MAX_SUGGESTIONS` candidates that closely match `query`,
+/// best match first.
+///
+/// Matching is case-insensitive. A candidate qualifies if it is within a
+/// length-scaled edit distance of the query, contains it as a substring, or
+/// is a substring of it at least `MIN_CONTAINED_CHARS` long.
+pub fn suggest<'a, I>(query: &str, candidates: I) -> Vec<String>
+where
+    I: IntoIterator<Item = &'a str>,
+{
+    let query = query.trim().to_lowercase();
+    if query.is_empty() {
+        return vec![];
+    }
+    let threshold = (query.chars().count() / 3).max(2);
+
+    let mut scored: Vec<(usize, &str)> = candidates
+        .into_iter()
+        .filter_map(|candidate| {
+            let lowered = candidate.to_lowercase();
+            let distance = edit_distance(&query, &lowered);
+            if distance <= threshold {
+                Some((distance, candidate))
+            } else if lowered.contains(&query)
+                || (lowered.chars().count() >= MIN_CONTAINED_CHARS && query.contains(&lowered))
+            {
+                // Substring matches rank behind close typos
+                Some((threshold + 1, candidate))
+            } else {
+                None
+            }
+        })
+        .collect();
+
+    scored.sort_by(|a, b| a.0.cmp(&b.0).then_with(|| a.1.cmp(b.1)));
+    scored.dedup_by(|a, b| a.1 == b.1);
+    scored
+        .into_iter()
+        .take(MAX_SUGGESTIONS)
+        .map(|(_, candidate)| candidate.to_string())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_edit_distance() {
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+        assert_eq!(edit_distance("", "abc"), 3);
+        assert_eq!(edit_distance("same", "same"), 0);
+        assert_eq!(edit_distance("café", "cafe"), 1);
+    }
+
+    #[test]
+    fn test_suggest_orders_by_closeness() {
+        let pages = [
+            "Project Alpha",
+            "Project Beta",
+            "Groceries",
+            "project alpha notes",
+        ];
+        let suggestions = suggest("Project alpha", pages);
+        assert_eq!(suggestions[0], "Project Alpha");
+        assert!(suggestions.contains(&"project alpha notes".to_string()));
+        assert!(!suggestions.contains(&"Groceries".to_string()));
+    }
+
+    #[test]
+    fn test_suggest_skips_short_names_inside_the_query() {
+        let pages = ["a", "to", "Alpha", "Project"];
+        let suggestions = suggest("Project alpha notes", pages);
+        assert_eq!(suggestions, vec!["Alpha", "Project"]);
+    }
+
+    #[test]
+    fn test_suggest_empty_query() {
+        assert!(suggest("  ", ["anything"]).is_empty());
+    }
+}
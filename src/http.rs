@@ -0,0 +1,317 @@
+//! MCP over streamable HTTP, as a tower service or axum router
+//!
+//! Other Rust applications can mount the LogSeq MCP endpoint inside their own
+//! web server instead of spawning the binary:
+//!
+//! ```no_run
+//! # async fn example() -> anyhow::Result<()> {
+//! use logseq_mcp_server::http::{HttpOptions, router};
+//! use logseq_mcp_server::logseq::api::LogSeqClient;
+//! use logseq_mcp_server::server::LogSeqMcpServer;
+//!
+//! let client = LogSeqClient::new("http://localhost:12315", "token")?;
+//! let app = axum::Router::new()
+//!     .merge(router(LogSeqMcpServer::new(client), &HttpOptions::default()));
+//! let listener = tokio::net::TcpListener::bind("127.0.0.1:8080").await?;
+//! axum::serve(listener, app).await?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! The router also answers liveness and readiness probes for container
+//! deployments: `/healthz` as long as the process is serving, and `/readyz`
+//! only while LogSeq answers for every graph.
+
+use crate::server::LogSeqMcpServer;
+use anyhow::Result;
+use axum::extract::State;
+use axum::http::StatusCode;
+use axum::routing::get;
+use axum::{Json, Router};
+use rmcp::transport::streamable_http_server::{
+    StreamableHttpServerConfig, StreamableHttpService, session::local::LocalSessionManager,
+};
+use serde_json::{Value, json};
+use std::future::Future;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Where `router` mounts the MCP endpoint by default
+pub const DEFAULT_MCP_PATH: &str = "/mcp";
+
+/// The MCP endpoint as a tower service
+pub type McpHttpService = StreamableHttpService<LogSeqMcpServer, LocalSessionManager>;
+
+/// How long `/readyz` waits for each graph's LogSeq to answer
+const READINESS_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// How the HTTP endpoint is exposed
+#[derive(Debug, Clone)]
+pub struct HttpOptions {
+    /// Path the endpoint is mounted at by `router`
+    pub path: String,
+    /// Keep a session per client (identified by the `Mcp-Session-Id`
+    /// header), which allows server-to-client streams over GET. When false
+    /// every POST is handled on its own.
+    pub stateful: bool,
+    /// Interval between keep-alive pings on open event streams
+    pub sse_keep_alive: Option<Duration>,
+    /// Serve `/healthz` and `/readyz` alongside the endpoint
+    pub health_checks: bool,
+}
+
+impl Default for HttpOptions {
+    fn default() -> Self {
+        Self {
+            path: DEFAULT_MCP_PATH.to_string(),
+            stateful: true,
+            sse_keep_alive: Some(Duration::from_secs(15)),
+            health_checks: true,
+        }
+    }
+}
+
+/// A tower service speaking MCP's streamable HTTP transport. All sessions
+/// share `server`'s cache, page locks, and tool registry, while each keeps
+/// the settings its client sent when it initialized (see [`crate::session`])
+/// and its own write budgets. Without `stateful` sessions, budgets only
+/// last for a single request.
+pub fn service(server: LogSeqMcpServer, options: &HttpOptions) -> McpHttpService {
+    StreamableHttpService::new(
+        move || Ok(server.for_new_session()),
+        Arc::new(LocalSessionManager::default()),
+        StreamableHttpServerConfig {
+            sse_keep_alive: options.sse_keep_alive,
+            stateful_mode: options.stateful,
+        },
+    )
+}
+
+/// An axum router with the MCP endpoint at `options.path` and, unless
+/// they're turned off, the health checks, ready to be merged into a host
+/// application's router
+pub fn router(server: LogSeqMcpServer, options: &HttpOptions) -> Router {
+    let router = Router::new().route_service(&options.path, service(server.clone(), options));
+    if !options.health_checks {
+        return router;
+    }
+    router.merge(
+        Router::new()
+            .route("/healthz", get(healthz))
+            .route("/readyz", get(readyz))
+            .with_state(server),
+    )
+}
+
+/// Liveness: the process is up and serving requests
+async fn healthz() -> Json<Value> {
+    Json(json!({ "status": "ok" }))
+}
+
+/// Readiness: LogSeq answers for every graph. Responds 503, with each
+/// graph's error, when any doesn't.
+async fn readyz(State(server): State<LogSeqMcpServer>) -> (StatusCode, Json<Value>) {
+    let mut graphs = serde_json::Map::new();
+    let mut ready = true;
+    for (graph, error) in server.check_backends(READINESS_TIMEOUT).await {
+        let status = match error {
+            None => json!({ "status": "ok" }),
+            Some(error) => {
+                ready = false;
+                json!({ "status": "unavailable", "error": error })
+            }
+        };
+        graphs.insert(graph, status);
+    }
+    let status = match ready {
+        true => StatusCode::OK,
+        false => StatusCode::SERVICE_UNAVAILABLE,
+    };
+    let body = json!({
+        "status": if ready { "ready" } else { "unavailable" },
+        "graphs": graphs,
+    });
+    (status, Json(body))
+}
+
+/// Serve the endpoint on `addr` until `shutdown` resolves
+pub async fn serve(
+    server: LogSeqMcpServer,
+    addr: SocketAddr,
+    options: &HttpOptions,
+    shutdown: impl Future<Output = ()> + Send + 'static,
+) -> Result<()> {
+    let listener = tokio::net::TcpListener::bind(addr).await?;
+    tracing::info!(
+        "Serving MCP over HTTP at http://{}{}",
+        listener.local_addr()?,
+        options.path
+    );
+    axum::serve(listener, router(server, options))
+        .with_graceful_shutdown(shutdown)
+        .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::logseq::api::LogSeqClient;
+    use crate::logseq::mock::{MockGraph, MockLogSeq};
+    use crate::server::ServerOptions;
+    use crate::tools::registry::ToolRegistry;
+
+    /// POST a JSON-RPC message and return the JSON payload of the first
+    /// server-sent event in the response
+    async fn post(url: &str, message: Value) -> Value {
+        post_in_session(url, None, message).await.1
+    }
+
+    /// POST a JSON-RPC message within `session`, returning the session id
+    /// the server assigned (if any) and the first event's JSON payload
+    async fn post_in_session(
+        url: &str,
+        session: Option<&str>,
+        message: Value,
+    ) -> (Option<String>, Value) {
+        let mut request = reqwest::Client::new()
+            .post(url)
+            .header("Accept", "application/json, text/event-stream")
+            .json(&message);
+        if let Some(session) = session {
+            request = request.header("Mcp-Session-Id", session);
+        }
+        let response = request.send().await.unwrap();
+        assert!(response.status().is_success(), "{}", response.status());
+
+        let session = response
+            .headers()
+            .get("Mcp-Session-Id")
+            .and_then(|id| id.to_str().ok())
+            .map(String::from);
+        let body = response.text().await.unwrap();
+        let data = body
+            .lines()
+            .find_map(|line| line.strip_prefix("data:"))
+            .map(|data| serde_json::from_str(data.trim()).unwrap())
+            .unwrap_or(Value::Null);
+        (session, data)
+    }
+
+    /// Initialize a session with the given `logseq` settings and return the
+    /// names of the tools it lists
+    async fn session_tools(url: &str, settings: Value) -> Vec<String> {
+        let (session, _) = post_in_session(
+            url,
+            None,
+            json!({"jsonrpc": "2.0", "id": 1, "method": "initialize", "params": {
+                "protocolVersion": "2025-03-26",
+                "capabilities": {"experimental": {"logseq": settings}},
+                "clientInfo": {"name": "test", "version": "0"}
+            }}),
+        )
+        .await;
+        let session = session.expect("stateful mode should assign a session id");
+        post_in_session(
+            url,
+            Some(&session),
+            json!({"jsonrpc": "2.0", "method": "notifications/initialized"}),
+        )
+        .await;
+
+        let (_, response) = post_in_session(
+            url,
+            Some(&session),
+            json!({"jsonrpc": "2.0", "id": 2, "method": "tools/list", "params": {}}),
+        )
+        .await;
+        response["result"]["tools"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|t| t["name"].as_str().unwrap().to_string())
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_router_serves_tools_list() {
+        // Listing tools doesn't touch LogSeq, so the client can point nowhere
+        let client = LogSeqClient::new("http://127.0.0.1:9", "token").unwrap();
+        let options = HttpOptions {
+            path: "/logseq/mcp".to_string(),
+            stateful: false,
+            sse_keep_alive: None,
+            health_checks: true,
+        };
+        let app = router(LogSeqMcpServer::new(client), &options);
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/logseq/mcp", listener.local_addr().unwrap());
+        let server = tokio::spawn(async move { axum::serve(listener, app).await });
+
+        let response = post(
+            &url,
+            json!({"jsonrpc": "2.0", "id": 1, "method": "tools/list", "params": {}}),
+        )
+        .await;
+        let tools = response["result"]["tools"].as_array().unwrap();
+        assert!(tools.iter().any(|t| t["name"] == "list_pages"));
+
+        server.abort();
+    }
+
+    #[tokio::test]
+    async fn test_sessions_have_their_own_settings() {
+        let client = LogSeqClient::new("http://127.0.0.1:9", "token").unwrap();
+        let options = HttpOptions {
+            sse_keep_alive: None,
+            ..HttpOptions::default()
+        };
+        let app = router(LogSeqMcpServer::new(client), &options);
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/mcp", listener.local_addr().unwrap());
+        let server = tokio::spawn(async move { axum::serve(listener, app).await });
+
+        let read_only = session_tools(&url, json!({"readOnly": true})).await;
+        let limited = session_tools(&url, json!({"allowedTools": ["search", "delete_page"]})).await;
+        let full = session_tools(&url, json!({})).await;
+
+        assert!(read_only.contains(&"search".to_string()));
+        assert!(!read_only.contains(&"delete_page".to_string()));
+        assert_eq!(limited, vec!["search", "delete_page"]);
+        assert_eq!(full.len(), ToolRegistry::builtin().definitions().len());
+
+        server.abort();
+    }
+
+    #[tokio::test]
+    async fn test_readiness_follows_logseq() {
+        let logseq = MockLogSeq::serve(MockGraph::new()).await.unwrap();
+        let down = LogSeqClient::new("http://127.0.0.1:9", "token").unwrap();
+        let graphs = vec![
+            ("default".to_string(), logseq.client()),
+            ("offline".to_string(), down),
+        ];
+        let app = router(
+            LogSeqMcpServer::with_graphs(graphs, ServerOptions::default()),
+            &HttpOptions::default(),
+        );
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let base = format!("http://{}", listener.local_addr().unwrap());
+        let server = tokio::spawn(async move { axum::serve(listener, app).await });
+
+        let response = reqwest::get(format!("{}/healthz", base)).await.unwrap();
+        assert_eq!(response.status(), reqwest::StatusCode::OK);
+
+        let response = reqwest::get(format!("{}/readyz", base)).await.unwrap();
+        assert_eq!(response.status(), reqwest::StatusCode::SERVICE_UNAVAILABLE);
+        let body: Value = response.json().await.unwrap();
+        assert_eq!(body["graphs"]["default"]["status"], "ok");
+        assert_eq!(body["graphs"]["offline"]["status"], "unavailable");
+
+        server.abort();
+    }
+}
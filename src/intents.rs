@@ -0,0 +1,360 @@
+//! Write-ahead log of multi-step changes
+//!
+//! Tools that change the graph in several steps, such as importing a
+//! document or applying a batch of corrections, write down their plan here
+//! before starting and tick off each step as it's done, along with how to
+//! undo it. A finished operation's entry is removed. One left behind by a
+//! server that crashed part-way through, or by a call that failed after
+//! changing something, is an interrupted operation: the steps it records
+//! show what was applied, and the inverse operations can roll it back.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::sync::{Arc, OnceLock};
+
+/// How to undo one change
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum InverseOp {
+    /// Remove a block the step inserted, with its children
+    RemoveBlock { uuid: String },
+    /// Put back the content a block had before the step
+    RestoreContent { uuid: String, content: String },
+    /// Delete a page the step created
+    DeletePage { name: String },
+}
+
+impl InverseOp {
+    /// A short human-readable description, e.g. `delete page 'Projects'`
+    pub fn describe(&self) -> String {
+        match self {
+            InverseOp::RemoveBlock { uuid } => format!("remove block {}", uuid),
+            InverseOp::RestoreContent { uuid, .. } => {
+                format!("restore the previous content of block {}", uuid)
+            }
+            InverseOp::DeletePage { name } => format!("delete page '{}'", name),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StepStatus {
+    Pending,
+    Done,
+    Skipped,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IntentStep {
+    pub description: String,
+    pub status: StepStatus,
+    /// How to undo the step once it's done, in the order to apply them
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub undo: Vec<InverseOp>,
+}
+
+/// One multi-step operation and how far it got
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Intent {
+    pub id: String,
+    pub tool: String,
+    #[serde(default)]
+    pub request_id: Option<String>,
+    pub started_at: DateTime<Utc>,
+    /// The server process that started the operation
+    run: String,
+    /// Whether the call failed after changing something
+    #[serde(default)]
+    pub failed: bool,
+    pub steps: Vec<IntentStep>,
+}
+
+impl Intent {
+    /// Whether the operation stopped part-way: it failed, or the server
+    /// that started it is no longer running
+    pub fn is_interrupted(&self) -> bool {
+        self.failed || self.run != run_id()
+    }
+
+    /// The inverse operations that roll back every step done, most recent
+    /// first
+    pub fn rollback_plan(&self) -> Vec<&InverseOp> {
+        self.steps
+            .iter()
+            .rev()
+            .filter(|step| step.status == StepStatus::Done)
+            .flat_map(|step| step.undo.iter())
+            .collect()
+    }
+}
+
+/// A directory of operations in progress, one `<id>.json` file each
+#[derive(Debug, Clone)]
+pub struct IntentLog {
+    dir: PathBuf,
+}
+
+impl IntentLog {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// Record the plan for an operation before any of it is carried out
+    pub fn begin(
+        &self,
+        tool: &str,
+        request_id: Option<&str>,
+        steps: Vec<String>,
+    ) -> io::Result<Intent> {
+        fs::create_dir_all(&self.dir)?;
+
+        let started_at = Utc::now();
+        let suffix = uuid::Uuid::new_v4().simple().to_string();
+        let intent = Intent {
+            id: format!("{}-{}", started_at.format("%Y%m%dT%H%M%S"), &suffix[..8]),
+            tool: tool.to_string(),
+            request_id: request_id.map(str::to_string),
+            started_at,
+            run: run_id().to_string(),
+            failed: false,
+            steps: steps
+                .into_iter()
+                .map(|description| IntentStep {
+                    description,
+                    status: StepStatus::Pending,
+                    undo: Vec::new(),
+                })
+                .collect(),
+        };
+        self.save(&intent)?;
+        Ok(intent)
+    }
+
+    /// Write an operation's current state
+    pub fn save(&self, intent: &Intent) -> io::Result<()> {
+        // Write then rename so a crash never leaves a truncated entry behind
+        let path = self.entry_path(&intent.id);
+        let partial = path.with_extension("json.partial");
+        fs::write(&partial, serde_json::to_vec_pretty(intent)?)?;
+        fs::rename(&partial, &path)
+    }
+
+    /// Interrupted operations, oldest first. Files that can't be read are
+    /// skipped with a warning.
+    pub fn interrupted(&self) -> io::Result<Vec<Intent>> {
+        let dir = match fs::read_dir(&self.dir) {
+            Ok(dir) => dir,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e),
+        };
+
+        let mut intents: Vec<Intent> = Vec::new();
+        for file in dir {
+            let path = file?.path();
+            if path.extension().is_none_or(|ext| ext != "json") {
+                continue;
+            }
+            let intent = fs::read(&path)
+                .map_err(|e| e.to_string())
+                .and_then(|bytes| serde_json::from_slice(&bytes).map_err(|e| e.to_string()));
+            match intent {
+                Ok(intent) => intents.push(intent),
+                Err(e) => tracing::warn!("Skipping unreadable intent log entry {:?}: {}", path, e),
+            }
+        }
+        intents.retain(Intent::is_interrupted);
+        intents.sort_by(|a, b| a.started_at.cmp(&b.started_at).then(a.id.cmp(&b.id)));
+        Ok(intents)
+    }
+
+    /// Look up an operation by id
+    pub fn get(&self, id: &str) -> io::Result<Option<Intent>> {
+        if !is_valid_id(id) {
+            return Ok(None);
+        }
+        match fs::read(self.entry_path(id)) {
+            Ok(bytes) => Ok(Some(serde_json::from_slice(&bytes)?)),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Forget an operation, once it has finished or been dealt with
+    pub fn remove(&self, id: &str) -> io::Result<()> {
+        if !is_valid_id(id) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("invalid intent id: {}", id),
+            ));
+        }
+        fs::remove_file(self.entry_path(id))
+    }
+
+    fn entry_path(&self, id: &str) -> PathBuf {
+        self.dir.join(format!("{}.json", id))
+    }
+}
+
+/// A tool's handle on the operation it's carrying out. Without a log every
+/// method does nothing.
+///
+/// Dropping it without calling [`finish`](Self::finish), as happens when a
+/// call fails, leaves the entry behind as a failed operation if any step was
+/// done, and removes it otherwise.
+#[derive(Debug)]
+pub struct RunningIntent {
+    log: Option<(Arc<IntentLog>, Intent)>,
+}
+
+impl RunningIntent {
+    /// Start an operation in `log`, if there is one
+    pub fn begin(
+        log: Option<&Arc<IntentLog>>,
+        tool: &str,
+        request_id: Option<&str>,
+        steps: Vec<String>,
+    ) -> io::Result<Self> {
+        let log = match log {
+            Some(log) => Some((log.clone(), log.begin(tool, request_id, steps)?)),
+            None => None,
+        };
+        Ok(Self { log })
+    }
+
+    /// Mark step `index` as done, undone by `undo`
+    pub fn done(&mut self, index: usize, undo: Vec<InverseOp>) {
+        self.update(index, StepStatus::Done, undo);
+    }
+
+    /// Mark step `index` as skipped
+    pub fn skipped(&mut self, index: usize) {
+        self.update(index, StepStatus::Skipped, Vec::new());
+    }
+
+    /// The operation completed; forget it
+    pub fn finish(mut self) {
+        if let Some((log, intent)) = self.log.take()
+            && let Err(e) = log.remove(&intent.id)
+        {
+            tracing::warn!("Could not remove intent log entry {}: {}", intent.id, e);
+        }
+    }
+
+    fn update(&mut self, index: usize, status: StepStatus, undo: Vec<InverseOp>) {
+        let Some((log, intent)) = &mut self.log else {
+            return;
+        };
+        if let Some(step) = intent.steps.get_mut(index) {
+            step.status = status;
+            step.undo = undo;
+        }
+        // The change has already been made, so failing the call now would
+        // only hide that; the entry is just less complete
+        if let Err(e) = log.save(intent) {
+            tracing::warn!("Could not update intent log entry {}: {}", intent.id, e);
+        }
+    }
+}
+
+impl Drop for RunningIntent {
+    fn drop(&mut self) {
+        let Some((log, intent)) = &mut self.log else {
+            return;
+        };
+        let result = if intent.steps.iter().any(|s| s.status == StepStatus::Done) {
+            intent.failed = true;
+            log.save(intent)
+        } else {
+            log.remove(&intent.id)
+        };
+        if let Err(e) = result {
+            tracing::warn!("Could not update intent log entry {}: {}", intent.id, e);
+        }
+    }
+}
+
+/// Identifies this server process, so its own operations in progress aren't
+/// mistaken for interrupted ones
+fn run_id() -> &'static str {
+    static RUN_ID: OnceLock<String> = OnceLock::new();
+    RUN_ID.get_or_init(|| uuid::Uuid::new_v4().simple().to_string())
+}
+
+/// Ids are generated by `begin`; anything else (such as a path) is rejected
+fn is_valid_id(id: &str) -> bool {
+    !id.is_empty() && id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn steps() -> Vec<String> {
+        vec!["create page 'Notes'".into(), "write 3 blocks".into()]
+    }
+
+    #[test]
+    fn test_finished_and_untouched_operations_leave_nothing_behind() {
+        let dir = tempfile::tempdir().unwrap();
+        let log = Arc::new(IntentLog::new(dir.path()));
+
+        let mut running =
+            RunningIntent::begin(Some(&log), "import_markdown", None, steps()).unwrap();
+        running.done(0, vec![]);
+        running.finish();
+        drop(RunningIntent::begin(Some(&log), "import_markdown", None, steps()).unwrap());
+
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 0);
+    }
+
+    #[test]
+    fn test_failed_operation_is_interrupted_with_a_rollback_plan() {
+        let dir = tempfile::tempdir().unwrap();
+        let log = Arc::new(IntentLog::new(dir.path()));
+
+        let mut running =
+            RunningIntent::begin(Some(&log), "import_markdown", Some("abc123"), steps()).unwrap();
+        let page = InverseOp::DeletePage {
+            name: "Notes".into(),
+        };
+        running.done(0, vec![page.clone()]);
+        // Still in progress, so not interrupted yet
+        assert!(log.interrupted().unwrap().is_empty());
+        drop(running);
+
+        let interrupted = log.interrupted().unwrap();
+        assert_eq!(interrupted.len(), 1);
+        let intent = &interrupted[0];
+        assert!(intent.failed);
+        assert_eq!(intent.request_id.as_deref(), Some("abc123"));
+        assert_eq!(intent.steps[1].status, StepStatus::Pending);
+        assert_eq!(intent.rollback_plan(), vec![&page]);
+
+        log.remove(&intent.id).unwrap();
+        assert!(log.get(&intent.id).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_operations_of_an_earlier_run_are_interrupted() {
+        let dir = tempfile::tempdir().unwrap();
+        let log = IntentLog::new(dir.path());
+
+        let mut intent = log.begin("apply_corrections", None, steps()).unwrap();
+        intent.run = "crashed".into();
+        log.save(&intent).unwrap();
+        fs::write(dir.path().join("broken.json"), "{").unwrap();
+
+        let ids: Vec<String> = log
+            .interrupted()
+            .unwrap()
+            .into_iter()
+            .map(|i| i.id)
+            .collect();
+        assert_eq!(ids, vec![intent.id]);
+        assert!(log.get("../secrets").unwrap().is_none());
+    }
+}
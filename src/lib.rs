@@ -1,2 +1,81 @@
+//! An MCP server for LogSeq, and the typed LogSeq HTTP API client it's
+//! built on
+//!
+//! The client lives in [`logseq`]. Other projects can use it without the
+//! server by turning off the default `server` feature:
+//!
+//! ```toml
+//! logseq-mcp-server = { version = "0.1", default-features = false }
+//! ```
+//!
+//! ```no_run
+//! # async fn example() -> anyhow::Result<()> {
+//! use logseq_mcp_server::logseq::api::LogSeqClient;
+//! use std::time::Duration;
+//!
+//! let client = LogSeqClient::builder()
+//!     .url("http://localhost:12315")
+//!     .token("my-token")
+//!     .timeout(Duration::from_secs(10))
+//!     .build()?;
+//! for page in client.get_all_pages().await?.items {
+//!     println!("{}", page.name);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! With the `blocking` feature, [`logseq::blocking`] wraps the client for
+//! code without an async runtime.
+//!
+//! Without the `server` feature, the client also builds for `wasm32`, and
+//! can send its calls through a [`logseq::transport::Transport`] of your
+//! own, such as one that calls LogSeq's plugin API.
+
+#[cfg(feature = "server")]
+pub mod activity;
+#[cfg(feature = "server")]
+pub mod audit;
+#[cfg(feature = "server")]
+pub mod capture_template;
+#[cfg(feature = "server")]
+pub mod citations;
+#[cfg(feature = "server")]
+pub mod config;
+#[cfg(feature = "server")]
+pub mod confirm;
+#[cfg(feature = "server")]
+pub mod context_pack;
+#[cfg(feature = "server")]
+pub mod fuzzy;
+#[cfg(feature = "http")]
+pub mod http;
+#[cfg(feature = "server")]
+pub mod intents;
+#[cfg(feature = "server")]
+pub mod limits;
 pub mod logseq;
+#[cfg(feature = "server")]
+pub mod page_lock;
+#[cfg(feature = "server")]
+pub mod quota;
+#[cfg(feature = "server")]
+pub mod ranking;
+#[cfg(feature = "server")]
+pub mod region_base;
+#[cfg(feature = "server")]
+pub mod sandbox;
+#[cfg(feature = "server")]
+pub mod schedule;
+#[cfg(feature = "server")]
+pub mod server;
+#[cfg(feature = "server")]
+pub mod session;
+#[cfg(feature = "server")]
+pub mod state;
+#[cfg(feature = "server")]
 pub mod tools;
+#[cfg(feature = "server")]
+pub mod trash;
+#[cfg(feature = "server")]
+pub mod workspace;
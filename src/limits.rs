@@ -0,0 +1,148 @@
+//! Caps on how much content a single tool call may write
+//!
+//! An agent stuck in a generation loop can otherwise dump megabytes of text
+//! into a graph. Administrators can limit the size of each block, of what
+//! one call writes to any one page, and of everything one call writes. Tools
+//! charge each block against a [`WriteBudget`] before writing anything, so a
+//! call over the limit fails without leaving partial content behind.
+
+use std::collections::HashMap;
+use thiserror::Error;
+
+/// Maximum sizes, in bytes of UTF-8 text, of content written by one call.
+/// `None` means unlimited.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct WriteLimits {
+    pub block: Option<usize>,
+    pub page: Option<usize>,
+    pub call: Option<usize>,
+}
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum LimitExceeded {
+    #[error("Block content is {size} bytes, over the limit of {limit} bytes per block")]
+    Block { size: usize, limit: usize },
+    #[error(
+        "This call would write {size} bytes to page '{page}', over the limit of {limit} bytes per page"
+    )]
+    Page {
+        page: String,
+        size: usize,
+        limit: usize,
+    },
+    #[error("This call would write {size} bytes, over the limit of {limit} bytes per call")]
+    Call { size: usize, limit: usize },
+}
+
+impl LimitExceeded {
+    /// The limit that was exceeded
+    pub fn limit(&self) -> usize {
+        match self {
+            Self::Block { limit, .. } | Self::Page { limit, .. } | Self::Call { limit, .. } => {
+                *limit
+            }
+        }
+    }
+}
+
+/// Content written so far by one tool call
+#[derive(Debug, Default)]
+pub struct WriteBudget {
+    limits: WriteLimits,
+    written: usize,
+    pages: HashMap<String, usize>,
+}
+
+impl WriteBudget {
+    pub fn new(limits: WriteLimits) -> Self {
+        Self {
+            limits,
+            ..Self::default()
+        }
+    }
+
+    /// Count a block about to be written, to `page` when it's known. Nothing
+    /// is counted if the block would go over a limit.
+    pub fn charge(&mut self, page: Option<&str>, content: &str) -> Result<(), LimitExceeded> {
+        let size = content.len();
+        if let Some(limit) = self.limits.block
+            && size > limit
+        {
+            return Err(LimitExceeded::Block { size, limit });
+        }
+        let written = self.written + size;
+        if let Some(limit) = self.limits.call
+            && written > limit
+        {
+            return Err(LimitExceeded::Call {
+                size: written,
+                limit,
+            });
+        }
+        let page = page.map(str::to_lowercase);
+        if let Some(page) = &page {
+            let on_page = self.pages.get(page).copied().unwrap_or_default() + size;
+            if let Some(limit) = self.limits.page
+                && on_page > limit
+            {
+                return Err(LimitExceeded::Page {
+                    page: page.clone(),
+                    size: on_page,
+                    limit,
+                });
+            }
+        }
+
+        self.written = written;
+        if let Some(page) = page {
+            *self.pages.entry(page).or_default() += size;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unlimited_budget_accepts_anything() {
+        let mut budget = WriteBudget::default();
+        assert_eq!(budget.charge(Some("Notes"), &"x".repeat(1 << 20)), Ok(()));
+    }
+
+    #[test]
+    fn test_budget_enforces_each_limit() {
+        let mut budget = WriteBudget::new(WriteLimits {
+            block: Some(10),
+            page: Some(15),
+            call: Some(25),
+        });
+
+        assert_eq!(
+            budget.charge(None, "eleven byte"),
+            Err(LimitExceeded::Block {
+                size: 11,
+                limit: 10
+            })
+        );
+        assert_eq!(budget.charge(Some("Notes"), "ten bytes!"), Ok(()));
+        assert_eq!(
+            budget.charge(Some("notes"), "six b!"),
+            Err(LimitExceeded::Page {
+                page: "notes".to_string(),
+                size: 16,
+                limit: 15
+            })
+        );
+        assert_eq!(budget.charge(Some("Other"), "ten bytes!"), Ok(()));
+        assert_eq!(
+            budget.charge(None, "six b!"),
+            Err(LimitExceeded::Call {
+                size: 26,
+                limit: 25
+            })
+        );
+        assert_eq!(budget.charge(None, "five!"), Ok(()));
+    }
+}
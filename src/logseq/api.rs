@@ -1,13 +1,95 @@
+use crate::logseq::compat::{self, Backend, Version};
+use crate::logseq::content::{
+    INCOMPLETE_MARKERS, extract_priority, extract_tags, normalize_search_highlights,
+    property_value, search_snippet, search_terms, set_marker, split_marker, strip_property_lines,
+};
+use crate::logseq::error::LogSeqError;
+use crate::logseq::journal::DEFAULT_DATE_FORMAT;
+use crate::logseq::markdown::BatchBlock;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::logseq::payloads::PayloadJournal;
+use crate::logseq::transport::{HttpTransport, Transport};
 use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::{OnceCell, Semaphore};
 
 #[derive(Clone)]
 pub struct LogSeqClient {
-    base_url: String,
-    token: String,
-    client: reqwest::Client,
+    transport: Arc<dyn Transport>,
+    /// Why the last request couldn't connect, until one does
+    unreachable: Arc<Mutex<Option<String>>>,
+    /// Permits for requests in flight, shared by clones of the client.
+    /// Requests beyond the limit wait for a permit.
+    requests: Arc<Semaphore>,
+    /// LogSeq's version and the kind of graph open, learned when first
+    /// needed to pick between a method's names
+    backend: Arc<OnceCell<Backend>>,
+    /// Where responses that don't decode are saved for bug reports
+    #[cfg(not(target_arch = "wasm32"))]
+    payloads: Option<Arc<PayloadJournal>>,
+}
+
+/// What a health check found out about LogSeq
+#[derive(Debug, Clone, Serialize)]
+pub struct Health {
+    /// How long LogSeq took to answer, in milliseconds
+    pub latency_ms: u64,
+    /// LogSeq's version, if it says
+    pub version: Option<String>,
+    /// The name of the graph open in LogSeq
+    pub graph: Option<String>,
+}
+
+/// What the user has open in LogSeq's editor
+#[derive(Debug, Serialize)]
+pub struct EditorContext {
+    /// The open graph's name and path
+    pub graph: Value,
+    /// The page on screen, unless it's something else, such as the
+    /// journals view
+    pub page: Option<Page>,
+    /// The block being edited
+    pub block: Option<Block>,
+    /// The original name of the block's page, which differs from `page`
+    /// when editing a block embedded from elsewhere
+    pub block_page: Option<String>,
+    /// The blocks above the one being edited, from the top of its page
+    /// down, with just their UUID and content
+    pub parents: Vec<Block>,
+    /// Blocks the user has selected, as opposed to editing
+    pub selection: Vec<Block>,
+}
+
+/// Where LogSeq's HTTP API server listens by default
+pub const DEFAULT_API_URL: &str = "http://localhost:12315";
+
+/// How many requests a client sends LogSeq at once by default. LogSeq
+/// handles API calls one at a time and starts failing them when too many
+/// pile up.
+pub const DEFAULT_MAX_CONCURRENT_REQUESTS: usize = 4;
+
+/// Limits on how long the client waits for LogSeq
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ClientTimeouts {
+    /// Time allowed to establish the TCP connection
+    pub connect: Duration,
+    /// Time allowed for a whole API call, from sending the request to reading the body
+    pub request: Duration,
+}
+
+impl Default for ClientTimeouts {
+    fn default() -> Self {
+        Self {
+            connect: Duration::from_secs(5),
+            request: Duration::from_secs(30),
+        }
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -17,6 +99,63 @@ pub struct Page {
     #[serde(rename = "original-name")]
     pub original_name: Option<String>,
     pub properties: Option<HashMap<String, Value>>,
+    /// Last change in milliseconds since the Unix epoch
+    #[serde(rename = "updatedAt", default, skip_serializing_if = "Option::is_none")]
+    pub updated_at: Option<i64>,
+}
+
+impl Page {
+    /// The page's name as the user wrote it
+    pub fn display_name(&self) -> &str {
+        self.original_name.as_deref().unwrap_or(&self.name)
+    }
+
+    /// Other names the page goes by, from its `alias::` property
+    pub fn aliases(&self) -> Vec<String> {
+        let alias = self
+            .properties
+            .as_ref()
+            .and_then(|props| props.get("alias"));
+        match alias {
+            Some(Value::String(aliases)) => aliases
+                .split(',')
+                .map(|alias| alias.trim().trim_start_matches("[[").trim_end_matches("]]"))
+                .filter(|alias| !alias.is_empty())
+                .map(str::to_string)
+                .collect(),
+            Some(Value::Array(aliases)) => aliases
+                .iter()
+                .filter_map(Value::as_str)
+                .map(str::to_string)
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+}
+
+/// Entries decoded from a list response. Entries that failed to decode are
+/// left out of `items` and described in `warnings`.
+#[derive(Debug)]
+pub struct Lenient<T> {
+    pub items: Vec<T>,
+    pub warnings: Vec<String>,
+}
+
+impl<T> Default for Lenient<T> {
+    fn default() -> Self {
+        Self {
+            items: Vec::new(),
+            warnings: Vec::new(),
+        }
+    }
+}
+
+impl<T> std::ops::Deref for Lenient<T> {
+    type Target = [T];
+
+    fn deref(&self) -> &[T] {
+        &self.items
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -35,6 +174,21 @@ pub struct Block {
     pub format: Option<String>,
 }
 
+impl Block {
+    /// A bare block with just its identity and content
+    pub fn new(uuid: String, content: String) -> Self {
+        Self {
+            uuid,
+            content,
+            page: None,
+            properties: None,
+            children: vec![],
+            level: None,
+            format: None,
+        }
+    }
+}
+
 // Custom deserializer to handle both integer and PageRef formats
 fn deserialize_page_ref<'de, D>(deserializer: D) -> Result<Option<PageRef>, D::Error>
 where
@@ -107,10 +261,44 @@ pub struct PageRef {
     pub id: u64,
 }
 
+/// A search match as LogSeq reports it, before the client looks up where
+/// the block is
+#[derive(Debug)]
+pub struct SearchHit {
+    pub block: Block,
+    pub score: Option<f64>,
+    /// Name of the page containing the block, when the search gave it
+    pub page_name: Option<String>,
+    /// Excerpt of the block around the match, with matches wrapped in `**`
+    pub snippet: Option<String>,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct SearchResult {
     pub block: Block,
     pub score: Option<f64>,
+    /// Name of the page containing the block, as the user wrote it
+    pub page_name: String,
+    /// First lines of the block's parents, outermost first
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub breadcrumb: Vec<String>,
+    /// When the block was last changed, if LogSeq recorded it
+    #[serde(default)]
+    pub updated_at: Option<DateTime<Utc>>,
+    /// Excerpt of the block around the match, with matches wrapped in `**`
+    #[serde(default)]
+    pub snippet: Option<String>,
+}
+
+/// Filters and bounds for `LogSeqClient::search`
+#[derive(Debug, Clone, Default)]
+pub struct SearchOptions {
+    /// Match the query's case exactly instead of ignoring case
+    pub case_sensitive: bool,
+    /// Return at most this many results
+    pub limit: Option<usize>,
+    /// Only return blocks on the page with this name
+    pub page: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Default)]
@@ -121,6 +309,44 @@ pub struct InsertBlockOptions {
     pub properties: Option<HashMap<String, Value>>,
 }
 
+/// Where a block sits in the graph
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlockLocation {
+    /// Name of the page the block is on (lower-cased, as LogSeq stores it)
+    pub page: String,
+    /// UUID of the parent block, or `None` for a top-level block
+    pub parent: Option<String>,
+}
+
+/// Options accepted by `logseq.Editor.createPage`
+#[derive(Debug, Serialize, Deserialize, Default)]
+pub struct CreatePageOptions {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub journal: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub redirect: Option<bool>,
+    /// If not the user's preferred format
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub format: Option<PageFormat>,
+}
+
+/// The file format LogSeq writes a page in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "server", derive(schemars::JsonSchema))]
+#[serde(rename_all = "lowercase")]
+pub enum PageFormat {
+    Markdown,
+    Org,
+}
+
+#[derive(Debug, Serialize, Deserialize, Default)]
+pub struct MoveBlockOptions {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub before: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub children: Option<bool>,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct TodoItem {
     pub uuid: String,
@@ -128,85 +354,565 @@ pub struct TodoItem {
     pub marker: String,
     pub page_name: String,
     pub priority: Option<String>,
+    /// Scheduled date as a LogSeq journal day (e.g. `20240131`)
+    pub scheduled: Option<u32>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+}
+
+/// Narrows `find_incomplete_todos` to todos matching every field that is set
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TodoFilter {
+    /// Only todos on this page
+    pub page: Option<String>,
+    /// Only todos referencing this page, as `#tag` or `[[tag]]`
+    pub tag: Option<String>,
+    /// Only todos with this marker, e.g. `NOW`
+    pub marker: Option<String>,
+    /// Only todos with this priority letter, e.g. `A`
+    pub priority: Option<String>,
+}
+
+/// Counts describing the size and shape of a graph
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GraphStats {
+    /// All pages, journals included
+    pub pages: u64,
+    pub journal_pages: u64,
+    pub blocks: u64,
+    /// Distinct pages used as tags through `tags::` properties
+    pub tags: u64,
+    /// Blocks with each task marker, done and cancelled ones included
+    pub todos: std::collections::BTreeMap<String, u64>,
+    /// References from blocks to pages and other blocks, each `[[link]]`,
+    /// `#tag`, and `((ref))` counting once
+    pub backlinks: u64,
+}
+
+/// A block and when it was written, for writing statistics
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CreatedBlock {
+    pub content: String,
+    pub page_name: String,
+    /// Creation time in milliseconds since the Unix epoch
+    pub created_at: i64,
+}
+
+/// A block and when it last changed, for reporting recent changes
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChangedBlock {
+    pub uuid: String,
+    pub content: String,
+    pub page_name: String,
+    /// Last change in milliseconds since the Unix epoch
+    pub updated_at: i64,
+}
+
+/// A block's text, for checking outside the server
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CorpusBlock {
+    pub uuid: String,
+    pub page_name: String,
+    pub content: String,
+}
+
+/// The tags set on one page or block through its `tags::` property
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TagUsage {
+    /// Entity id of the page or block
+    pub id: u64,
+    /// Name of the page, or `None` when the tags are on a block
+    pub page: Option<String>,
+    /// Tag names, lower-cased and sorted
+    pub tags: Vec<String>,
+}
+
+/// An unfinished block with a `SCHEDULED:` or `DEADLINE:` date
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScheduledTask {
+    pub uuid: String,
+    pub content: String,
+    /// Task marker such as `TODO`, or `None` for a plain block with a date
+    pub marker: Option<String>,
+    pub page_name: String,
+    /// Scheduled date as a LogSeq journal day (e.g. `20240131`)
+    pub scheduled: Option<u32>,
+    /// Deadline as a LogSeq journal day
+    pub deadline: Option<u32>,
+}
+
+impl ScheduledTask {
+    /// The earlier of the task's scheduled date and deadline
+    pub fn due(&self) -> Option<u32> {
+        match (self.scheduled, self.deadline) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, b) => a.or(b),
+        }
+    }
+}
+
+/// Links from blocks on one page to another page, with `[[link]]`, a
+/// `#tag`, or a `tags::` property
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PageLink {
+    /// Name of the linking page (lower-cased, as LogSeq stores it)
+    pub from: String,
+    /// Name of the linked page (lower-cased)
+    pub to: String,
+    /// How many blocks on `from` link to `to`
+    pub count: u64,
+}
+
+/// A block that references another, with `((uuid))` or an embed
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReferencingBlock {
+    pub uuid: String,
+    pub content: String,
+    /// Name of the page the block is on (lower-cased, as LogSeq stores it)
+    pub page_name: String,
+}
+
+/// A block tagged `#card`, with the review state LogSeq's spaced-repetition
+/// keeps in its `card-*` properties
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Flashcard {
+    pub uuid: String,
+    pub content: String,
+    pub page_name: String,
+    /// When the card is next due, or `None` if it has never been reviewed
+    pub next_schedule: Option<DateTime<Utc>>,
+    /// Days between the last review and the next
+    pub last_interval: Option<f64>,
+    /// Reviews in a row the card was remembered
+    pub repeats: Option<u32>,
+    pub ease_factor: Option<f64>,
+    pub last_reviewed: Option<DateTime<Utc>>,
+}
+
+impl Flashcard {
+    /// A card from its block, reading the review state from its properties
+    pub fn from_block(uuid: String, content: String, page_name: String) -> Self {
+        let time = |key| {
+            property_value(&content, key)
+                .and_then(|value| DateTime::parse_from_rfc3339(value).ok())
+                .map(|time| time.with_timezone(&Utc))
+        };
+        let number = |key| property_value(&content, key).and_then(|value| value.parse().ok());
+        Self {
+            next_schedule: time("card-next-schedule"),
+            last_interval: number("card-last-interval"),
+            repeats: property_value(&content, "card-repeats").and_then(|v| v.parse().ok()),
+            ease_factor: number("card-ease-factor"),
+            last_reviewed: time("card-last-reviewed"),
+            uuid,
+            content,
+            page_name,
+        }
+    }
+
+    /// Whether the card has never been reviewed
+    pub fn is_new(&self) -> bool {
+        self.next_schedule.is_none()
+    }
+
+    /// Whether the card is due for review at `now`. New cards always are.
+    pub fn is_due(&self, now: DateTime<Utc>) -> bool {
+        self.next_schedule.is_none_or(|next| next <= now)
+    }
+
+    /// The front of the card: its content without properties or the
+    /// `#card` tag
+    pub fn question(&self) -> String {
+        strip_property_lines(&self.content)
+            .lines()
+            .map(|line| {
+                line.split(' ')
+                    .filter(|word| !matches!(word.to_lowercase().as_str(), "#card" | "#[[card]]"))
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+            .trim()
+            .to_string()
+    }
+}
+
+impl TodoItem {
+    /// Deterministic ordering for todo reports: priority (A before B before C
+    /// before none), then scheduled date (earliest first, unscheduled last),
+    /// then page name, with the UUID as a final tie-breaker.
+    pub fn report_order(&self, other: &Self) -> std::cmp::Ordering {
+        fn priority_rank(priority: &Option<String>) -> u8 {
+            match priority.as_deref() {
+                Some("A") => 0,
+                Some("B") => 1,
+                Some("C") => 2,
+                _ => 3,
+            }
+        }
+
+        priority_rank(&self.priority)
+            .cmp(&priority_rank(&other.priority))
+            .then_with(|| match (self.scheduled, other.scheduled) {
+                (Some(a), Some(b)) => a.cmp(&b),
+                (Some(_), None) => std::cmp::Ordering::Less,
+                (None, Some(_)) => std::cmp::Ordering::Greater,
+                (None, None) => std::cmp::Ordering::Equal,
+            })
+            .then_with(|| self.page_name.cmp(&other.page_name))
+            .then_with(|| self.uuid.cmp(&other.uuid))
+    }
+}
+
+/// Configures a [`LogSeqClient`]. The URL defaults to [`DEFAULT_API_URL`];
+/// the token must be given, unless calls go through another
+/// [`transport`](Self::transport).
+#[derive(Clone, Default)]
+pub struct LogSeqClientBuilder {
+    url: Option<String>,
+    token: Option<String>,
+    timeouts: ClientTimeouts,
+    max_concurrent_requests: Option<usize>,
+    transport: Option<Arc<dyn Transport>>,
+    #[cfg(not(target_arch = "wasm32"))]
+    payloads: Option<PayloadJournal>,
+}
+
+impl std::fmt::Debug for LogSeqClientBuilder {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("LogSeqClientBuilder")
+            .field("url", &self.url)
+            .field("timeouts", &self.timeouts)
+            .field("max_concurrent_requests", &self.max_concurrent_requests)
+            .field(
+                "transport",
+                &self.transport.as_ref().map(|t| t.endpoint().to_string()),
+            )
+            .finish_non_exhaustive()
+    }
+}
+
+impl LogSeqClientBuilder {
+    /// The URL of LogSeq's HTTP API server
+    pub fn url(mut self, url: impl Into<String>) -> Self {
+        self.url = Some(url.into());
+        self
+    }
+
+    /// The authorization token set in LogSeq's API server settings
+    pub fn token(mut self, token: impl Into<String>) -> Self {
+        self.token = Some(token.into());
+        self
+    }
+
+    /// Time allowed for a whole API call
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeouts.request = timeout;
+        self
+    }
+
+    /// Time allowed to connect to LogSeq
+    pub fn connect_timeout(mut self, timeout: Duration) -> Self {
+        self.timeouts.connect = timeout;
+        self
+    }
+
+    /// How many requests to send LogSeq at once, instead of
+    /// [`DEFAULT_MAX_CONCURRENT_REQUESTS`]. Further requests queue until
+    /// one finishes. At least one is always allowed.
+    pub fn max_concurrent_requests(mut self, max: usize) -> Self {
+        self.max_concurrent_requests = Some(max);
+        self
+    }
+
+    /// Send calls through `transport` instead of LogSeq's HTTP API server,
+    /// such as to call the plugin API from within LogSeq. The URL, token,
+    /// and timeouts are then unused.
+    pub fn transport(mut self, transport: impl Transport + 'static) -> Self {
+        self.transport = Some(Arc::new(transport));
+        self
+    }
+
+    /// Save responses that don't decode to `journal`, redacted, and name
+    /// the saved entry in the error, so it can be attached to a bug report
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn payload_journal(mut self, journal: PayloadJournal) -> Self {
+        self.payloads = Some(journal);
+        self
+    }
+
+    pub fn build(self) -> Result<LogSeqClient> {
+        let transport = match &self.transport {
+            Some(transport) => transport.clone(),
+            None => Arc::new(self.http_transport()?),
+        };
+        let mut client = LogSeqClient::from_transport(transport);
+        if let Some(max) = self.max_concurrent_requests {
+            client.requests = Arc::new(Semaphore::new(max.max(1)));
+        }
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            client.payloads = self.payloads.map(Arc::new);
+        }
+        Ok(client)
+    }
+
+    fn http_transport(&self) -> Result<HttpTransport> {
+        let token = self
+            .token
+            .as_deref()
+            .ok_or_else(|| anyhow::anyhow!("A LogSeq API token is required"))?;
+        let url = self.url.as_deref().unwrap_or(DEFAULT_API_URL);
+        HttpTransport::new(url, token, self.timeouts)
+    }
 }
 
 impl LogSeqClient {
+    pub fn builder() -> LogSeqClientBuilder {
+        LogSeqClientBuilder::default()
+    }
+
     pub fn new(base_url: &str, token: &str) -> Result<Self> {
-        Ok(Self {
-            base_url: base_url.to_string(),
-            token: token.to_string(),
-            client: reqwest::Client::new(),
+        Self::with_timeouts(base_url, token, ClientTimeouts::default())
+    }
+
+    pub fn with_timeouts(base_url: &str, token: &str, timeouts: ClientTimeouts) -> Result<Self> {
+        let transport = HttpTransport::new(base_url, token, timeouts)?;
+        Ok(Self::from_transport(Arc::new(transport)))
+    }
+
+    /// A client whose calls go through `transport`
+    pub fn with_transport(transport: impl Transport + 'static) -> Self {
+        Self::from_transport(Arc::new(transport))
+    }
+
+    fn from_transport(transport: Arc<dyn Transport>) -> Self {
+        Self {
+            transport,
+            unreachable: Arc::default(),
+            requests: Arc::new(Semaphore::new(DEFAULT_MAX_CONCURRENT_REQUESTS)),
+            backend: Arc::default(),
+            #[cfg(not(target_arch = "wasm32"))]
+            payloads: None,
+        }
+    }
+
+    /// Why LogSeq couldn't be reached, if the last request failed to connect
+    pub fn unreachable(&self) -> Option<String> {
+        self.unreachable
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    /// Ping LogSeq and ask which version it is and which graph it has open
+    pub async fn check_health(&self) -> Result<Health> {
+        // chrono's clock, unlike `Instant`, works in the browser
+        let started = Utc::now();
+        let graph = self.get_current_graph().await?;
+        let latency_ms = (Utc::now() - started).num_milliseconds().max(0) as u64;
+        // Older versions of LogSeq don't have getInfo; that's no reason to
+        // call LogSeq unhealthy
+        let version = match self.call_api("logseq.App.getInfo", vec![]).await {
+            Ok(info) => info["version"].as_str().map(str::to_string),
+            Err(e) => {
+                tracing::debug!("Could not get LogSeq's version: {}", e);
+                None
+            }
+        };
+        // Saves asking again to pick between method names
+        let _ = self.backend.set(Backend {
+            version: version.as_deref().and_then(Version::parse),
+        });
+        Ok(Health {
+            latency_ms,
+            version,
+            graph: graph["name"].as_str().map(str::to_string),
         })
     }
 
+    fn set_unreachable(&self, reason: Option<String>) {
+        *self.unreachable.lock().unwrap_or_else(|e| e.into_inner()) = reason;
+    }
+
+    /// Decode LogSeq's answer to `method`, saving it to the payload journal
+    /// if it doesn't fit
+    fn decode<T: DeserializeOwned>(&self, method: &str, value: Value) -> Result<T> {
+        let e = match T::deserialize(&value) {
+            Ok(decoded) => return Ok(decoded),
+            Err(e) => e,
+        };
+        let reason = e.to_string();
+        #[cfg(not(target_arch = "wasm32"))]
+        let report = self.payloads.as_ref().and_then(|journal| {
+            match journal.record(self.transport.endpoint(), method, &reason, &value) {
+                Ok(entry) => {
+                    tracing::warn!(
+                        "Saved LogSeq's response to {} in {}",
+                        method,
+                        journal.entry_path(&entry.id).display()
+                    );
+                    Some(entry.id)
+                }
+                Err(e) => {
+                    tracing::warn!("Could not save LogSeq's response to {}: {}", method, e);
+                    None
+                }
+            }
+        });
+        #[cfg(target_arch = "wasm32")]
+        let report = None;
+        Err(LogSeqError::MalformedResponse {
+            method: method.to_string(),
+            reason,
+            report,
+        }
+        .into())
+    }
+
+    /// LogSeq's version, asked for the first time it's needed. If LogSeq
+    /// doesn't answer, it's taken to be a current one and asked again next
+    /// time.
+    pub async fn backend(&self) -> Backend {
+        let detected = self
+            .backend
+            .get_or_try_init(|| async {
+                let version = match self.send("logseq.App.getInfo", vec![]).await {
+                    Ok(info) => info["version"].as_str().and_then(Version::parse),
+                    // Releases before getInfo don't say
+                    Err(e) if is_missing_method(&e) => None,
+                    Err(e) => return Err(e),
+                };
+                let backend = Backend { version };
+                tracing::debug!("Talking to LogSeq {:?}", backend);
+                anyhow::Ok(backend)
+            })
+            .await;
+        match detected {
+            Ok(backend) => *backend,
+            Err(e) => {
+                tracing::debug!("Could not tell which LogSeq this is: {}", e);
+                Backend::default()
+            }
+        }
+    }
+
+    /// Call an API method by the name LogSeq knows it by (see
+    /// [`compat`]), trying its other name if LogSeq doesn't know that one
     async fn call_api(&self, method: &str, args: Vec<Value>) -> Result<Value> {
+        if compat::shim(method).is_none() {
+            return self.send(method, args).await;
+        }
+        let first = compat::resolve(method, &self.backend().await);
+        match self.send(first, args.clone()).await {
+            Err(e) if is_missing_method(&e) => {
+                let Some(second) = compat::other_name(first) else {
+                    return Err(e);
+                };
+                tracing::debug!("LogSeq has no {}; calling {} instead", first, second);
+                self.send(second, args).await
+            }
+            result => result,
+        }
+    }
+
+    async fn send(&self, method: &str, args: Vec<Value>) -> Result<Value> {
+        // Held until the response has been read, as LogSeq is busy until then
+        let _permit = match self.requests.try_acquire() {
+            Ok(permit) => permit,
+            Err(_) => {
+                tracing::debug!(
+                    "Waiting for another LogSeq API call to finish before {}",
+                    method
+                );
+                self.requests
+                    .acquire()
+                    .await
+                    .map_err(|_| anyhow::anyhow!("The LogSeq client has been shut down"))?
+            }
+        };
         tracing::debug!(
             "Making API call to {} with method: {}",
-            self.base_url,
+            self.transport.endpoint(),
             method
         );
 
-        let response = self
-            .client
-            .post(format!("{}/api", self.base_url))
-            .header("Authorization", format!("Bearer {}", self.token))
-            .json(&serde_json::json!({
-                "method": method,
-                "args": args
-            }))
-            .send()
-            .await?;
-
-        let status = response.status();
-        if status.is_success() {
-            Ok(response.json().await?)
-        } else {
-            let error_text = response
-                .text()
-                .await
-                .unwrap_or_else(|_| "Unknown error".to_string());
-            tracing::error!("API call failed with status {}: {}", status, error_text);
-            Err(anyhow::anyhow!(
-                "API call failed: {} - {}",
-                status,
-                error_text
-            ))
-        }
+        let result = self.transport.call(method, args).await;
+        let unreachable = match &result {
+            Err(e) => match e.downcast_ref() {
+                Some(LogSeqError::Unreachable { reason, .. }) => Some(reason.clone()),
+                _ => None,
+            },
+            Ok(_) => None,
+        };
+        self.set_unreachable(unreachable);
+        result
     }
 
-    pub async fn get_all_pages(&self) -> Result<Vec<Page>> {
+    /// All pages in the graph. Pages that don't decode are skipped and
+    /// reported in the result's warnings rather than failing the whole list.
+    pub async fn get_all_pages(&self) -> Result<Lenient<Page>> {
         let result = self.call_api("logseq.Editor.getAllPages", vec![]).await?;
-        Ok(serde_json::from_value(result)?)
+        decode_entries(result, "page")
+    }
+
+    /// Every page under a namespace, such as `Projects/Alpha` and
+    /// `Projects/Alpha/Tasks` for `Projects`, at any depth
+    pub async fn get_pages_from_namespace(&self, namespace: &str) -> Result<Lenient<Page>> {
+        let result = self
+            .call_api(
+                "logseq.Editor.getPagesFromNamespace",
+                vec![namespace.into()],
+            )
+            .await?;
+        decode_entries(result, "page")
     }
 
     pub async fn get_page(&self, name_or_uuid: &str) -> Result<Page> {
+        self.find_page(name_or_uuid)
+            .await?
+            .ok_or_else(|| LogSeqError::page_not_found(name_or_uuid).into())
+    }
+
+    /// Look up a page, returning `None` when LogSeq has no such page
+    pub async fn find_page(&self, name_or_uuid: &str) -> Result<Option<Page>> {
         let result = self
             .call_api("logseq.Editor.getPage", vec![name_or_uuid.into()])
             .await?;
-        Ok(serde_json::from_value(result)?)
+
+        if result.is_null() {
+            return Ok(None);
+        }
+
+        Ok(Some(self.decode("logseq.Editor.getPage", result)?))
     }
 
     pub async fn create_page(
         &self,
         name: &str,
         properties: Option<HashMap<String, Value>>,
+        opts: CreatePageOptions,
     ) -> Result<Page> {
         let args = vec![
             name.into(),
             serde_json::to_value(properties).unwrap_or(Value::Null),
+            serde_json::to_value(&opts)?,
         ];
         let result = self.call_api("logseq.Editor.createPage", args).await?;
-        Ok(serde_json::from_value(result)?)
+        self.decode("logseq.Editor.createPage", result)
     }
 
-    pub async fn get_page_blocks_tree(&self, page_name_or_uuid: &str) -> Result<Vec<Block>> {
+    /// A page's block tree. A block that doesn't decode is skipped along
+    /// with its children and reported in the result's warnings.
+    pub async fn get_page_blocks_tree(&self, page_name_or_uuid: &str) -> Result<Lenient<Block>> {
         let result = self
             .call_api(
                 "logseq.Editor.getPageBlocksTree",
                 vec![page_name_or_uuid.into()],
             )
             .await?;
-        Ok(serde_json::from_value(result)?)
+        decode_block_tree(result)
     }
 
     pub async fn insert_block(&self, content: &str, opts: InsertBlockOptions) -> Result<Block> {
@@ -217,14 +923,31 @@ impl LogSeqClient {
             .clone()
             .or(opts.sibling.clone())
             .ok_or_else(|| anyhow::anyhow!("Either parent or sibling must be specified"))?;
+        let page = opts.parent.as_deref().filter(|parent| {
+            opts.sibling.is_none() && uuid::Uuid::parse_str(parent.trim()).is_err()
+        });
 
         // For insertBlock, we just need [parent, content]
         // The options like 'before' are for different use cases
         let args = vec![parent_or_sibling.into(), content.into()];
         tracing::debug!("insert_block args: {:?}", args);
-        let result = self.call_api("logseq.Editor.insertBlock", args).await?;
+        let result = self.call_api("logseq.Editor.insertBlock", args).await;
         tracing::debug!("insert_block result: {:?}", result);
 
+        // insertBlock can fail to add to a page, notably one with no blocks
+        // yet, where appendBlockInPage works
+        if let Some(page) = page {
+            let failed = match &result {
+                Ok(value) => value.is_null(),
+                Err(e) => matches!(e.downcast_ref(), Some(LogSeqError::Api { .. })),
+            };
+            if failed {
+                tracing::debug!("insertBlock failed on page {}, appending instead", page);
+                return self.append_block_in_page(page, content).await;
+            }
+        }
+        let result = result?;
+
         // The LogSeq API should return a block object with UUID
         if result.is_null() {
             return Err(anyhow::anyhow!(
@@ -233,8 +956,77 @@ impl LogSeqClient {
         }
 
         // Parse the response as a Block
-        serde_json::from_value(result)
-            .map_err(|e| anyhow::anyhow!("Failed to parse insertBlock response: {}", e))
+        self.decode("logseq.Editor.insertBlock", result)
+    }
+
+    /// Add a block after the last top-level block of a page, which may have
+    /// none yet
+    pub async fn append_block_in_page(&self, page: &str, content: &str) -> Result<Block> {
+        self.block_in_page("logseq.Editor.appendBlockInPage", page, content)
+            .await
+    }
+
+    /// Add a block before the first top-level block of a page
+    pub async fn prepend_block_in_page(&self, page: &str, content: &str) -> Result<Block> {
+        self.block_in_page("logseq.Editor.prependBlockInPage", page, content)
+            .await
+    }
+
+    async fn block_in_page(&self, method: &str, page: &str, content: &str) -> Result<Block> {
+        let result = self
+            .call_api(method, vec![page.into(), content.into()])
+            .await?;
+        if result.is_null() {
+            return Err(LogSeqError::page_not_found(page).into());
+        }
+        self.decode(method, result)
+    }
+
+    /// Insert a tree of blocks in one call, as children of the block `src`
+    /// or, with `sibling`, as its next siblings
+    pub async fn insert_batch_block(
+        &self,
+        src: &str,
+        blocks: &[BatchBlock],
+        sibling: bool,
+    ) -> Result<()> {
+        let args = vec![
+            src.into(),
+            serde_json::to_value(blocks)?,
+            serde_json::json!({ "sibling": sibling }),
+        ];
+        let result = self
+            .call_api("logseq.Editor.insertBatchBlock", args)
+            .await?;
+        tracing::debug!("insert_batch_block result: {:?}", result);
+
+        match result.get("error") {
+            Some(error) => Err(anyhow::anyhow!("Failed to insert blocks: {}", error)),
+            None => Ok(()),
+        }
+    }
+
+    /// Append a tree of blocks to the end of a page. `insertBatchBlock`
+    /// needs an existing block to insert relative to, so the first block is
+    /// inserted on its own.
+    pub async fn append_batch_block(&self, page: &str, blocks: &[BatchBlock]) -> Result<()> {
+        let Some((first, rest)) = blocks.split_first() else {
+            return Ok(());
+        };
+        let opts = InsertBlockOptions {
+            parent: Some(page.to_string()),
+            ..Default::default()
+        };
+        let first_block = self.insert_block(&first.content, opts).await?;
+        if !first.children.is_empty() {
+            self.insert_batch_block(&first_block.uuid, &first.children, false)
+                .await?;
+        }
+        if !rest.is_empty() {
+            self.insert_batch_block(&first_block.uuid, rest, true)
+                .await?;
+        }
+        Ok(())
     }
 
     pub async fn update_block(
@@ -255,86 +1047,342 @@ impl LogSeqClient {
         if result.is_null() {
             self.get_block(uuid).await
         } else {
-            Ok(serde_json::from_value(result)?)
+            self.decode("logseq.Editor.updateBlock", result)
         }
     }
 
-    pub async fn search(&self, query: &str) -> Result<Vec<SearchResult>> {
-        // Use DataScript to search for blocks containing the query text
-        let datascript_query = format!(
-            "[:find ?uuid ?content :where [?b :block/uuid ?uuid] [?b :block/content ?content] [(clojure.string/includes? ?content \"{}\")]]",
-            query.replace('"', "\\\"")
-        );
-
-        let result = self
-            .call_api("logseq.DB.datascriptQuery", vec![datascript_query.into()])
-            .await?;
-        tracing::debug!("Search DataScript result: {:?}", result);
+    /// Rewrite a block's task marker (e.g. TODO → DONE), returning the
+    /// previous marker alongside the updated block
+    pub async fn set_block_marker(
+        &self,
+        uuid: &str,
+        marker: &str,
+    ) -> Result<(Option<String>, Block)> {
+        let block = self.get_block(uuid).await?;
+        let previous = split_marker(&block.content).0.map(String::from);
+        let content = set_marker(&block.content, marker);
+        let updated = self.update_block(uuid, &content, None).await?;
+        Ok((previous, updated))
+    }
 
-        // Convert the DataScript result to SearchResult format
-        let mut search_results = Vec::new();
+    pub async fn move_block(
+        &self,
+        src_uuid: &str,
+        target_uuid: &str,
+        opts: MoveBlockOptions,
+    ) -> Result<()> {
+        // LogSeq's moveBlock API expects: [src_block, target_block, {before, children}]
+        let args = vec![
+            src_uuid.into(),
+            target_uuid.into(),
+            serde_json::to_value(&opts)?,
+        ];
+        tracing::debug!("move_block args: {:?}", args);
+        let result = self.call_api("logseq.Editor.moveBlock", args).await?;
+        tracing::debug!("move_block result: {:?}", result);
 
-        if let Some(results_array) = result.as_array() {
-            for result_row in results_array {
-                if let Some(row) = result_row.as_array()
-                    && row.len() >= 2
-                    && let (Some(uuid), Some(content)) = (
-                        row[0].as_str().map(String::from),
-                        row[1].as_str().map(String::from),
-                    )
-                {
-                    let block = Block {
-                        uuid,
-                        content,
-                        page: None, // We don't have page info from this query
-                        properties: None,
-                        children: vec![],
-                        level: None,
-                        format: None,
-                    };
-                    search_results.push(SearchResult {
-                        block,
-                        score: None, // DataScript doesn't provide scoring
-                    });
-                }
-            }
+        // The API returns null/void on success
+        if let Some(error) = result.get("error") {
+            Err(anyhow::anyhow!("Failed to move block: {}", error))
+        } else {
+            Ok(())
         }
-
-        Ok(search_results)
     }
 
-    // New Editor methods
-    pub async fn get_block(&self, uuid: &str) -> Result<Block> {
+    /// Read a single property of a block, returning `None` when it isn't set
+    pub async fn get_block_property(&self, uuid: &str, key: &str) -> Result<Option<Value>> {
+        let result = self
+            .call_api(
+                "logseq.Editor.getBlockProperty",
+                vec![uuid.into(), key.into()],
+            )
+            .await?;
+        tracing::debug!("get_block_property result: {:?}", result);
+
+        Ok((!result.is_null()).then_some(result))
+    }
+
+    /// Set a block property, adding it if absent. Only the property line is
+    /// rewritten; the rest of the block and its children are untouched.
+    pub async fn upsert_block_property(&self, uuid: &str, key: &str, value: &Value) -> Result<()> {
+        let args = vec![uuid.into(), key.into(), value.clone()];
+        let result = self
+            .call_api("logseq.Editor.upsertBlockProperty", args)
+            .await?;
+        tracing::debug!("upsert_block_property result: {:?}", result);
+
+        // The API returns null/void on success
+        match result.get("error") {
+            Some(error) => Err(anyhow::anyhow!("Failed to set block property: {}", error)),
+            None => Ok(()),
+        }
+    }
+
+    pub async fn remove_block_property(&self, uuid: &str, key: &str) -> Result<()> {
+        let result = self
+            .call_api(
+                "logseq.Editor.removeBlockProperty",
+                vec![uuid.into(), key.into()],
+            )
+            .await?;
+        tracing::debug!("remove_block_property result: {:?}", result);
+
+        // The API returns null/void on success
+        match result.get("error") {
+            Some(error) => Err(anyhow::anyhow!(
+                "Failed to remove block property: {}",
+                error
+            )),
+            None => Ok(()),
+        }
+    }
+
+    /// Full-text search using LogSeq's own search index, which returns
+    /// ranked, case-insensitive matches. Case-sensitive and page-scoped
+    /// searches, which the index can't express, match every term of the query
+    /// (see [`search_terms`]) with datascript instead, as does any search when
+    /// the search API is unavailable.
+    pub async fn search(&self, query: &str, options: &SearchOptions) -> Result<Vec<SearchResult>> {
+        let mut hits = if options.case_sensitive || options.page.is_some() {
+            self.search_content(query, options).await?
+        } else {
+            let mut args = vec![query.into()];
+            if let Some(limit) = options.limit {
+                args.push(serde_json::json!({ "limit": limit }));
+            }
+            match self.call_api("logseq.App.search", args).await {
+                Ok(result) => {
+                    tracing::debug!("App.search result: {:?}", result);
+                    parse_app_search(&result, query)
+                }
+                Err(e) => {
+                    tracing::warn!(
+                        "logseq.App.search failed ({}), falling back to datascript search",
+                        e
+                    );
+                    self.search_content(query, options).await?
+                }
+            }
+        };
+
+        if let Some(limit) = options.limit {
+            hits.truncate(limit);
+        }
+        // Highlight what was searched for, not the stopwords around it
+        let highlight = search_terms(query).join(" ");
+        for hit in &mut hits {
+            if hit.snippet.is_none() {
+                hit.snippet = search_snippet(&hit.block.content, &highlight);
+            }
+        }
+        self.locate_hits(hits).await
+    }
+
+    /// Match every term of the query against block content via datascript
+    async fn search_content(&self, query: &str, options: &SearchOptions) -> Result<Vec<SearchHit>> {
+        let datascript_query = search_content_query(query, options);
+
+        let result = self
+            .call_api("logseq.DB.datascriptQuery", vec![datascript_query.into()])
+            .await?;
+        tracing::debug!("Search DataScript result: {:?}", result);
+
+        Ok(parse_search_rows(&result))
+    }
+
+    /// Look up the page, parents, and last change of each search hit, a
+    /// level of parents at a time for all of them together. Hits on blocks
+    /// that have since gone are dropped.
+    async fn locate_hits(&self, hits: Vec<SearchHit>) -> Result<Vec<SearchResult>> {
+        let mut located: HashMap<String, BlockPlace> = HashMap::new();
+        let mut wanted: Vec<String> = hits.iter().map(|hit| hit.block.uuid.clone()).collect();
+        // The hits themselves, then one level of parents per round
+        for _ in 0..=MAX_BREADCRUMB_DEPTH {
+            wanted.sort_unstable();
+            wanted.dedup();
+            wanted.retain(|uuid| !located.contains_key(uuid));
+            if wanted.is_empty() {
+                break;
+            }
+            let rows = self
+                .call_api(
+                    "logseq.DB.datascriptQuery",
+                    vec![block_place_query(&wanted).into()],
+                )
+                .await?;
+            let places = parse_block_places(&rows);
+            wanted = places
+                .values()
+                .filter_map(|place| place.parent.clone())
+                .collect();
+            located.extend(places);
+        }
+
+        let mut results = Vec::with_capacity(hits.len());
+        for hit in hits {
+            let Some(place) = located.get(&hit.block.uuid) else {
+                tracing::debug!("Dropping search hit {}: block not found", hit.block.uuid);
+                continue;
+            };
+            let mut breadcrumb = Vec::new();
+            let mut parent = place.parent.as_ref();
+            while let Some(uuid) = parent {
+                let Some(ancestor) = located.get(uuid) else {
+                    // Deeper than we looked
+                    breadcrumb.push("…".to_string());
+                    break;
+                };
+                breadcrumb.push(first_line(&ancestor.content));
+                parent = ancestor.parent.as_ref();
+            }
+            breadcrumb.reverse();
+            results.push(SearchResult {
+                page_name: hit.page_name.unwrap_or_else(|| place.page.clone()),
+                breadcrumb,
+                updated_at: place.updated_at,
+                block: hit.block,
+                score: hit.score,
+                snippet: hit.snippet,
+            });
+        }
+        Ok(results)
+    }
+
+    // New Editor methods
+    pub async fn get_block(&self, uuid: &str) -> Result<Block> {
         let result = self
             .call_api("logseq.Editor.getBlock", vec![uuid.into()])
             .await?;
 
         if result.is_null() {
-            return Err(anyhow::anyhow!("Block with UUID {} not found", uuid));
+            return Err(LogSeqError::block_not_found(uuid).into());
         }
 
-        Ok(serde_json::from_value(result)?)
+        self.decode("logseq.Editor.getBlock", result)
+    }
+
+    /// Fetch a block together with its full tree of children
+    pub async fn get_block_tree(&self, uuid: &str) -> Result<Block> {
+        let result = self
+            .call_api(
+                "logseq.Editor.getBlock",
+                vec![uuid.into(), serde_json::json!({ "includeChildren": true })],
+            )
+            .await?;
+
+        if result.is_null() {
+            return Err(LogSeqError::block_not_found(uuid).into());
+        }
+
+        self.decode("logseq.Editor.getBlock", result)
+    }
+
+    /// Find the page and parent block of a block, or `None` if the block
+    /// doesn't exist
+    pub async fn get_block_location(&self, uuid: &str) -> Result<Option<BlockLocation>> {
+        let query = format!(
+            "[:find ?page ?parent-uuid ?top :where [?b :block/uuid #uuid {}] [?b :block/page ?p] [?p :block/name ?page] [?b :block/parent ?parent] [?parent :block/uuid ?parent-uuid] [(= ?parent ?p) ?top]]",
+            edn_string(uuid)
+        );
+        let result = self.datascript_query(&query, &[]).await?;
+        Ok(parse_block_location(&result))
     }
 
     pub async fn get_current_page(&self) -> Result<Page> {
         let result = self
             .call_api("logseq.Editor.getCurrentPage", vec![])
             .await?;
-        Ok(serde_json::from_value(result)?)
+        self.decode("logseq.Editor.getCurrentPage", result)
     }
 
     pub async fn get_current_block(&self) -> Result<Block> {
         let result = self
             .call_api("logseq.Editor.getCurrentBlock", vec![])
             .await?;
-        Ok(serde_json::from_value(result)?)
+        self.decode("logseq.Editor.getCurrentBlock", result)
     }
 
-    // Database methods
-    pub async fn datascript_query(&self, query: &str) -> Result<Value> {
+    /// The blocks the user has selected, if any
+    pub async fn get_selected_blocks(&self) -> Result<Vec<Block>> {
         let result = self
-            .call_api("logseq.DB.datascriptQuery", vec![query.into()])
+            .call_api("logseq.Editor.getSelectedBlocks", vec![])
             .await?;
+        if result.is_null() {
+            return Ok(Vec::new());
+        }
+        self.decode("logseq.Editor.getSelectedBlocks", result)
+    }
+
+    /// The open graph, page, edited block and its parents, and selection,
+    /// asked for together so the answers agree as far as possible while the
+    /// user clicks around
+    pub async fn get_editor_context(&self) -> Result<EditorContext> {
+        let (graph, page, block, selection) = tokio::try_join!(
+            self.get_current_graph(),
+            self.call_api("logseq.Editor.getCurrentPage", vec![]),
+            self.call_api("logseq.Editor.getCurrentBlock", vec![]),
+            self.get_selected_blocks(),
+        )?;
+        let page: Option<Page> = match page {
+            Value::Null => None,
+            page => Some(self.decode("logseq.Editor.getCurrentPage", page)?),
+        };
+        let block: Option<Block> = match block {
+            Value::Null => None,
+            block => Some(self.decode("logseq.Editor.getCurrentBlock", block)?),
+        };
+        let (block_page, parents) = match &block {
+            Some(block) => self.block_parents(&block.uuid).await?,
+            None => (None, Vec::new()),
+        };
+        Ok(EditorContext {
+            graph,
+            page,
+            block,
+            block_page,
+            parents,
+            selection,
+        })
+    }
+
+    /// The original name of a block's page, and the blocks above it from the
+    /// top of the page down, one level per query
+    async fn block_parents(&self, uuid: &str) -> Result<(Option<String>, Vec<Block>)> {
+        let mut page = None;
+        let mut parents = Vec::new();
+        let mut current = uuid.to_string();
+        for _ in 0..=MAX_PARENT_DEPTH {
+            let rows = self
+                .call_api(
+                    "logseq.DB.datascriptQuery",
+                    vec![block_place_query(std::slice::from_ref(&current)).into()],
+                )
+                .await?;
+            let Some(place) = parse_block_places(&rows).remove(&current) else {
+                break;
+            };
+            if current == uuid {
+                page = Some(place.page);
+            } else {
+                parents.push(Block::new(current, place.content));
+            }
+            match place.parent {
+                Some(parent) => current = parent,
+                None => break,
+            }
+        }
+        parents.reverse();
+        Ok((page, parents))
+    }
+
+    // Database methods
+    /// Run a datascript query, binding `inputs` to the query's `:in`
+    /// variables after the database. LogSeq reads string inputs as EDN, so
+    /// a string value must be quoted (see `edn_string`).
+    pub async fn datascript_query(&self, query: &str, inputs: &[Value]) -> Result<Value> {
+        let mut args = vec![Value::from(query)];
+        args.extend_from_slice(inputs);
+        let result = self.call_api("logseq.DB.datascriptQuery", args).await?;
         Ok(result)
     }
 
@@ -351,11 +1399,253 @@ impl LogSeqClient {
         Ok(result)
     }
 
+    /// Show a notification in the LogSeq window. `status` is LogSeq's
+    /// message type (`success`, `warning`, or `error`), and `timeout` how
+    /// long it stays up if not LogSeq's default.
+    pub async fn show_msg(
+        &self,
+        content: &str,
+        status: &str,
+        timeout: Option<Duration>,
+    ) -> Result<()> {
+        let mut args = vec![content.into(), status.into()];
+        if let Some(timeout) = timeout {
+            args.push(serde_json::json!({ "timeout": timeout.as_millis() as u64 }));
+        }
+        self.call_api("logseq.UI.showMsg", args).await?;
+        Ok(())
+    }
+
+    /// Navigate the LogSeq window to a route, e.g. `page` with
+    /// `{"name": "Projects"}`
+    pub async fn push_state(&self, route: &str, params: Value) -> Result<()> {
+        self.call_api("logseq.App.pushState", vec![route.into(), params])
+            .await?;
+        Ok(())
+    }
+
+    /// Open a page in the LogSeq window and scroll to one of its blocks
+    pub async fn scroll_to_block_in_page(&self, page: &str, uuid: &str) -> Result<()> {
+        self.call_api(
+            "logseq.Editor.scrollToBlockInPage",
+            vec![page.into(), uuid.into()],
+        )
+        .await?;
+        Ok(())
+    }
+
     pub async fn get_user_configs(&self) -> Result<Value> {
         let result = self.call_api("logseq.App.getUserConfigs", vec![]).await?;
         Ok(result)
     }
 
+    /// The user's journal title format, falling back to LogSeq's default
+    pub async fn get_preferred_date_format(&self) -> Result<String> {
+        let configs = self.get_user_configs().await?;
+        Ok(preferred_date_format(&configs))
+    }
+
+    /// Names of every page used as a tag via a `tags::` property, sorted
+    pub async fn get_all_tags(&self) -> Result<Vec<String>> {
+        let query = r#"[:find ?name
+            :where
+            [_ :block/tags ?t]
+            [?t :block/name ?name]]"#;
+        let result = self
+            .call_api("logseq.DB.datascriptQuery", vec![query.into()])
+            .await?;
+
+        let mut tags: Vec<String> = result
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|row| row.get(0)?.as_str().map(String::from))
+            .collect();
+        tags.sort();
+        tags.dedup();
+        Ok(tags)
+    }
+
+    /// Every page and block with a `tags::` property and the tags it sets,
+    /// ordered by entity id
+    pub async fn get_tag_usage(&self) -> Result<Vec<TagUsage>> {
+        let query = r#"[:find ?e ?name ?page
+            :where
+            [?e :block/tags ?t]
+            [?t :block/name ?name]
+            [(get-else $ ?e :block/name "") ?page]]"#;
+        let result = self
+            .call_api("logseq.DB.datascriptQuery", vec![query.into()])
+            .await?;
+
+        let mut usage: std::collections::BTreeMap<u64, TagUsage> = Default::default();
+        for row in result.as_array().into_iter().flatten() {
+            let (Some(id), Some(tag)) = (
+                row.get(0).and_then(|v| v.as_u64()),
+                row.get(1).and_then(|v| v.as_str()),
+            ) else {
+                continue;
+            };
+            let entry = usage.entry(id).or_insert_with(|| TagUsage {
+                id,
+                page: row
+                    .get(2)
+                    .and_then(|v| v.as_str())
+                    .filter(|p| !p.is_empty())
+                    .map(String::from),
+                tags: Vec::new(),
+            });
+            entry.tags.push(tag.to_string());
+        }
+        Ok(usage
+            .into_values()
+            .map(|mut entry| {
+                entry.tags.sort();
+                entry.tags.dedup();
+                entry
+            })
+            .collect())
+    }
+
+    /// Blocks created from `from` (inclusive) to `to` (exclusive), both in
+    /// milliseconds since the Unix epoch, oldest first
+    pub async fn get_blocks_created_between(
+        &self,
+        from: i64,
+        to: i64,
+    ) -> Result<Vec<CreatedBlock>> {
+        let query = format!(
+            "[:find ?content ?page-name ?created :where [?b :block/created-at ?created] [(>= ?created {})] [(< ?created {})] [?b :block/content ?content] [?b :block/page ?p] [?p :block/original-name ?page-name]]",
+            from, to
+        );
+        let result = self
+            .call_api("logseq.DB.datascriptQuery", vec![query.into()])
+            .await?;
+
+        let mut blocks: Vec<CreatedBlock> = result
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|row| {
+                Some(CreatedBlock {
+                    content: row.get(0)?.as_str()?.to_string(),
+                    page_name: row.get(1)?.as_str()?.to_string(),
+                    created_at: row.get(2)?.as_i64()?,
+                })
+            })
+            .collect();
+        blocks.sort_by_key(|b| b.created_at);
+        Ok(blocks)
+    }
+
+    /// Blocks changed at or after `since`, in milliseconds since the Unix
+    /// epoch, most recent first
+    pub async fn get_blocks_updated_since(&self, since: i64) -> Result<Vec<ChangedBlock>> {
+        let query = format!(
+            "[:find ?uuid ?content ?page-name ?updated :where [?b :block/updated-at ?updated] [(>= ?updated {})] [?b :block/uuid ?u] [(str ?u) ?uuid] [?b :block/content ?content] [?b :block/page ?p] [?p :block/original-name ?page-name]]",
+            since
+        );
+        let result = self
+            .call_api("logseq.DB.datascriptQuery", vec![query.into()])
+            .await?;
+
+        let mut blocks: Vec<ChangedBlock> = result
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|row| {
+                Some(ChangedBlock {
+                    uuid: row.get(0)?.as_str()?.to_string(),
+                    content: row.get(1)?.as_str()?.to_string(),
+                    page_name: row.get(2)?.as_str()?.to_string(),
+                    updated_at: row.get(3)?.as_i64()?,
+                })
+            })
+            .collect();
+        blocks.sort_by(|a, b| b.updated_at.cmp(&a.updated_at).then(a.uuid.cmp(&b.uuid)));
+        Ok(blocks)
+    }
+
+    /// Every non-empty block in the graph, or on one page, ordered by page
+    /// and then UUID so the order is stable between calls
+    pub async fn get_corpus(&self, page: Option<&str>) -> Result<Vec<CorpusBlock>> {
+        let scope = match page {
+            Some(page) => format!(" [?p :block/name {}]", edn_string(&page.to_lowercase())),
+            None => String::new(),
+        };
+        let query = format!(
+            "[:find ?uuid ?page-name ?content :where{} [?b :block/page ?p] [?p :block/original-name ?page-name] [?b :block/uuid ?uuid] [?b :block/content ?content]]",
+            scope
+        );
+        let result = self
+            .call_api("logseq.DB.datascriptQuery", vec![query.into()])
+            .await?;
+
+        let mut blocks: Vec<CorpusBlock> = result
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|row| {
+                Some(CorpusBlock {
+                    uuid: row.get(0)?.as_str()?.to_string(),
+                    page_name: row.get(1)?.as_str()?.to_string(),
+                    content: row.get(2)?.as_str()?.to_string(),
+                })
+            })
+            .filter(|b| !b.content.trim().is_empty())
+            .collect();
+        blocks.sort_by(|a, b| {
+            a.page_name
+                .to_lowercase()
+                .cmp(&b.page_name.to_lowercase())
+                .then_with(|| a.uuid.cmp(&b.uuid))
+        });
+        Ok(blocks)
+    }
+
+    /// Page, block, tag, task, and reference counts for the whole graph
+    pub async fn get_graph_stats(&self) -> Result<GraphStats> {
+        let count = |query: &'static str| async move {
+            let result = self
+                .call_api("logseq.DB.datascriptQuery", vec![query.into()])
+                .await?;
+            // Aggregates over no rows come back as an empty result
+            Ok::<u64, anyhow::Error>(
+                result
+                    .get(0)
+                    .and_then(|row| row.get(0))
+                    .and_then(|n| n.as_u64())
+                    .unwrap_or(0),
+            )
+        };
+        let (pages, journal_pages, blocks, tags, backlinks, todos) = tokio::try_join!(
+            count("[:find (count ?p) :where [?p :block/name]]"),
+            count("[:find (count ?p) :where [?p :block/journal? true]]"),
+            count("[:find (count ?b) :where [?b :block/page]]"),
+            count("[:find (count-distinct ?t) :where [_ :block/tags ?t]]"),
+            count("[:find (count ?b) :with ?r :where [?b :block/refs ?r]]"),
+            self.call_api(
+                "logseq.DB.datascriptQuery",
+                vec!["[:find ?marker (count ?b) :where [?b :block/marker ?marker]]".into()],
+            ),
+        )?;
+
+        let todos = todos
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|row| Some((row.get(0)?.as_str()?.to_string(), row.get(1)?.as_u64()?)))
+            .collect();
+        Ok(GraphStats {
+            pages,
+            journal_pages,
+            blocks,
+            tags,
+            todos,
+            backlinks,
+        })
+    }
+
     // Delete operations
     pub async fn remove_block(&self, block_uuid: &str) -> Result<()> {
         let result = self
@@ -395,21 +1685,11 @@ impl LogSeqClient {
         }
     }
 
-    // Search for incomplete todos across all pages
-    pub async fn find_incomplete_todos(&self) -> Result<Vec<TodoItem>> {
-        // Use DataScript query to find all incomplete todos
-        // Based on LogSeq docs, incomplete todos are marked as TODO, DOING, LATER, NOW
-        let datascript_query = r#"[:find ?uuid ?content ?marker ?page-name
-            :where 
-            [?b :block/uuid ?uuid]
-            [?b :block/content ?content]
-            [?b :block/marker ?marker]
-            [?b :block/page ?p]
-            [?p :block/name ?page-name]
-            [(contains? #{"TODO" "DOING" "LATER" "NOW" "WAITING"} ?marker)]]"#;
-
+    /// Incomplete todos (TODO, DOING, LATER, NOW, WAITING) across all pages,
+    /// narrowed by `filter`, in report order
+    pub async fn find_incomplete_todos(&self, filter: &TodoFilter) -> Result<Vec<TodoItem>> {
         let result = self
-            .call_api("logseq.DB.datascriptQuery", vec![datascript_query.into()])
+            .call_api("logseq.DB.datascriptQuery", vec![todo_query(filter).into()])
             .await?;
         tracing::debug!("find_incomplete_todos DataScript result: {:?}", result);
 
@@ -427,24 +1707,524 @@ impl LogSeqClient {
                         row[3].as_str().map(String::from),
                     )
                 {
+                    // Optional attributes come back as "" / 0 via get-else
+                    let priority = row
+                        .get(4)
+                        .and_then(|v| v.as_str())
+                        .filter(|p| !p.is_empty())
+                        .map(String::from)
+                        .or_else(|| extract_priority(&content));
+                    let scheduled = row
+                        .get(5)
+                        .and_then(|v| v.as_u64())
+                        .filter(|d| *d > 0)
+                        .and_then(|d| u32::try_from(d).ok());
+
+                    let tags = extract_tags(&content);
                     todos.push(TodoItem {
                         uuid,
                         content,
                         marker,
                         page_name,
-                        priority: None, // Could be extended to include priority
+                        priority,
+                        scheduled,
+                        tags,
                     });
                 }
             }
         }
 
+        todos.sort_by(TodoItem::report_order);
         Ok(todos)
     }
+
+    /// Blocks tagged `#card`, by page
+    pub async fn find_flashcards(&self) -> Result<Vec<Flashcard>> {
+        let query = r#"[:find ?uuid ?content ?page-name
+            :where
+            [?t :block/name "card"]
+            [?b :block/refs ?t]
+            [?b :block/uuid ?uuid]
+            [?b :block/content ?content]
+            [?b :block/page ?p]
+            [?p :block/name ?page-name]]"#;
+        let result = self
+            .call_api("logseq.DB.datascriptQuery", vec![query.into()])
+            .await?;
+        tracing::debug!("find_flashcards DataScript result: {:?}", result);
+
+        let mut cards: Vec<Flashcard> = result
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|row| {
+                let row = row.as_array()?;
+                Some(Flashcard::from_block(
+                    row.first()?.as_str()?.to_string(),
+                    row.get(1)?.as_str()?.to_string(),
+                    row.get(2)?.as_str()?.to_string(),
+                ))
+            })
+            .collect();
+        cards.sort_by(|a, b| {
+            a.page_name
+                .cmp(&b.page_name)
+                .then_with(|| a.uuid.cmp(&b.uuid))
+        });
+        Ok(cards)
+    }
+
+    /// Every link from a page's blocks to another page, by linking page then
+    /// linked page
+    pub async fn get_page_links(&self) -> Result<Vec<PageLink>> {
+        let query = r#"[:find ?from ?to (count ?b)
+            :where
+            [?b :block/page ?p]
+            [?b :block/refs ?r]
+            [(not= ?p ?r)]
+            [?p :block/name ?from]
+            [?r :block/name ?to]]"#;
+        let result = self
+            .call_api("logseq.DB.datascriptQuery", vec![query.into()])
+            .await?;
+
+        let mut links: Vec<PageLink> = result
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|row| {
+                let row = row.as_array()?;
+                Some(PageLink {
+                    from: row.first()?.as_str()?.to_string(),
+                    to: row.get(1)?.as_str()?.to_string(),
+                    count: row.get(2)?.as_u64()?,
+                })
+            })
+            .collect();
+        links.sort_by(|a, b| a.from.cmp(&b.from).then_with(|| a.to.cmp(&b.to)));
+        Ok(links)
+    }
+
+    /// Blocks that reference the block `uuid`, by page then UUID
+    pub async fn find_block_references(&self, uuid: &str) -> Result<Vec<ReferencingBlock>> {
+        // Checked, as the UUID goes into the query text
+        let uuid = uuid::Uuid::parse_str(uuid.trim())
+            .map_err(|_| LogSeqError::block_not_found(uuid.trim()))?;
+        let query = format!(
+            r#"[:find ?uuid ?content ?page-name
+            :where
+            [?target :block/uuid #uuid "{}"]
+            [?b :block/refs ?target]
+            [?b :block/uuid ?uuid]
+            [?b :block/content ?content]
+            [?b :block/page ?p]
+            [?p :block/name ?page-name]]"#,
+            uuid
+        );
+        let result = self
+            .call_api("logseq.DB.datascriptQuery", vec![query.into()])
+            .await?;
+
+        let mut blocks: Vec<ReferencingBlock> = result
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|row| {
+                let row = row.as_array()?;
+                Some(ReferencingBlock {
+                    uuid: row.first()?.as_str()?.to_string(),
+                    content: row.get(1)?.as_str()?.to_string(),
+                    page_name: row.get(2)?.as_str()?.to_string(),
+                })
+            })
+            .collect();
+        blocks.sort_by(|a, b| {
+            a.page_name
+                .cmp(&b.page_name)
+                .then_with(|| a.uuid.cmp(&b.uuid))
+        });
+        Ok(blocks)
+    }
+
+    /// Blocks with a scheduled date or deadline that aren't done or
+    /// cancelled, earliest first
+    pub async fn find_scheduled_tasks(&self) -> Result<Vec<ScheduledTask>> {
+        let query = r#"[:find ?uuid ?content ?marker ?page-name ?scheduled ?deadline
+            :where
+            [?b :block/uuid ?uuid]
+            [?b :block/content ?content]
+            (or [?b :block/scheduled _] [?b :block/deadline _])
+            [(get-else $ ?b :block/marker "") ?marker]
+            (not [(contains? #{"DONE" "CANCELED" "CANCELLED"} ?marker)])
+            [?b :block/page ?p]
+            [?p :block/name ?page-name]
+            [(get-else $ ?b :block/scheduled 0) ?scheduled]
+            [(get-else $ ?b :block/deadline 0) ?deadline]]"#;
+        let result = self
+            .call_api("logseq.DB.datascriptQuery", vec![query.into()])
+            .await?;
+        tracing::debug!("find_scheduled_tasks DataScript result: {:?}", result);
+
+        // Missing attributes come back as "" / 0 via get-else
+        let journal_day = |value: Option<&Value>| {
+            value
+                .and_then(|v| v.as_u64())
+                .filter(|d| *d > 0)
+                .and_then(|d| u32::try_from(d).ok())
+        };
+        let mut tasks: Vec<ScheduledTask> = result
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|row| {
+                let row = row.as_array()?;
+                Some(ScheduledTask {
+                    uuid: row.first()?.as_str()?.to_string(),
+                    content: row.get(1)?.as_str()?.to_string(),
+                    marker: row
+                        .get(2)
+                        .and_then(|v| v.as_str())
+                        .filter(|m| !m.is_empty())
+                        .map(String::from),
+                    page_name: row.get(3)?.as_str()?.to_string(),
+                    scheduled: journal_day(row.get(4)),
+                    deadline: journal_day(row.get(5)),
+                })
+            })
+            .filter(|task| task.due().is_some())
+            .collect();
+        tasks.sort_by(|a, b| {
+            a.due()
+                .cmp(&b.due())
+                .then_with(|| a.page_name.cmp(&b.page_name))
+                .then_with(|| a.uuid.cmp(&b.uuid))
+        });
+        Ok(tasks)
+    }
+}
+
+/// Convert a `logseq.App.search` response into block hits, in rank order.
+///
+/// Page names are left unset; hits only carry the page's entity id.
+pub fn parse_app_search(result: &Value, query: &str) -> Vec<SearchHit> {
+    let hits = result
+        .get("blocks")
+        .and_then(|v| v.as_array())
+        .map(Vec::as_slice)
+        .unwrap_or_default();
+
+    hits.iter()
+        .filter_map(|hit| {
+            let uuid = hit.get("block/uuid")?.as_str()?.to_string();
+            let content = hit.get("block/content")?.as_str()?.to_string();
+            let mut block = Block::new(uuid, content);
+            block.page = match hit.get("block/page") {
+                Some(Value::Number(id)) => id.as_u64().map(|id| PageRef { id }),
+                Some(page) => page
+                    .get("db/id")
+                    .or_else(|| page.get("id"))
+                    .and_then(|v| v.as_u64())
+                    .map(|id| PageRef { id }),
+                None => None,
+            };
+            let snippet = hit
+                .get("block/snippet")
+                .and_then(|v| v.as_str())
+                .map(normalize_search_highlights)
+                .or_else(|| search_snippet(&block.content, query));
+            Some(SearchHit {
+                block,
+                score: hit.get("score").and_then(|v| v.as_f64()),
+                page_name: None,
+                snippet,
+            })
+        })
+        .collect()
+}
+
+/// Convert `[uuid content page-name]` rows from the datascript content
+/// search into hits, skipping rows of any other shape.
+pub fn parse_search_rows(result: &Value) -> Vec<SearchHit> {
+    let rows = result.as_array().map(Vec::as_slice).unwrap_or_default();
+
+    rows.iter()
+        .filter_map(|row| {
+            let row = row.as_array()?;
+            let uuid = row.first()?.as_str()?.to_string();
+            let content = row.get(1)?.as_str()?.to_string();
+            Some(SearchHit {
+                block: Block::new(uuid, content),
+                score: None, // DataScript doesn't provide scoring
+                page_name: row.get(2).and_then(|v| v.as_str()).map(String::from),
+                snippet: None,
+            })
+        })
+        .collect()
+}
+
+/// How many levels of parents a search result's breadcrumb shows
+const MAX_BREADCRUMB_DEPTH: usize = 8;
+
+/// How many levels of parents the editor context walks up, in case of a
+/// cycle in a damaged graph
+const MAX_PARENT_DEPTH: usize = 64;
+
+/// Where a block is, for placing a search hit
+#[derive(Debug, Clone, PartialEq)]
+struct BlockPlace {
+    content: String,
+    /// Original name of the block's page
+    page: String,
+    /// The parent block, unless the block is at the top of its page
+    parent: Option<String>,
+    updated_at: Option<DateTime<Utc>>,
+}
+
+/// Query the content, page, parent, and last change of the blocks with
+/// these UUIDs
+fn block_place_query(uuids: &[String]) -> String {
+    let uuids: Vec<String> = uuids.iter().map(|uuid| edn_string(uuid)).collect();
+    format!(
+        "[:find ?uuid ?content ?page ?parent-uuid ?top ?updated :where [?b :block/uuid ?u] [(str ?u) ?uuid] [(contains? #{{{}}} ?uuid)] [?b :block/content ?content] [?b :block/page ?p] [?p :block/original-name ?page] [?b :block/parent ?parent] [?parent :block/uuid ?pu] [(str ?pu) ?parent-uuid] [(= ?parent ?p) ?top] [(get-else $ ?b :block/updated-at 0) ?updated]]",
+        uuids.join(" ")
+    )
+}
+
+/// Decode the rows of [`block_place_query`], by UUID
+fn parse_block_places(rows: &Value) -> HashMap<String, BlockPlace> {
+    rows.as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|row| {
+            let row = row.as_array()?;
+            let parent = match row.get(4)?.as_bool()? {
+                true => None,
+                false => Some(row.get(3)?.as_str()?.to_string()),
+            };
+            let place = BlockPlace {
+                content: row.get(1)?.as_str()?.to_string(),
+                page: row.get(2)?.as_str()?.to_string(),
+                parent,
+                updated_at: row
+                    .get(5)
+                    .and_then(Value::as_i64)
+                    .filter(|ms| *ms > 0)
+                    .and_then(DateTime::from_timestamp_millis),
+            };
+            Some((row.first()?.as_str()?.to_string(), place))
+        })
+        .collect()
+}
+
+/// A block's first line, for showing where something is
+fn first_line(content: &str) -> String {
+    content
+        .lines()
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .to_string()
+}
+
+/// Build the datascript query behind `find_incomplete_todos`. Page and tag
+/// names match LogSeq's lower-cased `:block/name`; a tag matches any
+/// reference to its page, as LogSeq doesn't tell `#tag` and `[[tag]]` apart.
+fn todo_query(filter: &TodoFilter) -> String {
+    let markers: Vec<String> = match &filter.marker {
+        Some(marker) => vec![edn_string(marker)],
+        None => INCOMPLETE_MARKERS.iter().map(|m| edn_string(m)).collect(),
+    };
+    let mut clauses = vec![
+        "[?b :block/uuid ?uuid]".to_string(),
+        "[?b :block/content ?content]".to_string(),
+        "[?b :block/marker ?marker]".to_string(),
+        format!("[(contains? #{{{}}} ?marker)]", markers.join(" ")),
+        "[?b :block/page ?p]".to_string(),
+    ];
+    if let Some(page) = &filter.page {
+        clauses.push(format!(
+            "[?p :block/name {}]",
+            edn_string(&page.to_lowercase())
+        ));
+    }
+    clauses.push("[?p :block/name ?page-name]".to_string());
+    if let Some(tag) = &filter.tag {
+        clauses.push(format!(
+            "[?t :block/name {}]",
+            edn_string(&tag.to_lowercase())
+        ));
+        clauses.push("[?b :block/refs ?t]".to_string());
+    }
+    if let Some(priority) = &filter.priority {
+        clauses.push(format!("[?b :block/priority {}]", edn_string(priority)));
+    }
+    clauses.push(r#"[(get-else $ ?b :block/priority "") ?priority]"#.to_string());
+    clauses.push("[(get-else $ ?b :block/scheduled 0) ?scheduled]".to_string());
+
+    format!(
+        "[:find ?uuid ?content ?marker ?page-name ?priority ?scheduled :where {}]",
+        clauses.join(" ")
+    )
+}
+
+/// Build the datascript query behind `search_content`: every term of the
+/// query (see [`search_terms`]) must appear somewhere in the block.
+/// Case-insensitive matching lower-cases both sides; page scoping matches
+/// LogSeq's lower-cased `:block/name`.
+fn search_content_query(query: &str, options: &SearchOptions) -> String {
+    let mut terms = search_terms(query);
+    if terms.is_empty() {
+        terms.push(query.to_string());
+    }
+    let mut clauses = vec![
+        "[?b :block/uuid ?uuid]".to_string(),
+        "[?b :block/content ?content]".to_string(),
+    ];
+    if options.case_sensitive {
+        for term in &terms {
+            clauses.push(format!(
+                "[(clojure.string/includes? ?content {})]",
+                edn_string(term)
+            ));
+        }
+    } else {
+        clauses.push("[(clojure.string/lower-case ?content) ?lower]".to_string());
+        for term in &terms {
+            clauses.push(format!(
+                "[(clojure.string/includes? ?lower {})]",
+                edn_string(&term.to_lowercase())
+            ));
+        }
+    }
+    clauses.push("[?b :block/page ?p]".to_string());
+    if let Some(page) = &options.page {
+        clauses.push(format!(
+            "[?p :block/name {}]",
+            edn_string(&page.to_lowercase())
+        ));
+    }
+    clauses.push("[?p :block/original-name ?page]".to_string());
+
+    format!("[:find ?uuid ?content ?page :where {}]", clauses.join(" "))
+}
+
+/// Decode each entry of a list response on its own, skipping (and warning
+/// about) entries that don't decode. `null` is treated as an empty list.
+pub fn decode_entries<T: DeserializeOwned>(value: Value, kind: &str) -> Result<Lenient<T>> {
+    let mut decoded = Lenient::default();
+    for (index, entry) in expect_list(value, kind)?.into_iter().enumerate() {
+        let label = entry_label(&entry);
+        match serde_json::from_value(entry) {
+            Ok(item) => decoded.items.push(item),
+            Err(e) => {
+                decoded
+                    .warnings
+                    .push(format!("Skipped {} {}{}: {}", kind, index + 1, label, e))
+            }
+        }
+    }
+    Ok(decoded)
+}
+
+/// Whether LogSeq refused a call because it has no such method
+fn is_missing_method(e: &anyhow::Error) -> bool {
+    matches!(
+        e.downcast_ref(),
+        Some(LogSeqError::Api { body, .. }) if body.contains("MethodNotExist")
+    )
+}
+
+/// Decode a block tree node by node, so one malformed block only costs its
+/// own subtree. Warnings locate skipped blocks by their path in the tree
+/// (e.g. `2.1` is the first child of the second top-level block).
+pub fn decode_block_tree(value: Value) -> Result<Lenient<Block>> {
+    let mut decoded = Lenient::default();
+    decoded.items = decode_blocks(expect_list(value, "block")?, "", &mut decoded.warnings);
+    Ok(decoded)
+}
+
+fn decode_blocks(entries: Vec<Value>, parent: &str, warnings: &mut Vec<String>) -> Vec<Block> {
+    let mut blocks = Vec::with_capacity(entries.len());
+    for (index, mut entry) in entries.into_iter().enumerate() {
+        let path = format!("{}{}", parent, index + 1);
+        let children = match entry.as_object_mut().and_then(|o| o.remove("children")) {
+            Some(Value::Array(children)) => children,
+            _ => Vec::new(),
+        };
+        let label = entry_label(&entry);
+
+        match serde_json::from_value::<Block>(entry) {
+            Ok(mut block) => {
+                block.children = decode_blocks(children, &format!("{}.", path), warnings);
+                blocks.push(block);
+            }
+            Err(e) => {
+                let skipped = match children.len() {
+                    0 => String::new(),
+                    n => format!(" and its {} child block(s)", n),
+                };
+                warnings.push(format!("Skipped block {}{}{}: {}", path, label, skipped, e));
+            }
+        }
+    }
+    blocks
+}
+
+/// Decode a successful response, blaming LogSeq if it isn't the expected shape
+/// The innermost cause of a request error, which is usually the one that
+/// says what happened (e.g. "Connection refused")
+fn expect_list(value: Value, kind: &str) -> Result<Vec<Value>> {
+    match value {
+        Value::Array(entries) => Ok(entries),
+        Value::Null => Ok(Vec::new()),
+        other => Err(anyhow::anyhow!(
+            "Expected a list of {}s from LogSeq, got: {}",
+            kind,
+            other
+        )),
+    }
+}
+
+/// A short identifier for an entry in a warning, e.g. ` ("Project Alpha")`
+fn entry_label(entry: &Value) -> String {
+    ["original-name", "name", "uuid"]
+        .iter()
+        .find_map(|key| entry.get(*key)?.as_str())
+        .map(|id| format!(" ({:?})", id))
+        .unwrap_or_default()
+}
+
+/// Quote `s` as an EDN string literal, for passing a string as a datascript
+/// query input
+pub fn edn_string(s: &str) -> String {
+    format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// Read the single `[page parent-uuid top?]` row of a block location query
+pub fn parse_block_location(result: &Value) -> Option<BlockLocation> {
+    let row = result.as_array()?.first()?.as_array()?;
+    let page = row.first()?.as_str()?.to_string();
+    let parent = row.get(1)?.as_str()?.to_string();
+    let top = row.get(2)?.as_bool()?;
+    Some(BlockLocation {
+        page,
+        parent: (!top).then_some(parent),
+    })
+}
+
+/// Read the journal title format from a user configs object
+pub fn preferred_date_format(configs: &Value) -> String {
+    configs
+        .get("preferredDateFormat")
+        .and_then(|v| v.as_str())
+        .unwrap_or(DEFAULT_DATE_FORMAT)
+        .to_string()
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::logseq::transport::TransportFuture;
 
     #[test]
     fn test_client_creation() {
@@ -455,8 +2235,239 @@ mod tests {
         );
 
         let client = result.unwrap();
-        assert_eq!(client.base_url, "http://localhost:12315");
-        assert_eq!(client.token, "test-token");
+        assert_eq!(client.transport.endpoint(), "http://localhost:12315");
+
+        let transport =
+            HttpTransport::new("http://localhost:12315", "test-token", Default::default()).unwrap();
+        assert_eq!(transport.token, "test-token");
+    }
+
+    #[test]
+    fn test_builder_defaults_the_url_and_requires_a_token() {
+        let transport = LogSeqClient::builder()
+            .token("test-token")
+            .timeout(Duration::from_secs(10))
+            .http_transport()
+            .unwrap();
+        assert_eq!(transport.base_url, DEFAULT_API_URL);
+        assert_eq!(transport.timeouts.request, Duration::from_secs(10));
+        assert_eq!(
+            transport.timeouts.connect,
+            ClientTimeouts::default().connect
+        );
+
+        let client = LogSeqClient::builder()
+            .url("http://logseq.local:9000")
+            .token("test-token")
+            .build()
+            .unwrap();
+        assert_eq!(client.transport.endpoint(), "http://logseq.local:9000");
+
+        let Err(err) = LogSeqClient::builder().build() else {
+            panic!("a client was built without a token");
+        };
+        assert_eq!(err.to_string(), "A LogSeq API token is required");
+    }
+
+    /// Answers calls from a map of canned responses
+    struct CannedTransport {
+        responses: HashMap<&'static str, Value>,
+    }
+
+    impl Transport for CannedTransport {
+        fn call<'a>(&'a self, method: &'a str, _args: Vec<Value>) -> TransportFuture<'a> {
+            let result = match self.responses.get(method) {
+                Some(response) => Ok(response.clone()),
+                None => Err(LogSeqError::Unreachable {
+                    url: "canned".to_string(),
+                    reason: format!("no response for {}", method),
+                }
+                .into()),
+            };
+            Box::pin(std::future::ready(result))
+        }
+
+        fn endpoint(&self) -> &str {
+            "canned"
+        }
+    }
+
+    #[tokio::test]
+    async fn test_calls_go_through_a_custom_transport() {
+        let transport = CannedTransport {
+            responses: HashMap::from([(
+                "logseq.Editor.getPage",
+                serde_json::json!({"name": "projects", "uuid": "page-uuid", "id": 1}),
+            )]),
+        };
+        // No token is needed without the HTTP transport
+        let client = LogSeqClient::builder()
+            .transport(transport)
+            .build()
+            .unwrap();
+
+        let page = client.get_page("Projects").await.unwrap();
+        assert_eq!(page.uuid, "page-uuid");
+        assert!(client.unreachable().is_none());
+
+        let err = client.get_all_pages().await.unwrap_err();
+        assert!(matches!(
+            err.downcast_ref(),
+            Some(LogSeqError::Unreachable { .. })
+        ));
+        assert_eq!(
+            client.unreachable().as_deref(),
+            Some("no response for logseq.Editor.getAllPages")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_renamed_methods_follow_the_version() {
+        use crate::logseq::mock::{MockGraph, MockLogSeq};
+
+        let logseq = MockLogSeq::serve(MockGraph::new()).await.unwrap();
+        let methods = |logseq: &MockLogSeq| -> Vec<String> {
+            logseq.calls().into_iter().map(|call| call.method).collect()
+        };
+
+        // Methods without another name never need the version
+        let client = logseq.client();
+        client.get_all_pages().await.unwrap();
+        assert_eq!(methods(&logseq), ["logseq.Editor.getAllPages"]);
+
+        // A current release is asked once, then sent the current name
+        client.show_msg("Hi", "success", None).await.unwrap();
+        client.show_msg("Hi", "success", None).await.unwrap();
+        assert_eq!(client.backend().await.version, Some(Version::new(0, 10, 9)));
+        assert_eq!(
+            methods(&logseq)[1..],
+            [
+                "logseq.App.getInfo",
+                "logseq.UI.showMsg",
+                "logseq.UI.showMsg"
+            ]
+        );
+
+        // An older release gets the old name, and the current one if it
+        // turns out not to know the old one after all
+        logseq.respond(
+            "logseq.App.getInfo",
+            serde_json::json!({"version": "0.8.17"}),
+        );
+        let client = logseq.client();
+        client.show_msg("Hi", "success", None).await.unwrap();
+        assert_eq!(
+            methods(&logseq)[4..],
+            [
+                "logseq.App.getInfo",
+                "logseq.App.showMsg",
+                "logseq.UI.showMsg"
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_undecodable_responses_are_saved() {
+        let transport = CannedTransport {
+            responses: HashMap::from([(
+                "logseq.Editor.getBlock",
+                serde_json::json!({"uuid": 7, "content": "Call Sam"}),
+            )]),
+        };
+        let dir = tempfile::tempdir().unwrap();
+        let journal = PayloadJournal::new(dir.path());
+        let client = LogSeqClient::builder()
+            .transport(transport)
+            .payload_journal(journal.clone())
+            .build()
+            .unwrap();
+
+        let err = client.get_block("block-uuid").await.unwrap_err();
+        let Some(LogSeqError::MalformedResponse {
+            report: Some(id), ..
+        }) = err.downcast_ref()
+        else {
+            panic!("the response wasn't saved: {}", err);
+        };
+        assert!(
+            err.to_string().contains(&format!("saved as {}", id)),
+            "{}",
+            err
+        );
+        let saved = std::fs::read_to_string(journal.entry_path(id)).unwrap();
+        assert!(
+            saved.contains("\"method\": \"logseq.Editor.getBlock\""),
+            "{}",
+            saved
+        );
+        assert!(saved.contains("\"uuid\": 7"), "{}", saved);
+        assert!(!saved.contains("Call Sam"), "{}", saved);
+    }
+
+    #[tokio::test]
+    async fn test_request_timeout_is_reported() {
+        // A server that accepts connections but never answers
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let server = tokio::spawn(async move {
+            let mut held = Vec::new();
+            while let Ok((socket, _)) = listener.accept().await {
+                held.push(socket);
+            }
+        });
+
+        let timeouts = ClientTimeouts {
+            connect: Duration::from_secs(1),
+            request: Duration::from_millis(100),
+        };
+        let client = LogSeqClient::with_timeouts(&url, "test-token", timeouts).unwrap();
+        let err = client.get_all_pages().await.unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "LogSeq did not respond to logseq.Editor.getAllPages within 100ms"
+        );
+
+        server.abort();
+    }
+
+    #[tokio::test]
+    async fn test_requests_beyond_the_limit_queue() {
+        // A server that counts connections but never answers
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let accepted = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let counter = accepted.clone();
+        let server = tokio::spawn(async move {
+            let mut held = Vec::new();
+            while let Ok((socket, _)) = listener.accept().await {
+                counter.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+                held.push(socket);
+            }
+        });
+
+        let client = LogSeqClient::builder()
+            .url(&url)
+            .token("test-token")
+            .timeout(Duration::from_millis(300))
+            .max_concurrent_requests(2)
+            .build()
+            .unwrap();
+        let calls: Vec<_> = (0..5)
+            .map(|_| {
+                let client = client.clone();
+                tokio::spawn(async move { client.get_all_pages().await })
+            })
+            .collect();
+
+        tokio::time::sleep(Duration::from_millis(150)).await;
+        assert_eq!(accepted.load(std::sync::atomic::Ordering::SeqCst), 2);
+        for call in calls {
+            let err = call.await.unwrap().unwrap_err();
+            assert!(err.to_string().contains("did not respond"), "{}", err);
+        }
+        assert_eq!(accepted.load(std::sync::atomic::Ordering::SeqCst), 5);
+
+        server.abort();
     }
 
     #[test]
@@ -468,6 +2479,185 @@ mod tests {
         assert!(opts.properties.is_none());
     }
 
+    #[test]
+    fn test_move_block_options_serialization() {
+        let opts = MoveBlockOptions {
+            before: Some(true),
+            children: None,
+        };
+        let value = serde_json::to_value(&opts).unwrap();
+        assert_eq!(value["before"], Value::Bool(true));
+        assert!(value["children"].is_null());
+    }
+
+    #[test]
+    fn test_parse_app_search() {
+        let response = serde_json::json!({
+            "blocks": [
+                {"block/uuid": "b1", "block/content": "Learning Rust today", "block/page": 12},
+                {"block/uuid": "b2", "block/content": "x", "block/page": {"db/id": 7},
+                 "block/snippet": "about $pfts_2lqh>$rust$<pfts_2lqh$"},
+                {"block/content": "missing uuid is skipped"}
+            ],
+            "pages": ["rust"]
+        });
+
+        let results = parse_app_search(&response, "rust");
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].block.uuid, "b1");
+        assert_eq!(results[0].block.page.as_ref().map(|p| p.id), Some(12));
+        assert_eq!(
+            results[0].snippet.as_deref(),
+            Some("Learning **Rust** today")
+        );
+        assert_eq!(results[1].block.page.as_ref().map(|p| p.id), Some(7));
+        assert_eq!(results[1].snippet.as_deref(), Some("about **rust**"));
+        assert!(results.iter().all(|r| r.page_name.is_none()));
+
+        assert!(parse_app_search(&Value::Null, "rust").is_empty());
+    }
+
+    #[test]
+    fn test_decode_entries_skips_malformed_pages() {
+        let response = serde_json::json!([
+            {"name": "good", "uuid": "u1"},
+            {"name": "no uuid", "original-name": "No UUID"},
+            "not a page",
+            {"name": "also good", "uuid": "u2", "properties": null}
+        ]);
+
+        let pages: Lenient<Page> = decode_entries(response, "page").unwrap();
+        assert_eq!(
+            pages.iter().map(|p| p.name.as_str()).collect::<Vec<_>>(),
+            vec!["good", "also good"]
+        );
+        assert_eq!(pages.warnings.len(), 2);
+        assert!(
+            pages.warnings[0].starts_with("Skipped page 2 (\"No UUID\"): missing field `uuid`")
+        );
+        assert!(pages.warnings[1].starts_with("Skipped page 3:"));
+
+        assert!(
+            decode_entries::<Page>(Value::Null, "page")
+                .unwrap()
+                .is_empty()
+        );
+        assert!(decode_entries::<Page>(serde_json::json!({"error": "x"}), "page").is_err());
+    }
+
+    #[test]
+    fn test_decode_block_tree_skips_malformed_subtrees() {
+        let response = serde_json::json!([
+            {"uuid": "a", "content": "first", "children": [
+                {"uuid": "a1", "content": "kept child"},
+                {"uuid": "a2", "content": 42, "children": [{"uuid": "a2a", "content": "lost"}]}
+            ]},
+            {"content": "no uuid"},
+            {"uuid": "b", "content": "second"}
+        ]);
+
+        let tree = decode_block_tree(response).unwrap();
+        assert_eq!(tree.len(), 2);
+        assert_eq!(tree[0].children.len(), 1);
+        assert_eq!(tree[0].children[0].uuid, "a1");
+        assert_eq!(tree[1].uuid, "b");
+        assert_eq!(tree.warnings.len(), 2);
+        assert!(
+            tree.warnings[0].starts_with("Skipped block 1.2 (\"a2\") and its 1 child block(s):")
+        );
+        assert!(tree.warnings[1].starts_with("Skipped block 2: missing field `uuid`"));
+    }
+
+    #[test]
+    fn test_parse_block_location() {
+        let nested =
+            serde_json::json!([["projects", "6523a1b2-0000-4000-8000-000000000001", false]]);
+        assert_eq!(
+            parse_block_location(&nested),
+            Some(BlockLocation {
+                page: "projects".into(),
+                parent: Some("6523a1b2-0000-4000-8000-000000000001".into()),
+            })
+        );
+
+        let top = serde_json::json!([["projects", "6523a1b2-0000-4000-8000-00000000000f", true]]);
+        assert_eq!(parse_block_location(&top).unwrap().parent, None);
+        assert_eq!(parse_block_location(&serde_json::json!([])), None);
+        assert_eq!(parse_block_location(&Value::Null), None);
+    }
+
+    #[test]
+    fn test_edn_string() {
+        assert_eq!(edn_string("My Page"), "\"My Page\"");
+        assert_eq!(edn_string(r#"say "hi" \o/"#), r#""say \"hi\" \\o/""#);
+    }
+
+    #[test]
+    fn test_todo_query() {
+        let query = todo_query(&TodoFilter::default());
+        assert!(
+            query.contains(r#"[(contains? #{"NOW" "DOING" "TODO" "LATER" "WAITING"} ?marker)]"#)
+        );
+        assert!(!query.contains("?t"));
+
+        let query = todo_query(&TodoFilter {
+            page: Some("Work".into()),
+            tag: Some("Urgent".into()),
+            marker: Some("NOW".into()),
+            priority: Some("A".into()),
+        });
+        assert!(query.contains(r#"[(contains? #{"NOW"} ?marker)]"#));
+        assert!(query.contains(r#"[?p :block/name "work"]"#));
+        assert!(query.contains(r#"[?t :block/name "urgent"] [?b :block/refs ?t]"#));
+        assert!(query.contains(r#"[?b :block/priority "A"]"#));
+    }
+
+    #[test]
+    fn test_page_aliases() {
+        let page = |alias: Value| Page {
+            name: "project alpha".to_string(),
+            uuid: "page-uuid".to_string(),
+            original_name: Some("Project Alpha".to_string()),
+            properties: Some(HashMap::from([("alias".to_string(), alias)])),
+            updated_at: None,
+        };
+        assert_eq!(
+            page(serde_json::json!("[[Apollo]], PA")).aliases(),
+            vec!["Apollo", "PA"]
+        );
+        assert_eq!(
+            page(serde_json::json!(["apollo"])).aliases(),
+            vec!["apollo"]
+        );
+        assert_eq!(
+            page(serde_json::json!(["apollo"])).display_name(),
+            "Project Alpha"
+        );
+    }
+
+    #[test]
+    fn test_search_content_query() {
+        let query =
+            search_content_query("Say \"Hi there\" from C:\\Temp", &SearchOptions::default());
+        assert!(query.contains("[(clojure.string/lower-case ?content) ?lower]"));
+        assert!(query.contains("[(clojure.string/includes? ?lower \"say\")]"));
+        assert!(query.contains("[(clojure.string/includes? ?lower \"hi there\")]"));
+        assert!(query.contains("[(clojure.string/includes? ?lower \"c:\\\\temp\")]"));
+        assert!(!query.contains("\"from\""));
+        assert!(!query.contains(":block/name"));
+
+        let options = SearchOptions {
+            case_sensitive: true,
+            limit: Some(5),
+            page: Some("Projects/Alpha".to_string()),
+        };
+        let query = search_content_query("Rust and Tokio", &options);
+        assert!(query.contains("[(clojure.string/includes? ?content \"Rust\")]"));
+        assert!(query.contains("[(clojure.string/includes? ?content \"Tokio\")]"));
+        assert!(!query.contains("\"and\""));
+        assert!(query.contains("[?p :block/name \"projects/alpha\"]"));
+    }
+
     #[test]
     fn test_block_structure() {
         // Test that we can create block structures correctly
@@ -486,4 +2676,32 @@ mod tests {
         assert_eq!(block.level, Some(1));
         assert!(block.children.is_empty());
     }
+
+    fn todo(uuid: &str, page: &str, priority: Option<&str>, scheduled: Option<u32>) -> TodoItem {
+        TodoItem {
+            uuid: uuid.to_string(),
+            content: format!("TODO {}", uuid),
+            marker: "TODO".to_string(),
+            page_name: page.to_string(),
+            priority: priority.map(String::from),
+            scheduled,
+            tags: vec![],
+        }
+    }
+
+    #[test]
+    fn test_todo_report_order() {
+        let mut todos = [
+            todo("1", "b", None, None),
+            todo("2", "a", None, Some(20240301)),
+            todo("3", "z", Some("B"), None),
+            todo("4", "c", Some("A"), Some(20240501)),
+            todo("5", "a", Some("A"), Some(20240101)),
+            todo("6", "a", None, None),
+        ];
+        todos.sort_by(TodoItem::report_order);
+
+        let order: Vec<&str> = todos.iter().map(|t| t.uuid.as_str()).collect();
+        assert_eq!(order, vec!["5", "4", "3", "2", "6", "1"]);
+    }
 }
@@ -0,0 +1,349 @@
+//! Links to files in a graph's `assets/` folder
+//!
+//! Blocks link to images and other files the graph keeps relative to the
+//! page file, as in `![diagram](../assets/diagram_1700000000.png)`, which
+//! means nothing outside LogSeq. Given the graph's folder, as reported by
+//! `getCurrentGraph`, these links can be rewritten to `file://` URLs, and
+//! the files they name served to clients that display them.
+
+use std::io;
+use std::ops::Range;
+use std::path::{Component, Path, PathBuf};
+use thiserror::Error;
+
+/// The largest asset served whole
+pub const MAX_ASSET_BYTES: u64 = 10 * 1024 * 1024;
+
+/// A file in a graph's assets folder
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AssetFile {
+    pub path: PathBuf,
+    /// The path within the graph folder, such as `assets/diagram.png`
+    pub relative: String,
+    pub size: u64,
+}
+
+#[derive(Debug, Error)]
+pub enum AssetError {
+    #[error("{0} is not a file in the graph's assets folder")]
+    NotAnAsset(String),
+    #[error("{path} is {size} bytes, more than the {max} bytes served")]
+    TooLarge { path: String, size: u64, max: u64 },
+    #[error(transparent)]
+    Io(#[from] io::Error),
+}
+
+/// A markdown link or image, `[text](target)` or `![alt](target)`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Link<'a> {
+    pub image: bool,
+    pub text: &'a str,
+    pub target: &'a str,
+    /// Where the target sits in the text it was found in
+    pub target_range: Range<usize>,
+}
+
+/// Markdown links and images in `text`, in order. Page references like
+/// `[[page]]` aren't links.
+pub fn links(text: &str) -> Vec<Link<'_>> {
+    let mut links = Vec::new();
+    let mut pos = 0;
+    while let Some(offset) = text[pos..].find('[') {
+        let open = pos + offset;
+        pos = open + 1;
+        let Some(offset) = text[open..].find("](") else {
+            break;
+        };
+        let middle = open + offset;
+        let link_text = &text[open + 1..middle];
+        if link_text.contains(['[', ']', '\n']) {
+            continue;
+        }
+        let start = middle + 2;
+        let Some(offset) = text[start..].find(')') else {
+            break;
+        };
+        let end = start + offset;
+        if text[start..end].contains('\n') {
+            continue;
+        }
+        links.push(Link {
+            image: text[..open].ends_with('!'),
+            text: link_text,
+            target: &text[start..end],
+            target_range: start..end,
+        });
+        pos = end + 1;
+    }
+    links
+}
+
+/// The file a link target such as `../assets/image.png` names in the graph
+/// folder, or `None` if it isn't an asset
+pub fn asset_file(graph_dir: &Path, target: &str) -> Option<PathBuf> {
+    let target = target.trim();
+    let relative = ["../assets/", "./assets/", "assets/"]
+        .iter()
+        .find_map(|prefix| target.strip_prefix(prefix))?;
+    let relative = Path::new(relative);
+    // Don't let a link reach outside the assets folder
+    if relative
+        .components()
+        .any(|c| !matches!(c, Component::Normal(_)))
+    {
+        return None;
+    }
+    Some(graph_dir.join("assets").join(relative))
+}
+
+/// A `file://` URL for an absolute path
+pub fn file_url(path: &Path) -> String {
+    let path = path.to_string_lossy().replace('\\', "/");
+    let mut url = String::from("file://");
+    if !path.starts_with('/') {
+        url.push('/');
+    }
+    for byte in path.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' | b'/' | b':' => {
+                url.push(byte as char)
+            }
+            _ => url.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    url
+}
+
+/// `text` with every link to an asset pointing at its file in `graph_dir`
+pub fn rewrite_asset_links(text: &str, graph_dir: &Path) -> String {
+    let mut rewritten = String::with_capacity(text.len());
+    let mut copied = 0;
+    for link in links(text) {
+        if let Some(file) = asset_file(graph_dir, link.target) {
+            rewritten.push_str(&text[copied..link.target_range.start]);
+            rewritten.push_str(&file_url(&file));
+            copied = link.target_range.end;
+        }
+    }
+    rewritten.push_str(&text[copied..]);
+    rewritten
+}
+
+/// The files in a graph's assets folder and the folders within it, by
+/// relative path. Hidden files are left out, and a graph without an assets
+/// folder has no assets.
+pub fn list_assets(graph_dir: &Path) -> io::Result<Vec<AssetFile>> {
+    let mut assets = Vec::new();
+    let mut dirs = vec![graph_dir.join("assets")];
+    while let Some(dir) = dirs.pop() {
+        let entries = match std::fs::read_dir(&dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => continue,
+            Err(e) => return Err(e),
+        };
+        for entry in entries {
+            let entry = entry?;
+            if entry.file_name().to_string_lossy().starts_with('.') {
+                continue;
+            }
+            let metadata = entry.metadata()?;
+            let path = entry.path();
+            if metadata.is_dir() {
+                dirs.push(path);
+            } else if metadata.is_file() {
+                let relative = path.strip_prefix(graph_dir).unwrap_or(&path);
+                assets.push(AssetFile {
+                    relative: relative.to_string_lossy().replace('\\', "/"),
+                    path,
+                    size: metadata.len(),
+                });
+            }
+        }
+    }
+    assets.sort_by(|a, b| a.relative.cmp(&b.relative));
+    Ok(assets)
+}
+
+/// The path a `file://` URL names
+pub fn url_file(url: &str) -> Option<PathBuf> {
+    let encoded = url.strip_prefix("file://")?.as_bytes();
+    let mut bytes = Vec::with_capacity(encoded.len());
+    let mut i = 0;
+    while i < encoded.len() {
+        let escaped = (encoded[i] == b'%')
+            .then(|| encoded.get(i + 1..i + 3))
+            .flatten()
+            .and_then(|hex| u8::from_str_radix(std::str::from_utf8(hex).ok()?, 16).ok());
+        match escaped {
+            Some(byte) => {
+                bytes.push(byte);
+                i += 3;
+            }
+            None => {
+                bytes.push(encoded[i]);
+                i += 1;
+            }
+        }
+    }
+    let path = String::from_utf8(bytes).ok()?;
+    // `file:///C:/Graph` names `C:/Graph`
+    let path = match path.as_bytes() {
+        [b'/', drive, b':', ..] if drive.is_ascii_alphabetic() => &path[1..],
+        _ => &path,
+    };
+    Some(PathBuf::from(path))
+}
+
+/// The file a `file://` URL names, provided it's in the graph's assets
+/// folder and no larger than `max_bytes`
+pub fn asset_for_url(graph_dir: &Path, url: &str, max_bytes: u64) -> Result<AssetFile, AssetError> {
+    let not_an_asset = || AssetError::NotAnAsset(url.to_string());
+    let path = url_file(url).ok_or_else(not_an_asset)?;
+    let assets_dir = graph_dir
+        .join("assets")
+        .canonicalize()
+        .map_err(|_| not_an_asset())?;
+    // Resolving links and `..` stops a URL reaching outside the folder
+    let path = path.canonicalize().map_err(|_| not_an_asset())?;
+    let Ok(within) = path.strip_prefix(&assets_dir) else {
+        return Err(not_an_asset());
+    };
+    let metadata = std::fs::metadata(&path)?;
+    if !metadata.is_file() {
+        return Err(not_an_asset());
+    }
+    if metadata.len() > max_bytes {
+        return Err(AssetError::TooLarge {
+            path: path.display().to_string(),
+            size: metadata.len(),
+            max: max_bytes,
+        });
+    }
+    Ok(AssetFile {
+        relative: format!("assets/{}", within.to_string_lossy().replace('\\', "/")),
+        path,
+        size: metadata.len(),
+    })
+}
+
+/// The MIME type of an asset, judged by its extension
+pub fn mime_type(path: &Path) -> &'static str {
+    if let Some(image) = image_mime_type(path) {
+        return image;
+    }
+    let extension = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(str::to_lowercase)
+        .unwrap_or_default();
+    match extension.as_str() {
+        "pdf" => "application/pdf",
+        "md" | "markdown" => "text/markdown",
+        "org" => "text/org",
+        "txt" | "log" => "text/plain",
+        "csv" => "text/csv",
+        "html" | "htm" => "text/html",
+        "json" => "application/json",
+        "mp3" => "audio/mpeg",
+        "wav" => "audio/wav",
+        "mp4" => "video/mp4",
+        "webm" => "video/webm",
+        "zip" => "application/zip",
+        _ => "application/octet-stream",
+    }
+}
+
+/// The MIME type of an image file, judged by its extension
+pub fn image_mime_type(path: &Path) -> Option<&'static str> {
+    let extension = path.extension()?.to_str()?.to_lowercase();
+    Some(match extension.as_str() {
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        "svg" => "image/svg+xml",
+        "bmp" => "image/bmp",
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_links_skip_page_references() {
+        let text = "See [[Page]] and ![chart](../assets/chart.png){:height 200}\n[notes](https://example.com)";
+        let found = links(text);
+        assert_eq!(found.len(), 2);
+        assert!(found[0].image);
+        assert_eq!(found[0].text, "chart");
+        assert_eq!(found[0].target, "../assets/chart.png");
+        assert!(!found[1].image);
+        assert_eq!(&text[found[1].target_range.clone()], "https://example.com");
+    }
+
+    #[test]
+    fn test_asset_links_are_rewritten_to_file_urls() {
+        let dir = Path::new("/home/me/My Graph");
+        let text = "![a](../assets/a b.png) [doc](../assets/doc.pdf) [web](https://x.org) ![up](../assets/../secret.png)";
+        assert_eq!(
+            rewrite_asset_links(text, dir),
+            "![a](file:///home/me/My%20Graph/assets/a%20b.png) [doc](file:///home/me/My%20Graph/assets/doc.pdf) [web](https://x.org) ![up](../assets/../secret.png)"
+        );
+        assert_eq!(
+            file_url(Path::new(r"C:\Graph\assets\a.png")),
+            "file:///C:/Graph/assets/a.png"
+        );
+        assert_eq!(image_mime_type(Path::new("a.JPG")), Some("image/jpeg"));
+        assert_eq!(image_mime_type(Path::new("a.pdf")), None);
+        assert_eq!(mime_type(Path::new("a.pdf")), "application/pdf");
+    }
+
+    #[test]
+    fn test_assets_are_found_by_url_only_in_the_assets_folder() {
+        let dir = tempfile::tempdir().unwrap();
+        let graph = dir.path().join("My Graph");
+        std::fs::create_dir_all(graph.join("assets/scans")).unwrap();
+        std::fs::write(graph.join("assets/a b.png"), b"png").unwrap();
+        std::fs::write(graph.join("assets/scans/c.pdf"), b"pdf!").unwrap();
+        std::fs::write(graph.join("assets/.DS_Store"), b"").unwrap();
+        std::fs::write(graph.join("secret.md"), b"secret").unwrap();
+
+        let assets = list_assets(&graph).unwrap();
+        let listed: Vec<(&str, u64)> = assets
+            .iter()
+            .map(|a| (a.relative.as_str(), a.size))
+            .collect();
+        assert_eq!(listed, [("assets/a b.png", 3), ("assets/scans/c.pdf", 4)]);
+
+        let url = file_url(&graph.join("assets/a b.png"));
+        assert_eq!(url_file(&url).unwrap(), graph.join("assets/a b.png"));
+        assert_eq!(
+            asset_for_url(&graph, &url, MAX_ASSET_BYTES)
+                .unwrap()
+                .relative,
+            "assets/a b.png"
+        );
+        assert!(matches!(
+            asset_for_url(&graph, &url, 2),
+            Err(AssetError::TooLarge { size: 3, .. })
+        ));
+        for outside in [
+            file_url(&graph.join("secret.md")),
+            file_url(&graph.join("assets/../secret.md")),
+            file_url(&graph.join("assets/missing.png")),
+            "https://example.com/a.png".to_string(),
+        ] {
+            assert!(
+                matches!(
+                    asset_for_url(&graph, &outside, MAX_ASSET_BYTES),
+                    Err(AssetError::NotAnAsset(_))
+                ),
+                "{}",
+                outside
+            );
+        }
+        assert!(list_assets(dir.path()).unwrap().is_empty());
+    }
+}
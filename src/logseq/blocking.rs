@@ -0,0 +1,146 @@
+//! A blocking wrapper around [`LogSeqClient`], for code that isn't async
+//!
+//! Each call runs the async client to completion on a small runtime the
+//! wrapper owns, so scripts and tools without their own async runtime can
+//! use the client directly. Calls must not be made from within an async
+//! runtime, as blocking there would stall it; use the async client instead.
+//!
+//! The common page and block calls are wrapped here. Anything else can be
+//! reached through [`LogSeqClient::block_on`] and [`LogSeqClient::inner`].
+
+use crate::logseq::api::{
+    self, Block, CreatePageOptions, InsertBlockOptions, Lenient, LogSeqClientBuilder, Page,
+    SearchOptions, SearchResult,
+};
+use anyhow::Result;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::future::Future;
+use tokio::runtime::Runtime;
+
+/// A LogSeq API client whose calls block until they finish
+pub struct LogSeqClient {
+    inner: api::LogSeqClient,
+    runtime: Runtime,
+}
+
+impl LogSeqClient {
+    /// Build a client, configured as for the async one
+    pub fn build(builder: LogSeqClientBuilder) -> Result<Self> {
+        Self::from_async(builder.build()?)
+    }
+
+    pub fn new(base_url: &str, token: &str) -> Result<Self> {
+        Self::from_async(api::LogSeqClient::new(base_url, token)?)
+    }
+
+    /// Wrap an async client
+    pub fn from_async(inner: api::LogSeqClient) -> Result<Self> {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()?;
+        Ok(Self { inner, runtime })
+    }
+
+    /// The async client, for calls this wrapper doesn't cover, which
+    /// [`block_on`](Self::block_on) can run:
+    /// `client.block_on(client.inner().get_all_tags())`
+    pub fn inner(&self) -> &api::LogSeqClient {
+        &self.inner
+    }
+
+    /// Run a future, such as a call on [`inner`](Self::inner), to completion
+    pub fn block_on<F: Future>(&self, future: F) -> F::Output {
+        self.runtime.block_on(future)
+    }
+
+    pub fn get_all_pages(&self) -> Result<Lenient<Page>> {
+        self.block_on(self.inner.get_all_pages())
+    }
+
+    pub fn get_page(&self, name_or_uuid: &str) -> Result<Page> {
+        self.block_on(self.inner.get_page(name_or_uuid))
+    }
+
+    /// Look up a page, returning `None` when LogSeq has no such page
+    pub fn find_page(&self, name_or_uuid: &str) -> Result<Option<Page>> {
+        self.block_on(self.inner.find_page(name_or_uuid))
+    }
+
+    pub fn create_page(
+        &self,
+        name: &str,
+        properties: Option<HashMap<String, Value>>,
+        opts: CreatePageOptions,
+    ) -> Result<Page> {
+        self.block_on(self.inner.create_page(name, properties, opts))
+    }
+
+    pub fn delete_page(&self, page_name: &str) -> Result<()> {
+        self.block_on(self.inner.delete_page(page_name))
+    }
+
+    pub fn get_page_blocks_tree(&self, page_name_or_uuid: &str) -> Result<Lenient<Block>> {
+        self.block_on(self.inner.get_page_blocks_tree(page_name_or_uuid))
+    }
+
+    pub fn get_block(&self, uuid: &str) -> Result<Block> {
+        self.block_on(self.inner.get_block(uuid))
+    }
+
+    pub fn append_block_in_page(&self, page: &str, content: &str) -> Result<Block> {
+        self.block_on(self.inner.append_block_in_page(page, content))
+    }
+
+    pub fn insert_block(&self, content: &str, opts: InsertBlockOptions) -> Result<Block> {
+        self.block_on(self.inner.insert_block(content, opts))
+    }
+
+    pub fn update_block(
+        &self,
+        uuid: &str,
+        content: &str,
+        properties: Option<HashMap<String, Value>>,
+    ) -> Result<Block> {
+        self.block_on(self.inner.update_block(uuid, content, properties))
+    }
+
+    pub fn remove_block(&self, block_uuid: &str) -> Result<()> {
+        self.block_on(self.inner.remove_block(block_uuid))
+    }
+
+    pub fn search(&self, query: &str, options: &SearchOptions) -> Result<Vec<SearchResult>> {
+        self.block_on(self.inner.search(query, options))
+    }
+
+    pub fn datascript_query(&self, query: &str, inputs: &[Value]) -> Result<Value> {
+        self.block_on(self.inner.datascript_query(query, inputs))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::logseq::mock::{MockGraph, MockLogSeq};
+
+    #[test]
+    fn test_blocking_calls_reach_logseq() {
+        // The mock needs a runtime of its own to keep serving between calls
+        let server = tokio::runtime::Runtime::new().unwrap();
+        let mut graph = MockGraph::new();
+        graph.add_page("Projects");
+        let logseq = server.block_on(MockLogSeq::serve(graph)).unwrap();
+        let client = LogSeqClient::from_async(logseq.client()).unwrap();
+
+        assert_eq!(client.get_page("projects").unwrap().name, "projects");
+        let block = client.append_block_in_page("Projects", "Plan").unwrap();
+        client.update_block(&block.uuid, "Plan it", None).unwrap();
+        let blocks = client.get_page_blocks_tree("Projects").unwrap();
+        assert_eq!(blocks.items[0].content, "Plan it");
+        client.remove_block(&block.uuid).unwrap();
+        assert!(client.get_page_blocks_tree("Projects").unwrap().is_empty());
+
+        let tags = client.block_on(client.inner().get_all_tags()).unwrap();
+        assert!(tags.is_empty());
+    }
+}
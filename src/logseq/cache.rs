@@ -0,0 +1,367 @@
+//! In-memory cache of slow-changing graph data (page list, tags, user
+//! configs, current graph)
+//!
+//! By default each value is reused for a short time, so an agent listing
+//! pages several times in one conversation only fetches them once. A warm
+//! cache is instead prefetched at startup and refreshed in the background so
+//! the first list/search calls of a session don't have to wait on the API.
+//! With a zero TTL every read goes straight to LogSeq.
+//!
+//! Datascript query results are cached separately in a [`QueryCache`] for a
+//! short time, as agents often repeat the same exploratory query within a
+//! conversation.
+
+use crate::logseq::api::{Lenient, LogSeqClient, Page, TagUsage, preferred_date_format};
+use anyhow::Result;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{Arc, Mutex, RwLock, Weak};
+use std::time::{Duration, Instant};
+use tokio::task::JoinHandle;
+
+/// How long a datascript query result is reused unless the server is
+/// configured otherwise
+pub const DEFAULT_QUERY_TTL: Duration = Duration::from_secs(30);
+
+/// How long the page list, tags, configs, and current graph are reused
+/// unless the server is configured otherwise
+pub const DEFAULT_GRAPH_TTL: Duration = Duration::from_secs(60);
+
+/// Most query results kept at once; the oldest is dropped to make room
+const MAX_CACHED_QUERIES: usize = 100;
+
+/// A single cached value and when it was stored
+struct Slot<T> {
+    value: RwLock<Option<(Instant, Arc<T>)>>,
+}
+
+impl<T> Default for Slot<T> {
+    fn default() -> Self {
+        Self {
+            value: RwLock::new(None),
+        }
+    }
+}
+
+impl<T> Slot<T> {
+    /// The cached value, if it was stored less than `ttl` ago
+    fn get(&self, ttl: Duration) -> Option<Arc<T>> {
+        let value = self.value.read().unwrap_or_else(|e| e.into_inner());
+        value
+            .as_ref()
+            .filter(|(stored, _)| stored.elapsed() < ttl)
+            .map(|(_, value)| value.clone())
+    }
+
+    fn set(&self, value: Arc<T>) {
+        *self.value.write().unwrap_or_else(|e| e.into_inner()) = Some((Instant::now(), value));
+    }
+
+    fn clear(&self) {
+        *self.value.write().unwrap_or_else(|e| e.into_inner()) = None;
+    }
+
+    /// Return the cached value if it's younger than `ttl`, otherwise fetch
+    /// it (and, unless `ttl` is zero, cache it)
+    async fn get_or_fetch<F, Fut>(&self, ttl: Duration, fetch: F) -> Result<Arc<T>>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        if let Some(value) = self.get(ttl) {
+            return Ok(value);
+        }
+        let value = Arc::new(fetch().await?);
+        if !ttl.is_zero() {
+            self.set(value.clone());
+        }
+        Ok(value)
+    }
+}
+
+pub struct GraphCache {
+    client: Arc<LogSeqClient>,
+    /// How long a value is reused; zero disables the cache and
+    /// [`Duration::MAX`] keeps values until they're refreshed or invalidated
+    ttl: Duration,
+    pages: Slot<Lenient<Page>>,
+    tags: Slot<Vec<String>>,
+    tag_usage: Slot<Vec<TagUsage>>,
+    configs: Slot<Value>,
+    graph: Slot<Value>,
+}
+
+impl GraphCache {
+    pub fn new(client: Arc<LogSeqClient>, ttl: Duration) -> Self {
+        Self {
+            client,
+            ttl,
+            pages: Slot::default(),
+            tags: Slot::default(),
+            tag_usage: Slot::default(),
+            configs: Slot::default(),
+            graph: Slot::default(),
+        }
+    }
+
+    pub async fn pages(&self) -> Result<Arc<Lenient<Page>>> {
+        self.pages
+            .get_or_fetch(self.ttl, || self.client.get_all_pages())
+            .await
+    }
+
+    pub async fn tags(&self) -> Result<Arc<Vec<String>>> {
+        self.tags
+            .get_or_fetch(self.ttl, || self.client.get_all_tags())
+            .await
+    }
+
+    /// Which tags each page and block sets
+    pub async fn tag_usage(&self) -> Result<Arc<Vec<TagUsage>>> {
+        self.tag_usage
+            .get_or_fetch(self.ttl, || self.client.get_tag_usage())
+            .await
+    }
+
+    pub async fn user_configs(&self) -> Result<Arc<Value>> {
+        self.configs
+            .get_or_fetch(self.ttl, || self.client.get_user_configs())
+            .await
+    }
+
+    /// Name, path, and settings of the graph open in LogSeq
+    pub async fn current_graph(&self) -> Result<Arc<Value>> {
+        self.graph
+            .get_or_fetch(self.ttl, || self.client.get_current_graph())
+            .await
+    }
+
+    /// The user's journal title format, falling back to LogSeq's default
+    pub async fn preferred_date_format(&self) -> Result<String> {
+        Ok(preferred_date_format(&*self.user_configs().await?))
+    }
+
+    /// Forget the page and tag lists after this server creates or deletes a page
+    pub fn invalidate_pages(&self) {
+        self.pages.clear();
+        self.tags.clear();
+        self.tag_usage.clear();
+    }
+
+    /// Forget everything, so the next read of each value goes to LogSeq
+    pub fn clear(&self) {
+        self.invalidate_pages();
+        self.configs.clear();
+        self.graph.clear();
+    }
+
+    /// Refetch everything. Failures are logged and leave the previous value
+    /// in place.
+    pub async fn refresh(&self) {
+        let (pages, tags, tag_usage, configs, graph) = tokio::join!(
+            self.client.get_all_pages(),
+            self.client.get_all_tags(),
+            self.client.get_tag_usage(),
+            self.client.get_user_configs(),
+            self.client.get_current_graph(),
+        );
+        store_refreshed(&self.pages, "page list", pages);
+        store_refreshed(&self.tags, "tag list", tags);
+        store_refreshed(&self.tag_usage, "tag usage", tag_usage);
+        store_refreshed(&self.configs, "user configs", configs);
+        store_refreshed(&self.graph, "current graph", graph);
+    }
+
+    /// Prefetch now and then refresh every `interval` until the cache is
+    /// dropped. Does nothing when the cache is disabled.
+    pub fn spawn_refresh(self: &Arc<Self>, interval: Duration) -> Option<JoinHandle<()>> {
+        if self.ttl.is_zero() {
+            return None;
+        }
+
+        let cache: Weak<Self> = Arc::downgrade(self);
+        Some(tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            loop {
+                ticker.tick().await;
+                let Some(cache) = cache.upgrade() else {
+                    break;
+                };
+                cache.refresh().await;
+                tracing::debug!("Graph cache refreshed");
+            }
+        }))
+    }
+}
+
+/// Datascript query results keyed on the query text and its inputs, each
+/// reused for `ttl`. Anything that writes to the graph should [`clear`] it.
+///
+/// [`clear`]: QueryCache::clear
+pub struct QueryCache {
+    ttl: Duration,
+    entries: Mutex<HashMap<String, (Instant, Arc<Value>)>>,
+}
+
+impl Default for QueryCache {
+    fn default() -> Self {
+        Self::new(DEFAULT_QUERY_TTL)
+    }
+}
+
+impl QueryCache {
+    /// A cache keeping results for `ttl`; a zero `ttl` disables it
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            entries: Mutex::default(),
+        }
+    }
+
+    /// The result of an identical query run within the TTL, and how long ago
+    /// it ran
+    pub fn get(&self, query: &str, inputs: &[Value]) -> Option<(Arc<Value>, Duration)> {
+        let key = query_key(query, inputs);
+        let mut entries = self.lock();
+        let (stored, value) = entries.get(&key)?;
+        let age = stored.elapsed();
+        if age < self.ttl {
+            return Some((value.clone(), age));
+        }
+        entries.remove(&key);
+        None
+    }
+
+    pub fn insert(&self, query: &str, inputs: &[Value], value: Arc<Value>) {
+        if self.ttl.is_zero() {
+            return;
+        }
+        let mut entries = self.lock();
+        if entries.len() >= MAX_CACHED_QUERIES {
+            entries.retain(|_, (stored, _)| stored.elapsed() < self.ttl);
+        }
+        if entries.len() >= MAX_CACHED_QUERIES
+            && let Some(oldest) = entries
+                .iter()
+                .min_by_key(|(_, (stored, _))| *stored)
+                .map(|(key, _)| key.clone())
+        {
+            entries.remove(&oldest);
+        }
+        entries.insert(query_key(query, inputs), (Instant::now(), value));
+    }
+
+    /// Forget every result, after something may have changed the graph
+    pub fn clear(&self) {
+        self.lock().clear();
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<String, (Instant, Arc<Value>)>> {
+        self.entries.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+fn query_key(query: &str, inputs: &[Value]) -> String {
+    format!("{}\n{}", query, Value::from(inputs))
+}
+
+fn store_refreshed<T>(slot: &Slot<T>, what: &str, result: Result<T>) {
+    match result {
+        Ok(value) => slot.set(Arc::new(value)),
+        Err(e) => tracing::warn!("Failed to refresh cached {}: {}", what, e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    async fn fetch_counted(slot: &Slot<u32>, ttl: Duration, calls: &AtomicUsize) -> u32 {
+        *slot
+            .get_or_fetch(ttl, || async {
+                Ok(calls.fetch_add(1, Ordering::SeqCst) as u32)
+            })
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_enabled_slot_fetches_once() {
+        let slot = Slot::default();
+        let calls = AtomicUsize::new(0);
+
+        assert_eq!(fetch_counted(&slot, Duration::MAX, &calls).await, 0);
+        assert_eq!(fetch_counted(&slot, Duration::MAX, &calls).await, 0);
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+        slot.clear();
+        assert_eq!(fetch_counted(&slot, Duration::MAX, &calls).await, 1);
+    }
+
+    #[tokio::test]
+    async fn test_slot_refetches_after_ttl() {
+        let slot = Slot::default();
+        let calls = AtomicUsize::new(0);
+        let ttl = Duration::from_millis(20);
+
+        assert_eq!(fetch_counted(&slot, ttl, &calls).await, 0);
+        assert_eq!(fetch_counted(&slot, ttl, &calls).await, 0);
+        tokio::time::sleep(ttl).await;
+        assert_eq!(fetch_counted(&slot, ttl, &calls).await, 1);
+    }
+
+    #[tokio::test]
+    async fn test_disabled_slot_always_fetches() {
+        let slot = Slot::default();
+        let calls = AtomicUsize::new(0);
+
+        fetch_counted(&slot, Duration::ZERO, &calls).await;
+        fetch_counted(&slot, Duration::ZERO, &calls).await;
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+        assert!(slot.get(Duration::MAX).is_none());
+    }
+
+    #[test]
+    fn test_query_cache_matches_query_and_inputs() {
+        let cache = QueryCache::default();
+        let inputs = [Value::from("\"inbox\"")];
+        cache.insert("[:find ?b]", &inputs, Arc::new(Value::from(1)));
+
+        let (value, _) = cache.get("[:find ?b]", &inputs).unwrap();
+        assert_eq!(*value, Value::from(1));
+        assert!(cache.get("[:find ?b]", &[]).is_none());
+        assert!(cache.get("[:find ?c]", &inputs).is_none());
+
+        cache.clear();
+        assert!(cache.get("[:find ?b]", &inputs).is_none());
+    }
+
+    #[test]
+    fn test_query_cache_expires_and_stays_bounded() {
+        let cache = QueryCache::new(Duration::ZERO);
+        cache.insert("q", &[], Arc::new(Value::Null));
+        assert!(cache.get("q", &[]).is_none());
+
+        let cache = QueryCache::default();
+        for i in 0..=MAX_CACHED_QUERIES {
+            cache.insert(&i.to_string(), &[], Arc::new(Value::Null));
+        }
+        assert_eq!(cache.lock().len(), MAX_CACHED_QUERIES);
+        assert!(cache.get(&MAX_CACHED_QUERIES.to_string(), &[]).is_some());
+    }
+
+    #[tokio::test]
+    async fn test_failed_refresh_keeps_previous_value() {
+        let slot = Slot::default();
+        slot.set(Arc::new(7));
+
+        store_refreshed(&slot, "test", Err(anyhow::anyhow!("offline")));
+        assert_eq!(slot.get(Duration::MAX).as_deref(), Some(&7));
+
+        store_refreshed(&slot, "test", Ok(8));
+        assert_eq!(slot.get(Duration::MAX).as_deref(), Some(&8));
+    }
+}
@@ -0,0 +1,118 @@
+//! Cheap, stable checksums of block content
+//!
+//! 64-bit FNV-1a: not collision-resistant against someone trying, but fast
+//! and the same across runs and platforms, unlike `DefaultHasher`, so
+//! checksums can be stored and compared later.
+
+use crate::logseq::api::Block;
+use serde::Serialize;
+use std::hash::Hasher;
+
+const OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+const PRIME: u64 = 0x100000001b3;
+
+/// A 64-bit FNV-1a hasher
+#[derive(Debug, Clone, Copy)]
+pub struct Fnv1a(u64);
+
+impl Default for Fnv1a {
+    fn default() -> Self {
+        Self(OFFSET_BASIS)
+    }
+}
+
+impl Hasher for Fnv1a {
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 = (self.0 ^ *byte as u64).wrapping_mul(PRIME);
+        }
+    }
+
+    fn finish(&self) -> u64 {
+        self.0
+    }
+}
+
+/// The checksum of `text`, as 16 hex digits
+pub fn checksum(text: &str) -> String {
+    let mut hasher = Fnv1a::default();
+    hasher.write(text.as_bytes());
+    format!("{:016x}", hasher.finish())
+}
+
+/// One block's checksum, and where it sits in its page
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct BlockChecksum {
+    pub uuid: String,
+    /// The parent block's UUID, or `None` for a top-level block
+    pub parent: Option<String>,
+    /// Checksum of the block's own content, properties included
+    pub hash: String,
+}
+
+/// Checksums of every block in a tree, parents before their children, and
+/// a digest of the whole tree that changes when any block's content does or
+/// when blocks are added, removed, or moved
+pub fn tree_checksums(blocks: &[Block]) -> (String, Vec<BlockChecksum>) {
+    fn walk(blocks: &[Block], parent: Option<&str>, sums: &mut Vec<BlockChecksum>) {
+        for block in blocks {
+            sums.push(BlockChecksum {
+                uuid: block.uuid.clone(),
+                parent: parent.map(str::to_string),
+                hash: checksum(&block.content),
+            });
+            walk(&block.children, Some(&block.uuid), sums);
+        }
+    }
+    let mut sums = Vec::new();
+    walk(blocks, None, &mut sums);
+
+    let mut digest = Fnv1a::default();
+    for sum in &sums {
+        for field in [&sum.uuid, sum.parent.as_deref().unwrap_or(""), &sum.hash] {
+            digest.write(field.as_bytes());
+            // Separates fields, so moving text between them changes the digest
+            digest.write_u8(0);
+        }
+    }
+    (format!("{:016x}", digest.finish()), sums)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tree() -> Vec<Block> {
+        let mut parent = Block::new("a".into(), "Parent".into());
+        parent.children = vec![Block::new("b".into(), "Child".into())];
+        vec![parent, Block::new("c".into(), "Sibling".into())]
+    }
+
+    #[test]
+    fn test_checksums_are_stable() {
+        // Known FNV-1a values
+        assert_eq!(checksum(""), "cbf29ce484222325");
+        assert_eq!(checksum("a"), "af63dc4c8601ec8c");
+    }
+
+    #[test]
+    fn test_digest_changes_with_content_and_structure() {
+        let (digest, sums) = tree_checksums(&tree());
+        assert_eq!(sums.len(), 3);
+        assert_eq!(sums[1].parent.as_deref(), Some("a"));
+        assert_eq!(sums[1].hash, checksum("Child"));
+        assert_eq!(tree_checksums(&tree()).0, digest);
+
+        let mut edited = tree();
+        edited[0].children[0].content = "Child, edited".into();
+        assert_ne!(tree_checksums(&edited).0, digest);
+
+        // The same blocks with the child moved to the top level
+        let mut moved = tree();
+        let child = moved[0].children.pop().unwrap();
+        moved.insert(1, child);
+        let (moved_digest, moved_sums) = tree_checksums(&moved);
+        assert_ne!(moved_digest, digest);
+        assert_eq!(moved_sums[1].hash, sums[1].hash);
+    }
+}
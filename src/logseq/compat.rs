@@ -0,0 +1,153 @@
+//! API method names that differ between the LogSeq versions users run
+//!
+//! The client is written against the names current LogSeq releases use.
+//! Where a method went by another name in older releases, a [`Shim`] says
+//! which and until when, and the client sends the name that LogSeq
+//! understands. Tools never see the difference.
+//!
+//! The client asks LogSeq its version the first time it calls a method with
+//! a shim, and never for other methods. If LogSeq turns out not to know the
+//! name chosen, such as when its version couldn't be told, the other name
+//! is tried too.
+
+use std::fmt;
+
+/// A LogSeq release, such as `0.10.9`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Version {
+    pub major: u32,
+    pub minor: u32,
+    pub patch: u32,
+}
+
+impl Version {
+    pub const fn new(major: u32, minor: u32, patch: u32) -> Self {
+        Self {
+            major,
+            minor,
+            patch,
+        }
+    }
+
+    /// Parse a version as LogSeq reports it. Suffixes such as `-nightly`
+    /// and a missing patch number are allowed.
+    pub fn parse(text: &str) -> Option<Self> {
+        let text = text.trim().trim_start_matches('v');
+        let release = text.split(['-', '+', ' ']).next()?;
+        let mut parts = release.split('.');
+        let major = parts.next()?.parse().ok()?;
+        let minor = parts.next()?.parse().ok()?;
+        let patch = match parts.next() {
+            Some(patch) => patch.parse().ok()?,
+            None => 0,
+        };
+        Some(Self::new(major, minor, patch))
+    }
+}
+
+impl fmt::Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
+/// What the client knows about the LogSeq it's talking to
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Backend {
+    /// `None` if LogSeq didn't say, as releases before `logseq.App.getInfo`
+    /// don't
+    pub version: Option<Version>,
+}
+
+/// Another name for an API method, and until when LogSeq used it instead
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Shim {
+    /// The name the client calls the method by
+    pub method: &'static str,
+    /// The name LogSeq knows it by when the shim applies
+    pub alias: &'static str,
+    /// Applies to releases before this one
+    pub before: Version,
+}
+
+impl Shim {
+    /// Whether `backend` knows the method by its alias. An unknown version
+    /// is taken to be a current one.
+    pub fn applies(&self, backend: &Backend) -> bool {
+        backend.version.is_some_and(|version| version < self.before)
+    }
+}
+
+/// Methods known by other names in older LogSeq releases
+pub const SHIMS: &[Shim] = &[
+    // Messages moved to the `logseq.UI` namespace; `logseq.App.showMsg`
+    // lingered on as a deprecated alias for a while
+    Shim {
+        method: "logseq.UI.showMsg",
+        alias: "logseq.App.showMsg",
+        before: Version::new(0, 9, 0),
+    },
+];
+
+/// The shim for `method`, if it has one
+pub fn shim(method: &str) -> Option<&'static Shim> {
+    SHIMS.iter().find(|shim| shim.method == method)
+}
+
+/// The name to call `method` by on `backend`
+pub fn resolve<'a>(method: &'a str, backend: &Backend) -> &'a str {
+    match shim(method) {
+        Some(shim) if shim.applies(backend) => shim.alias,
+        _ => method,
+    }
+}
+
+/// The other name of a method with a shim, given either of its names
+pub fn other_name(name: &str) -> Option<&'static str> {
+    SHIMS.iter().find_map(|shim| {
+        if shim.method == name {
+            Some(shim.alias)
+        } else if shim.alias == name {
+            Some(shim.method)
+        } else {
+            None
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_versions() {
+        assert_eq!(Version::parse("0.10.9"), Some(Version::new(0, 10, 9)));
+        assert_eq!(
+            Version::parse("0.10.10-nightly.20250101"),
+            Some(Version::new(0, 10, 10))
+        );
+        assert_eq!(Version::parse("v0.8"), Some(Version::new(0, 8, 0)));
+        assert_eq!(Version::parse("nightly"), None);
+        assert!(Version::new(0, 10, 0) > Version::new(0, 9, 20));
+    }
+
+    #[test]
+    fn test_resolve_by_version() {
+        let backend = |version| Backend { version };
+        let old = backend(Version::parse("0.8.17"));
+        let current = backend(Version::parse("0.10.9"));
+        assert_eq!(resolve("logseq.UI.showMsg", &old), "logseq.App.showMsg");
+        assert_eq!(resolve("logseq.UI.showMsg", &current), "logseq.UI.showMsg");
+        assert_eq!(
+            resolve("logseq.UI.showMsg", &backend(None)),
+            "logseq.UI.showMsg"
+        );
+        assert_eq!(
+            resolve("logseq.Editor.getPage", &old),
+            "logseq.Editor.getPage"
+        );
+        assert_eq!(other_name("logseq.App.showMsg"), Some("logseq.UI.showMsg"));
+        assert_eq!(other_name("logseq.UI.showMsg"), Some("logseq.App.showMsg"));
+        assert_eq!(other_name("logseq.Editor.getPage"), None);
+    }
+}
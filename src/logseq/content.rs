@@ -0,0 +1,735 @@
+//! Helpers for parsing LogSeq block content
+
+/// Extract tags referenced in block content, in order of first appearance.
+///
+/// Recognises both `#tag` and `#[[multi word tag]]` forms. Tags are returned
+/// lowercased, matching LogSeq's case-insensitive page names.
+pub fn extract_tags(content: &str) -> Vec<String> {
+    let mut tags: Vec<String> = Vec::new();
+    let mut offset = 0;
+
+    while let Some(found) = content[offset..].find('#') {
+        let pos = offset + found;
+        // A tag must start the content or follow whitespace
+        let at_boundary = content[..pos]
+            .chars()
+            .next_back()
+            .is_none_or(char::is_whitespace);
+        let after = &content[pos + 1..];
+
+        let (tag, consumed) = if let Some(inner) = after.strip_prefix("[[") {
+            match inner.find("]]") {
+                Some(end) => (&inner[..end], end + 4),
+                None => ("", 0),
+            }
+        } else {
+            let end = after.find(is_tag_end).unwrap_or(after.len());
+            (&after[..end], end)
+        };
+
+        if at_boundary && !tag.is_empty() && !tag.starts_with('#') {
+            let tag = tag.to_lowercase();
+            if !tags.contains(&tag) {
+                tags.push(tag);
+            }
+        }
+        offset = pos + 1 + consumed;
+    }
+
+    tags
+}
+
+/// Pages linked from block content as `[[Page Name]]`, in order of first
+/// appearance and as written. `#[[tag]]` tags aren't included.
+pub fn extract_page_links(content: &str) -> Vec<String> {
+    let mut links: Vec<String> = Vec::new();
+    let mut offset = 0;
+    while let Some(found) = content[offset..].find("[[") {
+        let start = offset + found + 2;
+        let Some(end) = content[start..].find("]]") else {
+            break;
+        };
+        let link = content[start..start + end].trim();
+        let is_tag = content[..offset + found].ends_with('#');
+        if !is_tag
+            && !link.is_empty()
+            && !links
+                .iter()
+                .any(|seen| seen.to_lowercase() == link.to_lowercase())
+        {
+            links.push(link.to_string());
+        }
+        offset = start + end + 2;
+    }
+    links
+}
+
+/// Whether a character ends a `#tag`
+fn is_tag_end(c: char) -> bool {
+    c.is_whitespace() || matches!(c, ',' | '.' | '!' | '?' | ';')
+}
+
+/// The `#tag` form of a tag name, or `#[[multi word tag]]` when the name
+/// wouldn't survive as a bare tag
+pub fn format_tag(name: &str) -> String {
+    match name.contains(is_tag_end) || name.is_empty() {
+        true => format!("#[[{}]]", name),
+        false => format!("#{}", name),
+    }
+}
+
+/// Rewrite references to the tag `from` as references to `to`: `#from`,
+/// `#[[from]]`, `[[from]]`, and `from` in a `tags::` property. Names are
+/// matched ignoring case, as LogSeq's page names are.
+pub fn rename_tag(content: &str, from: &str, to: &str) -> String {
+    let from = from.to_lowercase();
+    content
+        .split('\n')
+        .map(|line| match rename_in_tags_property(line, &from, to) {
+            Some(line) => line,
+            None => rename_references(line, &from, to),
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// [`rename_tag`] for a `tags::` property line, or `None` if `line` isn't
+/// one. Each tag keeps its `[[link]]` or `#tag` form.
+fn rename_in_tags_property(line: &str, from: &str, to: &str) -> Option<String> {
+    if !is_property_line(line) {
+        return None;
+    }
+    let (key, value) = line.split_once("::")?;
+    if !key.trim().eq_ignore_ascii_case("tags") {
+        return None;
+    }
+    let mut renamed = false;
+    let tags: Vec<String> = value
+        .split(',')
+        .map(str::trim)
+        .filter(|tag| !tag.is_empty())
+        .map(|tag| {
+            let bare = tag.strip_prefix('#').unwrap_or(tag);
+            let name = bare
+                .strip_prefix("[[")
+                .and_then(|name| name.strip_suffix("]]"))
+                .unwrap_or(bare);
+            if name.to_lowercase() != from {
+                return tag.to_string();
+            }
+            renamed = true;
+            if tag.starts_with('#') {
+                format_tag(to)
+            } else if tag.starts_with("[[") {
+                format!("[[{}]]", to)
+            } else {
+                to.to_string()
+            }
+        })
+        .collect();
+    // Lines without the tag are left exactly as they were
+    Some(match renamed {
+        true => format!("{}:: {}", key, tags.join(", ")),
+        false => line.to_string(),
+    })
+}
+
+/// [`rename_tag`] for an ordinary line: `[[links]]` and `#tags`
+fn rename_references(line: &str, from: &str, to: &str) -> String {
+    let mut renamed = String::with_capacity(line.len());
+    let mut i = 0;
+    while let Some(rest) = line.get(i..).filter(|rest| !rest.is_empty()) {
+        if let Some(inner) = rest.strip_prefix("[[")
+            && let Some(end) = inner.find("]]")
+        {
+            match inner[..end].to_lowercase() == from {
+                true => renamed.push_str(&format!("[[{}]]", to)),
+                false => renamed.push_str(&rest[..end + 4]),
+            }
+            i += end + 4;
+            continue;
+        }
+        // `#[[...]]` is handled as a link on the next character
+        if let Some(after) = rest.strip_prefix('#')
+            && !after.starts_with("[[")
+            && renamed.chars().next_back().is_none_or(char::is_whitespace)
+        {
+            let end = after.find(is_tag_end).unwrap_or(after.len());
+            if end > 0 && after[..end].to_lowercase() == from {
+                renamed.push_str(&format_tag(to));
+                i += 1 + end;
+                continue;
+            }
+        }
+        let c = rest.chars().next().unwrap_or_default();
+        renamed.push(c);
+        i += c.len_utf8();
+    }
+    renamed
+}
+
+/// Task markers LogSeq recognises at the start of a block
+pub const TASK_MARKERS: [&str; 11] = [
+    "TODO",
+    "DOING",
+    "DONE",
+    "LATER",
+    "NOW",
+    "WAITING",
+    "WAIT",
+    "CANCELED",
+    "CANCELLED",
+    "IN-PROGRESS",
+    "STARTED",
+];
+
+/// Markers of tasks that are still open, most active first
+pub const INCOMPLETE_MARKERS: [&str; 5] = ["NOW", "DOING", "TODO", "LATER", "WAITING"];
+
+/// Split a leading task marker off block content, returning the marker (if
+/// any) and the remaining content.
+pub fn split_marker(content: &str) -> (Option<&str>, &str) {
+    for marker in TASK_MARKERS {
+        if let Some(rest) = content.strip_prefix(marker) {
+            if rest.is_empty() {
+                return (Some(marker), rest);
+            }
+            if let Some(rest) = rest.strip_prefix(' ') {
+                return (Some(marker), rest);
+            }
+        }
+    }
+    (None, content)
+}
+
+/// Replace (or add) the task marker at the start of block content.
+///
+/// Everything after the marker, including priority tags like `[#A]` and any
+/// property lines, is left untouched.
+pub fn set_marker(content: &str, marker: &str) -> String {
+    let (_, rest) = split_marker(content);
+    if rest.is_empty() {
+        marker.to_string()
+    } else {
+        format!("{} {}", marker, rest)
+    }
+}
+
+/// Add a line of text to the end of block content.
+///
+/// Property lines stay directly after the first line, where LogSeq looks
+/// for them.
+pub fn append_text(content: &str, text: &str) -> String {
+    let content = content.trim_end();
+    if content.is_empty() {
+        text.to_string()
+    } else {
+        format!("{}\n{}", content, text)
+    }
+}
+
+/// Add text to the start of block content's first line, after any task
+/// marker, so the marker and property lines keep their places.
+///
+/// A page's property block starts with its properties, so there the text
+/// goes on a line of its own.
+pub fn prepend_text(content: &str, text: &str) -> String {
+    if content.trim().is_empty() {
+        return text.to_string();
+    }
+    if content.split('\n').next().is_some_and(is_property_line) {
+        return format!("{}\n{}", text, content);
+    }
+    match split_marker(content) {
+        (Some(marker), "") => format!("{} {}", marker, text),
+        (Some(marker), rest) => format!("{} {} {}", marker, text, rest),
+        (None, rest) => format!("{} {}", text, rest),
+    }
+}
+
+/// The priority letter of the first `[#A]`, `[#B]` or `[#C]` in block
+/// content, as LogSeq reads it
+pub fn extract_priority(content: &str) -> Option<String> {
+    content.match_indices("[#").find_map(|(pos, _)| {
+        let rest = &content[pos + 2..];
+        let priority = rest.get(..1)?;
+        (matches!(priority, "A" | "B" | "C") && rest[1..].starts_with(']'))
+            .then(|| priority.to_string())
+    })
+}
+
+/// Whether a line is a LogSeq `key:: value` property line
+pub fn is_property_line(line: &str) -> bool {
+    let line = line.trim_start();
+    let Some((key, value)) = line.split_once("::") else {
+        return false;
+    };
+    !key.is_empty()
+        && !key.starts_with(':')
+        && !key.chars().any(char::is_whitespace)
+        && (value.is_empty() || value.starts_with(' '))
+}
+
+/// Remove a block's property lines, leaving the rest of its content.
+///
+/// LogSeq only treats the run of `key:: value` lines directly after the
+/// first line as properties (or starting at the first line, for a page's
+/// property block), so lookalike lines later on, e.g. in code, are kept.
+pub fn strip_property_lines(content: &str) -> String {
+    let lines: Vec<&str> = content.split('\n').collect();
+    let (start, end) = property_run(&lines);
+    [&lines[..start], &lines[end..]].concat().join("\n")
+}
+
+/// The value of one of a block's properties, from its property lines
+pub fn property_value<'a>(content: &'a str, key: &str) -> Option<&'a str> {
+    let lines: Vec<&str> = content.split('\n').collect();
+    let (start, end) = property_run(&lines);
+    lines[start..end].iter().find_map(|line| {
+        let (name, value) = line.trim_start().split_once("::")?;
+        (name == key).then(|| value.trim())
+    })
+}
+
+/// Where a block's property lines start and end, as for
+/// [`strip_property_lines`]
+fn property_run(lines: &[&str]) -> (usize, usize) {
+    let start = match lines.first() {
+        Some(first) if is_property_line(first) => 0,
+        _ => 1.min(lines.len()),
+    };
+    let end = start
+        + lines
+            .iter()
+            .skip(start)
+            .take_while(|line| is_property_line(line))
+            .count();
+    (start, end)
+}
+
+/// A `>` quote of a block's content, without its task marker or
+/// properties, ending with a link to its page and a `((ref))` to the block
+pub fn quote_block(content: &str, uuid: &str, page: Option<&str>) -> String {
+    let text = strip_property_lines(content);
+    let (_, text) = split_marker(&text);
+    let mut lines: Vec<String> = text
+        .trim()
+        .lines()
+        .map(|line| match line.is_empty() {
+            true => ">".to_string(),
+            false => format!("> {}", line),
+        })
+        .collect();
+    lines.push(match page {
+        Some(page) => format!("> — [[{}]] (({}))", page, uuid),
+        None => format!("> — (({}))", uuid),
+    });
+    lines.join("\n")
+}
+
+/// A `((uuid))` reference to a block, or `{{embed ((uuid))}}` to show the
+/// block and its children in place
+pub fn block_ref(uuid: &str, embed: bool) -> String {
+    match embed {
+        true => format!("{{{{embed (({}))}}}}", uuid),
+        false => format!("(({}))", uuid),
+    }
+}
+
+/// Replace `{{name}}` placeholders in template content with values from
+/// `variables`, returning the filled content and the names of placeholders
+/// left unfilled.
+///
+/// Only a single word (letters, digits, `-`, `_`) counts as a placeholder,
+/// so LogSeq macros such as `{{query (todo now)}}` or `{{embed [[page]]}}`
+/// pass through untouched.
+pub fn fill_template(
+    content: &str,
+    variables: &std::collections::HashMap<String, String>,
+) -> (String, Vec<String>) {
+    let mut filled = String::with_capacity(content.len());
+    let mut unfilled: Vec<String> = Vec::new();
+    let mut rest = content;
+
+    while let Some(start) = rest.find("{{") {
+        filled.push_str(&rest[..start]);
+        let after = &rest[start + 2..];
+        let Some(end) = after.find("}}") else {
+            rest = &rest[start..];
+            break;
+        };
+
+        let name = after[..end].trim();
+        let is_placeholder = !name.is_empty()
+            && name
+                .chars()
+                .all(|c| c.is_alphanumeric() || c == '-' || c == '_');
+        match variables.get(name) {
+            Some(value) if is_placeholder => filled.push_str(value),
+            _ => {
+                if is_placeholder && !unfilled.iter().any(|n| n == name) {
+                    unfilled.push(name.to_string());
+                }
+                filled.push_str(&rest[start..start + end + 4]);
+            }
+        }
+        rest = &after[end + 2..];
+    }
+    filled.push_str(rest);
+
+    (filled, unfilled)
+}
+
+/// Common English words left out of multi-word searches, since requiring
+/// them would only rule out blocks that are otherwise a match
+const STOPWORDS: [&str; 48] = [
+    "a", "about", "an", "and", "are", "as", "at", "be", "by", "did", "do", "does", "for", "from",
+    "had", "has", "have", "how", "i", "in", "is", "it", "its", "me", "my", "of", "on", "or", "our",
+    "that", "the", "their", "there", "this", "to", "was", "we", "were", "what", "when", "where",
+    "which", "who", "why", "will", "with", "you", "your",
+];
+
+/// Split a search query into the terms a block must all contain.
+///
+/// Words are separated by whitespace and lose any punctuation around them;
+/// a `"quoted phrase"` is kept as one term. Stopwords are dropped, unless
+/// the query is nothing but stopwords, and repeated terms are only kept
+/// once. Case is left alone, so case-sensitive searches can use the terms
+/// as they are.
+pub fn search_terms(query: &str) -> Vec<String> {
+    let mut words: Vec<String> = Vec::new();
+    for (i, part) in query.split('"').enumerate() {
+        // Odd-numbered parts are between quotes
+        if i % 2 == 1 {
+            let phrase = part.split_whitespace().collect::<Vec<_>>().join(" ");
+            if !phrase.is_empty() {
+                words.push(phrase);
+            }
+            continue;
+        }
+        words.extend(
+            part.split_whitespace()
+                .map(|word| word.trim_matches(|c: char| !c.is_alphanumeric()))
+                .filter(|word| !word.is_empty())
+                .map(str::to_string),
+        );
+    }
+
+    let is_stopword = |word: &String| STOPWORDS.contains(&word.to_lowercase().as_str());
+    if !words.iter().all(is_stopword) {
+        words.retain(|word| word.contains(' ') || !is_stopword(word));
+    }
+    let mut terms: Vec<String> = Vec::new();
+    for word in words {
+        if !terms.contains(&word) {
+            terms.push(word);
+        }
+    }
+    terms
+}
+
+/// Characters of context kept on each side of the first match in a snippet
+const SNIPPET_CONTEXT: usize = 60;
+
+/// Markers LogSeq's full-text search wraps around matches in its snippets
+const SEARCH_HIGHLIGHT_MARKERS: [&str; 2] = ["$pfts_2lqh>$", "$<pfts_2lqh$"];
+
+/// Build a one-line excerpt of `content` around the first match of `query`,
+/// with every matched term wrapped in `**`.
+///
+/// Matching is case-insensitive and per whitespace-separated term. Returns
+/// `None` when no term occurs in the content.
+pub fn search_snippet(content: &str, query: &str) -> Option<String> {
+    let chars: Vec<char> = content
+        .chars()
+        .map(|c| if c.is_whitespace() { ' ' } else { c })
+        .collect();
+    let folded: Vec<char> = chars.iter().map(|c| fold_char(*c)).collect();
+
+    let mut highlighted = vec![false; chars.len()];
+    for term in query.split_whitespace() {
+        let term: Vec<char> = term.chars().map(fold_char).collect();
+        if term.is_empty() || term.len() > folded.len() {
+            continue;
+        }
+        for start in 0..=folded.len() - term.len() {
+            if folded[start..start + term.len()] == term[..] {
+                highlighted[start..start + term.len()].fill(true);
+            }
+        }
+    }
+
+    let first = highlighted.iter().position(|h| *h)?;
+    let last = highlighted.iter().rposition(|h| *h)?;
+    let start = first.saturating_sub(SNIPPET_CONTEXT);
+    let end = (last + 1 + SNIPPET_CONTEXT).min(chars.len());
+
+    let mut snippet = String::new();
+    if start > 0 {
+        snippet.push('…');
+    }
+    for i in start..end {
+        if highlighted[i] && (i == start || !highlighted[i - 1]) {
+            snippet.push_str("**");
+        }
+        snippet.push(chars[i]);
+        if highlighted[i] && (i + 1 == end || !highlighted[i + 1]) {
+            snippet.push_str("**");
+        }
+    }
+    if end < chars.len() {
+        snippet.push('…');
+    }
+    Some(snippet.trim().to_string())
+}
+
+/// The first `max_chars` characters of `text`, cut at a word boundary where
+/// there is one and followed by an ellipsis, or all of `text` if it fits
+pub fn truncate(text: &str, max_chars: usize) -> String {
+    let Some((end, _)) = text.char_indices().nth(max_chars) else {
+        return text.to_string();
+    };
+    let cut = &text[..end];
+    let cut = match cut.rfind(char::is_whitespace) {
+        Some(space) if space > 0 => &cut[..space],
+        _ => cut,
+    };
+    format!("{}…", cut.trim_end())
+}
+
+/// Convert LogSeq's search highlight markers to markdown bold
+pub fn normalize_search_highlights(snippet: &str) -> String {
+    SEARCH_HIGHLIGHT_MARKERS
+        .iter()
+        .fold(snippet.to_string(), |s, marker| s.replace(marker, "**"))
+}
+
+/// Lowercase a character without changing the character count
+fn fold_char(c: char) -> char {
+    c.to_lowercase().next().unwrap_or(c)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rename_tag_rewrites_every_form() {
+        let content = "Read #Rust and #[[rust]] notes, see [[RUST]]\ntags:: [[rust]], web, rust\nnot #rustacean or rust";
+        assert_eq!(
+            rename_tag(content, "rust", "Programming Languages"),
+            "Read #[[Programming Languages]] and #[[Programming Languages]] notes, see [[Programming Languages]]\ntags:: [[Programming Languages]], web, Programming Languages\nnot #rustacean or rust"
+        );
+        assert_eq!(rename_tag("#rust.", "rust", "go"), "#go.");
+        // A tag elsewhere in the line isn't a tag, and nothing else changes
+        assert_eq!(
+            rename_tag("a#rust\nkey:: rust", "rust", "go"),
+            "a#rust\nkey:: rust"
+        );
+    }
+
+    #[test]
+    fn test_extract_priority() {
+        assert_eq!(extract_priority("TODO [#A] Ship it").as_deref(), Some("A"));
+        assert_eq!(
+            extract_priority("NOW see [#link] then [#C]").as_deref(),
+            Some("C")
+        );
+        assert_eq!(extract_priority("TODO [#D] not a priority"), None);
+        assert_eq!(extract_priority("TODO [#AB]"), None);
+        assert_eq!(extract_priority("TODO plain"), None);
+    }
+
+    #[test]
+    fn test_is_property_line() {
+        assert!(is_property_line("status:: done"));
+        assert!(is_property_line("  collapsed:: true"));
+        assert!(is_property_line("empty::"));
+        assert!(!is_property_line("not a property"));
+        assert!(!is_property_line("two words:: x"));
+        assert!(!is_property_line("std::collections::HashMap"));
+        assert!(!is_property_line(":: x"));
+    }
+
+    #[test]
+    fn test_fill_template() {
+        let variables: std::collections::HashMap<String, String> = [
+            ("project".to_string(), "Apollo".to_string()),
+            ("date".to_string(), "2024-05-01".to_string()),
+        ]
+        .into();
+
+        let (filled, unfilled) = fill_template(
+            "Kick-off for {{project}} on {{ date }}\nowner:: {{owner}}\n{{query (todo now)}} {{project}} {{owner}} {{",
+            &variables,
+        );
+        assert_eq!(
+            filled,
+            "Kick-off for Apollo on 2024-05-01\nowner:: {{owner}}\n{{query (todo now)}} Apollo {{owner}} {{"
+        );
+        assert_eq!(unfilled, vec!["owner"]);
+    }
+
+    #[test]
+    fn test_quote_block() {
+        assert_eq!(
+            quote_block("TODO Ship it\nid:: 1234\n\nSoon", "1234", Some("Plans")),
+            "> Ship it\n>\n> Soon\n> — [[Plans]] ((1234))"
+        );
+        assert_eq!(quote_block("Loose", "99", None), "> Loose\n> — ((99))");
+    }
+
+    #[test]
+    fn test_strip_property_lines() {
+        assert_eq!(
+            strip_property_lines("Task\nstatus:: done\ncollapsed:: true\nBody text"),
+            "Task\nBody text"
+        );
+        assert_eq!(strip_property_lines("title:: Page\ntags:: a, b"), "");
+        assert_eq!(
+            strip_property_lines("Code\nplain line\nkey:: kept"),
+            "Code\nplain line\nkey:: kept"
+        );
+        assert_eq!(strip_property_lines("Just text"), "Just text");
+        assert_eq!(strip_property_lines(""), "");
+    }
+
+    #[test]
+    fn test_property_value() {
+        let content = "What is 2 + 2? #card\ncard-repeats:: 3\ncard-ease-factor:: 2.5\nfoo:: bar";
+        assert_eq!(property_value(content, "card-repeats"), Some("3"));
+        assert_eq!(property_value(content, "foo"), Some("bar"));
+        assert_eq!(property_value(content, "card"), None);
+        assert_eq!(property_value("Text\nplain\nkey:: late", "key"), None);
+        assert_eq!(property_value("", "key"), None);
+    }
+
+    #[test]
+    fn test_set_marker_replaces_existing() {
+        assert_eq!(set_marker("TODO Buy milk", "DONE"), "DONE Buy milk");
+        assert_eq!(set_marker("LATER", "NOW"), "NOW");
+    }
+
+    #[test]
+    fn test_set_marker_preserves_priority_and_properties() {
+        assert_eq!(
+            set_marker("DOING [#A] Ship release\nowner:: me", "DONE"),
+            "DONE [#A] Ship release\nowner:: me"
+        );
+    }
+
+    #[test]
+    fn test_set_marker_adds_when_missing() {
+        assert_eq!(set_marker("Call the bank", "TODO"), "TODO Call the bank");
+        // Words that merely start with a marker are not markers
+        assert_eq!(
+            set_marker("TODOS for today", "TODO"),
+            "TODO TODOS for today"
+        );
+    }
+
+    #[test]
+    fn test_append_and_prepend_keep_marker_and_properties() {
+        let content = "TODO Ship release\nowner:: me";
+        assert_eq!(
+            append_text(content, "Blocked on review"),
+            "TODO Ship release\nowner:: me\nBlocked on review"
+        );
+        assert_eq!(
+            prepend_text(content, "[[Alpha]]"),
+            "TODO [[Alpha]] Ship release\nowner:: me"
+        );
+        assert_eq!(prepend_text("LATER", "Call Sam"), "LATER Call Sam");
+        assert_eq!(
+            prepend_text("tags:: work\nalias:: w", "Work"),
+            "Work\ntags:: work\nalias:: w"
+        );
+        assert_eq!(append_text("", "First"), "First");
+        assert_eq!(prepend_text("", "First"), "First");
+    }
+
+    #[test]
+    fn test_extract_simple_tags() {
+        assert_eq!(
+            extract_tags("TODO Write report #work #Urgent"),
+            vec!["work", "urgent"]
+        );
+    }
+
+    #[test]
+    fn test_extract_bracketed_tags() {
+        assert_eq!(
+            extract_tags("Plan trip #[[Summer Holiday]], then #travel."),
+            vec!["summer holiday", "travel"]
+        );
+    }
+
+    #[test]
+    fn test_ignores_headings_and_anchors() {
+        assert!(extract_tags("## Heading").is_empty());
+        assert!(extract_tags("see https://example.com/page#section").is_empty());
+        assert_eq!(extract_tags("#a #a"), vec!["a"]);
+    }
+
+    #[test]
+    fn test_search_snippet_highlights_terms() {
+        assert_eq!(
+            search_snippet("Reading about Rust async runtimes", "rust ASYNC").as_deref(),
+            Some("Reading about **Rust** **async** runtimes")
+        );
+        assert_eq!(search_snippet("nothing here", "rust"), None);
+    }
+
+    #[test]
+    fn test_truncate_cuts_at_a_word_boundary() {
+        assert_eq!(truncate("short", 10), "short");
+        assert_eq!(truncate("one two three", 9), "one two…");
+        assert_eq!(truncate("naïveté café", 4), "naïv…");
+    }
+
+    #[test]
+    fn test_search_snippet_trims_long_content() {
+        let content = format!("{} needle {}", "a".repeat(100), "b".repeat(100));
+        let snippet = search_snippet(&content, "needle").unwrap();
+        assert!(snippet.starts_with('…'));
+        assert!(snippet.ends_with('…'));
+        assert!(snippet.contains("**needle**"));
+        assert_eq!(
+            snippet.chars().count(),
+            "…**needle**…".chars().count() + 2 * SNIPPET_CONTEXT
+        );
+    }
+
+    #[test]
+    fn test_search_terms_drop_stopwords_and_keep_phrases() {
+        assert_eq!(
+            search_terms("what did I write about Rust lifetimes?"),
+            vec!["write", "Rust", "lifetimes"]
+        );
+        assert_eq!(
+            search_terms("notes on \"the borrow checker\" (rust)"),
+            vec!["notes", "the borrow checker", "rust"]
+        );
+        assert_eq!(search_terms("The Who"), vec!["The", "Who"]);
+        assert!(search_terms("  \"\" ?! ").is_empty());
+    }
+
+    #[test]
+    fn test_extract_page_links() {
+        assert_eq!(
+            extract_page_links(
+                "See [[Style Guide]], [[style guide]] and #[[Long Reads]]\nrelated:: [[Book]]"
+            ),
+            vec!["Style Guide", "Book"]
+        );
+        assert!(extract_page_links("[[]] and [[unclosed").is_empty());
+    }
+
+    #[test]
+    fn test_normalize_search_highlights() {
+        assert_eq!(
+            normalize_search_highlights("a $pfts_2lqh>$match$<pfts_2lqh$ here"),
+            "a **match** here"
+        );
+    }
+}
@@ -0,0 +1,337 @@
+//! Static checks on datascript queries before they are sent to LogSeq
+//!
+//! LogSeq evaluates queries on its UI thread, so a query that walks every
+//! block in a large graph freezes the app until it finishes. [`full_scans`]
+//! spots the common shape of such a query: a block variable that is only
+//! ever matched against attributes every block has (`:block/content`,
+//! `:block/page`, ...) and never narrowed by a constant, an `:in` input, a
+//! sparse attribute such as `:block/marker`, or a join to something that is.
+
+use std::collections::BTreeSet;
+
+/// Attributes every block carries; matching on one alone visits every block
+const DENSE_ATTRIBUTES: [&str; 7] = [
+    ":block/content",
+    ":block/uuid",
+    ":block/page",
+    ":block/parent",
+    ":block/left",
+    ":block/format",
+    ":block/properties",
+];
+
+/// Reference attributes, which let a narrowed variable narrow the other end
+const REF_ATTRIBUTES: [&str; 6] = [
+    ":block/page",
+    ":block/parent",
+    ":block/left",
+    ":block/refs",
+    ":block/path-refs",
+    ":block/tags",
+];
+
+#[derive(Debug, Clone, PartialEq)]
+enum Form {
+    Vector(Vec<Form>),
+    List(Vec<Form>),
+    Set(Vec<Form>),
+    Map(Vec<Form>),
+    Str,
+    Atom(String),
+}
+
+/// Variables of a query that range over every block, in name order. Returns
+/// nothing for queries it can't read.
+pub fn full_scans(query: &str) -> Vec<String> {
+    let Some(Form::Vector(forms)) = parse(query) else {
+        return Vec::new();
+    };
+
+    let mut narrowed: BTreeSet<String> = section(&forms, ":in")
+        .iter()
+        .filter_map(variable)
+        .map(String::from)
+        .collect();
+    let mut patterns = Vec::new();
+    collect_patterns(section(&forms, ":where"), &mut patterns);
+
+    for pattern in &patterns {
+        let sparse = !DENSE_ATTRIBUTES.contains(&pattern.attribute.as_str())
+            && !REF_ATTRIBUTES.contains(&pattern.attribute.as_str());
+        if pattern.constant || sparse {
+            narrowed.insert(pattern.entity.clone());
+        }
+    }
+    // A join through a reference narrows whichever side isn't yet
+    loop {
+        let before = narrowed.len();
+        for pattern in &patterns {
+            let Some(value) = &pattern.value else {
+                continue;
+            };
+            if !REF_ATTRIBUTES.contains(&pattern.attribute.as_str()) {
+                continue;
+            }
+            if narrowed.contains(&pattern.entity) {
+                narrowed.insert(value.clone());
+            } else if narrowed.contains(value) {
+                narrowed.insert(pattern.entity.clone());
+            }
+        }
+        if narrowed.len() == before {
+            break;
+        }
+    }
+
+    patterns
+        .iter()
+        .filter(|p| DENSE_ATTRIBUTES.contains(&p.attribute.as_str()))
+        .filter(|p| !narrowed.contains(&p.entity))
+        .map(|p| p.entity.clone())
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect()
+}
+
+/// Names for the values a query's `:find` clause returns, in order: `uuid`
+/// for `?uuid`, `count b` for `(count ?b)`, and `b` for `(pull ?b [...])`.
+/// Returns nothing for queries it can't read.
+pub fn find_columns(query: &str) -> Vec<String> {
+    let Some(Form::Vector(forms)) = parse(query) else {
+        return Vec::new();
+    };
+    let name = |form: &Form| match form {
+        Form::Atom(atom) if atom.starts_with('?') => Some(atom[1..].to_string()),
+        Form::List(items) => {
+            let mut words: Vec<&str> = items
+                .iter()
+                .filter_map(|item| match item {
+                    Form::Atom(atom) => Some(atom.trim_start_matches('?')),
+                    _ => None,
+                })
+                .collect();
+            if words.first() == Some(&"pull") {
+                words.remove(0);
+            }
+            Some(words.join(" "))
+        }
+        _ => None,
+    };
+    section(&forms, ":find")
+        .iter()
+        .filter_map(|form| match form {
+            // `[?x ...]` finds a collection of one value
+            Form::Vector(items) => items.first().and_then(name),
+            form => name(form),
+        })
+        .collect()
+}
+
+/// A `[?e :attr ?v]` data pattern
+struct Pattern {
+    entity: String,
+    attribute: String,
+    /// The value variable, when the value is one
+    value: Option<String>,
+    /// Whether the value is a constant, which makes it an index lookup
+    constant: bool,
+}
+
+fn collect_patterns(clauses: &[Form], out: &mut Vec<Pattern>) {
+    for clause in clauses {
+        match clause {
+            Form::Vector(items) => {
+                // Skip an explicit `$` source
+                let items = match items.first() {
+                    Some(Form::Atom(source)) if source.starts_with('$') => &items[1..],
+                    _ => &items[..],
+                };
+                let (Some(entity), Some(Form::Atom(attribute))) =
+                    (items.first().and_then(variable), items.get(1))
+                else {
+                    continue;
+                };
+                let value = items.get(2);
+                out.push(Pattern {
+                    entity: entity.to_string(),
+                    attribute: attribute.clone(),
+                    value: value.and_then(variable).map(String::from),
+                    constant: value
+                        .is_some_and(|v| variable(v).is_none() && *v != Form::Atom("_".into())),
+                });
+            }
+            // `(or ...)`, `(not ...)`, `(and ...)` and friends hold clauses too
+            Form::List(items) => collect_patterns(items, out),
+            _ => {}
+        }
+    }
+}
+
+fn variable(form: &Form) -> Option<&str> {
+    match form {
+        Form::Atom(atom) if atom.starts_with('?') => Some(atom),
+        _ => None,
+    }
+}
+
+/// The forms following `keyword` up to the next section keyword
+fn section<'a>(forms: &'a [Form], keyword: &str) -> &'a [Form] {
+    let Some(start) = forms.iter().position(|f| *f == Form::Atom(keyword.into())) else {
+        return &[];
+    };
+    let rest = &forms[start + 1..];
+    let end = rest
+        .iter()
+        .position(|f| matches!(f, Form::Atom(a) if a.starts_with(':')))
+        .unwrap_or(rest.len());
+    &rest[..end]
+}
+
+/// Read the first EDN form of `text`, enough for query structure: strings,
+/// tagged literals, and numbers all read as opaque values
+fn parse(text: &str) -> Option<Form> {
+    let mut chars = text.chars().peekable();
+    read(&mut chars)
+}
+
+fn read(chars: &mut std::iter::Peekable<std::str::Chars>) -> Option<Form> {
+    loop {
+        match chars.peek()? {
+            c if c.is_whitespace() || *c == ',' => {
+                chars.next();
+            }
+            ';' => {
+                chars.find(|c| *c == '\n');
+            }
+            _ => break,
+        }
+    }
+    match chars.next()? {
+        '[' => read_seq(chars, ']').map(Form::Vector),
+        '(' => read_seq(chars, ')').map(Form::List),
+        '{' => read_seq(chars, '}').map(Form::Map),
+        '"' => {
+            let mut escaped = false;
+            for c in chars.by_ref() {
+                match c {
+                    '\\' if !escaped => escaped = true,
+                    '"' if !escaped => return Some(Form::Str),
+                    _ => escaped = false,
+                }
+            }
+            None
+        }
+        '#' if chars.peek() == Some(&'{') => {
+            chars.next();
+            read_seq(chars, '}').map(Form::Set)
+        }
+        // A tagged literal such as `#uuid "..."` stands for its value
+        '#' => {
+            while chars
+                .peek()
+                .is_some_and(|c| !c.is_whitespace() && !is_delimiter(*c))
+            {
+                chars.next();
+            }
+            read(chars).map(|_| Form::Str)
+        }
+        c if is_delimiter(c) => None,
+        c => {
+            let mut atom = c.to_string();
+            while let Some(&c) = chars.peek() {
+                if c.is_whitespace() || c == ',' || is_delimiter(c) {
+                    break;
+                }
+                atom.push(c);
+                chars.next();
+            }
+            Some(Form::Atom(atom))
+        }
+    }
+}
+
+fn read_seq(chars: &mut std::iter::Peekable<std::str::Chars>, close: char) -> Option<Vec<Form>> {
+    let mut items = Vec::new();
+    loop {
+        while chars.peek().is_some_and(|c| c.is_whitespace() || *c == ',') {
+            chars.next();
+        }
+        if chars.peek() == Some(&close) {
+            chars.next();
+            return Some(items);
+        }
+        items.push(read(chars)?);
+    }
+}
+
+fn is_delimiter(c: char) -> bool {
+    matches!(c, '[' | ']' | '(' | ')' | '{' | '}' | '"')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_flags_unnarrowed_block_variables() {
+        let query =
+            "[:find ?uuid ?content :where [?b :block/uuid ?uuid] [?b :block/content ?content]]";
+        assert_eq!(full_scans(query), vec!["?b"]);
+
+        let query =
+            r#"[:find ?c :where [?b :block/content ?c] [(clojure.string/includes? ?c "x")]]"#;
+        assert_eq!(full_scans(query), vec!["?b"]);
+    }
+
+    #[test]
+    fn test_narrowed_queries_pass() {
+        for query in [
+            // A constant value
+            r#"[:find ?c :where [?p :block/name "projects"] [?b :block/page ?p] [?b :block/content ?c]]"#,
+            // A tagged constant
+            r#"[:find ?c :where [?b :block/uuid #uuid "6650a1b2-0000-4c1d-8e2f-123456789abc"] [?b :block/content ?c]]"#,
+            // An input
+            "[:find ?c :in $ ?p :where [?b :block/page ?p] [?b :block/content ?c]]",
+            // A sparse attribute
+            "[:find ?c :where [?b :block/marker ?m] [?b :block/content ?c]]",
+            // Children of a narrowed block, inside an `or`
+            r#"[:find ?c :where [?p :block/uuid #uuid "abc"] (or [?b :block/parent ?p]) [?b :block/content ?c]]"#,
+            // Pages only
+            "[:find ?name :where [?p :block/name ?name]]",
+        ] {
+            assert!(full_scans(query).is_empty(), "{}", query);
+        }
+    }
+
+    #[test]
+    fn test_find_columns_name_each_value() {
+        assert_eq!(
+            find_columns("[:find ?uuid ?content :where [?b :block/uuid ?uuid]]"),
+            vec!["uuid", "content"]
+        );
+        assert_eq!(
+            find_columns("[:find ?m (count ?b) :where [?b :block/marker ?m]]"),
+            vec!["m", "count b"]
+        );
+        assert_eq!(
+            find_columns("[:find (pull ?b [:block/content]) :where [?b :block/marker]]"),
+            vec!["b"]
+        );
+        assert_eq!(
+            find_columns("[:find [?name ...] :where [?p :block/name ?name]]"),
+            vec!["name"]
+        );
+        assert_eq!(
+            find_columns("[:find (count ?b) . :where [?b :block/page]]"),
+            vec!["count b"]
+        );
+        assert!(find_columns("(not a query)").is_empty());
+    }
+
+    #[test]
+    fn test_unreadable_queries_are_not_flagged() {
+        assert!(full_scans("").is_empty());
+        assert!(full_scans("[:find ?b :where [?b :block/content").is_empty());
+        assert!(full_scans("(not a query)").is_empty());
+    }
+}
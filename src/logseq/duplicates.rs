@@ -0,0 +1,267 @@
+//! Duplicate detection: texts that say the same thing, such as a note
+//! captured twice
+//!
+//! Texts are compared by the words they use, ignoring properties, task
+//! markers, case, punctuation, and word order. Two texts are near-duplicates
+//! when the Jaccard similarity of their word sets (shared words over all
+//! words) reaches the threshold; a group is a connected set of
+//! near-duplicates.
+//!
+//! Rather than compare every pair, which is too slow for large graphs, only
+//! pairs sharing one of their rarest words are compared. Two sets can't
+//! reach the threshold without sharing one of those ("prefix filtering"),
+//! so no pair is missed.
+
+use crate::logseq::content::{split_marker, strip_property_lines};
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+/// Similarity texts must reach to be reported by default
+pub const DEFAULT_THRESHOLD: f64 = 0.8;
+
+/// Words a text needs to be compared by default. Shorter ones, such as
+/// headings like "Notes", repeat harmlessly.
+pub const DEFAULT_MIN_WORDS: usize = 3;
+
+/// Texts that duplicate one another
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DuplicateGroup {
+    /// Indexes of the texts, in order
+    pub members: Vec<usize>,
+    /// The lowest similarity between two members found to match, from 0
+    /// to 1. Members matched only through others may be less alike.
+    pub similarity: f64,
+    /// Whether every member has the same words in the same order
+    pub identical: bool,
+}
+
+/// The words of a block's text, lower-cased, without properties, its task
+/// marker, or punctuation
+pub fn words(content: &str) -> Vec<String> {
+    let text = strip_property_lines(content);
+    let (_, text) = split_marker(&text);
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .collect()
+}
+
+/// Group `texts` that are at least `threshold` similar. Texts with fewer
+/// than `min_words` distinct words are left out. Largest groups first.
+pub fn duplicate_groups(texts: &[&str], threshold: f64, min_words: usize) -> Vec<DuplicateGroup> {
+    let threshold = threshold.clamp(f64::EPSILON, 1.0);
+    let words: Vec<Vec<String>> = texts.iter().map(|text| words(text)).collect();
+
+    // Each text's distinct words as ids, rarest first
+    let mut ids: HashMap<&str, usize> = HashMap::new();
+    let mut frequency: Vec<usize> = Vec::new();
+    let mut sets: Vec<Vec<usize>> = Vec::with_capacity(texts.len());
+    for text in &words {
+        let set: BTreeSet<usize> = text
+            .iter()
+            .map(|word| {
+                let next = ids.len();
+                *ids.entry(word).or_insert(next)
+            })
+            .collect();
+        frequency.resize(ids.len(), 0);
+        for &id in &set {
+            frequency[id] += 1;
+        }
+        sets.push(set.into_iter().collect());
+    }
+    for set in &mut sets {
+        set.sort_by_key(|&id| (frequency[id], id));
+    }
+
+    // Smallest first, so each text is compared with those no larger
+    let mut order: Vec<usize> = (0..texts.len())
+        .filter(|&i| sets[i].len() >= min_words.max(1))
+        .collect();
+    order.sort_by_key(|&i| (sets[i].len(), i));
+
+    let mut index: HashMap<usize, Vec<usize>> = HashMap::new();
+    let mut matches: Vec<(usize, usize, f64)> = Vec::new();
+    for &x in &order {
+        let size = sets[x].len();
+        let prefix = size - min_overlap(size, threshold) + 1;
+        let mut candidates: BTreeSet<usize> = BTreeSet::new();
+        for id in &sets[x][..prefix] {
+            if let Some(others) = index.get(id) {
+                candidates.extend(
+                    others
+                        .iter()
+                        .filter(|&&y| sets[y].len() as f64 >= threshold * size as f64 - 1e-9),
+                );
+            }
+        }
+        for y in candidates {
+            let similarity = jaccard(&sets[x], &sets[y]);
+            if similarity >= threshold - 1e-9 {
+                matches.push((y.min(x), y.max(x), similarity));
+            }
+        }
+        for &id in &sets[x][..prefix] {
+            index.entry(id).or_default().push(x);
+        }
+    }
+
+    group(&matches, &words)
+}
+
+/// The fewest shared words a set of `size` needs with one no larger to
+/// reach `threshold`
+fn min_overlap(size: usize, threshold: f64) -> usize {
+    ((threshold * size as f64) - 1e-9).ceil().max(1.0) as usize
+}
+
+/// Shared words over all words of two sets of word ids
+fn jaccard(a: &[usize], b: &[usize]) -> f64 {
+    let a: BTreeSet<usize> = a.iter().copied().collect();
+    let shared = b.iter().filter(|id| a.contains(id)).count();
+    shared as f64 / (a.len() + b.len() - shared) as f64
+}
+
+/// The connected components of the matched pairs
+fn group(matches: &[(usize, usize, f64)], words: &[Vec<String>]) -> Vec<DuplicateGroup> {
+    // Union-find over text indexes, each root standing for its component
+    let mut parent: HashMap<usize, usize> = HashMap::new();
+    fn root(parent: &mut HashMap<usize, usize>, i: usize) -> usize {
+        let mut current = i;
+        while let Some(&next) = parent.get(&current) {
+            if next == current {
+                break;
+            }
+            current = next;
+        }
+        parent.insert(i, current);
+        current
+    }
+    for &(a, b, _) in matches {
+        parent.entry(a).or_insert(a);
+        parent.entry(b).or_insert(b);
+        let (ra, rb) = (root(&mut parent, a), root(&mut parent, b));
+        if ra != rb {
+            parent.insert(ra.max(rb), ra.min(rb));
+        }
+    }
+
+    let mut components: BTreeMap<usize, DuplicateGroup> = BTreeMap::new();
+    for &(a, _, similarity) in matches {
+        let r = root(&mut parent, a);
+        let group = components.entry(r).or_insert(DuplicateGroup {
+            members: Vec::new(),
+            similarity: 1.0,
+            identical: true,
+        });
+        group.similarity = group.similarity.min(similarity);
+    }
+    let members: Vec<usize> = parent.keys().copied().collect();
+    for i in members {
+        let r = root(&mut parent, i);
+        if let Some(group) = components.get_mut(&r) {
+            group.members.push(i);
+        }
+    }
+
+    let mut groups: Vec<DuplicateGroup> = components
+        .into_values()
+        .map(|mut group| {
+            group.members.sort_unstable();
+            let first = &words[group.members[0]];
+            group.identical = group.members.iter().all(|&i| words[i] == *first);
+            group
+        })
+        .collect();
+    groups.sort_by(|x, y| {
+        y.members
+            .len()
+            .cmp(&x.members.len())
+            .then_with(|| y.similarity.total_cmp(&x.similarity))
+            .then_with(|| x.members.cmp(&y.members))
+    });
+    groups
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_words_ignore_markup() {
+        assert_eq!(
+            words("TODO Call [[Alice]] about the #budget!\nid:: 64f0c2a1-0000"),
+            ["call", "alice", "about", "the", "budget"]
+        );
+    }
+
+    #[test]
+    fn test_duplicate_groups() {
+        let texts = [
+            "Buy oat milk and bread on the way home",
+            "Meeting notes",
+            "TODO buy oat milk and bread on the way home",
+            "Read the paper on prefix filtering for similarity joins",
+            "Buy oat milk and bread on the way back home",
+            "Meeting notes",
+            "Read the paper on prefix filtering for set similarity joins",
+            "Something else entirely, about gardening and tomatoes",
+        ];
+        let groups = duplicate_groups(&texts, DEFAULT_THRESHOLD, DEFAULT_MIN_WORDS);
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups[0].members, [0, 2, 4]);
+        assert!(!groups[0].identical);
+        // 9 words of 10 shared
+        assert!((groups[0].similarity - 0.9).abs() < 1e-9);
+        assert_eq!(groups[1].members, [3, 6]);
+
+        // "Meeting notes" is too short to count
+        let groups = duplicate_groups(&texts, DEFAULT_THRESHOLD, 2);
+        assert!(groups.iter().any(|g| g.members == [1, 5] && g.identical));
+
+        let groups = duplicate_groups(&texts, 1.0, DEFAULT_MIN_WORDS);
+        assert_eq!(groups.len(), 1);
+        assert_eq!(groups[0].members, [0, 2]);
+        assert!(groups[0].identical);
+    }
+
+    #[test]
+    fn test_prefix_filtering_finds_every_pair() {
+        // Compare against checking every pair
+        let texts: Vec<String> = (0..40)
+            .map(|i| {
+                (0..8)
+                    .map(|w| format!("w{}", (i * 7 + w * (i % 3 + 1)) % 12))
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            })
+            .collect();
+        let texts: Vec<&str> = texts.iter().map(String::as_str).collect();
+        for threshold in [0.5, 0.7, 0.9] {
+            let mut expected: Vec<(usize, usize)> = Vec::new();
+            for a in 0..texts.len() {
+                for b in a + 1..texts.len() {
+                    let (x, y) = (words(texts[a]), words(texts[b]));
+                    let x: BTreeSet<&String> = x.iter().collect();
+                    let y: BTreeSet<&String> = y.iter().collect();
+                    let shared = x.intersection(&y).count();
+                    if shared as f64 / (x.len() + y.len() - shared) as f64 >= threshold {
+                        expected.push((a, b));
+                    }
+                }
+            }
+            let groups = duplicate_groups(&texts, threshold, 1);
+            for (a, b) in expected {
+                assert!(
+                    groups
+                        .iter()
+                        .any(|g| g.members.contains(&a) && g.members.contains(&b)),
+                    "{} and {} should be grouped at {}",
+                    a,
+                    b,
+                    threshold
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,88 @@
+//! Why a call to the LogSeq API failed
+//!
+//! The client returns `anyhow` errors, but the failures callers need to tell
+//! apart are raised as a [`LogSeqError`] and can be recovered with
+//! `downcast_ref`: a page or block that doesn't exist, a rejected token,
+//! LogSeq not running, and a response that doesn't make sense.
+
+use reqwest::StatusCode;
+use std::time::Duration;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum LogSeqError {
+    /// A page or block the call named doesn't exist
+    #[error("{kind} not found: {name}")]
+    NotFound { kind: &'static str, name: String },
+    #[error(
+        "LogSeq at {url} rejected the API token; check that it matches a token in LogSeq's API server settings"
+    )]
+    Unauthorized { url: String },
+    #[error(
+        "LogSeq can't be reached at {url} ({reason}); check that it's running with the HTTP API server turned on"
+    )]
+    Unreachable { url: String, reason: String },
+    #[error("LogSeq did not respond to {method} within {timeout:?}")]
+    Timeout { method: String, timeout: Duration },
+    /// A response that doesn't fit, with the id it was saved under in the
+    /// client's payload journal, if it has one
+    #[error("LogSeq's response to {method} couldn't be understood: {reason}{}", report_note(.report))]
+    MalformedResponse {
+        method: String,
+        reason: String,
+        report: Option<String>,
+    },
+    /// Any other error status, with what LogSeq said
+    #[error("API call failed: {status} - {body}")]
+    Api { status: StatusCode, body: String },
+}
+
+impl LogSeqError {
+    pub fn page_not_found(name: impl Into<String>) -> Self {
+        Self::NotFound {
+            kind: "Page",
+            name: name.into(),
+        }
+    }
+
+    pub fn block_not_found(uuid: impl Into<String>) -> Self {
+        Self::NotFound {
+            kind: "Block",
+            name: uuid.into(),
+        }
+    }
+
+    /// Whether the same call might succeed if tried again later
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            Self::Unreachable { .. } | Self::Timeout { .. } => true,
+            Self::Api { status, .. } => status.is_server_error(),
+            Self::NotFound { .. } | Self::Unauthorized { .. } | Self::MalformedResponse { .. } => {
+                false
+            }
+        }
+    }
+
+    /// A short name for the kind of failure, for clients to check for
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::NotFound { .. } => "not_found",
+            Self::Unauthorized { .. } => "unauthorized",
+            Self::Unreachable { .. } => "backend_unavailable",
+            Self::Timeout { .. } => "timeout",
+            Self::MalformedResponse { .. } => "malformed_response",
+            Self::Api { .. } => "logseq_error",
+        }
+    }
+}
+
+/// Where to find a saved response, to attach to a bug report
+fn report_note(report: &Option<String>) -> String {
+    match report {
+        Some(id) => format!(
+            " (the response was saved as {}; please attach it to a bug report)",
+            id
+        ),
+        None => String::new(),
+    }
+}
@@ -0,0 +1,286 @@
+//! Journal page titles in LogSeq's configurable (date-fns style) date formats
+
+use chrono::{Datelike, NaiveDate, Weekday};
+
+/// LogSeq's default `:journal/page-title-format`
+pub const DEFAULT_DATE_FORMAT: &str = "MMM do, yyyy";
+
+/// Formats worth recognising even when they aren't the user's preferred one
+const COMMON_DATE_FORMATS: [&str; 4] = ["yyyy-MM-dd", "yyyy/MM/dd", "yyyy_MM_dd", "MMM do, yyyy"];
+
+const MONTHS: [&str; 12] = [
+    "January",
+    "February",
+    "March",
+    "April",
+    "May",
+    "June",
+    "July",
+    "August",
+    "September",
+    "October",
+    "November",
+    "December",
+];
+
+const WEEKDAYS: [&str; 7] = [
+    "Monday",
+    "Tuesday",
+    "Wednesday",
+    "Thursday",
+    "Friday",
+    "Saturday",
+    "Sunday",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Token<'a> {
+    Year,
+    ShortYear,
+    MonthName,
+    MonthShortName,
+    Month2,
+    Month,
+    Day2,
+    Day,
+    DayOrdinal,
+    WeekdayName,
+    WeekdayShortName,
+    Literal(&'a str),
+}
+
+fn tokenize(format: &str) -> Vec<Token<'_>> {
+    let mut tokens = Vec::new();
+    let mut rest = format;
+
+    while let Some(c) = rest.chars().next() {
+        let run = rest.chars().take_while(|r| *r == c).count();
+        let (token, len) = match (c, run) {
+            ('y', n) if n >= 3 => (Token::Year, n),
+            ('y', n) => (Token::ShortYear, n),
+            ('M', n) if n >= 4 => (Token::MonthName, n),
+            ('M', 3) => (Token::MonthShortName, 3),
+            ('M', 2) => (Token::Month2, 2),
+            ('M', _) => (Token::Month, 1),
+            ('d', 1) if rest[1..].starts_with('o') => (Token::DayOrdinal, 2),
+            ('d', 1) => (Token::Day, 1),
+            ('d', n) => (Token::Day2, n),
+            ('E', n) if n >= 4 => (Token::WeekdayName, n),
+            ('E', n) => (Token::WeekdayShortName, n),
+            _ => {
+                let len = c.len_utf8();
+                (Token::Literal(&rest[..len]), len)
+            }
+        };
+        tokens.push(token);
+        rest = &rest[len..];
+    }
+
+    tokens
+}
+
+fn ordinal_suffix(day: u32) -> &'static str {
+    match (day % 10, day % 100) {
+        (_, 11..=13) => "th",
+        (1, _) => "st",
+        (2, _) => "nd",
+        (3, _) => "rd",
+        _ => "th",
+    }
+}
+
+/// Render a date as a journal page title using a date-fns style format
+pub fn format_journal_title(date: NaiveDate, format: &str) -> String {
+    let month = MONTHS[date.month0() as usize];
+    let weekday = WEEKDAYS[date.weekday().num_days_from_monday() as usize];
+
+    tokenize(format)
+        .into_iter()
+        .map(|token| match token {
+            Token::Year => format!("{:04}", date.year()),
+            Token::ShortYear => format!("{:02}", date.year() % 100),
+            Token::MonthName => month.to_string(),
+            Token::MonthShortName => month[..3].to_string(),
+            Token::Month2 => format!("{:02}", date.month()),
+            Token::Month => date.month().to_string(),
+            Token::Day2 => format!("{:02}", date.day()),
+            Token::Day => date.day().to_string(),
+            Token::DayOrdinal => format!("{}{}", date.day(), ordinal_suffix(date.day())),
+            Token::WeekdayName => weekday.to_string(),
+            Token::WeekdayShortName => weekday[..3].to_string(),
+            Token::Literal(s) => s.to_string(),
+        })
+        .collect()
+}
+
+fn take_digits(input: &str, min: usize, max: usize) -> Option<(u32, &str)> {
+    let len = input
+        .chars()
+        .take(max)
+        .take_while(char::is_ascii_digit)
+        .count();
+    if len < min {
+        return None;
+    }
+    Some((input[..len].parse().ok()?, &input[len..]))
+}
+
+/// Match the longest name from `names` (optionally truncated to three
+/// letters) at the start of `input`, case-insensitively.
+fn take_name<'a>(input: &'a str, names: &[&str], short: bool) -> Option<(usize, &'a str)> {
+    names.iter().enumerate().find_map(|(i, name)| {
+        let name = if short { &name[..3] } else { name };
+        let candidate = input.get(..name.len())?;
+        candidate
+            .eq_ignore_ascii_case(name)
+            .then(|| (i, &input[name.len()..]))
+    })
+}
+
+/// Parse a page title as a journal date in the given date-fns style format
+pub fn parse_journal_title(title: &str, format: &str) -> Option<NaiveDate> {
+    let mut rest = title.trim();
+    let (mut year, mut month, mut day) = (None, None, None);
+    let mut weekday = None;
+
+    for token in tokenize(format) {
+        match token {
+            Token::Year => {
+                let (y, r) = take_digits(rest, 4, 4)?;
+                year = Some(y as i32);
+                rest = r;
+            }
+            Token::ShortYear => {
+                let (y, r) = take_digits(rest, 2, 2)?;
+                year = Some(2000 + y as i32);
+                rest = r;
+            }
+            Token::MonthName | Token::MonthShortName => {
+                let (m, r) = take_name(rest, &MONTHS, token == Token::MonthShortName)?;
+                month = Some(m as u32 + 1);
+                rest = r;
+            }
+            Token::Month2 | Token::Month => {
+                let min = if token == Token::Month2 { 2 } else { 1 };
+                let (m, r) = take_digits(rest, min, 2)?;
+                month = Some(m);
+                rest = r;
+            }
+            Token::Day2 | Token::Day => {
+                let min = if token == Token::Day2 { 2 } else { 1 };
+                let (d, r) = take_digits(rest, min, 2)?;
+                day = Some(d);
+                rest = r;
+            }
+            Token::DayOrdinal => {
+                let (d, r) = take_digits(rest, 1, 2)?;
+                let suffix = r.get(..2)?;
+                if !suffix.eq_ignore_ascii_case(ordinal_suffix(d)) {
+                    return None;
+                }
+                day = Some(d);
+                rest = &r[2..];
+            }
+            Token::WeekdayName | Token::WeekdayShortName => {
+                let (w, r) = take_name(rest, &WEEKDAYS, token == Token::WeekdayShortName)?;
+                weekday = Weekday::try_from(w as u8).ok();
+                rest = r;
+            }
+            Token::Literal(s) => {
+                rest = rest.strip_prefix(s)?;
+            }
+        }
+    }
+
+    if !rest.is_empty() {
+        return None;
+    }
+
+    let date = NaiveDate::from_ymd_opt(year?, month?, day?)?;
+    match weekday {
+        Some(w) if w != date.weekday() => None,
+        _ => Some(date),
+    }
+}
+
+/// Detect whether a page name looks like a journal date, trying the user's
+/// preferred format first and then other common journal title formats.
+pub fn detect_journal_date(name: &str, preferred_format: &str) -> Option<NaiveDate> {
+    std::iter::once(preferred_format)
+        .chain(COMMON_DATE_FORMATS)
+        .find_map(|format| parse_journal_title(name, format))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    #[test]
+    fn test_format_default_title() {
+        assert_eq!(
+            format_journal_title(date(2024, 1, 31), DEFAULT_DATE_FORMAT),
+            "Jan 31st, 2024"
+        );
+        assert_eq!(
+            format_journal_title(date(2024, 3, 12), "EEEE, dd.MM.yyyy"),
+            "Tuesday, 12.03.2024"
+        );
+        assert_eq!(
+            format_journal_title(date(2024, 3, 2), "yyyy年MM月dd日"),
+            "2024年03月02日"
+        );
+    }
+
+    #[test]
+    fn test_parse_round_trips_logseq_formats() {
+        let formats = [
+            "MMM do, yyyy",
+            "do MMMM yyyy",
+            "E, MM/dd/yyyy",
+            "EEE, yyyy/MM/dd",
+            "yyyy-MM-dd EEEE",
+            "yyyyMMdd",
+            "yyyy年MM月dd日",
+        ];
+        for format in formats {
+            for d in [date(2023, 11, 11), date(2024, 2, 29), date(2025, 12, 22)] {
+                let title = format_journal_title(d, format);
+                assert_eq!(parse_journal_title(&title, format), Some(d), "{}", title);
+            }
+        }
+    }
+
+    #[test]
+    fn test_parse_rejects_non_dates() {
+        assert_eq!(
+            parse_journal_title("Project Alpha", DEFAULT_DATE_FORMAT),
+            None
+        );
+        assert_eq!(
+            parse_journal_title("Feb 30th, 2024", DEFAULT_DATE_FORMAT),
+            None
+        );
+        assert_eq!(
+            parse_journal_title("Jan 31th, 2024", DEFAULT_DATE_FORMAT),
+            None
+        );
+        // Weekday must agree with the date
+        assert_eq!(
+            parse_journal_title("Monday, 12.03.2024", "EEEE, dd.MM.yyyy"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_detect_journal_date_falls_back_to_common_formats() {
+        assert_eq!(
+            detect_journal_date("2024-01-31", DEFAULT_DATE_FORMAT),
+            Some(date(2024, 1, 31))
+        );
+        assert_eq!(detect_journal_date("2024 plans", DEFAULT_DATE_FORMAT), None);
+    }
+}
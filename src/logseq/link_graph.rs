@@ -0,0 +1,203 @@
+//! The graph of links between pages, written as DOT or GraphML for
+//! graph visualisation tools such as Graphviz, Gephi, or yEd
+//!
+//! Each page that links or is linked to is a node, labelled with its name
+//! as the user wrote it. Each edge runs from a linking page to the page it
+//! links, weighted by how many of the linking page's blocks make the link.
+
+use crate::logseq::api::PageLink;
+use std::collections::BTreeMap;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LinkEdge {
+    /// Index of the linking page in [`LinkGraph::nodes`]
+    pub from: usize,
+    /// Index of the linked page
+    pub to: usize,
+    /// Blocks making the link
+    pub weight: u64,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LinkGraph {
+    /// Page names, sorted
+    pub nodes: Vec<String>,
+    pub edges: Vec<LinkEdge>,
+}
+
+impl LinkGraph {
+    /// The graph of `links` between pages that `keep` accepts, given their
+    /// lower-cased names. Pages are labelled by `label`.
+    pub fn new(
+        links: &[PageLink],
+        keep: impl Fn(&str) -> bool,
+        label: impl Fn(&str) -> String,
+    ) -> Self {
+        let links: Vec<&PageLink> = links
+            .iter()
+            .filter(|link| keep(&link.from) && keep(&link.to))
+            .collect();
+        let mut labels: BTreeMap<&str, String> = BTreeMap::new();
+        for link in &links {
+            for name in [link.from.as_str(), link.to.as_str()] {
+                labels.entry(name).or_insert_with(|| label(name));
+            }
+        }
+        let mut nodes: Vec<(&str, String)> = labels.into_iter().collect();
+        nodes.sort_by(|a, b| a.1.cmp(&b.1).then_with(|| a.0.cmp(b.0)));
+        let index: BTreeMap<&str, usize> = nodes
+            .iter()
+            .enumerate()
+            .map(|(i, (name, _))| (*name, i))
+            .collect();
+
+        let mut edges: Vec<LinkEdge> = links
+            .iter()
+            .map(|link| LinkEdge {
+                from: index[link.from.as_str()],
+                to: index[link.to.as_str()],
+                weight: link.count,
+            })
+            .collect();
+        edges.sort_by_key(|edge| (edge.from, edge.to));
+        Self {
+            nodes: nodes.into_iter().map(|(_, label)| label).collect(),
+            edges,
+        }
+    }
+
+    /// The graph in Graphviz's DOT language
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph logseq {\n");
+        for node in &self.nodes {
+            dot.push_str(&format!("  {};\n", dot_id(node)));
+        }
+        for edge in &self.edges {
+            dot.push_str(&format!(
+                "  {} -> {} [weight={}];\n",
+                dot_id(&self.nodes[edge.from]),
+                dot_id(&self.nodes[edge.to]),
+                edge.weight
+            ));
+        }
+        dot.push_str("}\n");
+        dot
+    }
+
+    /// The graph as a GraphML document, with each page's name in a `label`
+    /// attribute and each link's `weight`
+    pub fn to_graphml(&self) -> String {
+        let mut xml = String::from(concat!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n",
+            "<graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\">\n",
+            "  <key id=\"label\" for=\"node\" attr.name=\"label\" attr.type=\"string\"/>\n",
+            "  <key id=\"weight\" for=\"edge\" attr.name=\"weight\" attr.type=\"long\"/>\n",
+            "  <graph id=\"logseq\" edgedefault=\"directed\">\n",
+        ));
+        for (i, node) in self.nodes.iter().enumerate() {
+            xml.push_str(&format!(
+                "    <node id=\"n{}\"><data key=\"label\">{}</data></node>\n",
+                i,
+                xml_escape(node)
+            ));
+        }
+        for edge in &self.edges {
+            xml.push_str(&format!(
+                "    <edge source=\"n{}\" target=\"n{}\"><data key=\"weight\">{}</data></edge>\n",
+                edge.from, edge.to, edge.weight
+            ));
+        }
+        xml.push_str("  </graph>\n</graphml>\n");
+        xml
+    }
+}
+
+/// A page name as a quoted DOT identifier
+fn dot_id(name: &str) -> String {
+    let mut id = String::with_capacity(name.len() + 2);
+    id.push('"');
+    for c in name.chars() {
+        match c {
+            '"' | '\\' => {
+                id.push('\\');
+                id.push(c);
+            }
+            '\n' => id.push_str("\\n"),
+            c => id.push(c),
+        }
+    }
+    id.push('"');
+    id
+}
+
+/// Text safe to put in XML content or attributes
+fn xml_escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn link(from: &str, to: &str, count: u64) -> PageLink {
+        PageLink {
+            from: from.to_string(),
+            to: to.to_string(),
+            count,
+        }
+    }
+
+    #[test]
+    fn test_link_graph_formats() {
+        let links = [
+            link("projects", "alpha", 2),
+            link("alpha", "r&d \"lab\"", 1),
+            link("journal", "alpha", 1),
+        ];
+        let graph = LinkGraph::new(
+            &links,
+            |name| name != "journal",
+            |name| match name {
+                "alpha" => "Alpha".to_string(),
+                "projects" => "Projects".to_string(),
+                name => name.to_string(),
+            },
+        );
+        assert_eq!(graph.nodes, ["Alpha", "Projects", "r&d \"lab\""]);
+
+        assert_eq!(
+            graph.to_dot(),
+            concat!(
+                "digraph logseq {\n",
+                "  \"Alpha\";\n",
+                "  \"Projects\";\n",
+                "  \"r&d \\\"lab\\\"\";\n",
+                "  \"Alpha\" -> \"r&d \\\"lab\\\"\" [weight=1];\n",
+                "  \"Projects\" -> \"Alpha\" [weight=2];\n",
+                "}\n",
+            )
+        );
+
+        let xml = graph.to_graphml();
+        assert!(
+            xml.contains(
+                "<node id=\"n2\"><data key=\"label\">r&amp;d &quot;lab&quot;</data></node>"
+            )
+        );
+        assert!(
+            xml.contains("<edge source=\"n1\" target=\"n0\"><data key=\"weight\">2</data></edge>")
+        );
+        assert!(xml.ends_with("  </graph>\n</graphml>\n"));
+    }
+}
@@ -0,0 +1,720 @@
+//! Parsing LogSeq-style markdown outlines back into block trees
+//!
+//! This is the inverse of `format_blocks_as_markdown`: each block is a `* ` or
+//! `- ` bullet indented two spaces per level, and any further lines of a
+//! block's content are indented to line up with the text after its bullet.
+//! Lines inside fenced code blocks are always treated as content, so code
+//! containing bullets or odd indentation survives intact.
+//!
+//! [`parse_document`] handles ordinary markdown documents instead, turning
+//! headings into parent blocks for the sections below them.
+
+use crate::logseq::api::Block;
+use serde::{Deserialize, Serialize};
+
+/// A block to be written to LogSeq, in the shape `insertBatchBlock` accepts
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BatchBlock {
+    pub content: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub children: Vec<BatchBlock>,
+}
+
+impl From<&Block> for BatchBlock {
+    fn from(block: &Block) -> Self {
+        Self {
+            content: block.content.clone(),
+            children: block.children.iter().map(BatchBlock::from).collect(),
+        }
+    }
+}
+
+/// A block being assembled, with its depth and the open code fence (if any)
+struct OpenBlock {
+    depth: usize,
+    lines: Vec<String>,
+    fence: Option<String>,
+    children: Vec<BatchBlock>,
+}
+
+impl OpenBlock {
+    fn new(depth: usize, first_line: &str) -> Self {
+        let mut block = Self {
+            depth,
+            lines: Vec::new(),
+            fence: None,
+            children: Vec::new(),
+        };
+        block.push_line(first_line);
+        block
+    }
+
+    fn push_line(&mut self, line: &str) {
+        track_fence(&mut self.fence, line);
+        self.lines.push(line.to_string());
+    }
+
+    /// Indentation (in spaces) of this block's continuation lines
+    fn content_indent(&self) -> usize {
+        self.depth * 2 + 2
+    }
+
+    fn finish(self) -> BatchBlock {
+        BatchBlock {
+            content: self.lines.join("\n").trim_end_matches('\n').to_string(),
+            children: self.children,
+        }
+    }
+}
+
+/// Parse a markdown outline into a block tree.
+///
+/// Text before the first bullet becomes a top-level block of its own.
+/// Indentation deeper than one level below the previous block is treated as
+/// a child of that block.
+pub fn parse_outline(markdown: &str) -> Vec<BatchBlock> {
+    let mut roots: Vec<BatchBlock> = Vec::new();
+    let mut stack: Vec<OpenBlock> = Vec::new();
+
+    for line in markdown.lines() {
+        let in_fence = stack.last().is_some_and(|b| b.fence.is_some());
+        let bullet = if in_fence { None } else { parse_bullet(line) };
+
+        match bullet {
+            Some((level, content)) => {
+                // Close blocks at this depth or deeper; the new block nests
+                // under whatever remains open
+                while stack.last().is_some_and(|b| b.depth >= level) {
+                    close_top(&mut stack, &mut roots);
+                }
+                let depth = stack
+                    .last()
+                    .map_or(0, |parent| (parent.depth + 1).min(level));
+                stack.push(OpenBlock::new(depth, content));
+            }
+            None => match stack.last_mut() {
+                Some(block) => {
+                    let indent = block.content_indent();
+                    block.push_line(strip_indent(line, indent));
+                }
+                None if line.trim().is_empty() => {}
+                None => stack.push(OpenBlock::new(0, line)),
+            },
+        }
+    }
+
+    while !stack.is_empty() {
+        close_top(&mut stack, &mut roots);
+    }
+    roots
+}
+
+fn close_top(stack: &mut Vec<OpenBlock>, roots: &mut Vec<BatchBlock>) {
+    if let Some(block) = stack.pop() {
+        let finished = block.finish();
+        match stack.last_mut() {
+            Some(parent) => parent.children.push(finished),
+            None => roots.push(finished),
+        }
+    }
+}
+
+/// Split a bullet line into its nesting level and content
+fn parse_bullet(line: &str) -> Option<(usize, &str)> {
+    let body = line.trim_start_matches([' ', '\t']);
+    let indent = &line[..line.len() - body.len()];
+    let level = indent
+        .chars()
+        .map(|c| if c == '\t' { 2 } else { 1 })
+        .sum::<usize>()
+        / 2;
+
+    let content = match body.strip_prefix(['*', '-'])? {
+        "" => "",
+        rest => rest.strip_prefix(' ')?,
+    };
+    Some((level, content))
+}
+
+/// Remove up to `indent` columns of leading whitespace
+fn strip_indent(line: &str, indent: usize) -> &str {
+    let mut columns = 0;
+    for (i, c) in line.char_indices() {
+        if columns >= indent {
+            return &line[i..];
+        }
+        match c {
+            ' ' => columns += 1,
+            '\t' => columns += 2,
+            _ => return &line[i..],
+        }
+    }
+    ""
+}
+
+/// Open or close a code fence for the next line of a block
+fn track_fence(fence: &mut Option<String>, line: &str) {
+    let trimmed = line.trim_start();
+    match fence {
+        Some(open) if trimmed.starts_with(open.as_str()) => *fence = None,
+        Some(_) => {}
+        None => *fence = fence_marker(trimmed),
+    }
+}
+
+/// The fence that a line opens, if it starts a fenced code block
+fn fence_marker(line: &str) -> Option<String> {
+    ["```", "~~~"].iter().find_map(|marker| {
+        line.starts_with(marker).then(|| {
+            let ch = marker.chars().next().unwrap_or('`');
+            line.chars().take_while(|c| *c == ch).collect()
+        })
+    })
+}
+
+/// The property LogSeq uses to number a block as part of an ordered list
+const ORDERED_LIST_PROPERTY: &str = "logseq.order-list-type:: number";
+
+/// A list item being assembled by [`parse_document`]
+struct ListItem {
+    /// Column its text starts at; lines indented this far continue it
+    content_indent: usize,
+    ordered: bool,
+    lines: Vec<String>,
+    fence: Option<String>,
+    children: Vec<BatchBlock>,
+}
+
+impl ListItem {
+    fn push_line(&mut self, line: &str) {
+        track_fence(&mut self.fence, line);
+        self.lines.push(line.to_string());
+    }
+
+    fn finish(self) -> BatchBlock {
+        let mut content = self.lines.join("\n").trim_end().to_string();
+        if self.ordered {
+            content.push('\n');
+            content.push_str(ORDERED_LIST_PROPERTY);
+        }
+        BatchBlock {
+            content,
+            children: self.children,
+        }
+    }
+}
+
+/// Builds the block tree for [`parse_document`]
+#[derive(Default)]
+struct DocumentBuilder {
+    roots: Vec<BatchBlock>,
+    /// Open headings with their levels, outermost first
+    headings: Vec<(usize, BatchBlock)>,
+    paragraph: Vec<String>,
+    /// An open top-level code fence and its lines
+    fence: Option<(String, Vec<String>)>,
+    /// Open list items, outermost first
+    items: Vec<ListItem>,
+    /// Blank lines seen inside the current list item
+    blank_lines: usize,
+}
+
+impl DocumentBuilder {
+    /// Add a finished block under the innermost open heading
+    fn emit(&mut self, block: BatchBlock) {
+        match self.headings.last_mut() {
+            Some((_, heading)) => heading.children.push(block),
+            None => self.roots.push(block),
+        }
+    }
+
+    fn flush_paragraph(&mut self) {
+        if !self.paragraph.is_empty() {
+            let content = std::mem::take(&mut self.paragraph).join("\n");
+            self.emit(BatchBlock {
+                content,
+                children: Vec::new(),
+            });
+        }
+    }
+
+    fn close_item(&mut self) {
+        if let Some(item) = self.items.pop() {
+            let block = item.finish();
+            match self.items.last_mut() {
+                Some(parent) => parent.children.push(block),
+                None => self.emit(block),
+            }
+        }
+    }
+
+    fn close_list(&mut self) {
+        while !self.items.is_empty() {
+            self.close_item();
+        }
+        self.blank_lines = 0;
+    }
+
+    fn open_heading(&mut self, level: usize, content: &str) {
+        self.flush_paragraph();
+        self.close_list();
+        while self.headings.last().is_some_and(|(open, _)| *open >= level) {
+            self.close_heading();
+        }
+        self.headings.push((
+            level,
+            BatchBlock {
+                content: content.to_string(),
+                children: Vec::new(),
+            },
+        ));
+    }
+
+    fn close_heading(&mut self) {
+        if let Some((_, heading)) = self.headings.pop() {
+            self.emit(heading);
+        }
+    }
+
+    fn open_item(&mut self, item: ListMarker<'_>) {
+        self.flush_paragraph();
+        while self
+            .items
+            .last()
+            .is_some_and(|open| open.content_indent > item.indent)
+        {
+            self.close_item();
+        }
+        self.blank_lines = 0;
+        let mut open = ListItem {
+            content_indent: item.content_indent,
+            ordered: item.ordered,
+            lines: Vec::new(),
+            fence: None,
+            children: Vec::new(),
+        };
+        open.push_line(item.text);
+        self.items.push(open);
+    }
+
+    /// Add a line to the innermost list item, returning false if the line
+    /// ends the list instead
+    fn continue_item(&mut self, line: &str) -> bool {
+        let Some(item) = self.items.last() else {
+            return false;
+        };
+        if item.fence.is_none() {
+            // After a blank line, or for a fence, only indentation ties the
+            // line to an item; otherwise it's a lazy continuation of the last
+            let trimmed = line.trim_start();
+            let indent = line.len() - trimmed.len();
+            if self.blank_lines > 0 || fence_marker(trimmed).is_some() {
+                while self
+                    .items
+                    .last()
+                    .is_some_and(|item| item.content_indent > indent)
+                {
+                    self.close_item();
+                }
+            }
+        }
+        let Some(item) = self.items.last_mut() else {
+            return false;
+        };
+        for _ in 0..std::mem::take(&mut self.blank_lines) {
+            item.lines.push(String::new());
+        }
+        item.push_line(strip_indent(line, item.content_indent));
+        true
+    }
+
+    fn push_line(&mut self, line: &str) {
+        if let Some((marker, lines)) = &mut self.fence {
+            lines.push(line.to_string());
+            if lines.len() > 1 && line.trim_start().starts_with(marker.as_str()) {
+                let content = lines.join("\n");
+                self.fence = None;
+                self.emit(BatchBlock {
+                    content,
+                    children: Vec::new(),
+                });
+            }
+            return;
+        }
+        if self.items.last().is_some_and(|item| item.fence.is_some()) {
+            self.continue_item(line);
+            return;
+        }
+
+        if line.trim().is_empty() {
+            self.flush_paragraph();
+            if !self.items.is_empty() {
+                self.blank_lines += 1;
+            }
+            return;
+        }
+        if let Some(level) = heading_level(line) {
+            self.open_heading(level, line.trim());
+            return;
+        }
+        if is_thematic_break(line) {
+            self.flush_paragraph();
+            self.close_list();
+            self.emit(BatchBlock {
+                content: line.trim().to_string(),
+                children: Vec::new(),
+            });
+            return;
+        }
+        if let Some(item) = parse_list_marker(line) {
+            self.open_item(item);
+            return;
+        }
+        if self.continue_item(line) {
+            return;
+        }
+        self.close_list();
+        if let Some(marker) = fence_marker(line.trim_start()) {
+            self.flush_paragraph();
+            self.fence = Some((marker, vec![line.to_string()]));
+            return;
+        }
+        self.paragraph.push(line.trim_end().to_string());
+    }
+
+    fn finish(mut self) -> Vec<BatchBlock> {
+        if let Some((_, lines)) = self.fence.take() {
+            // An unterminated fence runs to the end of the document
+            self.emit(BatchBlock {
+                content: lines.join("\n"),
+                children: Vec::new(),
+            });
+        }
+        self.flush_paragraph();
+        self.close_list();
+        while !self.headings.is_empty() {
+            self.close_heading();
+        }
+        self.roots
+    }
+}
+
+/// Parse a markdown document into a block tree.
+///
+/// Each heading becomes a block whose children are the content up to the
+/// next heading of the same or a higher level, so sections nest as they do
+/// in the document. Paragraphs, code fences, and tables become a block each,
+/// and list items become blocks nested as the list is, numbered items
+/// keeping their numbering through LogSeq's ordered list property.
+pub fn parse_document(markdown: &str) -> Vec<BatchBlock> {
+    let mut builder = DocumentBuilder::default();
+    let mut lines = markdown.lines();
+    if let Some(properties) = front_matter(markdown) {
+        // Skip the properties and both `---` lines
+        lines.nth(properties.len() + 1);
+        if !properties.is_empty() {
+            builder.emit(BatchBlock {
+                content: properties.join("\n"),
+                children: Vec::new(),
+            });
+        }
+    }
+    for line in lines {
+        builder.push_line(line);
+    }
+    builder.finish()
+}
+
+/// YAML front matter of simple `key: value` lines, as `key:: value`
+/// property lines, which LogSeq reads as page properties when they make up
+/// a page's first block
+fn front_matter(markdown: &str) -> Option<Vec<String>> {
+    let mut lines = markdown.lines();
+    if lines.next()?.trim_end() != "---" {
+        return None;
+    }
+    let mut properties = Vec::new();
+    for line in lines {
+        if line.trim_end() == "---" {
+            return Some(properties);
+        }
+        let (key, value) = line.split_once(':')?;
+        let key = key.trim();
+        if key.is_empty() || key.contains(char::is_whitespace) {
+            return None;
+        }
+        properties.push(format!(
+            "{}:: {}",
+            key,
+            value.trim().trim_matches(['"', '[', ']'])
+        ));
+    }
+    None
+}
+
+/// The level of an ATX heading such as `## Title`
+fn heading_level(line: &str) -> Option<usize> {
+    let body = line.trim_start();
+    if line.len() - body.len() > 3 {
+        return None;
+    }
+    let level = body.chars().take_while(|c| *c == '#').count();
+    let rest = &body[level..];
+    ((1..=6).contains(&level) && (rest.is_empty() || rest.starts_with(' '))).then_some(level)
+}
+
+/// A `---`, `***`, or `- - -` style horizontal rule
+fn is_thematic_break(line: &str) -> bool {
+    let mut marks = line.chars().filter(|c| !c.is_whitespace());
+    let Some(first) = marks.next() else {
+        return false;
+    };
+    matches!(first, '-' | '*' | '_') && marks.clone().all(|c| c == first) && marks.count() >= 2
+}
+
+/// A list item's marker, as found by [`parse_list_marker`]
+struct ListMarker<'a> {
+    indent: usize,
+    content_indent: usize,
+    ordered: bool,
+    text: &'a str,
+}
+
+/// Recognise a `-`, `*`, `+`, or `1.`/`1)` list item
+fn parse_list_marker(line: &str) -> Option<ListMarker<'_>> {
+    let body = line.trim_start_matches(' ');
+    let indent = line.len() - body.len();
+    let digits = body.chars().take_while(char::is_ascii_digit).count();
+    let (marker_len, ordered) = match body.chars().next()? {
+        '-' | '*' | '+' => (1, false),
+        _ if (1..=9).contains(&digits) && body[digits..].starts_with(['.', ')']) => {
+            (digits + 1, true)
+        }
+        _ => return None,
+    };
+    let rest = &body[marker_len..];
+    let text = match rest {
+        "" => "",
+        _ => rest.strip_prefix(' ')?,
+    };
+    Some(ListMarker {
+        indent,
+        content_indent: indent + marker_len + 1,
+        ordered,
+        text,
+    })
+}
+
+#[cfg(all(test, feature = "server"))]
+mod tests {
+    use super::*;
+    use crate::tools::format_blocks_as_markdown;
+    use proptest::prelude::*;
+
+    fn block(content: &str, children: Vec<BatchBlock>) -> BatchBlock {
+        BatchBlock {
+            content: content.to_string(),
+            children,
+        }
+    }
+
+    fn to_blocks(batch: &[BatchBlock]) -> Vec<Block> {
+        batch
+            .iter()
+            .enumerate()
+            .map(|(i, b)| {
+                let mut block = Block::new(format!("uuid-{}", i), b.content.clone());
+                block.children = to_blocks(&b.children);
+                block
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_parse_nested_outline() {
+        let markdown = "* one\n  * two\n    * three\n  * four\n* five\n";
+        assert_eq!(
+            parse_outline(markdown),
+            vec![
+                block(
+                    "one",
+                    vec![
+                        block("two", vec![block("three", vec![])]),
+                        block("four", vec![])
+                    ]
+                ),
+                block("five", vec![]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_multiline_content_and_properties() {
+        let markdown = "- Page notes\n  tags:: rust, mcp\n  - child\n    second line\n";
+        assert_eq!(
+            parse_outline(markdown),
+            vec![block(
+                "Page notes\ntags:: rust, mcp",
+                vec![block("child\nsecond line", vec![])]
+            )]
+        );
+    }
+
+    #[test]
+    fn test_bullets_inside_code_fences_are_content() {
+        let markdown = "* Example\n  ```md\n  * not a block\n      - nor this\n  ```\n* next\n";
+        assert_eq!(
+            parse_outline(markdown),
+            vec![
+                block("Example\n```md\n* not a block\n    - nor this\n```", vec![]),
+                block("next", vec![]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_preamble_tabs_and_over_indentation() {
+        let markdown = "Intro text\n\n- a\n\t- b\n\t\t\t- c\n";
+        assert_eq!(
+            parse_outline(markdown),
+            vec![
+                block("Intro text", vec![]),
+                block("a", vec![block("b", vec![block("c", vec![])])]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_document_headings_nest_sections() {
+        let markdown = "Intro\n\n# One\nFirst paragraph\nstill first\n\nSecond\n## One.A\n- a\n# Two\n### Deep\ntext\n";
+        assert_eq!(
+            parse_document(markdown),
+            vec![
+                block("Intro", vec![]),
+                block(
+                    "# One",
+                    vec![
+                        block("First paragraph\nstill first", vec![]),
+                        block("Second", vec![]),
+                        block("## One.A", vec![block("a", vec![])]),
+                    ]
+                ),
+                block(
+                    "# Two",
+                    vec![block("### Deep", vec![block("text", vec![])])]
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_document_lists_and_code() {
+        let markdown = "\
+- one
+    - nested
+      more of nested
+
+  back in one
+* two
+1. first
+2. second
+   ```sh
+   - not an item
+   ```
+---
+```rust
+# not a heading
+```
+";
+        let ordered = |text: &str| format!("{}\n{}", text, ORDERED_LIST_PROPERTY);
+        assert_eq!(
+            parse_document(markdown),
+            vec![
+                block(
+                    "one\n\nback in one",
+                    vec![block("nested\nmore of nested", vec![])]
+                ),
+                block("two", vec![]),
+                block(&ordered("first"), vec![]),
+                block(&ordered("second\n```sh\n- not an item\n```"), vec![]),
+                block("---", vec![]),
+                block("```rust\n# not a heading\n```", vec![]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_document_front_matter_becomes_properties() {
+        let markdown = "---\ntitle: \"Notes\"\ntags: [rust, mcp]\n---\n# Body\n";
+        assert_eq!(
+            parse_document(markdown),
+            vec![
+                block("title:: Notes\ntags:: rust, mcp", vec![]),
+                block("# Body", vec![]),
+            ]
+        );
+    }
+
+    /// A line of block text that can't be mistaken for a bullet or fence
+    fn text_line() -> impl Strategy<Value = String> {
+        "[a-zA-Z0-9#\\[(àéß中文🎉][a-zA-Z0-9 #\\[\\]()*:.,!?àéß中文🎉-]{0,24}"
+    }
+
+    fn property_line() -> impl Strategy<Value = String> {
+        ("[a-z][a-z-]{0,8}", "[a-zA-Z0-9 ,中文]{1,12}").prop_map(|(k, v)| format!("{}:: {}", k, v))
+    }
+
+    /// Fenced code whose body may contain bullets, indentation, and blank lines
+    fn code_fence() -> impl Strategy<Value = String> {
+        let code_line = prop_oneof![
+            "[a-z0-9 (){};=]{0,20}",
+            "[ ]{0,6}[*-] [a-z ]{0,10}",
+            Just(String::new()),
+        ];
+        ("[a-z]{0,6}", prop::collection::vec(code_line, 0..5)).prop_map(|(lang, lines)| {
+            let mut fence = format!("```{}", lang);
+            for line in lines {
+                fence.push('\n');
+                fence.push_str(&line);
+            }
+            fence.push_str("\n```");
+            fence
+        })
+    }
+
+    fn paragraph() -> impl Strategy<Value = String> {
+        prop::collection::vec(text_line(), 1..3).prop_map(|lines| lines.join("\n"))
+    }
+
+    /// Block content: a paragraph optionally followed by more paragraphs,
+    /// properties, and code fences
+    fn content() -> impl Strategy<Value = String> {
+        let extra = prop_oneof![
+            paragraph().prop_map(|p| format!("\n\n{}", p)),
+            property_line().prop_map(|p| format!("\n{}", p)),
+            code_fence().prop_map(|f| format!("\n{}", f)),
+        ];
+        (paragraph(), prop::collection::vec(extra, 0..3))
+            .prop_map(|(first, rest)| format!("{}{}", first, rest.concat()))
+    }
+
+    fn outline() -> impl Strategy<Value = Vec<BatchBlock>> {
+        let leaf = content().prop_map(|content| BatchBlock {
+            content,
+            children: vec![],
+        });
+        let tree = leaf.prop_recursive(4, 32, 4, |inner| {
+            (content(), prop::collection::vec(inner, 0..4))
+                .prop_map(|(content, children)| BatchBlock { content, children })
+        });
+        prop::collection::vec(tree, 0..5)
+    }
+
+    proptest! {
+        #[test]
+        fn prop_blocks_survive_markdown_round_trip(blocks in outline()) {
+            let markdown = format_blocks_as_markdown(&to_blocks(&blocks));
+            prop_assert_eq!(parse_outline(&markdown), blocks, "markdown:\n{}", markdown);
+        }
+    }
+}
@@ -0,0 +1,140 @@
+//! Three-way merging of sequences, as `diff3` does for lines
+//!
+//! Given a common `base` and two edited versions of it, [`merge3`] keeps
+//! every change either side made, and reports the stretches both changed in
+//! different ways as conflicts.
+
+use std::ops::Range;
+
+/// A stretch changed differently on both sides, as ranges of each sequence
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Conflict {
+    pub base: Range<usize>,
+    pub current: Range<usize>,
+    pub incoming: Range<usize>,
+}
+
+/// Merge the changes `current` and `incoming` each made to `base`. Where
+/// only one side changed a stretch, its version wins; where both changed it
+/// the same way, that version is kept once.
+pub fn merge3<T: PartialEq + Clone>(
+    base: &[T],
+    current: &[T],
+    incoming: &[T],
+) -> Result<Vec<T>, Vec<Conflict>> {
+    let in_current = matches(base, current);
+    let in_incoming = matches(base, incoming);
+
+    let mut merged = Vec::new();
+    let mut conflicts = Vec::new();
+    let (mut b, mut c, mut i) = (0, 0, 0);
+    while b < base.len() || c < current.len() || i < incoming.len() {
+        // The next base item both sides kept, which ends this stretch
+        let stable = (b..base.len()).find_map(|k| Some((k, in_current[k]?, in_incoming[k]?)));
+        let (end_b, end_c, end_i) = stable.unwrap_or((base.len(), current.len(), incoming.len()));
+        if (end_b, end_c, end_i) == (b, c, i) {
+            merged.push(current[c].clone());
+            b += 1;
+            c += 1;
+            i += 1;
+            continue;
+        }
+
+        let (was, ours, theirs) = (&base[b..end_b], &current[c..end_c], &incoming[i..end_i]);
+        if ours == was || ours == theirs {
+            merged.extend_from_slice(theirs);
+        } else if theirs == was {
+            merged.extend_from_slice(ours);
+        } else {
+            conflicts.push(Conflict {
+                base: b..end_b,
+                current: c..end_c,
+                incoming: i..end_i,
+            });
+        }
+        (b, c, i) = (end_b, end_c, end_i);
+    }
+
+    match conflicts.is_empty() {
+        true => Ok(merged),
+        false => Err(conflicts),
+    }
+}
+
+/// For each item of `base`, the index of the item it's paired with in a
+/// longest common subsequence of `base` and `other`, if any
+fn matches<T: PartialEq>(base: &[T], other: &[T]) -> Vec<Option<usize>> {
+    // lengths[i][j]: the longest common subsequence of base[i..] and other[j..]
+    let width = other.len() + 1;
+    let mut lengths = vec![0u32; (base.len() + 1) * width];
+    for i in (0..base.len()).rev() {
+        for j in (0..other.len()).rev() {
+            lengths[i * width + j] = if base[i] == other[j] {
+                lengths[(i + 1) * width + j + 1] + 1
+            } else {
+                lengths[(i + 1) * width + j].max(lengths[i * width + j + 1])
+            };
+        }
+    }
+
+    let mut paired = vec![None; base.len()];
+    let (mut i, mut j) = (0, 0);
+    while i < base.len() && j < other.len() {
+        if base[i] == other[j] {
+            paired[i] = Some(j);
+            i += 1;
+            j += 1;
+        } else if lengths[(i + 1) * width + j] >= lengths[i * width + j + 1] {
+            i += 1;
+        } else {
+            j += 1;
+        }
+    }
+    paired
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(text: &str) -> Vec<&str> {
+        text.split_whitespace().collect()
+    }
+
+    #[test]
+    fn test_changes_on_either_side_are_kept() {
+        let base = lines("a b c d e");
+        let current = lines("a B c note d e");
+        let incoming = lines("a b c d E");
+        assert_eq!(
+            merge3(&base, &current, &incoming).unwrap(),
+            lines("a B c note d E")
+        );
+
+        // Both sides making the same change isn't a conflict
+        let same = lines("a b x d e");
+        assert_eq!(merge3(&base, &same, &same).unwrap(), same);
+        assert_eq!(merge3(&base, &base, &base).unwrap(), base);
+        assert_eq!(merge3(&[], &lines("x"), &[]).unwrap(), lines("x"));
+    }
+
+    #[test]
+    fn test_overlapping_changes_conflict() {
+        let base = lines("a b c");
+        let current = lines("a mine c");
+        let incoming = lines("a theirs c");
+        assert_eq!(
+            merge3(&base, &current, &incoming).unwrap_err(),
+            vec![Conflict {
+                base: 1..2,
+                current: 1..2,
+                incoming: 1..2,
+            }]
+        );
+
+        // Deleting what the other side changed is a conflict too
+        let deleted = lines("a c");
+        let conflicts = merge3(&base, &deleted, &incoming).unwrap_err();
+        assert_eq!(conflicts[0].current, 1..1);
+    }
+}
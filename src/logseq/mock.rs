@@ -0,0 +1,1265 @@
+//! An in-memory stand-in for LogSeq's HTTP API, for tests and benchmarks
+//!
+//! [`MockLogSeq`] serves `POST /api` on a local port from a [`MockGraph`], so
+//! the client and every tool can be exercised without a running LogSeq and
+//! without touching a real graph:
+//!
+//! ```no_run
+//! # async fn example() -> std::io::Result<()> {
+//! use logseq_mcp_server::logseq::mock::{MockGraph, MockLogSeq};
+//!
+//! let mut graph = MockGraph::new();
+//! graph.add_page("Projects");
+//! graph.add_block("Projects", "TODO Write the report");
+//! let logseq = MockLogSeq::serve(graph).await?;
+//! let client = logseq.client();
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Writes made through the API update the graph, so a block inserted by one
+//! call shows up in the next read. Datascript queries are emulated only for
+//! the shapes this crate sends; any other query returns no rows unless a
+//! canned response is registered with [`MockLogSeq::respond`].
+//!
+//! Available to this crate's unit tests, and elsewhere with the `mock` feature.
+
+use crate::logseq::api::LogSeqClient;
+use crate::logseq::content::{
+    INCOMPLETE_MARKERS, extract_page_links, extract_tags, is_property_line, split_marker,
+};
+use axum::{
+    Json, Router,
+    extract::State,
+    http::{HeaderMap, StatusCode},
+    routing::post,
+};
+use serde_json::{Map, Value, json};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex, MutexGuard};
+
+/// The only bearer token the mock accepts; anything else gets a 401
+pub const MOCK_TOKEN: &str = "mock-token";
+
+struct MockPage {
+    id: u64,
+    name: String,
+    original_name: String,
+    uuid: String,
+    properties: Map<String, Value>,
+    roots: Vec<String>,
+    journal: bool,
+    /// Milliseconds since the Unix epoch
+    updated_at: i64,
+}
+
+struct MockBlock {
+    id: u64,
+    uuid: String,
+    content: String,
+    page: u64,
+    parent: Option<String>,
+    children: Vec<String>,
+    /// Milliseconds since the Unix epoch
+    created_at: i64,
+    /// When the content last changed, in milliseconds since the Unix epoch
+    updated_at: i64,
+}
+
+/// The pages and blocks a [`MockLogSeq`] serves
+#[derive(Default)]
+pub struct MockGraph {
+    pages: Vec<MockPage>,
+    blocks: HashMap<String, MockBlock>,
+    next_id: u64,
+}
+
+impl MockGraph {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a page, returning its UUID. Adding an existing page (matched
+    /// case-insensitively) returns the existing one.
+    pub fn add_page(&mut self, name: &str) -> String {
+        self.create_page(name, Map::new(), false)
+    }
+
+    /// Add a journal page, such as `Jan 31st, 2024`, returning its UUID
+    pub fn add_journal_page(&mut self, name: &str) -> String {
+        self.create_page(name, Map::new(), true)
+    }
+
+    /// Append a block under `parent`, a page name or UUID or a block UUID,
+    /// returning the new block's UUID.
+    ///
+    /// Panics if `parent` doesn't exist.
+    pub fn add_block(&mut self, parent: &str, content: &str) -> String {
+        self.insert(parent, content, false, false)
+            .unwrap_or_else(|| panic!("no page or block {:?} in the mock graph", parent))
+    }
+
+    pub fn has_page(&self, name_or_uuid: &str) -> bool {
+        self.find_page(name_or_uuid).is_some()
+    }
+
+    /// Set a page property, such as `alias`
+    ///
+    /// Panics if the page doesn't exist.
+    pub fn set_page_property(&mut self, page: &str, key: &str, value: Value) {
+        let index = self
+            .find_page(page)
+            .unwrap_or_else(|| panic!("no page {:?} in the mock graph", page));
+        self.pages[index].properties.insert(key.to_string(), value);
+    }
+
+    /// Original names of every page, in creation order
+    pub fn page_names(&self) -> Vec<&str> {
+        self.pages
+            .iter()
+            .map(|p| p.original_name.as_str())
+            .collect()
+    }
+
+    /// Backdate a block, as if it was written at `millis` since the Unix
+    /// epoch
+    ///
+    /// Panics if the block doesn't exist.
+    pub fn set_created_at(&mut self, uuid: &str, millis: i64) {
+        self.blocks
+            .get_mut(uuid)
+            .unwrap_or_else(|| panic!("no block {:?} in the mock graph", uuid))
+            .created_at = millis;
+    }
+
+    /// Backdate a block's last change to `millis` since the Unix epoch
+    ///
+    /// Panics if the block doesn't exist.
+    pub fn set_updated_at(&mut self, uuid: &str, millis: i64) {
+        self.blocks
+            .get_mut(uuid)
+            .unwrap_or_else(|| panic!("no block {:?} in the mock graph", uuid))
+            .updated_at = millis;
+    }
+
+    pub fn block_content(&self, uuid: &str) -> Option<&str> {
+        self.blocks.get(uuid).map(|b| b.content.as_str())
+    }
+
+    /// UUIDs of the top-level blocks of a page, or the children of a block
+    pub fn children(&self, parent: &str) -> Vec<String> {
+        match self.blocks.get(parent) {
+            Some(block) => block.children.clone(),
+            None => self
+                .find_page(parent)
+                .map(|i| self.pages[i].roots.clone())
+                .unwrap_or_default(),
+        }
+    }
+
+    fn next_id(&mut self) -> u64 {
+        self.next_id += 1;
+        self.next_id
+    }
+
+    fn find_page(&self, name_or_uuid: &str) -> Option<usize> {
+        let name = name_or_uuid.trim().to_lowercase();
+        self.pages
+            .iter()
+            .position(|p| p.name == name || p.uuid == name_or_uuid)
+    }
+
+    fn page_by_id(&self, id: u64) -> Option<&MockPage> {
+        self.pages.iter().find(|p| p.id == id)
+    }
+
+    fn create_page(&mut self, name: &str, properties: Map<String, Value>, journal: bool) -> String {
+        if let Some(i) = self.find_page(name) {
+            return self.pages[i].uuid.clone();
+        }
+        let page = MockPage {
+            id: self.next_id(),
+            name: name.trim().to_lowercase(),
+            original_name: name.trim().to_string(),
+            uuid: uuid::Uuid::new_v4().to_string(),
+            properties,
+            roots: Vec::new(),
+            journal,
+            updated_at: chrono::Utc::now().timestamp_millis(),
+        };
+        let uuid = page.uuid.clone();
+        self.pages.push(page);
+        uuid
+    }
+
+    fn delete_page(&mut self, name: &str) -> bool {
+        let Some(i) = self.find_page(name) else {
+            return false;
+        };
+        let page = self.pages.remove(i);
+        for root in page.roots {
+            self.drop_subtree(&root);
+        }
+        true
+    }
+
+    /// Insert a block relative to `src`: appended to a page, as the last
+    /// child of a block, or as a sibling after (or before) it
+    fn insert(&mut self, src: &str, content: &str, sibling: bool, before: bool) -> Option<String> {
+        let (page, parent, index) = if let Some(block) = self.blocks.get(src) {
+            if sibling {
+                let index = self.position(src)? + usize::from(!before);
+                (block.page, block.parent.clone(), index)
+            } else {
+                (block.page, Some(src.to_string()), block.children.len())
+            }
+        } else {
+            let page = &self.pages[self.find_page(src)?];
+            (page.id, None, page.roots.len())
+        };
+
+        Some(self.new_block(content, page, parent, index))
+    }
+
+    /// Add a top-level block to a page, first or last, as
+    /// `prependBlockInPage` and `appendBlockInPage` do
+    fn insert_in_page(&mut self, page: &str, content: &str, first: bool) -> Option<String> {
+        let page = &self.pages[self.find_page(page)?];
+        let index = if first { 0 } else { page.roots.len() };
+        let page = page.id;
+        Some(self.new_block(content, page, None, index))
+    }
+
+    fn new_block(
+        &mut self,
+        content: &str,
+        page: u64,
+        parent: Option<String>,
+        index: usize,
+    ) -> String {
+        let now = chrono::Utc::now().timestamp_millis();
+        let block = MockBlock {
+            id: self.next_id(),
+            uuid: uuid::Uuid::new_v4().to_string(),
+            content: content.to_string(),
+            page,
+            parent,
+            children: Vec::new(),
+            created_at: now,
+            updated_at: now,
+        };
+        let uuid = block.uuid.clone();
+        let (page, parent) = (block.page, block.parent.clone());
+        self.blocks.insert(uuid.clone(), block);
+        self.attach(&uuid, page, parent, index);
+        uuid
+    }
+
+    /// Insert `{content, children}` trees under `src`, or after it as
+    /// siblings, returning the UUIDs of the top-level blocks inserted
+    fn insert_batch(&mut self, src: &str, batch: &[Value], sibling: bool) -> Option<Vec<String>> {
+        self.blocks.get(src)?;
+        let mut inserted = Vec::new();
+        let mut previous = src.to_string();
+        for item in batch {
+            let content = item["content"].as_str().unwrap_or_default();
+            let uuid = match sibling {
+                true => self.insert(&previous, content, true, false)?,
+                false => self.insert(src, content, false, false)?,
+            };
+            let children = item["children"].as_array().cloned().unwrap_or_default();
+            if !children.is_empty() {
+                self.insert_batch(&uuid, &children, false)?;
+            }
+            previous = uuid.clone();
+            inserted.push(uuid);
+        }
+        Some(inserted)
+    }
+
+    /// The list a block sits in: its parent's children or its page's roots
+    fn siblings_mut(&mut self, page: u64, parent: Option<&str>) -> Option<&mut Vec<String>> {
+        match parent {
+            Some(parent) => self.blocks.get_mut(parent).map(|b| &mut b.children),
+            None => self
+                .pages
+                .iter_mut()
+                .find(|p| p.id == page)
+                .map(|p| &mut p.roots),
+        }
+    }
+
+    fn position(&self, uuid: &str) -> Option<usize> {
+        let block = self.blocks.get(uuid)?;
+        let siblings = match &block.parent {
+            Some(parent) => &self.blocks.get(parent)?.children,
+            None => &self.page_by_id(block.page)?.roots,
+        };
+        siblings.iter().position(|s| s == uuid)
+    }
+
+    fn attach(&mut self, uuid: &str, page: u64, parent: Option<String>, index: usize) {
+        if let Some(siblings) = self.siblings_mut(page, parent.as_deref()) {
+            siblings.insert(index.min(siblings.len()), uuid.to_string());
+        }
+        self.set_page(uuid, page);
+        if let Some(block) = self.blocks.get_mut(uuid) {
+            block.parent = parent;
+        }
+    }
+
+    fn detach(&mut self, uuid: &str) {
+        let Some(block) = self.blocks.get(uuid) else {
+            return;
+        };
+        let (page, parent) = (block.page, block.parent.clone());
+        if let Some(siblings) = self.siblings_mut(page, parent.as_deref()) {
+            siblings.retain(|s| s != uuid);
+        }
+    }
+
+    /// Move a block (and its children) onto another block's page
+    fn set_page(&mut self, uuid: &str, page: u64) {
+        let Some(block) = self.blocks.get_mut(uuid) else {
+            return;
+        };
+        block.page = page;
+        for child in block.children.clone() {
+            self.set_page(&child, page);
+        }
+    }
+
+    fn remove_block(&mut self, uuid: &str) -> bool {
+        if !self.blocks.contains_key(uuid) {
+            return false;
+        }
+        self.detach(uuid);
+        self.drop_subtree(uuid);
+        true
+    }
+
+    fn drop_subtree(&mut self, uuid: &str) {
+        if let Some(block) = self.blocks.remove(uuid) {
+            for child in block.children {
+                self.drop_subtree(&child);
+            }
+        }
+    }
+
+    fn move_block(&mut self, src: &str, target: &str, before: bool, children: bool) -> bool {
+        let Some(target_block) = self.blocks.get(target) else {
+            return false;
+        };
+        if src == target || !self.blocks.contains_key(src) || self.is_ancestor(src, target) {
+            return false;
+        }
+        let page = target_block.page;
+        let parent = target_block.parent.clone();
+
+        self.detach(src);
+        if children {
+            let index = self.blocks[target].children.len();
+            self.attach(src, page, Some(target.to_string()), index);
+        } else {
+            let index = self.position(target).unwrap_or(0) + usize::from(!before);
+            self.attach(src, page, parent, index);
+        }
+        true
+    }
+
+    fn is_ancestor(&self, ancestor: &str, uuid: &str) -> bool {
+        let mut current = self.blocks.get(uuid).and_then(|b| b.parent.clone());
+        while let Some(parent) = current {
+            if parent == ancestor {
+                return true;
+            }
+            current = self.blocks.get(&parent).and_then(|b| b.parent.clone());
+        }
+        false
+    }
+
+    fn upsert_property(&mut self, uuid: &str, key: &str, value: &Value) -> bool {
+        let Some(block) = self.blocks.get_mut(uuid) else {
+            return false;
+        };
+        let line = format!("{}:: {}", key, property_text(value));
+        let mut lines: Vec<String> = block.content.lines().map(String::from).collect();
+        match lines.iter().position(|l| property_key(l) == Some(key)) {
+            Some(i) => lines[i] = line,
+            None => lines.insert(lines.len().min(1), line),
+        }
+        block.content = lines.join("\n");
+        block.updated_at = chrono::Utc::now().timestamp_millis();
+        true
+    }
+
+    fn remove_property(&mut self, uuid: &str, key: &str) -> bool {
+        let Some(block) = self.blocks.get_mut(uuid) else {
+            return false;
+        };
+        block.content = block
+            .content
+            .lines()
+            .filter(|l| property_key(l) != Some(key))
+            .collect::<Vec<_>>()
+            .join("\n");
+        block.updated_at = chrono::Utc::now().timestamp_millis();
+        true
+    }
+
+    fn page_json(&self, page: &MockPage) -> Value {
+        json!({
+            "id": page.id,
+            "name": page.name,
+            "original-name": page.original_name,
+            "uuid": page.uuid,
+            "properties": page.properties,
+            "journal?": page.journal,
+            "updatedAt": page.updated_at,
+        })
+    }
+
+    /// A block as `getBlock` returns it: children as `["uuid", ...]` refs,
+    /// or as nested blocks when `nested`
+    fn block_json(&self, uuid: &str, nested: bool) -> Value {
+        let Some(block) = self.blocks.get(uuid) else {
+            return Value::Null;
+        };
+        let children: Vec<Value> = block
+            .children
+            .iter()
+            .map(|child| match nested {
+                true => self.block_json(child, true),
+                false => json!(["uuid", child]),
+            })
+            .collect();
+        let parent = block
+            .parent
+            .as_ref()
+            .and_then(|p| self.blocks.get(p))
+            .map_or(block.page, |p| p.id);
+        json!({
+            "id": block.id,
+            "uuid": block.uuid,
+            "content": block.content,
+            "page": {"id": block.page},
+            "parent": {"id": parent},
+            "properties": properties(&block.content),
+            "children": children,
+            "format": "markdown",
+        })
+    }
+
+    fn tree_json(&self, page: &MockPage) -> Value {
+        Value::Array(
+            page.roots
+                .iter()
+                .map(|root| self.block_json(root, true))
+                .collect(),
+        )
+    }
+
+    /// Blocks in page order, depth first
+    fn all_blocks(&self) -> Vec<&MockBlock> {
+        fn walk<'a>(graph: &'a MockGraph, uuids: &[String], out: &mut Vec<&'a MockBlock>) {
+            for uuid in uuids {
+                if let Some(block) = graph.blocks.get(uuid) {
+                    out.push(block);
+                    walk(graph, &block.children, out);
+                }
+            }
+        }
+        let mut out = Vec::with_capacity(self.blocks.len());
+        for page in &self.pages {
+            walk(self, &page.roots, &mut out);
+        }
+        out
+    }
+
+    /// `logseq.App.search`: case-insensitive substring hits
+    fn search(&self, query: &str, limit: usize) -> Value {
+        let needle = query.to_lowercase();
+        let blocks: Vec<Value> = self
+            .all_blocks()
+            .into_iter()
+            .filter(|b| b.content.to_lowercase().contains(&needle))
+            .take(limit)
+            .map(
+                |b| json!({"block/uuid": b.uuid, "block/content": b.content, "block/page": b.page}),
+            )
+            .collect();
+        json!({"blocks": blocks, "pages": [], "files": []})
+    }
+
+    /// Names of every tag set with a `tags::` property, sorted
+    fn all_tags(&self) -> Vec<String> {
+        let mut tags: Vec<String> = self
+            .all_blocks()
+            .iter()
+            .flat_map(|b| property_values(&b.content, "tags"))
+            .chain(
+                self.pages
+                    .iter()
+                    .flat_map(|p| match p.properties.get("tags") {
+                        Some(Value::String(tags)) => split_tags(tags),
+                        _ => Vec::new(),
+                    }),
+            )
+            .collect();
+        tags.sort();
+        tags.dedup();
+        tags
+    }
+
+    /// Answer the aggregate queries behind `get_graph_stats`
+    fn count(&self, query: &str) -> Vec<Value> {
+        let blocks = self.all_blocks();
+        if query.contains(":block/marker") {
+            let mut markers: Vec<(&str, usize)> = Vec::new();
+            for marker in blocks.iter().filter_map(|b| split_marker(&b.content).0) {
+                match markers.iter_mut().find(|(m, _)| *m == marker) {
+                    Some((_, count)) => *count += 1,
+                    None => markers.push((marker, 1)),
+                }
+            }
+            return markers.into_iter().map(|(m, n)| json!([m, n])).collect();
+        }
+        let count = if query.contains(":block/journal?") {
+            self.pages.iter().filter(|p| p.journal).count()
+        } else if query.contains(":block/refs") {
+            blocks
+                .iter()
+                .map(|b| {
+                    // `#[[tag]]` is already among the tags
+                    let links = b.content.matches("[[").count() - b.content.matches("#[[").count();
+                    extract_tags(&b.content).len() + links + b.content.matches("((").count()
+                })
+                .sum()
+        } else if query.contains(":block/tags") {
+            self.all_tags().len()
+        } else if query.contains(":block/name") {
+            self.pages.len()
+        } else {
+            blocks.len()
+        };
+        vec![json!([count])]
+    }
+
+    /// Page-to-page links, counting the blocks that make each
+    fn page_links(&self) -> Vec<Value> {
+        let mut links: BTreeMap<(String, String), u64> = BTreeMap::new();
+        for b in self.all_blocks() {
+            let Some(page) = self.page_by_id(b.page) else {
+                continue;
+            };
+            let mut targets: Vec<String> = extract_page_links(&b.content)
+                .iter()
+                .map(|link| link.to_lowercase())
+                .chain(extract_tags(&b.content))
+                .filter(|target| *target != page.name)
+                .collect();
+            targets.sort();
+            targets.dedup();
+            for target in targets {
+                *links.entry((page.name.clone(), target)).or_default() += 1;
+            }
+        }
+        links
+            .into_iter()
+            .map(|((from, to), count)| json!([from, to, count]))
+            .collect()
+    }
+
+    /// Answer the datascript queries this crate sends
+    fn query(&self, query: &str) -> Value {
+        let rows: Vec<Value> = if query.contains("?from ?to (count ?b)") {
+            self.page_links()
+        } else if query.contains("(count") {
+            self.count(query)
+        } else if query.contains(":block/created-at") {
+            let bound = |op: &str| {
+                between(query, &format!("[({} ?created ", op), ")]")
+                    .and_then(|n| n.parse::<i64>().ok())
+            };
+            let (from, to) = (
+                bound(">=").unwrap_or(i64::MIN),
+                bound("<").unwrap_or(i64::MAX),
+            );
+            self.all_blocks()
+                .into_iter()
+                .filter(|b| (from..to).contains(&b.created_at))
+                .map(|b| {
+                    json!([
+                        b.content,
+                        self.page_by_id(b.page).map(|p| p.original_name.as_str()),
+                        b.created_at,
+                    ])
+                })
+                .collect()
+        } else if query.contains(":block/deadline") {
+            self.all_blocks()
+                .into_iter()
+                .filter_map(|b| {
+                    let marker = split_marker(&b.content).0.unwrap_or_default();
+                    let scheduled = planned_day(&b.content, "SCHEDULED");
+                    let deadline = planned_day(&b.content, "DEADLINE");
+                    let open = !matches!(marker, "DONE" | "CANCELED" | "CANCELLED");
+                    (open && (scheduled > 0 || deadline > 0)).then(|| {
+                        json!([
+                            b.uuid,
+                            b.content,
+                            marker,
+                            self.page_by_id(b.page).map(|p| p.name.as_str()),
+                            scheduled,
+                            deadline,
+                        ])
+                    })
+                })
+                .collect()
+        } else if query.contains(":block/marker") {
+            let markers = between(query, "(contains? #{", "}");
+            let page = between(query, "[?p :block/name \"", "\"");
+            let tag = between(query, "[?t :block/name \"", "\"");
+            let wanted_priority = between(query, "[?b :block/priority \"", "\"");
+            self.all_blocks()
+                .into_iter()
+                .filter(|b| {
+                    page.is_none_or(|page| self.page_by_id(b.page).is_some_and(|p| p.name == page))
+                })
+                .filter(|b| tag.is_none_or(|tag| extract_tags(&b.content).iter().any(|t| t == tag)))
+                .filter(|b| wanted_priority.is_none_or(|p| priority(&b.content) == p))
+                .filter_map(|b| {
+                    let marker = split_marker(&b.content).0?;
+                    let wanted = match markers {
+                        Some(markers) => markers.contains(&format!("\"{}\"", marker)),
+                        None => INCOMPLETE_MARKERS.contains(&marker),
+                    };
+                    wanted.then(|| {
+                        json!([
+                            b.uuid,
+                            b.content,
+                            marker,
+                            self.page_by_id(b.page).map(|p| p.name.as_str()),
+                            priority(&b.content),
+                            planned_day(&b.content, "SCHEDULED"),
+                        ])
+                    })
+                })
+                .collect()
+        } else if let Some(target) = query
+            .contains(":block/refs")
+            .then(|| between(query, "#uuid \"", "\""))
+            .flatten()
+        {
+            let target = format!("(({}))", target);
+            self.all_blocks()
+                .into_iter()
+                .filter(|b| b.content.contains(&target))
+                .map(|b| {
+                    json!([
+                        b.uuid,
+                        b.content,
+                        self.page_by_id(b.page).map(|p| p.name.as_str()),
+                    ])
+                })
+                .collect()
+        } else if query.contains(":block/refs") {
+            let tag = between(query, "[?t :block/name \"", "\"").unwrap_or_default();
+            self.all_blocks()
+                .into_iter()
+                .filter(|b| extract_tags(&b.content).iter().any(|t| t == tag))
+                .map(|b| {
+                    json!([
+                        b.uuid,
+                        b.content,
+                        self.page_by_id(b.page).map(|p| p.name.as_str()),
+                    ])
+                })
+                .collect()
+        } else if query.contains(":block/tags") && query.contains("?page") {
+            let blocks = self.all_blocks().into_iter().flat_map(|b| {
+                property_values(&b.content, "tags")
+                    .into_iter()
+                    .map(|tag| json!([b.id, tag, ""]))
+            });
+            let pages = self.pages.iter().flat_map(|p| {
+                let tags = match p.properties.get("tags") {
+                    Some(Value::String(tags)) => split_tags(tags),
+                    _ => Vec::new(),
+                };
+                tags.into_iter().map(|tag| json!([p.id, tag, p.name]))
+            });
+            blocks.chain(pages).collect()
+        } else if query.contains(":block/tags") {
+            self.all_tags().into_iter().map(|t| json!([t])).collect()
+        } else if let Some(since) = between(query, "[(>= ?updated ", ")]") {
+            let since: i64 = since.parse().unwrap_or(i64::MIN);
+            self.all_blocks()
+                .into_iter()
+                .filter(|b| b.updated_at >= since)
+                .filter_map(|b| {
+                    let page = self.page_by_id(b.page)?;
+                    Some(json!([b.uuid, b.content, page.original_name, b.updated_at]))
+                })
+                .collect()
+        } else if query.contains(":block/updated-at") {
+            let uuids = between(query, "(contains? #{", "}").unwrap_or_default();
+            self.all_blocks()
+                .into_iter()
+                .filter(|b| uuids.contains(&format!("\"{}\"", b.uuid)))
+                .filter_map(|b| {
+                    let page = self.page_by_id(b.page)?;
+                    let parent = b.parent.clone().unwrap_or_else(|| page.uuid.clone());
+                    Some(json!([
+                        b.uuid,
+                        b.content,
+                        page.original_name,
+                        parent,
+                        b.parent.is_none(),
+                        b.updated_at,
+                    ]))
+                })
+                .collect()
+        } else if query.contains(":block/parent") {
+            let uuid = between(query, "#uuid \"", "\"").unwrap_or_default();
+            self.blocks
+                .get(uuid)
+                .and_then(|b| {
+                    let page = self.page_by_id(b.page)?;
+                    let parent = b.parent.clone().unwrap_or_else(|| page.uuid.clone());
+                    Some(json!([page.name, parent, b.parent.is_none()]))
+                })
+                .into_iter()
+                .collect()
+        } else if let Some((lowercase, needles)) = search_needles(query) {
+            let page = between(query, ":block/name \"", "\"");
+            self.all_blocks()
+                .into_iter()
+                .filter(|b| {
+                    page.is_none_or(|name| self.page_by_id(b.page).is_some_and(|p| p.name == name))
+                })
+                .filter(|b| {
+                    let content = match lowercase {
+                        true => b.content.to_lowercase(),
+                        false => b.content.clone(),
+                    };
+                    needles
+                        .iter()
+                        .all(|needle| content.contains(needle.as_str()))
+                })
+                .map(|b| {
+                    json!([
+                        b.uuid,
+                        b.content,
+                        self.page_by_id(b.page).map(|p| p.original_name.as_str())
+                    ])
+                })
+                .collect()
+        } else if query.contains(":block/original-name") {
+            let page = between(query, "[?p :block/name \"", "\"");
+            self.all_blocks()
+                .into_iter()
+                .filter_map(|b| {
+                    let p = self.page_by_id(b.page)?;
+                    page.is_none_or(|name| p.name == name)
+                        .then(|| json!([b.uuid, p.original_name, b.content]))
+                })
+                .collect()
+        } else {
+            Vec::new()
+        };
+        Value::Array(rows)
+    }
+
+    /// Dispatch one API call
+    fn handle(&mut self, method: &str, args: &[Value]) -> Result<Value, (StatusCode, String)> {
+        let arg = |i: usize| args.get(i).and_then(Value::as_str).unwrap_or_default();
+        let opt = |i: usize, key: &str| {
+            args.get(i)
+                .and_then(|o| o.get(key))
+                .and_then(Value::as_bool)
+                .unwrap_or(false)
+        };
+        let not_found = |what: &str| {
+            Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("{} not found: {}", what, arg(0)),
+            ))
+        };
+
+        let response = match method {
+            "logseq.Editor.getAllPages" => {
+                Value::Array(self.pages.iter().map(|p| self.page_json(p)).collect())
+            }
+            "logseq.Editor.getPagesFromNamespace" => {
+                let prefix = format!("{}/", arg(0).to_lowercase());
+                Value::Array(
+                    self.pages
+                        .iter()
+                        .filter(|p| p.name.starts_with(&prefix))
+                        .map(|p| self.page_json(p))
+                        .collect(),
+                )
+            }
+            "logseq.Editor.getPage" => self
+                .find_page(arg(0))
+                .map_or(Value::Null, |i| self.page_json(&self.pages[i])),
+            "logseq.Editor.createPage" => {
+                let properties = args
+                    .get(1)
+                    .and_then(Value::as_object)
+                    .cloned()
+                    .unwrap_or_default();
+                let uuid = self.create_page(arg(0), properties, opt(2, "journal"));
+                let i = self.find_page(&uuid).unwrap_or_default();
+                self.page_json(&self.pages[i])
+            }
+            "logseq.Editor.deletePage" => match self.delete_page(arg(0)) {
+                true => Value::Null,
+                false => return not_found("Page"),
+            },
+            "logseq.Editor.getPageBlocksTree" => self
+                .find_page(arg(0))
+                .map_or(Value::Null, |i| self.tree_json(&self.pages[i])),
+            "logseq.Editor.getBlock" => self.block_json(arg(0), opt(1, "includeChildren")),
+            "logseq.Editor.getCurrentPage"
+            | "logseq.Editor.getCurrentBlock"
+            | "logseq.Editor.getSelectedBlocks" => Value::Null,
+            "logseq.Editor.insertBlock" => {
+                match self.insert(arg(0), arg(1), opt(2, "sibling"), opt(2, "before")) {
+                    Some(uuid) => self.block_json(&uuid, false),
+                    None => Value::Null,
+                }
+            }
+            "logseq.Editor.appendBlockInPage" | "logseq.Editor.prependBlockInPage" => {
+                let first = method == "logseq.Editor.prependBlockInPage";
+                match self.insert_in_page(arg(0), arg(1), first) {
+                    Some(uuid) => self.block_json(&uuid, false),
+                    None => Value::Null,
+                }
+            }
+            "logseq.Editor.insertBatchBlock" => {
+                let batch = args
+                    .get(1)
+                    .and_then(Value::as_array)
+                    .cloned()
+                    .unwrap_or_default();
+                match self.insert_batch(arg(0), &batch, opt(2, "sibling")) {
+                    Some(uuids) => Value::Array(
+                        uuids
+                            .iter()
+                            .map(|uuid| self.block_json(uuid, false))
+                            .collect(),
+                    ),
+                    None => return not_found("Block"),
+                }
+            }
+            "logseq.Editor.updateBlock" => match self.blocks.get_mut(arg(0)) {
+                Some(block) => {
+                    block.content = arg(1).to_string();
+                    block.updated_at = chrono::Utc::now().timestamp_millis();
+                    Value::Null
+                }
+                None => return not_found("Block"),
+            },
+            "logseq.Editor.removeBlock" => match self.remove_block(arg(0)) {
+                true => Value::Null,
+                false => return not_found("Block"),
+            },
+            "logseq.Editor.moveBlock" => {
+                match self.move_block(arg(0), arg(1), opt(2, "before"), opt(2, "children")) {
+                    true => Value::Null,
+                    false => return not_found("Block"),
+                }
+            }
+            "logseq.Editor.getBlockProperty" => match self.blocks.get(arg(0)) {
+                Some(block) => properties(&block.content)
+                    .remove(arg(1))
+                    .unwrap_or(Value::Null),
+                None => return not_found("Block"),
+            },
+            "logseq.Editor.upsertBlockProperty" => {
+                let value = args.get(2).cloned().unwrap_or(Value::Null);
+                match self.upsert_property(arg(0), arg(1), &value) {
+                    true => Value::Null,
+                    false => return not_found("Block"),
+                }
+            }
+            "logseq.Editor.removeBlockProperty" => match self.remove_property(arg(0), arg(1)) {
+                true => Value::Null,
+                false => return not_found("Block"),
+            },
+            "logseq.App.search" => {
+                let limit = args
+                    .get(1)
+                    .and_then(|o| o.get("limit"))
+                    .and_then(Value::as_u64)
+                    .unwrap_or(100);
+                self.search(arg(0), limit as usize)
+            }
+            "logseq.App.getCurrentGraph" => json!({"name": "mock", "path": "/mock"}),
+            "logseq.App.getInfo" => json!({"version": "0.10.9"}),
+            "logseq.App.getUserConfigs" => json!({"preferredDateFormat": "MMM do, yyyy"}),
+            "logseq.App.getStateFromStore" => Value::Null,
+            "logseq.UI.showMsg" => json!("mock-msg"),
+            "logseq.App.pushState" | "logseq.Editor.scrollToBlockInPage" => Value::Null,
+            "logseq.DB.datascriptQuery" => self.query(arg(0)),
+            method => {
+                return Err((StatusCode::NOT_FOUND, format!("MethodNotExist: {}", method)));
+            }
+        };
+        Ok(response)
+    }
+}
+
+/// The key of a `key:: value` line
+fn property_key(line: &str) -> Option<&str> {
+    is_property_line(line).then(|| line.trim_start().split_once("::").map(|(k, _)| k))?
+}
+
+/// A block's properties, read from its `key:: value` lines
+fn properties(content: &str) -> Map<String, Value> {
+    content
+        .lines()
+        .filter_map(|line| {
+            let (key, value) = line.trim_start().split_once("::")?;
+            is_property_line(line).then(|| (key.to_string(), Value::from(value.trim())))
+        })
+        .collect()
+}
+
+fn property_values(content: &str, key: &str) -> Vec<String> {
+    match properties(content).get(key) {
+        Some(Value::String(values)) => split_tags(values),
+        _ => Vec::new(),
+    }
+}
+
+fn split_tags(values: &str) -> Vec<String> {
+    values
+        .split(',')
+        .map(|t| {
+            t.trim()
+                .trim_start_matches('#')
+                .trim_matches(['[', ']'])
+                .to_lowercase()
+        })
+        .filter(|t| !t.is_empty())
+        .collect()
+}
+
+fn property_text(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+/// The `A` of a `[#A]` priority, or "" as `get-else` would give
+fn priority(content: &str) -> &str {
+    between(content, "[#", "]")
+        .filter(|p| matches!(*p, "A" | "B" | "C"))
+        .unwrap_or_default()
+}
+
+/// A `SCHEDULED: <2024-01-31 ...>` or `DEADLINE: <...>` date as a journal
+/// day, or 0
+fn planned_day(content: &str, keyword: &str) -> u64 {
+    between(content, &format!("{}: <", keyword), ">")
+        .and_then(|date| date.get(..10))
+        .and_then(|date| date.replace('-', "").parse().ok())
+        .unwrap_or(0)
+}
+
+fn between<'a>(text: &'a str, start: &str, end: &str) -> Option<&'a str> {
+    let (_, rest) = text.split_once(start)?;
+    rest.split_once(end).map(|(inner, _)| inner)
+}
+
+/// The needles of a content search query, all of which a block must
+/// contain, and whether they match lower-cased content
+fn search_needles(query: &str) -> Option<(bool, Vec<String>)> {
+    let lowercase = query.contains("includes? ?lower \"");
+    let marker = match lowercase {
+        true => "includes? ?lower \"",
+        false => "includes? ?content \"",
+    };
+    let needles: Vec<String> = query
+        .split(marker)
+        .skip(1)
+        .filter_map(|rest| rest.split_once("\")]"))
+        .map(|(needle, _)| needle.replace("\\\"", "\"").replace("\\\\", "\\"))
+        .collect();
+    (!needles.is_empty()).then_some((lowercase, needles))
+}
+
+/// A request the mock received
+#[derive(Debug, Clone)]
+pub struct MockCall {
+    pub method: String,
+    pub args: Vec<Value>,
+}
+
+#[derive(Default)]
+struct MockState {
+    graph: MockGraph,
+    responses: HashMap<String, Result<Value, (StatusCode, String)>>,
+    calls: Vec<MockCall>,
+}
+
+/// A running mock LogSeq API, stopped when dropped
+pub struct MockLogSeq {
+    url: String,
+    state: Arc<Mutex<MockState>>,
+    server: tokio::task::JoinHandle<()>,
+}
+
+impl MockLogSeq {
+    /// Serve an empty graph
+    pub async fn start() -> std::io::Result<Self> {
+        Self::serve(MockGraph::new()).await
+    }
+
+    /// Serve `graph` on an ephemeral local port
+    pub async fn serve(graph: MockGraph) -> std::io::Result<Self> {
+        let state = Arc::new(Mutex::new(MockState {
+            graph,
+            ..Default::default()
+        }));
+        let app = Router::new()
+            .route("/api", post(handle_api))
+            .with_state(state.clone());
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+        let url = format!("http://{}", listener.local_addr()?);
+        let server = tokio::spawn(async move {
+            let _ = axum::serve(listener, app).await;
+        });
+        Ok(Self { url, state, server })
+    }
+
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// A client pointed at this mock
+    pub fn client(&self) -> LogSeqClient {
+        LogSeqClient::new(&self.url, MOCK_TOKEN).expect("mock URL is valid")
+    }
+
+    /// Set up or inspect the graph being served
+    pub fn with_graph<R>(&self, f: impl FnOnce(&mut MockGraph) -> R) -> R {
+        f(&mut self.lock().graph)
+    }
+
+    /// Answer every later call to `method` with `response` instead of
+    /// emulating it
+    pub fn respond(&self, method: &str, response: Value) {
+        self.lock()
+            .responses
+            .insert(method.to_string(), Ok(response));
+    }
+
+    /// Fail every later call to `method` with an HTTP error
+    pub fn fail(&self, method: &str, status: u16, body: &str) {
+        let status = StatusCode::from_u16(status).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+        self.lock()
+            .responses
+            .insert(method.to_string(), Err((status, body.to_string())));
+    }
+
+    /// Every call received so far, oldest first
+    pub fn calls(&self) -> Vec<MockCall> {
+        self.lock().calls.clone()
+    }
+
+    fn lock(&self) -> MutexGuard<'_, MockState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl Drop for MockLogSeq {
+    fn drop(&mut self) {
+        self.server.abort();
+    }
+}
+
+async fn handle_api(
+    State(state): State<Arc<Mutex<MockState>>>,
+    headers: HeaderMap,
+    Json(request): Json<Value>,
+) -> Result<Json<Value>, (StatusCode, String)> {
+    let authorized = headers
+        .get("Authorization")
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v == format!("Bearer {}", MOCK_TOKEN));
+    if !authorized {
+        return Err((StatusCode::UNAUTHORIZED, "Unauthorized".to_string()));
+    }
+
+    let method = request["method"].as_str().unwrap_or_default().to_string();
+    let args = request["args"].as_array().cloned().unwrap_or_default();
+    let mut state = state.lock().unwrap_or_else(|e| e.into_inner());
+    state.calls.push(MockCall {
+        method: method.clone(),
+        args: args.clone(),
+    });
+
+    let response = match state.responses.get(&method) {
+        Some(canned) => canned.clone(),
+        None => state.graph.handle(&method, &args),
+    };
+    response.map(Json)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::logseq::api::{InsertBlockOptions, MoveBlockOptions, SearchOptions, TodoFilter};
+    use crate::logseq::error::LogSeqError;
+
+    #[tokio::test]
+    async fn test_reads_reflect_writes() {
+        let mut graph = MockGraph::new();
+        graph.add_page("Projects");
+        let first = graph.add_block("Projects", "First");
+        graph.add_block(&first, "Nested");
+        let logseq = MockLogSeq::serve(graph).await.unwrap();
+        let client = logseq.client();
+
+        let inserted = client
+            .insert_block(
+                "Second",
+                InsertBlockOptions {
+                    parent: Some("projects".into()),
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+        client
+            .update_block(&inserted.uuid, "Second, edited", None)
+            .await
+            .unwrap();
+
+        let tree = client.get_page_blocks_tree("Projects").await.unwrap().items;
+        assert_eq!(tree.len(), 2);
+        assert_eq!(tree[0].children[0].content, "Nested");
+        assert_eq!(tree[1].content, "Second, edited");
+
+        client
+            .move_block(
+                &inserted.uuid,
+                &first,
+                MoveBlockOptions {
+                    before: Some(true),
+                    children: None,
+                },
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            logseq.with_graph(|g| g.children("Projects")),
+            vec![inserted.uuid.clone(), first]
+        );
+
+        client.remove_block(&inserted.uuid).await.unwrap();
+        client.delete_page("Projects").await.unwrap();
+        assert!(client.find_page("Projects").await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_block_properties_live_in_content() {
+        let mut graph = MockGraph::new();
+        graph.add_page("Tasks");
+        let uuid = graph.add_block("Tasks", "TODO Ship it");
+        let logseq = MockLogSeq::serve(graph).await.unwrap();
+        let client = logseq.client();
+
+        client
+            .upsert_block_property(&uuid, "status", &json!("active"))
+            .await
+            .unwrap();
+        assert_eq!(
+            logseq.with_graph(|g| g.block_content(&uuid).map(String::from)),
+            Some("TODO Ship it\nstatus:: active".to_string())
+        );
+        assert_eq!(
+            client.get_block_property(&uuid, "status").await.unwrap(),
+            Some(json!("active"))
+        );
+
+        client.remove_block_property(&uuid, "status").await.unwrap();
+        assert_eq!(
+            client.get_block_property(&uuid, "status").await.unwrap(),
+            None
+        );
+    }
+
+    #[tokio::test]
+    async fn test_emulated_queries() {
+        let mut graph = MockGraph::new();
+        graph.add_page("Work");
+        let parent = graph.add_block(
+            "Work",
+            "TODO [#A] Plan\nSCHEDULED: <2024-01-31 Wed>\ntags:: planning",
+        );
+        let child = graph.add_block(&parent, "DONE Kick off");
+        let logseq = MockLogSeq::serve(graph).await.unwrap();
+        let client = logseq.client();
+
+        let todos = client
+            .find_incomplete_todos(&TodoFilter::default())
+            .await
+            .unwrap();
+        assert_eq!(todos.len(), 1);
+        assert_eq!(todos[0].priority.as_deref(), Some("A"));
+        assert_eq!(todos[0].scheduled, Some(20240131));
+        assert_eq!(todos[0].page_name, "work");
+
+        assert_eq!(client.get_all_tags().await.unwrap(), vec!["planning"]);
+
+        let location = client.get_block_location(&child).await.unwrap().unwrap();
+        assert_eq!(location.parent.as_deref(), Some(parent.as_str()));
+
+        let options = SearchOptions {
+            case_sensitive: true,
+            ..Default::default()
+        };
+        let hits = client.search("Kick", &options).await.unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].page_name, "Work");
+        assert_eq!(hits[0].breadcrumb, vec!["TODO [#A] Plan"]);
+        assert!(hits[0].updated_at.is_some());
+        assert!(client.search("kick", &options).await.unwrap().is_empty());
+
+        let hits = client
+            .search("kick", &SearchOptions::default())
+            .await
+            .unwrap();
+        assert_eq!(hits[0].page_name, "Work");
+    }
+
+    #[tokio::test]
+    async fn test_canned_responses_failures_and_auth() {
+        let logseq = MockLogSeq::start().await.unwrap();
+        logseq.respond("logseq.App.getCurrentGraph", json!({"name": "canned"}));
+        logseq.fail("logseq.Editor.getAllPages", 500, "boom");
+
+        let client = logseq.client();
+        assert_eq!(client.get_current_graph().await.unwrap()["name"], "canned");
+        let err = client.get_all_pages().await.unwrap_err();
+        assert!(err.to_string().contains("boom"), "{}", err);
+
+        let calls = logseq.calls();
+        assert_eq!(calls.len(), 2);
+        assert_eq!(calls[1].method, "logseq.Editor.getAllPages");
+
+        let stranger = LogSeqClient::new(logseq.url(), "wrong").unwrap();
+        let err = stranger.get_current_graph().await.unwrap_err();
+        assert!(
+            matches!(err.downcast_ref(), Some(LogSeqError::Unauthorized { .. })),
+            "{}",
+            err
+        );
+    }
+}
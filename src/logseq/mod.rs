@@ -1 +1,25 @@
 pub mod api;
+pub mod assets;
+#[cfg(all(feature = "blocking", not(target_arch = "wasm32")))]
+pub mod blocking;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod cache;
+pub mod checksum;
+pub mod compat;
+pub mod content;
+pub mod datalog;
+pub mod duplicates;
+pub mod error;
+pub mod journal;
+pub mod link_graph;
+pub mod markdown;
+pub mod merge;
+#[cfg(any(test, feature = "mock"))]
+pub mod mock;
+pub mod page_name;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod payloads;
+pub mod region;
+pub mod taxonomy;
+pub mod transport;
+pub mod writing;
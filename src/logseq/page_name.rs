@@ -0,0 +1,112 @@
+//! Validation and normalisation of page names before they reach LogSeq
+
+use thiserror::Error;
+
+/// Built-in pages LogSeq creates or treats specially; creating them as
+/// ordinary pages collides with task, priority, and sidebar behaviour.
+pub const RESERVED_PAGE_NAMES: [&str; 17] = [
+    "contents",
+    "card",
+    "favorites",
+    "todo",
+    "doing",
+    "done",
+    "later",
+    "now",
+    "waiting",
+    "wait",
+    "canceled",
+    "cancelled",
+    "in-progress",
+    "started",
+    "a",
+    "b",
+    "c",
+];
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum PageNameError {
+    #[error("Page name must not be empty")]
+    Empty,
+    #[error(
+        "Page name contains '{0}', which breaks LogSeq link syntax or file names; remove it and try again"
+    )]
+    ForbiddenCharacter(char),
+    #[error("Page name must not start with '{0}'; LogSeq would treat it as a tag or hidden file")]
+    ForbiddenPrefix(char),
+    #[error("'{0}' is reserved by LogSeq and cannot be created as a regular page")]
+    Reserved(String),
+}
+
+/// Normalise a requested page name, or explain why it can't be created.
+///
+/// Whitespace is trimmed and collapsed, and namespace separators are tidied
+/// so that `Projects/` or `Projects//Alpha` don't create unintended empty
+/// namespace levels.
+pub fn normalize_page_name(name: &str) -> Result<String, PageNameError> {
+    if let Some(c) = name
+        .chars()
+        .find(|c| c.is_control() || matches!(c, '[' | ']' | '{' | '}'))
+    {
+        return Err(PageNameError::ForbiddenCharacter(c));
+    }
+
+    let normalized = name
+        .split('/')
+        .map(|segment| segment.split_whitespace().collect::<Vec<_>>().join(" "))
+        .filter(|segment| !segment.is_empty())
+        .collect::<Vec<_>>()
+        .join("/");
+
+    if normalized.is_empty() {
+        return Err(PageNameError::Empty);
+    }
+    if let Some(c) = normalized.chars().next().filter(|c| matches!(c, '#' | '.')) {
+        return Err(PageNameError::ForbiddenPrefix(c));
+    }
+    if RESERVED_PAGE_NAMES.contains(&normalized.to_lowercase().as_str()) {
+        return Err(PageNameError::Reserved(normalized));
+    }
+
+    Ok(normalized)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalizes_whitespace_and_namespaces() {
+        assert_eq!(
+            normalize_page_name("  Project   Alpha ").unwrap(),
+            "Project Alpha"
+        );
+        assert_eq!(normalize_page_name("Projects/").unwrap(), "Projects");
+        assert_eq!(
+            normalize_page_name("/Projects // Alpha/").unwrap(),
+            "Projects/Alpha"
+        );
+    }
+
+    #[test]
+    fn test_rejects_invalid_names() {
+        assert_eq!(normalize_page_name("   "), Err(PageNameError::Empty));
+        assert_eq!(normalize_page_name("//"), Err(PageNameError::Empty));
+        assert_eq!(
+            normalize_page_name("[[Nested]]"),
+            Err(PageNameError::ForbiddenCharacter('['))
+        );
+        assert_eq!(
+            normalize_page_name("line\nbreak"),
+            Err(PageNameError::ForbiddenCharacter('\n'))
+        );
+        assert_eq!(
+            normalize_page_name("#tag"),
+            Err(PageNameError::ForbiddenPrefix('#'))
+        );
+        assert_eq!(
+            normalize_page_name("TODO"),
+            Err(PageNameError::Reserved("TODO".to_string()))
+        );
+    }
+}
@@ -0,0 +1,203 @@
+//! A journal of LogSeq responses the client couldn't decode, for bug reports
+//!
+//! When a response doesn't fit the shape the client expects, usually because
+//! a LogSeq release changed it, the client saves it here as `<id>.json` and
+//! names the id in its error. Users can attach the file to a bug report so
+//! the failure can be reproduced exactly.
+//!
+//! Every string in the payload is redacted, so notes, page names, and paths
+//! never leave the user's machine. Object keys, numbers, booleans, and nulls
+//! are kept, as they are what decoding depends on. Only the newest entries
+//! are kept.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Shown in place of each string in a saved payload
+pub const REDACTED: &str = "<redacted>";
+
+/// Entries kept; the oldest are removed to make room
+const MAX_ENTRIES: usize = 100;
+
+/// A response that couldn't be decoded, with what was asked for
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FailedPayload {
+    pub id: String,
+    pub recorded_at: DateTime<Utc>,
+    /// The API method called, such as `logseq.Editor.getPage`
+    pub method: String,
+    /// Where the call went
+    pub endpoint: String,
+    /// This crate's version
+    pub version: String,
+    /// Why decoding failed, with any quoted values redacted
+    pub error: String,
+    /// LogSeq's response, redacted
+    pub payload: Value,
+}
+
+/// A directory of failed payloads, one `<id>.json` file each
+#[derive(Debug, Clone)]
+pub struct PayloadJournal {
+    dir: PathBuf,
+}
+
+impl PayloadJournal {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Save a response to `method`, sent to `endpoint`, that failed to
+    /// decode with `error`, returning its entry
+    pub fn record(
+        &self,
+        endpoint: &str,
+        method: &str,
+        error: &str,
+        payload: &Value,
+    ) -> io::Result<FailedPayload> {
+        fs::create_dir_all(&self.dir)?;
+
+        let recorded_at = Utc::now();
+        let suffix = uuid::Uuid::new_v4().simple().to_string();
+        let entry = FailedPayload {
+            id: format!("{}-{}", recorded_at.format("%Y%m%dT%H%M%S"), &suffix[..8]),
+            recorded_at,
+            method: method.to_string(),
+            endpoint: endpoint.to_string(),
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            error: redact_message(error),
+            payload: redact(payload),
+        };
+
+        // Write then rename so a crash never leaves a truncated entry behind
+        let path = self.entry_path(&entry.id);
+        let partial = path.with_extension("json.partial");
+        fs::write(&partial, serde_json::to_vec_pretty(&entry)?)?;
+        fs::rename(&partial, &path)?;
+        self.prune()?;
+        Ok(entry)
+    }
+
+    /// Where an entry is saved
+    pub fn entry_path(&self, id: &str) -> PathBuf {
+        self.dir.join(format!("{}.json", id))
+    }
+
+    /// Remove the oldest entries beyond [`MAX_ENTRIES`]. Ids start with the
+    /// time they were recorded, so they sort oldest first.
+    fn prune(&self) -> io::Result<()> {
+        let mut files: Vec<PathBuf> = fs::read_dir(&self.dir)?
+            .filter_map(|file| file.ok().map(|file| file.path()))
+            .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+            .collect();
+        if files.len() <= MAX_ENTRIES {
+            return Ok(());
+        }
+        files.sort();
+        for path in &files[..files.len() - MAX_ENTRIES] {
+            fs::remove_file(path)?;
+        }
+        Ok(())
+    }
+}
+
+/// `value` with every string replaced by [`REDACTED`]
+pub fn redact(value: &Value) -> Value {
+    match value {
+        Value::String(_) => Value::from(REDACTED),
+        Value::Array(items) => items.iter().map(redact).collect(),
+        Value::Object(fields) => fields
+            .iter()
+            .map(|(key, value)| (key.clone(), redact(value)))
+            .collect(),
+        other => other.clone(),
+    }
+}
+
+/// A decoding error with the strings it quotes, such as `invalid type:
+/// string "Call Sam"`, redacted
+fn redact_message(message: &str) -> String {
+    let mut redacted = String::with_capacity(message.len());
+    let mut parts = message.split('"');
+    redacted.push_str(parts.next().unwrap_or_default());
+    let mut quoted = true;
+    for part in parts {
+        redacted.push('"');
+        redacted.push_str(if quoted { REDACTED } else { part });
+        quoted = !quoted;
+    }
+    redacted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_redact_keeps_shape() {
+        assert_eq!(
+            redact(&json!({
+                "uuid": "64f0c2a1-0000",
+                "content": "Call Sam",
+                "id": 42,
+                "properties": {"tags": ["work"], "done": true},
+                "parent": null
+            })),
+            json!({
+                "uuid": REDACTED,
+                "content": REDACTED,
+                "id": 42,
+                "properties": {"tags": [REDACTED], "done": true},
+                "parent": null
+            })
+        );
+    }
+
+    #[test]
+    fn test_record_keeps_newest_entries() {
+        let dir = tempfile::tempdir().unwrap();
+        let journal = PayloadJournal::new(dir.path().join("payloads"));
+
+        let entry = journal
+            .record(
+                "http://localhost:12315",
+                "logseq.Editor.getPage",
+                "invalid type: string \"diary\", expected i64 at line 1",
+                &json!({"name": "diary", "id": "7"}),
+            )
+            .unwrap();
+        let saved: FailedPayload =
+            serde_json::from_slice(&fs::read(journal.entry_path(&entry.id)).unwrap()).unwrap();
+        assert_eq!(saved.method, "logseq.Editor.getPage");
+        assert_eq!(
+            saved.error,
+            "invalid type: string \"<redacted>\", expected i64 at line 1"
+        );
+        assert_eq!(saved.endpoint, "http://localhost:12315");
+        assert_eq!(saved.payload, json!({"name": REDACTED, "id": REDACTED}));
+        let text = fs::read_to_string(journal.entry_path(&entry.id)).unwrap();
+        assert!(!text.contains("diary"));
+
+        for _ in 0..MAX_ENTRIES + 5 {
+            journal
+                .record(
+                    "http://localhost:12315",
+                    "logseq.Editor.getPage",
+                    "bad",
+                    &Value::Null,
+                )
+                .unwrap();
+        }
+        assert_eq!(fs::read_dir(journal.dir()).unwrap().count(), MAX_ENTRIES);
+    }
+}
@@ -0,0 +1,581 @@
+//! Managed regions: runs of blocks on a page that a tool owns
+//!
+//! A region is the blocks between two sibling marker blocks,
+//! `<!-- mcp:begin name -->` and `<!-- mcp:end name -->`, which LogSeq
+//! renders as nothing. [`write_region`] replaces whatever is between the
+//! markers and leaves every other block on the page alone, so generated
+//! content such as a report can be rewritten as often as needed while the
+//! user keeps their own notes around it. The markers can be moved anywhere
+//! on the page, including under another block, as long as they stay
+//! siblings.
+//!
+//! Given the blocks it last wrote as a base, [`write_region`] merges rather
+//! than replaces: blocks the user edited, added, or removed since are kept
+//! as they are, unless the new content changes the same blocks, which is a
+//! [`RegionError::Conflict`].
+
+use crate::logseq::api::{Block, InsertBlockOptions, LogSeqClient};
+use crate::logseq::markdown::BatchBlock;
+use crate::logseq::merge::merge3;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum RegionError {
+    #[error("Region name '{0}' must be non-empty, without whitespace or '--'")]
+    InvalidName(String),
+    #[error(
+        "Region '{0}' has no '<!-- mcp:end {0} -->' block after its begin marker at the same level; add one after the region's last block, or remove the begin marker"
+    )]
+    Unterminated(String),
+    #[error("Region '{0}' appears more than once on the page; remove the extra markers")]
+    Duplicate(String),
+    #[error(
+        "Region '{name}' was edited on the page where this update would also change it, at block(s) {}",
+        blocks.join(", ")
+    )]
+    Conflict {
+        name: String,
+        /// UUIDs of the edited blocks, or of the block before an edit that
+        /// removed blocks
+        blocks: Vec<String>,
+    },
+}
+
+/// A region located in a page's block tree
+#[derive(Debug)]
+pub struct Region<'a> {
+    pub begin: &'a Block,
+    pub end: &'a Block,
+    /// The blocks between the markers
+    pub blocks: &'a [Block],
+}
+
+/// What [`write_region`] did
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RegionWrite {
+    /// UUID of the region's begin marker block
+    pub begin: String,
+    /// Whether the region was new, rather than replacing an existing one
+    pub created: bool,
+    /// Whether edits made on the page since the base were kept
+    pub merged: bool,
+    /// The blocks written, as the base for the next write
+    pub base: Vec<RegionLine>,
+}
+
+/// A block of a region without its children, at its depth within the
+/// region. Merges compare regions as lists of these.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RegionLine {
+    pub depth: usize,
+    pub content: String,
+}
+
+/// A tree of blocks as a list of lines, parents before their children
+pub fn flatten(blocks: &[BatchBlock]) -> Vec<RegionLine> {
+    fn walk(blocks: &[BatchBlock], depth: usize, lines: &mut Vec<RegionLine>) {
+        for block in blocks {
+            lines.push(RegionLine {
+                depth,
+                content: block.content.clone(),
+            });
+            walk(&block.children, depth + 1, lines);
+        }
+    }
+    let mut lines = Vec::new();
+    walk(blocks, 0, &mut lines);
+    lines
+}
+
+/// A region's blocks as lines, each with its UUID. `id::` properties are
+/// left out, as LogSeq adds them to blocks that are referenced.
+fn flatten_page_blocks(blocks: &[Block]) -> Vec<(RegionLine, String)> {
+    fn walk(blocks: &[Block], depth: usize, lines: &mut Vec<(RegionLine, String)>) {
+        for block in blocks {
+            let content: Vec<&str> = block
+                .content
+                .lines()
+                .filter(|line| !line.trim_start().starts_with("id:: "))
+                .collect();
+            let line = RegionLine {
+                depth,
+                content: content.join("\n").trim_end().to_string(),
+            };
+            lines.push((line, block.uuid.clone()));
+            walk(&block.children, depth + 1, lines);
+        }
+    }
+    let mut lines = Vec::new();
+    walk(blocks, 0, &mut lines);
+    lines
+}
+
+/// The tree of blocks a list of lines describes. A line deeper than the one
+/// before it can be is put directly under that one.
+pub fn unflatten(lines: &[RegionLine]) -> Vec<BatchBlock> {
+    let mut roots: Vec<BatchBlock> = Vec::new();
+    for line in lines {
+        let mut siblings = &mut roots;
+        for _ in 0..line.depth {
+            if siblings.is_empty() {
+                break;
+            }
+            siblings = &mut siblings.last_mut().unwrap().children;
+        }
+        siblings.push(BatchBlock {
+            content: line.content.clone(),
+            children: Vec::new(),
+        });
+    }
+    roots
+}
+
+pub fn begin_marker(name: &str) -> String {
+    format!("<!-- mcp:begin {} -->", name)
+}
+
+pub fn end_marker(name: &str) -> String {
+    format!("<!-- mcp:end {} -->", name)
+}
+
+fn validate_name(name: &str) -> Result<(), RegionError> {
+    if name.is_empty() || name.chars().any(char::is_whitespace) || name.contains("--") {
+        return Err(RegionError::InvalidName(name.to_string()));
+    }
+    Ok(())
+}
+
+/// Whether a block is the given marker. Only the first line counts, as
+/// LogSeq may add properties such as `id::` below it.
+fn is_marker(block: &Block, marker: &str) -> bool {
+    block.content.lines().next().map(str::trim) == Some(marker)
+}
+
+/// Find the region `name` anywhere in `blocks`, or `None` if the page
+/// doesn't have one yet
+pub fn find_region<'a>(blocks: &'a [Block], name: &str) -> Result<Option<Region<'a>>, RegionError> {
+    validate_name(name)?;
+    let begin = begin_marker(name);
+    let end = end_marker(name);
+
+    let mut found = None;
+    let mut pending = vec![blocks];
+    while let Some(siblings) = pending.pop() {
+        for (index, block) in siblings.iter().enumerate() {
+            if is_marker(block, &begin) {
+                if found.is_some() {
+                    return Err(RegionError::Duplicate(name.to_string()));
+                }
+                let rest = &siblings[index + 1..];
+                let length = rest
+                    .iter()
+                    .position(|b| is_marker(b, &end))
+                    .ok_or_else(|| RegionError::Unterminated(name.to_string()))?;
+                found = Some(Region {
+                    begin: block,
+                    end: &rest[length],
+                    blocks: &rest[..length],
+                });
+            }
+            pending.push(&block.children);
+        }
+    }
+    Ok(found)
+}
+
+/// Replace the contents of the region `name` on `page` with `blocks`,
+/// adding the region at the end of the page if it isn't there yet.
+///
+/// With the `base` a previous write returned, changes made on the page
+/// since are merged into `blocks` instead of being lost, and the write fails
+/// with [`RegionError::Conflict`] if `blocks` changes the same blocks.
+///
+/// The new blocks are inserted before the old ones are removed, so a failure
+/// part way through can leave both but never loses the region's markers.
+pub async fn write_region(
+    client: &LogSeqClient,
+    page: &str,
+    name: &str,
+    blocks: &[BatchBlock],
+    base: Option<&[RegionLine]>,
+) -> anyhow::Result<RegionWrite> {
+    let incoming = flatten(blocks);
+    let tree = client.get_page_blocks_tree(page).await?;
+    let Some(region) = find_region(&tree.items, name)? else {
+        let opts = InsertBlockOptions {
+            parent: Some(page.to_string()),
+            ..Default::default()
+        };
+        let begin = client.insert_block(&begin_marker(name), opts).await?.uuid;
+        let mut batch = blocks.to_vec();
+        batch.push(BatchBlock {
+            content: end_marker(name),
+            children: Vec::new(),
+        });
+        client.insert_batch_block(&begin, &batch, true).await?;
+        return Ok(RegionWrite {
+            begin,
+            created: true,
+            merged: false,
+            base: incoming,
+        });
+    };
+
+    let (current, uuids): (Vec<RegionLine>, Vec<String>) =
+        flatten_page_blocks(region.blocks).into_iter().unzip();
+    let merged = match base {
+        Some(base) => merge3(base, &current, &incoming).map_err(|conflicts| {
+            let blocks = conflicts
+                .iter()
+                .filter_map(|conflict| match conflict.current.is_empty() {
+                    false => Some(uuids[conflict.current.clone()].to_vec()),
+                    true => conflict
+                        .current
+                        .start
+                        .checked_sub(1)
+                        .map(|before| vec![uuids[before].clone()])
+                        .or_else(|| Some(vec![region.begin.uuid.clone()])),
+                })
+                .flatten()
+                .collect();
+            RegionError::Conflict {
+                name: name.to_string(),
+                blocks,
+            }
+        })?,
+        None => incoming.clone(),
+    };
+    let write = RegionWrite {
+        begin: region.begin.uuid.clone(),
+        created: false,
+        merged: merged != incoming,
+        base: incoming,
+    };
+    if merged == current {
+        return Ok(write);
+    }
+
+    let blocks = unflatten(&merged);
+    if !blocks.is_empty() {
+        client
+            .insert_batch_block(&region.begin.uuid, &blocks, true)
+            .await?;
+    }
+    for block in region.blocks {
+        client.remove_block(&block.uuid).await?;
+    }
+    Ok(write)
+}
+
+/// Add `block` as the last child of the heading block `heading` that opens
+/// the region `name` on `page`, for regions that grow one entry at a time.
+/// The region is added at the end of the page if it isn't there yet, and
+/// rewritten with just the heading and `block` if it doesn't start with the
+/// heading.
+pub async fn append_to_region(
+    client: &LogSeqClient,
+    page: &str,
+    name: &str,
+    heading: &str,
+    block: BatchBlock,
+) -> anyhow::Result<RegionWrite> {
+    let tree = client.get_page_blocks_tree(page).await?;
+    if let Some(region) = find_region(&tree.items, name)?
+        && let Some(first) = region.blocks.first()
+        && first.content.lines().next().map(str::trim) == Some(heading)
+    {
+        client
+            .insert_batch_block(&first.uuid, &[block], false)
+            .await?;
+        return Ok(RegionWrite {
+            begin: region.begin.uuid.clone(),
+            created: false,
+            merged: false,
+            base: Vec::new(),
+        });
+    }
+    let section = BatchBlock {
+        content: heading.to_string(),
+        children: vec![block],
+    };
+    write_region(client, page, name, &[section], None).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::logseq::mock::{MockGraph, MockLogSeq};
+
+    fn block(uuid: &str, content: &str, children: Vec<Block>) -> Block {
+        let mut block = Block::new(uuid.to_string(), content.to_string());
+        block.children = children;
+        block
+    }
+
+    #[test]
+    fn test_find_region_at_any_depth() {
+        let blocks = [
+            block("1", "Notes", vec![]),
+            block(
+                "2",
+                "Reports",
+                vec![
+                    block("3", "<!-- mcp:begin todo -->", vec![]),
+                    block("4", "Generated", vec![block("5", "Nested", vec![])]),
+                    block("6", "<!-- mcp:end todo -->\nid:: 6", vec![]),
+                ],
+            ),
+        ];
+
+        let region = find_region(&blocks, "todo").unwrap().unwrap();
+        assert_eq!(region.begin.uuid, "3");
+        assert_eq!(region.end.uuid, "6");
+        assert_eq!(region.blocks.len(), 1);
+        assert!(find_region(&blocks, "other").unwrap().is_none());
+
+        let empty = [
+            block("1", "<!-- mcp:begin todo -->", vec![]),
+            block("2", "<!-- mcp:end todo -->", vec![]),
+        ];
+        let region = find_region(&empty, "todo").unwrap().unwrap();
+        assert!(region.blocks.is_empty());
+    }
+
+    #[test]
+    fn test_find_region_rejects_broken_markers() {
+        let unterminated = [
+            block("1", "<!-- mcp:begin todo -->", vec![]),
+            block(
+                "2",
+                "Parent",
+                vec![block("3", "<!-- mcp:end todo -->", vec![])],
+            ),
+        ];
+        assert_eq!(
+            find_region(&unterminated, "todo").unwrap_err(),
+            RegionError::Unterminated("todo".into())
+        );
+
+        let twice = [
+            block("1", "<!-- mcp:begin todo -->", vec![]),
+            block("2", "<!-- mcp:end todo -->", vec![]),
+            block("3", "<!-- mcp:begin todo -->", vec![]),
+            block("4", "<!-- mcp:end todo -->", vec![]),
+        ];
+        assert_eq!(
+            find_region(&twice, "todo").unwrap_err(),
+            RegionError::Duplicate("todo".into())
+        );
+
+        for name in ["", "two words", "a--b"] {
+            assert!(matches!(
+                find_region(&[], name),
+                Err(RegionError::InvalidName(_))
+            ));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_write_region_keeps_surrounding_blocks() {
+        let mut graph = MockGraph::new();
+        graph.add_page("Report");
+        graph.add_block("Report", "Before");
+        let logseq = MockLogSeq::serve(graph).await.unwrap();
+        let client = logseq.client();
+        let contents = |texts: &[&str]| -> Vec<BatchBlock> {
+            texts
+                .iter()
+                .map(|content| BatchBlock {
+                    content: content.to_string(),
+                    children: Vec::new(),
+                })
+                .collect()
+        };
+
+        let first = write_region(
+            &client,
+            "Report",
+            "summary",
+            &contents(&["One", "Two"]),
+            None,
+        )
+        .await
+        .unwrap();
+        assert!(first.created);
+        client
+            .insert_block(
+                "After",
+                InsertBlockOptions {
+                    parent: Some("Report".into()),
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+
+        let second = write_region(&client, "Report", "summary", &contents(&["Three"]), None)
+            .await
+            .unwrap();
+        assert!(!second.created);
+        assert_eq!(second.begin, first.begin);
+
+        let roots: Vec<String> = logseq.with_graph(|g| {
+            g.children("Report")
+                .iter()
+                .map(|uuid| g.block_content(uuid).unwrap().to_string())
+                .collect()
+        });
+        assert_eq!(
+            roots,
+            vec![
+                "Before",
+                "<!-- mcp:begin summary -->",
+                "Three",
+                "<!-- mcp:end summary -->",
+                "After"
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_write_region_merges_edits_since_the_base() {
+        let mut graph = MockGraph::new();
+        graph.add_page("Report");
+        let logseq = MockLogSeq::serve(graph).await.unwrap();
+        let client = logseq.client();
+        let lines = |texts: &[&str]| -> Vec<RegionLine> {
+            texts
+                .iter()
+                .map(|content| RegionLine {
+                    depth: 0,
+                    content: content.to_string(),
+                })
+                .collect()
+        };
+        let region = |logseq: &MockLogSeq| -> Vec<(String, String)> {
+            logseq.with_graph(|g| {
+                g.children("Report")
+                    .iter()
+                    .map(|uuid| (uuid.clone(), g.block_content(uuid).unwrap().to_string()))
+                    .filter(|(_, content)| !content.starts_with("<!--"))
+                    .collect()
+            })
+        };
+
+        let first = write_region(
+            &client,
+            "Report",
+            "summary",
+            &unflatten(&lines(&["One", "Two", "Three"])),
+            None,
+        )
+        .await
+        .unwrap();
+        let blocks = region(&logseq);
+        client
+            .update_block(&blocks[0].0, "One, edited", None)
+            .await
+            .unwrap();
+
+        // The edit to the first block survives an update to the last
+        let second = write_region(
+            &client,
+            "Report",
+            "summary",
+            &unflatten(&lines(&["One", "Two", "Four"])),
+            Some(&first.base),
+        )
+        .await
+        .unwrap();
+        assert!(second.merged);
+        assert_eq!(second.base, lines(&["One", "Two", "Four"]));
+        let blocks = region(&logseq);
+        let contents: Vec<&str> = blocks.iter().map(|(_, c)| c.as_str()).collect();
+        assert_eq!(contents, ["One, edited", "Two", "Four"]);
+
+        // An update to the edited block conflicts, and changes nothing
+        let err = write_region(
+            &client,
+            "Report",
+            "summary",
+            &unflatten(&lines(&["Five", "Two", "Four"])),
+            Some(&second.base),
+        )
+        .await
+        .unwrap_err();
+        match err.downcast_ref::<RegionError>() {
+            Some(RegionError::Conflict { blocks: uuids, .. }) => {
+                assert_eq!(uuids, &[blocks[0].0.clone()])
+            }
+            other => panic!("expected a conflict, got {:?}", other),
+        }
+        assert_eq!(region(&logseq), blocks);
+    }
+
+    #[test]
+    fn test_lines_round_trip_through_blocks() {
+        let blocks = vec![BatchBlock {
+            content: "Parent".into(),
+            children: vec![BatchBlock {
+                content: "Child".into(),
+                children: Vec::new(),
+            }],
+        }];
+        let lines = flatten(&blocks);
+        assert_eq!(lines[1].depth, 1);
+        assert_eq!(unflatten(&lines), blocks);
+
+        // Too deep a line goes under the one before it
+        let deep = [
+            RegionLine {
+                depth: 0,
+                content: "A".into(),
+            },
+            RegionLine {
+                depth: 3,
+                content: "B".into(),
+            },
+        ];
+        assert_eq!(unflatten(&deep)[0].children[0].content, "B");
+    }
+
+    #[tokio::test]
+    async fn test_append_to_region_adds_children_under_the_heading() {
+        let mut graph = MockGraph::new();
+        graph.add_page("Log");
+        let logseq = MockLogSeq::serve(graph).await.unwrap();
+        let client = logseq.client();
+        let entry = |content: &str| BatchBlock {
+            content: content.to_string(),
+            children: Vec::new(),
+        };
+
+        let first = append_to_region(&client, "Log", "activity", "Activity", entry("One"))
+            .await
+            .unwrap();
+        assert!(first.created);
+        let second = append_to_region(&client, "Log", "activity", "Activity", entry("Two"))
+            .await
+            .unwrap();
+        assert!(!second.created);
+
+        let (roots, entries): (Vec<String>, Vec<String>) = logseq.with_graph(|g| {
+            let content = |uuid: &String| g.block_content(uuid).unwrap().to_string();
+            let roots = g.children("Log");
+            (
+                roots.iter().map(content).collect(),
+                g.children(&roots[1]).iter().map(content).collect(),
+            )
+        });
+        assert_eq!(
+            roots,
+            vec![
+                "<!-- mcp:begin activity -->",
+                "Activity",
+                "<!-- mcp:end activity -->"
+            ]
+        );
+        assert_eq!(entries, vec!["One", "Two"]);
+    }
+}
@@ -0,0 +1,243 @@
+//! Tag co-occurrence: which tags are set together on the same page or
+//! block, and the clusters they form
+//!
+//! Two tags are linked when at least `min_count` pages or blocks carry both;
+//! a cluster is a connected group of linked tags, which tends to be a topic
+//! the graph keeps coming back to. Pairs that are almost always used together
+//! are often synonyms worth merging.
+
+use crate::logseq::api::TagUsage;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+
+/// Pairs whose Jaccard similarity is at least this are reported as
+/// candidates for merging
+pub const MERGE_SIMILARITY: f64 = 0.8;
+
+/// Two tags and how often they appear together
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TagPair {
+    pub tags: [String; 2],
+    /// Pages and blocks carrying both tags
+    pub count: usize,
+    /// `count` over the pages and blocks carrying either tag, from 0 to 1
+    pub similarity: f64,
+}
+
+/// A connected group of tags that co-occur at least `min_count` times
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TagCluster {
+    /// Most used first
+    pub tags: Vec<String>,
+    /// Pages and blocks carrying at least two of the cluster's tags
+    pub count: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CoOccurrenceReport {
+    /// Pages and blocks with at least one tag
+    pub tagged: usize,
+    /// Every tag with the number of pages and blocks carrying it, most used
+    /// first
+    pub tags: Vec<(String, usize)>,
+    /// Pairs seen together at least `min_count` times, most frequent first
+    pub pairs: Vec<TagPair>,
+    /// Largest first
+    pub clusters: Vec<TagCluster>,
+}
+
+impl CoOccurrenceReport {
+    /// Pairs similar enough that one tag could likely replace the other
+    pub fn merge_candidates(&self) -> impl Iterator<Item = &TagPair> {
+        self.pairs
+            .iter()
+            .filter(|pair| pair.similarity >= MERGE_SIMILARITY)
+    }
+}
+
+pub fn co_occurrence(usage: &[TagUsage], min_count: usize) -> CoOccurrenceReport {
+    let min_count = min_count.max(1);
+    let mut tag_counts: HashMap<&str, usize> = HashMap::new();
+    let mut pair_counts: BTreeMap<(&str, &str), usize> = BTreeMap::new();
+    for entry in usage {
+        for (i, a) in entry.tags.iter().enumerate() {
+            *tag_counts.entry(a).or_default() += 1;
+            for b in &entry.tags[i + 1..] {
+                let key = if a < b { (a, b) } else { (b, a) };
+                *pair_counts
+                    .entry((key.0.as_str(), key.1.as_str()))
+                    .or_default() += 1;
+            }
+        }
+    }
+
+    let mut pairs: Vec<TagPair> = pair_counts
+        .into_iter()
+        .filter(|(_, count)| *count >= min_count)
+        .map(|((a, b), count)| {
+            let either = tag_counts[a] + tag_counts[b] - count;
+            TagPair {
+                tags: [a.to_string(), b.to_string()],
+                count,
+                similarity: count as f64 / either as f64,
+            }
+        })
+        .collect();
+    pairs.sort_by(|x, y| {
+        y.count
+            .cmp(&x.count)
+            .then_with(|| y.similarity.total_cmp(&x.similarity))
+            .then_with(|| x.tags.cmp(&y.tags))
+    });
+
+    let mut tags: Vec<(String, usize)> = tag_counts
+        .iter()
+        .map(|(tag, count)| (tag.to_string(), *count))
+        .collect();
+    tags.sort_by(|x, y| y.1.cmp(&x.1).then_with(|| x.0.cmp(&y.0)));
+
+    let clusters = clusters(usage, &pairs, &tag_counts);
+    CoOccurrenceReport {
+        tagged: usage.iter().filter(|entry| !entry.tags.is_empty()).count(),
+        tags,
+        pairs,
+        clusters,
+    }
+}
+
+/// The connected components of the tag graph whose edges are `pairs`
+fn clusters(
+    usage: &[TagUsage],
+    pairs: &[TagPair],
+    tag_counts: &HashMap<&str, usize>,
+) -> Vec<TagCluster> {
+    // Union-find over tag names, each root standing for its component
+    let mut parent: HashMap<&str, &str> = HashMap::new();
+    fn root<'a>(parent: &mut HashMap<&'a str, &'a str>, tag: &'a str) -> &'a str {
+        let mut current = tag;
+        while let Some(&next) = parent.get(current) {
+            if next == current {
+                break;
+            }
+            current = next;
+        }
+        parent.insert(tag, current);
+        current
+    }
+    for pair in pairs {
+        let (a, b) = (pair.tags[0].as_str(), pair.tags[1].as_str());
+        parent.entry(a).or_insert(a);
+        parent.entry(b).or_insert(b);
+        let (ra, rb) = (root(&mut parent, a), root(&mut parent, b));
+        if ra != rb {
+            parent.insert(ra.max(rb), ra.min(rb));
+        }
+    }
+
+    let mut components: BTreeMap<&str, Vec<&str>> = BTreeMap::new();
+    let tags: Vec<&str> = parent.keys().copied().collect();
+    for tag in tags {
+        let r = root(&mut parent, tag);
+        components.entry(r).or_default().push(tag);
+    }
+
+    let mut clusters: Vec<TagCluster> = components
+        .into_values()
+        .map(|mut members| {
+            members.sort_by(|x, y| tag_counts[y].cmp(&tag_counts[x]).then_with(|| x.cmp(y)));
+            let count = usage
+                .iter()
+                .filter(|entry| {
+                    entry
+                        .tags
+                        .iter()
+                        .filter(|t| members.contains(&t.as_str()))
+                        .count()
+                        >= 2
+                })
+                .count();
+            TagCluster {
+                tags: members.into_iter().map(String::from).collect(),
+                count,
+            }
+        })
+        .collect();
+    clusters.sort_by(|x, y| {
+        y.tags
+            .len()
+            .cmp(&x.tags.len())
+            .then_with(|| y.count.cmp(&x.count))
+            .then_with(|| x.tags.cmp(&y.tags))
+    });
+    clusters
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn usage(tags: &[&[&str]]) -> Vec<TagUsage> {
+        tags.iter()
+            .enumerate()
+            .map(|(id, tags)| TagUsage {
+                id: id as u64,
+                page: None,
+                tags: tags.iter().map(|t| t.to_string()).collect(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_co_occurrence_clusters() {
+        let usage = usage(&[
+            &["rust", "programming"],
+            &["programming", "rust", "async"],
+            &["async", "tokio"],
+            &["async", "tokio"],
+            &["cooking", "recipes"],
+            &["cooking", "recipes"],
+            &["gardening", "cooking"],
+            &["travel"],
+        ]);
+
+        let report = co_occurrence(&usage, 2);
+        assert_eq!(report.tagged, 8);
+        assert_eq!(report.tags[0], ("async".to_string(), 3));
+        let pairs: Vec<(&str, &str, usize)> = report
+            .pairs
+            .iter()
+            .map(|p| (p.tags[0].as_str(), p.tags[1].as_str(), p.count))
+            .collect();
+        assert_eq!(
+            pairs,
+            vec![
+                ("programming", "rust", 2),
+                ("async", "tokio", 2),
+                ("cooking", "recipes", 2),
+            ]
+        );
+
+        let clusters: Vec<&[String]> = report.clusters.iter().map(|c| &c.tags[..]).collect();
+        assert_eq!(clusters.len(), 3);
+        assert_eq!(clusters[0], ["async", "tokio"]);
+        assert_eq!(report.clusters[0].count, 2);
+
+        let merge: Vec<&[String; 2]> = report.merge_candidates().map(|p| &p.tags).collect();
+        assert_eq!(
+            merge,
+            vec![&["programming".to_string(), "rust".to_string()]]
+        );
+    }
+
+    #[test]
+    fn test_clusters_join_through_shared_tags() {
+        let usage = usage(&[&["a", "b"], &["b", "c"], &["c", "d"], &["x", "y"]]);
+
+        let report = co_occurrence(&usage, 1);
+        assert_eq!(report.clusters.len(), 2);
+        assert_eq!(report.clusters[0].tags, ["b", "c", "a", "d"]);
+        assert_eq!(report.clusters[0].count, 3);
+        assert_eq!(report.clusters[1].tags, ["x", "y"]);
+        assert!(co_occurrence(&usage, 2).clusters.is_empty());
+    }
+}
@@ -0,0 +1,181 @@
+//! How [`LogSeqClient`](super::api::LogSeqClient) reaches LogSeq
+//!
+//! Every API call goes through a [`Transport`], which sends a method name
+//! and its arguments to LogSeq and returns the JSON it answers with. The
+//! default, [`HttpTransport`], posts to LogSeq's HTTP API server with
+//! reqwest, which uses the browser's `fetch` when built for `wasm32`.
+//!
+//! Code that reaches LogSeq some other way, such as a plugin calling
+//! LogSeq's plugin API through `wasm-bindgen`, can implement [`Transport`]
+//! and hand it to [`LogSeqClientBuilder::transport`], keeping the client's
+//! typed models and queries.
+//!
+//! On `wasm32`, transports and their futures needn't be `Send` or `Sync`,
+//! as JavaScript values are neither.
+//!
+//! [`LogSeqClientBuilder::transport`]: super::api::LogSeqClientBuilder::transport
+
+use crate::logseq::api::ClientTimeouts;
+use crate::logseq::error::LogSeqError;
+use anyhow::Result;
+use serde_json::Value;
+use std::future::Future;
+use std::pin::Pin;
+
+/// The future a [`Transport`] returns for each call
+#[cfg(not(target_arch = "wasm32"))]
+pub type TransportFuture<'a> = Pin<Box<dyn Future<Output = Result<Value>> + Send + 'a>>;
+/// The future a [`Transport`] returns for each call
+#[cfg(target_arch = "wasm32")]
+pub type TransportFuture<'a> = Pin<Box<dyn Future<Output = Result<Value>> + 'a>>;
+
+/// `Send + Sync` everywhere but `wasm32`
+#[cfg(not(target_arch = "wasm32"))]
+pub trait MaybeSendSync: Send + Sync {}
+#[cfg(not(target_arch = "wasm32"))]
+impl<T: Send + Sync + ?Sized> MaybeSendSync for T {}
+
+/// `Send + Sync` everywhere but `wasm32`
+#[cfg(target_arch = "wasm32")]
+pub trait MaybeSendSync {}
+#[cfg(target_arch = "wasm32")]
+impl<T: ?Sized> MaybeSendSync for T {}
+
+/// Sends API calls to LogSeq
+pub trait Transport: MaybeSendSync {
+    /// Call an API method, such as `logseq.Editor.getPage`, with its
+    /// arguments, and return LogSeq's answer.
+    ///
+    /// Failures should be [`LogSeqError`]s where one fits. The client
+    /// reports LogSeq as unreachable after an [`LogSeqError::Unreachable`]
+    /// until a call gets through.
+    fn call<'a>(&'a self, method: &'a str, args: Vec<Value>) -> TransportFuture<'a>;
+
+    /// Where calls go, for logs
+    fn endpoint(&self) -> &str;
+}
+
+/// Calls LogSeq's HTTP API server, authorising with its token.
+///
+/// The browser's `fetch` has no time limits, so timeouts only apply to
+/// native builds.
+#[derive(Clone)]
+pub struct HttpTransport {
+    pub(crate) base_url: String,
+    pub(crate) token: String,
+    client: reqwest::Client,
+    pub(crate) timeouts: ClientTimeouts,
+}
+
+impl HttpTransport {
+    pub fn new(base_url: &str, token: &str, timeouts: ClientTimeouts) -> Result<Self> {
+        let builder = reqwest::Client::builder();
+        #[cfg(not(target_arch = "wasm32"))]
+        let builder = builder
+            .connect_timeout(timeouts.connect)
+            .timeout(timeouts.request);
+        Ok(Self {
+            base_url: base_url.to_string(),
+            token: token.to_string(),
+            client: builder.build()?,
+            timeouts,
+        })
+    }
+
+    async fn post(&self, method: &str, args: Vec<Value>) -> Result<Value> {
+        let response = self
+            .client
+            .post(format!("{}/api", self.base_url))
+            .header("Authorization", format!("Bearer {}", self.token))
+            .json(&serde_json::json!({
+                "method": method,
+                "args": args
+            }))
+            .send()
+            .await
+            .map_err(|e| self.request_error(method, e))?;
+
+        let status = response.status();
+        if status.is_success() {
+            response
+                .json()
+                .await
+                .map_err(|e| self.request_error(method, e))
+        } else {
+            let error_text = response
+                .text()
+                .await
+                .unwrap_or_else(|_| "Unknown error".to_string());
+            tracing::error!("API call failed with status {}: {}", status, error_text);
+            Err(match status {
+                reqwest::StatusCode::UNAUTHORIZED | reqwest::StatusCode::FORBIDDEN => {
+                    LogSeqError::Unauthorized {
+                        url: self.base_url.clone(),
+                    }
+                }
+                _ => LogSeqError::Api {
+                    status,
+                    body: error_text,
+                },
+            }
+            .into())
+        }
+    }
+
+    /// Turn transport failures into errors that say what went wrong: LogSeq
+    /// couldn't be reached, didn't answer in time, or sent something that
+    /// isn't JSON
+    fn request_error(&self, method: &str, e: reqwest::Error) -> anyhow::Error {
+        #[cfg(not(target_arch = "wasm32"))]
+        let unreachable = e.is_connect();
+        // `fetch` doesn't say why a request failed, only that it did
+        #[cfg(target_arch = "wasm32")]
+        let unreachable = e.is_request();
+        if unreachable {
+            let reason = if e.is_timeout() {
+                format!("timed out after {:?}", self.timeouts.connect)
+            } else {
+                root_cause(&e)
+            };
+            return LogSeqError::Unreachable {
+                url: self.base_url.clone(),
+                reason,
+            }
+            .into();
+        }
+        if e.is_timeout() {
+            return LogSeqError::Timeout {
+                method: method.to_string(),
+                timeout: self.timeouts.request,
+            }
+            .into();
+        }
+        if e.is_decode() {
+            return LogSeqError::MalformedResponse {
+                method: method.to_string(),
+                reason: root_cause(&e),
+                report: None,
+            }
+            .into();
+        }
+        e.into()
+    }
+}
+
+impl Transport for HttpTransport {
+    fn call<'a>(&'a self, method: &'a str, args: Vec<Value>) -> TransportFuture<'a> {
+        Box::pin(self.post(method, args))
+    }
+
+    fn endpoint(&self) -> &str {
+        &self.base_url
+    }
+}
+
+fn root_cause(e: &reqwest::Error) -> String {
+    let mut cause: &dyn std::error::Error = e;
+    while let Some(source) = cause.source() {
+        cause = source;
+    }
+    cause.to_string()
+}
@@ -0,0 +1,269 @@
+//! Writing statistics: how much was written each day, streaks of days with
+//! writing, and where the writing went
+//!
+//! Days are bucketed by block creation time in a given time zone, so a block
+//! written late in the evening counts towards that evening's day wherever
+//! the server's clock is. A block edited later still counts only for the day
+//! it was created.
+//!
+//! [`content_stats`] measures a page's blocks as they are now instead.
+
+use crate::logseq::api::{Block, CreatedBlock};
+use crate::logseq::content::strip_property_lines;
+use chrono::{DateTime, Days, NaiveDate, TimeZone};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+
+/// Most active pages listed in a report
+pub const TOP_PAGES: usize = 10;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DayStats {
+    pub date: NaiveDate,
+    pub words: usize,
+    pub blocks: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PageStats {
+    pub page: String,
+    pub words: usize,
+    pub blocks: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct WritingStats {
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+    pub words: usize,
+    pub blocks: usize,
+    /// Days in the range with any writing, oldest first
+    pub days: Vec<DayStats>,
+    /// Consecutive days with writing up to `to`, or up to the day before
+    /// when nothing has been written on `to` yet
+    pub current_streak: usize,
+    /// Most consecutive days with writing within the range
+    pub longest_streak: usize,
+    /// Pages with the most words written, at most [`TOP_PAGES`]
+    pub pages: Vec<PageStats>,
+}
+
+/// How much a tree of blocks holds. Property lines aren't counted.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct ContentStats {
+    pub blocks: usize,
+    pub words: usize,
+    /// Characters other than line breaks, spaces included
+    pub characters: usize,
+    /// `[[page]]` links and `#tags`
+    pub page_links: usize,
+    /// `((uuid))` block references and embeds
+    pub block_refs: usize,
+    /// `http://` and `https://` URLs
+    pub external_links: usize,
+}
+
+impl ContentStats {
+    /// Links of every kind
+    pub fn links(&self) -> usize {
+        self.page_links + self.block_refs + self.external_links
+    }
+}
+
+/// Count the blocks, words, characters, and links in `blocks` and their
+/// children
+pub fn content_stats(blocks: &[Block]) -> ContentStats {
+    fn walk(blocks: &[Block], stats: &mut ContentStats) {
+        for block in blocks {
+            let text = strip_property_lines(&block.content);
+            stats.blocks += 1;
+            stats.words += text.split_whitespace().count();
+            stats.characters += text.chars().filter(|c| *c != '\n').count();
+            stats.page_links += text.matches("[[").count() + bare_tags(&text);
+            stats.block_refs += text.matches("((").count();
+            stats.external_links += text.matches("http://").count();
+            stats.external_links += text.matches("https://").count();
+            walk(&block.children, stats);
+        }
+    }
+    let mut stats = ContentStats::default();
+    walk(blocks, &mut stats);
+    stats
+}
+
+/// `#tags` not written as `#[[tag]]`, which count as links already
+fn bare_tags(text: &str) -> usize {
+    text.split_whitespace()
+        .filter(|word| word.len() > 1 && word.starts_with('#') && !word.starts_with("#[["))
+        // Markdown headings, such as `## Notes`, are `#`s alone
+        .filter(|word| !word.trim_start_matches('#').is_empty())
+        .count()
+}
+
+/// Words in a block, not counting its `key:: value` property lines
+pub fn word_count(content: &str) -> usize {
+    strip_property_lines(content).split_whitespace().count()
+}
+
+/// Summarise the blocks written from `from` to `to`, inclusive, taking
+/// days in the time zone `tz`. Blocks created outside the range are ignored.
+pub fn writing_stats<Tz: TimeZone>(
+    blocks: &[CreatedBlock],
+    from: NaiveDate,
+    to: NaiveDate,
+    tz: &Tz,
+) -> WritingStats {
+    let mut days: BTreeMap<NaiveDate, DayStats> = BTreeMap::new();
+    let mut pages: HashMap<&str, PageStats> = HashMap::new();
+    for block in blocks {
+        let Some(created) = DateTime::from_timestamp_millis(block.created_at) else {
+            continue;
+        };
+        let date = created.with_timezone(tz).date_naive();
+        if date < from || date > to {
+            continue;
+        }
+        let words = word_count(&block.content);
+        let day = days.entry(date).or_insert(DayStats {
+            date,
+            words: 0,
+            blocks: 0,
+        });
+        day.words += words;
+        day.blocks += 1;
+        let page = pages
+            .entry(block.page_name.as_str())
+            .or_insert_with(|| PageStats {
+                page: block.page_name.clone(),
+                words: 0,
+                blocks: 0,
+            });
+        page.words += words;
+        page.blocks += 1;
+    }
+
+    let mut longest_streak = 0;
+    let mut streak = 0;
+    let mut previous: Option<NaiveDate> = None;
+    for date in days.keys() {
+        streak = match previous {
+            Some(prev) if prev.checked_add_days(Days::new(1)) == Some(*date) => streak + 1,
+            _ => 1,
+        };
+        longest_streak = longest_streak.max(streak);
+        previous = Some(*date);
+    }
+    let mut current_streak = 0;
+    let mut date = match days.contains_key(&to) {
+        true => Some(to),
+        false => to.pred_opt(),
+    };
+    while let Some(day) = date.filter(|d| days.contains_key(d)) {
+        current_streak += 1;
+        date = day.pred_opt();
+    }
+
+    let mut pages: Vec<PageStats> = pages.into_values().collect();
+    pages.sort_by(|a, b| {
+        b.words
+            .cmp(&a.words)
+            .then_with(|| b.blocks.cmp(&a.blocks))
+            .then_with(|| a.page.cmp(&b.page))
+    });
+    pages.truncate(TOP_PAGES);
+
+    let days: Vec<DayStats> = days.into_values().collect();
+    WritingStats {
+        from,
+        to,
+        words: days.iter().map(|d| d.words).sum(),
+        blocks: days.iter().map(|d| d.blocks).sum(),
+        days,
+        current_streak,
+        longest_streak,
+        pages,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    fn written(day: NaiveDate, page: &str, content: &str) -> CreatedBlock {
+        CreatedBlock {
+            content: content.to_string(),
+            page_name: page.to_string(),
+            created_at: day
+                .and_hms_opt(12, 0, 0)
+                .unwrap()
+                .and_utc()
+                .timestamp_millis(),
+        }
+    }
+
+    #[test]
+    fn test_content_stats_count_nested_blocks_and_links() {
+        let mut parent = Block::new(
+            "a".into(),
+            "## Draft #writing\nstatus:: draft\nSee [[Style Guide]] and ((1234))".into(),
+        );
+        parent.children = vec![Block::new(
+            "b".into(),
+            "Read https://example.com and #[[Long Reads]]".into(),
+        )];
+        let stats = content_stats(&[parent]);
+        assert_eq!(stats.blocks, 2);
+        assert_eq!(stats.words, 8 + 5);
+        assert_eq!(stats.characters, 17 + 32 + 44);
+        assert_eq!(stats.page_links, 3);
+        assert_eq!(stats.block_refs, 1);
+        assert_eq!(stats.external_links, 1);
+        assert_eq!(stats.links(), 5);
+    }
+
+    #[test]
+    fn test_word_count_skips_properties() {
+        assert_eq!(word_count("Three little words\nid:: 1234\ntags:: a"), 3);
+        assert_eq!(word_count(""), 0);
+    }
+
+    #[test]
+    fn test_writing_stats_days_streaks_and_pages() {
+        let blocks = [
+            written(date(2024, 2, 28), "Old", "outside the range"),
+            written(date(2024, 3, 1), "Journal", "one two three"),
+            written(date(2024, 3, 2), "Journal", "four five"),
+            written(date(2024, 3, 2), "Novel", "six"),
+            written(date(2024, 3, 3), "Novel", "seven eight nine ten"),
+            written(date(2024, 3, 6), "Journal", "eleven"),
+            written(date(2024, 3, 7), "Journal", "twelve"),
+        ];
+
+        let stats = writing_stats(&blocks, date(2024, 3, 1), date(2024, 3, 8), &Utc);
+        assert_eq!(stats.words, 12);
+        assert_eq!(stats.blocks, 6);
+        assert_eq!(stats.days.len(), 5);
+        assert_eq!(
+            stats.days[1],
+            DayStats {
+                date: date(2024, 3, 2),
+                words: 3,
+                blocks: 2
+            }
+        );
+        assert_eq!(stats.longest_streak, 3);
+        // Nothing yet on the 8th, so the streak runs to the 7th
+        assert_eq!(stats.current_streak, 2);
+        assert_eq!(stats.pages[0].page, "Journal");
+        assert_eq!(stats.pages[0].words, 7);
+        assert_eq!(stats.pages[1].page, "Novel");
+
+        let stats = writing_stats(&blocks, date(2024, 3, 1), date(2024, 3, 10), &Utc);
+        assert_eq!(stats.current_streak, 0);
+    }
+}
@@ -1,869 +1,274 @@
-mod logseq;
-mod tools;
-
 use anyhow::Result;
 use clap::{Arg, Command};
-use logseq::api::{InsertBlockOptions, LogSeqClient};
-use rmcp::{
-    ErrorData as McpError,
-    handler::server::ServerHandler,
-    model::{
-        CallToolRequestParam, CallToolResult, Implementation, InitializeResult, ListToolsResult,
-        PaginatedRequestParam, ProtocolVersion, RawContent, RawTextContent, ServerCapabilities,
-        ServerInfo, Tool,
-    },
-    service::{RequestContext, RoleServer, ServiceExt},
-    transport::io::stdio,
+use logseq_mcp_server::audit::{AUDIT_LOG_FILE, AuditLog};
+use logseq_mcp_server::capture_template::CaptureTemplate;
+use logseq_mcp_server::config::{CONFIG_ARG, CONFIG_ENV, Settings};
+use logseq_mcp_server::limits::WriteLimits;
+use logseq_mcp_server::logseq::api::{
+    ClientTimeouts, DEFAULT_API_URL, DEFAULT_MAX_CONCURRENT_REQUESTS, LogSeqClient,
 };
-use std::env;
+use logseq_mcp_server::logseq::payloads::PayloadJournal;
+use logseq_mcp_server::quota::QuotaLimits;
+use logseq_mcp_server::sandbox::Namespace;
+use logseq_mcp_server::schedule::{self, Job};
+use logseq_mcp_server::server::{LogSeqMcpServer, ServerOptions};
+use logseq_mcp_server::state::{STATE_DIR_ENV, StateArchive, StateArea, StateDir};
+use logseq_mcp_server::tools::context::DEFAULT_GRAPH;
+use logseq_mcp_server::tools::registry::ToolRegistry;
+use logseq_mcp_server::workspace::Workspace;
+use rmcp::{ServiceExt, transport::io::stdio};
+use std::path::{Component, Path, PathBuf};
 use std::sync::Arc;
-use tools::{format_blocks_as_markdown, format_search_results, format_todos};
+use std::time::Duration;
 
-#[derive(Clone, Default)]
-pub struct LogSeqMcpServer {
-    logseq_client: Option<Arc<LogSeqClient>>,
-}
+/// Default interval between background cache refreshes, in seconds
+const DEFAULT_CACHE_REFRESH_SECS: u64 = 60;
 
-impl LogSeqMcpServer {
-    fn new(logseq_client: LogSeqClient) -> Self {
-        Self {
-            logseq_client: Some(Arc::new(logseq_client)),
+async fn check_connection(logseq_client: &LogSeqClient) -> Result<()> {
+    match logseq_client.get_all_pages().await {
+        Ok(pages) => {
+            println!(
+                "✓ Connection successful! Found {} pages in LogSeq.",
+                pages.len()
+            );
+            for warning in &pages.warnings {
+                println!("⚠ {}", warning);
+            }
+            Ok(())
+        }
+        Err(e) => {
+            eprintln!("✗ Connection failed: {}", e);
+            std::process::exit(1);
         }
-    }
-
-    fn get_client(&self) -> Result<Arc<LogSeqClient>, McpError> {
-        self.logseq_client
-            .clone()
-            .ok_or_else(|| McpError::internal_error("LogSeq client not initialized", None))
     }
 }
 
-impl ServerHandler for LogSeqMcpServer {
-    fn get_info(&self) -> ServerInfo {
-        InitializeResult {
-            protocol_version: ProtocolVersion::LATEST,
-            capabilities: ServerCapabilities::builder().enable_tools().build(),
-            server_info: Implementation {
-                name: "logseq-mcp-server".into(),
-                version: env!("CARGO_PKG_VERSION").into(),
-            },
-            instructions: Some("A LogSeq MCP server for managing your knowledge graph".into()),
-        }
+fn clear_state(state_dir: &StateDir) -> Result<()> {
+    let cleared = state_dir.clear()?;
+    if cleared.is_empty() {
+        println!("Nothing to clear in {}", state_dir.root().display());
+    } else {
+        let areas = cleared
+            .iter()
+            .map(|area| area.dir_name())
+            .collect::<Vec<_>>()
+            .join(", ");
+        println!("✓ Cleared {} in {}", areas, state_dir.root().display());
     }
+    Ok(())
+}
 
-    async fn list_tools(
-        &self,
-        _params: Option<PaginatedRequestParam>,
-        _context: RequestContext<RoleServer>,
-    ) -> Result<ListToolsResult, McpError> {
-        Ok(ListToolsResult {
-            tools: vec![
-                Tool {
-                    name: "list_pages".into(),
-                    description: Some("List all pages in the current LogSeq graph. Returns a list of page names that can be used with other page-related tools.".into()),
-                    input_schema: Arc::new(
-                        serde_json::json!({
-                            "type": "object",
-                            "properties": {},
-                            "additionalProperties": false
-                        })
-                        .as_object()
-                        .unwrap()
-                        .clone(),
-                    ),
-                    annotations: None,
-                    output_schema: None,
-                },
-                Tool {
-                    name: "get_page_content".into(),
-                    description: Some("Get the content of a specific page formatted as markdown. Use this to read and understand the structure of a page's blocks and content.".into()),
-                    input_schema: Arc::new(
-                        serde_json::json!({
-                            "type": "object",
-                            "properties": {
-                                "page_name": {
-                                    "type": "string",
-                                    "description": "The name or UUID of the page. Page names are case-sensitive and should match exactly as they appear in LogSeq."
-                                }
-                            },
-                            "required": ["page_name"],
-                            "additionalProperties": false
-                        })
-                        .as_object()
-                        .unwrap()
-                        .clone(),
-                    ),
-                    annotations: None,
-                    output_schema: None,
-                },
-                Tool {
-                    name: "create_page".into(),
-                    description: Some("Create a new page in LogSeq. You can optionally specify page properties like tags, template, aliases, and custom properties.".into()),
-                    input_schema: Arc::new(
-                        serde_json::json!({
-                            "type": "object",
-                            "properties": {
-                                "name": {
-                                    "type": "string",
-                                    "description": "The name of the new page"
-                                },
-                "properties": {
-                                    "type": "object",
-                                    "description": "Optional page properties. Common properties include: 'tags' (array of strings), 'template' (string), 'alias' (array of strings), 'public' (boolean), 'filters' (object), and any custom properties you want to associate with the page.",
-                                    "properties": {
-                                        "tags": {
-                                            "type": "array",
-                                            "items": {"type": "string"},
-                                            "description": "Tags to apply to the page"
-                                        },
-                                        "template": {
-                                            "type": "string",
-                                            "description": "Template to use for the page"
-                                        },
-                                        "alias": {
-                                            "type": "array",
-                                            "items": {"type": "string"},
-                                            "description": "Alternative names for the page"
-                                        },
-                                        "public": {
-                                            "type": "boolean",
-                                            "description": "Whether the page should be public"
-                                        },
-                                        "filters": {
-                                            "type": "object",
-                                            "description": "Filters to apply to the page view"
-                                        }
-                                    },
-                                    "additionalProperties": true
-                                }
-                            },
-                            "required": ["name"],
-                            "additionalProperties": false
-                        })
-                        .as_object()
-                        .unwrap()
-                        .clone(),
-                    ),
-                    annotations: None,
-                    output_schema: None,
-                },
-                Tool {
-                    name: "search".into(),
-                    description: Some("Search for content across all pages and blocks in the LogSeq graph. Returns matching blocks with their content and context.".into()),
-                    input_schema: Arc::new(
-                        serde_json::json!({
-                            "type": "object",
-                            "properties": {
-                                "query": {
-                                    "type": "string",
-                                    "description": "Search query string. Supports text search across block content. Use keywords or phrases to find relevant blocks."
-                                }
-                            },
-                            "required": ["query"],
-                            "additionalProperties": false
-                        })
-                        .as_object()
-                        .unwrap()
-                        .clone(),
-                    ),
-                    annotations: None,
-                    output_schema: None,
-                },
-                Tool {
-                    name: "create_block".into(),
-                    description: Some("Insert a new block into LogSeq. You can specify a parent page/block or insert relative to a sibling block. Returns the created block's UUID.".into()),
-                    input_schema: Arc::new(
-                        serde_json::json!({
-                            "type": "object",
-                            "properties": {
-                                "content": {
-                                    "type": "string",
-                                    "description": "Block content in markdown format. Can include text, links, formatting, and LogSeq-specific syntax."
-                                },
-                                "parent": {
-                                    "type": "string",
-                                    "description": "Parent page name or block UUID where this block should be created. If not specified, block will be created on the current page."
-                                },
-                                "sibling": {
-                                    "type": "string",
-                                    "description": "Block UUID of an existing block. The new block will be inserted as a sibling at the same level."
-                                }
-                            },
-                            "required": ["content"],
-                            "additionalProperties": false
-                        })
-                        .as_object()
-                        .unwrap()
-                        .clone(),
-                    ),
-                    annotations: None,
-                    output_schema: None,
-                },
-                Tool {
-                    name: "get_page".into(),
-                    description: Some("Get detailed information about a specific page by name or UUID. Returns page metadata including properties, UUID, and structure.".into()),
-                    input_schema: Arc::new(
-                        serde_json::json!({
-                            "type": "object",
-                            "properties": {
-                                "name_or_uuid": {
-                                    "type": "string",
-                                    "description": "The page name (case-sensitive) or UUID. Use page names as they appear in LogSeq, or the UUID from other API calls."
-                                }
-                            },
-                            "required": ["name_or_uuid"],
-                            "additionalProperties": false
-                        })
-                        .as_object()
-                        .unwrap()
-                        .clone(),
-                    ),
-                    annotations: None,
-                    output_schema: None,
-                },
-                Tool {
-                    name: "get_block".into(),
-                    description: Some("Get detailed information about a specific block by UUID. Returns block content, properties, children, and metadata.".into()),
-                    input_schema: Arc::new(
-                        serde_json::json!({
-                            "type": "object",
-                            "properties": {
-                                "uuid": {
-                                    "type": "string",
-                                    "description": "The UUID of the block to retrieve. UUIDs can be obtained from other API calls like create_block, search, or datascript_query."
-                                }
-                            },
-                            "required": ["uuid"],
-                            "additionalProperties": false
-                        })
-                        .as_object()
-                        .unwrap()
-                        .clone(),
-                    ),
-                    annotations: None,
-                    output_schema: None,
-                },
-                Tool {
-                    name: "get_current_page".into(),
-                    description: Some("Get information about the currently active/focused page in the LogSeq interface. Useful for context-aware operations.".into()),
-                    input_schema: Arc::new(
-                        serde_json::json!({
-                            "type": "object",
-                            "properties": {},
-                            "additionalProperties": false
-                        })
-                        .as_object()
-                        .unwrap()
-                        .clone(),
-                    ),
-                    annotations: None,
-                    output_schema: None,
-                },
-                Tool {
-                    name: "get_current_block".into(),
-                    description: Some("Get information about the currently active/focused block in the LogSeq interface. Useful for context-aware operations.".into()),
-                    input_schema: Arc::new(
-                        serde_json::json!({
-                            "type": "object",
-                            "properties": {},
-                            "additionalProperties": false
-                        })
-                        .as_object()
-                        .unwrap()
-                        .clone(),
-                    ),
-                    annotations: None,
-                    output_schema: None,
-                },
-                Tool {
-                    name: "datascript_query".into(),
-                    description: Some("Execute a Datascript query against the LogSeq database for advanced data retrieval. Use this for complex queries that other tools cannot handle. Requires knowledge of Datascript syntax and LogSeq's data model.".into()),
-                    input_schema: Arc::new(
-                        serde_json::json!({
-                            "type": "object",
-                            "properties": {
-                                "query": {
-                                    "type": "string",
-                                    "description": "Datascript query string. Example: '[:find ?uuid ?content :where [?b :block/uuid ?uuid] [?b :block/content ?content] :limit 10]'. Requires knowledge of LogSeq's data schema."
-                                }
-                            },
-                            "required": ["query"],
-                            "additionalProperties": false
-                        })
-                        .as_object()
-                        .unwrap()
-                        .clone(),
-                    ),
-                    annotations: None,
-                    output_schema: None,
-                },
-                Tool {
-                    name: "get_current_graph".into(),
-                    description: Some("Get information about the current LogSeq graph including name, path, and configuration details.".into()),
-                    input_schema: Arc::new(
-                        serde_json::json!({
-                            "type": "object",
-                            "properties": {},
-                            "additionalProperties": false
-                        })
-                        .as_object()
-                        .unwrap()
-                        .clone(),
-                    ),
-                    annotations: None,
-                    output_schema: None,
-                },
-                Tool {
-                    name: "get_state_from_store".into(),
-                    description: Some("Get application state from the LogSeq store using a key path (e.g., 'ui/theme', 'ui/sidebar-open'). Useful for accessing LogSeq's internal application state.".into()),
-                    input_schema: Arc::new(
-                        serde_json::json!({
-                            "type": "object",
-                            "properties": {
-                                "key": {
-                                    "type": "string",
-                                    "description": "State key path to retrieve from LogSeq's application store. Examples: 'ui/theme', 'ui/sidebar-open', 'config/preferred-format'."
-                                }
-                            },
-                            "required": ["key"],
-                            "additionalProperties": false
-                        })
-                        .as_object()
-                        .unwrap()
-                        .clone(),
-                    ),
-                    annotations: None,
-                    output_schema: None,
-                },
-                Tool {
-                    name: "get_user_configs".into(),
-                    description: Some("Get user configuration settings for the LogSeq application. Returns the current user preferences and configuration options.".into()),
-                    input_schema: Arc::new(
-                        serde_json::json!({
-                            "type": "object",
-                            "properties": {},
-                            "additionalProperties": false
-                        })
-                        .as_object()
-                        .unwrap()
-                        .clone(),
-                    ),
-                    annotations: None,
-                    output_schema: None,
-                },
-                Tool {
-                    name: "update_block".into(),
-                    description: Some("Update the content of an existing block by UUID. Can also update block properties. Use this to modify existing content in LogSeq.".into()),
-                    input_schema: Arc::new(
-                        serde_json::json!({
-                            "type": "object",
-                            "properties": {
-                                "uuid": {
-                                    "type": "string",
-                                    "description": "The UUID of the block to update. Must be an existing block UUID."
-                                },
-                                "content": {
-                                    "type": "string",
-                                    "description": "The new content for the block in markdown format. This will replace the existing block content."
-                                },
-                                "properties": {
-                                    "type": "object",
-                                    "description": "Optional block properties to update. These are key-value pairs that define metadata for the block (e.g., {'priority': 'high', 'status': 'todo'}).",
-                                    "additionalProperties": true
-                                }
-                            },
-                            "required": ["uuid", "content"],
-                            "additionalProperties": false
-                        })
-                        .as_object()
-                        .unwrap()
-                        .clone(),
-                    ),
-                    annotations: None,
-                    output_schema: None,
-                },
-                Tool {
-                    name: "delete_block".into(),
-                    description: Some("Delete an existing block by UUID. Use with caution as this operation cannot be undone. The block and all its children will be permanently removed from LogSeq.".into()),
-                    input_schema: Arc::new(
-                        serde_json::json!({
-                            "type": "object",
-                            "properties": {
-                                "uuid": {
-                                    "type": "string",
-                                    "description": "The UUID of the block to delete. Must be an existing block UUID. This operation will also delete all child blocks."
-                                }
-                            },
-                            "required": ["uuid"],
-                            "additionalProperties": false
-                        })
-                        .as_object()
-                        .unwrap()
-                        .clone(),
-                    ),
-                    annotations: None,
-                    output_schema: None,
-                },
-                Tool {
-                    name: "delete_page".into(),
-                    description: Some("Delete an existing page by name. Use with caution as this operation cannot be undone. The page and all its content will be permanently removed from LogSeq.".into()),
-                    input_schema: Arc::new(
-                        serde_json::json!({
-                            "type": "object",
-                            "properties": {
-                                "page_name": {
-                                    "type": "string",
-                                    "description": "The name of the page to delete. Must be an existing page name as it appears in LogSeq. This operation will delete the entire page and all its blocks."
-                                }
-                            },
-                            "required": ["page_name"],
-                            "additionalProperties": false
-                        })
-                        .as_object()
-                        .unwrap()
-                        .clone(),
-                    ),
-                    annotations: None,
-                    output_schema: None,
-                },
-                Tool {
-                    name: "find_incomplete_todos".into(),
-                    description: Some("Search for all incomplete todos across all pages in LogSeq. Returns todos with markers like TODO, DOING, LATER, NOW, and WAITING. Useful for getting an overview of all outstanding tasks and their current status.".into()),
-                    input_schema: Arc::new(
-                        serde_json::json!({
-                            "type": "object",
-                            "properties": {},
-                            "additionalProperties": false
-                        })
-                        .as_object()
-                        .unwrap()
-                        .clone(),
-                    ),
-                    annotations: None,
-                    output_schema: None,
-                },
-            ],
-            next_cursor: None,
+/// Write the state worth moving to another machine, and the config file in
+/// use, to `path`
+fn export_state(state_dir: &StateDir, config: Option<PathBuf>, path: &Path) -> Result<()> {
+    let config = config
+        .map(|config| {
+            std::fs::read_to_string(&config).map_err(|e| {
+                anyhow::anyhow!("Failed to read config file {}: {}", config.display(), e)
+            })
         })
-    }
-
-    async fn call_tool(
-        &self,
-        params: CallToolRequestParam,
-        _context: RequestContext<RoleServer>,
-    ) -> Result<CallToolResult, McpError> {
-        let client = self.get_client()?;
-
-        match params.name.as_ref() {
-            "list_pages" => {
-                let pages = client
-                    .get_all_pages()
-                    .await
-                    .map_err(|e| McpError::internal_error(e.to_string(), None))?;
-
-                let content_text = pages
-                    .iter()
-                    .map(|p| format!("- {}", p.name))
-                    .collect::<Vec<_>>()
-                    .join("\n");
-
-                Ok(CallToolResult {
-                    content: Some(vec![rmcp::model::Content {
-                        raw: RawContent::Text(RawTextContent { text: content_text }),
-                        annotations: None,
-                    }]),
-                    structured_content: None,
-                    is_error: Some(false),
-                })
-            }
-            "get_page_content" => {
-                let page_name = params
-                    .arguments
-                    .and_then(|args| args.get("page_name")?.as_str().map(String::from))
-                    .ok_or_else(|| McpError::invalid_params("Missing page_name parameter", None))?;
-
-                let blocks = client
-                    .get_page_blocks_tree(&page_name)
-                    .await
-                    .map_err(|e| McpError::internal_error(e.to_string(), None))?;
-
-                let content_text = format_blocks_as_markdown(&blocks);
-                Ok(CallToolResult {
-                    content: Some(vec![rmcp::model::Content {
-                        raw: RawContent::Text(RawTextContent { text: content_text }),
-                        annotations: None,
-                    }]),
-                    structured_content: None,
-                    is_error: Some(false),
-                })
-            }
-            "create_page" => {
-                let arguments = params.arguments.ok_or_else(|| {
-                    McpError::invalid_params("Missing arguments for create_page", None)
-                })?;
-                let name = arguments
-                    .get("name")
-                    .and_then(|v| v.as_str())
-                    .ok_or_else(|| McpError::invalid_params("Missing name parameter", None))?;
-                let properties = arguments
-                    .get("properties")
-                    .and_then(|v| serde_json::from_value(v.clone()).ok());
-
-                let page = client
-                    .create_page(name, properties)
-                    .await
-                    .map_err(|e| McpError::internal_error(e.to_string(), None))?;
-
-                Ok(CallToolResult {
-                    content: Some(vec![rmcp::model::Content {
-                        raw: RawContent::Text(RawTextContent {
-                            text: format!("Created page: {}", page.name),
-                        }),
-                        annotations: None,
-                    }]),
-                    structured_content: None,
-                    is_error: Some(false),
-                })
-            }
-            "search" => {
-                let query = params
-                    .arguments
-                    .and_then(|args| args.get("query")?.as_str().map(String::from))
-                    .ok_or_else(|| McpError::invalid_params("Missing query parameter", None))?;
-
-                let results = client
-                    .search(&query)
-                    .await
-                    .map_err(|e| McpError::internal_error(e.to_string(), None))?;
-
-                let content_text = format_search_results(&results);
-                Ok(CallToolResult {
-                    content: Some(vec![rmcp::model::Content {
-                        raw: RawContent::Text(RawTextContent { text: content_text }),
-                        annotations: None,
-                    }]),
-                    structured_content: None,
-                    is_error: Some(false),
-                })
-            }
-            "create_block" => {
-                let arguments = params.arguments.ok_or_else(|| {
-                    McpError::invalid_params("Missing arguments for create_block", None)
-                })?;
-                let content = arguments
-                    .get("content")
-                    .and_then(|v| v.as_str())
-                    .ok_or_else(|| McpError::invalid_params("Missing content parameter", None))?;
-                let parent = arguments
-                    .get("parent")
-                    .and_then(|v| v.as_str())
-                    .map(String::from);
-                let sibling = arguments
-                    .get("sibling")
-                    .and_then(|v| v.as_str())
-                    .map(String::from);
-
-                let opts = InsertBlockOptions {
-                    parent,
-                    sibling,
-                    ..Default::default()
-                };
-
-                let block = client
-                    .insert_block(content, opts)
-                    .await
-                    .map_err(|e| McpError::internal_error(e.to_string(), None))?;
-
-                Ok(CallToolResult {
-                    content: Some(vec![rmcp::model::Content {
-                        raw: RawContent::Text(RawTextContent {
-                            text: format!("Created block with UUID: {}", block.uuid),
-                        }),
-                        annotations: None,
-                    }]),
-                    structured_content: None,
-                    is_error: Some(false),
-                })
-            }
-            "get_page" => {
-                let name_or_uuid = params
-                    .arguments
-                    .and_then(|args| args.get("name_or_uuid")?.as_str().map(String::from))
-                    .ok_or_else(|| {
-                        McpError::invalid_params("Missing name_or_uuid parameter", None)
-                    })?;
-
-                let page = client
-                    .get_page(&name_or_uuid)
-                    .await
-                    .map_err(|e| McpError::internal_error(e.to_string(), None))?;
-
-                Ok(CallToolResult {
-                    content: Some(vec![rmcp::model::Content {
-                        raw: RawContent::Text(RawTextContent {
-                            text: serde_json::to_string_pretty(&page)
-                                .unwrap_or_else(|_| "Error serializing page".to_string()),
-                        }),
-                        annotations: None,
-                    }]),
-                    structured_content: None,
-                    is_error: Some(false),
-                })
-            }
-            "get_block" => {
-                let uuid = params
-                    .arguments
-                    .and_then(|args| args.get("uuid")?.as_str().map(String::from))
-                    .ok_or_else(|| McpError::invalid_params("Missing uuid parameter", None))?;
-
-                let block = client
-                    .get_block(&uuid)
-                    .await
-                    .map_err(|e| McpError::internal_error(e.to_string(), None))?;
-
-                Ok(CallToolResult {
-                    content: Some(vec![rmcp::model::Content {
-                        raw: RawContent::Text(RawTextContent {
-                            text: serde_json::to_string_pretty(&block)
-                                .unwrap_or_else(|_| "Error serializing block".to_string()),
-                        }),
-                        annotations: None,
-                    }]),
-                    structured_content: None,
-                    is_error: Some(false),
-                })
-            }
-            "get_current_page" => {
-                let page = client
-                    .get_current_page()
-                    .await
-                    .map_err(|e| McpError::internal_error(e.to_string(), None))?;
-
-                Ok(CallToolResult {
-                    content: Some(vec![rmcp::model::Content {
-                        raw: RawContent::Text(RawTextContent {
-                            text: serde_json::to_string_pretty(&page)
-                                .unwrap_or_else(|_| "Error serializing page".to_string()),
-                        }),
-                        annotations: None,
-                    }]),
-                    structured_content: None,
-                    is_error: Some(false),
-                })
-            }
-            "get_current_block" => {
-                let block = client
-                    .get_current_block()
-                    .await
-                    .map_err(|e| McpError::internal_error(e.to_string(), None))?;
-
-                Ok(CallToolResult {
-                    content: Some(vec![rmcp::model::Content {
-                        raw: RawContent::Text(RawTextContent {
-                            text: serde_json::to_string_pretty(&block)
-                                .unwrap_or_else(|_| "Error serializing block".to_string()),
-                        }),
-                        annotations: None,
-                    }]),
-                    structured_content: None,
-                    is_error: Some(false),
-                })
-            }
-            "datascript_query" => {
-                let query = params
-                    .arguments
-                    .and_then(|args| args.get("query")?.as_str().map(String::from))
-                    .ok_or_else(|| McpError::invalid_params("Missing query parameter", None))?;
-
-                let result = client
-                    .datascript_query(&query)
-                    .await
-                    .map_err(|e| McpError::internal_error(e.to_string(), None))?;
-
-                Ok(CallToolResult {
-                    content: Some(vec![rmcp::model::Content {
-                        raw: RawContent::Text(RawTextContent {
-                            text: serde_json::to_string_pretty(&result)
-                                .unwrap_or_else(|_| "Error serializing result".to_string()),
-                        }),
-                        annotations: None,
-                    }]),
-                    structured_content: None,
-                    is_error: Some(false),
-                })
-            }
-            "get_current_graph" => {
-                let graph = client
-                    .get_current_graph()
-                    .await
-                    .map_err(|e| McpError::internal_error(e.to_string(), None))?;
-
-                Ok(CallToolResult {
-                    content: Some(vec![rmcp::model::Content {
-                        raw: RawContent::Text(RawTextContent {
-                            text: serde_json::to_string_pretty(&graph)
-                                .unwrap_or_else(|_| "Error serializing graph info".to_string()),
-                        }),
-                        annotations: None,
-                    }]),
-                    structured_content: None,
-                    is_error: Some(false),
-                })
-            }
-            "get_state_from_store" => {
-                let key = params
-                    .arguments
-                    .and_then(|args| args.get("key")?.as_str().map(String::from))
-                    .ok_or_else(|| McpError::invalid_params("Missing key parameter", None))?;
+        .transpose()?;
+    let archive = state_dir.export(config)?;
+    let file = std::fs::File::create(path)
+        .map_err(|e| anyhow::anyhow!("Failed to create {}: {}", path.display(), e))?;
+    serde_json::to_writer(std::io::BufWriter::new(file), &archive)?;
+    println!(
+        "✓ Exported {} state file(s){} from {} to {}",
+        archive.files.len(),
+        if archive.config.is_some() {
+            " and the config file"
+        } else {
+            ""
+        },
+        state_dir.root().display(),
+        path.display()
+    );
+    Ok(())
+}
 
-                let state = client
-                    .get_state_from_store(&key)
-                    .await
-                    .map_err(|e| McpError::internal_error(e.to_string(), None))?;
+/// Unpack an archive written by `--export-state` into the state directory
+fn import_state(state_dir: &StateDir, path: &Path) -> Result<()> {
+    let file = std::fs::File::open(path)
+        .map_err(|e| anyhow::anyhow!("Failed to open {}: {}", path.display(), e))?;
+    let archive: StateArchive = serde_json::from_reader(std::io::BufReader::new(file))
+        .map_err(|e| anyhow::anyhow!("{} is not a state archive: {}", path.display(), e))?;
+    state_dir.init()?;
+    let summary = state_dir.import(&archive)?;
+    println!(
+        "✓ Imported {} state file(s) into {}",
+        summary.files,
+        state_dir.root().display()
+    );
+    if let Some(config) = summary.config {
+        println!(
+            "  The config file was written to {}; pass --config {} to use it",
+            config.display(),
+            config.display()
+        );
+    }
+    Ok(())
+}
 
-                Ok(CallToolResult {
-                    content: Some(vec![rmcp::model::Content {
-                        raw: RawContent::Text(RawTextContent {
-                            text: serde_json::to_string_pretty(&state)
-                                .unwrap_or_else(|_| "Error serializing state".to_string()),
-                        }),
-                        annotations: None,
-                    }]),
-                    structured_content: None,
-                    is_error: Some(false),
-                })
-            }
-            "get_user_configs" => {
-                let configs = client
-                    .get_user_configs()
-                    .await
-                    .map_err(|e| McpError::internal_error(e.to_string(), None))?;
+/// The config file named by `--config` or `LOGSEQ_MCP_CONFIG`, if any
+fn config_path(matches: &clap::ArgMatches) -> Option<PathBuf> {
+    matches
+        .get_one::<String>(CONFIG_ARG)
+        .cloned()
+        .or_else(|| std::env::var(CONFIG_ENV).ok())
+        .filter(|path| !path.is_empty())
+        .map(PathBuf::from)
+}
 
-                Ok(CallToolResult {
-                    content: Some(vec![rmcp::model::Content {
-                        raw: RawContent::Text(RawTextContent {
-                            text: serde_json::to_string_pretty(&configs)
-                                .unwrap_or_else(|_| "Error serializing configs".to_string()),
-                        }),
-                        annotations: None,
-                    }]),
-                    structured_content: None,
-                    is_error: Some(false),
-                })
-            }
-            "update_block" => {
-                let arguments = params.arguments.ok_or_else(|| {
-                    McpError::invalid_params("Missing arguments for update_block", None)
-                })?;
-                let uuid = arguments
-                    .get("uuid")
-                    .and_then(|v| v.as_str())
-                    .ok_or_else(|| McpError::invalid_params("Missing uuid parameter", None))?;
-                let content = arguments
-                    .get("content")
-                    .and_then(|v| v.as_str())
-                    .ok_or_else(|| McpError::invalid_params("Missing content parameter", None))?;
-                let properties = arguments
-                    .get("properties")
-                    .and_then(|v| serde_json::from_value(v.clone()).ok());
+/// A LogSeq API URL
+fn parse_url(value: &str) -> Result<String, String> {
+    match reqwest::Url::parse(value) {
+        Ok(url) if matches!(url.scheme(), "http" | "https") => Ok(value.to_string()),
+        _ => Err(format!(
+            "must be an http:// or https:// URL such as {}, got {:?}",
+            DEFAULT_API_URL, value
+        )),
+    }
+}
 
-                let block = client
-                    .update_block(uuid, content, properties)
-                    .await
-                    .map_err(|e| McpError::internal_error(e.to_string(), None))?;
+/// An extra graph to serve alongside the default one, as `(name, url)`,
+/// from `NAME=URL`. The name is also the graph's directory under each
+/// state area, so it must be a single plain path component.
+fn parse_graph(spec: &str) -> Result<(String, String), String> {
+    let Some((name, url)) = spec
+        .split_once('=')
+        .map(|(name, url)| (name.trim(), url.trim()))
+        .filter(|(name, url)| !name.is_empty() && !url.is_empty())
+    else {
+        return Err(format!("graphs must be given as NAME=URL, got {:?}", spec));
+    };
+    let mut components = Path::new(name).components();
+    let plain = matches!(
+        (components.next(), components.next()),
+        (Some(Component::Normal(component)), None) if component == name
+    );
+    if !plain || name.contains(['/', '\\']) {
+        return Err(format!(
+            "graph names can't contain path separators or be '.' or '..', got {:?}",
+            name
+        ));
+    }
+    Ok((name.to_string(), parse_url(url)?))
+}
 
-                Ok(CallToolResult {
-                    content: Some(vec![rmcp::model::Content {
-                        raw: RawContent::Text(RawTextContent {
-                            text: format!("Updated block with UUID: {}", block.uuid),
-                        }),
-                        annotations: None,
-                    }]),
-                    structured_content: None,
-                    is_error: Some(false),
-                })
-            }
-            "delete_block" => {
-                let uuid = params
-                    .arguments
-                    .and_then(|args| args.get("uuid")?.as_str().map(String::from))
-                    .ok_or_else(|| McpError::invalid_params("Missing uuid parameter", None))?;
+/// Extra graphs from `--graph NAME=URL` flags, `LOGSEQ_MCP_GRAPHS`
+/// (comma-separated), or `graph` in the config file, with their tokens
+fn extra_graphs(settings: &mut Settings) -> Vec<(String, String, String)> {
+    let mut graphs: Vec<(String, String, String)> = Vec::new();
+    for (name, url) in settings.list("graph", Some("LOGSEQ_MCP_GRAPHS"), parse_graph) {
+        if name == DEFAULT_GRAPH || graphs.iter().any(|(n, _, _)| *n == name) {
+            settings.invalid(
+                "graph",
+                format!("graph name {:?} is used more than once", name),
+            );
+            continue;
+        }
+        let key = format!("api-token-{}", name.to_lowercase());
+        let var = graph_token_var(&name);
+        match settings.secret(&key, Some(&var)) {
+            Some(token) => graphs.push((name, url, token)),
+            None => settings.missing(&key, &var),
+        }
+    }
+    graphs
+}
 
-                client
-                    .remove_block(&uuid)
-                    .await
-                    .map_err(|e| McpError::internal_error(e.to_string(), None))?;
+/// The variable holding the API token for an extra graph, e.g.
+/// `LOGSEQ_API_TOKEN_WORK` for `work`
+fn graph_token_var(name: &str) -> String {
+    let suffix: String = name
+        .chars()
+        .map(|c| match c.is_ascii_alphanumeric() {
+            true => c.to_ascii_uppercase(),
+            false => '_',
+        })
+        .collect();
+    format!("LOGSEQ_API_TOKEN_{}", suffix)
+}
 
-                Ok(CallToolResult {
-                    content: Some(vec![rmcp::model::Content {
-                        raw: RawContent::Text(RawTextContent {
-                            text: format!("Successfully deleted block with UUID: {}", uuid),
-                        }),
-                        annotations: None,
-                    }]),
-                    structured_content: None,
-                    is_error: Some(false),
-                })
-            }
-            "delete_page" => {
-                let page_name = params
-                    .arguments
-                    .and_then(|args| args.get("page_name")?.as_str().map(String::from))
-                    .ok_or_else(|| McpError::invalid_params("Missing page_name parameter", None))?;
+/// A cache lifetime in seconds. Unlike other durations this may be zero,
+/// which turns the cache off.
+fn parse_cache_ttl(value: &str) -> Result<Duration, String> {
+    value
+        .parse()
+        .map(Duration::from_secs)
+        .map_err(|_| format!("must be a whole number of seconds, got {:?}", value))
+}
 
-                client
-                    .delete_page(&page_name)
-                    .await
-                    .map_err(|e| McpError::internal_error(e.to_string(), None))?;
+/// Where to write the audit log, from `--audit-log [PATH]`,
+/// `LOGSEQ_MCP_AUDIT_LOG`, or `audit-log` in the config file: a path, or
+/// `1`/`true` for the default location in the state directory. Auditing is
+/// off when none is set.
+fn audit_log_path(settings: &mut Settings, state_dir: &StateDir) -> Option<PathBuf> {
+    settings
+        .get("audit-log", Some("LOGSEQ_MCP_AUDIT_LOG"), |value| {
+            Ok(match value {
+                "1" | "true" => Some(state_dir.path(StateArea::Audit).join(AUDIT_LOG_FILE)),
+                "" | "0" | "false" => None,
+                path => Some(PathBuf::from(path)),
+            })
+        })
+        .flatten()
+}
 
-                Ok(CallToolResult {
-                    content: Some(vec![rmcp::model::Content {
-                        raw: RawContent::Text(RawTextContent {
-                            text: format!("Successfully deleted page: {}", page_name),
-                        }),
-                        annotations: None,
-                    }]),
-                    structured_content: None,
-                    is_error: Some(false),
-                })
-            }
-            "find_incomplete_todos" => {
-                let todos = client
-                    .find_incomplete_todos()
-                    .await
-                    .map_err(|e| McpError::internal_error(e.to_string(), None))?;
+/// A scheduled job, naming one of the server's tools
+fn parse_job(value: &str) -> Result<Job, String> {
+    let job = Job::parse(value)?;
+    if !ToolRegistry::builtin().contains(&job.tool) {
+        return Err(format!("'{}' is not one of the server's tools", job.tool));
+    }
+    Ok(job)
+}
 
-                let content_text = format_todos(&todos);
-                Ok(CallToolResult {
-                    content: Some(vec![rmcp::model::Content {
-                        raw: RawContent::Text(RawTextContent { text: content_text }),
-                        annotations: None,
-                    }]),
-                    structured_content: None,
-                    is_error: Some(false),
-                })
-            }
-            _ => Err(McpError::method_not_found::<
-                rmcp::model::CallToolRequestMethod,
-            >()),
+/// The templates defined with `--capture-template`, each named once
+fn capture_templates(settings: &mut Settings) -> Vec<CaptureTemplate> {
+    let mut templates: Vec<CaptureTemplate> = Vec::new();
+    for template in settings.list("capture-template", None, CaptureTemplate::parse) {
+        if templates.iter().any(|t| t.name == template.name) {
+            settings.invalid(
+                "capture-template",
+                format!(
+                    "capture template name {:?} is used more than once",
+                    template.name
+                ),
+            );
+            continue;
         }
+        templates.push(template);
     }
+    templates
 }
 
-async fn check_connection(logseq_client: &LogSeqClient) -> Result<()> {
-    match logseq_client.get_all_pages().await {
-        Ok(pages) => {
-            println!(
-                "✓ Connection successful! Found {} pages in LogSeq.",
-                pages.len()
+/// The workspaces defined with `--workspace`, each named once and using
+/// only capture templates in `templates`
+fn workspaces(settings: &mut Settings, templates: &[CaptureTemplate]) -> Vec<Workspace> {
+    let mut workspaces: Vec<Workspace> = Vec::new();
+    for workspace in settings.list("workspace", None, Workspace::parse) {
+        if workspaces.iter().any(|w| w.name == workspace.name) {
+            settings.invalid(
+                "workspace",
+                format!("workspace name {:?} is used more than once", workspace.name),
             );
-            Ok(())
+            continue;
         }
-        Err(e) => {
-            eprintln!("✗ Connection failed: {}", e);
-            std::process::exit(1);
+        if let Some(template) = &workspace.template
+            && !templates.iter().any(|t| &t.name == template)
+        {
+            settings.invalid(
+                "workspace",
+                format!(
+                    "workspace {} uses capture template {:?}, which isn't defined with --capture-template",
+                    workspace.name, template
+                ),
+            );
         }
+        workspaces.push(workspace);
     }
+    workspaces
 }
 
 #[tokio::main]
@@ -872,34 +277,408 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     dotenvy::dotenv().ok();
     tracing_subscriber::fmt()
         .with_writer(std::io::stderr)
+        .with_env_filter(
+            tracing_subscriber::EnvFilter::builder()
+                .with_default_directive(tracing::level_filters::LevelFilter::INFO.into())
+                .from_env_lossy(),
+        )
         .init();
 
     // Parse command line arguments
-    let matches = Command::new("logseq-mcp-server")
+    let command = Command::new("logseq-mcp-server")
         .version(env!("CARGO_PKG_VERSION"))
         .about("LogSeq MCP Server - provides MCP interface to LogSeq HTTP API")
+        .arg(
+            Arg::new("config")
+                .long("config")
+                .value_name("PATH")
+                .help("Read settings from this TOML file, keyed by the long flag names (e.g. connect-timeout = 10); string values may use ${VAR} to read environment variables. Flags and environment variables take precedence [env: LOGSEQ_MCP_CONFIG]"),
+        )
         .arg(
             Arg::new("check")
                 .long("check")
                 .action(clap::ArgAction::SetTrue)
                 .help("Check that the API connection and authentication are working"),
         )
-        .get_matches();
+        .arg(
+            Arg::new("graph")
+                .long("graph")
+                .value_name("NAME=URL")
+                .action(clap::ArgAction::Append)
+                .help("Also serve the LogSeq graph at URL as NAME; its token is read from LOGSEQ_API_TOKEN_<NAME>. May be repeated [env: LOGSEQ_MCP_GRAPHS, comma-separated]"),
+        )
+        .arg(
+            Arg::new("state-dir")
+                .long("state-dir")
+                .value_name("PATH")
+                .value_parser(clap::value_parser!(PathBuf))
+                .help("Directory for server state such as caches and snapshots [env: LOGSEQ_MCP_STATE_DIR]"),
+        )
+        .arg(
+            Arg::new("audit-log")
+                .long("audit-log")
+                .value_name("PATH")
+                .num_args(0..=1)
+                .value_parser(clap::value_parser!(PathBuf))
+                .help("Append a JSONL record of every change made through the server to PATH [default: audit/audit.jsonl in the state directory] [env: LOGSEQ_MCP_AUDIT_LOG]"),
+        )
+        .arg(
+            Arg::new("journal-activity")
+                .long("journal-activity")
+                .action(clap::ArgAction::SetTrue)
+                .help("Add a one-line summary of every change made through the server to today's journal, under an \"Agent activity\" heading [env: LOGSEQ_MCP_JOURNAL_ACTIVITY]"),
+        )
+        .arg(
+            Arg::new("schedule")
+                .long("schedule")
+                .value_name("JOB")
+                .action(clap::ArgAction::Append)
+                .help("Call a tool on a cron-style schedule in local time, e.g. '0 6 * * * write_task_dashboard {\"page\": \"Tasks\"}' or '@daily invalidate_cache'. May be repeated"),
+        )
+        .arg(
+            Arg::new("workspace")
+                .long("workspace")
+                .value_name("WORKSPACE")
+                .action(clap::ArgAction::Append)
+                .help("Define a workspace that sessions and calls can select to scope search, create_page, and capture, e.g. 'alpha {\"namespace\": \"Projects/Alpha\", \"tags\": [\"alpha\"], \"heading\": \"Alpha\"}'. May be repeated"),
+        )
+        .arg(
+            Arg::new("capture-template")
+                .long("capture-template")
+                .value_name("TEMPLATE")
+                .action(clap::ArgAction::Append)
+                .help("Define a named template for notes written by capture, using {{text}}, {{time}}, and {{source}}, e.g. 'default {{time}} {{text}} (via {{source}})'. The one named 'default' applies unless a call or workspace picks another. May be repeated"),
+        )
+        .arg(
+            Arg::new("clear-state")
+                .long("clear-state")
+                .action(clap::ArgAction::SetTrue)
+                .help("Remove all persisted server state, then exit"),
+        )
+        .arg(
+            Arg::new("export-state")
+                .long("export-state")
+                .value_name("PATH")
+                .value_parser(clap::value_parser!(PathBuf))
+                .conflicts_with_all(["clear-state", "import-state"])
+                .help("Write the trash, intent log, audit log, and snapshots, along with the config file, to an archive at PATH, then exit"),
+        )
+        .arg(
+            Arg::new("import-state")
+                .long("import-state")
+                .value_name("PATH")
+                .value_parser(clap::value_parser!(PathBuf))
+                .conflicts_with("clear-state")
+                .help("Unpack an archive written by --export-state into the state directory, then exit. Existing state is never overwritten"),
+        )
+        .arg(
+            Arg::new("warm-cache")
+                .long("warm-cache")
+                .action(clap::ArgAction::SetTrue)
+                .help("Prefetch pages, tags, and user configs at startup and keep them refreshed [env: LOGSEQ_MCP_WARM_CACHE]"),
+        )
+        .arg(
+            Arg::new("cache-refresh")
+                .long("cache-refresh")
+                .value_name("SECS")
+                .value_parser(clap::value_parser!(u64).range(1..))
+                .help("Seconds between background cache refreshes when --warm-cache is set [default: 60]"),
+        )
+        .arg(
+            Arg::new("connect-timeout")
+                .long("connect-timeout")
+                .value_name("SECS")
+                .value_parser(clap::value_parser!(u64).range(1..))
+                .help("Seconds to wait when connecting to LogSeq [env: LOGSEQ_API_CONNECT_TIMEOUT] [default: 5]"),
+        )
+        .arg(
+            Arg::new("request-timeout")
+                .long("request-timeout")
+                .value_name("SECS")
+                .value_parser(clap::value_parser!(u64).range(1..))
+                .help("Seconds to wait for each LogSeq API call [env: LOGSEQ_API_REQUEST_TIMEOUT] [default: 30]"),
+        )
+        .arg(
+            Arg::new("max-concurrent-requests")
+                .long("max-concurrent-requests")
+                .value_name("N")
+                .value_parser(clap::value_parser!(u64).range(1..))
+                .help("Send at most this many API calls to each LogSeq at once, queueing the rest [env: LOGSEQ_API_MAX_CONCURRENT_REQUESTS] [default: 4]"),
+        )
+        .arg(
+            Arg::new("tool-deadline")
+                .long("tool-deadline")
+                .value_name("SECS")
+                .value_parser(clap::value_parser!(u64).range(1..))
+                .help("Abort any tool call that takes longer than this many seconds [env: LOGSEQ_MCP_TOOL_DEADLINE]"),
+        )
+        .arg(
+            Arg::new("slow-query")
+                .long("slow-query")
+                .value_name("MS")
+                .value_parser(clap::value_parser!(u64).range(1..))
+                .help("Log and flag datascript_query calls taking at least this many milliseconds [env: LOGSEQ_MCP_SLOW_QUERY_MS] [default: 2000]"),
+        )
+        .arg(
+            Arg::new("query-cache-ttl")
+                .long("query-cache-ttl")
+                .value_name("SECS")
+                .value_parser(clap::value_parser!(u64))
+                .help("Seconds to reuse identical datascript_query results, or 0 to always rerun them [env: LOGSEQ_MCP_QUERY_CACHE_TTL] [default: 30]"),
+        )
+        .arg(
+            Arg::new("graph-cache-ttl")
+                .long("graph-cache-ttl")
+                .value_name("SECS")
+                .value_parser(clap::value_parser!(u64))
+                .help("Seconds to reuse the page list, tags, user configs, and current graph when the cache isn't warmed, or 0 to always refetch them [env: LOGSEQ_MCP_GRAPH_CACHE_TTL] [default: 60]"),
+        )
+        .arg(
+            Arg::new("max-block-bytes")
+                .long("max-block-bytes")
+                .value_name("BYTES")
+                .value_parser(clap::value_parser!(u64).range(1..))
+                .help("Reject writes of a block larger than this [env: LOGSEQ_MCP_MAX_BLOCK_BYTES] [default: unlimited]"),
+        )
+        .arg(
+            Arg::new("max-page-bytes")
+                .long("max-page-bytes")
+                .value_name("BYTES")
+                .value_parser(clap::value_parser!(u64).range(1..))
+                .help("Reject tool calls writing more than this to one page [env: LOGSEQ_MCP_MAX_PAGE_BYTES] [default: unlimited]"),
+        )
+        .arg(
+            Arg::new("max-call-bytes")
+                .long("max-call-bytes")
+                .value_name("BYTES")
+                .value_parser(clap::value_parser!(u64).range(1..))
+                .help("Reject tool calls writing more than this in total [env: LOGSEQ_MCP_MAX_CALL_BYTES] [default: unlimited]"),
+        )
+        .arg(
+            Arg::new("max-pages-created")
+                .long("max-pages-created")
+                .value_name("N")
+                .value_parser(clap::value_parser!(u64).range(1..=u32::MAX as u64))
+                .help("Pages a session may create before write tools refuse [env: LOGSEQ_MCP_MAX_PAGES_CREATED] [default: unlimited]"),
+        )
+        .arg(
+            Arg::new("max-blocks-deleted")
+                .long("max-blocks-deleted")
+                .value_name("N")
+                .value_parser(clap::value_parser!(u64).range(1..=u32::MAX as u64))
+                .help("Blocks a session may delete, counting children and the blocks of deleted pages [env: LOGSEQ_MCP_MAX_BLOCKS_DELETED] [default: unlimited]"),
+        )
+        .arg(
+            Arg::new("max-mutations")
+                .long("max-mutations")
+                .value_name("N")
+                .value_parser(clap::value_parser!(u64).range(1..=u32::MAX as u64))
+                .help("Calls that change the graph a session may make [env: LOGSEQ_MCP_MAX_MUTATIONS] [default: unlimited]"),
+        )
+        .arg(
+            Arg::new("namespace")
+                .long("namespace")
+                .value_name("PREFIX")
+                .help("Only let tools change pages in this namespace (e.g. AI/), creating new pages in it [env: LOGSEQ_MCP_NAMESPACE]"),
+        );
+    #[cfg(feature = "http")]
+    let command = command
+        .arg(
+            Arg::new("http")
+                .long("http")
+                .value_name("ADDR")
+                .value_parser(clap::value_parser!(std::net::SocketAddr))
+                .help("Serve MCP over streamable HTTP on this address (e.g. 127.0.0.1:8080) instead of stdio [env: LOGSEQ_MCP_HTTP_ADDR]"),
+        )
+        .arg(
+            Arg::new("http-path")
+                .long("http-path")
+                .value_name("PATH")
+                .default_value(logseq_mcp_server::http::DEFAULT_MCP_PATH)
+                .help("Path of the MCP endpoint when serving over HTTP"),
+        );
+    let matches = command.get_matches();
+    let mut settings = Settings::new(&matches);
+
+    let state_dir = StateDir::resolve(settings.get("state-dir", Some(STATE_DIR_ENV), |value| {
+        Ok(PathBuf::from(value))
+    }))?;
+
+    // Handle maintenance mode; this doesn't need a LogSeq connection
+    if matches.get_flag("clear-state") {
+        return clear_state(&state_dir).map_err(Into::into);
+    }
+    if let Some(path) = matches.get_one::<PathBuf>("export-state") {
+        return export_state(&state_dir, config_path(&matches), path).map_err(Into::into);
+    }
+    if let Some(path) = matches.get_one::<PathBuf>("import-state") {
+        return import_state(&state_dir, path).map_err(Into::into);
+    }
 
-    // Create LogSeq client
-    let logseq_url = env::var("LOGSEQ_API_URL").unwrap_or_else(|_| "http://localhost:12315".into());
-    let logseq_token = env::var("LOGSEQ_API_TOKEN").expect("LOGSEQ_API_TOKEN must be set");
-    let logseq_client = LogSeqClient::new(&logseq_url, &logseq_token)?;
+    // Read and check every setting before doing anything with them
+    let logseq_url = settings
+        .get("api-url", Some("LOGSEQ_API_URL"), parse_url)
+        .unwrap_or_else(|| DEFAULT_API_URL.into());
+    let logseq_token = settings.secret("api-token", Some("LOGSEQ_API_TOKEN"));
+    if logseq_token.is_none() {
+        settings.missing("api-token", "LOGSEQ_API_TOKEN");
+    }
+    let defaults = ClientTimeouts::default();
+    let timeouts = ClientTimeouts {
+        connect: settings
+            .seconds("connect-timeout", Some("LOGSEQ_API_CONNECT_TIMEOUT"))
+            .unwrap_or(defaults.connect),
+        request: settings
+            .seconds("request-timeout", Some("LOGSEQ_API_REQUEST_TIMEOUT"))
+            .unwrap_or(defaults.request),
+    };
+    let max_requests = settings
+        .count(
+            "max-concurrent-requests",
+            Some("LOGSEQ_API_MAX_CONCURRENT_REQUESTS"),
+        )
+        .unwrap_or(DEFAULT_MAX_CONCURRENT_REQUESTS);
+    let extra_graphs = extra_graphs(&mut settings);
+    let audit_log_path = audit_log_path(&mut settings, &state_dir);
+    let warm_cache = settings.switch("warm-cache", Some("LOGSEQ_MCP_WARM_CACHE"));
+    let jobs = settings.list("schedule", None, parse_job);
+    let journal_activity = settings.switch("journal-activity", Some("LOGSEQ_MCP_JOURNAL_ACTIVITY"));
+    let cache_refresh = settings
+        .seconds("cache-refresh", None)
+        .unwrap_or(Duration::from_secs(DEFAULT_CACHE_REFRESH_SECS));
+    let capture_templates = capture_templates(&mut settings);
+    let mut options = ServerOptions {
+        cache_refresh: warm_cache.then_some(cache_refresh),
+        journal_activity,
+        tool_deadline: settings.seconds("tool-deadline", Some("LOGSEQ_MCP_TOOL_DEADLINE")),
+        slow_query: settings.millis("slow-query", Some("LOGSEQ_MCP_SLOW_QUERY_MS")),
+        graph_cache_ttl: settings.get(
+            "graph-cache-ttl",
+            Some("LOGSEQ_MCP_GRAPH_CACHE_TTL"),
+            parse_cache_ttl,
+        ),
+        query_cache_ttl: settings.get(
+            "query-cache-ttl",
+            Some("LOGSEQ_MCP_QUERY_CACHE_TTL"),
+            parse_cache_ttl,
+        ),
+        quotas: QuotaLimits {
+            pages_created: settings
+                .count("max-pages-created", Some("LOGSEQ_MCP_MAX_PAGES_CREATED")),
+            blocks_deleted: settings
+                .count("max-blocks-deleted", Some("LOGSEQ_MCP_MAX_BLOCKS_DELETED")),
+            mutations: settings.count("max-mutations", Some("LOGSEQ_MCP_MAX_MUTATIONS")),
+        },
+        write_limits: WriteLimits {
+            block: settings.count("max-block-bytes", Some("LOGSEQ_MCP_MAX_BLOCK_BYTES")),
+            page: settings.count("max-page-bytes", Some("LOGSEQ_MCP_MAX_PAGE_BYTES")),
+            call: settings.count("max-call-bytes", Some("LOGSEQ_MCP_MAX_CALL_BYTES")),
+        },
+        namespace: settings.get("namespace", Some("LOGSEQ_MCP_NAMESPACE"), |value| {
+            Namespace::new(value)
+        }),
+        workspaces: workspaces(&mut settings, &capture_templates),
+        capture_templates,
+        ..Default::default()
+    };
+    #[cfg(feature = "http")]
+    let http = (
+        settings.get("http", Some("LOGSEQ_MCP_HTTP_ADDR"), |value| {
+            value.parse::<std::net::SocketAddr>().map_err(|_| {
+                format!(
+                    "must be a socket address such as 127.0.0.1:8080, got {:?}",
+                    value
+                )
+            })
+        }),
+        settings
+            .string("http-path", None)
+            .unwrap_or_else(|| logseq_mcp_server::http::DEFAULT_MCP_PATH.to_string()),
+    );
+    if let Err(e) = settings.finish() {
+        eprintln!("{}", e);
+        std::process::exit(2);
+    }
+    let logseq_token = logseq_token.unwrap_or_default();
+
+    state_dir.init()?;
+    tracing::debug!("Using state directory {}", state_dir.root().display());
+
+    // Create LogSeq clients
+    let client = |url: &str, token: &str| {
+        LogSeqClient::builder()
+            .url(url)
+            .token(token)
+            .connect_timeout(timeouts.connect)
+            .timeout(timeouts.request)
+            .max_concurrent_requests(max_requests)
+            .payload_journal(PayloadJournal::new(state_dir.path(StateArea::Payloads)))
+            .build()
+    };
+    let logseq_client = client(&logseq_url, &logseq_token)?;
+    let mut graphs = vec![(DEFAULT_GRAPH.to_string(), logseq_client.clone())];
+    for (name, url, token) in extra_graphs {
+        graphs.push((name, client(&url, &token)?));
+    }
 
     // Handle check mode
     if matches.get_flag("check") {
         return check_connection(&logseq_client).await.map_err(Into::into);
     }
 
-    // Create and run MCP server with STDIO transport
-    let service = LogSeqMcpServer::new(logseq_client);
+    if let Some(path) = audit_log_path {
+        let log = AuditLog::open(&path)
+            .map_err(|e| anyhow::anyhow!("Failed to open audit log {}: {}", path.display(), e))?;
+        tracing::info!("Recording changes in {}", path.display());
+        options.audit_log = Some(Arc::new(log));
+    }
+    options.state_dir = Some(state_dir);
+
+    // Create and run the MCP server
+    let service = LogSeqMcpServer::with_graphs(graphs, options);
+    tokio::spawn({
+        let service = service.clone();
+        async move { service.log_backend_health().await }
+    });
+    if !jobs.is_empty() {
+        tracing::info!("Running {} scheduled job(s)", jobs.len());
+        // Scheduled calls have budgets of their own, apart from any client's
+        tokio::spawn(schedule::run(service.for_new_session(), jobs));
+    }
+
+    #[cfg(feature = "http")]
+    if let (Some(addr), path) = http {
+        let options = logseq_mcp_server::http::HttpOptions {
+            path,
+            ..Default::default()
+        };
+        let shutdown = async {
+            tokio::signal::ctrl_c().await.ok();
+        };
+        return logseq_mcp_server::http::serve(service, addr, &options, shutdown)
+            .await
+            .map_err(Into::into);
+    }
+
     let server = service.serve(stdio()).await?;
 
     server.waiting().await?;
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::parse_graph;
+
+    #[test]
+    fn test_graph_names_are_single_path_components() {
+        assert_eq!(
+            parse_graph("work = http://localhost:12316"),
+            Ok(("work".to_string(), "http://localhost:12316".to_string()))
+        );
+        for name in [".", "..", "a/b", "../work", "work/", "/work", "a\\b"] {
+            let spec = format!("{}=http://localhost:12316", name);
+            assert!(parse_graph(&spec).is_err(), "{:?} was accepted", name);
+        }
+    }
+}
@@ -0,0 +1,85 @@
+//! Per-page async locks for serialising multi-step edits within the server
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, Weak};
+use tokio::sync::{Mutex as AsyncMutex, OwnedMutexGuard};
+
+/// Guard held for the duration of an operation on a page
+pub type PageGuard = OwnedMutexGuard<()>;
+
+/// Registry of per-page locks.
+///
+/// Tool calls that read-modify-write a page acquire its lock first so that
+/// composite operations aren't interleaved with other calls touching the same
+/// page. Locks only coordinate calls made through this server; edits made
+/// directly in LogSeq are not affected.
+#[derive(Default)]
+pub struct PageLocks {
+    locks: Mutex<HashMap<String, Weak<AsyncMutex<()>>>>,
+}
+
+impl PageLocks {
+    /// Wait for exclusive access to `page`, returning a guard that releases
+    /// the lock when dropped.
+    ///
+    /// Page names are matched case-insensitively, as in LogSeq.
+    pub async fn lock(&self, page: &str) -> PageGuard {
+        self.page_mutex(page).lock_owned().await
+    }
+
+    fn page_mutex(&self, page: &str) -> Arc<AsyncMutex<()>> {
+        let key = page.trim().to_lowercase();
+        let mut locks = self.locks.lock().unwrap_or_else(|e| e.into_inner());
+
+        // Drop entries for pages nobody holds or waits on so the map doesn't grow
+        // with every page ever touched
+        locks.retain(|_, lock| lock.strong_count() > 0);
+
+        if let Some(lock) = locks.get(&key).and_then(Weak::upgrade) {
+            return lock;
+        }
+        let lock = Arc::new(AsyncMutex::new(()));
+        locks.insert(key, Arc::downgrade(&lock));
+        lock
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+    use tokio::time::timeout;
+
+    #[tokio::test]
+    async fn test_same_page_is_exclusive() {
+        let locks = PageLocks::default();
+        let guard = locks.lock("Projects/Alpha").await;
+
+        let blocked = timeout(Duration::from_millis(50), locks.lock("projects/alpha ")).await;
+        assert!(blocked.is_err(), "second lock on the same page should wait");
+
+        drop(guard);
+        let acquired = timeout(Duration::from_millis(50), locks.lock("Projects/Alpha")).await;
+        assert!(acquired.is_ok(), "lock should be available once released");
+    }
+
+    #[tokio::test]
+    async fn test_different_pages_do_not_block() {
+        let locks = PageLocks::default();
+        let _alpha = locks.lock("Alpha").await;
+
+        let beta = timeout(Duration::from_millis(50), locks.lock("Beta")).await;
+        assert!(beta.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_released_locks_are_pruned() {
+        let locks = PageLocks::default();
+        drop(locks.lock("Alpha").await);
+        let _beta = locks.lock("Beta").await;
+
+        let map = locks.locks.lock().unwrap();
+        assert_eq!(map.len(), 1);
+        assert!(map.contains_key("beta"));
+    }
+}
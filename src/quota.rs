@@ -0,0 +1,163 @@
+//! Per-session budgets for changes to the graph
+//!
+//! An agent working unattended can be held to a number of pages created,
+//! blocks deleted, and calls that change the graph. Once a budget is spent,
+//! write tools refuse to run until the user resets it (or a new session
+//! starts), while reading keeps working.
+
+use serde::Serialize;
+use std::fmt;
+use std::sync::Mutex;
+use thiserror::Error;
+
+/// The most of each kind of change a session may make. `None` means
+/// unlimited.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct QuotaLimits {
+    pub pages_created: Option<u32>,
+    pub blocks_deleted: Option<u32>,
+    pub mutations: Option<u32>,
+}
+
+impl QuotaLimits {
+    pub fn is_unlimited(&self) -> bool {
+        *self == Self::default()
+    }
+
+    fn limit(&self, kind: QuotaKind) -> Option<u32> {
+        match kind {
+            QuotaKind::PagesCreated => self.pages_created,
+            QuotaKind::BlocksDeleted => self.blocks_deleted,
+            QuotaKind::Mutations => self.mutations,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QuotaKind {
+    PagesCreated,
+    BlocksDeleted,
+    Mutations,
+}
+
+impl fmt::Display for QuotaKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::PagesCreated => "pages created",
+            Self::BlocksDeleted => "blocks deleted",
+            Self::Mutations => "changes",
+        })
+    }
+}
+
+#[derive(Debug, Error, PartialEq, Eq)]
+#[error(
+    "This session's budget of {limit} {kind} is used up ({used} so far, this call needs {wanted} more); the user can reset it with reset_write_budget"
+)]
+pub struct QuotaExceeded {
+    pub kind: QuotaKind,
+    pub limit: u32,
+    pub used: u32,
+    pub wanted: u32,
+}
+
+/// How much of each budget has been used
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct QuotaUsage {
+    pub pages_created: u32,
+    pub blocks_deleted: u32,
+    pub mutations: u32,
+}
+
+impl QuotaUsage {
+    fn get_mut(&mut self, kind: QuotaKind) -> &mut u32 {
+        match kind {
+            QuotaKind::PagesCreated => &mut self.pages_created,
+            QuotaKind::BlocksDeleted => &mut self.blocks_deleted,
+            QuotaKind::Mutations => &mut self.mutations,
+        }
+    }
+}
+
+/// One session's budgets and what it has used of them
+#[derive(Debug, Default)]
+pub struct Quota {
+    limits: QuotaLimits,
+    used: Mutex<QuotaUsage>,
+}
+
+impl Quota {
+    pub fn new(limits: QuotaLimits) -> Self {
+        Self {
+            limits,
+            used: Mutex::default(),
+        }
+    }
+
+    pub fn limits(&self) -> QuotaLimits {
+        self.limits
+    }
+
+    pub fn usage(&self) -> QuotaUsage {
+        *self.lock()
+    }
+
+    /// Use `n` of a budget, or nothing if that would go over it
+    pub fn charge(&self, kind: QuotaKind, n: u32) -> Result<(), QuotaExceeded> {
+        let mut used = self.lock();
+        let count = used.get_mut(kind);
+        if let Some(limit) = self.limits.limit(kind)
+            && count.saturating_add(n) > limit
+        {
+            return Err(QuotaExceeded {
+                kind,
+                limit,
+                used: *count,
+                wanted: n,
+            });
+        }
+        *count = count.saturating_add(n);
+        Ok(())
+    }
+
+    /// Start every budget afresh
+    pub fn reset(&self) {
+        *self.lock() = QuotaUsage::default();
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, QuotaUsage> {
+        self.used.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_charges_stop_at_the_limit() {
+        let quota = Quota::new(QuotaLimits {
+            blocks_deleted: Some(5),
+            ..Default::default()
+        });
+
+        assert_eq!(quota.charge(QuotaKind::BlocksDeleted, 3), Ok(()));
+        assert_eq!(
+            quota.charge(QuotaKind::BlocksDeleted, 3),
+            Err(QuotaExceeded {
+                kind: QuotaKind::BlocksDeleted,
+                limit: 5,
+                used: 3,
+                wanted: 3,
+            })
+        );
+        assert_eq!(quota.charge(QuotaKind::BlocksDeleted, 2), Ok(()));
+        // Other budgets are unlimited
+        assert_eq!(quota.charge(QuotaKind::Mutations, 1000), Ok(()));
+        assert_eq!(quota.usage().blocks_deleted, 5);
+
+        quota.reset();
+        assert_eq!(quota.usage(), QuotaUsage::default());
+        assert_eq!(quota.charge(QuotaKind::BlocksDeleted, 5), Ok(()));
+    }
+}
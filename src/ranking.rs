@@ -0,0 +1,380 @@
+//! Ranking of search results
+//!
+//! A search finds pages by name, alias, or properties and blocks by content.
+//! Each hit is scored on four signals between 0 and 1, which are combined in
+//! a weighted sum so pages and blocks can share one list, best first:
+//!
+//! - **title**: how well a page's name or alias matches the query. A block
+//!   scores half of its page's title match.
+//! - **property**: the share of the query's terms found in property values.
+//! - **content**: the share of terms found in a block's text, averaged with
+//!   LogSeq's own relevance score when the search index supplied one.
+//! - **recency**: how recently the page or block changed, halving every
+//!   [`RECENCY_HALF_LIFE_DAYS`] days.
+
+use crate::logseq::api::{Page, SearchResult};
+use crate::logseq::content::{is_property_line, search_terms, strip_property_lines};
+use chrono::{DateTime, TimeZone, Utc};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Age at which a change counts half as much towards recency
+pub const RECENCY_HALF_LIFE_DAYS: f64 = 30.0;
+
+/// How much each signal counts towards a hit's score
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(default, deny_unknown_fields)]
+pub struct SearchWeights {
+    /// Weight of a page name or alias matching the query. Defaults to 3.
+    #[schemars(range(min = 0))]
+    pub title: f64,
+    /// Weight of property values matching the query. Defaults to 1.5.
+    #[schemars(range(min = 0))]
+    pub property: f64,
+    /// Weight of block text matching the query. Defaults to 2.
+    #[schemars(range(min = 0))]
+    pub content: f64,
+    /// Weight of having changed recently. Defaults to 0.5.
+    #[schemars(range(min = 0))]
+    pub recency: f64,
+}
+
+impl Default for SearchWeights {
+    fn default() -> Self {
+        Self {
+            title: 3.0,
+            property: 1.5,
+            content: 2.0,
+            recency: 0.5,
+        }
+    }
+}
+
+/// A hit's score on each signal, each between 0 and 1
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize)]
+pub struct Signals {
+    pub title: f64,
+    pub property: f64,
+    pub content: f64,
+    pub recency: f64,
+}
+
+impl Signals {
+    pub fn score(&self, weights: &SearchWeights) -> f64 {
+        self.title * weights.title.max(0.0)
+            + self.property * weights.property.max(0.0)
+            + self.content * weights.content.max(0.0)
+            + self.recency * weights.recency.max(0.0)
+    }
+}
+
+/// A page found by name, alias, or properties
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PageHit {
+    pub name: String,
+    pub uuid: String,
+    /// The alias that matched, when the page's own name didn't
+    pub alias: Option<String>,
+    pub score: f64,
+    pub signals: Signals,
+}
+
+/// One entry of a ranked search: a page or a block
+#[derive(Debug, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum RankedHit {
+    Page(PageHit),
+    Block {
+        #[serde(flatten)]
+        result: SearchResult,
+        signals: Signals,
+    },
+}
+
+impl RankedHit {
+    pub fn score(&self) -> f64 {
+        match self {
+            RankedHit::Page(page) => page.score,
+            RankedHit::Block { result, .. } => result.score.unwrap_or_default(),
+        }
+    }
+}
+
+/// The query's terms, lower-cased for matching
+struct Query {
+    phrase: String,
+    terms: Vec<String>,
+}
+
+impl Query {
+    fn new(query: &str) -> Self {
+        let phrase = query.trim().to_lowercase();
+        let terms = search_terms(&phrase);
+        Self { phrase, terms }
+    }
+
+    /// The share of terms `text` contains
+    fn coverage(&self, text: &str) -> f64 {
+        if self.terms.is_empty() {
+            return 0.0;
+        }
+        let text = text.to_lowercase();
+        let found = self
+            .terms
+            .iter()
+            .filter(|term| text.contains(term.as_str()))
+            .count();
+        found as f64 / self.terms.len() as f64
+    }
+
+    /// How well a name matches: exactly, as a prefix, or by containing
+    /// every term
+    fn title_match(&self, name: &str) -> f64 {
+        let name = name.to_lowercase();
+        if name == self.phrase {
+            1.0
+        } else if name.starts_with(&self.phrase) {
+            0.8
+        } else if self.coverage(&name) == 1.0 {
+            0.6
+        } else {
+            0.0
+        }
+    }
+}
+
+/// How recent a change at `updated_at` is, from 1 (now) towards 0
+pub fn recency(updated_at: Option<DateTime<Utc>>, now: DateTime<Utc>) -> f64 {
+    let Some(updated_at) = updated_at else {
+        return 0.0;
+    };
+    let age_days = (now - updated_at).num_seconds().max(0) as f64 / 86_400.0;
+    0.5_f64.powf(age_days / RECENCY_HALF_LIFE_DAYS)
+}
+
+/// Pages whose name or an alias matches the query, or whose properties
+/// contain every term, scored
+pub fn rank_pages(
+    query: &str,
+    pages: &[Page],
+    weights: &SearchWeights,
+    now: DateTime<Utc>,
+) -> Vec<PageHit> {
+    let query = Query::new(query);
+    if query.terms.is_empty() {
+        return Vec::new();
+    }
+
+    pages
+        .iter()
+        .filter_map(|page| {
+            let mut alias = None;
+            let mut title = query.title_match(page.display_name());
+            if title == 0.0 {
+                // An alias is a slightly weaker match than the name itself
+                for candidate in page.aliases() {
+                    let matched = query.title_match(&candidate) * 0.9;
+                    if matched > title {
+                        title = matched;
+                        alias = Some(candidate);
+                    }
+                }
+            }
+            let property = query.coverage(&property_text(page));
+            if title == 0.0 && property < 1.0 {
+                return None;
+            }
+            let signals = Signals {
+                title,
+                property,
+                content: 0.0,
+                recency: recency(
+                    page.updated_at
+                        .and_then(|millis| Utc.timestamp_millis_opt(millis).single()),
+                    now,
+                ),
+            };
+            Some(PageHit {
+                name: page.display_name().to_string(),
+                uuid: page.uuid.clone(),
+                alias,
+                score: signals.score(weights),
+                signals,
+            })
+        })
+        .collect()
+}
+
+/// Score block hits. LogSeq's relevance scores, where present, are scaled
+/// against the best of them and replaced by the combined score.
+pub fn rank_blocks(
+    query: &str,
+    results: Vec<SearchResult>,
+    weights: &SearchWeights,
+    now: DateTime<Utc>,
+) -> Vec<(SearchResult, Signals)> {
+    let query = Query::new(query);
+    let best = results
+        .iter()
+        .filter_map(|r| r.score)
+        .fold(0.0_f64, f64::max);
+
+    results
+        .into_iter()
+        .map(|mut result| {
+            let content = &result.block.content;
+            let properties: Vec<&str> = content.lines().filter(|l| is_property_line(l)).collect();
+            let mut text_match = query.coverage(&strip_property_lines(content));
+            if let Some(score) = result.score.filter(|_| best > 0.0) {
+                text_match = (text_match + score / best) / 2.0;
+            }
+            let signals = Signals {
+                title: query.title_match(&result.page_name) / 2.0,
+                property: query.coverage(&properties.join("\n")),
+                content: text_match,
+                recency: recency(result.updated_at, now),
+            };
+            result.score = Some(signals.score(weights));
+            (result, signals)
+        })
+        .collect()
+}
+
+/// Merge page and block hits into one list, best first
+pub fn merge(pages: Vec<PageHit>, blocks: Vec<(SearchResult, Signals)>) -> Vec<RankedHit> {
+    let mut hits: Vec<RankedHit> = pages
+        .into_iter()
+        .map(RankedHit::Page)
+        .chain(
+            blocks
+                .into_iter()
+                .map(|(result, signals)| RankedHit::Block { result, signals }),
+        )
+        .collect();
+    // A stable sort keeps equal scores in the order they were found
+    hits.sort_by(|a, b| b.score().total_cmp(&a.score()));
+    hits
+}
+
+/// A page's property values, one per line, leaving out its aliases, which
+/// count as titles
+fn property_text(page: &Page) -> String {
+    let Some(properties) = &page.properties else {
+        return String::new();
+    };
+    let mut lines: Vec<String> = properties
+        .iter()
+        .filter(|(key, _)| key.as_str() != "alias")
+        .map(|(_, value)| match value {
+            Value::String(text) => text.clone(),
+            other => other.to_string(),
+        })
+        .collect();
+    lines.sort();
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::logseq::api::Block;
+
+    fn page(name: &str, properties: Value) -> Page {
+        serde_json::from_value(serde_json::json!({
+            "name": name.to_lowercase(),
+            "original-name": name,
+            "uuid": format!("{}-uuid", name),
+            "properties": properties,
+        }))
+        .unwrap()
+    }
+
+    fn hit(content: &str, page_name: &str, score: Option<f64>) -> SearchResult {
+        SearchResult {
+            block: Block {
+                uuid: format!("{}-uuid", content),
+                content: content.to_string(),
+                page: None,
+                properties: None,
+                children: vec![],
+                level: None,
+                format: None,
+            },
+            score,
+            page_name: page_name.to_string(),
+            breadcrumb: vec![],
+            updated_at: None,
+            snippet: None,
+        }
+    }
+
+    #[test]
+    fn test_exact_title_outranks_passing_mentions() {
+        let now = Utc::now();
+        let weights = SearchWeights::default();
+        let pages = vec![
+            page("Rust", serde_json::json!({})),
+            page("Rusty Bikes", serde_json::json!({})),
+            page("Languages", serde_json::json!({"tags": "rust, go"})),
+            page("Groceries", serde_json::json!({"alias": ["shopping"]})),
+        ];
+        let pages = rank_pages("rust", &pages, &weights, now);
+        let names: Vec<&str> = pages.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, vec!["Rust", "Rusty Bikes", "Languages"]);
+
+        let blocks = rank_blocks(
+            "rust",
+            vec![hit("I should learn rust", "Journal", Some(3.0))],
+            &weights,
+            now,
+        );
+        let ranked = merge(pages, blocks);
+        assert!(matches!(&ranked[0], RankedHit::Page(p) if p.name == "Rust"));
+        assert!(
+            ranked
+                .windows(2)
+                .all(|pair| pair[0].score() >= pair[1].score())
+        );
+    }
+
+    #[test]
+    fn test_weights_change_the_order() {
+        let now = Utc::now();
+        let mut recent = hit("rust notes", "Journal", None);
+        recent.updated_at = Some(now);
+        let mut old = hit("rust notes", "Journal", None);
+        old.block.uuid = "old".into();
+        old.updated_at = Some(now - chrono::Duration::days(365));
+        let weights = SearchWeights {
+            recency: 5.0,
+            ..Default::default()
+        };
+        let ranked = merge(
+            vec![],
+            rank_blocks("rust", vec![old, recent], &weights, now),
+        );
+        let RankedHit::Block { result, signals } = &ranked[0] else {
+            panic!("expected a block");
+        };
+        assert_eq!(result.block.uuid, "rust notes-uuid");
+        assert!(signals.recency > 0.99);
+
+        let weights = SearchWeights {
+            title: 0.0,
+            property: 0.0,
+            content: 0.0,
+            recency: 0.0,
+        };
+        let pages = vec![page("Rust", serde_json::json!({}))];
+        assert_eq!(rank_pages("rust", &pages, &weights, now)[0].score, 0.0);
+    }
+
+    #[test]
+    fn test_recency_halves_every_half_life() {
+        let now = Utc::now();
+        assert_eq!(recency(None, now), 0.0);
+        let month_ago = now - chrono::Duration::days(RECENCY_HALF_LIFE_DAYS as i64);
+        assert!((recency(Some(month_ago), now) - 0.5).abs() < 1e-6);
+    }
+}
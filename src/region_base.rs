@@ -0,0 +1,113 @@
+//! What the server last wrote to each managed region
+//!
+//! [`write_region`](crate::logseq::region::write_region) merges changes made
+//! on the page since its last write, given what that write contained. The
+//! contents are kept here, one JSON file per page and region, so merges work
+//! across restarts.
+
+use crate::logseq::checksum::checksum;
+use crate::logseq::region::RegionLine;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RegionBase {
+    pub page: String,
+    pub region: String,
+    pub written_at: DateTime<Utc>,
+    pub lines: Vec<RegionLine>,
+}
+
+/// A directory of region contents, one file per page and region
+#[derive(Debug, Clone)]
+pub struct RegionBases {
+    dir: PathBuf,
+}
+
+impl RegionBases {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// What was last written to `region` on `page`, if anything
+    pub fn get(&self, page: &str, region: &str) -> io::Result<Option<Vec<RegionLine>>> {
+        match fs::read(self.path(page, region)) {
+            Ok(bytes) => {
+                let base: RegionBase = serde_json::from_slice(&bytes)?;
+                Ok(Some(base.lines))
+            }
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Record what was written to `region` on `page`
+    pub fn put(&self, page: &str, region: &str, lines: Vec<RegionLine>) -> io::Result<()> {
+        fs::create_dir_all(&self.dir)?;
+        let base = RegionBase {
+            page: page.to_string(),
+            region: region.to_string(),
+            written_at: Utc::now(),
+            lines,
+        };
+
+        // Write then rename so a crash never leaves a truncated file behind
+        let path = self.path(page, region);
+        let partial = path.with_extension("json.partial");
+        fs::write(&partial, serde_json::to_vec_pretty(&base)?)?;
+        fs::rename(&partial, &path)
+    }
+
+    /// Page names are case-insensitive and may hold any character, so files
+    /// are named with a readable slug and a hash of the names
+    fn path(&self, page: &str, region: &str) -> PathBuf {
+        let hash = checksum(&format!("{}\n{}", page.to_lowercase(), region));
+        let slug: String = format!("{}-{}", page, region)
+            .to_lowercase()
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+            .take(48)
+            .collect();
+        self.dir.join(format!("{}-{}.json", slug, hash))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn line(content: &str) -> RegionLine {
+        RegionLine {
+            depth: 0,
+            content: content.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_bases_are_kept_per_page_and_region() {
+        let dir = tempfile::tempdir().unwrap();
+        let bases = RegionBases::new(dir.path().join("regions"));
+        assert!(bases.get("Tasks", "dashboard").unwrap().is_none());
+
+        bases.put("Tasks", "dashboard", vec![line("One")]).unwrap();
+        bases
+            .put("Tasks/Old", "dashboard", vec![line("Two")])
+            .unwrap();
+        bases
+            .put("Tasks", "dashboard", vec![line("Three")])
+            .unwrap();
+
+        assert_eq!(
+            bases.get("tasks", "dashboard").unwrap(),
+            Some(vec![line("Three")])
+        );
+        assert_eq!(
+            bases.get("Tasks/Old", "dashboard").unwrap(),
+            Some(vec![line("Two")])
+        );
+        assert!(bases.get("Tasks", "summary").unwrap().is_none());
+    }
+}
@@ -0,0 +1,102 @@
+//! Confining changes to one namespace of the graph
+//!
+//! A cautious user can give an agent its own corner of the graph, such as
+//! every page under `AI/`. Tools that change the graph then refuse to touch
+//! pages outside it, and pages they create by name are put inside it, so
+//! `create_page` with `Notes` creates `AI/Notes`. Reading is unaffected.
+
+use crate::logseq::page_name::normalize_page_name;
+use thiserror::Error;
+
+/// A namespace prefix, such as `AI/`, that writes are confined to
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Namespace {
+    /// The prefix as given, ending in `/`
+    prefix: String,
+    /// The prefix lower-cased, for matching page names
+    lowered: String,
+}
+
+#[derive(Debug, Error, PartialEq, Eq)]
+#[error(
+    "'{page}' is outside the {namespace} namespace, and this server may only change pages in it"
+)]
+pub struct OutsideNamespace {
+    pub page: String,
+    pub namespace: String,
+}
+
+impl Namespace {
+    /// A namespace from a prefix such as `AI/` or `AI`, or why it isn't one
+    pub fn new(prefix: &str) -> Result<Self, String> {
+        let name = normalize_page_name(prefix).map_err(|e| e.to_string())?;
+        let prefix = format!("{}/", name);
+        Ok(Self {
+            lowered: prefix.to_lowercase(),
+            prefix,
+        })
+    }
+
+    /// The prefix, ending in `/`
+    pub fn prefix(&self) -> &str {
+        &self.prefix
+    }
+
+    /// Whether `page` is in the namespace. The namespace's own page, `AI`
+    /// for `AI/`, isn't.
+    pub fn contains(&self, page: &str) -> bool {
+        page.trim().to_lowercase().starts_with(&self.lowered)
+    }
+
+    /// Fail unless `page` is in the namespace
+    pub fn check(&self, page: &str) -> Result<(), OutsideNamespace> {
+        if self.contains(page) {
+            return Ok(());
+        }
+        Err(OutsideNamespace {
+            page: page.trim().to_string(),
+            namespace: self.prefix.clone(),
+        })
+    }
+
+    /// The name a page called `page` is created under: `page` itself when
+    /// it's already in the namespace, otherwise `page` within it
+    pub fn qualify(&self, page: &str) -> String {
+        if self.contains(page) {
+            page.to_string()
+        } else {
+            format!("{}{}", self.prefix, page.trim())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_namespace_matches_pages_under_it() {
+        let namespace = Namespace::new("AI").unwrap();
+        assert_eq!(namespace.prefix(), "AI/");
+        assert_eq!(Namespace::new(" AI/ ").unwrap(), namespace);
+        assert!(Namespace::new("").is_err());
+
+        assert!(namespace.contains("AI/Notes"));
+        assert!(namespace.contains("ai/notes/today"));
+        assert!(!namespace.contains("AI"));
+        assert!(!namespace.contains("AIR/Notes"));
+        assert!(!namespace.contains("Projects/AI/Notes"));
+        assert_eq!(
+            namespace.check("Journal").unwrap_err().to_string(),
+            "'Journal' is outside the AI/ namespace, and this server may only change pages in it"
+        );
+    }
+
+    #[test]
+    fn test_qualify_prefixes_names_outside_the_namespace() {
+        let namespace = Namespace::new("AI/").unwrap();
+        assert_eq!(namespace.qualify("Notes"), "AI/Notes");
+        assert_eq!(namespace.qualify("ai/Notes"), "ai/Notes");
+        assert_eq!(namespace.qualify("Projects/Alpha"), "AI/Projects/Alpha");
+    }
+}
@@ -0,0 +1,337 @@
+//! Running tools on a schedule
+//!
+//! Each job is a cron-style schedule, a tool, and its arguments, written as
+//! one line: `0 6 * * * write_task_dashboard {"page": "Tasks"}`. The five
+//! schedule fields are minute, hour, day of month, month, and day of week
+//! (0 or 7 for Sunday), each `*`, a number, a range `a-b`, a step `*/n` or
+//! `a-b/n`, or a comma-separated list of these; `@hourly`, `@daily`,
+//! `@weekly`, and `@monthly` stand for the usual schedules. Times are local.
+//!
+//! [`run`] calls each job's tool when it's due, through the server's tool
+//! registry, so scheduled calls are limited, audited, and journaled like any
+//! other. They keep the server doing useful work, such as refreshing a
+//! dashboard, while no agent is connected.
+
+use crate::server::LogSeqMcpServer;
+use chrono::{DateTime, Datelike, Duration, Local, NaiveDateTime, TimeZone, Timelike};
+use rmcp::model::JsonObject;
+use std::fmt;
+
+/// How far ahead to look for a schedule's next run before deciding it has
+/// none, such as `0 0 30 2 *`
+const SEARCH_DAYS: i64 = 5 * 366;
+
+/// The values one schedule field allows, as a bit set
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Field(u64);
+
+impl Field {
+    fn parse(text: &str, min: u32, max: u32, name: &str) -> Result<Self, String> {
+        let mut bits = 0u64;
+        for part in text.split(',') {
+            let (range, step) = match part.split_once('/') {
+                Some((range, step)) => match step.parse::<u32>() {
+                    Ok(step) if step > 0 => (range, step),
+                    _ => return Err(format!("invalid step in {} field '{}'", name, text)),
+                },
+                None => (part, 1),
+            };
+            let number = |value: &str| match value.parse::<u32>() {
+                Ok(n) if (min..=max).contains(&n) => Ok(n),
+                _ => Err(format!(
+                    "{} field '{}' must use numbers from {} to {}",
+                    name, text, min, max
+                )),
+            };
+            let (start, end) = match range.split_once('-') {
+                _ if range == "*" => (min, max),
+                Some((start, end)) => (number(start)?, number(end)?),
+                // `5/15` runs from 5 to the end of the field
+                None if step > 1 => (number(range)?, max),
+                None => {
+                    let n = number(range)?;
+                    (n, n)
+                }
+            };
+            if start > end {
+                return Err(format!("range in {} field '{}' runs backwards", name, text));
+            }
+            for value in (start..=end).step_by(step as usize) {
+                bits |= 1 << value;
+            }
+        }
+        Ok(Self(bits))
+    }
+
+    fn contains(self, value: u32) -> bool {
+        self.0 & (1 << value) != 0
+    }
+
+    /// Whether the field allows every value from `min` to `max`
+    fn is_any(self, min: u32, max: u32) -> bool {
+        (min..=max).all(|value| self.contains(value))
+    }
+}
+
+/// When a job runs
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Schedule {
+    minutes: Field,
+    hours: Field,
+    days: Field,
+    months: Field,
+    weekdays: Field,
+    source: String,
+}
+
+impl Schedule {
+    /// Parse five cron fields, or one of the `@` shorthands
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let expanded = match text.trim() {
+            "@hourly" => "0 * * * *",
+            "@daily" | "@midnight" => "0 0 * * *",
+            "@weekly" => "0 0 * * 0",
+            "@monthly" => "0 0 1 * *",
+            other => other,
+        };
+        let fields: Vec<&str> = expanded.split_whitespace().collect();
+        let [minute, hour, day, month, weekday] = fields[..] else {
+            return Err(format!(
+                "schedule '{}' must have five fields: minute hour day month weekday",
+                text.trim()
+            ));
+        };
+        let mut weekdays = Field::parse(weekday, 0, 7, "weekday")?;
+        // Sunday is both 0 and 7
+        if weekdays.contains(7) {
+            weekdays.0 = (weekdays.0 | 1) & !(1 << 7);
+        }
+        Ok(Self {
+            minutes: Field::parse(minute, 0, 59, "minute")?,
+            hours: Field::parse(hour, 0, 23, "hour")?,
+            days: Field::parse(day, 1, 31, "day")?,
+            months: Field::parse(month, 1, 12, "month")?,
+            weekdays,
+            source: text.trim().to_string(),
+        })
+    }
+
+    /// Whether the schedule runs at the start of `time`'s minute
+    pub fn matches(&self, time: NaiveDateTime) -> bool {
+        self.minutes.contains(time.minute())
+            && self.hours.contains(time.hour())
+            && self.months.contains(time.month())
+            && self.matches_day(time)
+    }
+
+    /// As in cron, a day matches either field when both are restricted
+    fn matches_day(&self, time: NaiveDateTime) -> bool {
+        let day = self.days.contains(time.day());
+        let weekday = self
+            .weekdays
+            .contains(time.weekday().num_days_from_sunday());
+        match (self.days.is_any(1, 31), self.weekdays.is_any(0, 6)) {
+            (false, false) => day || weekday,
+            _ => day && weekday,
+        }
+    }
+
+    /// The first minute after `time` that the schedule runs, if it ever does
+    pub fn next_after(&self, time: DateTime<Local>) -> Option<DateTime<Local>> {
+        let start = time.naive_local().with_second(0)?.with_nanosecond(0)? + Duration::minutes(1);
+        let end = start + Duration::days(SEARCH_DAYS);
+        let mut candidate = start;
+        while candidate < end {
+            if !self.months.contains(candidate.month()) || !self.matches_day(candidate) {
+                // Skip to the next day
+                candidate = candidate.date().succ_opt()?.and_hms_opt(0, 0, 0)?;
+                continue;
+            }
+            if self.matches(candidate) {
+                // A time skipped by a clock change has no local instant
+                if let Some(local) = Local.from_local_datetime(&candidate).earliest() {
+                    return Some(local);
+                }
+            }
+            candidate += Duration::minutes(1);
+        }
+        None
+    }
+}
+
+impl fmt::Display for Schedule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.source)
+    }
+}
+
+/// A tool to call on a schedule
+#[derive(Debug, Clone, PartialEq)]
+pub struct Job {
+    pub schedule: Schedule,
+    pub tool: String,
+    pub arguments: JsonObject,
+}
+
+impl Job {
+    /// Parse a schedule followed by a tool name and, optionally, its
+    /// arguments as a JSON object
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let text = text.trim();
+        let fields = if text.starts_with('@') { 1 } else { 5 };
+        let mut rest = text;
+        for _ in 0..fields {
+            rest = rest.trim_start();
+            let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+            rest = &rest[end..];
+        }
+        let schedule = Schedule::parse(&text[..text.len() - rest.len()])?;
+        let rest = rest.trim_start();
+        let (tool, arguments) = match rest.split_once(char::is_whitespace) {
+            Some((tool, arguments)) => (tool, arguments.trim()),
+            None => (rest, ""),
+        };
+        if tool.is_empty() {
+            return Err(format!(
+                "'{}' must name a tool to run after the schedule",
+                text
+            ));
+        }
+        let arguments = match arguments {
+            "" => JsonObject::new(),
+            json => serde_json::from_str(json).map_err(|e| {
+                format!(
+                    "arguments for {} must be a JSON object, such as {{\"page\": \"Tasks\"}}: {}",
+                    tool, e
+                )
+            })?,
+        };
+        Ok(Self {
+            schedule,
+            tool: tool.to_string(),
+            arguments,
+        })
+    }
+}
+
+/// Call each job's tool whenever it's due, for as long as the server runs.
+/// Jobs run one at a time, and a run missed while an earlier one was still
+/// going, or while the machine was asleep, is skipped rather than made up.
+pub async fn run(server: LogSeqMcpServer, jobs: Vec<Job>) {
+    let mut after = Local::now();
+    loop {
+        let Some(next) = jobs
+            .iter()
+            .filter_map(|job| job.schedule.next_after(after))
+            .min()
+        else {
+            return;
+        };
+        let wait = (next - Local::now()).to_std().unwrap_or_default();
+        tokio::time::sleep(wait).await;
+
+        for job in jobs
+            .iter()
+            .filter(|job| job.schedule.matches(next.naive_local()))
+        {
+            run_job(&server, job).await;
+        }
+        after = next.max(Local::now());
+    }
+}
+
+async fn run_job(server: &LogSeqMcpServer, job: &Job) {
+    tracing::info!("Running scheduled {} ({})", job.tool, job.schedule);
+    match server
+        .run_tool(&job.tool, Some(job.arguments.clone()))
+        .await
+    {
+        Ok(result) if result.is_error != Some(true) => {
+            tracing::info!("Scheduled {} finished", job.tool)
+        }
+        Ok(result) => tracing::warn!(
+            "Scheduled {} reported an error: {:?}",
+            job.tool,
+            result.content
+        ),
+        Err(e) => tracing::warn!("Scheduled {} failed: {}", job.tool, e.message),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    fn at(day: u32, hour: u32, minute: u32) -> NaiveDateTime {
+        // 2024-05-06 was a Monday
+        NaiveDate::from_ymd_opt(2024, 5, day)
+            .unwrap()
+            .and_hms_opt(hour, minute, 0)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_schedules_match_like_cron() {
+        let daily = Schedule::parse("0 6 * * *").unwrap();
+        assert!(daily.matches(at(6, 6, 0)));
+        assert!(!daily.matches(at(6, 6, 1)));
+
+        let weekdays = Schedule::parse("*/15 9-17 * * 1-5").unwrap();
+        assert!(weekdays.matches(at(6, 9, 45)));
+        assert!(!weekdays.matches(at(6, 9, 50)));
+        assert!(!weekdays.matches(at(5, 9, 45)));
+
+        let sundays = Schedule::parse("0 0 * * 7").unwrap();
+        assert!(sundays.matches(at(5, 0, 0)));
+        assert_eq!(
+            Schedule::parse("@weekly").unwrap().weekdays,
+            sundays.weekdays
+        );
+
+        // The 1st of the month or any Monday
+        let either = Schedule::parse("0 0 1 * 1").unwrap();
+        assert!(either.matches(at(1, 0, 0)));
+        assert!(either.matches(at(6, 0, 0)));
+        assert!(!either.matches(at(7, 0, 0)));
+
+        for bad in [
+            "0 6 * *",
+            "60 * * * *",
+            "*/0 * * * *",
+            "5-1 * * * *",
+            "x * * * *",
+        ] {
+            assert!(Schedule::parse(bad).is_err(), "{}", bad);
+        }
+    }
+
+    #[test]
+    fn test_next_run_is_found_after_a_time() {
+        let schedule = Schedule::parse("30 6 * * 1").unwrap();
+        let from = Local.from_local_datetime(&at(6, 6, 30)).unwrap();
+        let next = schedule.next_after(from).unwrap();
+        assert_eq!(next.naive_local(), at(13, 6, 30));
+
+        let never = Schedule::parse("0 0 31 2 *").unwrap();
+        assert!(never.next_after(from).is_none());
+    }
+
+    #[test]
+    fn test_jobs_name_a_tool_and_its_arguments() {
+        let job = Job::parse(
+            r#"0 6 * * * write_task_dashboard {"page": "Tasks", "include_agenda": true}"#,
+        )
+        .unwrap();
+        assert_eq!(job.schedule.to_string(), "0 6 * * *");
+        assert_eq!(job.tool, "write_task_dashboard");
+        assert_eq!(job.arguments["page"], "Tasks");
+
+        let job = Job::parse("@daily invalidate_cache").unwrap();
+        assert_eq!(job.tool, "invalidate_cache");
+        assert!(job.arguments.is_empty());
+
+        assert!(Job::parse("0 6 * * *").is_err());
+        assert!(Job::parse("@daily compile_digest [1, 2]").is_err());
+    }
+}
@@ -0,0 +1,729 @@
+//! The MCP server: advertises the tool registry and dispatches calls to it
+//!
+//! A server can front several LogSeq graphs. Each has its own client,
+//! caches, and trash; a call goes to the first graph unless it names another
+//! in a `graph` argument, which the server takes off before the tool sees it.
+
+use crate::audit::AuditLog;
+use crate::capture_template::CaptureTemplate;
+use crate::intents::IntentLog;
+use crate::limits::WriteLimits;
+use crate::logseq::api::LogSeqClient;
+use crate::logseq::assets::{
+    AssetError, AssetFile, MAX_ASSET_BYTES, asset_for_url, file_url, list_assets, mime_type,
+};
+use crate::logseq::cache::{DEFAULT_GRAPH_TTL, QueryCache};
+use crate::quota::{Quota, QuotaLimits};
+use crate::region_base::RegionBases;
+use crate::sandbox::Namespace;
+use crate::session::{SESSION_CAPABILITY, SessionConfig};
+use crate::state::{StateArea, StateDir};
+use crate::tools::context::{DEFAULT_GRAPH, ToolContext};
+use crate::tools::registry::ToolRegistry;
+use crate::trash::Trash;
+use crate::workspace::Workspace;
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use rmcp::{
+    ErrorData as McpError,
+    handler::server::ServerHandler,
+    model::{
+        AnnotateAble, CallToolRequestParam, CallToolResult, ClientInfo, Implementation,
+        InitializeRequestParam, InitializeResult, JsonObject, ListResourcesResult, ListToolsResult,
+        PaginatedRequestParam, ProtocolVersion, RawResource, ReadResourceRequestParam,
+        ReadResourceResult, Resource, ResourceContents, ServerCapabilities, ServerInfo, Tool,
+    },
+    service::{RequestContext, RoleServer},
+};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// How long listing tools waits to find out whether LogSeq is reachable
+const PROBE_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Assets listed per page of `resources/list`
+const RESOURCES_PER_PAGE: usize = 500;
+
+/// Optional server behaviour, all off by default
+
+#[derive(Debug, Clone, Default)]
+pub struct ServerOptions {
+    /// Serve the page list, tags, user configs, and current graph from a
+    /// cache that is prefetched at startup and refreshed at this interval
+    pub cache_refresh: Option<Duration>,
+    /// Without `cache_refresh`, reuse the page list, tags, user configs, and
+    /// current graph for this long instead of the default of 60 seconds.
+    /// Zero turns the cache off.
+    pub graph_cache_ttl: Option<Duration>,
+    /// Abort any tool call that takes longer than this
+    pub tool_deadline: Option<Duration>,
+    /// Where to keep server state. Deleted pages and blocks are only kept
+    /// in the trash, and multi-step changes recorded in the intent log, when
+    /// this is set.
+    pub state_dir: Option<StateDir>,
+    /// Record every call to a tool that changes the graph here
+    pub audit_log: Option<Arc<AuditLog>>,
+    /// Add a line for every successful change to today's journal page
+    pub journal_activity: bool,
+    /// Log and flag datascript queries taking at least this long, instead of
+    /// the default of 2 seconds
+    pub slow_query: Option<Duration>,
+    /// Reuse datascript query results for this long, instead of the default
+    /// of 30 seconds. Zero turns the query cache off.
+    pub query_cache_ttl: Option<Duration>,
+    /// Caps on the content a single tool call may write
+    pub write_limits: WriteLimits,
+    /// How many changes of each kind a session may make
+    pub quotas: QuotaLimits,
+    /// Only let tools change pages in this namespace, creating pages in it
+    pub namespace: Option<Namespace>,
+    /// Scopes that sessions and calls can select by name. Panics if two
+    /// share a name.
+    pub workspaces: Vec<Workspace>,
+    /// Templates `capture` shapes notes with, selected by name
+    pub capture_templates: Vec<CaptureTemplate>,
+}
+
+#[derive(Clone)]
+pub struct LogSeqMcpServer {
+    /// A context per graph, the default first
+    graphs: Arc<Vec<ToolContext>>,
+    tools: Arc<ToolRegistry>,
+}
+
+impl LogSeqMcpServer {
+    pub fn new(logseq_client: LogSeqClient) -> Self {
+        Self::with_options(logseq_client, ServerOptions::default())
+    }
+
+    /// Build a server with optional behaviour enabled. Must be called from
+    /// within a Tokio runtime when `cache_refresh` is set.
+    pub fn with_options(logseq_client: LogSeqClient, options: ServerOptions) -> Self {
+        Self::with_graphs(vec![(DEFAULT_GRAPH.to_string(), logseq_client)], options)
+    }
+
+    /// Build a server for several named graphs. Calls go to the first one
+    /// unless they pass another's name as `graph`. Panics if `graphs` is
+    /// empty or names a graph twice.
+    pub fn with_graphs(graphs: Vec<(String, LogSeqClient)>, options: ServerOptions) -> Self {
+        assert!(!graphs.is_empty(), "a server needs at least one graph");
+        let names: Arc<[String]> = graphs.iter().map(|(name, _)| name.clone()).collect();
+        for (i, name) in names.iter().enumerate() {
+            assert!(!names[..i].contains(name), "duplicate graph name: {}", name);
+        }
+
+        let workspaces: Arc<[Workspace]> = options.workspaces.clone().into();
+        for (i, workspace) in workspaces.iter().enumerate() {
+            assert!(
+                !workspaces[..i].iter().any(|w| w.name == workspace.name),
+                "duplicate workspace name: {}",
+                workspace.name
+            );
+        }
+
+        let capture_templates: Arc<[CaptureTemplate]> = options.capture_templates.clone().into();
+        let quota = Arc::new(Quota::new(options.quotas));
+        let contexts = graphs
+            .into_iter()
+            .enumerate()
+            .map(|(i, (name, client))| {
+                let mut context = graph_context(client, &options, (i > 0).then_some(&name));
+                context.graph = name;
+                context.graphs = names.clone();
+                context.quota = quota.clone();
+                context.workspaces = workspaces.clone();
+                context.capture_templates = capture_templates.clone();
+                context
+            })
+            .collect();
+
+        let mut tools = ToolRegistry::builtin();
+        tools.set_deadline(options.tool_deadline);
+
+        Self {
+            graphs: Arc::new(contexts),
+            tools: Arc::new(tools),
+        }
+    }
+
+    /// Ask LogSeq for the current graph of every graph served, waiting at
+    /// most `timeout` for each. Returns each graph's name and why it didn't
+    /// answer, if it didn't.
+    pub async fn check_backends(&self, timeout: Duration) -> Vec<(String, Option<String>)> {
+        let mut results = Vec::new();
+        for context in self.graphs.iter() {
+            let error =
+                match tokio::time::timeout(timeout, context.client.get_current_graph()).await {
+                    Ok(Ok(_)) => None,
+                    Ok(Err(e)) => Some(e.to_string()),
+                    Err(_) => Some(format!("no answer within {:?}", timeout)),
+                };
+            results.push((context.graph.clone(), error));
+        }
+        results
+    }
+
+    /// Check each graph's LogSeq once and log what's wrong with any, so a
+    /// bad URL or token shows up at startup rather than as errors from the
+    /// first tool calls
+    pub async fn log_backend_health(&self) {
+        for context in self.graphs.iter() {
+            match tokio::time::timeout(PROBE_TIMEOUT, context.client.check_health()).await {
+                Ok(Ok(health)) => tracing::info!(
+                    "Graph '{}': connected to LogSeq {} with graph {} ({} ms)",
+                    context.graph,
+                    health.version.as_deref().unwrap_or("(unknown version)"),
+                    health.graph.as_deref().unwrap_or("(unnamed)"),
+                    health.latency_ms
+                ),
+                Ok(Err(e)) => tracing::warn!(
+                    "Graph '{}': {}. Tools that need LogSeq will fail until this is fixed.",
+                    context.graph,
+                    e
+                ),
+                Err(_) => tracing::warn!(
+                    "Graph '{}': LogSeq didn't answer within {:?}; it may be busy or hung",
+                    context.graph,
+                    PROBE_TIMEOUT
+                ),
+            }
+        }
+    }
+
+    /// The same server for a new session, with its budgets unspent. Sessions
+    /// share everything else.
+    pub fn for_new_session(&self) -> Self {
+        let quota = Arc::new(Quota::new(self.graphs[0].quota.limits()));
+        let graphs = self
+            .graphs
+            .iter()
+            .map(|context| {
+                let mut context = context.clone();
+                context.quota = quota.clone();
+                context
+            })
+            .collect();
+        Self {
+            graphs: Arc::new(graphs),
+            tools: self.tools.clone(),
+        }
+    }
+
+    /// Call a tool outside any client session, as scheduled jobs do. Like a
+    /// client's call, it goes to the graph named by a `graph` argument, or
+    /// the default one.
+    pub async fn run_tool(
+        &self,
+        name: &str,
+        arguments: Option<JsonObject>,
+    ) -> Result<CallToolResult, McpError> {
+        let (graph, arguments) = self.route(arguments)?;
+        self.tools.call(graph, name, arguments).await
+    }
+
+    /// The context for the graph a call names, and the call's arguments
+    /// without the graph
+    fn route(
+        &self,
+        mut arguments: Option<JsonObject>,
+    ) -> Result<(&ToolContext, Option<JsonObject>), McpError> {
+        let graph = match arguments.as_mut().and_then(|args| args.remove("graph")) {
+            None | Some(serde_json::Value::Null) => return Ok((&self.graphs[0], arguments)),
+            Some(serde_json::Value::String(graph)) => graph,
+            Some(other) => {
+                return Err(McpError::invalid_params(
+                    format!("'graph' must be a graph name, got {}", other),
+                    None,
+                ));
+            }
+        };
+        match self.graphs.iter().find(|ctx| ctx.graph == graph) {
+            Some(context) => Ok((context, arguments)),
+            None => Err(McpError::invalid_params(
+                format!(
+                    "Unknown graph '{}'; configured graphs: {}",
+                    graph,
+                    self.graphs[0].graphs.join(", ")
+                ),
+                Some(serde_json::json!({ "graphs": &*self.graphs[0].graphs })),
+            )),
+        }
+    }
+
+    /// Tool definitions, with a `graph` parameter when there's more than one
+    /// graph to choose from
+    fn definitions_for(&self, config: &SessionConfig) -> Vec<Tool> {
+        let mut tools = self.tools.definitions_for(config);
+        if self.graphs.len() > 1 {
+            for tool in &mut tools {
+                add_graph_param(tool, &self.graphs[0].graphs);
+            }
+        }
+        tools
+    }
+
+    /// [`definitions_for`](Self::definitions_for), with the description of
+    /// each tool that needs LogSeq saying so when it can't be reached
+    async fn current_definitions(&self, config: &SessionConfig) -> Vec<Tool> {
+        let mut tools = self.definitions_for(config);
+        let down: Vec<String> = self
+            .check_backends(PROBE_TIMEOUT)
+            .await
+            .into_iter()
+            .filter_map(|(graph, error)| error.map(|_| graph))
+            .collect();
+        if down.is_empty() {
+            return tools;
+        }
+
+        let note = match self.graphs.len() {
+            1 => "Currently unavailable: LogSeq can't be reached, so calls fail until it's back."
+                .to_string(),
+            _ => format!(
+                "Currently unavailable for graph(s) {}: LogSeq can't be reached, so calls to them fail until it's back.",
+                down.join(", ")
+            ),
+        };
+        for tool in &mut tools {
+            if self.tools.needs_logseq(&tool.name) {
+                let description = tool.description.as_deref().unwrap_or_default();
+                tool.description = Some(format!("{} {}", note, description).into());
+            }
+        }
+        tools
+    }
+
+    /// Every graph's assets, as resources named by their path in the graph
+    /// folder
+    async fn asset_resources(&self) -> Result<Vec<Resource>, McpError> {
+        let mut resources = Vec::new();
+        for context in self.graphs.iter() {
+            let Some(graph_dir) = context.graph_dir().await else {
+                continue;
+            };
+            let assets = tokio::task::spawn_blocking(move || list_assets(&graph_dir))
+                .await
+                .map_err(|e| McpError::internal_error(e.to_string(), None))?
+                .map_err(|e| McpError::internal_error(e.to_string(), None))?;
+            for asset in assets {
+                resources.push(self.asset_resource(&context.graph, asset));
+            }
+        }
+        Ok(resources)
+    }
+
+    fn asset_resource(&self, graph: &str, asset: AssetFile) -> Resource {
+        let name = match self.graphs.len() {
+            1 => asset.relative,
+            _ => format!("{}: {}", graph, asset.relative),
+        };
+        RawResource {
+            uri: file_url(&asset.path),
+            name,
+            description: None,
+            mime_type: Some(mime_type(&asset.path).to_string()),
+            size: u32::try_from(asset.size).ok(),
+        }
+        .no_annotation()
+    }
+
+    /// The contents of the asset a `file://` URL names, from whichever
+    /// graph's assets folder it's in
+    async fn read_asset(&self, uri: &str) -> Result<ResourceContents, McpError> {
+        for context in self.graphs.iter() {
+            let Some(graph_dir) = context.graph_dir().await else {
+                continue;
+            };
+            let url = uri.to_string();
+            let found = tokio::task::spawn_blocking(move || {
+                asset_for_url(&graph_dir, &url, MAX_ASSET_BYTES)
+            })
+            .await
+            .map_err(|e| McpError::internal_error(e.to_string(), None))?;
+            let asset = match found {
+                Ok(asset) => asset,
+                Err(AssetError::NotAnAsset(_)) => continue,
+                Err(e @ AssetError::TooLarge { .. }) => {
+                    return Err(McpError::invalid_request(
+                        e.to_string(),
+                        Some(serde_json::json!({ "error": "too_large", "retryable": false })),
+                    ));
+                }
+                Err(AssetError::Io(e)) => {
+                    return Err(McpError::internal_error(e.to_string(), None));
+                }
+            };
+
+            let bytes = tokio::fs::read(&asset.path)
+                .await
+                .map_err(|e| McpError::internal_error(e.to_string(), None))?;
+            let mime_type = mime_type(&asset.path);
+            return Ok(match String::from_utf8(bytes) {
+                Ok(text) if mime_type.starts_with("text/") => {
+                    ResourceContents::TextResourceContents {
+                        uri: uri.to_string(),
+                        mime_type: Some(mime_type.to_string()),
+                        text,
+                    }
+                }
+                Ok(text) => blob(uri, mime_type, text.into_bytes()),
+                Err(e) => blob(uri, mime_type, e.into_bytes()),
+            });
+        }
+        Err(McpError::resource_not_found(
+            format!("{} is not a file in a graph's assets folder", uri),
+            None,
+        ))
+    }
+}
+
+/// Binary resource contents, base64 encoded
+fn blob(uri: &str, mime_type: &str, bytes: Vec<u8>) -> ResourceContents {
+    ResourceContents::BlobResourceContents {
+        uri: uri.to_string(),
+        mime_type: Some(mime_type.to_string()),
+        blob: STANDARD.encode(bytes),
+    }
+}
+
+/// The context for one graph. `state_subdir` names the subdirectory of the
+/// state areas its trash, intent log, and region contents are kept in, if
+/// not those areas themselves.
+fn graph_context(
+    logseq_client: LogSeqClient,
+    options: &ServerOptions,
+    state_subdir: Option<&str>,
+) -> ToolContext {
+    let mut context = match options.cache_refresh {
+        Some(interval) => {
+            let context = ToolContext::with_cache(logseq_client);
+            context.cache.spawn_refresh(interval);
+            context
+        }
+        None => ToolContext::with_cache_ttl(
+            logseq_client,
+            options.graph_cache_ttl.unwrap_or(DEFAULT_GRAPH_TTL),
+        ),
+    };
+    if let Some(state_dir) = &options.state_dir {
+        let area = |area| match state_subdir {
+            Some(dir) => state_dir.path(area).join(dir),
+            None => state_dir.path(area),
+        };
+        context.trash = Some(Arc::new(Trash::new(area(StateArea::Trash))));
+        context.region_bases = Some(Arc::new(RegionBases::new(
+            area(StateArea::Snapshots).join("regions"),
+        )));
+        let intents = IntentLog::new(area(StateArea::Intents));
+        match intents.interrupted() {
+            Ok(interrupted) if !interrupted.is_empty() => tracing::warn!(
+                "{} operation(s) on graph '{}' were interrupted part-way; use list_interrupted_operations to review them",
+                interrupted.len(),
+                state_subdir.unwrap_or(DEFAULT_GRAPH)
+            ),
+            Ok(_) => {}
+            Err(e) => tracing::warn!("Could not read the intent log: {}", e),
+        }
+        context.intents = Some(Arc::new(intents));
+    }
+    context.audit = options.audit_log.clone();
+    context.journal_activity = options.journal_activity;
+    if let Some(slow_query) = options.slow_query {
+        context.slow_query = slow_query;
+    }
+    if let Some(ttl) = options.query_cache_ttl {
+        context.queries = Arc::new(QueryCache::new(ttl));
+    }
+    context.limits = options.write_limits;
+    context.namespace = options.namespace.clone();
+    context
+}
+
+/// Add an optional `graph` parameter, naming one of `graphs`, to a tool's
+/// input schema
+fn add_graph_param(tool: &mut Tool, graphs: &[String]) {
+    let schema = Arc::make_mut(&mut tool.input_schema);
+    let properties = schema
+        .entry("properties")
+        .or_insert_with(|| serde_json::Value::Object(JsonObject::new()));
+    if let Some(properties) = properties.as_object_mut() {
+        properties.insert(
+            "graph".to_string(),
+            serde_json::json!({
+                "type": "string",
+                "enum": graphs,
+                "description": format!("The graph to use. Defaults to '{}'.", graphs[0]),
+            }),
+        );
+    }
+}
+
+/// The settings a client asked for when it initialized, rejecting ones we
+/// can't honour
+fn session_config(
+    tools: &ToolRegistry,
+    workspaces: &[Workspace],
+    info: Option<&ClientInfo>,
+) -> Result<SessionConfig, McpError> {
+    let config = SessionConfig::from_client(info).map_err(|e| {
+        McpError::invalid_params(
+            format!("Invalid '{}' session settings: {}", SESSION_CAPABILITY, e),
+            None,
+        )
+    })?;
+
+    let unknown: Vec<&str> = config
+        .allowed_tools
+        .iter()
+        .flatten()
+        .map(String::as_str)
+        .filter(|name| !tools.contains(name))
+        .collect();
+    if !unknown.is_empty() {
+        return Err(McpError::invalid_params(
+            format!("Unknown tools in allowedTools: {}", unknown.join(", ")),
+            Some(serde_json::json!({ "unknown_tools": unknown })),
+        ));
+    }
+    if let Some(workspace) = &config.workspace
+        && !workspaces.iter().any(|w| &w.name == workspace)
+    {
+        return Err(McpError::invalid_params(
+            format!("Unknown workspace '{}'", workspace),
+            Some(serde_json::json!({ "workspace": workspace })),
+        ));
+    }
+    Ok(config)
+}
+
+impl ServerHandler for LogSeqMcpServer {
+    fn get_info(&self) -> ServerInfo {
+        InitializeResult {
+            protocol_version: ProtocolVersion::LATEST,
+            capabilities: ServerCapabilities::builder()
+                .enable_tools()
+                .enable_resources()
+                .build(),
+            server_info: Implementation {
+                name: "logseq-mcp-server".into(),
+                version: env!("CARGO_PKG_VERSION").into(),
+            },
+            instructions: Some("A LogSeq MCP server for managing your knowledge graph".into()),
+        }
+    }
+
+    async fn initialize(
+        &self,
+        request: InitializeRequestParam,
+        context: RequestContext<RoleServer>,
+    ) -> Result<InitializeResult, McpError> {
+        let config = session_config(&self.tools, &self.graphs[0].workspaces, Some(&request))?;
+        if context.peer.peer_info().is_none() {
+            context.peer.set_peer_info(request);
+        }
+
+        let mut info = self.get_info();
+        if config != SessionConfig::default() {
+            info.capabilities.experimental =
+                Some([(SESSION_CAPABILITY.to_string(), config.to_capability())].into());
+        }
+        Ok(info)
+    }
+
+    async fn list_tools(
+        &self,
+        _params: Option<PaginatedRequestParam>,
+        context: RequestContext<RoleServer>,
+    ) -> Result<ListToolsResult, McpError> {
+        let config = session_config(
+            &self.tools,
+            &self.graphs[0].workspaces,
+            context.peer.peer_info(),
+        )?;
+        Ok(ListToolsResult {
+            tools: self.current_definitions(&config).await,
+            next_cursor: None,
+        })
+    }
+
+    async fn list_resources(
+        &self,
+        params: Option<PaginatedRequestParam>,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<ListResourcesResult, McpError> {
+        let start = match params.and_then(|p| p.cursor) {
+            Some(cursor) => cursor.parse::<usize>().map_err(|_| {
+                McpError::invalid_params(format!("Invalid cursor '{}'", cursor), None)
+            })?,
+            None => 0,
+        };
+        let resources = self.asset_resources().await?;
+        let end = resources
+            .len()
+            .min(start.saturating_add(RESOURCES_PER_PAGE));
+        Ok(ListResourcesResult {
+            next_cursor: (end < resources.len()).then(|| end.to_string()),
+            resources: resources
+                .into_iter()
+                .skip(start)
+                .take(end.saturating_sub(start))
+                .collect(),
+        })
+    }
+
+    async fn read_resource(
+        &self,
+        params: ReadResourceRequestParam,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<ReadResourceResult, McpError> {
+        Ok(ReadResourceResult {
+            contents: vec![self.read_asset(&params.uri).await?],
+        })
+    }
+
+    async fn call_tool(
+        &self,
+        params: CallToolRequestParam,
+        context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, McpError> {
+        let config = session_config(
+            &self.tools,
+            &self.graphs[0].workspaces,
+            context.peer.peer_info(),
+        )?;
+        let (graph, arguments) = self.route(params.arguments)?;
+        let mut ctx = graph.for_session(config);
+        ctx.client_name = context
+            .peer
+            .peer_info()
+            .map(|info| info.client_info.name.clone());
+        self.tools.call(&ctx, &params.name, arguments).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::logseq::mock::{MockGraph, MockLogSeq};
+    use serde_json::json;
+
+    async fn serve(page: &str) -> MockLogSeq {
+        let mut graph = MockGraph::new();
+        graph.add_page(page);
+        MockLogSeq::serve(graph).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_calls_are_routed_to_the_named_graph() {
+        let (work, home) = (serve("Standup").await, serve("Garden").await);
+        let server = LogSeqMcpServer::with_graphs(
+            vec![
+                ("work".to_string(), work.client()),
+                ("home".to_string(), home.client()),
+            ],
+            ServerOptions::default(),
+        );
+
+        for (graph, page) in [(None, "standup"), (Some("home"), "garden")] {
+            let arguments = graph.map(|g| json!({ "graph": g }).as_object().unwrap().clone());
+            let (ctx, arguments) = server.route(arguments).unwrap();
+            assert!(arguments.is_none_or(|args| args.is_empty()));
+            let result = server.tools.call(ctx, "list_pages", None).await.unwrap();
+            let text = format!("{:?}", result.content);
+            assert!(text.contains(page), "{}", text);
+        }
+
+        let arguments = json!({ "graph": "office" }).as_object().cloned();
+        let Err(err) = server.route(arguments) else {
+            panic!("an unknown graph was accepted");
+        };
+        assert!(err.message.contains("work, home"), "{}", err.message);
+
+        let tools = server.definitions_for(&SessionConfig::default());
+        assert!(tools.iter().all(
+            |tool| tool.input_schema["properties"]["graph"]["enum"] == json!(["work", "home"])
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_single_graph_schemas_have_no_graph_param() {
+        let server = LogSeqMcpServer::new(serve("Inbox").await.client());
+        let tools = server.definitions_for(&SessionConfig::default());
+        assert!(tools.iter().all(|tool| {
+            tool.input_schema
+                .get("properties")
+                .is_none_or(|p| p.get("graph").is_none())
+        }));
+    }
+
+    #[tokio::test]
+    async fn test_graph_assets_are_served_as_resources() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("assets")).unwrap();
+        std::fs::write(dir.path().join("assets/chart.png"), b"\x89PNG").unwrap();
+        std::fs::write(dir.path().join("assets/notes.txt"), "plain notes").unwrap();
+        std::fs::write(dir.path().join("logseq.md"), "not an asset").unwrap();
+        let logseq = serve("Inbox").await;
+        logseq.respond(
+            "logseq.App.getCurrentGraph",
+            json!({"name": "notes", "path": dir.path()}),
+        );
+        let server = LogSeqMcpServer::new(logseq.client());
+
+        let resources = server.asset_resources().await.unwrap();
+        let listed: Vec<_> = resources
+            .iter()
+            .map(|r| (r.name.as_str(), r.mime_type.as_deref(), r.size))
+            .collect();
+        assert_eq!(
+            listed,
+            [
+                ("assets/chart.png", Some("image/png"), Some(4)),
+                ("assets/notes.txt", Some("text/plain"), Some(11)),
+            ]
+        );
+
+        let chart = server.read_asset(&resources[0].uri).await.unwrap();
+        assert_eq!(
+            serde_json::to_value(chart).unwrap(),
+            json!({"uri": resources[0].uri, "mimeType": "image/png", "blob": "iVBORw=="})
+        );
+        let ResourceContents::TextResourceContents { text, .. } =
+            server.read_asset(&resources[1].uri).await.unwrap()
+        else {
+            panic!("text assets are read as text");
+        };
+        assert_eq!(text, "plain notes");
+
+        let outside = file_url(&dir.path().join("assets/../logseq.md"));
+        let err = server.read_asset(&outside).await.unwrap_err();
+        assert_eq!(err.code, rmcp::model::ErrorCode::RESOURCE_NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_unreachable_logseq_is_reported_consistently() {
+        let client = LogSeqClient::new("http://127.0.0.1:9", "token").unwrap();
+        let server = LogSeqMcpServer::new(client);
+
+        let tools = server.current_definitions(&SessionConfig::default()).await;
+        let description = |name: &str| {
+            let tool = tools.iter().find(|tool| tool.name == name).unwrap();
+            tool.description.clone().unwrap_or_default()
+        };
+        assert!(description("search").starts_with("Currently unavailable"));
+        assert!(!description("list_trash").starts_with("Currently unavailable"));
+
+        let (ctx, _) = server.route(None).unwrap();
+        let err = server
+            .tools
+            .call(ctx, "search", json!({"query": "x"}).as_object().cloned())
+            .await;
+        let err = err.unwrap_err();
+        let data = err.data.unwrap_or_default();
+        assert_eq!(data["error"], "backend_unavailable");
+        assert_eq!(data["retry_after_secs"], 5);
+
+        // Tools that don't need LogSeq still work
+        server.tools.call(ctx, "list_graphs", None).await.unwrap();
+    }
+}
@@ -0,0 +1,146 @@
+//! Per-session settings negotiated when a client initializes
+//!
+//! A client opts in by adding a `logseq` entry to the experimental
+//! capabilities of its `initialize` request:
+//!
+//! ```json
+//! {
+//!   "capabilities": {
+//!     "experimental": {
+//!       "logseq": { "readOnly": true, "allowedTools": ["search"], "outputFormat": "json" }
+//!     }
+//!   }
+//! }
+//! ```
+//!
+//! `workspace` names one of the server's workspaces (see
+//! [`crate::workspace`]) for calls to default to.
+//!
+//! Each session gets its own settings, so clients sharing one HTTP server can
+//! be restricted independently. Clients that don't send the capability get
+//! [`SessionConfig::default`]: every tool, markdown output.
+
+use rmcp::model::{ClientInfo, JsonObject};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+
+/// Key of the experimental capability carrying session settings
+pub const SESSION_CAPABILITY: &str = "logseq";
+
+/// How tools that render listings (page content, search results, todos,
+/// pages) present them
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputFormat {
+    #[default]
+    Markdown,
+    Json,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct SessionConfig {
+    /// Hide and refuse every tool that changes the graph
+    #[serde(default)]
+    pub read_only: bool,
+    /// Only these tools are listed and callable; all tools when unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub allowed_tools: Option<BTreeSet<String>>,
+    #[serde(default)]
+    pub output_format: OutputFormat,
+    /// The workspace calls default to when they don't name one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub workspace: Option<String>,
+}
+
+impl SessionConfig {
+    /// Read the settings a client sent with `initialize`, falling back to the
+    /// defaults when it sent none
+    pub fn from_client(info: Option<&ClientInfo>) -> Result<Self, serde_json::Error> {
+        let settings = info
+            .and_then(|info| info.capabilities.experimental.as_ref())
+            .and_then(|experimental| experimental.get(SESSION_CAPABILITY));
+        match settings {
+            Some(settings) => serde_json::from_value(settings.clone().into()),
+            None => Ok(Self::default()),
+        }
+    }
+
+    /// Whether a tool may be listed and called in this session
+    pub fn allows(&self, tool: &str, read_only_tool: bool) -> bool {
+        if self.read_only && !read_only_tool {
+            return false;
+        }
+        self.allowed_tools
+            .as_ref()
+            .is_none_or(|allowed| allowed.contains(tool))
+    }
+
+    /// The settings as an experimental capability, to echo back what the
+    /// server applied
+    pub fn to_capability(&self) -> JsonObject {
+        match serde_json::to_value(self) {
+            Ok(serde_json::Value::Object(object)) => object,
+            _ => JsonObject::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rmcp::model::ClientCapabilities;
+    use serde_json::json;
+    use std::collections::BTreeMap;
+
+    fn client_with(settings: serde_json::Value) -> ClientInfo {
+        let mut experimental = BTreeMap::new();
+        experimental.insert(
+            SESSION_CAPABILITY.to_string(),
+            settings.as_object().cloned().unwrap(),
+        );
+        ClientInfo {
+            capabilities: ClientCapabilities {
+                experimental: Some(experimental),
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_defaults_without_capability() {
+        let config = SessionConfig::from_client(Some(&ClientInfo::default())).unwrap();
+        assert_eq!(config, SessionConfig::default());
+        assert_eq!(SessionConfig::from_client(None).unwrap(), config);
+        assert!(config.allows("delete_page", false));
+    }
+
+    #[test]
+    fn test_parses_settings() {
+        let info = client_with(json!({
+            "readOnly": true,
+            "allowedTools": ["search", "create_page"],
+            "outputFormat": "json"
+        }));
+        let config = SessionConfig::from_client(Some(&info)).unwrap();
+
+        assert_eq!(config.output_format, OutputFormat::Json);
+        assert!(config.allows("search", true));
+        assert!(!config.allows("create_page", false), "read-only wins");
+        assert!(!config.allows("list_pages", true), "not in allowed tools");
+        assert_eq!(
+            config.to_capability()["allowedTools"],
+            json!(["create_page", "search"])
+        );
+    }
+
+    #[test]
+    fn test_rejects_unknown_settings() {
+        let info = client_with(json!({ "readonly": true }));
+        assert!(SessionConfig::from_client(Some(&info)).is_err());
+
+        let info = client_with(json!({ "outputFormat": "yaml" }));
+        assert!(SessionConfig::from_client(Some(&info)).is_err());
+    }
+}
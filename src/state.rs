@@ -0,0 +1,339 @@
+//! On-disk home for server state (audit log, snapshots, trash, intent log,
+//! failed payloads)
+//!
+//! The state worth keeping can be bundled into a [`StateArchive`], a JSON
+//! file that [`StateDir::import`] unpacks on another machine, so a setup
+//! moves along with the graph.
+
+use anyhow::{Result, anyhow, bail};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::{Component, Path, PathBuf};
+
+/// Environment variable overriding the state directory
+pub const STATE_DIR_ENV: &str = "LOGSEQ_MCP_STATE_DIR";
+
+/// Directory name used beneath the platform state directory
+const APP_DIR_NAME: &str = "logseq-mcp-server";
+
+/// What a state archive says it is
+pub const ARCHIVE_FORMAT: &str = "logseq-mcp-server-state";
+
+/// The version of the archive layout this server reads and writes
+pub const ARCHIVE_VERSION: u32 = 1;
+
+/// Where an imported config file is written, inside the state directory
+pub const IMPORTED_CONFIG_FILE: &str = "config.toml";
+
+/// A subsystem with its own area inside the state directory
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StateArea {
+    Audit,
+    Snapshots,
+    Trash,
+    Intents,
+    Payloads,
+}
+
+impl StateArea {
+    pub const ALL: [StateArea; 5] = [
+        StateArea::Audit,
+        StateArea::Snapshots,
+        StateArea::Trash,
+        StateArea::Intents,
+        StateArea::Payloads,
+    ];
+
+    /// Areas carried over by a [`StateArchive`]. Failed payloads are left
+    /// out, as they belong to bug reports from this machine.
+    pub const EXPORTED: [StateArea; 4] = [
+        StateArea::Audit,
+        StateArea::Snapshots,
+        StateArea::Trash,
+        StateArea::Intents,
+    ];
+
+    pub fn dir_name(self) -> &'static str {
+        match self {
+            StateArea::Audit => "audit",
+            StateArea::Snapshots => "snapshots",
+            StateArea::Trash => "trash",
+            StateArea::Intents => "intents",
+            StateArea::Payloads => "payloads",
+        }
+    }
+}
+
+/// The server's state directory and the per-subsystem areas within it
+#[derive(Debug, Clone)]
+pub struct StateDir {
+    root: PathBuf,
+}
+
+impl StateDir {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    /// Resolve the state directory from an explicit path, then
+    /// `LOGSEQ_MCP_STATE_DIR`, then the platform default
+    /// (`$XDG_STATE_HOME/logseq-mcp-server` on Linux).
+    pub fn resolve(explicit: Option<PathBuf>) -> Result<Self> {
+        explicit
+            .or_else(|| std::env::var_os(STATE_DIR_ENV).map(PathBuf::from))
+            .or_else(|| {
+                dirs::state_dir()
+                    .or_else(dirs::data_local_dir)
+                    .map(|dir| dir.join(APP_DIR_NAME))
+            })
+            .map(Self::new)
+            .ok_or_else(|| {
+                anyhow!(
+                    "Could not determine a state directory; set {} to choose one",
+                    STATE_DIR_ENV
+                )
+            })
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    pub fn path(&self, area: StateArea) -> PathBuf {
+        self.root.join(area.dir_name())
+    }
+
+    /// Create the state directory if it doesn't exist yet
+    pub fn init(&self) -> io::Result<()> {
+        fs::create_dir_all(&self.root)
+    }
+
+    /// Remove every subsystem area, returning the ones that existed.
+    ///
+    /// Only known areas are removed, so pointing the state directory at a
+    /// shared location never deletes unrelated files.
+    pub fn clear(&self) -> io::Result<Vec<StateArea>> {
+        let mut cleared = Vec::new();
+        for area in StateArea::ALL {
+            match fs::remove_dir_all(self.path(area)) {
+                Ok(()) => cleared.push(area),
+                Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(cleared)
+    }
+
+    /// Bundle the exported areas, and the config file's text if there is
+    /// one, into an archive
+    pub fn export(&self, config: Option<String>) -> io::Result<StateArchive> {
+        let mut files = Vec::new();
+        for area in StateArea::EXPORTED {
+            let mut dirs = vec![self.path(area)];
+            while let Some(dir) = dirs.pop() {
+                let entries = match fs::read_dir(&dir) {
+                    Ok(entries) => entries,
+                    Err(e) if e.kind() == io::ErrorKind::NotFound => continue,
+                    Err(e) => return Err(e),
+                };
+                for entry in entries {
+                    let path = entry?.path();
+                    if path.is_dir() {
+                        dirs.push(path);
+                        continue;
+                    }
+                    let relative = path.strip_prefix(&self.root).unwrap_or(&path);
+                    files.push(ArchivedFile {
+                        path: relative.to_string_lossy().replace('\\', "/"),
+                        data: STANDARD.encode(fs::read(&path)?),
+                    });
+                }
+            }
+        }
+        files.sort_by(|a, b| a.path.cmp(&b.path));
+        Ok(StateArchive {
+            format: ARCHIVE_FORMAT.to_string(),
+            version: ARCHIVE_VERSION,
+            created_at: Utc::now(),
+            config,
+            files,
+        })
+    }
+
+    /// Unpack an archive into this state directory. Nothing is written if
+    /// any file in it is malformed or already exists here, so existing
+    /// state is never overwritten; clear it first to replace it.
+    pub fn import(&self, archive: &StateArchive) -> Result<ImportSummary> {
+        if archive.format != ARCHIVE_FORMAT || archive.version != ARCHIVE_VERSION {
+            bail!(
+                "Not a version {} {} archive (found {} version {})",
+                ARCHIVE_VERSION,
+                ARCHIVE_FORMAT,
+                archive.format,
+                archive.version
+            );
+        }
+        let mut writes = Vec::new();
+        for file in &archive.files {
+            let relative = Path::new(&file.path);
+            let exported = StateArea::EXPORTED
+                .iter()
+                .any(|area| relative.starts_with(area.dir_name()));
+            let normal = relative
+                .components()
+                .all(|c| matches!(c, Component::Normal(_)));
+            if !exported || !normal {
+                bail!(
+                    "The archive has a file outside the state areas: {}",
+                    file.path
+                );
+            }
+            let data = STANDARD
+                .decode(&file.data)
+                .map_err(|e| anyhow!("The archive's copy of {} is corrupt: {}", file.path, e))?;
+            writes.push((self.root.join(relative), data));
+        }
+        let config = archive.config.as_ref().map(|config| {
+            (
+                self.root.join(IMPORTED_CONFIG_FILE),
+                config.clone().into_bytes(),
+            )
+        });
+        writes.extend(config.clone());
+
+        let existing: Vec<String> = writes
+            .iter()
+            .filter(|(path, _)| path.exists())
+            .map(|(path, _)| path.display().to_string())
+            .collect();
+        if !existing.is_empty() {
+            bail!(
+                "Importing would overwrite {}; clear the state directory first",
+                existing.join(", ")
+            );
+        }
+        for (path, data) in &writes {
+            if let Some(dir) = path.parent() {
+                fs::create_dir_all(dir)?;
+            }
+            fs::write(path, data)?;
+        }
+        Ok(ImportSummary {
+            files: archive.files.len(),
+            config: config.map(|(path, _)| path),
+        })
+    }
+}
+
+/// Server state bundled up to move to another machine
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StateArchive {
+    pub format: String,
+    pub version: u32,
+    pub created_at: DateTime<Utc>,
+    /// The config file in use when the archive was made, as it was written
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub config: Option<String>,
+    pub files: Vec<ArchivedFile>,
+}
+
+/// A file from the state directory
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ArchivedFile {
+    /// Where the file goes in the state directory, with `/` separators
+    pub path: String,
+    /// The file's contents, base64-encoded
+    pub data: String,
+}
+
+/// What [`StateDir::import`] did
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImportSummary {
+    /// How many state files were written
+    pub files: usize,
+    /// Where the archive's config file was written, if it had one
+    pub config: Option<PathBuf>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_explicit_path_takes_precedence() {
+        let state = StateDir::resolve(Some(PathBuf::from("/tmp/explicit-state"))).unwrap();
+        assert_eq!(state.root(), Path::new("/tmp/explicit-state"));
+        assert_eq!(
+            state.path(StateArea::Snapshots),
+            Path::new("/tmp/explicit-state/snapshots")
+        );
+    }
+
+    #[test]
+    fn test_clear_removes_only_known_areas() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = StateDir::new(dir.path());
+        state.init().unwrap();
+
+        fs::create_dir_all(state.path(StateArea::Trash).join("pages")).unwrap();
+        fs::write(state.path(StateArea::Trash).join("pages/a.json"), "{}").unwrap();
+        fs::create_dir_all(state.path(StateArea::Audit)).unwrap();
+        fs::write(dir.path().join("unrelated.txt"), "keep me").unwrap();
+
+        let cleared = state.clear().unwrap();
+        assert_eq!(cleared, vec![StateArea::Audit, StateArea::Trash]);
+        assert!(!state.path(StateArea::Trash).exists());
+        assert!(!state.path(StateArea::Audit).exists());
+        assert!(dir.path().join("unrelated.txt").exists());
+
+        assert!(state.clear().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_state_moves_between_directories() {
+        let from = tempfile::tempdir().unwrap();
+        let state = StateDir::new(from.path());
+        fs::create_dir_all(state.path(StateArea::Trash).join("pages")).unwrap();
+        fs::write(
+            state.path(StateArea::Trash).join("pages/a.json"),
+            b"{\"a\":1}",
+        )
+        .unwrap();
+        fs::create_dir_all(state.path(StateArea::Payloads)).unwrap();
+        fs::write(state.path(StateArea::Payloads).join("failed.json"), "{}").unwrap();
+
+        let archive = state.export(Some("warm-cache = true\n".into())).unwrap();
+        let paths: Vec<&str> = archive.files.iter().map(|f| f.path.as_str()).collect();
+        assert_eq!(paths, vec!["trash/pages/a.json"]);
+
+        let json = serde_json::to_string(&archive).unwrap();
+        let archive: StateArchive = serde_json::from_str(&json).unwrap();
+        let to = tempfile::tempdir().unwrap();
+        let target = StateDir::new(to.path());
+        let summary = target.import(&archive).unwrap();
+        assert_eq!(summary.files, 1);
+        assert_eq!(
+            fs::read_to_string(target.path(StateArea::Trash).join("pages/a.json")).unwrap(),
+            "{\"a\":1}"
+        );
+        assert_eq!(
+            fs::read_to_string(summary.config.unwrap()).unwrap(),
+            "warm-cache = true\n"
+        );
+
+        let err = target.import(&archive).unwrap_err();
+        assert!(err.to_string().contains("overwrite"), "{}", err);
+
+        let mut escaping = archive.clone();
+        escaping.config = None;
+        escaping.files[0].path = "trash/../../escaped".to_string();
+        let err = StateDir::new(tempfile::tempdir().unwrap().path())
+            .import(&escaping)
+            .unwrap_err();
+        assert!(err.to_string().contains("outside"), "{}", err);
+    }
+}
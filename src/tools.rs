@@ -1,22 +1,209 @@
+pub mod add_tag_to_block;
+pub mod analyze_tag_cooccurrence;
+pub mod append_block_to_page;
+pub mod apply_corrections;
+pub mod build_context;
+pub mod capture;
+pub mod compile_digest;
+pub mod context;
+pub mod create_block;
+pub mod create_block_reference;
+pub mod create_page;
+pub mod create_page_from_template;
+pub mod datascript_query;
+pub mod delete_block;
+pub mod delete_page;
+pub mod export_link_graph;
+pub mod extract_corpus;
+pub mod find_broken_links;
+pub mod find_duplicates;
+pub mod find_incomplete_todos;
+pub mod find_scheduled_tasks;
+pub mod get_block;
+pub mod get_block_children;
+pub mod get_block_property;
+pub mod get_block_referencing_blocks;
+pub mod get_current_block;
+pub mod get_current_graph;
+pub mod get_current_page;
+pub mod get_due_cards;
+pub mod get_editor_context;
+pub mod get_graph_stats;
+pub mod get_namespace_tree;
+pub mod get_on_this_day;
+pub mod get_page;
+pub mod get_page_checksums;
+pub mod get_page_content;
+pub mod get_recent_changes;
+pub mod get_state_from_store;
+pub mod get_user_configs;
+pub mod get_writing_stats;
+pub mod health_check;
+pub mod import_markdown;
+pub mod invalidate_cache;
+pub mod list_all_tags;
+pub mod list_flashcards;
+pub mod list_graphs;
+pub mod list_interrupted_operations;
+pub mod list_pages;
+pub mod list_trash;
+pub mod move_block;
+pub mod open_in_logseq;
+pub mod page_stats;
+pub mod prepend_block_to_page;
+pub mod quote_block;
+pub mod registry;
+pub mod remediation;
+pub mod remove_block_property;
+pub mod rename_tag;
+pub mod reset_write_budget;
+pub mod resolve_interrupted_operation;
+pub mod resolve_page_name;
+pub mod restore_from_trash;
+pub mod search;
+pub mod set_todo_state;
+pub mod show_ui_message;
+pub mod update_block;
+pub mod upsert_block_property;
+pub mod write_task_dashboard;
+
+#[cfg(test)]
+mod testing;
+
 use crate::logseq::api::{Block, SearchResult, TodoItem};
+use crate::logseq::content::{INCOMPLETE_MARKERS, is_property_line, strip_property_lines};
+use chrono::Datelike;
+use schemars::JsonSchema;
+use serde::Deserialize;
+use serde_json::Value;
+
+/// What `format_blocks_as_markdown_with` includes in its output
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MarkdownOptions {
+    /// Add an `id:: <uuid>` property line to each block so it can be
+    /// addressed by UUID
+    pub include_uuids: bool,
+    /// Keep the `key:: value` property lines in block content
+    pub include_properties: bool,
+    /// Only render this many levels of blocks (1 = top-level blocks only)
+    pub max_depth: Option<usize>,
+}
+
+impl Default for MarkdownOptions {
+    fn default() -> Self {
+        Self {
+            include_uuids: false,
+            include_properties: true,
+            max_depth: None,
+        }
+    }
+}
 
 pub fn format_blocks_as_markdown(blocks: &[Block]) -> String {
+    format_blocks_as_markdown_with(blocks, &MarkdownOptions::default())
+}
+
+pub fn format_blocks_as_markdown_with(blocks: &[Block], options: &MarkdownOptions) -> String {
     let mut result = String::new();
     for block in blocks {
-        format_block_recursive(&mut result, block, 0);
+        format_block_recursive(&mut result, block, 0, options);
     }
     result
 }
 
-fn format_block_recursive(result: &mut String, block: &Block, indent_level: usize) {
+fn format_block_recursive(
+    result: &mut String,
+    block: &Block,
+    indent_level: usize,
+    options: &MarkdownOptions,
+) {
     let indent = "  ".repeat(indent_level);
-    result.push_str(&format!("{}* {}\n", indent, block.content));
+    let content = if options.include_properties {
+        block.content.clone()
+    } else {
+        strip_property_lines(&block.content)
+    };
+    // A block holding nothing but properties (like a page's property block)
+    // has nothing left to show
+    if content.is_empty()
+        && !block.content.is_empty()
+        && block.children.is_empty()
+        && !options.include_uuids
+    {
+        return;
+    }
+
+    let mut lines: Vec<&str> = content.split('\n').collect();
+    let id_line = format!("id:: {}", block.uuid);
+    if options.include_uuids && !lines.iter().any(|l| l.trim_start().starts_with("id:: ")) {
+        // Properties follow the first line, as LogSeq writes them
+        lines.insert(1.min(lines.len()), &id_line);
+    }
+
+    result.push_str(&format!("{}* {}\n", indent, lines[0]));
+    // Continuation lines line up with the text after the bullet
+    for line in &lines[1..] {
+        if !line.is_empty() {
+            result.push_str(&format!("{}  {}", indent, line));
+        }
+        result.push('\n');
+    }
+
+    if options
+        .max_depth
+        .is_some_and(|max_depth| indent_level + 1 >= max_depth)
+    {
+        let hidden = count_descendants(block);
+        if hidden > 0 {
+            result.push_str(&format!(
+                "{}  * … {} nested block(s) not shown\n",
+                indent, hidden
+            ));
+        }
+        return;
+    }
 
     for child in &block.children {
-        format_block_recursive(result, child, indent_level + 1);
+        format_block_recursive(result, child, indent_level + 1, options);
     }
 }
 
+/// Total number of blocks in a forest, counting every nested child
+pub fn count_blocks(blocks: &[Block]) -> usize {
+    blocks
+        .iter()
+        .map(|block| 1 + count_descendants(block))
+        .sum()
+}
+
+/// Drop everything nested more than `max_depth` levels deep in a forest
+/// (1 keeps only the blocks themselves), returning how many blocks went
+pub fn prune_depth(blocks: &mut [Block], max_depth: usize) -> usize {
+    blocks
+        .iter_mut()
+        .map(|block| match max_depth {
+            0 | 1 => count_blocks(&std::mem::take(&mut block.children)),
+            _ => prune_depth(&mut block.children, max_depth - 1),
+        })
+        .sum()
+}
+
+/// A childless block made up only of `key:: value` lines, like the block
+/// holding a page's properties
+pub fn is_properties_block(block: &Block) -> bool {
+    block.children.is_empty()
+        && !block.content.trim().is_empty()
+        && block.content.lines().all(is_property_line)
+}
+
+fn count_descendants(block: &Block) -> usize {
+    block
+        .children
+        .iter()
+        .map(|child| 1 + count_descendants(child))
+        .sum()
+}
+
 pub fn format_search_results(results: &[SearchResult]) -> String {
     if results.is_empty() {
         return "No results found.".to_string();
@@ -26,20 +213,63 @@ pub fn format_search_results(results: &[SearchResult]) -> String {
     content.push_str(&format!("Found {} results:\n\n", results.len()));
 
     for (i, result) in results.iter().enumerate() {
-        content.push_str(&format!("{}. {}\n", i + 1, result.block.content));
-        if let Some(page) = &result.block.page {
-            content.push_str(&format!("   Page ID: {}\n", page.id));
-        }
-        if let Some(score) = result.score {
-            content.push_str(&format!("   Score: {:.2}\n", score));
-        }
-        content.push('\n');
+        content.push_str(&format_search_result(i + 1, result));
     }
 
     content
 }
 
-pub fn format_todos(todos: &[TodoItem]) -> String {
+/// One numbered entry of a list of search results
+pub fn format_search_result(number: usize, result: &SearchResult) -> String {
+    let mut content = String::new();
+    let text = result.snippet.as_deref().unwrap_or(&result.block.content);
+    content.push_str(&format!("{}. {}\n", number, text));
+    content.push_str(&format!("   Page: {}\n", result.page_name));
+    if !result.breadcrumb.is_empty() {
+        content.push_str(&format!("   Under: {}\n", result.breadcrumb.join(" › ")));
+    }
+    if let Some(updated_at) = result.updated_at {
+        content.push_str(&format!(
+            "   Updated: {}\n",
+            updated_at.format("%Y-%m-%d %H:%M UTC")
+        ));
+    }
+    content.push_str(&format!("   UUID: {}\n", result.block.uuid));
+    if let Some(score) = result.score {
+        content.push_str(&format!("   Score: {:.2}\n", score));
+    }
+    for line in format_blocks_as_markdown(&result.block.children).lines() {
+        content.push_str(&format!("   {}\n", line));
+    }
+    content.push('\n');
+    content
+}
+
+/// How `format_todos` should organise todos into sections
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize, JsonSchema)]
+#[serde(rename_all = "kebab-case")]
+pub enum TodoGroupBy {
+    #[default]
+    Marker,
+    Page,
+    Tag,
+    Priority,
+    DueWeek,
+}
+
+impl TodoGroupBy {
+    fn summary_label(self) -> &'static str {
+        match self {
+            TodoGroupBy::Marker => "Status",
+            TodoGroupBy::Page => "Page",
+            TodoGroupBy::Tag => "Tag",
+            TodoGroupBy::Priority => "Priority",
+            TodoGroupBy::DueWeek => "Due Week",
+        }
+    }
+}
+
+pub fn format_todos(todos: &[TodoItem], group_by: TodoGroupBy) -> String {
     if todos.is_empty() {
         return "No incomplete todos found.".to_string();
     }
@@ -47,42 +277,678 @@ pub fn format_todos(todos: &[TodoItem]) -> String {
     let mut content = String::new();
     content.push_str(&format!("Found {} incomplete todos:\n\n", todos.len()));
 
-    // Group todos by marker type for better organization
-    let mut by_marker: std::collections::HashMap<&str, Vec<&TodoItem>> =
-        std::collections::HashMap::new();
-    for todo in todos {
-        by_marker.entry(&todo.marker).or_default().push(todo);
-    }
+    let groups = group_todos(todos, group_by);
 
-    // Sort by marker priority: NOW > DOING > TODO > LATER > WAITING
-    let marker_order = ["NOW", "DOING", "TODO", "LATER", "WAITING"];
+    for (heading, group_todos) in &groups {
+        content.push_str(&format!("## {} ({} items)\n", heading, group_todos.len()));
 
-    for marker in marker_order {
-        if let Some(marker_todos) = by_marker.get(marker) {
-            content.push_str(&format!("## {} ({} items)\n", marker, marker_todos.len()));
-
-            for (i, todo) in marker_todos.iter().enumerate() {
+        for (i, todo) in group_todos.iter().enumerate() {
+            content.push_str(&format!(
+                "{}. **{}** {}\n",
+                i + 1,
+                todo.marker,
+                todo.content
+            ));
+            content.push_str(&format!("   📄 Page: {}\n", todo.page_name));
+            if let Some(priority) = &todo.priority {
+                content.push_str(&format!("   ⚡ Priority: {}\n", priority));
+            }
+            if let Some(scheduled) = todo.scheduled {
                 content.push_str(&format!(
-                    "{}. **{}** {}\n",
-                    i + 1,
-                    todo.marker,
-                    todo.content
+                    "   📅 Scheduled: {}\n",
+                    format_journal_day(scheduled)
                 ));
-                content.push_str(&format!("   📄 Page: {}\n", todo.page_name));
-                content.push_str(&format!("   🆔 UUID: {}\n", todo.uuid));
-                content.push('\n');
             }
+            content.push_str(&format!("   🆔 UUID: {}\n", todo.uuid));
+            content.push('\n');
         }
     }
 
     // Add summary
     content.push_str("---\n");
-    content.push_str("**Summary by Status:**\n");
-    for marker in marker_order {
-        if let Some(marker_todos) = by_marker.get(marker) {
-            content.push_str(&format!("- {}: {} todos\n", marker, marker_todos.len()));
+    content.push_str(&format!("**Summary by {}:**\n", group_by.summary_label()));
+    for (heading, group_todos) in &groups {
+        content.push_str(&format!("- {}: {} todos\n", heading, group_todos.len()));
+    }
+
+    content
+}
+
+/// Bucket todos into ordered sections. Todos keep their incoming order
+/// within a section; a todo with several tags appears under each of them.
+pub fn group_todos(todos: &[TodoItem], group_by: TodoGroupBy) -> Vec<(String, Vec<&TodoItem>)> {
+    let mut groups: std::collections::BTreeMap<(u32, String), Vec<&TodoItem>> =
+        std::collections::BTreeMap::new();
+
+    for todo in todos {
+        // Each key is (sort rank, heading) so sections render in a sensible order
+        let keys: Vec<(u32, String)> = match group_by {
+            TodoGroupBy::Marker => {
+                let rank = INCOMPLETE_MARKERS
+                    .iter()
+                    .position(|m| *m == todo.marker)
+                    .unwrap_or(INCOMPLETE_MARKERS.len()) as u32;
+                vec![(rank, todo.marker.clone())]
+            }
+            TodoGroupBy::Page => vec![(0, todo.page_name.clone())],
+            TodoGroupBy::Tag if todo.tags.is_empty() => vec![(1, "Untagged".to_string())],
+            TodoGroupBy::Tag => todo.tags.iter().map(|t| (0, format!("#{}", t))).collect(),
+            TodoGroupBy::Priority => match &todo.priority {
+                Some(p) => vec![(0, format!("Priority {}", p))],
+                None => vec![(1, "No priority".to_string())],
+            },
+            TodoGroupBy::DueWeek => match todo.scheduled.and_then(journal_day_to_date) {
+                Some(date) => {
+                    let week = date.iso_week();
+                    vec![(0, format!("{}-W{:02}", week.year(), week.week()))]
+                }
+                None => vec![(1, "No due date".to_string())],
+            },
+        };
+
+        for key in keys {
+            groups.entry(key).or_default().push(todo);
         }
     }
 
+    groups
+        .into_iter()
+        .map(|((_, heading), todos)| (heading, todos))
+        .collect()
+}
+
+/// The date of a LogSeq journal day integer, if it is a real date
+pub fn journal_day_to_date(day: u32) -> Option<chrono::NaiveDate> {
+    chrono::NaiveDate::from_ymd_opt((day / 10000) as i32, (day / 100) % 100, day % 100)
+}
+
+/// Local midnight at the start of `date`, in milliseconds since the Unix
+/// epoch, as LogSeq stores block timestamps
+pub fn start_of_day(date: chrono::NaiveDate) -> i64 {
+    use chrono::TimeZone;
+
+    let midnight = date.and_hms_opt(0, 0, 0).unwrap_or_default();
+    chrono::Local
+        .from_local_datetime(&midnight)
+        .earliest()
+        // Midnight skipped by a clock change; UTC is close enough
+        .map_or_else(
+            || midnight.and_utc().timestamp_millis(),
+            |t| t.timestamp_millis(),
+        )
+}
+
+/// Format a LogSeq journal day integer (e.g. `20240131`) as `2024-01-31`
+pub fn format_journal_day(day: u32) -> String {
+    format!(
+        "{:04}-{:02}-{:02}",
+        day / 10000,
+        (day / 100) % 100,
+        day % 100
+    )
+}
+
+/// A note listing entries skipped because LogSeq returned them in a shape
+/// that couldn't be decoded, or an empty string when there are none
+pub fn format_skipped_entries(warnings: &[String]) -> String {
+    if warnings.is_empty() {
+        return String::new();
+    }
+
+    let mut content = format!(
+        "\n⚠️ Skipped {} malformed entries returned by LogSeq:\n",
+        warnings.len()
+    );
+    for warning in warnings {
+        content.push_str(&format!("- {}\n", warning));
+    }
     content
 }
+
+/// A window onto a larger result set, addressed by an opaque cursor
+pub struct Paginated<'a, T> {
+    pub items: &'a [T],
+    pub offset: usize,
+    pub total: usize,
+    pub next_cursor: Option<String>,
+}
+
+/// Slice `items` starting at `cursor` (an offset previously returned as
+/// `next_cursor`), returning at most `limit` entries.
+pub fn paginate<'a, T>(
+    items: &'a [T],
+    limit: usize,
+    cursor: Option<&str>,
+) -> Result<Paginated<'a, T>, String> {
+    let offset = match cursor {
+        Some(cursor) => cursor
+            .parse::<usize>()
+            .map_err(|_| format!("Invalid cursor: {}", cursor))?,
+        None => 0,
+    };
+    let total = items.len();
+    let start = offset.min(total);
+    let end = start.saturating_add(limit).min(total);
+    let next_cursor = (end < total).then(|| end.to_string());
+
+    Ok(Paginated {
+        items: &items[start..end],
+        offset: start,
+        total,
+        next_cursor,
+    })
+}
+
+pub fn format_pagination_footer<T>(page: &Paginated<'_, T>) -> String {
+    if page.items.is_empty() {
+        return format!("Showing 0 of {} results.\n", page.total);
+    }
+
+    let mut footer = format!(
+        "Showing {}-{} of {} results.",
+        page.offset + 1,
+        page.offset + page.items.len(),
+        page.total
+    );
+    if let Some(cursor) = &page.next_cursor {
+        footer.push_str(&format!(
+            " More results available; call again with cursor \"{}\".",
+            cursor
+        ));
+    }
+    footer.push('\n');
+    footer
+}
+
+/// How `datascript_query` writes its result
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize, JsonSchema)]
+#[serde(rename_all = "kebab-case")]
+pub enum TableFormat {
+    #[default]
+    Json,
+    Csv,
+    Tsv,
+    MarkdownTable,
+}
+
+/// A query result as a table with a header row. Each result row is a table
+/// row, and a result of pulled entities has a column per attribute. Headers
+/// come from `columns` when there's one per value, and are numbered
+/// otherwise. `TableFormat::Json` gives the result as pretty JSON.
+pub fn format_table(result: &Value, columns: &[String], format: TableFormat) -> String {
+    let rows: Vec<Vec<&Value>> = match result {
+        Value::Array(rows) => rows
+            .iter()
+            .map(|row| match row {
+                Value::Array(cells) => cells.iter().collect(),
+                cell => vec![cell],
+            })
+            .collect(),
+        Value::Null => Vec::new(),
+        scalar => vec![vec![scalar]],
+    };
+
+    let pulled = !rows.is_empty() && rows.iter().all(|row| matches!(row[..], [Value::Object(_)]));
+    let (headers, cells): (Vec<String>, Vec<Vec<String>>) = if pulled {
+        let mut keys: Vec<&String> = Vec::new();
+        for row in &rows {
+            for key in row[0].as_object().into_iter().flat_map(|map| map.keys()) {
+                if !keys.contains(&key) {
+                    keys.push(key);
+                }
+            }
+        }
+        let cells = rows
+            .iter()
+            .map(|row| {
+                keys.iter()
+                    .map(|key| cell_text(&row[0][key.as_str()]))
+                    .collect()
+            })
+            .collect();
+        (keys.into_iter().cloned().collect(), cells)
+    } else {
+        let width = rows.iter().map(Vec::len).max().unwrap_or(columns.len());
+        let headers = match columns.len() == width {
+            true => columns.to_vec(),
+            false => (1..=width).map(|n| format!("column {}", n)).collect(),
+        };
+        let cells = rows
+            .iter()
+            .map(|row| {
+                let mut cells: Vec<String> = row.iter().map(|cell| cell_text(cell)).collect();
+                cells.resize(width, String::new());
+                cells
+            })
+            .collect();
+        (headers, cells)
+    };
+
+    let lines = std::iter::once(&headers).chain(&cells);
+    match format {
+        TableFormat::Json => serde_json::to_string_pretty(result).unwrap_or_default(),
+        TableFormat::Csv => lines
+            .map(|row| {
+                row.iter()
+                    .map(|cell| csv_field(cell))
+                    .collect::<Vec<_>>()
+                    .join(",")
+                    + "\r\n"
+            })
+            .collect(),
+        TableFormat::Tsv => lines
+            .map(|row| {
+                row.iter()
+                    .map(|cell| cell.replace(['\t', '\n', '\r'], " "))
+                    .collect::<Vec<_>>()
+                    .join("\t")
+                    + "\n"
+            })
+            .collect(),
+        TableFormat::MarkdownTable => {
+            let row_text = |row: &Vec<String>| {
+                let cells: Vec<String> = row
+                    .iter()
+                    .map(|cell| {
+                        cell.replace('|', "\\|")
+                            .replace("\r\n", "<br>")
+                            .replace('\n', "<br>")
+                    })
+                    .collect();
+                format!("| {} |\n", cells.join(" | "))
+            };
+            let mut table = row_text(&headers);
+            table.push_str(&format!("|{}\n", " --- |".repeat(headers.len().max(1))));
+            for row in &cells {
+                table.push_str(&row_text(row));
+            }
+            table
+        }
+    }
+}
+
+/// A value as a table cell: strings as they are, and nested values as JSON
+fn cell_text(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(text) => text.clone(),
+        other => other.to_string(),
+    }
+}
+
+/// A CSV field, quoted when it holds a comma, quote, or line break
+fn csv_field(cell: &str) -> String {
+    match cell.contains([',', '"', '\n', '\r']) {
+        true => format!("\"{}\"", cell.replace('"', "\"\"")),
+        false => cell.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_table_writes_rows_with_headers() {
+        let columns = vec!["name".to_string(), "count b".to_string()];
+        let rows = serde_json::json!([["Projects", 3], ["Notes, \"draft\"", null]]);
+        assert_eq!(
+            format_table(&rows, &columns, TableFormat::Csv),
+            "name,count b\r\nProjects,3\r\n\"Notes, \"\"draft\"\"\",\r\n"
+        );
+        assert_eq!(
+            format_table(&rows, &columns, TableFormat::Tsv),
+            "name\tcount b\nProjects\t3\nNotes, \"draft\"\t\n"
+        );
+
+        // Pulled entities get a column per attribute
+        let pulled = serde_json::json!([
+            [{"content": "a | b\nc", "marker": "TODO"}],
+            [{"content": "Done", "page": {"id": 7}}]
+        ]);
+        assert_eq!(
+            format_table(&pulled, &["b".to_string()], TableFormat::MarkdownTable),
+            "| content | marker | page |\n| --- | --- | --- |\n| a \\| b<br>c | TODO |  |\n| Done |  | {\"id\":7} |\n"
+        );
+
+        // A scalar result, or columns that don't match, are still a table
+        assert_eq!(
+            format_table(&serde_json::json!(42), &[], TableFormat::Csv),
+            "column 1\r\n42\r\n"
+        );
+    }
+
+    #[test]
+    fn test_prune_depth_drops_deeper_blocks() {
+        let mut child = Block::new("b".into(), "child".into());
+        child.children = vec![Block::new("c".into(), "grandchild".into())];
+        let mut parent = Block::new("a".into(), "parent".into());
+        parent.children = vec![child, Block::new("d".into(), "sibling".into())];
+        let mut blocks = vec![parent];
+
+        assert_eq!(prune_depth(&mut blocks.clone(), 3), 0);
+        assert_eq!(prune_depth(&mut blocks, 2), 1);
+        assert_eq!(count_blocks(&blocks), 3);
+        assert_eq!(prune_depth(&mut blocks, 1), 2);
+        assert!(blocks[0].children.is_empty());
+    }
+
+    #[test]
+    fn test_count_blocks_includes_nested_children() {
+        let mut parent = Block::new("a".into(), "parent".into());
+        let mut child = Block::new("b".into(), "child".into());
+        child.children = vec![Block::new("c".into(), "grandchild".into())];
+        parent.children = vec![child, Block::new("d".into(), "sibling".into())];
+
+        assert_eq!(count_blocks(&[]), 0);
+        assert_eq!(
+            count_blocks(&[parent, Block::new("e".into(), "next".into())]),
+            5
+        );
+    }
+
+    #[test]
+    fn test_paginate_walks_all_items() {
+        let items: Vec<u32> = (0..5).collect();
+
+        let first = paginate(&items, 2, None).unwrap();
+        assert_eq!(first.items, &[0, 1]);
+        assert_eq!(first.next_cursor.as_deref(), Some("2"));
+
+        let second = paginate(&items, 2, first.next_cursor.as_deref()).unwrap();
+        assert_eq!(second.items, &[2, 3]);
+
+        let last = paginate(&items, 2, second.next_cursor.as_deref()).unwrap();
+        assert_eq!(last.items, &[4]);
+        assert!(last.next_cursor.is_none());
+    }
+
+    #[test]
+    fn test_paginate_rejects_bad_cursor() {
+        let items = vec![1, 2, 3];
+        assert!(paginate(&items, 2, Some("not-a-number")).is_err());
+        assert!(paginate(&items, 2, Some("99")).unwrap().items.is_empty());
+    }
+
+    fn todo(uuid: &str, marker: &str, page: &str, tags: &[&str]) -> TodoItem {
+        TodoItem {
+            uuid: uuid.to_string(),
+            content: format!("{} item {}", marker, uuid),
+            marker: marker.to_string(),
+            page_name: page.to_string(),
+            priority: None,
+            scheduled: None,
+            tags: tags.iter().map(|t| t.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn test_format_todos_groups_by_marker_in_priority_order() {
+        let todos = [todo("1", "LATER", "a", &[]), todo("2", "NOW", "b", &[])];
+        let output = format_todos(&todos, TodoGroupBy::Marker);
+        let now = output.find("## NOW").unwrap();
+        let later = output.find("## LATER").unwrap();
+        assert!(now < later);
+        assert!(output.contains("**Summary by Status:**"));
+    }
+
+    #[test]
+    fn test_format_todos_groups_by_tag() {
+        let todos = [
+            todo("1", "TODO", "a", &["work", "urgent"]),
+            todo("2", "TODO", "b", &[]),
+        ];
+        let output = format_todos(&todos, TodoGroupBy::Tag);
+        assert!(output.contains("## #urgent (1 items)"));
+        assert!(output.contains("## #work (1 items)"));
+        assert!(output.contains("## Untagged (1 items)"));
+    }
+
+    #[test]
+    fn test_format_todos_groups_by_due_week() {
+        let mut scheduled = todo("1", "TODO", "a", &[]);
+        scheduled.scheduled = Some(20240131);
+        let todos = [scheduled, todo("2", "TODO", "b", &[])];
+        let output = format_todos(&todos, TodoGroupBy::DueWeek);
+        assert!(output.contains("## 2024-W05 (1 items)"));
+        assert!(output.find("2024-W05").unwrap() < output.find("No due date").unwrap());
+    }
+
+    #[test]
+    fn test_todo_group_by_parsing() {
+        assert_eq!(
+            serde_json::from_value::<TodoGroupBy>("due-week".into()).unwrap(),
+            TodoGroupBy::DueWeek
+        );
+        assert!(serde_json::from_value::<TodoGroupBy>("colour".into()).is_err());
+    }
+
+    #[test]
+    fn test_format_journal_day() {
+        assert_eq!(format_journal_day(20240131), "2024-01-31");
+    }
+
+    // Golden-file tests: agents parse this output, so any change to it should
+    // show up as a reviewed snapshot diff. Update with `cargo insta review`.
+
+    fn tree(uuid: &str, content: &str, children: Vec<Block>) -> Block {
+        let mut block = Block::new(uuid.to_string(), content.to_string());
+        block.children = children;
+        block
+    }
+
+    #[test]
+    fn snapshot_blocks_empty() {
+        insta::assert_snapshot!(format_blocks_as_markdown(&[]), @"");
+    }
+
+    #[test]
+    fn snapshot_blocks_deep_nesting() {
+        let mut leaf = tree("6", "level 5", vec![]);
+        for level in (0..5).rev() {
+            leaf = tree(&level.to_string(), &format!("level {}", level), vec![leaf]);
+        }
+        let blocks = [leaf, tree("7", "sibling at root", vec![])];
+        insta::assert_snapshot!(format_blocks_as_markdown(&blocks));
+    }
+
+    #[test]
+    fn snapshot_blocks_properties_and_multiline() {
+        let blocks = [
+            tree("1", "title:: Project Alpha\ntags:: rust, mcp", vec![]),
+            tree(
+                "2",
+                "Notes with a property\nstatus:: active",
+                vec![tree(
+                    "3",
+                    "Example:\n```rust\nfn main() {\n    println!(\"hi\");\n}\n```",
+                    vec![],
+                )],
+            ),
+            tree("4", "First paragraph\n\nSecond paragraph", vec![]),
+        ];
+        insta::assert_snapshot!(format_blocks_as_markdown(&blocks));
+    }
+
+    fn outline_with_properties() -> Vec<Block> {
+        vec![
+            tree("page-props", "title:: Project Alpha\ntags:: rust", vec![]),
+            tree(
+                "root",
+                "Root task\nstatus:: active\ncollapsed:: true",
+                vec![tree(
+                    "child",
+                    "Child\nid:: child",
+                    vec![tree(
+                        "grandchild",
+                        "Grandchild",
+                        vec![tree("leaf", "Leaf", vec![])],
+                    )],
+                )],
+            ),
+        ]
+    }
+
+    #[test]
+    fn snapshot_blocks_with_uuids() {
+        let options = MarkdownOptions {
+            include_uuids: true,
+            ..Default::default()
+        };
+        insta::assert_snapshot!(format_blocks_as_markdown_with(
+            &outline_with_properties(),
+            &options
+        ));
+    }
+
+    #[test]
+    fn snapshot_blocks_without_properties() {
+        let options = MarkdownOptions {
+            include_properties: false,
+            ..Default::default()
+        };
+        insta::assert_snapshot!(format_blocks_as_markdown_with(
+            &outline_with_properties(),
+            &options
+        ));
+    }
+
+    #[test]
+    fn snapshot_blocks_max_depth() {
+        let options = MarkdownOptions {
+            max_depth: Some(2),
+            ..Default::default()
+        };
+        insta::assert_snapshot!(format_blocks_as_markdown_with(
+            &outline_with_properties(),
+            &options
+        ));
+    }
+
+    #[test]
+    fn snapshot_blocks_unicode() {
+        let blocks = [tree(
+            "1",
+            "日本語のメモ #[[東京 旅行]]",
+            vec![
+                tree("2", "Ünïcödé — naïve café ☕", vec![]),
+                tree("3", "emoji 🎉🚀 and RTL שלום", vec![]),
+            ],
+        )];
+        insta::assert_snapshot!(format_blocks_as_markdown(&blocks));
+    }
+
+    fn search_result(uuid: &str, content: &str) -> SearchResult {
+        SearchResult {
+            block: Block::new(uuid.to_string(), content.to_string()),
+            score: None,
+            page_name: "Journal".to_string(),
+            breadcrumb: vec![],
+            updated_at: None,
+            snippet: None,
+        }
+    }
+
+    #[test]
+    fn snapshot_search_results_empty() {
+        insta::assert_snapshot!(format_search_results(&[]), @"No results found.");
+    }
+
+    #[test]
+    fn snapshot_search_results() {
+        let mut with_snippet = search_result("1", "A long block that mentions rust somewhere");
+        with_snippet.snippet = Some("…mentions **rust** somewhere".to_string());
+        with_snippet.page_name = "Programming/Rust".to_string();
+        with_snippet.breadcrumb = vec!["Learning".to_string(), "Languages".to_string()];
+        with_snippet.updated_at = chrono::DateTime::from_timestamp(1_714_557_600, 0);
+        with_snippet.score = Some(0.875);
+
+        let mut journal = search_result("2", "Rust notes in a journal");
+        journal.block.children = vec![tree(
+            "4",
+            "TODO Read the book",
+            vec![tree("5", "Chapter 4 on ownership", vec![])],
+        )];
+
+        let unicode = search_result("3", "Rust auf Deutsch: Übung macht den Meister 🦀");
+
+        insta::assert_snapshot!(format_search_results(&[with_snippet, journal, unicode]));
+    }
+
+    fn todo_fixture() -> Vec<TodoItem> {
+        let mut urgent = todo("a1", "NOW", "Work", &["work", "urgent"]);
+        urgent.priority = Some("A".to_string());
+        urgent.scheduled = Some(20240131);
+
+        let mut waiting = todo("b2", "WAITING", "Work", &["work"]);
+        waiting.scheduled = Some(20240205);
+
+        let mut later = todo("c3", "LATER", "Ideen für 2024", &["träume"]);
+        later.priority = Some("C".to_string());
+
+        vec![
+            urgent,
+            todo("d4", "TODO", "Home", &[]),
+            waiting,
+            later,
+            todo("e5", "DOING", "Home", &[]),
+        ]
+    }
+
+    #[test]
+    fn snapshot_todos_empty() {
+        insta::assert_snapshot!(
+            format_todos(&[], TodoGroupBy::Marker),
+            @"No incomplete todos found."
+        );
+    }
+
+    #[test]
+    fn snapshot_todos_by_marker() {
+        insta::assert_snapshot!(format_todos(&todo_fixture(), TodoGroupBy::Marker));
+    }
+
+    #[test]
+    fn snapshot_todos_by_page() {
+        insta::assert_snapshot!(format_todos(&todo_fixture(), TodoGroupBy::Page));
+    }
+
+    #[test]
+    fn snapshot_todos_by_tag() {
+        insta::assert_snapshot!(format_todos(&todo_fixture(), TodoGroupBy::Tag));
+    }
+
+    #[test]
+    fn snapshot_todos_by_priority() {
+        insta::assert_snapshot!(format_todos(&todo_fixture(), TodoGroupBy::Priority));
+    }
+
+    #[test]
+    fn snapshot_todos_by_due_week() {
+        insta::assert_snapshot!(format_todos(&todo_fixture(), TodoGroupBy::DueWeek));
+    }
+
+    #[test]
+    fn snapshot_skipped_entries() {
+        assert_eq!(format_skipped_entries(&[]), "");
+        let warnings = [
+            "Skipped page 2 (\"No UUID\"): missing field `uuid`".to_string(),
+            "Skipped block 1.2 (\"a2\") and its 1 child block(s): invalid type: integer `42`, expected a string".to_string(),
+        ];
+        insta::assert_snapshot!(format_skipped_entries(&warnings));
+    }
+
+    #[test]
+    fn snapshot_pagination_footer() {
+        let items: Vec<u32> = (0..5).collect();
+        let footers = [
+            paginate(&items, 2, None).unwrap(),
+            paginate(&items, 2, Some("4")).unwrap(),
+            paginate(&items, 2, Some("9")).unwrap(),
+        ]
+        .iter()
+        .map(format_pagination_footer)
+        .collect::<String>();
+        insta::assert_snapshot!(footers);
+    }
+}
@@ -0,0 +1,143 @@
+//! The `add_tag_to_block` tool
+
+use super::context::{ToolContext, logseq_error, text_result};
+use super::registry::McpTool;
+use crate::logseq::content::{extract_tags, format_tag, is_property_line};
+use rmcp::{ErrorData as McpError, model::CallToolResult};
+use schemars::JsonSchema;
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct AddTagToBlockArgs {
+    /// The UUID of the block to tag.
+    pub uuid: String,
+    /// The tag's name, without '#'. Names with spaces are written as #[[multi word tag]].
+    pub tag: String,
+}
+
+pub struct AddTagToBlock;
+
+impl McpTool for AddTagToBlock {
+    type Args = AddTagToBlockArgs;
+
+    fn name(&self) -> &'static str {
+        "add_tag_to_block"
+    }
+
+    fn description(&self) -> &'static str {
+        "Tag a block by adding #tag to the end of its first line, leaving the rest of its content and its properties alone. Does nothing if the block already has the tag."
+    }
+
+    fn destructive(&self) -> bool {
+        false
+    }
+
+    fn idempotent(&self) -> bool {
+        true
+    }
+
+    async fn execute(
+        &self,
+        ctx: &ToolContext,
+        args: AddTagToBlockArgs,
+    ) -> Result<CallToolResult, McpError> {
+        let AddTagToBlockArgs { uuid, tag } = args;
+        let name = tag_name("tag", &tag)?;
+        ctx.require_writable(&uuid).await?;
+
+        let block = ctx.client.get_block(&uuid).await.map_err(logseq_error)?;
+        if extract_tags(&block.content).contains(&name.to_lowercase()) {
+            return Ok(text_result(format!(
+                "Block {} is already tagged {}",
+                block.uuid,
+                format_tag(name)
+            )));
+        }
+
+        let content = with_tag(&block.content, name);
+        ctx.charge_write(None, &content)?;
+        ctx.client
+            .update_block(&block.uuid, &content, None)
+            .await
+            .map_err(logseq_error)?;
+
+        Ok(text_result(format!(
+            "Tagged block {} with {}",
+            block.uuid,
+            format_tag(name)
+        )))
+    }
+}
+
+/// A tag name from an argument, without any `#` or `[[...]]` around it
+pub(crate) fn tag_name<'a>(arg: &str, tag: &'a str) -> Result<&'a str, McpError> {
+    let name = tag.trim().trim_start_matches('#');
+    let name = name
+        .strip_prefix("[[")
+        .and_then(|name| name.strip_suffix("]]"))
+        .unwrap_or(name)
+        .trim();
+    if name.is_empty() {
+        return Err(McpError::invalid_params(
+            format!("'{}' is not a tag name", arg),
+            Some(serde_json::json!({ arg: tag })),
+        ));
+    }
+    Ok(name)
+}
+
+/// `content` with the tag added to the end of its first line, ahead of any
+/// properties. A block of nothing but properties gets a first line of its
+/// own.
+pub(crate) fn with_tag(content: &str, name: &str) -> String {
+    if content.lines().next().is_some_and(is_property_line) {
+        return format!("{}\n{}", format_tag(name), content);
+    }
+    let (first, rest) = match content.split_once('\n') {
+        Some((first, rest)) => (first, Some(rest)),
+        None => (content, None),
+    };
+    let mut tagged = first.trim_end().to_string();
+    if !tagged.is_empty() {
+        tagged.push(' ');
+    }
+    tagged.push_str(&format_tag(name));
+    if let Some(rest) = rest {
+        tagged.push('\n');
+        tagged.push_str(rest);
+    }
+    tagged
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::logseq::mock::MockGraph;
+    use crate::tools::testing::{call_text, serve};
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_tag_is_added_once_ahead_of_properties() {
+        let mut graph = MockGraph::new();
+        graph.add_page("Notes");
+        let idea = graph.add_block("Notes", "Try a cache\nstatus:: open");
+        let (logseq, ctx) = serve(graph).await;
+
+        let args = json!({"uuid": idea, "tag": "#[[Big Ideas]]"});
+        let text = call_text(&ctx, "add_tag_to_block", args).await.unwrap();
+        assert_eq!(text, format!("Tagged block {} with #[[Big Ideas]]", idea));
+
+        let args = json!({"uuid": idea, "tag": "big ideas"});
+        let text = call_text(&ctx, "add_tag_to_block", args).await.unwrap();
+        assert!(text.contains("already tagged"), "{}", text);
+        logseq.with_graph(|g| {
+            assert_eq!(
+                g.block_content(&idea),
+                Some("Try a cache #[[Big Ideas]]\nstatus:: open")
+            );
+        });
+
+        let args = json!({"uuid": idea, "tag": "#"});
+        assert!(call_text(&ctx, "add_tag_to_block", args).await.is_err());
+    }
+}
@@ -0,0 +1,161 @@
+//! The `analyze_tag_cooccurrence` tool
+
+use super::context::{ToolContext, json_result, logseq_error, text_result};
+use super::registry::McpTool;
+use crate::logseq::taxonomy::{CoOccurrenceReport, co_occurrence};
+use crate::session::OutputFormat;
+use rmcp::{ErrorData as McpError, model::CallToolResult};
+use schemars::JsonSchema;
+use serde::Deserialize;
+
+/// Pages and blocks two tags must share to count as co-occurring by default
+const DEFAULT_MIN_COUNT: usize = 2;
+
+/// Pairs listed by default
+const DEFAULT_PAIR_LIMIT: usize = 20;
+
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct AnalyzeTagCooccurrenceArgs {
+    /// How many pages or blocks two tags must share before they count as co-occurring. Defaults to 2.
+    #[schemars(range(min = 1))]
+    pub min_count: Option<usize>,
+    /// Maximum number of tag pairs to list. Clusters are always listed in full. Defaults to 20.
+    #[schemars(range(min = 1))]
+    pub limit: Option<usize>,
+}
+
+pub struct AnalyzeTagCooccurrence;
+
+impl McpTool for AnalyzeTagCooccurrence {
+    type Args = AnalyzeTagCooccurrenceArgs;
+
+    fn name(&self) -> &'static str {
+        "analyze_tag_cooccurrence"
+    }
+
+    fn description(&self) -> &'static str {
+        "Report which tags (set with 'tags::') are used together on the same pages and blocks, and group them into clusters of related tags. Useful for discovering emergent topics and tidying the tag taxonomy: pairs that are almost always used together are flagged as candidates for merging. Works from the cached tag data, so recent edits may take a moment to show up."
+    }
+
+    fn read_only(&self) -> bool {
+        true
+    }
+
+    async fn execute(
+        &self,
+        ctx: &ToolContext,
+        args: AnalyzeTagCooccurrenceArgs,
+    ) -> Result<CallToolResult, McpError> {
+        let min_count = args.min_count.unwrap_or(DEFAULT_MIN_COUNT).max(1);
+        let limit = args.limit.unwrap_or(DEFAULT_PAIR_LIMIT).max(1);
+
+        let usage = ctx.cache.tag_usage().await.map_err(logseq_error)?;
+        let mut report = co_occurrence(&usage, min_count);
+        if ctx.session.output_format == OutputFormat::Json {
+            report.pairs.truncate(limit);
+            return Ok(json_result(&report, "tag report"));
+        }
+        Ok(text_result(format_report(&report, min_count, limit)))
+    }
+}
+
+fn format_report(report: &CoOccurrenceReport, min_count: usize, limit: usize) -> String {
+    if report.tagged == 0 {
+        return "No pages or blocks have tags.".to_string();
+    }
+
+    let mut content = format!(
+        "Analyzed {} tags on {} pages and blocks.\n\n",
+        report.tags.len(),
+        report.tagged
+    );
+    if report.pairs.is_empty() {
+        content.push_str(&format!(
+            "No tags are used together on {} or more pages or blocks.\n",
+            min_count
+        ));
+        return content;
+    }
+
+    content.push_str(&format!("## Clusters ({})\n", report.clusters.len()));
+    for (i, cluster) in report.clusters.iter().enumerate() {
+        content.push_str(&format!(
+            "{}. {} ({} pages/blocks)\n",
+            i + 1,
+            cluster
+                .tags
+                .iter()
+                .map(|t| format!("#{}", t))
+                .collect::<Vec<_>>()
+                .join(", "),
+            cluster.count
+        ));
+    }
+
+    content.push_str(&format!(
+        "\n## Top pairs ({} of {})\n",
+        report.pairs.len().min(limit),
+        report.pairs.len()
+    ));
+    for pair in report.pairs.iter().take(limit) {
+        content.push_str(&format!(
+            "- #{} + #{}: {} together ({:.0}% overlap)\n",
+            pair.tags[0],
+            pair.tags[1],
+            pair.count,
+            pair.similarity * 100.0
+        ));
+    }
+
+    let merge: Vec<String> = report
+        .merge_candidates()
+        .map(|pair| format!("- #{} and #{}", pair.tags[0], pair.tags[1]))
+        .collect();
+    if !merge.is_empty() {
+        content.push_str("\n## Candidates for merging\n");
+        content.push_str("These tags are almost always used together:\n");
+        content.push_str(&merge.join("\n"));
+        content.push('\n');
+    }
+    content
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::logseq::mock::MockGraph;
+    use crate::tools::testing::{call_text, serve};
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_tag_cooccurrence_report() {
+        let mut graph = MockGraph::new();
+        graph.add_page("Notes");
+        graph.add_block("Notes", "Borrow checker\ntags:: rust, programming");
+        graph.add_block("Notes", "Lifetimes\ntags:: rust, programming");
+        graph.add_block("Notes", "Sourdough\ntags:: baking, recipes");
+        graph.add_block("Notes", "Focaccia\ntags:: baking");
+        let (_logseq, ctx) = serve(graph).await;
+
+        let text = call_text(&ctx, "analyze_tag_cooccurrence", json!({}))
+            .await
+            .unwrap();
+        assert!(text.contains("4 tags on 4 pages and blocks"), "{}", text);
+        assert!(
+            text.contains("1. #programming, #rust (2 pages/blocks)"),
+            "{}",
+            text
+        );
+        assert!(!text.contains("#baking +"), "{}", text);
+        assert!(text.contains("- #programming and #rust"), "{}", text);
+
+        let text = call_text(&ctx, "analyze_tag_cooccurrence", json!({"min_count": 1}))
+            .await
+            .unwrap();
+        assert!(
+            text.contains("#baking + #recipes: 1 together (50% overlap)"),
+            "{}",
+            text
+        );
+    }
+}
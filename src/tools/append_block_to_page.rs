@@ -0,0 +1,108 @@
+//! The `append_block_to_page` tool
+
+use super::context::{ToolContext, logseq_error, text_result};
+use super::registry::McpTool;
+use rmcp::{ErrorData as McpError, model::CallToolResult};
+use schemars::JsonSchema;
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct AppendBlockToPageArgs {
+    /// Name of the page to add the block to. It must already exist.
+    pub page: String,
+    /// Block content in markdown format. Can include text, links, formatting, and LogSeq-specific syntax.
+    pub content: String,
+}
+
+pub struct AppendBlockToPage;
+
+impl McpTool for AppendBlockToPage {
+    type Args = AppendBlockToPageArgs;
+
+    fn name(&self) -> &'static str {
+        "append_block_to_page"
+    }
+
+    fn description(&self) -> &'static str {
+        "Add a block at the end of a page, after its last top-level block. Works on empty pages and needs no block UUIDs. Returns the new block's UUID."
+    }
+
+    fn destructive(&self) -> bool {
+        false
+    }
+
+    async fn execute(
+        &self,
+        ctx: &ToolContext,
+        args: AppendBlockToPageArgs,
+    ) -> Result<CallToolResult, McpError> {
+        let AppendBlockToPageArgs { page, content } = args;
+        let page = page.trim();
+
+        ctx.require_page(page).await?;
+        ctx.require_writable(page).await?;
+        ctx.charge_write(Some(page), &content)?;
+        let block = ctx
+            .client
+            .append_block_in_page(page, &content)
+            .await
+            .map_err(logseq_error)?;
+
+        Ok(text_result(format!(
+            "Appended block with UUID {} to page {}",
+            block.uuid, page
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::logseq::mock::MockGraph;
+    use crate::tools::testing::{call_text, serve};
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_blocks_are_appended_and_prepended_to_pages() {
+        let mut graph = MockGraph::new();
+        graph.add_page("Log");
+        graph.add_block("Log", "middle");
+        graph.add_page("Empty");
+        let (logseq, ctx) = serve(graph).await;
+
+        let args = json!({"page": "Log", "content": "last"});
+        let text = call_text(&ctx, "append_block_to_page", args).await.unwrap();
+        assert!(text.starts_with("Appended block with UUID "), "{}", text);
+        let args = json!({"page": "Log", "content": "first"});
+        call_text(&ctx, "prepend_block_to_page", args)
+            .await
+            .unwrap();
+        let blocks = ctx.client.get_page_blocks_tree("Log").await.unwrap().items;
+        let contents: Vec<&str> = blocks.iter().map(|b| b.content.as_str()).collect();
+        assert_eq!(contents, vec!["first", "middle", "last"]);
+
+        let args = json!({"page": "Logs", "content": "lost"});
+        let err = call_text(&ctx, "append_block_to_page", args)
+            .await
+            .unwrap_err();
+        assert!(
+            err.message.contains("Did you mean: Log?"),
+            "{}",
+            err.message
+        );
+
+        // When insertBlock can't add to a page, create_block appends instead
+        logseq.fail("logseq.Editor.insertBlock", 500, "no block to insert after");
+        let args = json!({"parent": "Empty", "content": "only"});
+        call_text(&ctx, "create_block", args).await.unwrap();
+        let blocks = ctx
+            .client
+            .get_page_blocks_tree("Empty")
+            .await
+            .unwrap()
+            .items;
+        assert_eq!(blocks[0].content, "only");
+        let args = json!({"parent": blocks[0].uuid, "content": "child"});
+        assert!(call_text(&ctx, "create_block", args).await.is_err());
+    }
+}
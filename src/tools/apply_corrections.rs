@@ -0,0 +1,236 @@
+//! The `apply_corrections` tool
+
+use super::context::{ToolContext, internal_error, text_result};
+use super::registry::McpTool;
+use crate::intents::InverseOp;
+use crate::logseq::content::is_property_line;
+use rmcp::{ErrorData as McpError, model::CallToolResult};
+use schemars::JsonSchema;
+use serde::Deserialize;
+use std::collections::HashSet;
+
+/// The most corrections applied in one call
+const MAX_CORRECTIONS: usize = 100;
+
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct Correction {
+    /// The UUID of the block to correct.
+    pub uuid: String,
+    /// The block's content as extracted. The correction is skipped if the block has changed since.
+    pub original: String,
+    /// The corrected content, replacing the whole block.
+    pub corrected: String,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct ApplyCorrectionsArgs {
+    /// Block-level corrections, at most 100 per call, each block at most once.
+    pub corrections: Vec<Correction>,
+}
+
+pub struct ApplyCorrections;
+
+impl McpTool for ApplyCorrections {
+    type Args = ApplyCorrectionsArgs;
+
+    fn name(&self) -> &'static str {
+        "apply_corrections"
+    }
+
+    fn description(&self) -> &'static str {
+        "Apply block-level text corrections in bulk, such as fixes suggested by a spelling or consistency check run over extract_corpus output. Each correction replaces a block's whole content, but only if the block still matches 'original'. Corrections that would empty a block or change its property lines are skipped. Reports which corrections were applied and why any were skipped."
+    }
+
+    fn idempotent(&self) -> bool {
+        true
+    }
+
+    async fn execute(
+        &self,
+        ctx: &ToolContext,
+        args: ApplyCorrectionsArgs,
+    ) -> Result<CallToolResult, McpError> {
+        let corrections = args.corrections;
+        if corrections.is_empty() {
+            return Err(McpError::invalid_params("No corrections given", None));
+        }
+        if corrections.len() > MAX_CORRECTIONS {
+            return Err(McpError::invalid_params(
+                format!(
+                    "At most {} corrections can be applied at once, got {}; split them into several calls",
+                    MAX_CORRECTIONS,
+                    corrections.len()
+                ),
+                None,
+            ));
+        }
+        let mut seen = HashSet::new();
+        if let Some(repeated) = corrections.iter().find(|c| !seen.insert(c.uuid.as_str())) {
+            return Err(McpError::invalid_params(
+                format!("Block {} is corrected more than once", repeated.uuid),
+                Some(serde_json::json!({ "uuid": repeated.uuid })),
+            ));
+        }
+
+        for correction in &corrections {
+            ctx.charge_write(None, &correction.corrected)?;
+        }
+
+        let mut intent = ctx.begin_intent(
+            self.name(),
+            corrections
+                .iter()
+                .map(|c| format!("correct block {}", c.uuid))
+                .collect(),
+        )?;
+        let mut applied = 0;
+        let mut skipped = Vec::new();
+        for (i, correction) in corrections.iter().enumerate() {
+            if let Some(reason) = check(ctx, correction).await? {
+                skipped.push(format!("{}: {}", correction.uuid, reason));
+                intent.skipped(i);
+                continue;
+            }
+            ctx.client
+                .update_block(&correction.uuid, &correction.corrected, None)
+                .await
+                .map_err(|e| {
+                    internal_error(format!(
+                        "Applied {} correction(s), then failed to update block {}: {}",
+                        applied, correction.uuid, e
+                    ))
+                })?;
+            intent.done(
+                i,
+                vec![InverseOp::RestoreContent {
+                    uuid: correction.uuid.clone(),
+                    content: correction.original.clone(),
+                }],
+            );
+            applied += 1;
+        }
+        intent.finish();
+
+        let mut content = format!(
+            "Applied {} of {} correction(s).",
+            applied,
+            corrections.len()
+        );
+        if !skipped.is_empty() {
+            content.push_str("\n\nSkipped:\n");
+            for line in &skipped {
+                content.push_str(&format!("- {}\n", line));
+            }
+        }
+        Ok(text_result(content))
+    }
+}
+
+/// Why a correction can't be applied safely, if it can't
+async fn check(ctx: &ToolContext, correction: &Correction) -> Result<Option<String>, McpError> {
+    if correction.corrected.trim().is_empty() {
+        return Ok(Some("the correction would empty the block".to_string()));
+    }
+    if correction.corrected == correction.original {
+        return Ok(Some("nothing to change".to_string()));
+    }
+    if property_lines(&correction.corrected) != property_lines(&correction.original) {
+        return Ok(Some(
+            "the correction changes the block's properties".to_string(),
+        ));
+    }
+    if let Err(e) = ctx.require_writable(&correction.uuid).await {
+        return Ok(Some(e.message.to_string()));
+    }
+    // A block that can't be read is skipped rather than failing the batch
+    let block = match ctx.client.get_block(&correction.uuid).await {
+        Ok(block) => block,
+        Err(e) => return Ok(Some(e.to_string())),
+    };
+    if block.content != correction.original {
+        return Ok(Some(
+            "the block has changed since it was extracted".to_string(),
+        ));
+    }
+    Ok(None)
+}
+
+fn property_lines(content: &str) -> Vec<&str> {
+    content
+        .lines()
+        .filter(|line| is_property_line(line))
+        .map(str::trim)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::logseq::mock::MockGraph;
+    use crate::tools::testing::{call_text, serve};
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_only_safe_corrections_are_applied() {
+        let mut graph = MockGraph::new();
+        graph.add_page("Notes");
+        let typo = graph.add_block("Notes", "Teh quick fox");
+        let stale = graph.add_block("Notes", "Recieve the parcel");
+        let tagged = graph.add_block("Notes", "Colour scheme\nstatus:: draft");
+        let (logseq, ctx) = serve(graph).await;
+
+        let args = json!({"corrections": [
+            {"uuid": typo, "original": "Teh quick fox", "corrected": "The quick fox"},
+            {"uuid": stale, "original": "Recieve a parcel", "corrected": "Receive a parcel"},
+            {
+                "uuid": tagged,
+                "original": "Colour scheme\nstatus:: draft",
+                "corrected": "Color scheme\nstatus:: final",
+            },
+        ]});
+        let text = call_text(&ctx, "apply_corrections", args).await.unwrap();
+        assert!(
+            text.starts_with("Applied 1 of 3 correction(s)."),
+            "{}",
+            text
+        );
+        assert!(
+            text.contains(&format!(
+                "- {}: the block has changed since it was extracted",
+                stale
+            )),
+            "{}",
+            text
+        );
+        assert!(
+            text.contains(&format!(
+                "- {}: the correction changes the block's properties",
+                tagged
+            )),
+            "{}",
+            text
+        );
+        logseq.with_graph(|g| {
+            assert_eq!(g.block_content(&typo).unwrap(), "The quick fox");
+            assert_eq!(g.block_content(&stale).unwrap(), "Recieve the parcel");
+            assert_eq!(
+                g.block_content(&tagged).unwrap(),
+                "Colour scheme\nstatus:: draft"
+            );
+        });
+
+        let args = json!({"corrections": [
+            {"uuid": typo, "original": "a", "corrected": "b"},
+            {"uuid": typo, "original": "b", "corrected": "c"},
+        ]});
+        let error = call_text(&ctx, "apply_corrections", args)
+            .await
+            .unwrap_err();
+        assert!(
+            error.message.contains("more than once"),
+            "{}",
+            error.message
+        );
+    }
+}
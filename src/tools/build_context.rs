@@ -0,0 +1,216 @@
+//! The `build_context` tool
+
+use super::context::{ToolContext, cited_result, json_result, logseq_error};
+use super::registry::McpTool;
+use crate::citations::Citations;
+use crate::context_pack::{ContextPack, pack};
+use crate::logseq::api::SearchOptions;
+use crate::ranking::{SearchWeights, rank_blocks};
+use crate::session::OutputFormat;
+use chrono::Utc;
+use rmcp::{ErrorData as McpError, model::CallToolResult};
+use schemars::JsonSchema;
+use serde::Deserialize;
+
+/// Token budget when none is given
+const DEFAULT_TOKEN_BUDGET: usize = 2000;
+
+/// The largest token budget accepted
+const MAX_TOKEN_BUDGET: usize = 50_000;
+
+/// Search results considered for the pack
+const CANDIDATES: usize = 100;
+
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct BuildContextArgs {
+    /// The question or topic to gather context for. Matched like 'search': blocks must contain every word, ignoring common words like 'the'; put a phrase in "double quotes" to match it exactly.
+    pub query: String,
+    /// Roughly how many tokens the snippets may take up, provenance included. Defaults to 2000, at most 50000.
+    #[schemars(range(min = 1, max = 50000))]
+    pub token_budget: Option<usize>,
+    /// Only use blocks on this page (page name).
+    pub page: Option<String>,
+    /// How much page titles, properties, block text, and recent changes count towards the ranking, as for 'search'.
+    pub weights: Option<SearchWeights>,
+}
+
+pub struct BuildContext;
+
+impl McpTool for BuildContext {
+    type Args = BuildContextArgs;
+
+    fn name(&self) -> &'static str {
+        "build_context"
+    }
+
+    fn description(&self) -> &'static str {
+        "Gather the blocks most relevant to a question into a context pack that fits a token budget (default 2000), for answering questions from the graph. Blocks are ranked as by 'search', blocks repeating text already included (such as quotes) are skipped, property lines are left out, and the last snippet is cut short if needed. Each snippet is headed by a citation marker such as '[1] Page > Parent > Block' to cite in answers; the result's structured content maps each marker to the block's UUID and ((ref)). Token counts are estimates."
+    }
+
+    fn read_only(&self) -> bool {
+        true
+    }
+
+    async fn execute(
+        &self,
+        ctx: &ToolContext,
+        args: BuildContextArgs,
+    ) -> Result<CallToolResult, McpError> {
+        let BuildContextArgs {
+            query,
+            token_budget,
+            page,
+            weights,
+        } = args;
+        let token_budget = token_budget
+            .unwrap_or(DEFAULT_TOKEN_BUDGET)
+            .clamp(1, MAX_TOKEN_BUDGET);
+
+        if let Some(page) = &page {
+            ctx.require_page(page).await?;
+        }
+        let options = SearchOptions {
+            case_sensitive: false,
+            limit: Some(CANDIDATES),
+            page,
+        };
+        let results = ctx
+            .client
+            .search(&query, &options)
+            .await
+            .map_err(logseq_error)?;
+        let mut ranked = rank_blocks(&query, results, &weights.unwrap_or_default(), Utc::now());
+        ranked.sort_by(|(a, _), (b, _)| {
+            b.score
+                .unwrap_or_default()
+                .total_cmp(&a.score.unwrap_or_default())
+        });
+
+        let pack = pack(
+            &query,
+            ranked.into_iter().map(|(result, _)| result).collect(),
+            token_budget,
+        );
+        if ctx.session.output_format == OutputFormat::Json {
+            return Ok(json_result(&pack, "context pack"));
+        }
+        let mut citations = Citations::default();
+        for snippet in &pack.snippets {
+            citations.cite(
+                &snippet.uuid,
+                &snippet.page,
+                &snippet.breadcrumb,
+                &snippet.content,
+            );
+        }
+        Ok(cited_result(format_pack(&pack), &citations))
+    }
+}
+
+fn format_pack(pack: &ContextPack) -> String {
+    if pack.snippets.is_empty() {
+        return if pack.omitted > 0 {
+            format!(
+                "No snippet about '{}' fits in {} tokens. Try a larger budget.",
+                pack.query, pack.token_budget
+            )
+        } else {
+            format!("Nothing in the graph matches '{}'.", pack.query)
+        };
+    }
+
+    let mut content = format!(
+        "Context for '{}': {} snippet{}, about {} of {} tokens.\n",
+        pack.query,
+        pack.snippets.len(),
+        if pack.snippets.len() == 1 { "" } else { "s" },
+        pack.tokens_used,
+        pack.token_budget
+    );
+    for (i, snippet) in pack.snippets.iter().enumerate() {
+        content.push_str(&format!("\n{}\n", snippet.render(i + 1)));
+    }
+
+    let mut notes = Vec::new();
+    if pack.omitted > 0 {
+        notes.push(format!(
+            "{} more matching block{} didn't fit",
+            pack.omitted,
+            if pack.omitted == 1 { "" } else { "s" }
+        ));
+    }
+    if pack.duplicates > 0 {
+        notes.push(format!(
+            "{} block{} repeating included text {} left out",
+            pack.duplicates,
+            if pack.duplicates == 1 { "" } else { "s" },
+            if pack.duplicates == 1 { "was" } else { "were" }
+        ));
+    }
+    if !notes.is_empty() {
+        content.push_str(&format!("\n{}.\n", notes.join("; ")));
+    }
+    content
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::logseq::mock::MockGraph;
+    use crate::tools::registry::ToolRegistry;
+    use crate::tools::testing::{call_text, serve};
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_build_context_packs_cited_snippets() {
+        let mut graph = MockGraph::new();
+        graph.add_page("Rust");
+        let heading = graph.add_block("Rust", "Memory");
+        let uuid = graph.add_block(&heading, "Ownership frees memory without a collector");
+        graph.add_page("Digest");
+        graph.add_block(
+            "Digest",
+            &format!(
+                "> Ownership frees memory without a collector\n[[Rust]] (({}))",
+                uuid
+            ),
+        );
+        graph.add_block(
+            "Rust",
+            &format!("Ownership notes {}", "and more ".repeat(300)),
+        );
+        let (_logseq, ctx) = serve(graph).await;
+
+        let args = json!({"query": "ownership", "token_budget": 100});
+        let text = call_text(&ctx, "build_context", args.clone())
+            .await
+            .unwrap();
+        assert!(
+            text.contains(
+                "[1] Rust > Memory > Ownership frees memory without a collector\nOwnership frees memory without a collector\n"
+            ),
+            "{}",
+            text
+        );
+        let result = ToolRegistry::builtin()
+            .call(&ctx, "build_context", args.as_object().cloned())
+            .await
+            .unwrap();
+        let citations = result.structured_content.unwrap()["citations"].clone();
+        assert_eq!(citations[0]["marker"], 1);
+        assert_eq!(citations[0]["uuid"], uuid.as_str());
+        assert_eq!(citations[0]["block_ref"], format!("(({}))", uuid));
+        assert_eq!(citations[1]["marker"], 2);
+        assert!(text.contains("…"), "{}", text);
+        assert!(
+            text.contains("1 block repeating included text was left out"),
+            "{}",
+            text
+        );
+
+        let text = call_text(&ctx, "build_context", json!({"query": "lifetimes"}))
+            .await
+            .unwrap();
+        assert_eq!(text, "Nothing in the graph matches 'lifetimes'.");
+    }
+}
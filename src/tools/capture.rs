@@ -0,0 +1,251 @@
+//! The `capture` tool
+
+use super::context::{ToolContext, logseq_error, text_result};
+use super::registry::McpTool;
+use crate::logseq::api::{Block, InsertBlockOptions};
+use chrono::Local;
+use rmcp::{ErrorData as McpError, model::CallToolResult};
+use schemars::JsonSchema;
+use serde::Deserialize;
+use serde_json::{Value, json};
+
+/// A template's `{{source}}` when neither the call nor the client says
+const UNKNOWN_SOURCE: &str = "agent";
+
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct CaptureArgs {
+    /// The note, in markdown. It becomes one block.
+    pub content: String,
+    /// Capture it as a task, starting with TODO. Defaults to false.
+    pub task: Option<bool>,
+    /// The page to add it to, within the workspace's namespace when there is one. Defaults to today's journal page, which is created if needed.
+    pub page: Option<String>,
+    /// Add it under the top-level block with this text, which is added to the end of the page if it isn't there. Defaults to the workspace's heading; without one, the note goes at the end of the page.
+    pub heading: Option<String>,
+    /// Capture in this workspace instead of the session's: its tags are added to the note, and its heading, namespace, and capture template used by default.
+    pub workspace: Option<String>,
+    /// The configured capture template to shape the note with. Defaults to the workspace's, then the one named 'default', if any.
+    pub template: Option<String>,
+    /// Where the note came from, such as a URL or an app, for templates that show it. Defaults to the MCP client's name.
+    pub source: Option<String>,
+}
+
+pub struct Capture;
+
+impl McpTool for Capture {
+    type Args = CaptureArgs;
+
+    fn name(&self) -> &'static str {
+        "capture"
+    }
+
+    fn description(&self) -> &'static str {
+        "Quickly note something down, or with 'task' add a TODO, without finding a place for it first. It goes on today's journal page unless 'page' says otherwise, under a heading block when there is one. In a workspace, the workspace's tags are added and its journal heading used, so notes for a project land together. Notes are shaped by the server's configured capture template, if there is one. Returns the new block's UUID."
+    }
+
+    fn examples(&self) -> Vec<Value> {
+        vec![
+            json!({"content": "Ask Sam about the venue"}),
+            json!({"content": "Send the launch budget", "task": true, "heading": "Launch"}),
+        ]
+    }
+
+    fn destructive(&self) -> bool {
+        false
+    }
+
+    async fn execute(
+        &self,
+        ctx: &ToolContext,
+        args: CaptureArgs,
+    ) -> Result<CallToolResult, McpError> {
+        let CaptureArgs {
+            content,
+            task,
+            page,
+            heading,
+            workspace,
+            template,
+            source,
+        } = args;
+        let workspace = ctx.workspace(workspace.as_deref())?;
+        let template = ctx.capture_template(template.as_deref(), workspace)?;
+
+        let mut content = content.trim().to_string();
+        if content.is_empty() {
+            return Err(McpError::invalid_params(
+                "'content' must not be empty",
+                Some(json!({ "content": content })),
+            ));
+        }
+        if let Some(template) = template {
+            let source = source
+                .as_deref()
+                .map(str::trim)
+                .filter(|source| !source.is_empty())
+                .or(ctx.client_name.as_deref())
+                .unwrap_or(UNKNOWN_SOURCE);
+            content = template.render(&content, source, Local::now());
+        }
+        if task.unwrap_or(false) {
+            content = format!("TODO {}", content);
+        }
+        if let Some(workspace) = workspace {
+            content = workspace.tag(&content);
+        }
+        let heading = heading
+            .map(|heading| heading.trim().to_string())
+            .filter(|heading| !heading.is_empty())
+            .or_else(|| workspace.and_then(|workspace| workspace.heading.clone()));
+
+        let journal = page.is_none();
+        let page = match page.as_deref().map(str::trim) {
+            Some(page) => {
+                let page = match workspace {
+                    Some(workspace) => workspace.page_name(page),
+                    None => page.to_string(),
+                };
+                ctx.require_page(&page).await?;
+                page
+            }
+            None => ctx.today_title().await,
+        };
+        ctx.require_writable(&page).await?;
+        ctx.charge_write(Some(&page), &content)?;
+
+        let _guard = ctx.page_locks.lock(&page).await;
+        if journal {
+            ctx.ensure_journal_page(&page).await.map_err(logseq_error)?;
+        }
+        let block = match &heading {
+            Some(heading) => {
+                let parent = heading_block(ctx, &page, heading).await?;
+                let opts = InsertBlockOptions {
+                    parent: Some(parent.uuid),
+                    ..Default::default()
+                };
+                ctx.client.insert_block(&content, opts).await
+            }
+            None => ctx.client.append_block_in_page(&page, &content).await,
+        }
+        .map_err(logseq_error)?;
+
+        let mut text = format!("Captured block {} on page {}", block.uuid, page);
+        if let Some(heading) = heading {
+            text.push_str(&format!(" under '{}'", heading));
+        }
+        Ok(text_result(text))
+    }
+}
+
+/// The top-level block of `page` reading `heading`, ignoring case and any
+/// markdown heading marks, added to the end of the page if there's none
+async fn heading_block(ctx: &ToolContext, page: &str, heading: &str) -> Result<Block, McpError> {
+    let blocks = ctx
+        .client
+        .get_page_blocks_tree(page)
+        .await
+        .map_err(logseq_error)?;
+    let found = blocks.items.into_iter().find(|block| {
+        let first = block.content.lines().next().unwrap_or_default();
+        first
+            .trim_start_matches('#')
+            .trim()
+            .eq_ignore_ascii_case(heading)
+    });
+    match found {
+        Some(block) => Ok(block),
+        None => {
+            ctx.charge_write(Some(page), heading)?;
+            ctx.client
+                .append_block_in_page(page, heading)
+                .await
+                .map_err(logseq_error)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::capture_template::CaptureTemplate;
+    use crate::logseq::mock::MockGraph;
+    use crate::tools::testing::{call_text, serve};
+    use crate::workspace::Workspace;
+    use rmcp::ErrorData as McpError;
+    use serde_json::{Value, json};
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn test_captures_are_shaped_by_templates() {
+        let mut graph = MockGraph::new();
+        graph.add_page("Inbox");
+        let (logseq, mut ctx) = serve(graph).await;
+        ctx.capture_templates = Arc::new([
+            CaptureTemplate::parse("default {{text}} (via {{source}})").unwrap(),
+            CaptureTemplate::parse("meeting {{time}} {{text}}").unwrap(),
+        ]);
+        ctx.workspaces = Arc::new([Workspace::parse(r#"alpha {"template": "meeting"}"#).unwrap()]);
+        ctx.client_name = Some("Claude".to_string());
+
+        let capture = |args: Value| {
+            let ctx = &ctx;
+            async move {
+                call_text(ctx, "capture", args).await?;
+                let blocks = ctx.client.get_page_blocks_tree("Inbox").await.unwrap();
+                Ok::<_, McpError>(blocks.items.last().unwrap().content.clone())
+            }
+        };
+        assert_eq!(
+            capture(json!({"content": "Ask Sam", "page": "Inbox"}))
+                .await
+                .unwrap(),
+            "Ask Sam (via Claude)"
+        );
+        assert_eq!(
+            capture(json!({"content": "Read it", "page": "Inbox", "task": true, "source": "https://example.com"}))
+                .await
+                .unwrap(),
+            "TODO Read it (via https://example.com)"
+        );
+        let standup =
+            capture(json!({"content": "Standup", "page": "Inbox", "template": "meeting"}))
+                .await
+                .unwrap();
+        assert!(
+            standup.len() == 13 && standup[2..3] == *":" && standup.ends_with(" Standup"),
+            "{}",
+            standup
+        );
+
+        // The workspace's template is used ahead of the default, within its
+        // namespace
+        logseq.with_graph(|g| {
+            g.add_page("Alpha/Inbox");
+        });
+        call_text(
+            &ctx,
+            "capture",
+            json!({"content": "Retro", "page": "Inbox", "workspace": "alpha"}),
+        )
+        .await
+        .unwrap();
+        let blocks = ctx
+            .client
+            .get_page_blocks_tree("Alpha/Inbox")
+            .await
+            .unwrap();
+        assert!(blocks.items[0].content.ends_with(" Retro"));
+
+        let error = capture(json!({"content": "x", "page": "Inbox", "template": "weekly"}))
+            .await
+            .unwrap_err();
+        assert!(
+            error
+                .message
+                .contains("configured templates: default, meeting"),
+            "{}",
+            error.message
+        );
+    }
+}
@@ -0,0 +1,287 @@
+//! The `compile_digest` tool
+
+use super::context::{ToolContext, logseq_error, text_result};
+use super::quote_block::{block_page, ensure_block_id};
+use super::registry::McpTool;
+use crate::intents::InverseOp;
+use crate::logseq::api::{CreatePageOptions, InsertBlockOptions, SearchOptions};
+use crate::logseq::content::quote_block;
+use crate::logseq::markdown::BatchBlock;
+use crate::logseq::page_name::normalize_page_name;
+use crate::quota::QuotaKind;
+use rmcp::{ErrorData as McpError, model::CallToolResult};
+use schemars::JsonSchema;
+use serde::Deserialize;
+use serde_json::{Value, json};
+
+/// Blocks quoted when no limit is given
+const DEFAULT_LIMIT: usize = 20;
+
+/// The most blocks a single digest quotes
+const MAX_LIMIT: usize = 100;
+
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct CompileDigestArgs {
+    /// What to collect: full-text search terms, or a datascript query (starting with '[') returning block UUIDs or pulled blocks, e.g. '[:find ?uuid :where [?b :block/refs ?t] [?t :block/name "rust"] [?b :block/uuid ?uuid]]'.
+    pub query: String,
+    /// Page to write the digest to. It's created if it doesn't exist, otherwise the digest is added to the end. Blocks already on this page are never quoted.
+    pub target_page: String,
+    /// Content of the block the quotes are nested under. Defaults to 'Digest: ' followed by the search terms.
+    pub heading: Option<String>,
+    /// Most blocks to quote. Defaults to 20, at most 100.
+    #[schemars(range(min = 1, max = 100))]
+    pub limit: Option<usize>,
+}
+
+pub struct CompileDigest;
+
+impl McpTool for CompileDigest {
+    type Args = CompileDigestArgs;
+
+    fn name(&self) -> &'static str {
+        "compile_digest"
+    }
+
+    fn description(&self) -> &'static str {
+        "Collect blocks matching a full-text search or datascript query into a digest on a target page in one call: each hit becomes a '>' quote ending with a link to its source page and a ((ref)) to the original block, nested under a heading block. The page is created if needed. The quoted blocks get an 'id::' property so the references keep working, as with quote_block."
+    }
+
+    fn examples(&self) -> Vec<Value> {
+        vec![json!({"query": "launch", "target_page": "Digests/Launch", "limit": 10})]
+    }
+
+    fn destructive(&self) -> bool {
+        false
+    }
+
+    async fn execute(
+        &self,
+        ctx: &ToolContext,
+        args: CompileDigestArgs,
+    ) -> Result<CallToolResult, McpError> {
+        let CompileDigestArgs {
+            query,
+            target_page,
+            heading,
+            limit,
+        } = args;
+        let limit = limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT);
+        let title = normalize_page_name(&target_page)
+            .map_err(|e| McpError::invalid_params(e.to_string(), None))?;
+        let title = ctx.page_to_create(&title);
+
+        let datascript = query.trim_start().starts_with('[');
+        let uuids = match datascript {
+            true => {
+                let result = ctx
+                    .client
+                    .datascript_query(&query, &[])
+                    .await
+                    .map_err(logseq_error)?;
+                block_uuids(&result)
+            }
+            false => {
+                let options = SearchOptions {
+                    limit: Some(limit),
+                    ..Default::default()
+                };
+                ctx.client
+                    .search(&query, &options)
+                    .await
+                    .map_err(logseq_error)?
+                    .into_iter()
+                    .map(|r| r.block.uuid)
+                    .collect()
+            }
+        };
+
+        let _guard = ctx.page_locks.lock(&title).await;
+        let mut hits = Vec::new();
+        for uuid in uuids {
+            if hits.len() == limit {
+                break;
+            }
+            let page = block_page(ctx, &uuid).await?;
+            if page
+                .as_deref()
+                .is_some_and(|page| page.eq_ignore_ascii_case(&title))
+            {
+                continue;
+            }
+            let block = ctx.client.get_block(&uuid).await.map_err(logseq_error)?;
+            hits.push((block, page));
+        }
+        if hits.is_empty() {
+            return Ok(text_result(format!(
+                "Nothing matched '{}'; no digest was written",
+                query.trim()
+            )));
+        }
+
+        let quotes = hits
+            .iter()
+            .map(|(block, page)| BatchBlock {
+                content: quote_block(&block.content, &block.uuid, page.as_deref()),
+                children: Vec::new(),
+            })
+            .collect();
+        let heading = heading.unwrap_or_else(|| match datascript {
+            true => "Digest".to_string(),
+            false => format!("Digest: {}", query.trim()),
+        });
+
+        let digest = BatchBlock {
+            content: heading,
+            children: quotes,
+        };
+        ctx.charge_blocks(Some(&title), std::slice::from_ref(&digest))?;
+        let create_page = ctx
+            .client
+            .find_page(&title)
+            .await
+            .map_err(logseq_error)?
+            .is_none();
+        if create_page {
+            ctx.charge_quota(QuotaKind::PagesCreated, 1)?;
+        }
+
+        // An id:: property only keeps references working, so adding one
+        // needs no undoing
+        let mut steps = vec![format!("give the {} quoted block(s) ids", hits.len())];
+        if create_page {
+            steps.push(format!("create page '{}'", title));
+        }
+        steps.push(format!("write the digest to page '{}'", title));
+        let mut intent = ctx.begin_intent(self.name(), steps)?;
+
+        for (block, _) in &hits {
+            ensure_block_id(ctx, block).await?;
+        }
+        intent.done(0, Vec::new());
+
+        if create_page {
+            ctx.client
+                .create_page(&title, None, CreatePageOptions::default())
+                .await
+                .map_err(logseq_error)?;
+            ctx.cache.invalidate_pages();
+            intent.done(
+                1,
+                vec![InverseOp::DeletePage {
+                    name: title.clone(),
+                }],
+            );
+        }
+        let opts = InsertBlockOptions {
+            parent: Some(title.clone()),
+            ..Default::default()
+        };
+        let heading = ctx
+            .client
+            .insert_block(&digest.content, opts)
+            .await
+            .map_err(logseq_error)?;
+        let written = vec![InverseOp::RemoveBlock {
+            uuid: heading.uuid.clone(),
+        }];
+        let result = ctx
+            .client
+            .insert_batch_block(&heading.uuid, &digest.children, false)
+            .await;
+        intent.done(if create_page { 2 } else { 1 }, written);
+        result.map_err(logseq_error)?;
+        intent.finish();
+
+        Ok(text_result(format!(
+            "Compiled {} quoted block(s) into page: {}",
+            hits.len(),
+            title
+        )))
+    }
+}
+
+/// UUIDs of the blocks a datascript query found, in order and without
+/// repeats. Each result row's first value may be a UUID or a pulled block.
+fn block_uuids(result: &Value) -> Vec<String> {
+    let mut uuids: Vec<String> = Vec::new();
+    for row in result.as_array().into_iter().flatten() {
+        let value = match row {
+            Value::Array(values) => values.first(),
+            value => Some(value),
+        };
+        let uuid = match value {
+            Some(Value::String(uuid)) => Some(uuid.as_str()),
+            Some(Value::Object(block)) => block.get("uuid").and_then(Value::as_str),
+            _ => None,
+        };
+        if let Some(uuid) = uuid.filter(|u| uuid::Uuid::parse_str(u).is_ok())
+            && !uuids.iter().any(|u| u == uuid)
+        {
+            uuids.push(uuid.to_string());
+        }
+    }
+    uuids
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::logseq::mock::MockGraph;
+    use crate::tools::testing::{call_text, serve};
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_digest_quotes_search_hits_on_a_new_page() {
+        let mut graph = MockGraph::new();
+        graph.add_page("Rust");
+        let ownership = graph.add_block("Rust", "Ownership makes borrowing explicit");
+        graph.add_page("Journal");
+        let later = graph.add_block("Journal", "DONE Read about borrowing rules");
+        graph.add_block("Journal", "Unrelated");
+        let (logseq, ctx) = serve(graph).await;
+
+        let args = json!({"query": "borrowing", "target_page": "Borrowing Digest"});
+        let text = call_text(&ctx, "compile_digest", args.clone())
+            .await
+            .unwrap();
+        assert!(text.contains("Compiled 2 quoted block(s)"), "{}", text);
+
+        logseq.with_graph(|g| {
+            let digest = &g.children("Borrowing Digest")[0];
+            assert_eq!(g.block_content(digest).unwrap(), "Digest: borrowing");
+            let quotes: Vec<&str> = g
+                .children(digest)
+                .iter()
+                .map(|q| g.block_content(q).unwrap())
+                .collect();
+            assert!(
+                quotes.contains(
+                    &format!(
+                        "> Ownership makes borrowing explicit\n> — [[Rust]] (({}))",
+                        ownership
+                    )
+                    .as_str()
+                )
+            );
+            assert!(
+                quotes.contains(
+                    &format!(
+                        "> Read about borrowing rules\n> — [[Journal]] (({}))",
+                        later
+                    )
+                    .as_str()
+                )
+            );
+            assert!(g.block_content(&later).unwrap().contains("id:: "));
+        });
+
+        // The first digest's quotes match too, but aren't quoted again
+        let text = call_text(&ctx, "compile_digest", args).await.unwrap();
+        assert!(text.contains("Compiled 2 quoted block(s)"), "{}", text);
+
+        let args = json!({"query": "nowhere", "target_page": "Empty"});
+        let text = call_text(&ctx, "compile_digest", args).await.unwrap();
+        assert!(text.contains("no digest was written"), "{}", text);
+        logseq.with_graph(|g| assert!(g.children("Empty").is_empty()));
+    }
+}
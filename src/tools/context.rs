@@ -0,0 +1,809 @@
+//! Shared state and helpers available to every tool
+
+use crate::audit::AuditLog;
+use crate::capture_template::{CaptureTemplate, DEFAULT_TEMPLATE};
+use crate::citations::Citations;
+use crate::confirm::{CONFIRMATION_TTL, Confirmations};
+use crate::fuzzy;
+use crate::intents::{IntentLog, RunningIntent};
+use crate::limits::{WriteBudget, WriteLimits};
+use crate::logseq::api::{CreatePageOptions, LogSeqClient, Page};
+use crate::logseq::cache::{GraphCache, QueryCache};
+use crate::logseq::error::LogSeqError;
+use crate::logseq::journal::{DEFAULT_DATE_FORMAT, detect_journal_date, format_journal_title};
+use crate::logseq::markdown::BatchBlock;
+use crate::page_lock::PageLocks;
+use crate::quota::{Quota, QuotaKind};
+use crate::region_base::RegionBases;
+use crate::sandbox::{Namespace, OutsideNamespace};
+use crate::session::SessionConfig;
+use crate::tools::datascript_query::DEFAULT_SLOW_QUERY;
+use crate::trash::Trash;
+use crate::workspace::Workspace;
+use chrono::{Local, NaiveDate};
+use rmcp::{
+    ErrorData as McpError,
+    model::{CallToolResult, Content, RawContent, RawTextContent},
+};
+use serde::Serialize;
+use std::fmt::Display;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// Name of the graph a server serves when it's only given one
+pub const DEFAULT_GRAPH: &str = "default";
+
+/// Everything a tool needs to talk to LogSeq
+#[derive(Clone)]
+pub struct ToolContext {
+    pub client: Arc<LogSeqClient>,
+    pub cache: Arc<GraphCache>,
+    /// Recent datascript query results and page checksums, cleared whenever
+    /// a tool that changes the graph runs
+    pub queries: Arc<QueryCache>,
+    pub page_locks: Arc<PageLocks>,
+    pub confirmations: Arc<Confirmations>,
+    /// Settings of the session the current call belongs to
+    pub session: Arc<SessionConfig>,
+    /// Where deleted pages and blocks are kept, when the server has a state
+    /// directory
+    pub trash: Option<Arc<Trash>>,
+    /// Where multi-step changes record their progress, when the server has
+    /// a state directory
+    pub intents: Option<Arc<IntentLog>>,
+    /// What managed regions last had written to them, when the server has
+    /// a state directory
+    pub region_bases: Option<Arc<RegionBases>>,
+    /// Where calls that change the graph are recorded, when auditing is on
+    pub audit: Option<Arc<AuditLog>>,
+    /// Whether successful changes are summarised in today's journal
+    pub journal_activity: bool,
+    /// Datascript queries taking at least this long are logged and flagged
+    pub slow_query: Duration,
+    /// Correlation ID of the tool call being handled, set by the registry
+    pub request_id: Option<String>,
+    /// How much content a single call may write
+    pub limits: WriteLimits,
+    /// What the current call has written so far, counted against `limits`
+    budget: Arc<Mutex<WriteBudget>>,
+    /// This session's budgets for changes to the graph
+    pub quota: Arc<Quota>,
+    /// Name of the graph this context talks to
+    pub graph: String,
+    /// Names of every graph the server serves, the default first
+    pub graphs: Arc<[String]>,
+    /// The namespace changes are confined to, if they are
+    pub namespace: Option<Namespace>,
+    /// Workspaces that sessions and calls can select by name
+    pub workspaces: Arc<[Workspace]>,
+    /// Templates `capture` shapes notes with
+    pub capture_templates: Arc<[CaptureTemplate]>,
+    /// The name the MCP client gave when it connected, if the call came
+    /// from one
+    pub client_name: Option<String>,
+}
+
+impl ToolContext {
+    /// A context whose cache passes every read through to LogSeq
+    pub fn new(client: LogSeqClient) -> Self {
+        Self::with_cache_ttl(client, Duration::ZERO)
+    }
+
+    /// A context that serves the page list, tags, user configs, and current
+    /// graph from cache until they're refreshed or invalidated
+    pub fn with_cache(client: LogSeqClient) -> Self {
+        Self::with_cache_ttl(client, Duration::MAX)
+    }
+
+    /// A context that reuses the page list, tags, user configs, and current
+    /// graph for `ttl`
+    pub fn with_cache_ttl(client: LogSeqClient, ttl: Duration) -> Self {
+        let client = Arc::new(client);
+        Self {
+            cache: Arc::new(GraphCache::new(client.clone(), ttl)),
+            client,
+            queries: Arc::default(),
+            page_locks: Arc::default(),
+            confirmations: Arc::default(),
+            session: Arc::default(),
+            trash: None,
+            intents: None,
+            region_bases: None,
+            audit: None,
+            journal_activity: false,
+            slow_query: DEFAULT_SLOW_QUERY,
+            request_id: None,
+            limits: WriteLimits::default(),
+            budget: Arc::default(),
+            quota: Arc::default(),
+            graph: DEFAULT_GRAPH.to_string(),
+            graphs: Arc::new([DEFAULT_GRAPH.to_string()]),
+            namespace: None,
+            workspaces: Arc::new([]),
+            capture_templates: Arc::new([]),
+            client_name: None,
+        }
+    }
+
+    /// The same context, for calls made within a session using `session`
+    pub fn for_session(&self, session: SessionConfig) -> Self {
+        Self {
+            session: Arc::new(session),
+            ..self.clone()
+        }
+    }
+
+    /// The same context, for handling the tool call `request_id`
+    pub fn for_request(&self, request_id: &str) -> Self {
+        Self {
+            request_id: Some(request_id.to_string()),
+            budget: Arc::new(Mutex::new(WriteBudget::new(self.limits))),
+            ..self.clone()
+        }
+    }
+
+    /// The folder of the graph open in LogSeq, if LogSeq says where it is
+    pub async fn graph_dir(&self) -> Option<PathBuf> {
+        match self.cache.current_graph().await {
+            Ok(graph) => graph
+                .get("path")
+                .and_then(serde_json::Value::as_str)
+                .filter(|path| !path.is_empty())
+                .map(PathBuf::from),
+            Err(e) => {
+                tracing::debug!("The current graph's folder is unknown: {}", e);
+                None
+            }
+        }
+    }
+
+    /// The workspace a call works in: the one it names, otherwise the
+    /// session's, if either is set
+    pub fn workspace(&self, name: Option<&str>) -> Result<Option<&Workspace>, McpError> {
+        let Some(name) = name.or(self.session.workspace.as_deref()) else {
+            return Ok(None);
+        };
+        match self
+            .workspaces
+            .iter()
+            .find(|workspace| workspace.name == name)
+        {
+            Some(workspace) => Ok(Some(workspace)),
+            None => {
+                let known: Vec<&str> = self.workspaces.iter().map(|w| w.name.as_str()).collect();
+                Err(McpError::invalid_params(
+                    match known.is_empty() {
+                        true => format!("Unknown workspace '{}'; none are configured", name),
+                        false => format!(
+                            "Unknown workspace '{}'; configured workspaces: {}",
+                            name,
+                            known.join(", ")
+                        ),
+                    },
+                    Some(serde_json::json!({ "workspace": name, "workspaces": known })),
+                ))
+            }
+        }
+    }
+
+    /// The capture template a note is shaped with: the one it names,
+    /// otherwise its workspace's, otherwise the default one, if any
+    pub fn capture_template(
+        &self,
+        name: Option<&str>,
+        workspace: Option<&Workspace>,
+    ) -> Result<Option<&CaptureTemplate>, McpError> {
+        let named = name.or(workspace.and_then(|w| w.template.as_deref()));
+        let wanted = named.unwrap_or(DEFAULT_TEMPLATE);
+        let found = self.capture_templates.iter().find(|t| t.name == wanted);
+        match (found, named) {
+            (Some(template), _) => Ok(Some(template)),
+            (None, None) => Ok(None),
+            (None, Some(name)) => {
+                let known: Vec<&str> = self
+                    .capture_templates
+                    .iter()
+                    .map(|t| t.name.as_str())
+                    .collect();
+                Err(McpError::invalid_params(
+                    match known.is_empty() {
+                        true => format!("Unknown capture template '{}'; none are configured", name),
+                        false => format!(
+                            "Unknown capture template '{}'; configured templates: {}",
+                            name,
+                            known.join(", ")
+                        ),
+                    },
+                    Some(serde_json::json!({ "template": name, "templates": known })),
+                ))
+            }
+        }
+    }
+
+    /// The title of today's journal page, in the user's date format
+    pub async fn today_title(&self) -> String {
+        let date_format = self
+            .cache
+            .preferred_date_format()
+            .await
+            .unwrap_or_else(|_| DEFAULT_DATE_FORMAT.to_string());
+        format_journal_title(Local::now().date_naive(), &date_format)
+    }
+
+    /// Create the journal page titled `title` unless it already exists
+    pub async fn ensure_journal_page(&self, title: &str) -> anyhow::Result<()> {
+        if self.client.find_page(title).await?.is_none() {
+            let opts = CreatePageOptions {
+                journal: Some(true),
+                redirect: Some(false),
+                ..Default::default()
+            };
+            self.client.create_page(title, None, opts).await?;
+            self.cache.invalidate_pages();
+        }
+        Ok(())
+    }
+
+    /// The page, which must exist, otherwise fail with "did you mean"
+    /// suggestions drawn from the graph's page names.
+    pub async fn require_page(&self, name_or_uuid: &str) -> Result<Page, McpError> {
+        let page = self
+            .client
+            .find_page(name_or_uuid)
+            .await
+            .map_err(logseq_error)?;
+        if let Some(page) = page {
+            return Ok(page);
+        }
+
+        // Suggestions are best-effort; a failure here shouldn't mask the not-found error
+        let pages = self.cache.pages().await.unwrap_or_default();
+        let suggestions = fuzzy::suggest(
+            name_or_uuid,
+            pages
+                .iter()
+                .map(|p| p.original_name.as_deref().unwrap_or(&p.name)),
+        );
+
+        let mut message = format!("Page not found: {}", name_or_uuid);
+        if !suggestions.is_empty() {
+            message.push_str(&format!(". Did you mean: {}?", suggestions.join(", ")));
+        }
+
+        Err(McpError::invalid_params(
+            message,
+            Some(serde_json::json!({
+                "error": "not_found",
+                "retryable": false,
+                "kind": "page",
+                "name": name_or_uuid,
+                "page": name_or_uuid,
+                "suggestions": suggestions,
+            })),
+        ))
+    }
+
+    /// The name a page called `name` is created under, which is inside the
+    /// namespace changes are confined to, if they are
+    pub fn page_to_create(&self, name: &str) -> String {
+        match &self.namespace {
+            Some(namespace) => namespace.qualify(name),
+            None => name.to_string(),
+        }
+    }
+
+    /// Whether this server may change the page called `name`
+    pub fn can_write_page(&self, name: &str) -> bool {
+        self.namespace
+            .as_ref()
+            .is_none_or(|namespace| namespace.contains(name))
+    }
+
+    /// Fail unless this server may change the page called `name`
+    pub fn require_writable_page(&self, name: &str) -> Result<(), McpError> {
+        match &self.namespace {
+            Some(namespace) => namespace.check(name).map_err(outside_namespace),
+            None => Ok(()),
+        }
+    }
+
+    /// Fail unless this server may change `target`, a page name or the UUID
+    /// of a page or block. A target that doesn't exist is left for the tool
+    /// to report.
+    pub async fn require_writable(&self, target: &str) -> Result<(), McpError> {
+        let Some(namespace) = &self.namespace else {
+            return Ok(());
+        };
+        let target = target.trim();
+        if uuid::Uuid::parse_str(target).is_err() {
+            return namespace.check(target).map_err(outside_namespace);
+        }
+
+        let page = match self
+            .client
+            .get_block_location(target)
+            .await
+            .map_err(logseq_error)?
+        {
+            Some(location) => Some(location.page),
+            None => self
+                .client
+                .find_page(target)
+                .await
+                .map_err(logseq_error)?
+                .map(|page| page.name),
+        };
+        match page {
+            Some(page) => namespace.check(&page).map_err(outside_namespace),
+            None => Ok(()),
+        }
+    }
+
+    /// The trash, or an error if deletes aren't being kept
+    pub fn require_trash(&self) -> Result<&Trash, McpError> {
+        self.trash.as_deref().ok_or_else(|| {
+            McpError::invalid_request(
+                "The trash is not enabled because the server has no state directory",
+                None,
+            )
+        })
+    }
+
+    /// The intent log, or an error if operations aren't being recorded
+    pub fn require_intents(&self) -> Result<&IntentLog, McpError> {
+        self.intents.as_deref().ok_or_else(|| {
+            McpError::invalid_request(
+                "The intent log is not enabled because the server has no state directory",
+                None,
+            )
+        })
+    }
+
+    /// Record the steps of a multi-step change in the intent log before
+    /// making it
+    pub fn begin_intent(&self, tool: &str, steps: Vec<String>) -> Result<RunningIntent, McpError> {
+        RunningIntent::begin(
+            self.intents.as_ref(),
+            tool,
+            self.request_id.as_deref(),
+            steps,
+        )
+        .map_err(|e| {
+            internal_error(format!(
+                "Could not record the operation in the intent log, so nothing was changed: {}",
+                e
+            ))
+        })
+    }
+
+    /// Count a block this call is about to write, to `page` when it's known,
+    /// failing if it would go over the server's write limits
+    pub fn charge_write(&self, page: Option<&str>, content: &str) -> Result<(), McpError> {
+        let mut budget = self.budget.lock().unwrap_or_else(|e| e.into_inner());
+        budget.charge(page, content).map_err(|e| {
+            McpError::invalid_params(
+                e.to_string(),
+                Some(serde_json::json!({ "limit": e.limit() })),
+            )
+        })
+    }
+
+    /// Use `n` of this session's budget for `kind` of change, failing if
+    /// it's been used up
+    pub fn charge_quota(&self, kind: QuotaKind, n: u32) -> Result<(), McpError> {
+        self.quota.charge(kind, n).map_err(|e| {
+            McpError::invalid_request(
+                e.to_string(),
+                Some(serde_json::json!({ "budget": e.kind.to_string(), "limit": e.limit })),
+            )
+        })
+    }
+
+    /// [`charge_write`](Self::charge_write) for a tree of blocks
+    pub fn charge_blocks(&self, page: Option<&str>, blocks: &[BatchBlock]) -> Result<(), McpError> {
+        for block in blocks {
+            self.charge_write(page, &block.content)?;
+            self.charge_blocks(page, &block.children)?;
+        }
+        Ok(())
+    }
+
+    /// Redeem a confirmation token for a destructive `action`, as issued by
+    /// the tool's preview call
+    pub fn confirm(&self, token: &str, action: &str) -> Result<(), McpError> {
+        self.confirmations.redeem(token, action).map_err(|e| {
+            McpError::invalid_params(e.to_string(), Some(serde_json::json!({ "confirm": token })))
+        })
+    }
+
+    /// How a delete preview says `what` will be removed: moved to the trash
+    /// when there is one, otherwise gone for good
+    pub fn removal(&self, what: &str) -> String {
+        match self.trash {
+            Some(_) => format!(
+                "move {} to the trash (restore with restore_from_trash)",
+                what
+            ),
+            None => format!("permanently remove {}", what),
+        }
+    }
+}
+
+fn outside_namespace(e: OutsideNamespace) -> McpError {
+    McpError::invalid_request(
+        e.to_string(),
+        Some(serde_json::json!({
+            "error": "outside_namespace",
+            "retryable": false,
+            "namespace": e.namespace,
+            "page": e.page,
+        })),
+    )
+}
+
+/// Reject property keys LogSeq can't store as a `key:: value` line
+pub fn require_property_key(key: &str) -> Result<(), McpError> {
+    let problem = if key.is_empty() {
+        Some("must not be empty")
+    } else if key.chars().any(char::is_whitespace) {
+        Some("must not contain whitespace")
+    } else if key.contains("::") || key.starts_with(':') {
+        Some("must not contain '::' or start with ':'")
+    } else {
+        None
+    };
+
+    match problem {
+        Some(problem) => Err(McpError::invalid_params(
+            format!("Invalid property key '{}': {}", key, problem),
+            Some(serde_json::json!({ "key": key })),
+        )),
+        None => Ok(()),
+    }
+}
+
+/// A date argument in ISO form or the user's journal title format, with or
+/// without `[[ ]]`
+pub fn parse_date_arg(what: &str, value: &str, date_format: &str) -> Result<NaiveDate, McpError> {
+    let trimmed = value.trim().trim_start_matches("[[").trim_end_matches("]]");
+    detect_journal_date(trimmed, date_format).ok_or_else(|| {
+        McpError::invalid_params(
+            format!(
+                "Invalid {} '{}': expected YYYY-MM-DD or a date such as '{}'",
+                what,
+                value,
+                format_journal_title(Local::now().date_naive(), date_format)
+            ),
+            Some(serde_json::json!({ what: value })),
+        )
+    })
+}
+
+/// Seconds a client is told to wait before retrying while LogSeq is
+/// unreachable
+pub const BACKEND_RETRY_SECS: u64 = 5;
+
+/// The error for any call that needs LogSeq while it can't be reached. The
+/// data says so in a way clients can check for, with a retry hint.
+pub fn backend_unavailable(graph: &str, reason: &str) -> McpError {
+    McpError::internal_error(
+        format!(
+            "LogSeq is unavailable for graph '{}' ({}). Check that LogSeq is running with its HTTP API server enabled, then retry.",
+            graph, reason
+        ),
+        Some(serde_json::json!({
+            "error": "backend_unavailable",
+            "graph": graph,
+            "retryable": true,
+            "retry_after_secs": BACKEND_RETRY_SECS,
+        })),
+    )
+}
+
+/// Map a LogSeq client failure to an MCP error. Failures the client could
+/// tell apart (see [`LogSeqError`]) say which they were in the data, as
+/// `error`, with whether retrying might help; a page or block that doesn't
+/// exist is the caller's mistake rather than the server's.
+pub fn logseq_error(e: anyhow::Error) -> McpError {
+    let Some(error) = e.downcast_ref::<LogSeqError>() else {
+        return internal_error(e);
+    };
+    let mut data = serde_json::json!({
+        "error": error.code(),
+        "retryable": error.is_retryable(),
+    });
+    match error {
+        LogSeqError::NotFound { kind, name } => {
+            data["kind"] = kind.to_lowercase().into();
+            data["name"] = name.as_str().into();
+            return McpError::invalid_params(error.to_string(), Some(data));
+        }
+        LogSeqError::Unreachable { .. } => {
+            data["retry_after_secs"] = BACKEND_RETRY_SECS.into();
+        }
+        LogSeqError::Api { status, .. } => {
+            data["status"] = status.as_u16().into();
+        }
+        LogSeqError::MalformedResponse {
+            report: Some(id), ..
+        } => {
+            data["report"] = id.as_str().into();
+        }
+        _ => {}
+    }
+    McpError::internal_error(error.to_string(), Some(data))
+}
+
+/// Map a failure to an MCP internal error
+pub fn internal_error(e: impl Display) -> McpError {
+    McpError::internal_error(e.to_string(), None)
+}
+
+/// A successful tool result containing a single text block
+pub fn text_result(text: impl Into<String>) -> CallToolResult {
+    CallToolResult {
+        content: Some(vec![Content {
+            raw: RawContent::Text(RawTextContent { text: text.into() }),
+            annotations: None,
+        }]),
+        structured_content: None,
+        is_error: Some(false),
+    }
+}
+
+/// A text result citing blocks, with the citation map as structured content
+pub fn cited_result(text: impl Into<String>, citations: &Citations) -> CallToolResult {
+    let mut result = text_result(text);
+    result.structured_content = Some(citations.to_value());
+    result
+}
+
+/// The preview half of a two-phase destructive call: what would happen, and
+/// how to go ahead with it
+pub fn confirmation_result(preview: &str, tool: &str, token: &str) -> CallToolResult {
+    text_result(format!(
+        "{}\n\nNothing has been deleted yet. To proceed, call {} again with the same arguments and confirm: \"{}\". The token is single use and expires in {} minutes.",
+        preview.trim_end(),
+        tool,
+        token,
+        CONFIRMATION_TTL.as_secs() / 60
+    ))
+}
+
+/// A successful tool result containing `value` as pretty-printed JSON
+pub fn json_result<T: Serialize>(value: &T, what: &str) -> CallToolResult {
+    text_result(
+        serde_json::to_string_pretty(value)
+            .unwrap_or_else(|_| format!("Error serializing {}", what)),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::logseq::mock::{MockGraph, MockLogSeq};
+    use crate::tools::testing::{call_text, serve};
+    use rmcp::model::ErrorCode;
+    use serde_json::json;
+
+    #[test]
+    fn test_require_property_key() {
+        assert!(require_property_key("status").is_ok());
+        assert!(require_property_key("due-date").is_ok());
+        for bad in ["", "two words", "a::b", ":status", "tab\there"] {
+            assert!(
+                require_property_key(bad).is_err(),
+                "{:?} should be rejected",
+                bad
+            );
+        }
+    }
+
+    #[test]
+    fn test_confirm_redeems_issued_token_once() {
+        let client = LogSeqClient::new("http://localhost:12315", "token").unwrap();
+        let ctx = ToolContext::new(client);
+        let token = ctx.confirmations.issue("delete_page alpha");
+
+        let preview = confirmation_result("Deleting page 'alpha'", "delete_page", &token);
+        let text = serde_json::to_string(&preview).unwrap();
+        assert!(text.contains(&format!("confirm: \\\"{}\\\"", token)));
+
+        let err = ctx.confirm(&token, "delete_page beta").unwrap_err();
+        assert_eq!(err.data.unwrap()["confirm"], token.as_str());
+        assert!(ctx.confirm(&token, "delete_page alpha").is_ok());
+        assert!(ctx.confirm(&token, "delete_page alpha").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_logseq_failures_become_tool_errors() {
+        let logseq = MockLogSeq::start().await.unwrap();
+        logseq.fail("logseq.Editor.getAllPages", 500, "database is locked");
+        let ctx = ToolContext::new(logseq.client());
+
+        let err = call_text(&ctx, "list_pages", json!({})).await.unwrap_err();
+        assert!(
+            err.message.contains("database is locked"),
+            "{}",
+            err.message
+        );
+    }
+
+    #[tokio::test]
+    async fn test_write_limits_reject_oversized_content() {
+        let mut graph = MockGraph::new();
+        graph.add_page("Notes");
+        let (logseq, mut ctx) = serve(graph).await;
+        ctx.limits = WriteLimits {
+            block: Some(20),
+            page: Some(30),
+            call: None,
+        };
+
+        let args = json!({"content": "Short enough", "parent": "Notes"});
+        call_text(&ctx, "create_block", args).await.unwrap();
+        let args = json!({"content": "This block is far too long", "parent": "Notes"});
+        let err = call_text(&ctx, "create_block", args).await.unwrap_err();
+        assert!(
+            err.message.contains("limit of 20 bytes per block"),
+            "{}",
+            err.message
+        );
+
+        // Each block fits, but not all of them on one page
+        let markdown = "- First point here\n- Second point here\n";
+        let args = json!({"page": "Runaway", "markdown": markdown});
+        let err = call_text(&ctx, "import_markdown", args).await.unwrap_err();
+        assert!(err.message.contains("bytes per page"), "{}", err.message);
+        logseq.with_graph(|g| {
+            assert!(!g.has_page("Runaway"));
+            assert_eq!(g.children("Notes").len(), 1);
+        });
+    }
+
+    #[tokio::test]
+    async fn test_errors_say_what_went_wrong() {
+        let mut graph = MockGraph::new();
+        graph.add_page("Projects");
+        let (logseq, ctx) = serve(graph).await;
+
+        let missing = "00000000-0000-0000-0000-000000000000";
+        let err = call_text(&ctx, "get_block", json!({"uuid": missing}))
+            .await
+            .unwrap_err();
+        assert_eq!(err.code, ErrorCode::INVALID_PARAMS);
+        let data = err.data.unwrap();
+        assert_eq!(data["error"], "not_found");
+        assert_eq!(data["kind"], "block");
+        assert_eq!(data["name"], missing);
+        let err = call_text(&ctx, "get_page", json!({"name_or_uuid": "Nowhere"}))
+            .await
+            .unwrap_err();
+        assert_eq!(err.data.unwrap()["kind"], "page");
+
+        let ctx = ToolContext::new(LogSeqClient::new(logseq.url(), "wrong-token").unwrap());
+        let err = call_text(&ctx, "get_page", json!({"name_or_uuid": "Projects"}))
+            .await
+            .unwrap_err();
+        assert_eq!(err.code, ErrorCode::INTERNAL_ERROR);
+        assert!(
+            err.message.contains("rejected the API token"),
+            "{}",
+            err.message
+        );
+        let data = err.data.unwrap();
+        assert_eq!(data["error"], "unauthorized");
+        assert_eq!(data["retryable"], false);
+    }
+
+    #[tokio::test]
+    async fn test_namespace_confines_changes() {
+        let mut graph = MockGraph::new();
+        graph.add_page("Journal");
+        let outside = graph.add_block("Journal", "Learning #rust today");
+        graph.add_page("AI/Scratch");
+        let inside = graph.add_block("AI/Scratch", "Drafting #rust notes");
+        let (logseq, mut ctx) = serve(graph).await;
+        ctx.namespace = Some(Namespace::new("AI").unwrap());
+
+        let text = call_text(&ctx, "create_page", json!({"name": "Notes"}))
+            .await
+            .unwrap();
+        assert!(text.ends_with("in the AI/ namespace"), "{}", text);
+        assert!(logseq.with_graph(|g| g.has_page("AI/Notes")));
+
+        let args = json!({"uuid": outside, "content": "Rewritten"});
+        let err = call_text(&ctx, "update_block", args).await.unwrap_err();
+        assert_eq!(err.code, ErrorCode::INVALID_REQUEST);
+        let data = err.data.unwrap();
+        assert_eq!(data["error"], "outside_namespace");
+        assert_eq!(data["namespace"], "AI/");
+        assert_eq!(data["page"], "journal");
+        let args = json!({"parent": "Journal", "content": "New"});
+        assert!(call_text(&ctx, "create_block", args).await.is_err());
+
+        let args = json!({"uuid": inside, "content": "Rewritten"});
+        call_text(&ctx, "update_block", args).await.unwrap();
+        let args = json!({"parent": "AI/Scratch", "content": "More #rust"});
+        call_text(&ctx, "create_block", args).await.unwrap();
+
+        let args = json!({"from": "rust", "to": "rustlang"});
+        let text = call_text(&ctx, "rename_tag", args).await.unwrap();
+        assert!(text.contains("in 1 block(s) on pages in AI/"), "{}", text);
+        logseq.with_graph(|g| {
+            assert_eq!(g.block_content(&outside), Some("Learning #rust today"));
+        });
+    }
+
+    #[tokio::test]
+    async fn test_workspaces_scope_search_pages_and_captures() {
+        let mut graph = MockGraph::new();
+        graph.add_page("Projects/Alpha");
+        graph.add_page("Projects/Alpha/Notes");
+        graph.add_block("Projects/Alpha/Notes", "Launch checklist");
+        graph.add_page("Projects/Beta");
+        graph.add_block("Projects/Beta", "Launch party");
+        let (logseq, mut ctx) = serve(graph).await;
+        ctx.workspaces = Arc::new([Workspace::parse(
+            r#"alpha {"namespace": "Projects/Alpha", "tags": ["alpha"], "heading": "Alpha"}"#,
+        )
+        .unwrap()]);
+
+        let text = call_text(&ctx, "search", json!({"query": "launch"}))
+            .await
+            .unwrap();
+        assert!(text.contains("Projects/Beta"), "{}", text);
+        let text = call_text(
+            &ctx,
+            "search",
+            json!({"query": "launch", "workspace": "alpha"}),
+        )
+        .await
+        .unwrap();
+        assert!(text.contains("Projects/Alpha/Notes"));
+        assert!(!text.contains("Projects/Beta"), "{}", text);
+
+        let session = ctx.for_session(SessionConfig {
+            workspace: Some("alpha".to_string()),
+            ..Default::default()
+        });
+        for content in ["Send the budget", "Book the venue"] {
+            call_text(
+                &session,
+                "capture",
+                json!({"content": content, "task": true}),
+            )
+            .await
+            .unwrap();
+        }
+        let date_format = ctx.cache.preferred_date_format().await.unwrap();
+        let today = format_journal_title(chrono::Local::now().date_naive(), &date_format);
+        let tree = ctx.client.get_page_blocks_tree(&today).await.unwrap();
+        let heading = tree.items.iter().find(|b| b.content == "Alpha").unwrap();
+        let captured: Vec<&str> = heading
+            .children
+            .iter()
+            .map(|b| b.content.as_str())
+            .collect();
+        assert_eq!(
+            captured,
+            ["TODO Send the budget #alpha", "TODO Book the venue #alpha"]
+        );
+
+        let text = call_text(&session, "create_page", json!({"name": "Retro"}))
+            .await
+            .unwrap();
+        assert!(text.contains("in workspace alpha"), "{}", text);
+        assert!(logseq.with_graph(|g| g.has_page("Projects/Alpha/Retro")));
+
+        let error = call_text(
+            &ctx,
+            "capture",
+            json!({"content": "x", "workspace": "gamma"}),
+        )
+        .await
+        .unwrap_err();
+        assert!(error.message.contains("configured workspaces: alpha"));
+    }
+}
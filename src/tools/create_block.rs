@@ -0,0 +1,87 @@
+//! The `create_block` tool
+
+use super::context::{ToolContext, logseq_error, text_result};
+use super::registry::McpTool;
+use crate::logseq::api::InsertBlockOptions;
+use rmcp::{ErrorData as McpError, model::CallToolResult};
+use schemars::JsonSchema;
+use serde::Deserialize;
+use serde_json::{Value, json};
+
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct CreateBlockArgs {
+    /// Block content in markdown format. Can include text, links, formatting, and LogSeq-specific syntax.
+    pub content: String,
+    /// Parent page name or block UUID where this block should be created. If not specified, block will be created on the current page.
+    pub parent: Option<String>,
+    /// Block UUID of an existing block. The new block will be inserted as a sibling at the same level.
+    pub sibling: Option<String>,
+}
+
+pub struct CreateBlock;
+
+impl McpTool for CreateBlock {
+    type Args = CreateBlockArgs;
+
+    fn name(&self) -> &'static str {
+        "create_block"
+    }
+
+    fn description(&self) -> &'static str {
+        "Insert a new block into LogSeq. You can specify a parent page/block or insert relative to a sibling block. Returns the created block's UUID. To turn a whole markdown document into a page, use import_markdown instead."
+    }
+
+    fn examples(&self) -> Vec<Value> {
+        vec![
+            json!({"parent": "Projects", "content": "TODO Draft the launch plan"}),
+            json!({"parent": "Projects", "content": "Budget notes\nowner:: [[Sam]]"}),
+        ]
+    }
+
+    fn destructive(&self) -> bool {
+        false
+    }
+
+    async fn execute(
+        &self,
+        ctx: &ToolContext,
+        args: CreateBlockArgs,
+    ) -> Result<CallToolResult, McpError> {
+        let CreateBlockArgs {
+            content,
+            parent,
+            sibling,
+        } = args;
+
+        for target in [&parent, &sibling].into_iter().flatten() {
+            ctx.require_writable(target).await?;
+        }
+        if parent.is_none() && sibling.is_none() && ctx.namespace.is_some() {
+            // Without a target the block goes on the page open in LogSeq
+            let current = ctx.client.get_current_page().await.map_err(logseq_error)?;
+            ctx.require_writable_page(&current.name)?;
+        }
+
+        let page = parent
+            .as_deref()
+            .filter(|p| uuid::Uuid::parse_str(p.trim()).is_err());
+        ctx.charge_write(page, &content)?;
+        let opts = InsertBlockOptions {
+            parent,
+            sibling,
+            ..Default::default()
+        };
+
+        let block = ctx
+            .client
+            .insert_block(&content, opts)
+            .await
+            .map_err(logseq_error)?;
+
+        Ok(text_result(format!(
+            "Created block with UUID: {}",
+            block.uuid
+        )))
+    }
+}
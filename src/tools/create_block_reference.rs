@@ -0,0 +1,141 @@
+//! The `create_block_reference` tool
+
+use super::context::{ToolContext, logseq_error, text_result};
+use super::quote_block::ensure_block_id;
+use super::registry::McpTool;
+use crate::logseq::api::InsertBlockOptions;
+use crate::logseq::content::block_ref;
+use rmcp::{ErrorData as McpError, model::CallToolResult};
+use schemars::JsonSchema;
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct CreateBlockReferenceArgs {
+    /// The UUID of the block to reference.
+    pub source_uuid: String,
+    /// Page name or block UUID to add the reference to. It goes at the end of the page, or as the block's last child.
+    pub target: String,
+    /// Embed the block, showing it and its children in place, instead of referencing it inline. Defaults to false.
+    pub embed: Option<bool>,
+    /// Text to put before the reference in the new block, such as 'See also'.
+    pub text: Option<String>,
+}
+
+pub struct CreateBlockReference;
+
+impl McpTool for CreateBlockReference {
+    type Args = CreateBlockReferenceArgs;
+
+    fn name(&self) -> &'static str {
+        "create_block_reference"
+    }
+
+    fn description(&self) -> &'static str {
+        "Add a block that references another block, as '((uuid))', or embeds it, as '{{embed ((uuid))}}', optionally after some text. The referenced block gets an 'id::' property so the reference keeps working. The result gives the reference syntax, which can also be used in content passed to other tools. Use get_block_referencing_blocks to find where a block is already referenced."
+    }
+
+    fn destructive(&self) -> bool {
+        false
+    }
+
+    async fn execute(
+        &self,
+        ctx: &ToolContext,
+        args: CreateBlockReferenceArgs,
+    ) -> Result<CallToolResult, McpError> {
+        let CreateBlockReferenceArgs {
+            source_uuid,
+            target,
+            embed,
+            text,
+        } = args;
+        let target = target.trim();
+        ctx.require_writable(target).await?;
+
+        let source = ctx
+            .client
+            .get_block(source_uuid.trim())
+            .await
+            .map_err(logseq_error)?;
+        let target_page = match uuid::Uuid::parse_str(target) {
+            Ok(_) => {
+                ctx.client.get_block(target).await.map_err(logseq_error)?;
+                None
+            }
+            Err(_) => {
+                ctx.require_page(target).await?;
+                Some(target)
+            }
+        };
+
+        let reference = block_ref(&source.uuid, embed.unwrap_or(false));
+        let content = match text.as_deref().map(str::trim) {
+            Some(text) if !text.is_empty() => format!("{} {}", text, reference),
+            _ => reference.clone(),
+        };
+        ctx.charge_write(target_page, &content)?;
+        ensure_block_id(ctx, &source).await?;
+
+        let opts = InsertBlockOptions {
+            parent: Some(target.to_string()),
+            ..Default::default()
+        };
+        let block = ctx
+            .client
+            .insert_block(&content, opts)
+            .await
+            .map_err(logseq_error)?;
+
+        Ok(text_result(format!(
+            "Added {} to {} as block {}",
+            reference, target, block.uuid
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::logseq::mock::MockGraph;
+    use crate::tools::testing::{call_text, serve};
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_block_references_are_created_under_the_target() {
+        let mut graph = MockGraph::new();
+        graph.add_page("Ideas");
+        let source = graph.add_block("Ideas", "Ship smaller changes");
+        graph.add_page("Weekly");
+        let (logseq, ctx) = serve(graph).await;
+
+        let args = json!({"source_uuid": source, "target": "Weekly", "text": "See"});
+        let text = call_text(&ctx, "create_block_reference", args)
+            .await
+            .unwrap();
+        assert!(text.contains(&format!("(({}))", source)), "{}", text);
+        let args = json!({"source_uuid": source, "target": "Weekly", "embed": true});
+        call_text(&ctx, "create_block_reference", args)
+            .await
+            .unwrap();
+
+        let added: Vec<String> = logseq.with_graph(|g| {
+            let original = g.block_content(&source).unwrap();
+            assert!(
+                original.contains(&format!("id:: {}", source)),
+                "{}",
+                original
+            );
+            g.children("Weekly")
+                .iter()
+                .map(|uuid| g.block_content(uuid).unwrap().to_string())
+                .collect()
+        });
+        assert_eq!(
+            added,
+            vec![
+                format!("See (({}))", source),
+                format!("{{{{embed (({}))}}}}", source)
+            ]
+        );
+    }
+}
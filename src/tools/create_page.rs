@@ -0,0 +1,427 @@
+//! The `create_page` tool
+
+use super::context::{ToolContext, logseq_error, text_result};
+use super::import_markdown::count;
+use super::registry::McpTool;
+use crate::intents::InverseOp;
+use crate::logseq::api::{CreatePageOptions, PageFormat};
+use crate::logseq::journal::{DEFAULT_DATE_FORMAT, detect_journal_date, format_journal_title};
+use crate::logseq::markdown::{BatchBlock, parse_document};
+use crate::logseq::page_name::normalize_page_name;
+use crate::quota::QuotaKind;
+use rmcp::{ErrorData as McpError, model::CallToolResult};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value, json};
+use std::collections::HashMap;
+use std::time::Duration;
+
+/// How many times to look for a new page before writing its blocks
+const PAGE_LOOKUPS: u32 = 5;
+
+/// The wait before looking for a new page again, doubled each time
+const PAGE_LOOKUP_DELAY: Duration = Duration::from_millis(50);
+
+/// Optional page properties. Common properties include: 'tags' (array of strings), 'template' (string), 'alias' (array of strings), 'public' (boolean), 'filters' (object), and any custom properties you want to associate with the page.
+#[derive(Debug, Default, Serialize, Deserialize, JsonSchema)]
+pub struct PageProperties {
+    /// Tags to apply to the page
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tags: Option<Vec<String>>,
+    /// Template to use for the page
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub template: Option<String>,
+    /// Alternative names for the page
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub alias: Option<Vec<String>>,
+    /// Whether the page should be public
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub public: Option<bool>,
+    /// Filters to apply to the page view
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub filters: Option<Map<String, Value>>,
+    #[serde(flatten)]
+    pub custom: HashMap<String, Value>,
+}
+
+impl PageProperties {
+    /// Flatten into the property map expected by the LogSeq API
+    pub fn into_map(self) -> HashMap<String, Value> {
+        match serde_json::to_value(self) {
+            Ok(Value::Object(map)) => map.into_iter().collect(),
+            _ => HashMap::new(),
+        }
+    }
+}
+
+/// The first blocks of a new page
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(untagged)]
+pub enum PageContent {
+    /// A markdown document, split into blocks as import_markdown does
+    Markdown(String),
+    /// Blocks, each with any children nested under it
+    Blocks(Vec<ContentBlock>),
+}
+
+/// A block to write, with the blocks nested under it
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct ContentBlock {
+    pub content: String,
+    #[serde(default)]
+    pub children: Vec<ContentBlock>,
+}
+
+impl From<ContentBlock> for BatchBlock {
+    fn from(block: ContentBlock) -> Self {
+        Self {
+            content: block.content,
+            children: block.children.into_iter().map(BatchBlock::from).collect(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct CreatePageArgs {
+    /// The name of the new page. Use '/' to create namespaced pages (e.g. 'Projects/Alpha'). Surrounding whitespace and empty namespace levels are removed; names containing brackets, braces, or control characters, starting with '#' or '.', or matching LogSeq built-in pages (e.g. 'TODO', 'Contents') are rejected. Names that look like a date (in the user's journal title format or ISO 'yyyy-MM-dd') create the corresponding journal page instead of a regular page.
+    pub name: String,
+    pub properties: Option<PageProperties>,
+    /// The page's first blocks: a markdown document, split into blocks as import_markdown does, or a list of blocks such as [{'content': 'Goals', 'children': [{'content': 'Ship by March'}]}]. They're written once LogSeq has the page ready. The page must not already exist.
+    pub content: Option<PageContent>,
+    /// Create a journal page. Names that look like a date create one anyway; true makes a name that doesn't an error.
+    pub journal: Option<bool>,
+    /// Write the page's file as 'markdown' or 'org'. Defaults to the user's preferred format.
+    pub format: Option<PageFormat>,
+    /// Open the new page in LogSeq. Defaults to false for journal pages and to LogSeq's default, opening it, otherwise.
+    pub redirect: Option<bool>,
+    /// Create the page in this workspace's namespace with its tags, instead of the session's workspace.
+    pub workspace: Option<String>,
+}
+
+pub struct CreatePage;
+
+impl McpTool for CreatePage {
+    type Args = CreatePageArgs;
+
+    fn name(&self) -> &'static str {
+        "create_page"
+    }
+
+    fn description(&self) -> &'static str {
+        "Create a new page in LogSeq. You can optionally specify page properties like tags, template, aliases, and custom properties, and its first blocks as markdown or a list of blocks, written as soon as LogSeq has the page ready. The journal, format, and redirect options are passed to LogSeq's createPage."
+    }
+
+    fn examples(&self) -> Vec<Value> {
+        vec![
+            json!({
+                "name": "Projects/Apollo",
+                "properties": {"tags": ["project"], "status": "active"}
+            }),
+            json!({
+                "name": "Launch Plan",
+                "content": "# Goals\n- Ship by March\n\n# Risks\nSupplier delays.",
+                "redirect": false
+            }),
+        ]
+    }
+
+    fn destructive(&self) -> bool {
+        false
+    }
+
+    async fn execute(
+        &self,
+        ctx: &ToolContext,
+        args: CreatePageArgs,
+    ) -> Result<CallToolResult, McpError> {
+        let CreatePageArgs {
+            name,
+            properties,
+            content,
+            journal,
+            format,
+            redirect,
+            workspace,
+        } = args;
+        let mut properties = properties.map(PageProperties::into_map);
+        let blocks: Vec<BatchBlock> = match content {
+            Some(PageContent::Markdown(markdown)) => parse_document(&markdown),
+            Some(PageContent::Blocks(blocks)) => blocks.into_iter().map(BatchBlock::from).collect(),
+            None => Vec::new(),
+        };
+        let workspace = ctx.workspace(workspace.as_deref())?;
+
+        let normalized = normalize_page_name(&name)
+            .map_err(|e| McpError::invalid_params(e.to_string(), None))?;
+        // Journal-shaped names must go through journal creation, otherwise
+        // LogSeq ends up with a regular page shadowing the real journal day
+        let date_format = ctx
+            .cache
+            .preferred_date_format()
+            .await
+            .unwrap_or_else(|_| DEFAULT_DATE_FORMAT.to_string());
+        // Journal days stay journal days whatever the workspace
+        let workspace =
+            workspace.filter(|_| detect_journal_date(&normalized, &date_format).is_none());
+        let scoped = match workspace {
+            Some(workspace) => {
+                add_tags(properties.get_or_insert_with(HashMap::new), &workspace.tags);
+                workspace.page_name(&normalized)
+            }
+            None => normalized.clone(),
+        };
+        let name_in_namespace = ctx.page_to_create(&scoped);
+
+        let journal_date = detect_journal_date(&name_in_namespace, &date_format);
+        match (journal, journal_date) {
+            (Some(true), None) => {
+                return Err(McpError::invalid_params(
+                    format!(
+                        "'{}' isn't a date, so it can't be a journal page; name it like '{}'",
+                        name,
+                        format_journal_title(chrono::Local::now().date_naive(), &date_format)
+                    ),
+                    Some(json!({ "name": name })),
+                ));
+            }
+            (Some(false), Some(_)) => {
+                return Err(McpError::invalid_params(
+                    format!(
+                        "'{}' is a date, which always names a journal page; leave out 'journal' or choose another name",
+                        name
+                    ),
+                    Some(json!({ "name": name })),
+                ));
+            }
+            _ => {}
+        }
+        let (title, opts) = match journal_date {
+            Some(date) => (
+                format_journal_title(date, &date_format),
+                CreatePageOptions {
+                    journal: Some(true),
+                    redirect: Some(redirect.unwrap_or(false)),
+                    format,
+                },
+            ),
+            None => (
+                name_in_namespace.clone(),
+                CreatePageOptions {
+                    journal: None,
+                    redirect,
+                    format,
+                },
+            ),
+        };
+        if !blocks.is_empty() {
+            ctx.charge_blocks(Some(&title), &blocks)?;
+        }
+        let _guard = ctx.page_locks.lock(&title).await;
+        // Blocks go on a new page, not after what's on an existing one
+        if !blocks.is_empty()
+            && ctx
+                .client
+                .find_page(&title)
+                .await
+                .map_err(logseq_error)?
+                .is_some()
+        {
+            return Err(McpError::invalid_params(
+                format!(
+                    "A page named '{}' already exists; add blocks to it with append_block_to_page",
+                    title
+                ),
+                Some(json!({ "page": title })),
+            ));
+        }
+
+        ctx.charge_quota(QuotaKind::PagesCreated, 1)?;
+        let intent = match blocks.is_empty() {
+            true => None,
+            false => Some(ctx.begin_intent(
+                self.name(),
+                vec![
+                    format!("create page '{}'", title),
+                    format!("write {} block(s) to page '{}'", count(&blocks), title),
+                ],
+            )?),
+        };
+        let page = ctx
+            .client
+            .create_page(&title, properties, opts)
+            .await
+            .map_err(logseq_error)?;
+        ctx.cache.invalidate_pages();
+        if let Some(mut intent) = intent {
+            intent.done(
+                0,
+                vec![InverseOp::DeletePage {
+                    name: title.clone(),
+                }],
+            );
+            wait_for_page(ctx, &title).await;
+            ctx.client
+                .append_batch_block(&title, &blocks)
+                .await
+                .map_err(|e| {
+                    let mut error = logseq_error(e);
+                    let data = error.data.get_or_insert_with(|| json!({}));
+                    data["context"] =
+                        format!("Created page '{}' but failed to write its blocks", title).into();
+                    error
+                })?;
+            // Deleting the page undoes this too
+            intent.done(1, Vec::new());
+            intent.finish();
+        }
+
+        let mut text = match journal_date {
+            Some(date) => format!(
+                "Created journal page: {} (detected journal date {})",
+                page.original_name.as_deref().unwrap_or(&page.name),
+                date.format("%Y-%m-%d")
+            ),
+            None => format!("Created page: {}", page.name),
+        };
+        if !blocks.is_empty() {
+            text.push_str(&format!(" with {} block(s)", count(&blocks)));
+        }
+        if title != name_in_namespace || normalized != name {
+            text.push_str(&format!(" (name normalized from {:?})", name));
+        }
+        if let Some(workspace) = workspace {
+            text.push_str(&format!(" in workspace {}", workspace.name));
+        }
+        if let Some(namespace) = ctx
+            .namespace
+            .as_ref()
+            .filter(|_| name_in_namespace != scoped)
+        {
+            text.push_str(&format!(" in the {} namespace", namespace.prefix()));
+        }
+
+        Ok(text_result(text))
+    }
+}
+
+/// Wait until LogSeq can find a page it has just created, as blocks
+/// written to it before then can go missing. Gives up after a few tries,
+/// leaving writing the blocks to fail if the page still isn't there.
+async fn wait_for_page(ctx: &ToolContext, title: &str) {
+    let mut delay = PAGE_LOOKUP_DELAY;
+    for _ in 0..PAGE_LOOKUPS {
+        if let Ok(Some(_)) = ctx.client.find_page(title).await {
+            return;
+        }
+        tokio::time::sleep(delay).await;
+        delay *= 2;
+    }
+    tracing::debug!(
+        "Page '{}' still can't be found; writing its blocks anyway",
+        title
+    );
+}
+
+/// Add `tags` to the page's `tags` property, unless they're there already
+/// or it's been given as something other than a list
+fn add_tags(properties: &mut HashMap<String, Value>, tags: &[String]) {
+    if tags.is_empty() {
+        return;
+    }
+    let entry = properties
+        .entry("tags".to_string())
+        .or_insert_with(|| Value::Array(Vec::new()));
+    let Value::Array(given) = entry else {
+        return;
+    };
+    for tag in tags {
+        let present = given.iter().any(|given| {
+            given
+                .as_str()
+                .is_some_and(|given| given.trim_start_matches('#').eq_ignore_ascii_case(tag))
+        });
+        if !present {
+            given.push(Value::String(tag.clone()));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::logseq::mock::MockGraph;
+    use crate::tools::testing::{call_text, serve};
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_create_page_with_content() {
+        let mut graph = MockGraph::new();
+        graph.add_page("Existing");
+        let (logseq, ctx) = serve(graph).await;
+
+        let args = json!({
+            "name": "Launch Plan",
+            "content": "# Goals\n- Ship by March\n\n# Risks\nSupplier delays.",
+            "format": "org",
+            "redirect": false
+        });
+        let text = call_text(&ctx, "create_page", args).await.unwrap();
+        assert_eq!(text, "Created page: launch plan with 4 block(s)");
+        let create = logseq
+            .calls()
+            .into_iter()
+            .find(|call| call.method == "logseq.Editor.createPage")
+            .unwrap();
+        assert_eq!(create.args[2], json!({"redirect": false, "format": "org"}));
+        logseq.with_graph(|g| {
+            let sections = g.children("Launch Plan");
+            assert_eq!(sections.len(), 2);
+            assert_eq!(g.block_content(&sections[0]), Some("# Goals"));
+            let goals = g.children(&sections[0]);
+            assert_eq!(g.block_content(&goals[0]), Some("Ship by March"));
+        });
+
+        let args = json!({
+            "name": "Reading",
+            "content": [
+                {"content": "Books", "children": [{"content": "Dune"}]},
+                {"content": "Papers"}
+            ]
+        });
+        let text = call_text(&ctx, "create_page", args).await.unwrap();
+        assert!(text.ends_with("with 3 block(s)"), "{}", text);
+        logseq.with_graph(|g| {
+            let blocks = g.children("Reading");
+            assert_eq!(g.block_content(&blocks[1]), Some("Papers"));
+            assert_eq!(g.children(&blocks[0]).len(), 1);
+        });
+
+        // Content is only ever written to a new page
+        let args = json!({"name": "Existing", "content": "More"});
+        let err = call_text(&ctx, "create_page", args).await.unwrap_err();
+        assert!(err.message.contains("already exists"), "{}", err.message);
+        assert!(logseq.with_graph(|g| g.children("Existing").is_empty()));
+
+        let args = json!({"name": "Someday", "journal": true});
+        let err = call_text(&ctx, "create_page", args).await.unwrap_err();
+        assert!(err.message.contains("isn't a date"), "{}", err.message);
+        assert!(!logseq.with_graph(|g| g.has_page("Someday")));
+
+        logseq.fail("logseq.Editor.insertBlock", 500, "database is locked");
+        logseq.fail("logseq.Editor.appendBlockInPage", 500, "database is locked");
+        let args = json!({"name": "Drafts", "content": "First thoughts"});
+        let err = call_text(&ctx, "create_page", args).await.unwrap_err();
+        assert!(
+            err.message.contains("database is locked"),
+            "{}",
+            err.message
+        );
+        let data = err.data.unwrap();
+        assert_eq!(data["status"], 500);
+        assert_eq!(
+            data["context"],
+            "Created page 'Drafts' but failed to write its blocks"
+        );
+    }
+}
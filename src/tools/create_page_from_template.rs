@@ -0,0 +1,252 @@
+//! The `create_page_from_template` tool
+
+use super::context::{ToolContext, internal_error, logseq_error, text_result};
+use super::create_page::PageProperties;
+use super::registry::McpTool;
+use crate::intents::InverseOp;
+use crate::logseq::api::{Block, CreatePageOptions};
+use crate::logseq::content::{fill_template, is_property_line};
+use crate::logseq::markdown::BatchBlock;
+use crate::logseq::page_name::normalize_page_name;
+use crate::quota::QuotaKind;
+use crate::tools::{count_blocks, format_skipped_entries, is_properties_block};
+use rmcp::{ErrorData as McpError, model::CallToolResult};
+use schemars::JsonSchema;
+use serde::Deserialize;
+use serde_json::{Value, json};
+use std::collections::HashMap;
+
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct CreatePageFromTemplateArgs {
+    /// The name of the page to copy blocks from.
+    pub template: String,
+    /// The name of the new page. It must not already exist; the same naming rules as create_page apply.
+    pub name: String,
+    /// Values for the template's '{{variable}}' placeholders, e.g. {"project": "Apollo"}. Non-string values are inserted as JSON. Placeholders without a value are left as they are.
+    pub variables: Option<HashMap<String, Value>>,
+    pub properties: Option<PageProperties>,
+}
+
+pub struct CreatePageFromTemplate;
+
+impl McpTool for CreatePageFromTemplate {
+    type Args = CreatePageFromTemplateArgs;
+
+    fn name(&self) -> &'static str {
+        "create_page_from_template"
+    }
+
+    fn description(&self) -> &'static str {
+        "Create a new page from a template page: the template's blocks, with their nesting, are copied into the new page after replacing '{{variable}}' placeholders with the given values. The template page's own properties (such as 'template::') are not copied; pass 'properties' to set the new page's."
+    }
+
+    fn examples(&self) -> Vec<Value> {
+        vec![json!({
+            "template": "Templates/Meeting",
+            "name": "Meetings/Launch Review",
+            "variables": {"topic": "Launch"}
+        })]
+    }
+
+    fn destructive(&self) -> bool {
+        false
+    }
+
+    async fn execute(
+        &self,
+        ctx: &ToolContext,
+        args: CreatePageFromTemplateArgs,
+    ) -> Result<CallToolResult, McpError> {
+        let CreatePageFromTemplateArgs {
+            template,
+            name,
+            variables,
+            properties,
+        } = args;
+        let variables: HashMap<String, String> = variables
+            .unwrap_or_default()
+            .into_iter()
+            .map(|(key, value)| match value {
+                Value::String(s) => (key, s),
+                other => (key, other.to_string()),
+            })
+            .collect();
+
+        ctx.require_page(&template).await?;
+        let title = normalize_page_name(&name)
+            .map_err(|e| McpError::invalid_params(e.to_string(), None))?;
+        let title = ctx.page_to_create(&title);
+        let _guard = ctx.page_locks.lock(&title).await;
+        if ctx
+            .client
+            .find_page(&title)
+            .await
+            .map_err(logseq_error)?
+            .is_some()
+        {
+            return Err(McpError::invalid_params(
+                format!("A page named '{}' already exists", title),
+                Some(serde_json::json!({ "page": title })),
+            ));
+        }
+
+        let tree = ctx
+            .client
+            .get_page_blocks_tree(&template)
+            .await
+            .map_err(logseq_error)?;
+        let blocks = match tree.items.split_first() {
+            // The template page's own properties, e.g. `template:: meeting`
+            Some((first, rest)) if is_properties_block(first) => rest,
+            _ => &tree.items[..],
+        };
+        let mut unfilled = Vec::new();
+        let batch: Vec<BatchBlock> = blocks
+            .iter()
+            .map(|block| instantiate(block, &variables, &mut unfilled))
+            .collect();
+        ctx.charge_blocks(Some(&title), &batch)?;
+        ctx.charge_quota(QuotaKind::PagesCreated, 1)?;
+
+        let mut intent = ctx.begin_intent(
+            self.name(),
+            vec![
+                format!("create page '{}'", title),
+                format!(
+                    "copy {} block(s) from template '{}'",
+                    count_blocks(blocks),
+                    template
+                ),
+            ],
+        )?;
+        let page = ctx
+            .client
+            .create_page(
+                &title,
+                properties.map(PageProperties::into_map),
+                CreatePageOptions::default(),
+            )
+            .await
+            .map_err(logseq_error)?;
+        ctx.cache.invalidate_pages();
+        intent.done(
+            0,
+            vec![InverseOp::DeletePage {
+                name: title.clone(),
+            }],
+        );
+        ctx.client
+            .append_batch_block(&title, &batch)
+            .await
+            .map_err(|e| {
+                internal_error(format!(
+                    "Created page '{}' but failed to copy the template's blocks: {}",
+                    title, e
+                ))
+            })?;
+        // Deleting the page undoes this too
+        intent.done(1, Vec::new());
+        intent.finish();
+
+        let mut text = format!(
+            "Created page: {} from template '{}' with {} block(s)",
+            page.name,
+            template,
+            count_blocks(blocks)
+        );
+        if !unfilled.is_empty() {
+            let names: Vec<String> = unfilled.iter().map(|n| format!("{{{{{}}}}}", n)).collect();
+            text.push_str(&format!(
+                "\n\nPlaceholders left unfilled: {}",
+                names.join(", ")
+            ));
+        }
+        if !tree.warnings.is_empty() {
+            text.push_str("\n\nSome template blocks could not be read and were not copied:");
+            text.push_str(&format_skipped_entries(&tree.warnings));
+        }
+        Ok(text_result(text))
+    }
+}
+
+/// Copy a template block with its placeholders filled. `id::` properties
+/// are dropped, as the copies must not claim the template blocks' UUIDs.
+fn instantiate(
+    block: &Block,
+    variables: &HashMap<String, String>,
+    unfilled: &mut Vec<String>,
+) -> BatchBlock {
+    let content: Vec<&str> = block
+        .content
+        .lines()
+        .filter(|line| !(is_property_line(line) && line.trim_start().starts_with("id::")))
+        .collect();
+    let (content, missing) = fill_template(&content.join("\n"), variables);
+    for name in missing {
+        if !unfilled.contains(&name) {
+            unfilled.push(name);
+        }
+    }
+    BatchBlock {
+        content,
+        children: block
+            .children
+            .iter()
+            .map(|child| instantiate(child, variables, unfilled))
+            .collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::logseq::mock::MockGraph;
+    use crate::tools::testing::{call_text, serve};
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_page_is_created_from_template() {
+        let mut graph = MockGraph::new();
+        graph.add_page("Meeting Template");
+        graph.add_block("Meeting Template", "template:: meeting");
+        let agenda = graph.add_block(
+            "Meeting Template",
+            "Agenda for {{project}}\nid:: 6650a1b2-0000-4c1d-8e2f-123456789abc",
+        );
+        graph.add_block(&agenda, "Owner: {{ owner }}");
+        graph.add_block("Meeting Template", "{{query (todo now)}} for {{date}}");
+        let (logseq, ctx) = serve(graph).await;
+
+        let args = json!({
+            "template": "Meeting Template",
+            "name": "Apollo Sync",
+            "variables": {"project": "Apollo", "owner": "Sam"}
+        });
+        let text = call_text(&ctx, "create_page_from_template", args.clone())
+            .await
+            .unwrap();
+        assert!(text.contains("with 3 block(s)"), "{}", text);
+        assert!(
+            text.contains("Placeholders left unfilled: {{date}}"),
+            "{}",
+            text
+        );
+
+        logseq.with_graph(|g| {
+            let roots = g.children("Apollo Sync");
+            assert_eq!(roots.len(), 2);
+            assert_eq!(g.block_content(&roots[0]), Some("Agenda for Apollo"));
+            let child = &g.children(&roots[0])[0];
+            assert_eq!(g.block_content(child), Some("Owner: Sam"));
+            assert_eq!(
+                g.block_content(&roots[1]),
+                Some("{{query (todo now)}} for {{date}}")
+            );
+        });
+
+        let err = call_text(&ctx, "create_page_from_template", args)
+            .await
+            .unwrap_err();
+        assert!(err.message.contains("already exists"), "{}", err.message);
+    }
+}
@@ -0,0 +1,348 @@
+//! The `datascript_query` tool
+
+use super::context::{ToolContext, json_result, logseq_error, text_result};
+use super::registry::McpTool;
+use crate::logseq::datalog::{find_columns, full_scans};
+use crate::tools::{TableFormat, format_table};
+use rmcp::{
+    ErrorData as McpError,
+    model::{CallToolResult, Content},
+};
+use schemars::JsonSchema;
+use serde::Deserialize;
+use serde_json::{Value, json};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Queries taking longer than this are logged and flagged in their result
+/// unless the server is configured otherwise
+pub const DEFAULT_SLOW_QUERY: Duration = Duration::from_secs(2);
+
+/// Rows returned when no limit is given
+const DEFAULT_LIMIT: usize = 200;
+
+/// Size of the returned JSON when no maximum is given, about 12k tokens
+const DEFAULT_MAX_OUTPUT_BYTES: usize = 50_000;
+
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct DatascriptQueryArgs {
+    /// Datascript query string. Example: '[:find ?uuid ?content :where [?p :block/name "projects"] [?b :block/page ?p] [?b :block/uuid ?uuid] [?b :block/content ?content]]'. Requires knowledge of LogSeq's data schema.
+    pub query: String,
+    /// Values bound to the query's ':in' variables after '$', in order. Example: query '[:find ?b :in $ ?name :where [?p :block/name ?name] [?b :block/page ?p]]' with inputs ['"my page"']. LogSeq reads string inputs as EDN, so string values must be quoted ('"my page"'), while ':today' or ':-7d' resolve to journal days; numbers and booleans are passed as-is.
+    pub inputs: Option<Vec<Value>>,
+    /// Run the query even if an identical one (same query and inputs) was answered moments ago. Results are otherwise reused for a short time, until something is changed through this server.
+    pub refresh: Option<bool>,
+    /// Return at most this many result rows. Datascript has no limit clause, so the query still runs in full; rows beyond the limit are counted and left out. Defaults to 200.
+    #[schemars(range(min = 1))]
+    pub limit: Option<usize>,
+    /// Leave out further rows once the JSON result would exceed this many bytes. Defaults to 50000.
+    #[schemars(range(min = 1))]
+    pub max_output_bytes: Option<usize>,
+    /// How to return the rows: 'json' (default), or as a table with a header row in 'csv', 'tsv', or 'markdown-table' for spreadsheets and reports. Columns are named after the :find variables, and pulled entities get a column per attribute.
+    pub format: Option<TableFormat>,
+}
+
+pub struct DatascriptQuery;
+
+impl McpTool for DatascriptQuery {
+    type Args = DatascriptQueryArgs;
+
+    fn name(&self) -> &'static str {
+        "datascript_query"
+    }
+
+    fn description(&self) -> &'static str {
+        "Execute a Datascript query against the LogSeq database for advanced data retrieval. Use this for complex queries that other tools cannot handle. Pass values through 'inputs' and ':in' bindings rather than splicing them into the query string. Large results are cut to 'limit' rows (default 200) and 'max_output_bytes' (default 50000), with a note giving the full row count. Set 'format' to 'csv', 'tsv', or 'markdown-table' to get rows as a table for spreadsheets. Requires knowledge of Datascript syntax and LogSeq's data model."
+    }
+
+    fn examples(&self) -> Vec<Value> {
+        vec![
+            json!({
+                "query": "[:find ?content :in $ ?name :where [?p :block/name ?name] [?b :block/page ?p] [?b :block/content ?content]]",
+                "inputs": ["\"projects\""]
+            }),
+            json!({
+                "query": "[:find (pull ?b [:block/uuid :block/content]) :where [?b :block/marker \"TODO\"]]",
+                "limit": 20
+            }),
+        ]
+    }
+
+    fn read_only(&self) -> bool {
+        true
+    }
+
+    async fn execute(
+        &self,
+        ctx: &ToolContext,
+        args: DatascriptQueryArgs,
+    ) -> Result<CallToolResult, McpError> {
+        let DatascriptQueryArgs {
+            query,
+            inputs,
+            refresh,
+            limit,
+            max_output_bytes,
+            format,
+        } = args;
+        let inputs = inputs.unwrap_or_default();
+        let limit = limit.unwrap_or(DEFAULT_LIMIT).max(1);
+        let max_output_bytes = max_output_bytes.unwrap_or(DEFAULT_MAX_OUTPUT_BYTES).max(1);
+
+        if !inputs.is_empty() && !query.contains(":in") {
+            return Err(McpError::invalid_params(
+                "Query has inputs but no ':in' clause to bind them; add ':in $ ?var ...' before ':where'",
+                None,
+            ));
+        }
+
+        let mut notes = Vec::new();
+        let mut warnings = Vec::new();
+        let cached = match refresh {
+            Some(true) => None,
+            _ => ctx.queries.get(&query, &inputs),
+        };
+        let result = match cached {
+            Some((result, age)) => {
+                tracing::debug!("datascript query answered from cache ({:?} old)", age);
+                notes.push(format!(
+                    "Note: this result was cached {}s ago; pass refresh: true to rerun the query.",
+                    age.as_secs()
+                ));
+                result
+            }
+            None => {
+                let started = Instant::now();
+                let result = ctx
+                    .client
+                    .datascript_query(&query, &inputs)
+                    .await
+                    .map_err(logseq_error)?;
+                let elapsed = started.elapsed();
+                tracing::debug!("datascript query took {:?}", elapsed);
+                if elapsed >= ctx.slow_query {
+                    tracing::warn!("Slow datascript query ({:?}): {}", elapsed, query);
+                    warnings.push(format!(
+                        "This query took {:.1?}, and LogSeq is unresponsive while a query runs.",
+                        elapsed
+                    ));
+                }
+
+                let result = Arc::new(result);
+                ctx.queries.insert(&query, &inputs, result.clone());
+                result
+            }
+        };
+
+        let scans = full_scans(&query);
+        if !scans.is_empty() {
+            warnings.push(format!(
+                "{} {} over every block in the graph. Narrow the query with a constant (e.g. [?p :block/name \"page\"] [?b :block/page ?p]), an ':in' input, or an attribute only some blocks have (e.g. :block/marker).",
+                scans.join(", "),
+                if scans.len() == 1 { "ranges" } else { "range" }
+            ));
+        }
+
+        let rows = match truncate_rows(&result, limit, max_output_bytes) {
+            Some((rows, note)) => {
+                notes.push(note);
+                Arc::new(rows)
+            }
+            None => result,
+        };
+        let mut result = match format.unwrap_or_default() {
+            TableFormat::Json => json_result(&*rows, "result"),
+            format => text_result(format_table(&rows, &find_columns(&query), format)),
+        };
+        if !warnings.is_empty() {
+            notes.push(format!("Warning: {}", warnings.join(" ")));
+        }
+        if let Some(content) = result.content.as_mut() {
+            content.extend(notes.into_iter().map(Content::text));
+        }
+        Ok(result)
+    }
+}
+
+/// The first rows of a query result that fit within `limit` rows and
+/// `max_bytes` of JSON, with a note on what was left out, or `None` if the
+/// whole result fits
+fn truncate_rows(result: &Value, limit: usize, max_bytes: usize) -> Option<(Value, String)> {
+    let rows = result.as_array()?;
+    let total = rows.len();
+    let size = |n: usize| serde_json::to_string_pretty(&rows[..n]).map_or(0, |json| json.len());
+
+    let mut shown = total.min(limit);
+    let over_size = size(shown) > max_bytes;
+    if over_size {
+        // Bisect for the most rows that fit
+        let (mut fits, mut too_many) = (0, shown);
+        while too_many - fits > 1 {
+            let middle = (fits + too_many) / 2;
+            if size(middle) <= max_bytes {
+                fits = middle;
+            } else {
+                too_many = middle;
+            }
+        }
+        shown = fits;
+    }
+    if shown == total {
+        return None;
+    }
+
+    let reason = if over_size {
+        format!("to stay under max_output_bytes ({})", max_bytes)
+    } else {
+        format!("(limit {})", limit)
+    };
+    let note = format!(
+        "Note: showing {} of {} rows {}. Rows come in no particular order; narrow the query, :find fewer values, or raise {} to see more.",
+        shown,
+        total,
+        reason,
+        if over_size {
+            "max_output_bytes"
+        } else {
+            "limit"
+        }
+    );
+    Some((Value::Array(rows[..shown].to_vec()), note))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::logseq::mock::{MockGraph, MockLogSeq};
+    use crate::tools::context::ToolContext;
+    use crate::tools::testing::{call_text, serve};
+    use serde_json::{Value, json};
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_datascript_query_warns_about_expensive_queries() {
+        let logseq = MockLogSeq::start().await.unwrap();
+        let mut ctx = ToolContext::new(logseq.client());
+
+        let narrow = r#"[:find ?c :where [?p :block/name "inbox"] [?b :block/page ?p] [?b :block/content ?c]]"#;
+        let text = call_text(&ctx, "datascript_query", json!({"query": narrow}))
+            .await
+            .unwrap();
+        assert!(!text.contains("Warning"), "{}", text);
+
+        let scan = "[:find ?c :where [?b :block/content ?c]]";
+        let text = call_text(&ctx, "datascript_query", json!({"query": scan}))
+            .await
+            .unwrap();
+        assert!(
+            text.contains("Warning: ?b ranges over every block"),
+            "{}",
+            text
+        );
+        assert!(!text.contains("This query took"), "{}", text);
+
+        ctx.slow_query = Duration::ZERO;
+        let args = json!({"query": narrow, "refresh": true});
+        let text = call_text(&ctx, "datascript_query", args).await.unwrap();
+        assert!(text.contains("Warning: This query took"), "{}", text);
+    }
+
+    #[tokio::test]
+    async fn test_datascript_results_are_cached_until_a_write() {
+        let mut graph = MockGraph::new();
+        graph.add_page("Inbox");
+        let (logseq, ctx) = serve(graph).await;
+        let query_calls = || {
+            logseq
+                .calls()
+                .iter()
+                .filter(|c| c.method == "logseq.DB.datascriptQuery")
+                .count()
+        };
+
+        let args =
+            json!({"query": "[:find ?c :where [?b :block/marker ?m] [?b :block/content ?c]]"});
+        call_text(&ctx, "datascript_query", args.clone())
+            .await
+            .unwrap();
+        let text = call_text(&ctx, "datascript_query", args.clone())
+            .await
+            .unwrap();
+        assert!(text.contains("this result was cached"), "{}", text);
+        assert_eq!(query_calls(), 1);
+
+        call_text(
+            &ctx,
+            "create_block",
+            json!({"content": "TODO New task", "parent": "Inbox"}),
+        )
+        .await
+        .unwrap();
+        let text = call_text(&ctx, "datascript_query", args).await.unwrap();
+        assert!(text.contains("New task"), "{}", text);
+        assert_eq!(query_calls(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_datascript_results_are_truncated() {
+        let logseq = MockLogSeq::start().await.unwrap();
+        let rows: Vec<Value> = (0..500)
+            .map(|i| json!([format!("Block number {}", i)]))
+            .collect();
+        logseq.respond("logseq.DB.datascriptQuery", json!(rows));
+        let ctx = ToolContext::new(logseq.client());
+        let query = "[:find ?c :where [?b :block/content ?c]]";
+        let shown = |text: &str| {
+            let json = text.split("Note:").next().unwrap();
+            serde_json::from_str::<Vec<Value>>(json).unwrap().len()
+        };
+
+        let text = call_text(&ctx, "datascript_query", json!({"query": query}))
+            .await
+            .unwrap();
+        assert_eq!(shown(&text), 200);
+        assert!(
+            text.contains("Note: showing 200 of 500 rows (limit 200)."),
+            "{}",
+            text
+        );
+
+        let args = json!({"query": query, "limit": 1000, "max_output_bytes": 1000});
+        let text = call_text(&ctx, "datascript_query", args).await.unwrap();
+        let json_len = text.split("Note:").next().unwrap().len();
+        assert!(json_len <= 1000, "{}", text);
+        assert!(
+            text.contains(&format!(
+                "Note: showing {} of 500 rows to stay under max_output_bytes (1000).",
+                shown(&text)
+            )),
+            "{}",
+            text
+        );
+
+        let args = json!({"query": query, "limit": 500});
+        let text = call_text(&ctx, "datascript_query", args).await.unwrap();
+        assert!(!text.contains("Note: showing"), "{}", text);
+    }
+
+    #[tokio::test]
+    async fn test_datascript_results_can_be_tables() {
+        let logseq = MockLogSeq::start().await.unwrap();
+        let rows: Vec<Value> = (0..3).map(|i| json!([format!("Page {}", i), i])).collect();
+        logseq.respond("logseq.DB.datascriptQuery", json!(rows));
+        let ctx = ToolContext::new(logseq.client());
+        let query = "[:find ?name (count ?b) :where [?p :block/name ?name] [?b :block/page ?p]]";
+
+        let args = json!({"query": query, "format": "csv", "limit": 2});
+        let text = call_text(&ctx, "datascript_query", args).await.unwrap();
+        assert!(
+            text.starts_with("name,count b\r\nPage 0,0\r\nPage 1,1\r\n"),
+            "{}",
+            text
+        );
+        assert!(text.contains("Note: showing 2 of 3 rows"), "{}", text);
+
+        let args = json!({"query": query, "format": "xlsx"});
+        assert!(call_text(&ctx, "datascript_query", args).await.is_err());
+    }
+}
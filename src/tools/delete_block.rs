@@ -0,0 +1,207 @@
+//! The `delete_block` tool
+
+use super::context::{ToolContext, confirmation_result, internal_error, logseq_error, text_result};
+use super::registry::McpTool;
+use crate::logseq::api::Block;
+use crate::quota::QuotaKind;
+use crate::tools::count_blocks;
+use crate::trash::{Trash, TrashedItem};
+use rmcp::{
+    ErrorData as McpError,
+    model::{CallToolResult, JsonObject},
+};
+use schemars::JsonSchema;
+use serde::Deserialize;
+
+/// Characters of the block's first line quoted in the preview
+const PREVIEW_CHARS: usize = 80;
+
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct DeleteBlockArgs {
+    /// The UUID of the block to delete. Must be an existing block UUID. This operation will also delete all child blocks.
+    pub uuid: String,
+    /// The confirmation token returned by a previous call for this block. Omit it to get a preview of what will be removed and a token; the block is only deleted when a valid token is passed.
+    #[serde(default)]
+    pub confirm: Option<String>,
+}
+
+pub struct DeleteBlock;
+
+impl McpTool for DeleteBlock {
+    type Args = DeleteBlockArgs;
+
+    fn name(&self) -> &'static str {
+        "delete_block"
+    }
+
+    fn description(&self) -> &'static str {
+        "Delete an existing block by UUID, along with all its children. This is a two-step operation: the first call returns a preview of what will be removed and a confirmation token, and nothing is deleted until the tool is called again with `confirm` set to that token. The deleted block and its children are kept in the trash and can be brought back with `restore_from_trash`, unless the server has no state directory, in which case deletion cannot be undone."
+    }
+
+    fn idempotent(&self) -> bool {
+        true
+    }
+
+    /// Only confirmed calls delete anything; previews aren't audited
+    fn audited(&self, arguments: &JsonObject) -> bool {
+        arguments.contains_key("confirm")
+    }
+
+    async fn execute(
+        &self,
+        ctx: &ToolContext,
+        args: DeleteBlockArgs,
+    ) -> Result<CallToolResult, McpError> {
+        let DeleteBlockArgs { uuid, confirm } = args;
+        let action = format!("delete_block {}", uuid.trim());
+        ctx.require_writable(&uuid).await?;
+
+        let Some(token) = confirm else {
+            let block = ctx
+                .client
+                .get_block_tree(&uuid)
+                .await
+                .map_err(logseq_error)?;
+            let token = ctx.confirmations.issue(&action);
+            return Ok(confirmation_result(
+                &preview(ctx, &block),
+                self.name(),
+                &token,
+            ));
+        };
+        ctx.confirm(&token, &action)?;
+
+        let block = ctx
+            .client
+            .get_block_tree(&uuid)
+            .await
+            .map_err(logseq_error)?;
+        let removed = 1 + count_blocks(&block.children);
+        ctx.charge_quota(
+            QuotaKind::BlocksDeleted,
+            u32::try_from(removed).unwrap_or(u32::MAX),
+        )?;
+
+        let trashed = match &ctx.trash {
+            Some(trash) => Some(save_to_trash(ctx, trash, block).await?),
+            None => None,
+        };
+
+        ctx.client.remove_block(&uuid).await.map_err(logseq_error)?;
+
+        let mut text = format!("Successfully deleted block with UUID: {}", uuid);
+        if let Some(id) = trashed {
+            text.push_str(&format!(
+                "\nSaved to the trash as {}. Use restore_from_trash to bring it back.",
+                id
+            ));
+        }
+        Ok(text_result(text))
+    }
+}
+
+/// Copy a block and its children into the trash before it is deleted,
+/// returning the trash entry's id
+async fn save_to_trash(ctx: &ToolContext, trash: &Trash, block: Block) -> Result<String, McpError> {
+    // The location only decides where a restore puts the block by default,
+    // so a failed lookup shouldn't stop the delete
+    let location = ctx
+        .client
+        .get_block_location(&block.uuid)
+        .await
+        .ok()
+        .flatten();
+
+    let item = TrashedItem::Block {
+        uuid: block.uuid.clone(),
+        page: location.as_ref().map(|l| l.page.clone()),
+        parent: location.and_then(|l| l.parent),
+    };
+    let entry = trash.put(item, vec![block]).map_err(|e| {
+        internal_error(format!(
+            "Could not save the block to the trash, so it was not deleted: {}",
+            e
+        ))
+    })?;
+    Ok(entry.id)
+}
+
+/// Describe what deleting `block` would remove
+fn preview(ctx: &ToolContext, block: &Block) -> String {
+    let first_line = block.content.lines().next().unwrap_or_default();
+    let mut quoted: String = first_line.chars().take(PREVIEW_CHARS).collect();
+    if first_line.chars().count() > PREVIEW_CHARS {
+        quoted.push('…');
+    }
+
+    let what = format!(
+        "it and {} nested child block(s)",
+        count_blocks(&block.children)
+    );
+    format!(
+        "Deleting block {} (\"{}\") will {}.",
+        block.uuid,
+        quoted,
+        ctx.removal(&what)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::logseq::mock::MockGraph;
+    use crate::tools::testing::{call_text, confirm_token, serve};
+    use crate::trash::Trash;
+    use serde_json::json;
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn test_block_is_deleted_only_once_confirmed() {
+        let mut graph = MockGraph::new();
+        graph.add_page("Inbox");
+        let errand = graph.add_block("Inbox", "Buy stamps");
+        graph.add_block(&errand, "At the post office");
+        let (logseq, ctx) = serve(graph).await;
+
+        let args = json!({"uuid": errand});
+        let preview = call_text(&ctx, "delete_block", args).await.unwrap();
+        assert!(
+            preview.contains(
+                "(\"Buy stamps\") will permanently remove it and 1 nested child block(s)"
+            ),
+            "{}",
+            preview
+        );
+        assert_eq!(logseq.with_graph(|g| g.children("Inbox").len()), 1);
+
+        let args = json!({"uuid": errand, "confirm": "not-the-token"});
+        assert!(call_text(&ctx, "delete_block", args).await.is_err());
+        let args = json!({"uuid": errand, "confirm": confirm_token(&preview)});
+        let text = call_text(&ctx, "delete_block", args).await.unwrap();
+        assert!(text.starts_with("Successfully deleted block"), "{}", text);
+        assert!(logseq.with_graph(|g| g.children("Inbox").is_empty()));
+    }
+
+    #[tokio::test]
+    async fn test_preview_mentions_the_trash_when_there_is_one() {
+        let mut graph = MockGraph::new();
+        graph.add_page("Inbox");
+        let errand = graph.add_block("Inbox", "Buy stamps");
+        let (_logseq, mut ctx) = serve(graph).await;
+        let dir = tempfile::tempdir().unwrap();
+        ctx.trash = Some(Arc::new(Trash::new(dir.path())));
+
+        let args = json!({"uuid": errand});
+        let preview = call_text(&ctx, "delete_block", args).await.unwrap();
+        assert!(
+            preview.contains("will move it and 0 nested child block(s) to the trash (restore with restore_from_trash)"),
+            "{}",
+            preview
+        );
+        assert!(!preview.contains("permanently"), "{}", preview);
+
+        let args = json!({"uuid": errand, "confirm": confirm_token(&preview)});
+        let text = call_text(&ctx, "delete_block", args).await.unwrap();
+        assert!(text.contains("Saved to the trash as"), "{}", text);
+    }
+}
@@ -0,0 +1,225 @@
+//! The `delete_page` tool
+
+use super::context::{ToolContext, confirmation_result, internal_error, logseq_error, text_result};
+use super::registry::McpTool;
+use crate::logseq::api::{Block, Lenient};
+use crate::quota::QuotaKind;
+use crate::tools::{count_blocks, format_skipped_entries};
+use crate::trash::{Trash, TrashedItem};
+use rmcp::{
+    ErrorData as McpError,
+    model::{CallToolResult, JsonObject},
+};
+use schemars::JsonSchema;
+use serde::Deserialize;
+
+/// Child pages listed by name in the preview before the rest are summarised
+const MAX_LISTED_CHILD_PAGES: usize = 20;
+
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct DeletePageArgs {
+    /// The name of the page to delete. Must be an existing page name as it appears in LogSeq. This operation will delete the entire page and all its blocks.
+    pub page_name: String,
+    /// The confirmation token returned by a previous call for this page. Omit it to get a preview of what will be removed and a token; the page is only deleted when a valid token is passed.
+    #[serde(default)]
+    pub confirm: Option<String>,
+}
+
+pub struct DeletePage;
+
+impl McpTool for DeletePage {
+    type Args = DeletePageArgs;
+
+    fn name(&self) -> &'static str {
+        "delete_page"
+    }
+
+    fn description(&self) -> &'static str {
+        "Delete an existing page by name. This is a two-step operation: the first call returns a preview of what will be removed (block count and child pages) and a confirmation token, and nothing is deleted until the tool is called again with `confirm` set to that token. The deleted page is kept in the trash and can be brought back with `restore_from_trash`, unless the server has no state directory, in which case deletion cannot be undone."
+    }
+
+    fn idempotent(&self) -> bool {
+        true
+    }
+
+    /// Only confirmed calls delete anything; previews aren't audited
+    fn audited(&self, arguments: &JsonObject) -> bool {
+        arguments.contains_key("confirm")
+    }
+
+    async fn execute(
+        &self,
+        ctx: &ToolContext,
+        args: DeletePageArgs,
+    ) -> Result<CallToolResult, McpError> {
+        let DeletePageArgs { page_name, confirm } = args;
+        let action = format!("delete_page {}", page_name.trim().to_lowercase());
+        let _guard = ctx.page_locks.lock(&page_name).await;
+
+        ctx.require_page(&page_name).await?;
+        ctx.require_writable(&page_name).await?;
+
+        let Some(token) = confirm else {
+            let preview = preview(ctx, &page_name).await?;
+            let token = ctx.confirmations.issue(&action);
+            return Ok(confirmation_result(&preview, self.name(), &token));
+        };
+        ctx.confirm(&token, &action)?;
+
+        let blocks = ctx
+            .client
+            .get_page_blocks_tree(&page_name)
+            .await
+            .map_err(logseq_error)?;
+        ctx.charge_quota(
+            QuotaKind::BlocksDeleted,
+            u32::try_from(count_blocks(&blocks)).unwrap_or(u32::MAX),
+        )?;
+
+        let trashed = match &ctx.trash {
+            Some(trash) => Some(save_to_trash(ctx, trash, &page_name, blocks).await?),
+            None => None,
+        };
+
+        ctx.client
+            .delete_page(&page_name)
+            .await
+            .map_err(logseq_error)?;
+        ctx.cache.invalidate_pages();
+
+        let mut text = format!("Successfully deleted page: {}", page_name);
+        if let Some(trashed) = trashed {
+            text.push_str(&trashed);
+        }
+        Ok(text_result(text))
+    }
+}
+
+/// Copy a page into the trash before it is deleted, returning a note for
+/// the tool result
+async fn save_to_trash(
+    ctx: &ToolContext,
+    trash: &Trash,
+    page_name: &str,
+    blocks: Lenient<Block>,
+) -> Result<String, McpError> {
+    let page = ctx.client.get_page(page_name).await.map_err(logseq_error)?;
+
+    let item = TrashedItem::Page {
+        name: page.original_name.unwrap_or(page.name),
+        properties: page.properties,
+    };
+    let entry = trash.put(item, blocks.items).map_err(|e| {
+        internal_error(format!(
+            "Could not save the page to the trash, so it was not deleted: {}",
+            e
+        ))
+    })?;
+
+    let mut note = format!(
+        "\nSaved to the trash as {}. Use restore_from_trash to bring it back.",
+        entry.id
+    );
+    if !blocks.warnings.is_empty() {
+        note.push_str("\nThese entries could not be read, so the trash copy doesn't include them:");
+        note.push_str(&format_skipped_entries(&blocks.warnings));
+    }
+    Ok(note)
+}
+
+/// Describe what deleting `page_name` would remove
+async fn preview(ctx: &ToolContext, page_name: &str) -> Result<String, McpError> {
+    let blocks = ctx
+        .client
+        .get_page_blocks_tree(page_name)
+        .await
+        .map_err(logseq_error)?;
+    let pages = ctx.cache.pages().await.map_err(logseq_error)?;
+
+    let prefix = format!("{}/", page_name.trim().to_lowercase());
+    let mut children: Vec<&str> = pages
+        .iter()
+        .filter(|p| p.name.to_lowercase().starts_with(&prefix))
+        .map(|p| p.original_name.as_deref().unwrap_or(&p.name))
+        .collect();
+    children.sort_unstable();
+
+    let what = format!("it and its {} block(s)", count_blocks(&blocks));
+    let mut preview = format!("Deleting page '{}' will {}.", page_name, ctx.removal(&what));
+    if !children.is_empty() {
+        preview.push_str(&format!(
+            "\n\nIt has {} child page(s), which are not deleted but will lose their parent page:\n",
+            children.len()
+        ));
+        for child in children.iter().take(MAX_LISTED_CHILD_PAGES) {
+            preview.push_str(&format!("- {}\n", child));
+        }
+        if children.len() > MAX_LISTED_CHILD_PAGES {
+            preview.push_str(&format!(
+                "- … and {} more\n",
+                children.len() - MAX_LISTED_CHILD_PAGES
+            ));
+        }
+    }
+    Ok(preview)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::logseq::mock::MockGraph;
+    use crate::tools::testing::{call_text, confirm_token, serve};
+    use crate::trash::Trash;
+    use serde_json::json;
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn test_page_is_deleted_only_once_confirmed() {
+        let mut graph = MockGraph::new();
+        graph.add_page("Projects");
+        graph.add_block("Projects", "Plan");
+        graph.add_page("Projects/Alpha");
+        let (logseq, ctx) = serve(graph).await;
+
+        let args = json!({"page_name": "Projects"});
+        let preview = call_text(&ctx, "delete_page", args).await.unwrap();
+        assert!(
+            preview.contains("will permanently remove it and its 1 block(s)"),
+            "{}",
+            preview
+        );
+        assert!(preview.contains("1 child page(s)"), "{}", preview);
+        assert!(preview.contains("- Projects/Alpha"), "{}", preview);
+        assert!(logseq.with_graph(|g| g.has_page("Projects")));
+
+        let args = json!({"page_name": "Projects", "confirm": confirm_token(&preview)});
+        let text = call_text(&ctx, "delete_page", args.clone()).await.unwrap();
+        assert_eq!(text, "Successfully deleted page: Projects");
+        logseq.with_graph(|g| {
+            assert!(!g.has_page("Projects"));
+            assert!(g.has_page("Projects/Alpha"));
+        });
+        // The token is single use
+        assert!(call_text(&ctx, "delete_page", args).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_preview_mentions_the_trash_when_there_is_one() {
+        let mut graph = MockGraph::new();
+        graph.add_page("Drafts");
+        graph.add_block("Drafts", "Opening line");
+        graph.add_block("Drafts", "Closing line");
+        let (_logseq, mut ctx) = serve(graph).await;
+        let dir = tempfile::tempdir().unwrap();
+        ctx.trash = Some(Arc::new(Trash::new(dir.path())));
+
+        let args = json!({"page_name": "Drafts"});
+        let preview = call_text(&ctx, "delete_page", args).await.unwrap();
+        assert!(
+            preview.starts_with("Deleting page 'Drafts' will move it and its 2 block(s) to the trash (restore with restore_from_trash)."),
+            "{}",
+            preview
+        );
+        assert!(!preview.contains("permanently"), "{}", preview);
+    }
+}
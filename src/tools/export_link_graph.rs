@@ -0,0 +1,175 @@
+//! The `export_link_graph` tool
+
+use super::context::{ToolContext, logseq_error, text_result};
+use super::registry::McpTool;
+use crate::logseq::link_graph::LinkGraph;
+use rmcp::{ErrorData as McpError, model::CallToolResult};
+use schemars::JsonSchema;
+use serde::Deserialize;
+use serde_json::{Value, json};
+use std::collections::{HashMap, HashSet};
+
+/// The format to write the graph in
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum LinkGraphFormat {
+    /// Graphviz's DOT language
+    #[default]
+    Dot,
+    /// GraphML, for tools such as Gephi, yEd, and Cytoscape
+    Graphml,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct ExportLinkGraphArgs {
+    /// 'dot' for Graphviz or 'graphml' for Gephi, yEd, and the like. Defaults to dot.
+    pub format: Option<LinkGraphFormat>,
+    /// Only include the namespace page, such as 'Projects', and the pages under it.
+    pub namespace: Option<String>,
+    /// Only include pages tagged with this tag (set with 'tags::'), and the tag's own page.
+    pub tag: Option<String>,
+}
+
+pub struct ExportLinkGraph;
+
+impl McpTool for ExportLinkGraph {
+    type Args = ExportLinkGraphArgs;
+
+    fn name(&self) -> &'static str {
+        "export_link_graph"
+    }
+
+    fn description(&self) -> &'static str {
+        "Export the graph of links between pages as DOT (for Graphviz) or GraphML (for Gephi, yEd, or Cytoscape) text, to visualise how the graph fits together in an external tool. Each page is a node; each edge runs from a page to a page it links or tags, weighted by how many of its blocks do so. Scope it to a namespace or a tag to export part of the graph; only links with both pages in scope are kept, and pages without links are left out."
+    }
+
+    fn examples(&self) -> Vec<Value> {
+        vec![
+            json!({}),
+            json!({"format": "graphml", "namespace": "Projects"}),
+        ]
+    }
+
+    fn read_only(&self) -> bool {
+        true
+    }
+
+    async fn execute(
+        &self,
+        ctx: &ToolContext,
+        args: ExportLinkGraphArgs,
+    ) -> Result<CallToolResult, McpError> {
+        let namespace = args
+            .namespace
+            .as_deref()
+            .map(|ns| {
+                ns.trim()
+                    .trim_start_matches("[[")
+                    .trim_end_matches("]]")
+                    .trim_end_matches('/')
+                    .to_lowercase()
+            })
+            .filter(|ns| !ns.is_empty());
+        let tag = args
+            .tag
+            .as_deref()
+            .map(|tag| tag.trim().trim_start_matches('#').to_lowercase())
+            .filter(|tag| !tag.is_empty());
+
+        let tagged: Option<HashSet<String>> = match &tag {
+            Some(tag) => {
+                let usage = ctx.cache.tag_usage().await.map_err(logseq_error)?;
+                let mut pages: HashSet<String> = usage
+                    .iter()
+                    .filter(|usage| usage.tags.contains(tag))
+                    .filter_map(|usage| usage.page.as_ref().map(|p| p.to_lowercase()))
+                    .collect();
+                pages.insert(tag.clone());
+                Some(pages)
+            }
+            None => None,
+        };
+
+        let links = ctx.client.get_page_links().await.map_err(logseq_error)?;
+        let pages = ctx.cache.pages().await.map_err(logseq_error)?;
+        let labels: HashMap<&str, &str> = pages
+            .iter()
+            .map(|page| (page.name.as_str(), page.display_name()))
+            .collect();
+
+        let graph = LinkGraph::new(
+            &links,
+            |name| {
+                namespace
+                    .as_deref()
+                    .is_none_or(|ns| name == ns || name.starts_with(&format!("{}/", ns)))
+                    && tagged.as_ref().is_none_or(|pages| pages.contains(name))
+            },
+            |name| labels.get(name).copied().unwrap_or(name).to_string(),
+        );
+        Ok(text_result(match args.format.unwrap_or_default() {
+            LinkGraphFormat::Dot => graph.to_dot(),
+            LinkGraphFormat::Graphml => graph.to_graphml(),
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::logseq::mock::MockGraph;
+    use crate::tools::testing::{call_text, serve};
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_link_graph_is_exported() {
+        let mut graph = MockGraph::new();
+        graph.add_page("Projects");
+        graph.add_block("Projects", "See [[Projects/Alpha]] and [[Projects/Beta]]");
+        graph.add_block("Projects", "More on [[Projects/Alpha]]");
+        graph.add_page("Projects/Alpha");
+        graph.add_block("Projects/Alpha", "Part of [[Projects]] #reading");
+        graph.set_page_property("Projects/Alpha", "tags", json!("reading"));
+        graph.add_page("Projects/Beta");
+        graph.add_page("Reading");
+        let (_logseq, ctx) = serve(graph).await;
+
+        let text = call_text(&ctx, "export_link_graph", json!({}))
+            .await
+            .unwrap();
+        assert!(text.starts_with("digraph logseq {\n"), "{}", text);
+        assert!(
+            text.contains("  \"Projects\" -> \"Projects/Alpha\" [weight=2];\n"),
+            "{}",
+            text
+        );
+        assert!(
+            text.contains("  \"Projects/Alpha\" -> \"Reading\" [weight=1];\n"),
+            "{}",
+            text
+        );
+
+        let text = call_text(
+            &ctx,
+            "export_link_graph",
+            json!({"namespace": "[[Projects/]]", "format": "graphml"}),
+        )
+        .await
+        .unwrap();
+        assert!(text.contains("<graph id=\"logseq\" edgedefault=\"directed\">"));
+        assert!(
+            text.contains("<data key=\"label\">Projects/Beta</data>"),
+            "{}",
+            text
+        );
+        assert!(!text.contains("Reading"), "{}", text);
+
+        let text = call_text(&ctx, "export_link_graph", json!({"tag": "#Reading"}))
+            .await
+            .unwrap();
+        assert_eq!(
+            text,
+            "digraph logseq {\n  \"Projects/Alpha\";\n  \"Reading\";\n  \"Projects/Alpha\" -> \"Reading\" [weight=1];\n}\n"
+        );
+    }
+}
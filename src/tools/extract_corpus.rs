@@ -0,0 +1,147 @@
+//! The `extract_corpus` tool
+
+use super::context::{ToolContext, json_result, logseq_error};
+use super::registry::McpTool;
+use crate::tools::paginate;
+use rmcp::{ErrorData as McpError, model::CallToolResult};
+use schemars::JsonSchema;
+use serde::Deserialize;
+
+/// Blocks per chunk when no limit is given
+const DEFAULT_CHUNK: usize = 200;
+
+/// The most blocks returned in one chunk
+const MAX_CHUNK: usize = 1000;
+
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct ExtractCorpusArgs {
+    /// Only extract blocks on this page. Defaults to the whole graph.
+    pub page: Option<String>,
+    /// Blocks per chunk. Defaults to 200, at most 1000.
+    #[schemars(range(min = 1, max = 1000))]
+    pub limit: Option<usize>,
+    /// The 'next_cursor' of the previous chunk, to continue from there.
+    pub cursor: Option<String>,
+}
+
+pub struct ExtractCorpus;
+
+impl McpTool for ExtractCorpus {
+    type Args = ExtractCorpusArgs;
+
+    fn name(&self) -> &'static str {
+        "extract_corpus"
+    }
+
+    fn description(&self) -> &'static str {
+        "Extract the text of every block in the graph (or on one page) in chunks, for spelling, grammar, or consistency checks done outside LogSeq. Returns JSON with each block's 'uuid', 'page_name', and raw 'content', plus a 'next_cursor' to pass back for the next chunk until it is null. Send fixes back with apply_corrections, quoting each block's content as extracted."
+    }
+
+    fn read_only(&self) -> bool {
+        true
+    }
+
+    async fn execute(
+        &self,
+        ctx: &ToolContext,
+        args: ExtractCorpusArgs,
+    ) -> Result<CallToolResult, McpError> {
+        let ExtractCorpusArgs {
+            page,
+            limit,
+            cursor,
+        } = args;
+
+        if let Some(page) = &page {
+            ctx.require_page(page).await?;
+        }
+        let blocks = ctx
+            .client
+            .get_corpus(page.as_deref())
+            .await
+            .map_err(logseq_error)?;
+        let chunk = paginate(
+            &blocks,
+            limit.unwrap_or(DEFAULT_CHUNK).clamp(1, MAX_CHUNK),
+            cursor.as_deref(),
+        )
+        .map_err(|e| McpError::invalid_params(e, None))?;
+
+        Ok(json_result(
+            &serde_json::json!({
+                "blocks": chunk.items,
+                "offset": chunk.offset,
+                "total": chunk.total,
+                "next_cursor": chunk.next_cursor,
+            }),
+            "corpus",
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::logseq::mock::MockGraph;
+    use crate::tools::testing::{call_text, serve};
+    use serde_json::{Value, json};
+
+    #[tokio::test]
+    async fn test_corpus_is_extracted_and_corrected() {
+        let mut graph = MockGraph::new();
+        graph.add_page("Notes");
+        let typo = graph.add_block("Notes", "Teh quick fox\nstatus:: draft");
+        let stale = graph.add_block("Notes", "Recieve the parcel");
+        graph.add_page("Other");
+        graph.add_block("Other", "Seperate page");
+        let (logseq, ctx) = serve(graph).await;
+
+        let text = call_text(&ctx, "extract_corpus", json!({"limit": 2}))
+            .await
+            .unwrap();
+        let chunk: Value = serde_json::from_str(&text).unwrap();
+        assert_eq!(chunk["total"], 3);
+        assert_eq!(chunk["blocks"].as_array().unwrap().len(), 2);
+        assert_eq!(chunk["blocks"][0]["page_name"], "Notes");
+        let args = json!({"limit": 2, "cursor": chunk["next_cursor"]});
+        let text = call_text(&ctx, "extract_corpus", args).await.unwrap();
+        let chunk: Value = serde_json::from_str(&text).unwrap();
+        assert_eq!(chunk["blocks"][0]["content"], "Seperate page");
+        assert!(chunk["next_cursor"].is_null());
+
+        ctx.client
+            .update_block(&stale, "Receive the parcel today", None)
+            .await
+            .unwrap();
+        let args = json!({"corrections": [
+            {"uuid": typo, "original": "Teh quick fox\nstatus:: draft", "corrected": "The quick fox\nstatus:: draft"},
+            {"uuid": stale, "original": "Recieve the parcel", "corrected": "Receive the parcel"},
+            {"uuid": typo, "original": "x", "corrected": "y"},
+        ]});
+        let err = call_text(&ctx, "apply_corrections", args)
+            .await
+            .unwrap_err();
+        assert!(err.message.contains("more than once"), "{}", err.message);
+
+        let args = json!({"corrections": [
+            {"uuid": typo, "original": "Teh quick fox\nstatus:: draft", "corrected": "The quick fox\nstatus:: drafted"},
+            {"uuid": stale, "original": "Recieve the parcel", "corrected": "Receive the parcel"},
+        ]});
+        let text = call_text(&ctx, "apply_corrections", args).await.unwrap();
+        assert!(text.contains("Applied 0 of 2"), "{}", text);
+        assert!(text.contains("changes the block's properties"), "{}", text);
+        assert!(text.contains("changed since it was extracted"), "{}", text);
+
+        let args = json!({"corrections": [
+            {"uuid": typo, "original": "Teh quick fox\nstatus:: draft", "corrected": "The quick fox\nstatus:: draft"},
+        ]});
+        let text = call_text(&ctx, "apply_corrections", args).await.unwrap();
+        assert!(text.contains("Applied 1 of 1"), "{}", text);
+        logseq.with_graph(|g| {
+            assert_eq!(
+                g.block_content(&typo).unwrap(),
+                "The quick fox\nstatus:: draft"
+            );
+        });
+    }
+}
@@ -0,0 +1,226 @@
+//! The `find_broken_links` tool
+
+use super::context::{ToolContext, json_result, logseq_error, text_result};
+use super::registry::McpTool;
+use crate::fuzzy;
+use crate::logseq::content::extract_page_links;
+use crate::logseq::journal::{DEFAULT_DATE_FORMAT, detect_journal_date};
+use crate::session::OutputFormat;
+use rmcp::{ErrorData as McpError, model::CallToolResult};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+
+/// Broken links listed when no limit is given
+const DEFAULT_LIMIT: usize = 100;
+
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct FindBrokenLinksArgs {
+    /// Only check links on this page. Defaults to the whole graph.
+    pub page: Option<String>,
+    /// The most broken links to list. Defaults to 100.
+    #[schemars(range(min = 1))]
+    pub limit: Option<usize>,
+}
+
+/// A link to a page with nothing on it
+#[derive(Debug, Serialize)]
+struct BrokenLink {
+    uuid: String,
+    target: String,
+    /// Existing pages with similar names, in case the link is a typo
+    suggestions: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct SourcePage {
+    page: String,
+    links: Vec<BrokenLink>,
+}
+
+pub struct FindBrokenLinks;
+
+impl McpTool for FindBrokenLinks {
+    type Args = FindBrokenLinksArgs;
+
+    fn name(&self) -> &'static str {
+        "find_broken_links"
+    }
+
+    fn description(&self) -> &'static str {
+        "Find [[Page Name]] links to pages that don't exist or have nothing on them, grouped by the page the link is on, with the linking block's UUID and similarly named pages in case of a typo. LogSeq creates a page for every link, so an empty page counts as missing unless it's a journal date, an alias of another page, or has pages with content under it in its namespace. Use it to offer to create the missing pages or fix the links. Reads every block, so it can be slow on large graphs."
+    }
+
+    fn read_only(&self) -> bool {
+        true
+    }
+
+    async fn execute(
+        &self,
+        ctx: &ToolContext,
+        args: FindBrokenLinksArgs,
+    ) -> Result<CallToolResult, McpError> {
+        let FindBrokenLinksArgs { page, limit } = args;
+        let limit = limit.unwrap_or(DEFAULT_LIMIT).max(1);
+        if let Some(page) = &page {
+            ctx.require_page(page).await?;
+        }
+        let date_format = ctx
+            .cache
+            .preferred_date_format()
+            .await
+            .unwrap_or_else(|_| DEFAULT_DATE_FORMAT.to_string());
+        let pages = ctx.cache.pages().await.map_err(logseq_error)?;
+        let corpus = ctx.client.get_corpus(None).await.map_err(logseq_error)?;
+
+        // Pages with content, under every name they go by
+        let mut written: Vec<&str> = corpus.iter().map(|b| b.page_name.as_str()).collect();
+        written.dedup();
+        let mut existing: HashSet<String> = written.iter().map(|p| p.to_lowercase()).collect();
+        for page in pages.iter() {
+            if existing.contains(&page.name.to_lowercase()) {
+                existing.extend(page.aliases().iter().map(|alias| alias.to_lowercase()));
+            }
+        }
+        // Namespaces holding pages with content, such as `projects` for
+        // `projects/alpha`
+        let namespaces: HashSet<&str> = existing
+            .iter()
+            .flat_map(|page| page.match_indices('/').map(|(i, _)| &page[..i]))
+            .collect();
+        let exists = |target: &str| {
+            let target = target.to_lowercase();
+            existing.contains(&target)
+                || namespaces.contains(target.as_str())
+                || detect_journal_date(&target, &date_format).is_some()
+        };
+
+        let source = page.as_deref().map(str::to_lowercase);
+        let mut by_page: BTreeMap<String, SourcePage> = BTreeMap::new();
+        let mut missing: BTreeMap<String, String> = BTreeMap::new();
+        let mut total = 0;
+        for block in &corpus {
+            if source
+                .as_ref()
+                .is_some_and(|source| *source != block.page_name.to_lowercase())
+            {
+                continue;
+            }
+            for target in extract_page_links(&block.content) {
+                if exists(&target) {
+                    continue;
+                }
+                total += 1;
+                missing
+                    .entry(target.to_lowercase())
+                    .or_insert_with(|| target.clone());
+                if total > limit {
+                    continue;
+                }
+                let suggestions = fuzzy::suggest(&target, written.iter().copied());
+                by_page
+                    .entry(block.page_name.to_lowercase())
+                    .or_insert_with(|| SourcePage {
+                        page: block.page_name.clone(),
+                        links: Vec::new(),
+                    })
+                    .links
+                    .push(BrokenLink {
+                        uuid: block.uuid.clone(),
+                        target,
+                        suggestions,
+                    });
+            }
+        }
+        let pages: Vec<SourcePage> = by_page.into_values().collect();
+        let missing: Vec<String> = missing.into_values().collect();
+
+        if ctx.session.output_format == OutputFormat::Json {
+            return Ok(json_result(
+                &serde_json::json!({
+                    "total": total,
+                    "missing_pages": missing,
+                    "pages": pages,
+                }),
+                "broken links",
+            ));
+        }
+        if total == 0 {
+            return Ok(text_result("No broken links found."));
+        }
+        let mut content = format!(
+            "Found {} broken link(s) to {} missing page(s): {}\n",
+            total,
+            missing.len(),
+            missing.join(", ")
+        );
+        for page in &pages {
+            content.push_str(&format!("\n## {}\n", page.page));
+            for link in &page.links {
+                content.push_str(&format!("- [[{}]] in block {}", link.target, link.uuid));
+                if !link.suggestions.is_empty() {
+                    content.push_str(&format!(
+                        " (did you mean: {}?)",
+                        link.suggestions.join(", ")
+                    ));
+                }
+                content.push('\n');
+            }
+        }
+        if total > limit {
+            content.push_str(&format!(
+                "\n…and {} more; raise 'limit' to list them.\n",
+                total - limit
+            ));
+        }
+        Ok(text_result(content))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::logseq::mock::MockGraph;
+    use crate::tools::testing::{call_text, serve};
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_broken_links_are_grouped_by_page() {
+        let mut graph = MockGraph::new();
+        graph.add_page("Style Guide");
+        graph.add_block("Style Guide", "Use the Oxford comma");
+        graph.add_page("Projects/Alpha");
+        graph.add_block("Projects/Alpha", "Kickoff");
+        graph.add_page("Draft");
+        let bad = graph.add_block(
+            "Draft",
+            "See [[Style Gide]], [[Projects]], [[Style Guide]], and [[Mar 3rd, 2024]]",
+        );
+        graph.add_page("Notes");
+        graph.add_block("Notes", "Ask about [[Venue]]");
+        let (_logseq, ctx) = serve(graph).await;
+
+        let text = call_text(&ctx, "find_broken_links", json!({}))
+            .await
+            .unwrap();
+        assert!(
+            text.starts_with("Found 2 broken link(s) to 2 missing page(s): Style Gide, Venue"),
+            "{}",
+            text
+        );
+        assert!(
+            text.contains(&format!(
+                "## Draft\n- [[Style Gide]] in block {} (did you mean: Style Guide?)",
+                bad
+            )),
+            "{}",
+            text
+        );
+
+        let text = call_text(&ctx, "find_broken_links", json!({"page": "notes"}))
+            .await
+            .unwrap();
+        assert!(text.contains("## Notes\n- [[Venue]]"), "{}", text);
+        assert!(!text.contains("Draft"), "{}", text);
+    }
+}
@@ -0,0 +1,283 @@
+//! The `find_duplicates` tool
+
+use super::context::{ToolContext, json_result, logseq_error, text_result};
+use super::registry::McpTool;
+use crate::logseq::duplicates::{DEFAULT_MIN_WORDS, DEFAULT_THRESHOLD, duplicate_groups};
+use crate::session::OutputFormat;
+use rmcp::{ErrorData as McpError, model::CallToolResult};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use std::collections::BTreeMap;
+
+/// Groups listed when no limit is given
+const DEFAULT_LIMIT: usize = 20;
+
+/// Characters of each block shown in text output
+const PREVIEW_CHARS: usize = 80;
+
+/// What to compare
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum DuplicateScope {
+    /// Each block on its own
+    #[default]
+    Blocks,
+    /// Each page's blocks taken together
+    Pages,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct FindDuplicatesArgs {
+    /// Compare 'blocks' or whole 'pages'. Defaults to blocks.
+    pub scope: Option<DuplicateScope>,
+    /// How alike texts must be, from 0.5 to 1: the share of their words in common, ignoring case, punctuation, and word order. 1 finds only texts with the same words. Defaults to 0.8.
+    #[schemars(range(min = 0.5, max = 1.0))]
+    pub threshold: Option<f64>,
+    /// Leave out blocks or pages with fewer distinct words than this, such as repeated headings. Defaults to 3.
+    #[schemars(range(min = 1))]
+    pub min_words: Option<usize>,
+    /// The most groups to list, largest first. Defaults to 20.
+    #[schemars(range(min = 1))]
+    pub limit: Option<usize>,
+}
+
+/// A block or page in a group
+#[derive(Debug, Serialize)]
+struct Member {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    uuid: Option<String>,
+    page: String,
+    /// Left out for pages, which can be long
+    #[serde(skip_serializing_if = "String::is_empty")]
+    content: String,
+}
+
+#[derive(Debug, Serialize)]
+struct Group {
+    similarity: f64,
+    identical: bool,
+    members: Vec<Member>,
+}
+
+pub struct FindDuplicates;
+
+impl McpTool for FindDuplicates {
+    type Args = FindDuplicatesArgs;
+
+    fn name(&self) -> &'static str {
+        "find_duplicates"
+    }
+
+    fn description(&self) -> &'static str {
+        "Find blocks, or whole pages, with the same or nearly the same content anywhere in the graph, such as notes captured more than once. Texts are compared by the words they share, ignoring properties, task markers, case, punctuation, and word order. Returns groups of duplicates, largest first, with each block's UUID and page, as candidates for merging; nothing is changed. Reads every block, so it can be slow on large graphs."
+    }
+
+    fn examples(&self) -> Vec<Value> {
+        vec![json!({}), json!({"scope": "pages", "threshold": 0.9})]
+    }
+
+    fn read_only(&self) -> bool {
+        true
+    }
+
+    async fn execute(
+        &self,
+        ctx: &ToolContext,
+        args: FindDuplicatesArgs,
+    ) -> Result<CallToolResult, McpError> {
+        let FindDuplicatesArgs {
+            scope,
+            threshold,
+            min_words,
+            limit,
+        } = args;
+        let scope = scope.unwrap_or_default();
+        let threshold = threshold.unwrap_or(DEFAULT_THRESHOLD);
+        if !(0.5..=1.0).contains(&threshold) {
+            return Err(McpError::invalid_params(
+                "'threshold' must be between 0.5 and 1",
+                None,
+            ));
+        }
+        let min_words = min_words.unwrap_or(DEFAULT_MIN_WORDS).max(1);
+        let limit = limit.unwrap_or(DEFAULT_LIMIT).max(1);
+
+        let corpus = ctx.client.get_corpus(None).await.map_err(logseq_error)?;
+        let members: Vec<Member> = match scope {
+            DuplicateScope::Blocks => corpus
+                .into_iter()
+                .map(|block| Member {
+                    uuid: Some(block.uuid),
+                    page: block.page_name,
+                    content: block.content,
+                })
+                .collect(),
+            DuplicateScope::Pages => {
+                let mut pages: BTreeMap<String, Member> = BTreeMap::new();
+                for block in corpus {
+                    let page = pages
+                        .entry(block.page_name.to_lowercase())
+                        .or_insert_with(|| Member {
+                            uuid: None,
+                            page: block.page_name,
+                            content: String::new(),
+                        });
+                    if !page.content.is_empty() {
+                        page.content.push('\n');
+                    }
+                    page.content.push_str(&block.content);
+                }
+                pages.into_values().collect()
+            }
+        };
+
+        let texts: Vec<&str> = members.iter().map(|m| m.content.as_str()).collect();
+        let found = duplicate_groups(&texts, threshold, min_words);
+        let total = found.len();
+        let mut members: Vec<Option<Member>> = members.into_iter().map(Some).collect();
+        let groups: Vec<Group> = found
+            .into_iter()
+            .take(limit)
+            .map(|group| Group {
+                similarity: (group.similarity * 100.0).round() / 100.0,
+                identical: group.identical,
+                members: group
+                    .members
+                    .iter()
+                    .filter_map(|&i| members[i].take())
+                    .map(|mut member| {
+                        if scope == DuplicateScope::Pages {
+                            member.content.clear();
+                        }
+                        member
+                    })
+                    .collect(),
+            })
+            .collect();
+
+        if ctx.session.output_format == OutputFormat::Json {
+            return Ok(json_result(
+                &json!({"total": total, "groups": groups}),
+                "duplicates",
+            ));
+        }
+        let what = match scope {
+            DuplicateScope::Blocks => "blocks",
+            DuplicateScope::Pages => "pages",
+        };
+        if total == 0 {
+            return Ok(text_result(format!(
+                "No duplicate {} found at {:.0}% similarity.",
+                what,
+                threshold * 100.0
+            )));
+        }
+        let mut content = format!(
+            "Found {} group(s) of duplicate {} at {:.0}% similarity or more:\n",
+            total,
+            what,
+            threshold * 100.0
+        );
+        for (n, group) in groups.iter().enumerate() {
+            let alike = if group.identical {
+                "identical".to_string()
+            } else {
+                format!("{:.0}% similar", group.similarity * 100.0)
+            };
+            content.push_str(&format!(
+                "\n## Group {}: {} {}, {}\n",
+                n + 1,
+                group.members.len(),
+                what,
+                alike
+            ));
+            for member in &group.members {
+                match &member.uuid {
+                    Some(uuid) => content.push_str(&format!(
+                        "- {} ({}): {}\n",
+                        member.page,
+                        uuid,
+                        preview(&member.content)
+                    )),
+                    None => content.push_str(&format!("- {}\n", member.page)),
+                }
+            }
+        }
+        if total > limit {
+            content.push_str(&format!(
+                "\n…and {} more group(s); raise 'limit' to list them.\n",
+                total - limit
+            ));
+        }
+        Ok(text_result(content))
+    }
+}
+
+/// The first line of `content`, shortened to fit on one line
+fn preview(content: &str) -> String {
+    let line = content.lines().next().unwrap_or_default().trim();
+    if line.chars().count() > PREVIEW_CHARS {
+        let cut: String = line.chars().take(PREVIEW_CHARS).collect();
+        format!("{}…", cut.trim_end())
+    } else {
+        line.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::logseq::mock::MockGraph;
+    use crate::tools::testing::{call_text, serve};
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_duplicates_are_grouped() {
+        let mut graph = MockGraph::new();
+        graph.add_page("Inbox");
+        let first = graph.add_block("Inbox", "TODO Renew the passport before the trip");
+        graph.add_block("Inbox", "Notes");
+        graph.add_page("Travel");
+        let second = graph.add_block("Travel", "Renew the passport before the trip!");
+        graph.add_block("Travel", "Notes");
+        graph.add_page("Journal");
+        graph.add_block("Journal", "Renew the passport well before the trip");
+        let (_logseq, ctx) = serve(graph).await;
+
+        let text = call_text(&ctx, "find_duplicates", json!({"threshold": 1.0}))
+            .await
+            .unwrap();
+        assert!(
+            text.starts_with("Found 1 group(s) of duplicate blocks at 100% similarity or more:"),
+            "{}",
+            text
+        );
+        assert!(
+            text.contains(&format!(
+                "## Group 1: 2 blocks, identical\n- Inbox ({}): TODO Renew the passport before the trip\n- Travel ({}): ",
+                first, second
+            )),
+            "{}",
+            text
+        );
+
+        // Adding "well" leaves 5 of 6 distinct words shared
+        let text = call_text(&ctx, "find_duplicates", json!({})).await.unwrap();
+        assert!(
+            text.contains("## Group 1: 3 blocks, 83% similar"),
+            "{}",
+            text
+        );
+        assert!(!text.contains("Notes"), "{}", text);
+
+        let text = call_text(&ctx, "find_duplicates", json!({"threshold": 0.2}))
+            .await
+            .unwrap_err();
+        assert!(
+            text.message.contains("between 0.5 and 1"),
+            "{}",
+            text.message
+        );
+    }
+}
@@ -0,0 +1,190 @@
+//! The `find_incomplete_todos` tool
+
+use super::context::{ToolContext, json_result, logseq_error, text_result};
+use super::registry::McpTool;
+use crate::logseq::api::TodoFilter;
+use crate::logseq::content::INCOMPLETE_MARKERS;
+use crate::session::OutputFormat;
+use crate::tools::{TodoGroupBy, format_pagination_footer, format_todos, paginate};
+use rmcp::{ErrorData as McpError, model::CallToolResult};
+use schemars::JsonSchema;
+use serde::Deserialize;
+use serde_json::{Value, json};
+
+/// Default page size for `find_incomplete_todos`
+const DEFAULT_TODO_LIMIT: usize = 100;
+
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct FindIncompleteTodosArgs {
+    /// Maximum number of todos to return. Defaults to 100.
+    #[schemars(range(min = 1))]
+    pub limit: Option<usize>,
+    /// Cursor returned by a previous call to fetch the next page of results.
+    pub cursor: Option<String>,
+    /// How to group todos in the report: by 'marker' (default), 'page', 'tag', 'priority', or 'due-week' (ISO week of the scheduled date).
+    pub group_by: Option<TodoGroupBy>,
+    /// Only return todos on this page.
+    pub page: Option<String>,
+    /// Only return todos tagged with, or linking to, this page, e.g. 'work' or '#work'.
+    pub tag: Option<String>,
+    /// Only return todos with this marker: 'TODO', 'DOING', 'LATER', 'NOW', or 'WAITING'.
+    pub marker: Option<String>,
+    /// Only return todos with this priority: 'A', 'B', or 'C' (as written '[#A]').
+    pub priority: Option<String>,
+}
+
+pub struct FindIncompleteTodos;
+
+impl McpTool for FindIncompleteTodos {
+    type Args = FindIncompleteTodosArgs;
+
+    fn name(&self) -> &'static str {
+        "find_incomplete_todos"
+    }
+
+    fn description(&self) -> &'static str {
+        "Search for all incomplete todos across all pages in LogSeq. Returns todos with markers like TODO, DOING, LATER, NOW, and WAITING. Useful for getting an overview of all outstanding tasks and their current status; narrow large graphs with the 'page', 'tag', 'marker', and 'priority' filters. Results are ordered by priority, then scheduled date, then page, and are paginated."
+    }
+
+    fn examples(&self) -> Vec<Value> {
+        vec![json!({"group_by": "page", "tag": "work", "priority": "A"})]
+    }
+
+    fn read_only(&self) -> bool {
+        true
+    }
+
+    async fn execute(
+        &self,
+        ctx: &ToolContext,
+        args: FindIncompleteTodosArgs,
+    ) -> Result<CallToolResult, McpError> {
+        let FindIncompleteTodosArgs {
+            limit,
+            cursor,
+            group_by,
+            page,
+            tag,
+            marker,
+            priority,
+        } = args;
+        let limit = limit.unwrap_or(DEFAULT_TODO_LIMIT).max(1);
+
+        if let Some(page) = &page {
+            ctx.require_page(page).await?;
+        }
+        let filter = TodoFilter {
+            page: page.map(|p| p.trim().to_string()),
+            tag: tag.map(|t| t.trim().trim_start_matches('#').to_string()),
+            marker: marker
+                .map(|m| parse_choice("marker", &m, &INCOMPLETE_MARKERS))
+                .transpose()?,
+            priority: priority
+                .map(|p| parse_choice("priority", &p, &["A", "B", "C"]))
+                .transpose()?,
+        };
+        let todos = ctx
+            .client
+            .find_incomplete_todos(&filter)
+            .await
+            .map_err(logseq_error)?;
+
+        let page = paginate(&todos, limit, cursor.as_deref())
+            .map_err(|e| McpError::invalid_params(e, None))?;
+        if ctx.session.output_format == OutputFormat::Json {
+            return Ok(json_result(
+                &serde_json::json!({
+                    "todos": page.items,
+                    "total": page.total,
+                    "next_cursor": page.next_cursor,
+                }),
+                "todos",
+            ));
+        }
+
+        let mut content_text = format_todos(page.items, group_by.unwrap_or_default());
+        if !todos.is_empty() {
+            content_text.push('\n');
+            content_text.push_str(&format_pagination_footer(&page));
+        }
+
+        Ok(text_result(content_text))
+    }
+}
+
+/// `value` as one of `choices`, ignoring case
+fn parse_choice(what: &str, value: &str, choices: &[&str]) -> Result<String, McpError> {
+    let upper = value.trim().to_uppercase();
+    match choices.contains(&upper.as_str()) {
+        true => Ok(upper),
+        false => Err(McpError::invalid_params(
+            format!(
+                "Invalid {} '{}': expected one of {}",
+                what,
+                value,
+                choices.join(", ")
+            ),
+            Some(serde_json::json!({ what: value })),
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::logseq::mock::MockGraph;
+    use crate::tools::testing::{call_text, serve};
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_find_incomplete_todos_filters() {
+        let mut graph = MockGraph::new();
+        graph.add_page("Work");
+        graph.add_block("Work", "NOW [#A] Ship it #release");
+        graph.add_block("Work", "TODO [#C] Tidy the backlog");
+        graph.add_page("Home");
+        graph.add_block("Home", "TODO [#A] Fix the sink");
+        let (_logseq, ctx) = serve(graph).await;
+
+        let found = |text: &str| -> Vec<&str> {
+            ["Ship it", "Tidy the backlog", "Fix the sink"]
+                .into_iter()
+                .filter(|todo| text.contains(todo))
+                .collect()
+        };
+        for (args, expected) in [
+            (json!({"page": "work"}), vec!["Ship it", "Tidy the backlog"]),
+            (json!({"tag": "#Release"}), vec!["Ship it"]),
+            (
+                json!({"marker": "todo"}),
+                vec!["Tidy the backlog", "Fix the sink"],
+            ),
+            (
+                json!({"priority": "a", "page": "Home"}),
+                vec!["Fix the sink"],
+            ),
+        ] {
+            let text = call_text(&ctx, "find_incomplete_todos", args.clone())
+                .await
+                .unwrap();
+            assert_eq!(found(&text), expected, "{}", args);
+        }
+
+        let err = call_text(&ctx, "find_incomplete_todos", json!({"marker": "DONE"}))
+            .await
+            .unwrap_err();
+        assert!(
+            err.message.contains("expected one of NOW"),
+            "{}",
+            err.message
+        );
+        let err = call_text(&ctx, "find_incomplete_todos", json!({"page": "Wrok"}))
+            .await
+            .unwrap_err();
+        assert!(
+            err.message.contains("Did you mean: Work"),
+            "{}",
+            err.message
+        );
+    }
+}
@@ -0,0 +1,207 @@
+//! The `find_scheduled_tasks` tool
+
+use super::context::{ToolContext, json_result, logseq_error, parse_date_arg, text_result};
+use super::registry::McpTool;
+use crate::logseq::api::ScheduledTask;
+use crate::logseq::journal::{DEFAULT_DATE_FORMAT, format_journal_title};
+use crate::session::OutputFormat;
+use crate::tools::journal_day_to_date;
+use chrono::{Local, NaiveDate};
+use rmcp::{ErrorData as McpError, model::CallToolResult};
+use schemars::JsonSchema;
+use serde::Deserialize;
+use serde_json::{Value, json};
+
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct FindScheduledTasksArgs {
+    /// Only tasks with a scheduled date or deadline on or before this date, as YYYY-MM-DD or a journal page title such as 'Jan 31st, 2024'.
+    pub due_before: Option<String>,
+    /// Only tasks with a scheduled date or deadline on or after this date, in the same formats as due_before.
+    pub due_after: Option<String>,
+    /// Only tasks with a scheduled date or deadline before today. Defaults to false.
+    pub overdue_only: Option<bool>,
+}
+
+pub struct FindScheduledTasks;
+
+impl McpTool for FindScheduledTasks {
+    type Args = FindScheduledTasksArgs;
+
+    fn name(&self) -> &'static str {
+        "find_scheduled_tasks"
+    }
+
+    fn description(&self) -> &'static str {
+        "Find unfinished tasks with a SCHEDULED date or DEADLINE, earliest first. Narrow them to a date range with 'due_after' and 'due_before', or to overdue tasks with 'overdue_only'; a task matches if either of its dates does. Dates are shown in the graph's journal title format."
+    }
+
+    fn examples(&self) -> Vec<Value> {
+        vec![
+            json!({"due_after": "2025-01-01", "due_before": "2025-01-31"}),
+            json!({"overdue_only": true}),
+        ]
+    }
+
+    fn read_only(&self) -> bool {
+        true
+    }
+
+    async fn execute(
+        &self,
+        ctx: &ToolContext,
+        args: FindScheduledTasksArgs,
+    ) -> Result<CallToolResult, McpError> {
+        let FindScheduledTasksArgs {
+            due_before,
+            due_after,
+            overdue_only,
+        } = args;
+        let date_format = ctx
+            .cache
+            .preferred_date_format()
+            .await
+            .unwrap_or_else(|_| DEFAULT_DATE_FORMAT.to_string());
+        let before = due_before
+            .map(|d| parse_date_arg("due_before", &d, &date_format))
+            .transpose()?;
+        let after = due_after
+            .map(|d| parse_date_arg("due_after", &d, &date_format))
+            .transpose()?;
+        let today = Local::now().date_naive();
+        let overdue_only = overdue_only.unwrap_or(false);
+
+        let tasks: Vec<ScheduledTask> = ctx
+            .client
+            .find_scheduled_tasks()
+            .await
+            .map_err(logseq_error)?
+            .into_iter()
+            .filter(|task| {
+                dates(task).any(|date| {
+                    before.is_none_or(|before| date <= before)
+                        && after.is_none_or(|after| date >= after)
+                        && (!overdue_only || date < today)
+                })
+            })
+            .collect();
+
+        if ctx.session.output_format == OutputFormat::Json {
+            return Ok(json_result(
+                &serde_json::json!({ "tasks": tasks, "total": tasks.len() }),
+                "tasks",
+            ));
+        }
+        Ok(text_result(format_tasks(&tasks, &date_format, today)))
+    }
+}
+
+/// The task's scheduled date and deadline, where set
+fn dates(task: &ScheduledTask) -> impl Iterator<Item = NaiveDate> {
+    [task.scheduled, task.deadline]
+        .into_iter()
+        .flatten()
+        .filter_map(journal_day_to_date)
+}
+
+fn format_tasks(tasks: &[ScheduledTask], date_format: &str, today: NaiveDate) -> String {
+    if tasks.is_empty() {
+        return "No scheduled tasks found.".to_string();
+    }
+
+    let format_day = |day: u32| {
+        journal_day_to_date(day)
+            .map(|date| {
+                let title = format_journal_title(date, date_format);
+                match date < today {
+                    true => format!("{} (overdue)", title),
+                    false => title,
+                }
+            })
+            .unwrap_or_else(|| day.to_string())
+    };
+    let mut content = format!("Found {} scheduled tasks:\n\n", tasks.len());
+    for (i, task) in tasks.iter().enumerate() {
+        match &task.marker {
+            Some(marker) => {
+                content.push_str(&format!("{}. **{}** {}\n", i + 1, marker, task.content))
+            }
+            None => content.push_str(&format!("{}. {}\n", i + 1, task.content)),
+        }
+        content.push_str(&format!("   📄 Page: {}\n", task.page_name));
+        if let Some(scheduled) = task.scheduled {
+            content.push_str(&format!("   📅 Scheduled: {}\n", format_day(scheduled)));
+        }
+        if let Some(deadline) = task.deadline {
+            content.push_str(&format!("   ⏰ Deadline: {}\n", format_day(deadline)));
+        }
+        content.push_str(&format!("   🆔 UUID: {}\n\n", task.uuid));
+    }
+    content
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::logseq::mock::MockGraph;
+    use crate::tools::testing::{call_text, serve};
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_find_scheduled_tasks() {
+        let mut graph = MockGraph::new();
+        graph.add_page("Chores");
+        graph.add_block("Chores", "LATER Renew passport\nDEADLINE: <2999-03-01 Fri>");
+        graph.add_block("Chores", "TODO Pay rent\nSCHEDULED: <2020-01-10 Fri>");
+        graph.add_block("Chores", "DONE File taxes\nDEADLINE: <2020-01-05 Sun>");
+        graph.add_block("Chores", "TODO Call mum");
+        graph.add_block("Chores", "Dentist\nSCHEDULED: <2020-02-01 Sat>");
+        let (_logseq, ctx) = serve(graph).await;
+
+        let found = |text: &str| -> Vec<&str> {
+            let mut found: Vec<(usize, &str)> = [
+                "Renew passport",
+                "Pay rent",
+                "File taxes",
+                "Call mum",
+                "Dentist",
+            ]
+            .into_iter()
+            .filter_map(|task| Some((text.find(task)?, task)))
+            .collect();
+            found.sort();
+            found.into_iter().map(|(_, task)| task).collect()
+        };
+        let text = call_text(&ctx, "find_scheduled_tasks", json!({}))
+            .await
+            .unwrap();
+        assert_eq!(found(&text), vec!["Pay rent", "Dentist", "Renew passport"]);
+        assert!(
+            text.contains("Scheduled: Jan 10th, 2020 (overdue)"),
+            "{}",
+            text
+        );
+        assert!(text.contains("Deadline: Mar 1st, 2999\n"), "{}", text);
+
+        for (args, expected) in [
+            (json!({"overdue_only": true}), vec!["Pay rent", "Dentist"]),
+            (
+                json!({"due_after": "2020-01-15", "due_before": "[[Dec 31st, 2020]]"}),
+                vec!["Dentist"],
+            ),
+        ] {
+            let text = call_text(&ctx, "find_scheduled_tasks", args.clone())
+                .await
+                .unwrap();
+            assert_eq!(found(&text), expected, "{}", args);
+        }
+
+        let err = call_text(&ctx, "find_scheduled_tasks", json!({"due_before": "soon"}))
+            .await
+            .unwrap_err();
+        assert!(
+            err.message.contains("Invalid due_before 'soon'"),
+            "{}",
+            err.message
+        );
+    }
+}
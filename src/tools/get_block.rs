@@ -0,0 +1,65 @@
+//! The `get_block` tool
+
+use super::context::{ToolContext, json_result, logseq_error};
+use super::registry::McpTool;
+use crate::tools::prune_depth;
+use rmcp::{ErrorData as McpError, model::CallToolResult};
+use schemars::JsonSchema;
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct GetBlockArgs {
+    /// The UUID of the block to retrieve. UUIDs can be obtained from other API calls like create_block, search, or datascript_query.
+    pub uuid: String,
+    /// Include the block's children as full blocks, nested to 'max_depth' levels. Defaults to false, in which case children are only listed by reference.
+    pub include_children: Option<bool>,
+    /// With include_children, only include this many levels of children (1 = direct children only). Defaults to unlimited.
+    #[schemars(range(min = 1))]
+    pub max_depth: Option<usize>,
+}
+
+pub struct GetBlock;
+
+impl McpTool for GetBlock {
+    type Args = GetBlockArgs;
+
+    fn name(&self) -> &'static str {
+        "get_block"
+    }
+
+    fn description(&self) -> &'static str {
+        "Get detailed information about a specific block by UUID. Returns block content, properties, children, and metadata. Set include_children to get the block's whole subtree, optionally capped with max_depth."
+    }
+
+    fn read_only(&self) -> bool {
+        true
+    }
+
+    async fn execute(
+        &self,
+        ctx: &ToolContext,
+        args: GetBlockArgs,
+    ) -> Result<CallToolResult, McpError> {
+        let GetBlockArgs {
+            uuid,
+            include_children,
+            max_depth,
+        } = args;
+
+        if include_children != Some(true) {
+            let block = ctx.client.get_block(&uuid).await.map_err(logseq_error)?;
+            return Ok(json_result(&block, "block"));
+        }
+
+        let mut block = ctx
+            .client
+            .get_block_tree(&uuid)
+            .await
+            .map_err(logseq_error)?;
+        if let Some(max_depth) = max_depth {
+            prune_depth(&mut block.children, max_depth.max(1));
+        }
+        Ok(json_result(&block, "block"))
+    }
+}
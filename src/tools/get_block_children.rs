@@ -0,0 +1,111 @@
+//! The `get_block_children` tool
+
+use super::context::{ToolContext, json_result, logseq_error, text_result};
+use super::registry::McpTool;
+use crate::session::OutputFormat;
+use crate::tools::{MarkdownOptions, format_blocks_as_markdown_with, prune_depth};
+use rmcp::{ErrorData as McpError, model::CallToolResult};
+use schemars::JsonSchema;
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct GetBlockChildrenArgs {
+    /// The UUID of the block whose children to get.
+    pub uuid: String,
+    /// Only include this many levels of nesting (1 = direct children only); deeper blocks are summarised as a count. Defaults to unlimited.
+    #[schemars(range(min = 1))]
+    pub max_depth: Option<usize>,
+}
+
+pub struct GetBlockChildren;
+
+impl McpTool for GetBlockChildren {
+    type Args = GetBlockChildrenArgs;
+
+    fn name(&self) -> &'static str {
+        "get_block_children"
+    }
+
+    fn description(&self) -> &'static str {
+        "Get the subtree under a block as markdown, with each block's UUID, optionally capped at max_depth levels. Use this to read one section of a large page, such as the notes under a heading, without fetching the whole page."
+    }
+
+    fn read_only(&self) -> bool {
+        true
+    }
+
+    async fn execute(
+        &self,
+        ctx: &ToolContext,
+        args: GetBlockChildrenArgs,
+    ) -> Result<CallToolResult, McpError> {
+        let GetBlockChildrenArgs { uuid, max_depth } = args;
+        let max_depth = max_depth.map(|depth| depth.max(1));
+
+        let mut block = ctx
+            .client
+            .get_block_tree(&uuid)
+            .await
+            .map_err(logseq_error)?;
+
+        if ctx.session.output_format == OutputFormat::Json {
+            let pruned = match max_depth {
+                Some(max_depth) => prune_depth(&mut block.children, max_depth),
+                None => 0,
+            };
+            return Ok(json_result(
+                &serde_json::json!({ "children": block.children, "pruned": pruned }),
+                "block children",
+            ));
+        }
+
+        if block.children.is_empty() {
+            return Ok(text_result(format!("Block {} has no children", uuid)));
+        }
+        let options = MarkdownOptions {
+            include_uuids: true,
+            include_properties: true,
+            max_depth,
+        };
+        Ok(text_result(format_blocks_as_markdown_with(
+            &block.children,
+            &options,
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::logseq::mock::MockGraph;
+    use crate::tools::testing::{call_text, serve};
+    use serde_json::{Value, json};
+
+    #[tokio::test]
+    async fn test_block_subtree_respects_max_depth() {
+        let mut graph = MockGraph::new();
+        graph.add_page("Meeting");
+        let agenda = graph.add_block("Meeting", "Agenda");
+        let budget = graph.add_block(&agenda, "Budget");
+        graph.add_block(&budget, "Q3 numbers");
+        let (_logseq, ctx) = serve(graph).await;
+
+        let args = json!({"uuid": agenda});
+        let text = call_text(&ctx, "get_block_children", args).await.unwrap();
+        assert!(text.contains("Budget"), "{}", text);
+        assert!(text.contains("Q3 numbers"), "{}", text);
+        assert!(text.contains(&format!("id:: {}", budget)), "{}", text);
+
+        let args = json!({"uuid": agenda, "max_depth": 1});
+        let text = call_text(&ctx, "get_block_children", args).await.unwrap();
+        assert!(text.contains("Budget"), "{}", text);
+        assert!(!text.contains("Q3 numbers"), "{}", text);
+        assert!(text.contains("1 nested block(s) not shown"), "{}", text);
+
+        let args = json!({"uuid": agenda, "include_children": true, "max_depth": 1});
+        let text = call_text(&ctx, "get_block", args).await.unwrap();
+        let block: Value = serde_json::from_str(&text).unwrap();
+        assert_eq!(block["children"][0]["content"], "Budget");
+        assert_eq!(block["children"][0]["children"], json!([]));
+    }
+}
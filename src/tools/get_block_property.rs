@@ -0,0 +1,83 @@
+//! The `get_block_property` tool
+
+use super::context::{ToolContext, json_result, logseq_error, require_property_key, text_result};
+use super::registry::McpTool;
+use rmcp::{ErrorData as McpError, model::CallToolResult};
+use schemars::JsonSchema;
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct GetBlockPropertyArgs {
+    /// The UUID of the block to read the property from.
+    pub uuid: String,
+    /// The property key, as written before '::' in the block (e.g. 'status' for 'status:: done').
+    pub key: String,
+}
+
+pub struct GetBlockProperty;
+
+impl McpTool for GetBlockProperty {
+    type Args = GetBlockPropertyArgs;
+
+    fn name(&self) -> &'static str {
+        "get_block_property"
+    }
+
+    fn description(&self) -> &'static str {
+        "Get the value of a single property of a block by UUID and key. Returns the value as JSON, or a note that the property is not set. Cheaper than get_block when only one property is needed."
+    }
+
+    fn read_only(&self) -> bool {
+        true
+    }
+
+    async fn execute(
+        &self,
+        ctx: &ToolContext,
+        args: GetBlockPropertyArgs,
+    ) -> Result<CallToolResult, McpError> {
+        let GetBlockPropertyArgs { uuid, key } = args;
+        require_property_key(&key)?;
+
+        let value = ctx
+            .client
+            .get_block_property(&uuid, &key)
+            .await
+            .map_err(logseq_error)?;
+
+        Ok(match value {
+            Some(value) => json_result(&value, "property value"),
+            None => text_result(format!("Property '{}' is not set on block {}", key, uuid)),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::logseq::mock::MockGraph;
+    use crate::tools::testing::{call_text, serve};
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_property_is_read_or_reported_missing() {
+        let mut graph = MockGraph::new();
+        graph.add_page("Projects");
+        let task = graph.add_block("Projects", "Ship it\nstatus:: done");
+        let (_logseq, ctx) = serve(graph).await;
+
+        let args = json!({"uuid": task, "key": "status"});
+        let text = call_text(&ctx, "get_block_property", args).await.unwrap();
+        assert_eq!(text, "\"done\"");
+
+        let args = json!({"uuid": task, "key": "owner"});
+        let text = call_text(&ctx, "get_block_property", args).await.unwrap();
+        assert_eq!(
+            text,
+            format!("Property 'owner' is not set on block {}", task)
+        );
+
+        let args = json!({"uuid": task, "key": "two words"});
+        assert!(call_text(&ctx, "get_block_property", args).await.is_err());
+    }
+}
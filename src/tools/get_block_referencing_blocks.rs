@@ -0,0 +1,165 @@
+//! The `get_block_referencing_blocks` tool
+
+use super::context::{ToolContext, json_result, logseq_error, text_result};
+use super::registry::McpTool;
+use crate::citations::block_title;
+use crate::logseq::api::ReferencingBlock;
+use crate::logseq::content::block_ref;
+use crate::session::OutputFormat;
+use rmcp::{ErrorData as McpError, model::CallToolResult};
+use schemars::JsonSchema;
+use serde::Deserialize;
+
+/// Referencing blocks listed when no limit is given
+const DEFAULT_LIMIT: usize = 100;
+
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct GetBlockReferencingBlocksArgs {
+    /// The UUID of the referenced block.
+    pub uuid: String,
+    /// Maximum number of referencing blocks to list. Defaults to 100.
+    #[schemars(range(min = 1))]
+    pub limit: Option<usize>,
+}
+
+pub struct GetBlockReferencingBlocks;
+
+impl McpTool for GetBlockReferencingBlocks {
+    type Args = GetBlockReferencingBlocksArgs;
+
+    fn name(&self) -> &'static str {
+        "get_block_referencing_blocks"
+    }
+
+    fn description(&self) -> &'static str {
+        "Find the blocks that reference a block, with '((uuid))' or '{{embed ((uuid))}}': where it's quoted, cited, or embedded. Lists each referencing block's page, UUID, and whether it embeds the block. Use create_block_reference to add a reference."
+    }
+
+    fn read_only(&self) -> bool {
+        true
+    }
+
+    async fn execute(
+        &self,
+        ctx: &ToolContext,
+        args: GetBlockReferencingBlocksArgs,
+    ) -> Result<CallToolResult, McpError> {
+        let GetBlockReferencingBlocksArgs { uuid, limit } = args;
+        let limit = limit.unwrap_or(DEFAULT_LIMIT).max(1);
+        let block = ctx
+            .client
+            .get_block(uuid.trim())
+            .await
+            .map_err(logseq_error)?;
+        let references = ctx
+            .client
+            .find_block_references(&block.uuid)
+            .await
+            .map_err(logseq_error)?;
+        let total = references.len();
+        let shown = &references[..total.min(limit)];
+
+        if ctx.session.output_format == OutputFormat::Json {
+            let shown: Vec<serde_json::Value> = shown
+                .iter()
+                .map(|reference| {
+                    serde_json::json!({
+                        "uuid": reference.uuid,
+                        "content": reference.content,
+                        "page_name": reference.page_name,
+                        "embed": embeds(reference, &block.uuid),
+                    })
+                })
+                .collect();
+            return Ok(json_result(
+                &serde_json::json!({ "uuid": block.uuid, "references": shown, "total": total }),
+                "referencing blocks",
+            ));
+        }
+        if references.is_empty() {
+            return Ok(text_result(format!(
+                "No blocks reference block {}.",
+                block.uuid
+            )));
+        }
+
+        let mut content = format!(
+            "Block {} is referenced by {} block{}:\n\n",
+            block.uuid,
+            total,
+            if total == 1 { "" } else { "s" }
+        );
+        for (i, reference) in shown.iter().enumerate() {
+            content.push_str(&format!(
+                "{}. {}{}\n",
+                i + 1,
+                block_title(&reference.content),
+                if embeds(reference, &block.uuid) {
+                    " (embed)"
+                } else {
+                    ""
+                }
+            ));
+            content.push_str(&format!("   📄 Page: {}\n", reference.page_name));
+            content.push_str(&format!("   🆔 UUID: {}\n\n", reference.uuid));
+        }
+        if shown.len() < total {
+            content.push_str(&format!(
+                "…and {} more; raise 'limit' to list them.\n",
+                total - shown.len()
+            ));
+        }
+        Ok(text_result(content))
+    }
+}
+
+/// Whether a block embeds the block `uuid`, rather than only referencing it
+fn embeds(reference: &ReferencingBlock, uuid: &str) -> bool {
+    reference.content.contains(&block_ref(uuid, true))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::logseq::mock::MockGraph;
+    use crate::tools::testing::{call_text, serve};
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_references_and_embeds_are_found() {
+        let mut graph = MockGraph::new();
+        graph.add_page("Ideas");
+        let source = graph.add_block("Ideas", "Ship smaller changes");
+        let lonely = graph.add_block("Ideas", "Nobody quotes this");
+        graph.add_page("Weekly");
+        graph.add_block("Weekly", &format!("See (({}))", source));
+        graph.add_block("Weekly", &format!("{{{{embed (({}))}}}}", source));
+        let (_logseq, ctx) = serve(graph).await;
+
+        let args = json!({"uuid": source});
+        let text = call_text(&ctx, "get_block_referencing_blocks", args)
+            .await
+            .unwrap();
+        assert!(text.contains("referenced by 2 blocks"), "{}", text);
+        assert!(text.contains("📄 Page: weekly"), "{}", text);
+        assert_eq!(text.matches("(embed)").count(), 1, "{}", text);
+
+        let args = json!({"uuid": source, "limit": 1});
+        let text = call_text(&ctx, "get_block_referencing_blocks", args)
+            .await
+            .unwrap();
+        assert!(text.contains("…and 1 more"), "{}", text);
+
+        let args = json!({"uuid": lonely});
+        let text = call_text(&ctx, "get_block_referencing_blocks", args)
+            .await
+            .unwrap();
+        assert_eq!(text, format!("No blocks reference block {}.", lonely));
+
+        let args = json!({"uuid": "not-a-uuid"});
+        let err = call_text(&ctx, "get_block_referencing_blocks", args)
+            .await
+            .unwrap_err();
+        assert_eq!(err.data.unwrap()["error"], "not_found");
+    }
+}
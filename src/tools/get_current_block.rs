@@ -0,0 +1,29 @@
+//! The `get_current_block` tool
+
+use super::context::{ToolContext, json_result, logseq_error};
+use super::registry::{McpTool, NoArgs};
+use rmcp::{ErrorData as McpError, model::CallToolResult};
+
+pub struct GetCurrentBlock;
+
+impl McpTool for GetCurrentBlock {
+    type Args = NoArgs;
+
+    fn name(&self) -> &'static str {
+        "get_current_block"
+    }
+
+    fn description(&self) -> &'static str {
+        "Get information about the currently active/focused block in the LogSeq interface. Useful for context-aware operations."
+    }
+
+    fn read_only(&self) -> bool {
+        true
+    }
+
+    async fn execute(&self, ctx: &ToolContext, _args: NoArgs) -> Result<CallToolResult, McpError> {
+        let block = ctx.client.get_current_block().await.map_err(logseq_error)?;
+
+        Ok(json_result(&block, "block"))
+    }
+}
@@ -0,0 +1,29 @@
+//! The `get_current_graph` tool
+
+use super::context::{ToolContext, json_result, logseq_error};
+use super::registry::{McpTool, NoArgs};
+use rmcp::{ErrorData as McpError, model::CallToolResult};
+
+pub struct GetCurrentGraph;
+
+impl McpTool for GetCurrentGraph {
+    type Args = NoArgs;
+
+    fn name(&self) -> &'static str {
+        "get_current_graph"
+    }
+
+    fn description(&self) -> &'static str {
+        "Get information about the current LogSeq graph including name, path, and configuration details."
+    }
+
+    fn read_only(&self) -> bool {
+        true
+    }
+
+    async fn execute(&self, ctx: &ToolContext, _args: NoArgs) -> Result<CallToolResult, McpError> {
+        let graph = ctx.cache.current_graph().await.map_err(logseq_error)?;
+
+        Ok(json_result(&*graph, "graph info"))
+    }
+}
@@ -0,0 +1,235 @@
+//! The `get_due_cards` tool
+
+use super::context::{ToolContext, json_result, logseq_error, text_result};
+use super::list_flashcards::review_state;
+use super::registry::McpTool;
+use crate::logseq::api::{Block, Flashcard};
+use crate::logseq::content::strip_property_lines;
+use crate::session::OutputFormat;
+use chrono::{DateTime, Utc};
+use rmcp::{ErrorData as McpError, model::CallToolResult};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// Cards returned when no limit is given
+const DEFAULT_LIMIT: usize = 20;
+
+/// The most cards returned at once
+const MAX_LIMIT: usize = 100;
+
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct GetDueCardsArgs {
+    /// Only review cards on this page (page name).
+    pub page: Option<String>,
+    /// Maximum number of cards to return, most overdue first. Defaults to 20, at most 100.
+    #[schemars(range(min = 1, max = 100))]
+    pub limit: Option<usize>,
+    /// Include cards that have never been reviewed, after the overdue ones. Defaults to true.
+    pub include_new: Option<bool>,
+}
+
+/// A card to review
+#[derive(Debug, Serialize)]
+pub struct DueCard {
+    pub uuid: String,
+    pub page: String,
+    /// The card's content, without properties or the `#card` tag
+    pub question: String,
+    /// The card's child blocks, if it has any. Cloze cards keep their
+    /// answers in the question.
+    pub answer: Option<String>,
+    pub new: bool,
+    pub next_schedule: Option<DateTime<Utc>>,
+    pub last_interval: Option<f64>,
+    pub repeats: Option<u32>,
+    pub ease_factor: Option<f64>,
+}
+
+impl DueCard {
+    fn new(card: &Flashcard, answer: Option<String>) -> Self {
+        Self {
+            uuid: card.uuid.clone(),
+            page: card.page_name.clone(),
+            question: card.question(),
+            answer,
+            new: card.is_new(),
+            next_schedule: card.next_schedule,
+            last_interval: card.last_interval,
+            repeats: card.repeats,
+            ease_factor: card.ease_factor,
+        }
+    }
+}
+
+pub struct GetDueCards;
+
+impl McpTool for GetDueCards {
+    type Args = GetDueCardsArgs;
+
+    fn name(&self) -> &'static str {
+        "get_due_cards"
+    }
+
+    fn description(&self) -> &'static str {
+        "Get the flashcards (blocks tagged #card) due for review, most overdue first, then new cards, for running a review session. Each card has its question, its answer from its child blocks, and its spaced-repetition state; the result's structured content lists the cards as items."
+    }
+
+    fn read_only(&self) -> bool {
+        true
+    }
+
+    async fn execute(
+        &self,
+        ctx: &ToolContext,
+        args: GetDueCardsArgs,
+    ) -> Result<CallToolResult, McpError> {
+        let GetDueCardsArgs {
+            page,
+            limit,
+            include_new,
+        } = args;
+        let limit = limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT);
+        let include_new = include_new.unwrap_or(true);
+        if let Some(page) = &page {
+            ctx.require_page(page).await?;
+        }
+
+        let now = Utc::now();
+        let mut due: Vec<Flashcard> = ctx
+            .client
+            .find_flashcards()
+            .await
+            .map_err(logseq_error)?
+            .into_iter()
+            .filter(|card| {
+                page.as_ref()
+                    .is_none_or(|page| card.page_name == page.to_lowercase())
+            })
+            .filter(|card| card.is_due(now) && (include_new || !card.is_new()))
+            .collect();
+        // Overdue cards by when they fell due, then new ones
+        due.sort_by_key(|card| (card.is_new(), card.next_schedule));
+        let total = due.len();
+
+        let mut shown = Vec::new();
+        for card in due.into_iter().take(limit) {
+            let answer = match ctx.client.get_block_tree(&card.uuid).await {
+                Ok(block) => answer(&block.children),
+                Err(e) => {
+                    tracing::debug!("Couldn't read the answer to card {}: {}", card.uuid, e);
+                    None
+                }
+            };
+            shown.push((card, answer));
+        }
+        let cards: Vec<DueCard> = shown
+            .iter()
+            .map(|(card, answer)| DueCard::new(card, answer.clone()))
+            .collect();
+
+        let items = serde_json::json!({ "cards": cards, "total_due": total });
+        if ctx.session.output_format == OutputFormat::Json {
+            return Ok(json_result(&items, "due cards"));
+        }
+        let mut result = text_result(format_cards(&shown, total, now));
+        result.structured_content = Some(items);
+        Ok(result)
+    }
+}
+
+/// The text of a card's child blocks, as an outline
+fn answer(children: &[Block]) -> Option<String> {
+    fn outline(blocks: &[Block], depth: usize, lines: &mut Vec<String>) {
+        for block in blocks {
+            let content = strip_property_lines(&block.content);
+            let content = content.trim();
+            if !content.is_empty() {
+                lines.push(format!("{}- {}", "  ".repeat(depth), content));
+            }
+            outline(&block.children, depth + 1, lines);
+        }
+    }
+    let mut lines = Vec::new();
+    outline(children, 0, &mut lines);
+    (!lines.is_empty()).then(|| lines.join("\n"))
+}
+
+fn format_cards(cards: &[(Flashcard, Option<String>)], total: usize, now: DateTime<Utc>) -> String {
+    if cards.is_empty() {
+        return "No flashcards are due for review.".to_string();
+    }
+
+    let mut content = format!(
+        "{} card{} due for review{}:\n\n",
+        total,
+        if total == 1 { "" } else { "s" },
+        if cards.len() < total {
+            format!(", showing the first {}", cards.len())
+        } else {
+            String::new()
+        }
+    );
+    for (i, (card, answer)) in cards.iter().enumerate() {
+        content.push_str(&format!("{}. {}\n", i + 1, card.question()));
+        if let Some(answer) = answer {
+            content.push_str("   Answer:\n");
+            for line in answer.lines() {
+                content.push_str(&format!("   {}\n", line));
+            }
+        }
+        content.push_str(&format!("   📄 Page: {}\n", card.page_name));
+        content.push_str(&format!("   🔁 {}\n", review_state(card, now)));
+        content.push_str(&format!("   🆔 UUID: {}\n\n", card.uuid));
+    }
+    content
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::logseq::mock::MockGraph;
+    use crate::tools::registry::ToolRegistry;
+    use crate::tools::testing::{call_text, serve};
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_overdue_cards_come_before_new_ones() {
+        let mut graph = MockGraph::new();
+        graph.add_page("Spanish");
+        let new = graph.add_block("Spanish", "Hola #card");
+        graph.add_block(&new, "Hello");
+        let reviewed = graph.add_block(
+            "Spanish",
+            "¿Cómo estás? #card\ncard-last-interval:: 4\ncard-repeats:: 2\ncard-ease-factor:: 2.6\ncard-next-schedule:: 2020-01-10T08:00:00.000Z\ncard-last-reviewed:: 2020-01-06T08:00:00.000Z",
+        );
+        graph.add_block(&reviewed, "How are you?");
+        graph.add_block(
+            "Spanish",
+            "Adiós #card\ncard-next-schedule:: 2999-01-01T00:00:00.000Z",
+        );
+        let (_logseq, ctx) = serve(graph).await;
+
+        let result = ToolRegistry::builtin()
+            .call(&ctx, "get_due_cards", json!({}).as_object().cloned())
+            .await
+            .unwrap();
+        let items = result.structured_content.unwrap();
+        assert_eq!(items["total_due"], 2);
+        let cards = items["cards"].as_array().unwrap();
+        assert_eq!(cards[0]["uuid"], reviewed);
+        assert_eq!(cards[0]["question"], "¿Cómo estás?");
+        assert_eq!(cards[0]["answer"], "- How are you?");
+        assert_eq!(cards[0]["repeats"], 2);
+        assert_eq!(cards[1]["uuid"], new);
+        assert_eq!(cards[1]["new"], true);
+
+        let text = call_text(&ctx, "get_due_cards", json!({"include_new": false}))
+            .await
+            .unwrap();
+        assert!(text.starts_with("1 card due for review:"), "{}", text);
+        assert!(text.contains("   - How are you?"), "{}", text);
+
+        let args = json!({"page": "French"});
+        assert!(call_text(&ctx, "get_due_cards", args).await.is_err());
+    }
+}
@@ -0,0 +1,88 @@
+//! The `get_editor_context` tool
+
+use super::context::{ToolContext, json_result, logseq_error};
+use super::registry::{McpTool, NoArgs};
+use rmcp::{ErrorData as McpError, model::CallToolResult};
+
+pub struct GetEditorContext;
+
+impl McpTool for GetEditorContext {
+    type Args = NoArgs;
+
+    fn name(&self) -> &'static str {
+        "get_editor_context"
+    }
+
+    fn description(&self) -> &'static str {
+        "Get what the user has open in LogSeq in one call: the current graph, the page on screen, the block being edited with its parent blocks from the top of the page down, and any selected blocks. Fields are null or empty when nothing is open, edited, or selected. Prefer it to calling get_current_graph, get_current_page, and get_current_block separately, which can disagree if the user moves between calls."
+    }
+
+    fn read_only(&self) -> bool {
+        true
+    }
+
+    async fn execute(&self, ctx: &ToolContext, _args: NoArgs) -> Result<CallToolResult, McpError> {
+        let context = ctx
+            .client
+            .get_editor_context()
+            .await
+            .map_err(logseq_error)?;
+
+        Ok(json_result(&context, "editor context"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::logseq::mock::MockGraph;
+    use crate::tools::testing::{call_text, serve};
+    use serde_json::{Value, json};
+
+    #[tokio::test]
+    async fn test_editor_context_in_one_call() {
+        let mut graph = MockGraph::new();
+        graph.add_page("Projects");
+        let top = graph.add_block("Projects", "Alpha");
+        let middle = graph.add_block(&top, "Milestones");
+        let editing = graph.add_block(&middle, "Ship the beta");
+        let (logseq, ctx) = serve(graph).await;
+
+        // Nothing open
+        let text = call_text(&ctx, "get_editor_context", json!({}))
+            .await
+            .unwrap();
+        let context: Value = serde_json::from_str(&text).unwrap();
+        assert_eq!(context["graph"]["name"], "mock");
+        assert!(context["page"].is_null() && context["block"].is_null());
+        assert_eq!(context["parents"], json!([]));
+
+        logseq.respond(
+            "logseq.Editor.getCurrentPage",
+            json!({"name": "projects", "uuid": "page-uuid", "original-name": "Projects"}),
+        );
+        logseq.respond(
+            "logseq.Editor.getCurrentBlock",
+            json!({"uuid": editing, "content": "Ship the beta"}),
+        );
+        logseq.respond(
+            "logseq.Editor.getSelectedBlocks",
+            json!([{"uuid": top, "content": "Alpha"}]),
+        );
+        let text = call_text(&ctx, "get_editor_context", json!({}))
+            .await
+            .unwrap();
+        let context: Value = serde_json::from_str(&text).unwrap();
+        assert_eq!(context["page"]["original-name"], "Projects");
+        assert_eq!(context["block"]["uuid"], editing.as_str());
+        assert_eq!(context["block_page"], "Projects");
+        let parents: Vec<&str> = context["parents"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|p| p["content"].as_str().unwrap())
+            .collect();
+        assert_eq!(parents, ["Alpha", "Milestones"]);
+        assert_eq!(context["parents"][1]["uuid"], middle.as_str());
+        assert_eq!(context["selection"][0]["uuid"], top.as_str());
+    }
+}
@@ -0,0 +1,62 @@
+//! The `get_graph_stats` tool
+
+use super::context::{ToolContext, json_result, logseq_error};
+use super::registry::{McpTool, NoArgs};
+use rmcp::{ErrorData as McpError, model::CallToolResult};
+
+pub struct GetGraphStats;
+
+impl McpTool for GetGraphStats {
+    type Args = NoArgs;
+
+    fn name(&self) -> &'static str {
+        "get_graph_stats"
+    }
+
+    fn description(&self) -> &'static str {
+        "Get counts describing the current LogSeq graph as JSON: pages, journal pages, blocks, tags, tasks by marker (TODO, DONE, and so on), and backlinks (every [[link]], #tag, and ((ref)) from a block). Useful for getting oriented in an unfamiliar graph."
+    }
+
+    fn read_only(&self) -> bool {
+        true
+    }
+
+    async fn execute(&self, ctx: &ToolContext, _args: NoArgs) -> Result<CallToolResult, McpError> {
+        let stats = ctx.client.get_graph_stats().await.map_err(logseq_error)?;
+
+        Ok(json_result(&stats, "graph stats"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::logseq::mock::MockGraph;
+    use crate::tools::testing::{call_text, serve};
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_graph_stats() {
+        let mut graph = MockGraph::new();
+        graph.add_page("Projects");
+        graph.add_block("Projects", "TODO Plan [[Apollo]]");
+        graph.add_block("Projects", "DONE Kickoff #meeting");
+        graph.add_block("Projects", "Notes\ntags:: planning");
+        graph.add_journal_page("Jan 31st, 2024");
+        graph.add_block("Jan 31st, 2024", "TODO Follow up on ((abc))");
+        let (_logseq, ctx) = serve(graph).await;
+
+        let text = call_text(&ctx, "get_graph_stats", json!({})).await.unwrap();
+        let stats: serde_json::Value = serde_json::from_str(&text).unwrap();
+        assert_eq!(
+            stats,
+            json!({
+                "pages": 2,
+                "journal_pages": 1,
+                "blocks": 4,
+                "tags": 1,
+                "todos": {"DONE": 1, "TODO": 2},
+                "backlinks": 3,
+            })
+        );
+    }
+}
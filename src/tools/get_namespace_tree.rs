@@ -0,0 +1,248 @@
+//! The `get_namespace_tree` tool
+
+use super::context::{ToolContext, json_result, logseq_error, text_result};
+use super::registry::McpTool;
+use crate::logseq::api::Page;
+use crate::session::OutputFormat;
+use crate::tools::format_skipped_entries;
+use rmcp::{ErrorData as McpError, model::CallToolResult};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct GetNamespaceTreeArgs {
+    /// The namespace page, such as 'Projects' or 'Projects/Alpha'. A trailing '/' and [[ ]] are ignored.
+    pub namespace: String,
+    /// Only show this many levels below the namespace; pages further down are counted instead. Defaults to every level.
+    #[schemars(range(min = 1))]
+    pub max_depth: Option<usize>,
+}
+
+/// A level of the hierarchy under a namespace
+#[derive(Debug, Serialize)]
+pub struct NamespaceNode {
+    /// The full page name, such as `Projects/Alpha`
+    pub name: String,
+    /// The last part of the name, such as `Alpha`
+    pub title: String,
+    /// False for a level with pages below it but no page of its own
+    pub exists: bool,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub children: Vec<NamespaceNode>,
+    /// Pages below this one left out by `max_depth`
+    #[serde(skip_serializing_if = "is_zero")]
+    pub hidden: usize,
+}
+
+fn is_zero(count: &usize) -> bool {
+    *count == 0
+}
+
+pub struct GetNamespaceTree;
+
+impl McpTool for GetNamespaceTree {
+    type Args = GetNamespaceTreeArgs;
+
+    fn name(&self) -> &'static str {
+        "get_namespace_tree"
+    }
+
+    fn description(&self) -> &'static str {
+        "Show the hierarchy of pages under a namespace, such as 'Projects/Alpha' and 'Projects/Alpha/Tasks' under 'Projects', as an indented list of full page names that can be passed to other tools. Levels with pages below them but no page of their own are marked '(no page)'. Use max_depth to see only the top levels of a large hierarchy."
+    }
+
+    fn read_only(&self) -> bool {
+        true
+    }
+
+    async fn execute(
+        &self,
+        ctx: &ToolContext,
+        args: GetNamespaceTreeArgs,
+    ) -> Result<CallToolResult, McpError> {
+        let namespace = args
+            .namespace
+            .trim()
+            .trim_start_matches("[[")
+            .trim_end_matches("]]")
+            .trim_end_matches('/')
+            .to_string();
+        ctx.require_page(&namespace).await?;
+        let pages = ctx
+            .client
+            .get_pages_from_namespace(&namespace)
+            .await
+            .map_err(logseq_error)?;
+
+        let mut tree = namespace_tree(&namespace, &pages);
+        if let Some(max_depth) = args.max_depth {
+            prune(&mut tree, max_depth.max(1));
+        }
+        if ctx.session.output_format == OutputFormat::Json {
+            return Ok(json_result(
+                &serde_json::json!({
+                    "namespace": namespace,
+                    "pages": pages.len(),
+                    "tree": tree,
+                    "skipped": pages.warnings,
+                }),
+                "namespace tree",
+            ));
+        }
+
+        let mut content = if pages.is_empty() {
+            format!("Namespace '{}' has no pages under it.\n", namespace)
+        } else {
+            format!(
+                "Namespace '{}' has {} page(s) under it:\n",
+                namespace,
+                pages.len()
+            )
+        };
+        format_nodes(&tree, 0, &mut content);
+        content.push_str(&format_skipped_entries(&pages.warnings));
+        Ok(text_result(content))
+    }
+}
+
+/// The pages under `namespace` arranged by the parts of their names, each
+/// level sorted by title
+pub fn namespace_tree(namespace: &str, pages: &[Page]) -> Vec<NamespaceNode> {
+    let depth = namespace.split('/').count();
+    let mut roots: Vec<NamespaceNode> = Vec::new();
+    for page in pages {
+        let parts: Vec<&str> = page.display_name().split('/').collect();
+        if parts.len() <= depth {
+            continue;
+        }
+        let mut level = &mut roots;
+        for end in depth + 1..=parts.len() {
+            let title = parts[end - 1];
+            let index = match level
+                .iter()
+                .position(|node| node.title.to_lowercase() == title.to_lowercase())
+            {
+                Some(index) => index,
+                None => {
+                    level.push(NamespaceNode {
+                        name: parts[..end].join("/"),
+                        title: title.to_string(),
+                        exists: false,
+                        children: Vec::new(),
+                        hidden: 0,
+                    });
+                    level.len() - 1
+                }
+            };
+            let node = &mut level[index];
+            if end == parts.len() {
+                // The page's own name wins over one made up for a level
+                node.name = page.display_name().to_string();
+                node.title = title.to_string();
+                node.exists = true;
+            }
+            level = &mut node.children;
+        }
+    }
+    sort(&mut roots);
+    roots
+}
+
+fn sort(nodes: &mut [NamespaceNode]) {
+    nodes.sort_by_key(|node| node.title.to_lowercase());
+    for node in nodes {
+        sort(&mut node.children);
+    }
+}
+
+/// Replace the levels below `depth` with a count of their pages
+fn prune(nodes: &mut [NamespaceNode], depth: usize) {
+    for node in nodes {
+        if depth == 1 {
+            node.hidden = count_pages(&node.children);
+            node.children.clear();
+        } else {
+            prune(&mut node.children, depth - 1);
+        }
+    }
+}
+
+fn count_pages(nodes: &[NamespaceNode]) -> usize {
+    nodes
+        .iter()
+        .map(|node| usize::from(node.exists) + node.hidden + count_pages(&node.children))
+        .sum()
+}
+
+fn format_nodes(nodes: &[NamespaceNode], indent: usize, content: &mut String) {
+    for node in nodes {
+        content.push_str(&format!("{}- {}", "  ".repeat(indent), node.name));
+        if !node.exists {
+            content.push_str(" (no page)");
+        }
+        if node.hidden > 0 {
+            content.push_str(&format!(" ({} more below)", node.hidden));
+        }
+        content.push('\n');
+        format_nodes(&node.children, indent + 1, content);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::logseq::mock::MockGraph;
+    use crate::tools::testing::{call_text, serve};
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_namespace_tree_fills_in_missing_levels() {
+        let mut graph = MockGraph::new();
+        for name in [
+            "Projects",
+            "Projects/Beta/Notes",
+            "Projects/Alpha",
+            "Projects/Alpha/Tasks",
+            "Projects/Alpha/Tasks/Q3",
+            "Projectsish",
+        ] {
+            graph.add_page(name);
+        }
+        let (_logseq, ctx) = serve(graph).await;
+
+        let text = call_text(
+            &ctx,
+            "get_namespace_tree",
+            json!({"namespace": "[[projects/]]"}),
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            text,
+            "Namespace 'projects' has 4 page(s) under it:\n- Projects/Alpha\n  - Projects/Alpha/Tasks\n    - Projects/Alpha/Tasks/Q3\n- Projects/Beta (no page)\n  - Projects/Beta/Notes\n"
+        );
+
+        let text = call_text(
+            &ctx,
+            "get_namespace_tree",
+            json!({"namespace": "Projects", "max_depth": 1}),
+        )
+        .await
+        .unwrap();
+        assert!(
+            text.contains("- Projects/Alpha (2 more below)\n"),
+            "{}",
+            text
+        );
+        assert!(
+            text.contains("- Projects/Beta (no page) (1 more below)\n"),
+            "{}",
+            text
+        );
+
+        let err = call_text(&ctx, "get_namespace_tree", json!({"namespace": "Projetcs"}))
+            .await
+            .unwrap_err();
+        assert!(err.message.contains("Did you mean"), "{}", err.message);
+    }
+}
@@ -0,0 +1,202 @@
+//! The `get_on_this_day` tool
+
+use super::context::{ToolContext, json_result, logseq_error, parse_date_arg, text_result};
+use super::registry::McpTool;
+use crate::logseq::api::{Block, CreatedBlock};
+use crate::logseq::journal::{DEFAULT_DATE_FORMAT, format_journal_title};
+use crate::session::OutputFormat;
+use crate::tools::{format_blocks_as_markdown, start_of_day};
+use chrono::{Datelike, Days, Local, NaiveDate};
+use rmcp::{ErrorData as McpError, model::CallToolResult};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// How many years back to look by default
+const DEFAULT_YEARS: u32 = 10;
+
+/// The furthest back a single call looks
+const MAX_YEARS: u32 = 50;
+
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct GetOnThisDayArgs {
+    /// The day to look back from, as YYYY-MM-DD or a journal page title such as 'Jan 31st, 2024'. Defaults to today.
+    pub date: Option<String>,
+    /// How many previous years to include. Defaults to 10, at most 50.
+    #[schemars(range(min = 1, max = 50))]
+    pub years: Option<u32>,
+}
+
+/// What was written on the same day in one earlier year
+#[derive(Debug, Serialize)]
+struct YearEntry {
+    date: NaiveDate,
+    /// Title of that day's journal page, if it has one with content
+    journal: Option<String>,
+    journal_blocks: Vec<Block>,
+    /// Blocks created that day on other pages
+    created: Vec<CreatedBlock>,
+}
+
+pub struct GetOnThisDay;
+
+impl McpTool for GetOnThisDay {
+    type Args = GetOnThisDayArgs;
+
+    fn name(&self) -> &'static str {
+        "get_on_this_day"
+    }
+
+    fn description(&self) -> &'static str {
+        "Look back at what was written on this day (or a given date) in previous years: each year's journal page for the same month and day, and blocks created that day on other pages, grouped by year, most recent first. Good for review and reflection prompts. A February 29th is only matched in leap years."
+    }
+
+    fn read_only(&self) -> bool {
+        true
+    }
+
+    async fn execute(
+        &self,
+        ctx: &ToolContext,
+        args: GetOnThisDayArgs,
+    ) -> Result<CallToolResult, McpError> {
+        let date_format = ctx
+            .cache
+            .preferred_date_format()
+            .await
+            .unwrap_or_else(|_| DEFAULT_DATE_FORMAT.to_string());
+        let date = match &args.date {
+            Some(date) => parse_date_arg("date", date, &date_format)?,
+            None => Local::now().date_naive(),
+        };
+        let years = args.years.unwrap_or(DEFAULT_YEARS).clamp(1, MAX_YEARS);
+
+        let mut entries = Vec::new();
+        for year in (1..=years).filter_map(|back| date.year().checked_sub(back as i32)) {
+            let Some(day) = date.with_year(year) else {
+                continue;
+            };
+            let title = format_journal_title(day, &date_format);
+            let mut journal_blocks = ctx
+                .client
+                .get_page_blocks_tree(&title)
+                .await
+                .map_err(logseq_error)?
+                .items;
+            // LogSeq gives a journal page one empty block until it's written in
+            if journal_blocks
+                .iter()
+                .all(|b| b.content.trim().is_empty() && b.children.is_empty())
+            {
+                journal_blocks.clear();
+            }
+            let created = ctx
+                .client
+                .get_blocks_created_between(start_of_day(day), start_of_day(day + Days::new(1)))
+                .await
+                .map_err(logseq_error)?
+                .into_iter()
+                .filter(|b| !b.page_name.eq_ignore_ascii_case(&title))
+                .collect::<Vec<_>>();
+            if journal_blocks.is_empty() && created.is_empty() {
+                continue;
+            }
+            entries.push(YearEntry {
+                date: day,
+                journal: (!journal_blocks.is_empty()).then_some(title),
+                journal_blocks,
+                created,
+            });
+        }
+
+        if ctx.session.output_format == OutputFormat::Json {
+            return Ok(json_result(
+                &serde_json::json!({ "date": date, "years": entries }),
+                "entries",
+            ));
+        }
+        Ok(text_result(format_entries(&entries, date, &date_format)))
+    }
+}
+
+fn format_entries(entries: &[YearEntry], date: NaiveDate, date_format: &str) -> String {
+    let day = date.format("%B %-d");
+    if entries.is_empty() {
+        return format!("Nothing was written on {} in previous years.", day);
+    }
+
+    let mut content = format!("On this day, {}:\n", day);
+    for entry in entries {
+        let ago = date.year() - entry.date.year();
+        content.push_str(&format!(
+            "\n## {} ({} year{} ago)\n",
+            entry.date.year(),
+            ago,
+            if ago == 1 { "" } else { "s" }
+        ));
+        if let Some(journal) = &entry.journal {
+            content.push_str(&format!("### Journal: [[{}]]\n", journal));
+            content.push_str(&format_blocks_as_markdown(&entry.journal_blocks));
+        }
+        if !entry.created.is_empty() {
+            content.push_str(&format!(
+                "### Also written on {}\n",
+                format_journal_title(entry.date, date_format)
+            ));
+            for block in &entry.created {
+                content.push_str(&format!(
+                    "- {} (on [[{}]])\n",
+                    block.content.lines().next().unwrap_or_default(),
+                    block.page_name
+                ));
+            }
+        }
+    }
+    content
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::logseq::mock::MockGraph;
+    use crate::tools::testing::{call_text, noon, serve};
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_on_this_day_groups_by_year() {
+        let mut graph = MockGraph::new();
+        graph.add_journal_page("Mar 14th, 2023");
+        graph.add_block("Mar 14th, 2023", "Pi day picnic");
+        graph.add_journal_page("Mar 14th, 2021");
+        graph.add_block("Mar 14th, 2021", "");
+        graph.add_journal_page("Mar 15th, 2022");
+        graph.add_block("Mar 15th, 2022", "The day after");
+        graph.add_page("Projects");
+        for (date, content) in [
+            ("2022-03-14", "Started the garden"),
+            ("2022-03-15", "Watered it"),
+        ] {
+            let uuid = graph.add_block("Projects", content);
+            graph.set_created_at(&uuid, noon(date));
+        }
+        let (_logseq, ctx) = serve(graph).await;
+
+        let args = json!({"date": "2024-03-14", "years": 5});
+        let text = call_text(&ctx, "get_on_this_day", args).await.unwrap();
+        let picnic = text.find("## 2023 (1 year ago)").unwrap();
+        let garden = text.find("## 2022 (2 years ago)").unwrap();
+        assert!(picnic < garden, "{}", text);
+        assert!(
+            text.contains("### Journal: [[Mar 14th, 2023]]\n* Pi day picnic"),
+            "{}",
+            text
+        );
+        assert!(
+            text.contains("- Started the garden (on [[Projects]])"),
+            "{}",
+            text
+        );
+        assert!(!text.contains("2021"), "{}", text);
+        assert!(!text.contains("Watered it"), "{}", text);
+        assert!(!text.contains("The day after"), "{}", text);
+    }
+}
@@ -0,0 +1,50 @@
+//! The `get_page` tool
+
+use super::context::{ToolContext, json_result, logseq_error};
+use super::registry::McpTool;
+use rmcp::{ErrorData as McpError, model::CallToolResult};
+use schemars::JsonSchema;
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct GetPageArgs {
+    /// The page name (case-sensitive) or UUID. Use page names as they appear in LogSeq, or the UUID from other API calls.
+    pub name_or_uuid: String,
+}
+
+pub struct GetPage;
+
+impl McpTool for GetPage {
+    type Args = GetPageArgs;
+
+    fn name(&self) -> &'static str {
+        "get_page"
+    }
+
+    fn description(&self) -> &'static str {
+        "Get detailed information about a specific page by name or UUID. Returns page metadata including properties, UUID, and structure."
+    }
+
+    fn read_only(&self) -> bool {
+        true
+    }
+
+    async fn execute(
+        &self,
+        ctx: &ToolContext,
+        args: GetPageArgs,
+    ) -> Result<CallToolResult, McpError> {
+        let GetPageArgs { name_or_uuid } = args;
+
+        ctx.require_page(&name_or_uuid).await?;
+
+        let page = ctx
+            .client
+            .get_page(&name_or_uuid)
+            .await
+            .map_err(logseq_error)?;
+
+        Ok(json_result(&page, "page"))
+    }
+}
@@ -0,0 +1,146 @@
+//! The `get_page_checksums` tool
+
+use super::context::{ToolContext, json_result, logseq_error};
+use super::registry::McpTool;
+use crate::logseq::checksum::tree_checksums;
+use rmcp::{ErrorData as McpError, model::CallToolResult};
+use schemars::JsonSchema;
+use serde::Deserialize;
+use serde_json::{Value, json};
+use std::sync::Arc;
+
+/// Key of checksums in the query cache, which no Datascript query can clash
+/// with
+const CACHE_KEY: &str = "page-checksums";
+
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct GetPageChecksumsArgs {
+    /// The name or UUID of the page
+    pub page_name: String,
+    /// The digest from an earlier call. If the page hasn't changed since, only the digest is returned, with 'unchanged': true.
+    pub since: Option<String>,
+    /// Read the page again even if its checksums were computed in the last 30 seconds. Defaults to false.
+    pub refresh: Option<bool>,
+}
+
+pub struct GetPageChecksums;
+
+impl McpTool for GetPageChecksums {
+    type Args = GetPageChecksumsArgs;
+
+    fn name(&self) -> &'static str {
+        "get_page_checksums"
+    }
+
+    fn description(&self) -> &'static str {
+        "Get a checksum of each block's content on a page, with its UUID and parent, and a digest of the whole page. Use it to poll a page for changes cheaply: pass the previous digest as 'since' to learn only whether anything changed, and compare block checksums to find which blocks did, then fetch just those. Checksums are cached for 30 seconds unless 'refresh' is true, and reset whenever this server changes the graph."
+    }
+
+    fn examples(&self) -> Vec<Value> {
+        vec![
+            json!({"page_name": "Projects"}),
+            json!({"page_name": "Projects", "since": "9f3c2a4b7d1e8f60"}),
+        ]
+    }
+
+    fn read_only(&self) -> bool {
+        true
+    }
+
+    async fn execute(
+        &self,
+        ctx: &ToolContext,
+        args: GetPageChecksumsArgs,
+    ) -> Result<CallToolResult, McpError> {
+        let GetPageChecksumsArgs {
+            page_name,
+            since,
+            refresh,
+        } = args;
+        let key = [Value::from(page_name.trim().to_lowercase())];
+        let cached = match refresh {
+            Some(true) => None,
+            _ => ctx.queries.get(CACHE_KEY, &key),
+        };
+        let checksums = match cached {
+            Some((checksums, _)) => checksums,
+            None => {
+                ctx.require_page(&page_name).await?;
+                let blocks = ctx
+                    .client
+                    .get_page_blocks_tree(&page_name)
+                    .await
+                    .map_err(logseq_error)?;
+                let (digest, sums) = tree_checksums(&blocks.items);
+                let mut checksums = json!({
+                    "page": page_name,
+                    "digest": digest,
+                    "blocks": sums,
+                });
+                if !blocks.warnings.is_empty() {
+                    checksums["skipped"] = json!(blocks.warnings);
+                }
+                let checksums = Arc::new(checksums);
+                ctx.queries.insert(CACHE_KEY, &key, checksums.clone());
+                checksums
+            }
+        };
+
+        if since.as_deref().map(str::trim) == checksums["digest"].as_str() {
+            return Ok(json_result(
+                &json!({
+                    "page": checksums["page"],
+                    "digest": checksums["digest"],
+                    "unchanged": true,
+                }),
+                "page checksums",
+            ));
+        }
+        Ok(json_result(&*checksums, "page checksums"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::logseq::mock::MockGraph;
+    use crate::tools::testing::{call_text, serve};
+    use serde_json::{Value, json};
+
+    #[tokio::test]
+    async fn test_page_checksums_show_what_changed() {
+        let mut graph = MockGraph::new();
+        graph.add_page("Notes");
+        let first = graph.add_block("Notes", "First");
+        let second = graph.add_block("Notes", "Second");
+        let (_logseq, ctx) = serve(graph).await;
+        let checksums = |args: Value| {
+            let ctx = ctx.clone();
+            async move {
+                let text = call_text(&ctx, "get_page_checksums", args).await.unwrap();
+                serde_json::from_str::<Value>(&text).unwrap()
+            }
+        };
+
+        let before = checksums(json!({"page_name": "Notes"})).await;
+        assert_eq!(before["blocks"].as_array().unwrap().len(), 2);
+        assert_eq!(before["blocks"][0]["uuid"], first.as_str());
+        let digest = before["digest"].as_str().unwrap();
+        let unchanged = checksums(json!({"page_name": "notes", "since": digest})).await;
+        assert_eq!(unchanged["unchanged"], true);
+        assert!(unchanged.get("blocks").is_none());
+
+        // Changes made through the server aren't hidden by the cache
+        call_text(
+            &ctx,
+            "update_block",
+            json!({"uuid": second, "content": "Second, edited"}),
+        )
+        .await
+        .unwrap();
+        let after = checksums(json!({"page_name": "Notes", "since": digest})).await;
+        assert_ne!(after["digest"], before["digest"]);
+        assert_eq!(after["blocks"][0]["hash"], before["blocks"][0]["hash"]);
+        assert_ne!(after["blocks"][1]["hash"], before["blocks"][1]["hash"]);
+    }
+}
@@ -0,0 +1,325 @@
+//! The `get_page_content` tool
+
+use super::context::{ToolContext, json_result, logseq_error, text_result};
+use super::registry::McpTool;
+use crate::logseq::api::Block;
+use crate::logseq::assets::{asset_file, file_url, image_mime_type, links, rewrite_asset_links};
+use crate::logseq::content::strip_property_lines;
+use crate::session::OutputFormat;
+use crate::tools::{
+    MarkdownOptions, count_blocks, format_blocks_as_markdown_with, format_skipped_entries,
+};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use rmcp::{
+    ErrorData as McpError,
+    model::{CallToolResult, Content, ResourceContents},
+};
+use schemars::JsonSchema;
+use serde::Deserialize;
+use std::path::Path;
+
+/// The largest image attached by `inline_images`
+const MAX_INLINE_IMAGE_BYTES: u64 = 256 * 1024;
+
+/// The most images attached to one page's content
+const MAX_INLINE_IMAGES: usize = 10;
+
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct GetPageContentArgs {
+    /// The name or UUID of the page. Page names are case-sensitive and should match exactly as they appear in LogSeq.
+    pub page_name: String,
+    /// Add an 'id:: <uuid>' line to every block so specific blocks can be updated, moved, or referenced. Defaults to false.
+    pub include_uuids: Option<bool>,
+    /// Keep 'key:: value' property lines in block content. Set false for a more compact read. Defaults to true.
+    pub include_properties: Option<bool>,
+    /// Only show this many levels of nesting (1 = top-level blocks only); deeper blocks are summarised as a count. Defaults to unlimited.
+    #[schemars(range(min = 1))]
+    pub max_depth: Option<usize>,
+    /// Rewrite links to files in the graph's assets folder, such as '![](../assets/image.png)', to file:// URLs that resolve outside LogSeq. Defaults to true.
+    pub resolve_assets: Option<bool>,
+    /// Also attach the page's asset images of up to 256 KB as base64 resources, for clients that can show them. Defaults to false.
+    pub inline_images: Option<bool>,
+}
+
+pub struct GetPageContent;
+
+impl McpTool for GetPageContent {
+    type Args = GetPageContentArgs;
+
+    fn name(&self) -> &'static str {
+        "get_page_content"
+    }
+
+    fn description(&self) -> &'static str {
+        "Get the content of a specific page formatted as markdown. Use this to read and understand the structure of a page's blocks and content. Set include_uuids to get the block UUIDs needed to edit specific blocks. Links to the graph's assets, such as images, are given as file:// URLs; set inline_images to also attach small images."
+    }
+
+    fn read_only(&self) -> bool {
+        true
+    }
+
+    async fn execute(
+        &self,
+        ctx: &ToolContext,
+        args: GetPageContentArgs,
+    ) -> Result<CallToolResult, McpError> {
+        let GetPageContentArgs {
+            page_name,
+            include_uuids,
+            include_properties,
+            max_depth,
+            resolve_assets,
+            inline_images,
+        } = args;
+        let options = MarkdownOptions {
+            include_uuids: include_uuids.unwrap_or(false),
+            include_properties: include_properties.unwrap_or(true),
+            max_depth: max_depth.map(|depth| depth.max(1)),
+        };
+        let _guard = ctx.page_locks.lock(&page_name).await;
+
+        ctx.require_page(&page_name).await?;
+
+        let mut blocks = ctx
+            .client
+            .get_page_blocks_tree(&page_name)
+            .await
+            .map_err(logseq_error)?;
+
+        if ctx.session.output_format == OutputFormat::Json {
+            let hidden = prune_blocks(&mut blocks.items, &options, 0);
+            return Ok(json_result(
+                &serde_json::json!({
+                    "blocks": blocks.items,
+                    "skipped": blocks.warnings,
+                    "hidden_blocks": hidden,
+                }),
+                "page content",
+            ));
+        }
+
+        let mut content_text = format_blocks_as_markdown_with(&blocks, &options);
+        content_text.push_str(&format_skipped_entries(&blocks.warnings));
+
+        let resolve_assets = resolve_assets.unwrap_or(true);
+        let inline_images = inline_images.unwrap_or(false);
+        if !resolve_assets && !inline_images {
+            return Ok(text_result(content_text));
+        }
+        let Some(graph_dir) = ctx.graph_dir().await else {
+            return Ok(text_result(content_text));
+        };
+
+        let (images, skipped) = if inline_images {
+            attach_images(&content_text, &graph_dir).await
+        } else {
+            Default::default()
+        };
+        if resolve_assets {
+            content_text = rewrite_asset_links(&content_text, &graph_dir);
+        }
+        if !skipped.is_empty() {
+            content_text.push_str(&format!(
+                "\n\nImages not attached: {}\n",
+                skipped.join(", ")
+            ));
+        }
+
+        let mut result = text_result(content_text);
+        if let Some(content) = result.content.as_mut() {
+            content.extend(images);
+        }
+        Ok(result)
+    }
+}
+
+/// Apply `options` to blocks returned as JSON, as the markdown output does:
+/// dropping property lines and properties unless they're wanted, and the
+/// blocks below `max_depth`. Returns how many blocks were dropped.
+fn prune_blocks(blocks: &mut [Block], options: &MarkdownOptions, depth: usize) -> usize {
+    let mut hidden = 0;
+    for block in blocks {
+        if !options.include_properties {
+            block.content = strip_property_lines(&block.content);
+            block.properties = None;
+        }
+        if options
+            .max_depth
+            .is_some_and(|max_depth| depth + 1 >= max_depth)
+        {
+            hidden += count_blocks(&block.children);
+            block.children.clear();
+        } else {
+            hidden += prune_blocks(&mut block.children, options, depth + 1);
+        }
+    }
+    hidden
+}
+
+/// The asset images linked from `text` that can be attached, and why the
+/// others can't
+async fn attach_images(text: &str, graph_dir: &Path) -> (Vec<Content>, Vec<String>) {
+    let mut images = Vec::new();
+    let mut skipped = Vec::new();
+    let mut seen = Vec::new();
+    for link in links(text).into_iter().filter(|link| link.image) {
+        let Some(file) = asset_file(graph_dir, link.target) else {
+            continue;
+        };
+        let Some(mime_type) = image_mime_type(&file) else {
+            continue;
+        };
+        if seen.contains(&file) {
+            continue;
+        }
+        seen.push(file.clone());
+        let name = link.target.trim();
+
+        if images.len() == MAX_INLINE_IMAGES {
+            skipped.push(format!("{} (more than {} images)", name, MAX_INLINE_IMAGES));
+            continue;
+        }
+        match tokio::fs::metadata(&file).await {
+            Ok(metadata) if metadata.len() > MAX_INLINE_IMAGE_BYTES => {
+                skipped.push(format!(
+                    "{} (over {} KB)",
+                    name,
+                    MAX_INLINE_IMAGE_BYTES / 1024
+                ));
+                continue;
+            }
+            Ok(_) => {}
+            Err(_) => {
+                skipped.push(format!("{} (not found on this machine)", name));
+                continue;
+            }
+        }
+        match tokio::fs::read(&file).await {
+            Ok(bytes) => images.push(Content::resource(ResourceContents::BlobResourceContents {
+                uri: file_url(&file),
+                mime_type: Some(mime_type.to_string()),
+                blob: STANDARD.encode(bytes),
+            })),
+            Err(e) => skipped.push(format!("{} ({})", name, e)),
+        }
+    }
+    (images, skipped)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::logseq::mock::{MockGraph, MockLogSeq};
+    use crate::session::{OutputFormat, SessionConfig};
+    use crate::tools::context::ToolContext;
+    use crate::tools::registry::ToolRegistry;
+    use crate::tools::testing::call_text;
+    use rmcp::model::RawContent;
+    use serde_json::json;
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn test_page_content_resolves_assets() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("assets")).unwrap();
+        std::fs::write(dir.path().join("assets/chart.png"), b"not really a png").unwrap();
+
+        let mut graph = MockGraph::new();
+        graph.add_page("Report");
+        graph.add_block("Report", "![chart](../assets/chart.png)");
+        graph.add_block("Report", "![gone](../assets/gone.png) [[Elsewhere]]");
+        let logseq = MockLogSeq::serve(graph).await.unwrap();
+        logseq.respond(
+            "logseq.App.getCurrentGraph",
+            json!({"name": "report", "path": dir.path()}),
+        );
+        let ctx = ToolContext::new(logseq.client());
+        let chart_url = crate::logseq::assets::file_url(&dir.path().join("assets/chart.png"));
+
+        let text = call_text(&ctx, "get_page_content", json!({"page_name": "Report"}))
+            .await
+            .unwrap();
+        assert!(
+            text.contains(&format!("![chart]({})", chart_url)),
+            "{}",
+            text
+        );
+        assert!(text.contains("[[Elsewhere]]"), "{}", text);
+
+        let result = ToolRegistry::builtin()
+            .call(
+                &ctx,
+                "get_page_content",
+                json!({"page_name": "Report", "resolve_assets": false, "inline_images": true})
+                    .as_object()
+                    .cloned(),
+            )
+            .await
+            .unwrap();
+        let content = result.content.unwrap();
+        assert_eq!(content.len(), 2);
+        let RawContent::Text(text) = &content[0].raw else {
+            panic!("expected text first");
+        };
+        assert!(
+            text.text.contains("![chart](../assets/chart.png)"),
+            "{}",
+            text.text
+        );
+        assert!(
+            text.text
+                .contains("Images not attached: ../assets/gone.png (not found on this machine)"),
+            "{}",
+            text.text
+        );
+        let RawContent::Resource(image) = &content[1].raw else {
+            panic!("expected the image as a resource");
+        };
+        assert_eq!(
+            serde_json::to_value(&image.resource).unwrap(),
+            json!({
+                "uri": chart_url,
+                "mimeType": "image/png",
+                "blob": "bm90IHJlYWxseSBhIHBuZw==",
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_json_content_is_pruned_like_markdown() {
+        let mut graph = MockGraph::new();
+        graph.add_page("Plan");
+        let goal = graph.add_block("Plan", "Launch\nowner:: Sam");
+        let step = graph.add_block(&goal, "Write the post");
+        graph.add_block(&step, "Draft the intro");
+        let logseq = MockLogSeq::serve(graph).await.unwrap();
+        let mut ctx = ToolContext::new(logseq.client());
+        ctx.session = Arc::new(SessionConfig {
+            output_format: OutputFormat::Json,
+            ..Default::default()
+        });
+
+        let args = json!({"page_name": "Plan", "max_depth": 2, "include_properties": false});
+        let text = call_text(&ctx, "get_page_content", args).await.unwrap();
+        let page: serde_json::Value = serde_json::from_str(&text).unwrap();
+        assert_eq!(page["hidden_blocks"], 1);
+        let blocks = page["blocks"].as_array().unwrap();
+        assert_eq!(blocks[0]["content"], "Launch");
+        assert_eq!(blocks[0]["properties"], serde_json::Value::Null);
+        let children = blocks[0]["children"].as_array().unwrap();
+        assert_eq!(children[0]["content"], "Write the post");
+        assert_eq!(children[0]["children"], json!([]));
+
+        let text = call_text(&ctx, "get_page_content", json!({"page_name": "Plan"}))
+            .await
+            .unwrap();
+        let page: serde_json::Value = serde_json::from_str(&text).unwrap();
+        assert_eq!(page["hidden_blocks"], 0);
+        assert_eq!(page["blocks"][0]["content"], "Launch\nowner:: Sam");
+        assert_eq!(
+            page["blocks"][0]["children"][0]["children"][0]["content"],
+            "Draft the intro"
+        );
+    }
+}
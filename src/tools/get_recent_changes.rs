@@ -0,0 +1,248 @@
+//! The `get_recent_changes` tool
+
+use super::context::{ToolContext, json_result, logseq_error, text_result};
+use super::registry::McpTool;
+use crate::logseq::api::ChangedBlock;
+use crate::session::OutputFormat;
+use chrono::{DateTime, Local, TimeZone, Utc};
+use rmcp::{ErrorData as McpError, model::CallToolResult};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// Hours covered when neither `hours` nor `days` is given
+const DEFAULT_HOURS: u32 = 24;
+
+/// Blocks listed per page when `max_blocks_per_page` isn't given
+const DEFAULT_BLOCKS_PER_PAGE: usize = 10;
+
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct GetRecentChangesArgs {
+    /// Report changes made in the last this many hours. Defaults to 24. Give either 'hours' or 'days', not both.
+    #[schemars(range(min = 1))]
+    pub hours: Option<u32>,
+    /// Report changes made in the last this many days.
+    #[schemars(range(min = 1))]
+    pub days: Option<u32>,
+    /// List at most this many changed blocks for each page, most recent first. The rest are counted. Defaults to 10.
+    #[schemars(range(min = 1))]
+    pub max_blocks_per_page: Option<usize>,
+}
+
+/// One page's changes, as returned in JSON
+#[derive(Debug, Serialize)]
+struct PageChanges {
+    page: String,
+    last_updated: DateTime<Utc>,
+    /// How many of the page's blocks changed, including those not listed
+    changed_blocks: usize,
+    blocks: Vec<BlockChange>,
+}
+
+#[derive(Debug, Serialize)]
+struct BlockChange {
+    uuid: String,
+    content: String,
+    updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize)]
+struct RecentChanges {
+    since: DateTime<Utc>,
+    pages: Vec<PageChanges>,
+}
+
+pub struct GetRecentChanges;
+
+impl McpTool for GetRecentChanges {
+    type Args = GetRecentChangesArgs;
+
+    fn name(&self) -> &'static str {
+        "get_recent_changes"
+    }
+
+    fn description(&self) -> &'static str {
+        "List the blocks changed in the last N hours or days (the last 24 hours by default), grouped by page with the most recently changed page first. Answers questions like 'what did I work on yesterday?'. Blocks count as changed when they were created or edited."
+    }
+
+    fn read_only(&self) -> bool {
+        true
+    }
+
+    async fn execute(
+        &self,
+        ctx: &ToolContext,
+        args: GetRecentChangesArgs,
+    ) -> Result<CallToolResult, McpError> {
+        let hours = match (args.hours, args.days) {
+            (Some(_), Some(_)) => {
+                return Err(McpError::invalid_params(
+                    "Give either 'hours' or 'days', not both",
+                    None,
+                ));
+            }
+            (Some(hours), None) => hours,
+            (None, Some(days)) => days.saturating_mul(24),
+            (None, None) => DEFAULT_HOURS,
+        };
+        if hours == 0 {
+            return Err(McpError::invalid_params(
+                "The period to report on must be at least an hour",
+                None,
+            ));
+        }
+        let since = Utc::now() - chrono::Duration::hours(i64::from(hours));
+
+        let blocks = ctx
+            .client
+            .get_blocks_updated_since(since.timestamp_millis())
+            .await
+            .map_err(logseq_error)?;
+        let changes = RecentChanges {
+            since,
+            pages: group_by_page(
+                blocks,
+                args.max_blocks_per_page
+                    .unwrap_or(DEFAULT_BLOCKS_PER_PAGE)
+                    .max(1),
+            ),
+        };
+
+        if ctx.session.output_format == OutputFormat::Json {
+            return Ok(json_result(&changes, "recent changes"));
+        }
+        Ok(text_result(format_changes(&changes, hours)))
+    }
+}
+
+/// Group changed blocks, most recent first, by page, keeping the order: the
+/// page changed most recently comes first
+fn group_by_page(blocks: Vec<ChangedBlock>, per_page: usize) -> Vec<PageChanges> {
+    let mut pages: Vec<PageChanges> = Vec::new();
+    for block in blocks {
+        let updated_at = to_utc(block.updated_at);
+        let index = match pages.iter().position(|p| p.page == block.page_name) {
+            Some(index) => index,
+            None => {
+                pages.push(PageChanges {
+                    page: block.page_name.clone(),
+                    last_updated: updated_at,
+                    changed_blocks: 0,
+                    blocks: Vec::new(),
+                });
+                pages.len() - 1
+            }
+        };
+        let page = &mut pages[index];
+        page.changed_blocks += 1;
+        if page.blocks.len() < per_page {
+            page.blocks.push(BlockChange {
+                uuid: block.uuid,
+                content: block.content,
+                updated_at,
+            });
+        }
+    }
+    pages
+}
+
+fn to_utc(millis: i64) -> DateTime<Utc> {
+    Utc.timestamp_millis_opt(millis)
+        .single()
+        .unwrap_or_default()
+}
+
+fn format_changes(changes: &RecentChanges, hours: u32) -> String {
+    let period = match hours {
+        1 => "hour".to_string(),
+        h if h > 24 && h % 24 == 0 => format!("{} days", h / 24),
+        h => format!("{} hours", h),
+    };
+    if changes.pages.is_empty() {
+        return format!("Nothing has changed in the last {}.", period);
+    }
+
+    let total: usize = changes.pages.iter().map(|p| p.changed_blocks).sum();
+    let mut content = format!(
+        "{} block{} changed on {} page{} in the last {}:\n",
+        total,
+        if total == 1 { "" } else { "s" },
+        changes.pages.len(),
+        if changes.pages.len() == 1 { "" } else { "s" },
+        period
+    );
+    for page in &changes.pages {
+        content.push_str(&format!(
+            "\n## [[{}]] (last changed {})\n",
+            page.page,
+            local_time(page.last_updated)
+        ));
+        for block in &page.blocks {
+            content.push_str(&format!(
+                "- {} {} (`{}`)\n",
+                local_time(block.updated_at),
+                block.content.lines().next().unwrap_or_default().trim(),
+                block.uuid
+            ));
+        }
+        let unlisted = page.changed_blocks - page.blocks.len();
+        if unlisted > 0 {
+            content.push_str(&format!("- …and {} more\n", unlisted));
+        }
+    }
+    content
+}
+
+fn local_time(time: DateTime<Utc>) -> String {
+    time.with_timezone(&Local)
+        .format("%Y-%m-%d %H:%M")
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::logseq::mock::MockGraph;
+    use crate::tools::testing::{call_text, serve};
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_recent_changes_are_grouped_by_page() {
+        let mut graph = MockGraph::new();
+        graph.add_page("Projects");
+        graph.add_page("Journal");
+        let old = graph.add_block("Projects", "Untouched for a week");
+        let edited = graph.add_block("Projects", "Launch plan");
+        let new = graph.add_block("Journal", "Met with Sam");
+        let now = chrono::Utc::now().timestamp_millis();
+        graph.set_updated_at(&old, now - 7 * 24 * 3_600_000);
+        graph.set_updated_at(&edited, now - 3_600_000);
+        graph.set_updated_at(&new, now - 60_000);
+        let (_logseq, ctx) = serve(graph).await;
+
+        let text = call_text(&ctx, "get_recent_changes", json!({}))
+            .await
+            .unwrap();
+        assert!(
+            text.starts_with("2 blocks changed on 2 pages in the last 24 hours"),
+            "{}",
+            text
+        );
+        let journal = text.find("## [[Journal]]").unwrap();
+        let projects = text.find("## [[Projects]]").unwrap();
+        assert!(journal < projects, "{}", text);
+        assert!(
+            text.contains(&format!("Launch plan (`{}`)", edited)),
+            "{}",
+            text
+        );
+        assert!(!text.contains("Untouched"), "{}", text);
+
+        let text = call_text(&ctx, "get_recent_changes", json!({"days": 8}))
+            .await
+            .unwrap();
+        assert!(text.contains("Untouched"), "{}", text);
+
+        let err = call_text(&ctx, "get_recent_changes", json!({"hours": 1, "days": 1})).await;
+        assert!(err.is_err());
+    }
+}
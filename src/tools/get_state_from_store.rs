@@ -0,0 +1,48 @@
+//! The `get_state_from_store` tool
+
+use super::context::{ToolContext, json_result, logseq_error};
+use super::registry::McpTool;
+use rmcp::{ErrorData as McpError, model::CallToolResult};
+use schemars::JsonSchema;
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct GetStateFromStoreArgs {
+    /// State key path to retrieve from LogSeq's application store. Examples: 'ui/theme', 'ui/sidebar-open', 'config/preferred-format'.
+    pub key: String,
+}
+
+pub struct GetStateFromStore;
+
+impl McpTool for GetStateFromStore {
+    type Args = GetStateFromStoreArgs;
+
+    fn name(&self) -> &'static str {
+        "get_state_from_store"
+    }
+
+    fn description(&self) -> &'static str {
+        "Get application state from the LogSeq store using a key path (e.g., 'ui/theme', 'ui/sidebar-open'). Useful for accessing LogSeq's internal application state."
+    }
+
+    fn read_only(&self) -> bool {
+        true
+    }
+
+    async fn execute(
+        &self,
+        ctx: &ToolContext,
+        args: GetStateFromStoreArgs,
+    ) -> Result<CallToolResult, McpError> {
+        let GetStateFromStoreArgs { key } = args;
+
+        let state = ctx
+            .client
+            .get_state_from_store(&key)
+            .await
+            .map_err(logseq_error)?;
+
+        Ok(json_result(&state, "state"))
+    }
+}
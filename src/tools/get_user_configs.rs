@@ -0,0 +1,29 @@
+//! The `get_user_configs` tool
+
+use super::context::{ToolContext, json_result, logseq_error};
+use super::registry::{McpTool, NoArgs};
+use rmcp::{ErrorData as McpError, model::CallToolResult};
+
+pub struct GetUserConfigs;
+
+impl McpTool for GetUserConfigs {
+    type Args = NoArgs;
+
+    fn name(&self) -> &'static str {
+        "get_user_configs"
+    }
+
+    fn description(&self) -> &'static str {
+        "Get user configuration settings for the LogSeq application. Returns the current user preferences and configuration options."
+    }
+
+    fn read_only(&self) -> bool {
+        true
+    }
+
+    async fn execute(&self, ctx: &ToolContext, _args: NoArgs) -> Result<CallToolResult, McpError> {
+        let configs = ctx.cache.user_configs().await.map_err(logseq_error)?;
+
+        Ok(json_result(&*configs, "configs"))
+    }
+}
@@ -0,0 +1,167 @@
+//! The `get_writing_stats` tool
+
+use super::context::{ToolContext, json_result, logseq_error, parse_date_arg, text_result};
+use super::registry::McpTool;
+use crate::logseq::journal::{DEFAULT_DATE_FORMAT, format_journal_title};
+use crate::logseq::writing::{WritingStats, writing_stats};
+use crate::session::OutputFormat;
+use crate::tools::start_of_day;
+use chrono::{Days, Local};
+use rmcp::{ErrorData as McpError, model::CallToolResult};
+use schemars::JsonSchema;
+use serde::Deserialize;
+
+/// Days covered when no start date is given, counting the end date
+const DEFAULT_DAYS: u64 = 30;
+
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct GetWritingStatsArgs {
+    /// First day to report on, as YYYY-MM-DD or a journal page title such as 'Jan 31st, 2024'. Defaults to 30 days before 'to'.
+    pub from: Option<String>,
+    /// Last day to report on, inclusive, in the same formats as 'from'. Defaults to today.
+    pub to: Option<String>,
+}
+
+pub struct GetWritingStats;
+
+impl McpTool for GetWritingStats {
+    type Args = GetWritingStatsArgs;
+
+    fn name(&self) -> &'static str {
+        "get_writing_stats"
+    }
+
+    fn description(&self) -> &'static str {
+        "Report on writing habits over a date range (the last 30 days by default): words and blocks written per day, the current and longest streaks of days with writing, and the most active pages. Blocks count towards the day they were created, in the server's local time zone; words in property lines are not counted."
+    }
+
+    fn read_only(&self) -> bool {
+        true
+    }
+
+    async fn execute(
+        &self,
+        ctx: &ToolContext,
+        args: GetWritingStatsArgs,
+    ) -> Result<CallToolResult, McpError> {
+        let date_format = ctx
+            .cache
+            .preferred_date_format()
+            .await
+            .unwrap_or_else(|_| DEFAULT_DATE_FORMAT.to_string());
+        let to = match &args.to {
+            Some(to) => parse_date_arg("to", to, &date_format)?,
+            None => Local::now().date_naive(),
+        };
+        let from = match &args.from {
+            Some(from) => parse_date_arg("from", from, &date_format)?,
+            None => to - Days::new(DEFAULT_DAYS - 1),
+        };
+        if from > to {
+            return Err(McpError::invalid_params(
+                format!("'from' ({}) is after 'to' ({})", from, to),
+                Some(serde_json::json!({ "from": args.from, "to": args.to })),
+            ));
+        }
+
+        let blocks = ctx
+            .client
+            .get_blocks_created_between(start_of_day(from), start_of_day(to + Days::new(1)))
+            .await
+            .map_err(logseq_error)?;
+        let stats = writing_stats(&blocks, from, to, &Local);
+
+        if ctx.session.output_format == OutputFormat::Json {
+            return Ok(json_result(&stats, "writing stats"));
+        }
+        Ok(text_result(format_stats(&stats, &date_format)))
+    }
+}
+
+fn format_stats(stats: &WritingStats, date_format: &str) -> String {
+    let mut content = format!(
+        "Writing from {} to {}:\n\n",
+        format_journal_title(stats.from, date_format),
+        format_journal_title(stats.to, date_format)
+    );
+    if stats.blocks == 0 {
+        content.push_str("Nothing was written in this period.\n");
+        return content;
+    }
+
+    let days = (stats.to - stats.from).num_days() + 1;
+    content.push_str(&format!(
+        "- Words: {} in {} blocks\n- Active days: {} of {}\n- Current streak: {} days\n- Longest streak: {} days\n",
+        stats.words,
+        stats.blocks,
+        stats.days.len(),
+        days,
+        stats.current_streak,
+        stats.longest_streak
+    ));
+
+    content.push_str("\n## By day\n");
+    for day in &stats.days {
+        content.push_str(&format!(
+            "- {}: {} words, {} blocks\n",
+            format_journal_title(day.date, date_format),
+            day.words,
+            day.blocks
+        ));
+    }
+
+    content.push_str("\n## Most active pages\n");
+    for (i, page) in stats.pages.iter().enumerate() {
+        content.push_str(&format!(
+            "{}. {}: {} words, {} blocks\n",
+            i + 1,
+            page.page,
+            page.words,
+            page.blocks
+        ));
+    }
+    content
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::logseq::mock::MockGraph;
+    use crate::tools::testing::{call_text, noon, serve};
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_writing_stats_cover_the_requested_days() {
+        let mut graph = MockGraph::new();
+        graph.add_page("Journal");
+        let written = [
+            ("2024-03-01", "Morning pages, three words"),
+            ("2024-03-02", "Short"),
+            ("2024-03-02", "Another entry\ntags:: writing"),
+            ("2024-03-05", "Too late to count"),
+        ];
+        for (date, content) in written {
+            let uuid = graph.add_block("Journal", content);
+            graph.set_created_at(&uuid, noon(date));
+        }
+        let (_logseq, ctx) = serve(graph).await;
+
+        let args = json!({"from": "2024-03-01", "to": "[[Mar 3rd, 2024]]"});
+        let text = call_text(&ctx, "get_writing_stats", args).await.unwrap();
+        assert!(text.contains("Words: 7 in 3 blocks"), "{}", text);
+        assert!(text.contains("Active days: 2 of 3"), "{}", text);
+        assert!(text.contains("Current streak: 2 days"), "{}", text);
+        assert!(
+            text.contains("- Mar 2nd, 2024: 3 words, 2 blocks"),
+            "{}",
+            text
+        );
+        assert!(text.contains("1. Journal: 7 words, 3 blocks"), "{}", text);
+
+        let args = json!({"from": "2024-03-04", "to": "2024-03-01"});
+        let err = call_text(&ctx, "get_writing_stats", args)
+            .await
+            .unwrap_err();
+        assert!(err.message.contains("is after"), "{}", err.message);
+    }
+}
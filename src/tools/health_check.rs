@@ -0,0 +1,107 @@
+//! The `health_check` tool
+
+use super::context::{ToolContext, json_result, text_result};
+use super::registry::{McpTool, NoArgs};
+use crate::logseq::api::Health;
+use crate::session::OutputFormat;
+use rmcp::{ErrorData as McpError, model::CallToolResult};
+use serde::Serialize;
+
+/// The outcome of a health check, as returned in JSON
+#[derive(Debug, Serialize)]
+struct Report<'a> {
+    /// The server's name for the graph checked
+    graph: &'a str,
+    healthy: bool,
+    #[serde(flatten)]
+    health: Option<Health>,
+    /// What's wrong, when LogSeq isn't healthy
+    error: Option<String>,
+}
+
+pub struct HealthCheck;
+
+impl McpTool for HealthCheck {
+    type Args = NoArgs;
+
+    fn name(&self) -> &'static str {
+        "health_check"
+    }
+
+    fn description(&self) -> &'static str {
+        "Check that LogSeq is reachable and accepts the server's API token, reporting how long it took to answer, LogSeq's version, and the graph it has open. When other tools fail with connection errors, run this to find out what's wrong."
+    }
+
+    fn read_only(&self) -> bool {
+        true
+    }
+
+    // Reporting that LogSeq is down is the point, not a failure
+    fn needs_logseq(&self) -> bool {
+        false
+    }
+
+    async fn execute(&self, ctx: &ToolContext, _args: NoArgs) -> Result<CallToolResult, McpError> {
+        let check = match ctx.client.check_health().await {
+            Ok(health) => Report {
+                graph: &ctx.graph,
+                healthy: true,
+                health: Some(health),
+                error: None,
+            },
+            Err(e) => Report {
+                graph: &ctx.graph,
+                healthy: false,
+                health: None,
+                error: Some(e.to_string()),
+            },
+        };
+
+        if ctx.session.output_format == OutputFormat::Json {
+            return Ok(json_result(&check, "health check"));
+        }
+        Ok(text_result(format_check(&check)))
+    }
+}
+
+fn format_check(check: &Report) -> String {
+    let Some(health) = &check.health else {
+        return format!(
+            "✗ LogSeq is not healthy: {}",
+            check.error.as_deref().unwrap_or("unknown error")
+        );
+    };
+    let mut content = format!("✓ LogSeq answered in {} ms", health.latency_ms);
+    if let Some(version) = &health.version {
+        content.push_str(&format!("\nVersion: {}", version));
+    }
+    if let Some(graph) = &health.graph {
+        content.push_str(&format!("\nOpen graph: {}", graph));
+    }
+    content
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::logseq::api::LogSeqClient;
+    use crate::logseq::mock::MockGraph;
+    use crate::tools::context::ToolContext;
+    use crate::tools::testing::{call_text, serve};
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_health_check_reports_version_or_what_is_wrong() {
+        let (logseq, ctx) = serve(MockGraph::new()).await;
+        let text = call_text(&ctx, "health_check", json!({})).await.unwrap();
+        assert!(text.contains("Version: 0.10.9"), "{}", text);
+        assert!(text.contains("Open graph: mock"), "{}", text);
+
+        let ctx = ToolContext::new(LogSeqClient::new(logseq.url(), "wrong-token").unwrap());
+        let text = call_text(&ctx, "health_check", json!({})).await.unwrap();
+        assert!(text.contains("rejected the API token"), "{}", text);
+
+        let ctx = ToolContext::new(LogSeqClient::new("http://127.0.0.1:9", "token").unwrap());
+        let text = call_text(&ctx, "health_check", json!({})).await.unwrap();
+        assert!(text.contains("can't be reached"), "{}", text);
+    }
+}
@@ -0,0 +1,169 @@
+//! The `import_markdown` tool
+
+use super::context::{ToolContext, internal_error, logseq_error, text_result};
+use super::registry::McpTool;
+use crate::intents::InverseOp;
+use crate::logseq::api::CreatePageOptions;
+use crate::logseq::markdown::{BatchBlock, parse_document};
+use crate::logseq::page_name::normalize_page_name;
+use crate::quota::QuotaKind;
+use rmcp::{ErrorData as McpError, model::CallToolResult};
+use schemars::JsonSchema;
+use serde::Deserialize;
+use serde_json::{Value, json};
+
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct ImportMarkdownArgs {
+    /// The name of the new page. It must not already exist; the same naming rules as create_page apply.
+    pub page: String,
+    /// The markdown document to import. Headings, paragraphs, lists, code fences, and tables are supported; YAML front matter of 'key: value' lines becomes page properties.
+    pub markdown: String,
+}
+
+pub struct ImportMarkdown;
+
+impl McpTool for ImportMarkdown {
+    type Args = ImportMarkdownArgs;
+
+    fn name(&self) -> &'static str {
+        "import_markdown"
+    }
+
+    fn description(&self) -> &'static str {
+        "Create a page from a markdown document, split into a block tree rather than one giant block: each heading becomes a block with the content of its section nested under it, and each paragraph, list item, code fence, and table becomes a block of its own. Nested and numbered lists keep their structure. Use this instead of create_block for whole documents."
+    }
+
+    fn examples(&self) -> Vec<Value> {
+        vec![json!({
+            "page": "Launch Plan",
+            "markdown": "---\nstatus: draft\n---\n# Goals\n- Ship by March\n- Stay within budget\n\n# Risks\nSupplier delays."
+        })]
+    }
+
+    fn destructive(&self) -> bool {
+        false
+    }
+
+    async fn execute(
+        &self,
+        ctx: &ToolContext,
+        args: ImportMarkdownArgs,
+    ) -> Result<CallToolResult, McpError> {
+        let ImportMarkdownArgs { page, markdown } = args;
+        let blocks = parse_document(&markdown);
+        if blocks.is_empty() {
+            return Err(McpError::invalid_params(
+                "The markdown document is empty",
+                None,
+            ));
+        }
+
+        let title = normalize_page_name(&page)
+            .map_err(|e| McpError::invalid_params(e.to_string(), None))?;
+        let title = ctx.page_to_create(&title);
+        ctx.charge_blocks(Some(&title), &blocks)?;
+        let _guard = ctx.page_locks.lock(&title).await;
+        if ctx
+            .client
+            .find_page(&title)
+            .await
+            .map_err(logseq_error)?
+            .is_some()
+        {
+            return Err(McpError::invalid_params(
+                format!("A page named '{}' already exists", title),
+                Some(serde_json::json!({ "page": title })),
+            ));
+        }
+
+        ctx.charge_quota(QuotaKind::PagesCreated, 1)?;
+        let mut intent = ctx.begin_intent(
+            self.name(),
+            vec![
+                format!("create page '{}'", title),
+                format!("write {} block(s) to page '{}'", count(&blocks), title),
+            ],
+        )?;
+        let created = ctx
+            .client
+            .create_page(&title, None, CreatePageOptions::default())
+            .await
+            .map_err(logseq_error)?;
+        ctx.cache.invalidate_pages();
+        intent.done(
+            0,
+            vec![InverseOp::DeletePage {
+                name: title.clone(),
+            }],
+        );
+        ctx.client
+            .append_batch_block(&title, &blocks)
+            .await
+            .map_err(|e| {
+                internal_error(format!(
+                    "Created page '{}' but failed to import the document's blocks: {}",
+                    title, e
+                ))
+            })?;
+        // Deleting the page undoes this too
+        intent.done(1, Vec::new());
+        intent.finish();
+
+        Ok(text_result(format!(
+            "Imported {} block(s) into new page: {}",
+            count(&blocks),
+            created.name
+        )))
+    }
+}
+
+/// Blocks in a tree, counting children
+pub(crate) fn count(blocks: &[BatchBlock]) -> usize {
+    blocks.iter().map(|b| 1 + count(&b.children)).sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::logseq::mock::MockGraph;
+    use crate::tools::testing::{call_text, serve};
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_markdown_document_is_imported_as_blocks() {
+        let (logseq, ctx) = serve(MockGraph::new()).await;
+
+        let markdown = "# Plan\nWhy we do it.\n\n- Step one\n  - Detail\n- Step two\n# Notes\n";
+        let args = json!({"page": "Roadmap", "markdown": markdown});
+        let text = call_text(&ctx, "import_markdown", args.clone())
+            .await
+            .unwrap();
+        assert!(text.contains("Imported 6 block(s)"), "{}", text);
+
+        logseq.with_graph(|g| {
+            let content = |uuid: &String| g.block_content(uuid).unwrap().to_string();
+            let roots = g.children("Roadmap");
+            assert_eq!(
+                roots.iter().map(content).collect::<Vec<_>>(),
+                ["# Plan", "# Notes"]
+            );
+            let plan = g.children(&roots[0]);
+            assert_eq!(
+                plan.iter().map(content).collect::<Vec<_>>(),
+                ["Why we do it.", "Step one", "Step two"]
+            );
+            assert_eq!(content(&g.children(&plan[1])[0]), "Detail");
+        });
+
+        let err = call_text(&ctx, "import_markdown", args).await.unwrap_err();
+        assert!(err.message.contains("already exists"), "{}", err.message);
+        let err = call_text(
+            &ctx,
+            "import_markdown",
+            json!({"page": "Empty", "markdown": "\n"}),
+        )
+        .await
+        .unwrap_err();
+        assert!(err.message.contains("empty"), "{}", err.message);
+    }
+}
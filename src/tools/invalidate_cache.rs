@@ -0,0 +1,62 @@
+//! The `invalidate_cache` tool
+
+use super::context::{ToolContext, text_result};
+use super::registry::{McpTool, NoArgs};
+use rmcp::{ErrorData as McpError, model::CallToolResult};
+
+pub struct InvalidateCache;
+
+impl McpTool for InvalidateCache {
+    type Args = NoArgs;
+
+    fn name(&self) -> &'static str {
+        "invalidate_cache"
+    }
+
+    fn description(&self) -> &'static str {
+        "Forget the server's cached page list, tags, user configs, current graph, and datascript query results, so the next call fetches them from LogSeq again. Use this after editing the graph in LogSeq itself, or when a listing looks out of date; changes made through this server's tools already do this."
+    }
+
+    fn read_only(&self) -> bool {
+        true
+    }
+
+    fn needs_logseq(&self) -> bool {
+        false
+    }
+
+    async fn execute(&self, ctx: &ToolContext, _args: NoArgs) -> Result<CallToolResult, McpError> {
+        ctx.cache.clear();
+        ctx.queries.clear();
+        Ok(text_result("Cache cleared"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::logseq::mock::{MockGraph, MockLogSeq};
+    use crate::tools::context::ToolContext;
+    use crate::tools::testing::call_text;
+    use serde_json::json;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_invalidate_cache_refetches_pages() {
+        let mut graph = MockGraph::new();
+        graph.add_page("Inbox");
+        let logseq = MockLogSeq::serve(graph).await.unwrap();
+        let ctx = ToolContext::with_cache_ttl(logseq.client(), Duration::from_secs(60));
+
+        let text = call_text(&ctx, "list_pages", json!({})).await.unwrap();
+        assert!(text.contains("inbox"));
+        logseq.with_graph(|g| g.add_page("Written in LogSeq"));
+        let text = call_text(&ctx, "list_pages", json!({})).await.unwrap();
+        assert!(!text.contains("written in logseq"));
+
+        call_text(&ctx, "invalidate_cache", json!({}))
+            .await
+            .unwrap();
+        let text = call_text(&ctx, "list_pages", json!({})).await.unwrap();
+        assert!(text.contains("written in logseq"));
+    }
+}
@@ -0,0 +1,130 @@
+//! The `list_all_tags` tool
+
+use super::context::{ToolContext, json_result, logseq_error, text_result};
+use super::registry::{McpTool, NoArgs};
+use crate::logseq::content::{extract_tags, format_tag};
+use crate::session::OutputFormat;
+use rmcp::{ErrorData as McpError, model::CallToolResult};
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// How often one tag is used
+#[derive(Debug, Default, Serialize)]
+struct TagCount {
+    name: String,
+    /// Pages setting it in their `tags::` property
+    pages: usize,
+    /// Blocks setting it in their `tags::` property
+    blocks: usize,
+    /// Blocks mentioning it as `#tag` or `#[[tag]]`
+    inline: usize,
+}
+
+impl TagCount {
+    fn total(&self) -> usize {
+        self.pages + self.blocks + self.inline
+    }
+}
+
+pub struct ListAllTags;
+
+impl McpTool for ListAllTags {
+    type Args = NoArgs;
+
+    fn name(&self) -> &'static str {
+        "list_all_tags"
+    }
+
+    fn description(&self) -> &'static str {
+        "List every tag in the graph, most used first, with how many pages and blocks set it in a 'tags::' property and how many blocks use it inline as #tag or #[[tag]]. Tag names are lower-cased, as LogSeq's page names are. Reads every block, so it can be slow on large graphs."
+    }
+
+    fn read_only(&self) -> bool {
+        true
+    }
+
+    async fn execute(&self, ctx: &ToolContext, _args: NoArgs) -> Result<CallToolResult, McpError> {
+        let usage = ctx.cache.tag_usage().await.map_err(logseq_error)?;
+        let corpus = ctx.client.get_corpus(None).await.map_err(logseq_error)?;
+
+        let mut counts: BTreeMap<String, TagCount> = BTreeMap::new();
+        for entry in usage.iter() {
+            for tag in &entry.tags {
+                match entry.page {
+                    Some(_) => count(&mut counts, tag).pages += 1,
+                    None => count(&mut counts, tag).blocks += 1,
+                }
+            }
+        }
+        for block in &corpus {
+            for tag in extract_tags(&block.content) {
+                count(&mut counts, &tag).inline += 1;
+            }
+        }
+        let mut tags: Vec<TagCount> = counts.into_values().collect();
+        // Stable, so equally used tags stay in name order
+        tags.sort_by_key(|tag| std::cmp::Reverse(tag.total()));
+
+        if ctx.session.output_format == OutputFormat::Json {
+            return Ok(json_result(&tags, "tags"));
+        }
+        if tags.is_empty() {
+            return Ok(text_result("No tags are used in this graph."));
+        }
+        let mut content = format!("Found {} tag(s):\n", tags.len());
+        for tag in &tags {
+            content.push_str(&format!(
+                "- {}: {} use(s) ({} page(s), {} block(s) by property; {} inline)\n",
+                format_tag(&tag.name),
+                tag.total(),
+                tag.pages,
+                tag.blocks,
+                tag.inline
+            ));
+        }
+        Ok(text_result(content))
+    }
+}
+
+/// The count for a tag, starting from zero the first time it's seen
+fn count<'a>(counts: &'a mut BTreeMap<String, TagCount>, name: &str) -> &'a mut TagCount {
+    counts.entry(name.to_string()).or_insert_with(|| TagCount {
+        name: name.to_string(),
+        ..Default::default()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::logseq::mock::MockGraph;
+    use crate::tools::testing::{call_text, serve};
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_tags_are_counted_most_used_first() {
+        let (_logseq, ctx) = serve(MockGraph::new()).await;
+        let text = call_text(&ctx, "list_all_tags", json!({})).await.unwrap();
+        assert_eq!(text, "No tags are used in this graph.");
+
+        let mut graph = MockGraph::new();
+        graph.add_page("Notes");
+        graph.add_block("Notes", "tags:: rust, web");
+        graph.add_block("Notes", "Learning #rust today");
+        graph.add_block("Notes", "Reading #[[Big Ideas]]");
+        let (_logseq, ctx) = serve(graph).await;
+
+        let text = call_text(&ctx, "list_all_tags", json!({})).await.unwrap();
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines[0], "Found 3 tag(s):", "{}", text);
+        assert_eq!(
+            lines[1],
+            "- #rust: 2 use(s) (0 page(s), 1 block(s) by property; 1 inline)"
+        );
+        assert!(
+            lines[2].starts_with("- #[[big ideas]]: 1 use(s)"),
+            "{}",
+            text
+        );
+        assert!(lines[3].starts_with("- #web: 1 use(s)"), "{}", text);
+    }
+}
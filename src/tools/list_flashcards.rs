@@ -0,0 +1,166 @@
+//! The `list_flashcards` tool
+
+use super::context::{ToolContext, json_result, logseq_error, text_result};
+use super::registry::McpTool;
+use crate::logseq::api::Flashcard;
+use crate::session::OutputFormat;
+use chrono::{DateTime, Local, Utc};
+use rmcp::{ErrorData as McpError, model::CallToolResult};
+use schemars::JsonSchema;
+use serde::Deserialize;
+
+/// Cards listed when no limit is given
+const DEFAULT_LIMIT: usize = 100;
+
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct ListFlashcardsArgs {
+    /// Only list cards on this page (page name).
+    pub page: Option<String>,
+    /// Maximum number of cards to list. Defaults to 100.
+    #[schemars(range(min = 1))]
+    pub limit: Option<usize>,
+}
+
+pub struct ListFlashcards;
+
+impl McpTool for ListFlashcards {
+    type Args = ListFlashcardsArgs;
+
+    fn name(&self) -> &'static str {
+        "list_flashcards"
+    }
+
+    fn description(&self) -> &'static str {
+        "List the flashcards in the graph: blocks tagged #card, with their spaced-repetition state (next review, interval, repeats, and ease factor, from their card-* properties). Use get_due_cards to run a review session."
+    }
+
+    fn read_only(&self) -> bool {
+        true
+    }
+
+    async fn execute(
+        &self,
+        ctx: &ToolContext,
+        args: ListFlashcardsArgs,
+    ) -> Result<CallToolResult, McpError> {
+        let ListFlashcardsArgs { page, limit } = args;
+        let limit = limit.unwrap_or(DEFAULT_LIMIT).max(1);
+        if let Some(page) = &page {
+            ctx.require_page(page).await?;
+        }
+
+        let cards: Vec<Flashcard> = ctx
+            .client
+            .find_flashcards()
+            .await
+            .map_err(logseq_error)?
+            .into_iter()
+            .filter(|card| {
+                page.as_ref()
+                    .is_none_or(|page| card.page_name == page.to_lowercase())
+            })
+            .collect();
+        let now = Utc::now();
+        let total = cards.len();
+        let due = cards.iter().filter(|card| card.is_due(now)).count();
+        let new = cards.iter().filter(|card| card.is_new()).count();
+        let shown = &cards[..total.min(limit)];
+
+        if ctx.session.output_format == OutputFormat::Json {
+            return Ok(json_result(
+                &serde_json::json!({ "cards": shown, "total": total, "due": due, "new": new }),
+                "flashcards",
+            ));
+        }
+        if cards.is_empty() {
+            return Ok(text_result(
+                "No flashcards found. Tag a block #card to make one.",
+            ));
+        }
+
+        let mut content = format!(
+            "Found {} flashcard{}, {} due now ({} new):\n\n",
+            total,
+            if total == 1 { "" } else { "s" },
+            due,
+            new
+        );
+        for (i, card) in shown.iter().enumerate() {
+            content.push_str(&format!("{}. {}\n", i + 1, card.question()));
+            content.push_str(&format!("   📄 Page: {}\n", card.page_name));
+            content.push_str(&format!("   🔁 {}\n", review_state(card, now)));
+            content.push_str(&format!("   🆔 UUID: {}\n\n", card.uuid));
+        }
+        if shown.len() < total {
+            content.push_str(&format!(
+                "…and {} more; raise 'limit' to list them.\n",
+                total - shown.len()
+            ));
+        }
+        Ok(text_result(content))
+    }
+}
+
+/// When a card is due, and how its reviews have gone
+pub(crate) fn review_state(card: &Flashcard, now: DateTime<Utc>) -> String {
+    let Some(next) = card.next_schedule else {
+        return "New, not reviewed yet".to_string();
+    };
+    let when = next.with_timezone(&Local).format("%Y-%m-%d %H:%M");
+    let mut state = match next <= now {
+        true => format!("Due since {}", when),
+        false => format!("Next review {}", when),
+    };
+    if let Some(interval) = card.last_interval {
+        state.push_str(&format!(", every {} days", interval));
+    }
+    if let Some(repeats) = card.repeats {
+        state.push_str(&format!(", {} repeats", repeats));
+    }
+    if let Some(ease) = card.ease_factor {
+        state.push_str(&format!(", ease {}", ease));
+    }
+    state
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::logseq::mock::MockGraph;
+    use crate::tools::testing::{call_text, serve};
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_flashcards_are_listed_with_their_review_state() {
+        let mut graph = MockGraph::new();
+        graph.add_page("Spanish");
+        let reviewed = graph.add_block(
+            "Spanish",
+            "¿Cómo estás? #card\ncard-last-interval:: 4\ncard-repeats:: 2\ncard-ease-factor:: 2.6\ncard-next-schedule:: 2020-01-10T08:00:00.000Z\ncard-last-reviewed:: 2020-01-06T08:00:00.000Z",
+        );
+        graph.add_block(&reviewed, "How are you?");
+        let new = graph.add_block("Spanish", "Hola #card");
+        graph.add_block(&new, "Hello");
+        graph.add_block(
+            "Spanish",
+            "Adiós #card\ncard-next-schedule:: 2999-01-01T00:00:00.000Z",
+        );
+        graph.add_block("Spanish", "Not a card");
+        let (_logseq, ctx) = serve(graph).await;
+
+        let text = call_text(&ctx, "list_flashcards", json!({"page": "Spanish"}))
+            .await
+            .unwrap();
+        assert!(
+            text.contains("Found 3 flashcards, 2 due now (1 new)"),
+            "{}",
+            text
+        );
+        assert!(
+            text.contains("every 4 days, 2 repeats, ease 2.6"),
+            "{}",
+            text
+        );
+        assert!(!text.contains("Not a card"), "{}", text);
+    }
+}
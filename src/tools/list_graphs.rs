@@ -0,0 +1,60 @@
+//! The `list_graphs` tool
+
+use super::context::{ToolContext, text_result};
+use super::registry::{McpTool, NoArgs};
+use rmcp::{ErrorData as McpError, model::CallToolResult};
+
+pub struct ListGraphs;
+
+impl McpTool for ListGraphs {
+    type Args = NoArgs;
+
+    fn name(&self) -> &'static str {
+        "list_graphs"
+    }
+
+    fn description(&self) -> &'static str {
+        "List the LogSeq graphs this server is connected to. Any tool can work on one of them by passing its name as 'graph'; without it, tools use the default graph."
+    }
+
+    fn read_only(&self) -> bool {
+        true
+    }
+
+    fn needs_logseq(&self) -> bool {
+        false
+    }
+
+    async fn execute(&self, ctx: &ToolContext, _args: NoArgs) -> Result<CallToolResult, McpError> {
+        let content = ctx
+            .graphs
+            .iter()
+            .enumerate()
+            .map(|(i, name)| match i {
+                0 => format!("- {} (default)", name),
+                _ => format!("- {}", name),
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+        Ok(text_result(content))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::logseq::mock::MockGraph;
+    use crate::tools::testing::{call_text, serve};
+    use serde_json::json;
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn test_first_graph_is_the_default() {
+        let (_logseq, mut ctx) = serve(MockGraph::new()).await;
+        let text = call_text(&ctx, "list_graphs", json!({})).await.unwrap();
+        assert_eq!(text, "- default (default)");
+
+        ctx.graphs = Arc::new(["work".to_string(), "personal".to_string()]);
+        let text = call_text(&ctx, "list_graphs", json!({})).await.unwrap();
+        assert_eq!(text, "- work (default)\n- personal");
+    }
+}
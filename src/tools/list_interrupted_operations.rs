@@ -0,0 +1,139 @@
+//! The `list_interrupted_operations` tool
+
+use super::context::{ToolContext, internal_error, json_result, text_result};
+use super::registry::{McpTool, NoArgs};
+use crate::intents::{Intent, StepStatus};
+use crate::session::OutputFormat;
+use rmcp::{ErrorData as McpError, model::CallToolResult};
+
+pub struct ListInterruptedOperations;
+
+impl McpTool for ListInterruptedOperations {
+    type Args = NoArgs;
+
+    fn name(&self) -> &'static str {
+        "list_interrupted_operations"
+    }
+
+    fn description(&self) -> &'static str {
+        "List multi-step changes (such as import_markdown or apply_corrections) that stopped part-way, because the server was restarted mid-call or a step failed, oldest first. Each shows which steps were done and what rolling back would undo. Pass an operation's id to resolve_interrupted_operation to roll it back, or to dismiss it after finishing the remaining steps yourself."
+    }
+
+    fn read_only(&self) -> bool {
+        true
+    }
+
+    fn needs_logseq(&self) -> bool {
+        false
+    }
+
+    async fn execute(&self, ctx: &ToolContext, _args: NoArgs) -> Result<CallToolResult, McpError> {
+        let intents = ctx
+            .require_intents()?
+            .interrupted()
+            .map_err(internal_error)?;
+
+        if ctx.session.output_format == OutputFormat::Json {
+            return Ok(json_result(&intents, "operations"));
+        }
+        if intents.is_empty() {
+            return Ok(text_result("No operations were interrupted."));
+        }
+        let content_text = intents
+            .iter()
+            .map(format_intent)
+            .collect::<Vec<_>>()
+            .join("\n");
+        Ok(text_result(content_text))
+    }
+}
+
+fn format_intent(intent: &Intent) -> String {
+    let mut text = format!(
+        "## {}: {}, started {}",
+        intent.id,
+        intent.tool,
+        intent.started_at.format("%Y-%m-%d %H:%M:%S UTC")
+    );
+    if let Some(request_id) = &intent.request_id {
+        text.push_str(&format!(" (request {})", request_id));
+    }
+    text.push_str(if intent.failed {
+        "\nThe call failed part-way.\n"
+    } else {
+        "\nThe server stopped part-way.\n"
+    });
+    for step in &intent.steps {
+        let mark = match step.status {
+            StepStatus::Done => "x",
+            StepStatus::Skipped => "-",
+            StepStatus::Pending => " ",
+        };
+        text.push_str(&format!("- [{}] {}\n", mark, step.description));
+    }
+
+    let plan = intent.rollback_plan();
+    if plan.is_empty() {
+        text.push_str("Nothing needs undoing.\n");
+    } else {
+        text.push_str("Rolling back would:\n");
+        for op in plan {
+            text.push_str(&format!("- {}\n", op.describe()));
+        }
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::intents::{IntentLog, InverseOp};
+    use crate::logseq::mock::MockGraph;
+    use crate::tools::testing::{call_text, serve};
+    use serde_json::json;
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn test_failed_operation_is_listed_with_its_rollback() {
+        let (_logseq, mut ctx) = serve(MockGraph::new()).await;
+        let error = call_text(&ctx, "list_interrupted_operations", json!({}))
+            .await
+            .unwrap_err();
+        assert!(error.message.contains("not enabled"), "{}", error.message);
+
+        let dir = tempfile::tempdir().unwrap();
+        ctx.intents = Some(Arc::new(IntentLog::new(dir.path())));
+        let text = call_text(&ctx, "list_interrupted_operations", json!({}))
+            .await
+            .unwrap();
+        assert_eq!(text, "No operations were interrupted.");
+
+        let steps = ["create page 'Trip'", "add block", "add block"];
+        let mut intent = ctx
+            .begin_intent("import_markdown", steps.map(String::from).to_vec())
+            .unwrap();
+        let page = InverseOp::DeletePage {
+            name: "Trip".to_string(),
+        };
+        intent.done(0, vec![page]);
+        intent.skipped(1);
+        drop(intent);
+
+        let text = call_text(&ctx, "list_interrupted_operations", json!({}))
+            .await
+            .unwrap();
+        assert!(text.starts_with("## "), "{}", text);
+        assert!(text.contains(": import_markdown, started "), "{}", text);
+        assert!(
+            text.contains(
+                "The call failed part-way.\n\
+                 - [x] create page 'Trip'\n\
+                 - [-] add block\n\
+                 - [ ] add block\n\
+                 Rolling back would:\n\
+                 - delete page 'Trip'\n"
+            ),
+            "{}",
+            text
+        );
+    }
+}
@@ -0,0 +1,47 @@
+//! The `list_pages` tool
+
+use super::context::{ToolContext, json_result, logseq_error, text_result};
+use super::registry::{McpTool, NoArgs};
+use crate::session::OutputFormat;
+use crate::tools::format_skipped_entries;
+use rmcp::{ErrorData as McpError, model::CallToolResult};
+
+pub struct ListPages;
+
+impl McpTool for ListPages {
+    type Args = NoArgs;
+
+    fn name(&self) -> &'static str {
+        "list_pages"
+    }
+
+    fn description(&self) -> &'static str {
+        "List all pages in the current LogSeq graph. Returns a list of page names that can be used with other page-related tools."
+    }
+
+    fn read_only(&self) -> bool {
+        true
+    }
+
+    async fn execute(&self, ctx: &ToolContext, _args: NoArgs) -> Result<CallToolResult, McpError> {
+        let pages = ctx.cache.pages().await.map_err(logseq_error)?;
+        if ctx.session.output_format == OutputFormat::Json {
+            return Ok(json_result(
+                &serde_json::json!({ "pages": pages.items, "skipped": pages.warnings }),
+                "pages",
+            ));
+        }
+
+        let mut content_text = pages
+            .iter()
+            .map(|p| format!("- {}", p.name))
+            .collect::<Vec<_>>()
+            .join("\n");
+        if !pages.warnings.is_empty() {
+            content_text.push('\n');
+            content_text.push_str(&format_skipped_entries(&pages.warnings));
+        }
+
+        Ok(text_result(content_text))
+    }
+}
@@ -0,0 +1,121 @@
+//! The `list_trash` tool
+
+use super::context::{ToolContext, internal_error, json_result, text_result};
+use super::registry::{McpTool, NoArgs};
+use crate::session::OutputFormat;
+use crate::tools::count_blocks;
+use rmcp::{ErrorData as McpError, model::CallToolResult};
+
+pub struct ListTrash;
+
+impl McpTool for ListTrash {
+    type Args = NoArgs;
+
+    fn name(&self) -> &'static str {
+        "list_trash"
+    }
+
+    fn description(&self) -> &'static str {
+        "List pages and blocks deleted through this server that are still in its trash, newest first. Each entry's id can be passed to restore_from_trash."
+    }
+
+    fn read_only(&self) -> bool {
+        true
+    }
+
+    fn needs_logseq(&self) -> bool {
+        false
+    }
+
+    async fn execute(&self, ctx: &ToolContext, _args: NoArgs) -> Result<CallToolResult, McpError> {
+        let entries = ctx.require_trash()?.list().map_err(internal_error)?;
+
+        if ctx.session.output_format == OutputFormat::Json {
+            let summaries: Vec<_> = entries
+                .iter()
+                .map(|entry| {
+                    serde_json::json!({
+                        "id": entry.id,
+                        "deleted_at": entry.deleted_at,
+                        "item": entry.item,
+                        "block_count": count_blocks(&entry.blocks),
+                    })
+                })
+                .collect();
+            return Ok(json_result(&summaries, "trash"));
+        }
+
+        if entries.is_empty() {
+            return Ok(text_result("The trash is empty."));
+        }
+        let content_text = entries
+            .iter()
+            .map(|entry| {
+                format!(
+                    "- {}: {} with {} block(s), deleted {}",
+                    entry.id,
+                    entry.item.describe(&entry.blocks),
+                    count_blocks(&entry.blocks),
+                    entry.deleted_at.format("%Y-%m-%d %H:%M:%S UTC")
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        Ok(text_result(content_text))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::logseq::mock::MockGraph;
+    use crate::tools::testing::{call_text, confirm_token, serve};
+    use crate::trash::Trash;
+    use serde_json::json;
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn test_deleted_items_are_listed() {
+        let mut graph = MockGraph::new();
+        graph.add_page("Drafts");
+        graph.add_block("Drafts", "Opening line");
+        graph.add_page("Inbox");
+        let errand = graph.add_block("Inbox", "Buy stamps");
+        let (_logseq, mut ctx) = serve(graph).await;
+
+        let error = call_text(&ctx, "list_trash", json!({})).await.unwrap_err();
+        assert!(error.message.contains("not enabled"), "{}", error.message);
+
+        let dir = tempfile::tempdir().unwrap();
+        ctx.trash = Some(Arc::new(Trash::new(dir.path())));
+        let text = call_text(&ctx, "list_trash", json!({})).await.unwrap();
+        assert_eq!(text, "The trash is empty.");
+
+        for (tool, args) in [
+            ("delete_page", json!({"page_name": "Drafts"})),
+            ("delete_block", json!({"uuid": errand})),
+        ] {
+            let preview = call_text(&ctx, tool, args.clone()).await.unwrap();
+            let mut args = args;
+            args["confirm"] = json!(confirm_token(&preview));
+            call_text(&ctx, tool, args).await.unwrap();
+        }
+
+        let text = call_text(&ctx, "list_trash", json!({})).await.unwrap();
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines.len(), 2, "{}", text);
+        assert!(
+            lines[0].contains(&format!(
+                "block {} (\"Buy stamps\") from page 'inbox' with 1 block(s)",
+                errand
+            )),
+            "{}",
+            text
+        );
+        assert!(
+            lines[1].contains("page 'Drafts' with 1 block(s)"),
+            "{}",
+            text
+        );
+    }
+}
@@ -0,0 +1,100 @@
+//! The `move_block` tool
+
+use super::context::{ToolContext, logseq_error, text_result};
+use super::registry::McpTool;
+use crate::logseq::api::MoveBlockOptions;
+use rmcp::{ErrorData as McpError, model::CallToolResult};
+use schemars::JsonSchema;
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct MoveBlockArgs {
+    /// The UUID of the block to move.
+    pub uuid: String,
+    /// The UUID of the block to move relative to.
+    pub target_uuid: String,
+    /// Place the block before the target block instead of after it. Defaults to false.
+    pub before: Option<bool>,
+    /// Place the block as a child of the target block instead of as a sibling. Defaults to false.
+    pub children: Option<bool>,
+}
+
+pub struct MoveBlock;
+
+impl McpTool for MoveBlock {
+    type Args = MoveBlockArgs;
+
+    fn name(&self) -> &'static str {
+        "move_block"
+    }
+
+    fn description(&self) -> &'static str {
+        "Move an existing block (and its children) to a new position relative to a target block. Use this to reorganize an outline without deleting and recreating content."
+    }
+
+    fn idempotent(&self) -> bool {
+        true
+    }
+
+    async fn execute(
+        &self,
+        ctx: &ToolContext,
+        args: MoveBlockArgs,
+    ) -> Result<CallToolResult, McpError> {
+        let MoveBlockArgs {
+            uuid,
+            target_uuid,
+            before,
+            children,
+        } = args;
+        let opts = MoveBlockOptions { before, children };
+        ctx.require_writable(&uuid).await?;
+        ctx.require_writable(&target_uuid).await?;
+
+        ctx.client
+            .move_block(&uuid, &target_uuid, opts)
+            .await
+            .map_err(logseq_error)?;
+
+        Ok(text_result(format!(
+            "Moved block {} relative to block {}",
+            uuid, target_uuid
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::logseq::mock::MockGraph;
+    use crate::tools::testing::{call_text, serve};
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_block_is_moved_with_its_children() {
+        let mut graph = MockGraph::new();
+        graph.add_page("Projects");
+        let plan = graph.add_block("Projects", "Plan");
+        graph.add_block(&plan, "Budget");
+        let review = graph.add_block("Projects", "Review");
+        let (logseq, ctx) = serve(graph).await;
+
+        let args = json!({"uuid": plan, "target_uuid": review});
+        let text = call_text(&ctx, "move_block", args).await.unwrap();
+        assert!(text.starts_with("Moved block"), "{}", text);
+        logseq.with_graph(|g| {
+            assert_eq!(g.children("Projects"), [review.clone(), plan.clone()]);
+            assert_eq!(g.children(&plan).len(), 1);
+        });
+
+        let args = json!({"uuid": review, "target_uuid": plan, "children": true});
+        call_text(&ctx, "move_block", args).await.unwrap();
+        logseq.with_graph(|g| {
+            assert_eq!(g.children("Projects"), std::slice::from_ref(&plan));
+            assert_eq!(g.children(&plan).last(), Some(&review));
+        });
+
+        let args = json!({"uuid": plan, "target_uuid": review});
+        assert!(call_text(&ctx, "move_block", args).await.is_err());
+    }
+}
@@ -0,0 +1,110 @@
+//! The `open_in_logseq` tool
+
+use super::context::{ToolContext, logseq_error, text_result};
+use super::quote_block::block_page;
+use super::registry::McpTool;
+use rmcp::{ErrorData as McpError, model::CallToolResult};
+use schemars::JsonSchema;
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct OpenInLogseqArgs {
+    /// Page name or block UUID to show. A block is shown in its page, scrolled into view.
+    pub target: String,
+}
+
+pub struct OpenInLogseq;
+
+impl McpTool for OpenInLogseq {
+    type Args = OpenInLogseqArgs;
+
+    fn name(&self) -> &'static str {
+        "open_in_logseq"
+    }
+
+    fn description(&self) -> &'static str {
+        "Navigate the user's LogSeq window to a page, or to a block within its page, e.g. to show them something you just created or found. Only do this when it helps the user follow along: it changes what they're looking at. Nothing in the graph changes."
+    }
+
+    fn read_only(&self) -> bool {
+        true
+    }
+
+    async fn execute(
+        &self,
+        ctx: &ToolContext,
+        args: OpenInLogseqArgs,
+    ) -> Result<CallToolResult, McpError> {
+        let target = args.target.trim();
+
+        if uuid::Uuid::parse_str(target).is_err() {
+            ctx.require_page(target).await?;
+            ctx.client
+                .push_state("page", serde_json::json!({ "name": target }))
+                .await
+                .map_err(logseq_error)?;
+            return Ok(text_result(format!("Opened page '{}' in LogSeq", target)));
+        }
+
+        let block = ctx.client.get_block(target).await.map_err(logseq_error)?;
+        let Some(page) = block_page(ctx, &block.uuid).await? else {
+            return Err(McpError::invalid_params(
+                format!("Block {} isn't on a page", block.uuid),
+                None,
+            ));
+        };
+        ctx.client
+            .scroll_to_block_in_page(&page, &block.uuid)
+            .await
+            .map_err(logseq_error)?;
+        Ok(text_result(format!(
+            "Opened block {} on page '{}' in LogSeq",
+            block.uuid, page
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::logseq::mock::MockGraph;
+    use crate::tools::testing::{call_text, serve};
+    use serde_json::{Value, json};
+
+    #[tokio::test]
+    async fn test_open_in_logseq_navigates_to_pages_and_blocks() {
+        let mut graph = MockGraph::new();
+        graph.add_page("Projects");
+        let block = graph.add_block("Projects", "Launch plan");
+        let (logseq, ctx) = serve(graph).await;
+
+        call_text(&ctx, "open_in_logseq", json!({"target": "Projects"}))
+            .await
+            .unwrap();
+        call_text(&ctx, "open_in_logseq", json!({"target": block}))
+            .await
+            .unwrap();
+        let navigation: Vec<(String, Vec<Value>)> = logseq
+            .calls()
+            .into_iter()
+            .filter(|call| call.method.contains("pushState") || call.method.contains("scrollTo"))
+            .map(|call| (call.method, call.args))
+            .collect();
+        assert_eq!(
+            navigation,
+            vec![
+                (
+                    "logseq.App.pushState".to_string(),
+                    vec![json!("page"), json!({"name": "Projects"})]
+                ),
+                (
+                    "logseq.Editor.scrollToBlockInPage".to_string(),
+                    vec![json!("Projects"), json!(block)]
+                ),
+            ]
+        );
+
+        let missing = call_text(&ctx, "open_in_logseq", json!({"target": "Nowhere"})).await;
+        assert!(missing.is_err());
+    }
+}
@@ -0,0 +1,127 @@
+//! The `page_stats` tool
+
+use super::context::{ToolContext, json_result, logseq_error, text_result};
+use super::registry::McpTool;
+use crate::logseq::writing::{ContentStats, content_stats};
+use crate::session::OutputFormat;
+use chrono::{DateTime, Local, Utc};
+use rmcp::{ErrorData as McpError, model::CallToolResult};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct PageStatsArgs {
+    /// The name or UUID of the page
+    pub page_name: String,
+}
+
+#[derive(Debug, Serialize)]
+struct PageStatsReport {
+    page: String,
+    #[serde(flatten)]
+    stats: ContentStats,
+    links: usize,
+    updated_at: Option<DateTime<Utc>>,
+}
+
+pub struct PageStats;
+
+impl McpTool for PageStats {
+    type Args = PageStatsArgs;
+
+    fn name(&self) -> &'static str {
+        "page_stats"
+    }
+
+    fn description(&self) -> &'static str {
+        "Measure a page as it is now: its number of blocks, words, and characters, its links to pages, blocks, and the web, and when it was last modified. Property lines such as 'status:: draft' aren't counted. Use get_writing_stats for how much was written over time instead."
+    }
+
+    fn read_only(&self) -> bool {
+        true
+    }
+
+    async fn execute(
+        &self,
+        ctx: &ToolContext,
+        args: PageStatsArgs,
+    ) -> Result<CallToolResult, McpError> {
+        let page = ctx.require_page(&args.page_name).await?;
+        let blocks = ctx
+            .client
+            .get_page_blocks_tree(&args.page_name)
+            .await
+            .map_err(logseq_error)?;
+        let stats = content_stats(&blocks.items);
+        let report = PageStatsReport {
+            page: page.display_name().to_string(),
+            links: stats.links(),
+            stats,
+            updated_at: page.updated_at.and_then(DateTime::from_timestamp_millis),
+        };
+
+        if ctx.session.output_format == OutputFormat::Json {
+            return Ok(json_result(&report, "page stats"));
+        }
+        Ok(text_result(format_report(&report)))
+    }
+}
+
+fn format_report(report: &PageStatsReport) -> String {
+    let stats = &report.stats;
+    let mut content = format!(
+        "Page '{}':\n\n- Blocks: {}\n- Words: {}\n- Characters: {}\n- Links: {} ({} to pages, {} to blocks, {} to the web)\n",
+        report.page,
+        stats.blocks,
+        stats.words,
+        stats.characters,
+        report.links,
+        stats.page_links,
+        stats.block_refs,
+        stats.external_links
+    );
+    if let Some(updated_at) = report.updated_at {
+        content.push_str(&format!(
+            "- Last modified: {}\n",
+            updated_at.with_timezone(&Local).format("%Y-%m-%d %H:%M")
+        ));
+    }
+    content
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::logseq::mock::MockGraph;
+    use crate::tools::testing::{call_text, serve};
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_page_stats_count_words_and_links() {
+        let mut graph = MockGraph::new();
+        graph.add_page("Draft");
+        let intro = graph.add_block("Draft", "Intro for [[Book]]\nstatus:: draft");
+        graph.add_block(&intro, "See https://example.com #research");
+        let (_logseq, ctx) = serve(graph).await;
+
+        let text = call_text(&ctx, "page_stats", json!({"page_name": "draft"}))
+            .await
+            .unwrap();
+        assert!(text.starts_with("Page 'Draft':"), "{}", text);
+        assert!(text.contains("- Blocks: 2\n- Words: 6\n"), "{}", text);
+        assert!(
+            text.contains("- Links: 3 (2 to pages, 0 to blocks, 1 to the web)"),
+            "{}",
+            text
+        );
+
+        let err = call_text(&ctx, "page_stats", json!({"page_name": "Drafts"}))
+            .await
+            .unwrap_err();
+        assert!(
+            err.message.contains("Did you mean: Draft?"),
+            "{}",
+            err.message
+        );
+    }
+}
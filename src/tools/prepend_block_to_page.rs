@@ -0,0 +1,95 @@
+//! The `prepend_block_to_page` tool
+
+use super::context::{ToolContext, logseq_error, text_result};
+use super::registry::McpTool;
+use rmcp::{ErrorData as McpError, model::CallToolResult};
+use schemars::JsonSchema;
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct PrependBlockToPageArgs {
+    /// Name of the page to add the block to. It must already exist.
+    pub page: String,
+    /// Block content in markdown format. Can include text, links, formatting, and LogSeq-specific syntax.
+    pub content: String,
+}
+
+pub struct PrependBlockToPage;
+
+impl McpTool for PrependBlockToPage {
+    type Args = PrependBlockToPageArgs;
+
+    fn name(&self) -> &'static str {
+        "prepend_block_to_page"
+    }
+
+    fn description(&self) -> &'static str {
+        "Add a block at the start of a page, before its first top-level block, e.g. for a newest-first log. Works on empty pages and needs no block UUIDs. Returns the new block's UUID."
+    }
+
+    fn destructive(&self) -> bool {
+        false
+    }
+
+    async fn execute(
+        &self,
+        ctx: &ToolContext,
+        args: PrependBlockToPageArgs,
+    ) -> Result<CallToolResult, McpError> {
+        let PrependBlockToPageArgs { page, content } = args;
+        let page = page.trim();
+
+        ctx.require_page(page).await?;
+        ctx.require_writable(page).await?;
+        ctx.charge_write(Some(page), &content)?;
+        let block = ctx
+            .client
+            .prepend_block_in_page(page, &content)
+            .await
+            .map_err(logseq_error)?;
+
+        Ok(text_result(format!(
+            "Prepended block with UUID {} to page {}",
+            block.uuid, page
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::logseq::mock::MockGraph;
+    use crate::tools::testing::{call_text, serve};
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_block_goes_first_on_the_page() {
+        let mut graph = MockGraph::new();
+        graph.add_page("Log");
+        graph.add_page("Empty");
+        let older = graph.add_block("Log", "Yesterday's entry");
+        let (logseq, ctx) = serve(graph).await;
+
+        for page in ["Log", "Empty"] {
+            let args = json!({"page": page, "content": "Today's entry"});
+            let text = call_text(&ctx, "prepend_block_to_page", args)
+                .await
+                .unwrap();
+            assert!(text.starts_with("Prepended block with UUID "), "{}", text);
+            assert!(text.ends_with(&format!("to page {}", page)), "{}", text);
+        }
+        logseq.with_graph(|g| {
+            let log = g.children("Log");
+            assert_eq!(log.len(), 2);
+            assert_eq!(g.block_content(&log[0]), Some("Today's entry"));
+            assert_eq!(log[1], older);
+            assert_eq!(g.children("Empty").len(), 1);
+        });
+
+        let args = json!({"page": "Missing", "content": "Lost"});
+        let error = call_text(&ctx, "prepend_block_to_page", args)
+            .await
+            .unwrap_err();
+        assert!(error.message.contains("Missing"), "{}", error.message);
+    }
+}
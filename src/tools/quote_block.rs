@@ -0,0 +1,169 @@
+//! The `quote_block` tool
+
+use super::context::{ToolContext, logseq_error, text_result};
+use super::registry::McpTool;
+use crate::logseq::api::{Block, InsertBlockOptions};
+use crate::logseq::content::quote_block;
+use rmcp::{ErrorData as McpError, model::CallToolResult};
+use schemars::JsonSchema;
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct QuoteBlockArgs {
+    /// The UUID of the block to quote.
+    pub source_uuid: String,
+    /// Page name or block UUID to add the quote to. It goes at the end of the page, or as the block's last child.
+    pub target: String,
+}
+
+pub struct QuoteBlock;
+
+impl McpTool for QuoteBlock {
+    type Args = QuoteBlockArgs;
+
+    fn name(&self) -> &'static str {
+        "quote_block"
+    }
+
+    fn description(&self) -> &'static str {
+        "Quote a block somewhere else, keeping track of where it came from: its content is copied into a '>' quote on the target page or block, ending with a link to the source page and a ((ref)) to the original block. Useful for compiling research notes and digests. The original block gets an 'id::' property so the reference keeps working."
+    }
+
+    fn destructive(&self) -> bool {
+        false
+    }
+
+    async fn execute(
+        &self,
+        ctx: &ToolContext,
+        args: QuoteBlockArgs,
+    ) -> Result<CallToolResult, McpError> {
+        let QuoteBlockArgs {
+            source_uuid,
+            target,
+        } = args;
+        ctx.require_writable(&target).await?;
+
+        let source = ctx
+            .client
+            .get_block(&source_uuid)
+            .await
+            .map_err(logseq_error)?;
+        let target_page = match uuid::Uuid::parse_str(target.trim()) {
+            Ok(_) => {
+                ctx.client
+                    .get_block(target.trim())
+                    .await
+                    .map_err(logseq_error)?;
+                None
+            }
+            Err(_) => {
+                ctx.require_page(&target).await?;
+                Some(target.trim())
+            }
+        };
+
+        let page = block_page(ctx, &source.uuid).await?;
+        let content = quote_block(&source.content, &source.uuid, page.as_deref());
+        ctx.charge_write(target_page, &content)?;
+        ensure_block_id(ctx, &source).await?;
+
+        let opts = InsertBlockOptions {
+            parent: Some(target.trim().to_string()),
+            ..Default::default()
+        };
+        let block = ctx
+            .client
+            .insert_block(&content, opts)
+            .await
+            .map_err(logseq_error)?;
+
+        Ok(text_result(format!(
+            "Quoted block {} in {} as block {}",
+            source.uuid,
+            target.trim(),
+            block.uuid
+        )))
+    }
+}
+
+/// The name of the page a block is on, as the user wrote it
+pub(crate) async fn block_page(ctx: &ToolContext, uuid: &str) -> Result<Option<String>, McpError> {
+    let Some(location) = ctx
+        .client
+        .get_block_location(uuid)
+        .await
+        .map_err(logseq_error)?
+    else {
+        return Ok(None);
+    };
+    let pages = ctx.cache.pages().await.unwrap_or_default();
+    Ok(Some(
+        pages
+            .iter()
+            .find(|p| p.name == location.page)
+            .and_then(|p| p.original_name.clone())
+            .unwrap_or(location.page),
+    ))
+}
+
+/// Give a block an `id::` property, which a ((ref)) to it needs to survive
+/// a re-index
+pub(crate) async fn ensure_block_id(ctx: &ToolContext, block: &Block) -> Result<(), McpError> {
+    let has_id = block
+        .properties
+        .as_ref()
+        .is_some_and(|props| props.contains_key("id"));
+    if !has_id {
+        ctx.client
+            .upsert_block_property(&block.uuid, "id", &block.uuid.clone().into())
+            .await
+            .map_err(logseq_error)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::logseq::mock::MockGraph;
+    use crate::tools::testing::{call_text, serve};
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_quoted_block_links_back_to_its_source() {
+        let mut graph = MockGraph::new();
+        graph.add_page("Reading Notes");
+        let source = graph.add_block(
+            "Reading Notes",
+            "TODO Simple is better than complex.\nauthor:: Tim Peters\n\nFrom the Zen of Python",
+        );
+        graph.add_page("Digest");
+        let (logseq, ctx) = serve(graph).await;
+
+        let args = json!({"source_uuid": source, "target": "Digest"});
+        let text = call_text(&ctx, "quote_block", args).await.unwrap();
+        assert!(text.contains("Quoted block"), "{}", text);
+
+        logseq.with_graph(|g| {
+            let quoted = &g.children("Digest")[0];
+            assert_eq!(
+                g.block_content(quoted).unwrap(),
+                format!(
+                    "> Simple is better than complex.\n>\n> From the Zen of Python\n> — [[Reading Notes]] (({}))",
+                    source
+                )
+            );
+            let original = g.block_content(&source).unwrap();
+            assert!(original.contains(&format!("id:: {}", source)), "{}", original);
+        });
+
+        let args = json!({"source_uuid": source, "target": "Digets"});
+        let err = call_text(&ctx, "quote_block", args).await.unwrap_err();
+        assert!(
+            err.message.contains("Did you mean: Digest"),
+            "{}",
+            err.message
+        );
+    }
+}
@@ -0,0 +1,947 @@
+//! The `McpTool` trait and the registry the server uses to list and dispatch tools
+
+use super::context::{ToolContext, backend_unavailable};
+use super::remediation::remediate;
+use super::{
+    add_tag_to_block, analyze_tag_cooccurrence, append_block_to_page, apply_corrections,
+    build_context, capture, compile_digest, create_block, create_block_reference, create_page,
+    create_page_from_template, datascript_query, delete_block, delete_page, export_link_graph,
+    extract_corpus, find_broken_links, find_duplicates, find_incomplete_todos,
+    find_scheduled_tasks, get_block, get_block_children, get_block_property,
+    get_block_referencing_blocks, get_current_block, get_current_graph, get_current_page,
+    get_due_cards, get_editor_context, get_graph_stats, get_namespace_tree, get_on_this_day,
+    get_page, get_page_checksums, get_page_content, get_recent_changes, get_state_from_store,
+    get_user_configs, get_writing_stats, health_check, import_markdown, invalidate_cache,
+    list_all_tags, list_flashcards, list_graphs, list_interrupted_operations, list_pages,
+    list_trash, move_block, open_in_logseq, page_stats, prepend_block_to_page, quote_block,
+    remove_block_property, rename_tag, reset_write_budget, resolve_interrupted_operation,
+    resolve_page_name, restore_from_trash, search, set_todo_state, show_ui_message, update_block,
+    upsert_block_property, write_task_dashboard,
+};
+use crate::activity;
+use crate::audit::{AuditEntry, AuditOutcome, mentioned_uuids};
+use crate::quota::QuotaKind;
+use crate::session::SessionConfig;
+use chrono::Utc;
+use rmcp::{
+    ErrorData as McpError,
+    model::{CallToolRequestMethod, CallToolResult, ErrorCode, JsonObject, Tool, ToolAnnotations},
+};
+use schemars::{JsonSchema, r#gen::SchemaSettings};
+use serde::Deserialize;
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tracing::Instrument;
+
+/// Arguments for tools that take no parameters
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct NoArgs {}
+
+/// A single MCP tool. The input schema is derived from `Args`, so what we
+/// advertise and what we parse can't drift apart.
+pub trait McpTool: Send + Sync + 'static {
+    type Args: DeserializeOwned + JsonSchema + Send;
+
+    fn name(&self) -> &'static str;
+
+    fn description(&self) -> &'static str;
+
+    /// Whether the tool only reads from the graph. Tools are assumed to
+    /// modify it unless they say otherwise, so read-only sessions can't
+    /// reach a new tool by accident.
+    fn read_only(&self) -> bool {
+        false
+    }
+
+    /// Whether the tool may change or remove what's already in the graph,
+    /// as opposed to only adding to it. Clients can ask the user to confirm
+    /// destructive calls.
+    fn destructive(&self) -> bool {
+        !self.read_only()
+    }
+
+    /// Whether calling the tool again with the same arguments has no
+    /// further effect
+    fn idempotent(&self) -> bool {
+        self.read_only()
+    }
+
+    /// Whether a call with these arguments is recorded in the audit log.
+    /// By default that's every call to a tool that isn't read-only.
+    fn audited(&self, _arguments: &JsonObject) -> bool {
+        !self.read_only()
+    }
+
+    /// Whether the tool needs LogSeq to be reachable. Those that only use
+    /// the server's own state keep working while it isn't.
+    fn needs_logseq(&self) -> bool {
+        true
+    }
+
+    fn input_schema(&self) -> JsonObject {
+        input_schema::<Self::Args>()
+    }
+
+    /// Example arguments, shown after the description, for tools whose
+    /// arguments are easy to get wrong. The registry's tests run each one
+    /// against the mock graph, so they can't go stale.
+    fn examples(&self) -> Vec<Value> {
+        Vec::new()
+    }
+
+    fn execute(
+        &self,
+        ctx: &ToolContext,
+        args: Self::Args,
+    ) -> impl Future<Output = Result<CallToolResult, McpError>> + Send;
+}
+
+type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+
+/// Object-safe view of `McpTool` so tools with different argument types can
+/// live in one registry
+trait DynTool: Send + Sync {
+    fn name(&self) -> &'static str;
+
+    fn read_only(&self) -> bool;
+
+    fn audited(&self, arguments: &JsonObject) -> bool;
+
+    fn needs_logseq(&self) -> bool;
+
+    fn examples(&self) -> Vec<Value>;
+
+    fn definition(&self) -> Tool;
+
+    fn call<'a>(
+        &'a self,
+        ctx: &'a ToolContext,
+        arguments: Option<JsonObject>,
+    ) -> BoxFuture<'a, Result<CallToolResult, McpError>>;
+}
+
+impl<T: McpTool> DynTool for T {
+    fn name(&self) -> &'static str {
+        McpTool::name(self)
+    }
+
+    fn read_only(&self) -> bool {
+        McpTool::read_only(self)
+    }
+
+    fn audited(&self, arguments: &JsonObject) -> bool {
+        McpTool::audited(self, arguments)
+    }
+
+    fn needs_logseq(&self) -> bool {
+        McpTool::needs_logseq(self)
+    }
+
+    fn examples(&self) -> Vec<Value> {
+        McpTool::examples(self)
+    }
+
+    fn definition(&self) -> Tool {
+        let mut description = self.description().to_string();
+        let examples = DynTool::examples(self);
+        if !examples.is_empty() {
+            description.push_str("\n\nExample arguments:");
+            for example in &examples {
+                description.push_str(&format!("\n{}", example));
+            }
+        }
+        Tool {
+            name: McpTool::name(self).into(),
+            description: Some(description.into()),
+            input_schema: Arc::new(self.input_schema()),
+            annotations: Some(ToolAnnotations {
+                read_only_hint: Some(McpTool::read_only(self)),
+                destructive_hint: Some(McpTool::destructive(self)),
+                idempotent_hint: Some(McpTool::idempotent(self)),
+                ..Default::default()
+            }),
+            output_schema: None,
+        }
+    }
+
+    fn call<'a>(
+        &'a self,
+        ctx: &'a ToolContext,
+        arguments: Option<JsonObject>,
+    ) -> BoxFuture<'a, Result<CallToolResult, McpError>> {
+        Box::pin(async move {
+            let args = parse_args(McpTool::name(self), arguments)?;
+            self.execute(ctx, args).await
+        })
+    }
+}
+
+/// The set of tools a server exposes, in listing order
+#[derive(Default)]
+pub struct ToolRegistry {
+    tools: Vec<Box<dyn DynTool>>,
+    deadline: Option<Duration>,
+}
+
+impl ToolRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Every tool the server ships with
+    pub fn builtin() -> Self {
+        let mut registry = Self::new();
+        registry.register(list_pages::ListPages);
+        registry.register(get_page_content::GetPageContent);
+        registry.register(get_page_checksums::GetPageChecksums);
+        registry.register(page_stats::PageStats);
+        registry.register(create_page::CreatePage);
+        registry.register(create_page_from_template::CreatePageFromTemplate);
+        registry.register(import_markdown::ImportMarkdown);
+        registry.register(search::Search);
+        registry.register(build_context::BuildContext);
+        registry.register(create_block::CreateBlock);
+        registry.register(append_block_to_page::AppendBlockToPage);
+        registry.register(prepend_block_to_page::PrependBlockToPage);
+        registry.register(capture::Capture);
+        registry.register(get_page::GetPage);
+        registry.register(resolve_page_name::ResolvePageName);
+        registry.register(get_namespace_tree::GetNamespaceTree);
+        registry.register(get_block::GetBlock);
+        registry.register(get_block_children::GetBlockChildren);
+        registry.register(get_current_page::GetCurrentPage);
+        registry.register(get_current_block::GetCurrentBlock);
+        registry.register(get_editor_context::GetEditorContext);
+        registry.register(datascript_query::DatascriptQuery);
+        registry.register(get_current_graph::GetCurrentGraph);
+        registry.register(list_graphs::ListGraphs);
+        registry.register(health_check::HealthCheck);
+        registry.register(get_state_from_store::GetStateFromStore);
+        registry.register(get_user_configs::GetUserConfigs);
+        registry.register(show_ui_message::ShowUiMessage);
+        registry.register(open_in_logseq::OpenInLogseq);
+        registry.register(invalidate_cache::InvalidateCache);
+        registry.register(reset_write_budget::ResetWriteBudget);
+        registry.register(update_block::UpdateBlock);
+        registry.register(get_block_property::GetBlockProperty);
+        registry.register(upsert_block_property::UpsertBlockProperty);
+        registry.register(remove_block_property::RemoveBlockProperty);
+        registry.register(set_todo_state::SetTodoState);
+        registry.register(move_block::MoveBlock);
+        registry.register(quote_block::QuoteBlock);
+        registry.register(create_block_reference::CreateBlockReference);
+        registry.register(get_block_referencing_blocks::GetBlockReferencingBlocks);
+        registry.register(compile_digest::CompileDigest);
+        registry.register(delete_block::DeleteBlock);
+        registry.register(delete_page::DeletePage);
+        registry.register(list_trash::ListTrash);
+        registry.register(restore_from_trash::RestoreFromTrash);
+        registry.register(list_interrupted_operations::ListInterruptedOperations);
+        registry.register(resolve_interrupted_operation::ResolveInterruptedOperation);
+        registry.register(find_incomplete_todos::FindIncompleteTodos);
+        registry.register(find_scheduled_tasks::FindScheduledTasks);
+        registry.register(list_flashcards::ListFlashcards);
+        registry.register(get_due_cards::GetDueCards);
+        registry.register(analyze_tag_cooccurrence::AnalyzeTagCooccurrence);
+        registry.register(list_all_tags::ListAllTags);
+        registry.register(find_broken_links::FindBrokenLinks);
+        registry.register(find_duplicates::FindDuplicates);
+        registry.register(add_tag_to_block::AddTagToBlock);
+        registry.register(rename_tag::RenameTag);
+        registry.register(get_writing_stats::GetWritingStats);
+        registry.register(get_graph_stats::GetGraphStats);
+        registry.register(get_on_this_day::GetOnThisDay);
+        registry.register(get_recent_changes::GetRecentChanges);
+        registry.register(export_link_graph::ExportLinkGraph);
+        registry.register(extract_corpus::ExtractCorpus);
+        registry.register(apply_corrections::ApplyCorrections);
+        registry.register(write_task_dashboard::WriteTaskDashboard);
+        registry
+    }
+
+    /// Add a tool. Panics if a tool with the same name is already registered.
+    pub fn register<T: McpTool>(&mut self, tool: T) {
+        assert!(
+            self.find(tool.name()).is_none(),
+            "duplicate tool name: {}",
+            tool.name()
+        );
+        self.tools.push(Box::new(tool));
+    }
+
+    /// Abort tool calls that run longer than `deadline`
+    pub fn set_deadline(&mut self, deadline: Option<Duration>) {
+        self.deadline = deadline;
+    }
+
+    pub fn definitions(&self) -> Vec<Tool> {
+        self.tools.iter().map(|tool| tool.definition()).collect()
+    }
+
+    /// Definitions of the tools `session` may use
+    pub fn definitions_for(&self, session: &SessionConfig) -> Vec<Tool> {
+        self.tools
+            .iter()
+            .filter(|tool| session.allows(tool.name(), tool.read_only()))
+            .map(|tool| tool.definition())
+            .collect()
+    }
+
+    /// Whether a tool with this name is registered
+    pub fn contains(&self, name: &str) -> bool {
+        self.find(name).is_some()
+    }
+
+    /// Whether the named tool needs LogSeq to be reachable
+    pub fn needs_logseq(&self, name: &str) -> bool {
+        self.find(name).is_some_and(|tool| tool.needs_logseq())
+    }
+
+    /// Parse the arguments for the named tool and run it, subject to the
+    /// registry's deadline.
+    ///
+    /// Each call gets a request ID. It is recorded on a `tool_call` tracing
+    /// span, so every log line the call produces (LogSeq API calls included)
+    /// carries it; tools can read it from [`ToolContext::request_id`]; and
+    /// errors and audit log entries report it as `request_id`.
+    ///
+    /// Calls to tools that aren't read-only clear the datascript query cache,
+    /// and those that change the graph (see [`McpTool::audited`]) count
+    /// against the session's budget of changes and, when journaling is on,
+    /// are summarised in today's journal. A call that fails because
+    /// LogSeq can't be reached gets a [`backend_unavailable`] error, whatever
+    /// the tool made of the failure, and every error's data is filled in
+    /// with its kind and suggested next calls (see [`remediate`]).
+    pub async fn call(
+        &self,
+        ctx: &ToolContext,
+        name: &str,
+        arguments: Option<JsonObject>,
+    ) -> Result<CallToolResult, McpError> {
+        let request_id = new_request_id();
+        let span = tracing::info_span!("tool_call", tool = name, request_id = %request_id);
+        let ctx = ctx.for_request(&request_id);
+
+        async {
+            let changes = self
+                .find(name)
+                .is_some_and(|tool| tool.audited(arguments.as_ref().unwrap_or(&JsonObject::new())));
+            let audit_arguments =
+                (changes && ctx.audit.is_some()).then(|| arguments.clone().unwrap_or_default());
+            let writes = self.find(name).is_some_and(|tool| !tool.read_only());
+            let given = arguments.clone().unwrap_or_default();
+
+            let started = Instant::now();
+            let result = async {
+                if changes {
+                    ctx.charge_quota(QuotaKind::Mutations, 1)?;
+                }
+                self.dispatch(&ctx, name, arguments).await
+            }
+            .await
+            .map_err(|e| match ctx.client.unreachable() {
+                Some(reason) if e.code == ErrorCode::INTERNAL_ERROR && self.needs_logseq(name) => {
+                    backend_unavailable(&ctx.graph, &reason)
+                }
+                _ => e,
+            });
+            // Even a failed or timed-out write may have changed something
+            if writes {
+                ctx.queries.clear();
+            }
+            if let Some(arguments) = audit_arguments {
+                record_audit(&ctx, name, arguments, &result);
+            }
+            let succeeded = result
+                .as_ref()
+                .is_ok_and(|result| result.is_error != Some(true));
+            if changes && succeeded && ctx.journal_activity {
+                activity::journal(&ctx, name, &given).await;
+            }
+            match &result {
+                Ok(_) => tracing::debug!("{} finished in {:?}", name, started.elapsed()),
+                Err(e) => tracing::warn!(
+                    "{} failed after {:?}: {}",
+                    name,
+                    started.elapsed(),
+                    e.message
+                ),
+            }
+            result.map_err(|e| with_request_id(remediate(e, name, &given), &request_id))
+        }
+        .instrument(span)
+        .await
+    }
+
+    async fn dispatch(
+        &self,
+        ctx: &ToolContext,
+        name: &str,
+        arguments: Option<JsonObject>,
+    ) -> Result<CallToolResult, McpError> {
+        let Some(tool) = self.find(name) else {
+            return Err(McpError::method_not_found::<CallToolRequestMethod>());
+        };
+        if !ctx.session.allows(name, tool.read_only()) {
+            let reason = if ctx.session.read_only && !tool.read_only() {
+                "the session is read-only"
+            } else {
+                "it is not in the session's allowed tools"
+            };
+            return Err(McpError::invalid_request(
+                format!("{} is not available: {}", name, reason),
+                Some(serde_json::json!({ "tool": name })),
+            ));
+        }
+        let Some(deadline) = self.deadline else {
+            return tool.call(ctx, arguments).await;
+        };
+
+        tokio::time::timeout(deadline, tool.call(ctx, arguments))
+            .await
+            .unwrap_or_else(|_| {
+                Err(McpError::internal_error(
+                    format!("{} did not finish within the {:?} deadline", name, deadline),
+                    Some(serde_json::json!({
+                        "tool": name,
+                        "deadline_ms": deadline.as_millis() as u64,
+                    })),
+                ))
+            })
+    }
+
+    fn find(&self, name: &str) -> Option<&dyn DynTool> {
+        self.tools
+            .iter()
+            .find(|tool| tool.name() == name)
+            .map(|tool| tool.as_ref())
+    }
+}
+
+/// A short random ID correlating a tool call's logs and errors
+fn new_request_id() -> String {
+    let mut id = uuid::Uuid::new_v4().simple().to_string();
+    id.truncate(12);
+    id
+}
+
+/// Append a call to the audit log. A failure to record is logged rather
+/// than returned, as the call itself has already happened.
+fn record_audit(
+    ctx: &ToolContext,
+    tool: &str,
+    arguments: JsonObject,
+    result: &Result<CallToolResult, McpError>,
+) {
+    let Some(audit) = &ctx.audit else {
+        return;
+    };
+    let (outcome, uuids, error) = match result {
+        Ok(result) => {
+            let text: Vec<&str> = result
+                .content
+                .iter()
+                .flatten()
+                .filter_map(|c| c.as_text().map(|t| t.text.as_str()))
+                .collect();
+            let outcome = match result.is_error {
+                Some(true) => AuditOutcome::Error,
+                _ => AuditOutcome::Ok,
+            };
+            (outcome, mentioned_uuids(&text.join("\n")), None)
+        }
+        Err(e) => (AuditOutcome::Error, Vec::new(), Some(e.message.to_string())),
+    };
+    let entry = AuditEntry {
+        timestamp: Utc::now(),
+        request_id: ctx.request_id.clone(),
+        tool: tool.to_string(),
+        arguments,
+        outcome,
+        uuids,
+        error,
+    };
+    if let Err(e) = audit.record(&entry) {
+        tracing::error!("Failed to write audit log {:?}: {}", audit.path(), e);
+    }
+}
+
+/// Add `request_id` to an error's data, keeping whatever data it had
+fn with_request_id(mut error: McpError, request_id: &str) -> McpError {
+    let mut data = match error.data.take() {
+        Some(Value::Object(data)) => data,
+        Some(other) => JsonObject::from_iter([("detail".to_string(), other)]),
+        None => JsonObject::new(),
+    };
+    data.insert("request_id".to_string(), request_id.into());
+    error.data = Some(Value::Object(data));
+    error
+}
+
+/// Generate an MCP input schema (a JSON Schema object) for an argument struct
+pub fn input_schema<T: JsonSchema>() -> JsonObject {
+    let settings = SchemaSettings::draft07().with(|s| {
+        s.inline_subschemas = true;
+        s.option_add_null_type = false;
+        s.meta_schema = None;
+    });
+    let schema = settings.into_generator().into_root_schema_for::<T>();
+
+    let mut object = match serde_json::to_value(schema) {
+        Ok(Value::Object(object)) => object,
+        _ => JsonObject::new(),
+    };
+    // The tool description already covers what the arguments are for
+    object.remove("title");
+    object.remove("description");
+    object.remove("definitions");
+    // MCP clients expect a properties map even for argument-less tools
+    object
+        .entry("properties")
+        .or_insert_with(|| Value::Object(JsonObject::new()));
+    object
+}
+
+/// Deserialize tool call arguments into their typed struct
+pub fn parse_args<T: DeserializeOwned>(
+    tool: &str,
+    arguments: Option<JsonObject>,
+) -> Result<T, McpError> {
+    serde_json::from_value(Value::Object(arguments.unwrap_or_default())).map_err(|e| {
+        McpError::invalid_params(format!("Invalid arguments for {}: {}", tool, e), None)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audit::{AuditEntry, AuditLog, AuditOutcome};
+
+    use crate::logseq::api::LogSeqClient;
+    use crate::logseq::journal::format_journal_title;
+    use crate::logseq::mock::{MockGraph, MockLogSeq};
+    use crate::quota::{Quota, QuotaLimits};
+
+    use crate::tools::datascript_query::DatascriptQuery;
+    use crate::tools::get_block::GetBlockArgs;
+    use crate::tools::move_block::MoveBlockArgs;
+    use crate::tools::set_todo_state::{SetTodoStateArgs, TodoState};
+    use crate::tools::testing::{call_text, serve};
+
+    use serde_json::{Value, json};
+    use std::collections::{HashMap, HashSet};
+
+    #[test]
+    fn test_tool_names_are_unique() {
+        let tools = ToolRegistry::builtin().definitions();
+        let names: HashSet<_> = tools.iter().map(|t| t.name.clone()).collect();
+        assert_eq!(names.len(), tools.len());
+    }
+
+    #[test]
+    fn test_schemas_are_closed_objects() {
+        for tool in ToolRegistry::builtin().definitions() {
+            let schema = &tool.input_schema;
+            assert_eq!(schema["type"], "object", "{}", tool.name);
+            assert!(schema["properties"].is_object(), "{}", tool.name);
+            assert_eq!(schema["additionalProperties"], false, "{}", tool.name);
+        }
+    }
+
+    #[test]
+    fn test_schema_marks_required_fields() {
+        let schema = input_schema::<MoveBlockArgs>();
+        let required: Vec<&str> = schema["required"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .filter_map(|v| v.as_str())
+            .collect();
+        assert_eq!(required, vec!["target_uuid", "uuid"]);
+        assert_eq!(schema["properties"]["before"]["type"], "boolean");
+    }
+
+    #[test]
+    fn test_parse_args_rejects_unknown_and_missing_fields() {
+        let args = serde_json::json!({"uuid": "abc", "extra": 1});
+        assert!(parse_args::<GetBlockArgs>("get_block", args.as_object().cloned()).is_err());
+        assert!(parse_args::<GetBlockArgs>("get_block", None).is_err());
+
+        let args = serde_json::json!({"uuid": "abc", "marker": "DONE"});
+        let parsed: SetTodoStateArgs =
+            parse_args("set_todo_state", args.as_object().cloned()).unwrap();
+        assert_eq!(parsed.marker, TodoState::Done);
+    }
+
+    #[test]
+    #[should_panic(expected = "duplicate tool name: list_pages")]
+    fn test_register_rejects_duplicate_names() {
+        let mut registry = ToolRegistry::builtin();
+        registry.register(list_pages::ListPages);
+    }
+
+    struct Sleep;
+
+    impl McpTool for Sleep {
+        type Args = NoArgs;
+
+        fn name(&self) -> &'static str {
+            "sleep"
+        }
+
+        fn description(&self) -> &'static str {
+            "Sleep for a second"
+        }
+
+        async fn execute(
+            &self,
+            _ctx: &ToolContext,
+            _args: NoArgs,
+        ) -> Result<CallToolResult, McpError> {
+            tokio::time::sleep(Duration::from_secs(1)).await;
+            Ok(crate::tools::context::text_result("done"))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_call_enforces_deadline() {
+        let client = LogSeqClient::new("http://localhost:12315", "token").unwrap();
+        let ctx = ToolContext::new(client);
+        let mut registry = ToolRegistry::new();
+        registry.register(Sleep);
+        registry.set_deadline(Some(Duration::from_millis(20)));
+
+        let err = registry.call(&ctx, "sleep", None).await.unwrap_err();
+        assert_eq!(err.message, "sleep did not finish within the 20ms deadline");
+        assert_eq!(err.data.unwrap()["deadline_ms"], 20);
+    }
+
+    struct EchoRequestId;
+
+    impl McpTool for EchoRequestId {
+        type Args = NoArgs;
+
+        fn name(&self) -> &'static str {
+            "echo_request_id"
+        }
+
+        fn description(&self) -> &'static str {
+            "Return the request ID of this call"
+        }
+
+        async fn execute(
+            &self,
+            ctx: &ToolContext,
+            _args: NoArgs,
+        ) -> Result<CallToolResult, McpError> {
+            Ok(crate::tools::context::text_result(
+                ctx.request_id.clone().unwrap_or_default(),
+            ))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_each_call_gets_a_request_id() {
+        let client = LogSeqClient::new("http://localhost:12315", "token").unwrap();
+        let ctx = ToolContext::new(client);
+        let mut registry = ToolRegistry::new();
+        registry.register(EchoRequestId);
+
+        let mut ids = HashSet::new();
+        for _ in 0..2 {
+            let result = registry.call(&ctx, "echo_request_id", None).await.unwrap();
+            let text = serde_json::to_value(&result).unwrap()["content"][0]["text"].clone();
+            assert_eq!(text.as_str().unwrap().len(), 12);
+            ids.insert(text);
+        }
+        assert_eq!(ids.len(), 2);
+        assert!(ctx.request_id.is_none());
+
+        let args = serde_json::json!({"unexpected": true});
+        let err = registry
+            .call(&ctx, "echo_request_id", args.as_object().cloned())
+            .await
+            .unwrap_err();
+        assert_eq!(err.data.unwrap()["request_id"].as_str().unwrap().len(), 12);
+    }
+
+    #[test]
+    fn test_read_only_session_lists_only_read_only_tools() {
+        let registry = ToolRegistry::builtin();
+        let session = SessionConfig {
+            read_only: true,
+            ..Default::default()
+        };
+        let names: HashSet<_> = registry
+            .definitions_for(&session)
+            .into_iter()
+            .map(|t| t.name.to_string())
+            .collect();
+
+        assert!(names.contains("search"));
+        assert!(names.contains("get_page_content"));
+        for mutating in ["create_page", "update_block", "delete_page", "move_block"] {
+            assert!(!names.contains(mutating), "{} should be hidden", mutating);
+        }
+        for tool in registry.definitions_for(&session) {
+            assert_eq!(tool.annotations.unwrap().read_only_hint, Some(true));
+        }
+    }
+
+    #[test]
+    fn test_annotations_flag_destructive_tools() {
+        let definitions: HashMap<_, _> = ToolRegistry::builtin()
+            .definitions()
+            .into_iter()
+            .map(|t| (t.name.to_string(), t.annotations.unwrap()))
+            .collect();
+        let hints = |name: &str| {
+            let annotations = &definitions[name];
+            (
+                annotations.read_only_hint.unwrap(),
+                annotations.destructive_hint.unwrap(),
+                annotations.idempotent_hint.unwrap(),
+            )
+        };
+
+        assert_eq!(hints("search"), (true, false, true));
+        assert_eq!(hints("delete_page"), (false, true, true));
+        assert_eq!(hints("update_block"), (false, true, false));
+        assert_eq!(hints("create_block"), (false, false, false));
+        assert_eq!(hints("add_tag_to_block"), (false, false, true));
+        for (name, annotations) in &definitions {
+            if annotations.read_only_hint == Some(true) {
+                assert_eq!(annotations.destructive_hint, Some(false), "{}", name);
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_call_refuses_tools_outside_the_session() {
+        let client = LogSeqClient::new("http://localhost:12315", "token").unwrap();
+        let ctx = ToolContext::new(client).for_session(SessionConfig {
+            allowed_tools: Some(["search".to_string()].into()),
+            ..Default::default()
+        });
+
+        let err = ToolRegistry::builtin()
+            .call(&ctx, "list_pages", None)
+            .await
+            .unwrap_err();
+        assert_eq!(err.code, rmcp::model::ErrorCode::INVALID_REQUEST);
+        assert_eq!(
+            err.message,
+            "list_pages is not available: it is not in the session's allowed tools"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_call_unknown_tool_is_method_not_found() {
+        let client = LogSeqClient::new("http://localhost:12315", "token").unwrap();
+        let ctx = ToolContext::new(client);
+
+        let err = ToolRegistry::builtin()
+            .call(&ctx, "no_such_tool", None)
+            .await
+            .unwrap_err();
+        assert_eq!(err.code, rmcp::model::ErrorCode::METHOD_NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_tool_examples_run_against_the_mock_graph() {
+        let registry = ToolRegistry::builtin();
+        let description = registry
+            .find("datascript_query")
+            .unwrap()
+            .definition()
+            .description
+            .unwrap();
+        let (_, examples) = description.split_once("\n\nExample arguments:\n").unwrap();
+        let examples: Vec<Value> = examples
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(examples, McpTool::examples(&DatascriptQuery));
+
+        let mut ran = 0;
+        for tool in &registry.tools {
+            for example in tool.examples() {
+                let mut graph = MockGraph::new();
+                graph.add_page("Projects");
+                graph.add_block("Projects", "TODO [#A] Draft the launch plan #work #wip");
+                graph.add_block(
+                    "Projects",
+                    "Launch plan budget\nSCHEDULED: <2025-01-06 Mon>",
+                );
+                graph.add_page("Templates/Meeting");
+                graph.add_block("Templates/Meeting", "Agenda for {{topic}}");
+                let (_logseq, ctx) = serve(graph).await;
+
+                let result = registry
+                    .call(&ctx, tool.name(), example.as_object().cloned())
+                    .await
+                    .unwrap_or_else(|e| {
+                        panic!("{} example {} failed: {:?}", tool.name(), example, e)
+                    });
+                assert_ne!(
+                    result.is_error,
+                    Some(true),
+                    "{} example {}",
+                    tool.name(),
+                    example
+                );
+                ran += 1;
+            }
+        }
+        assert!(ran >= 10, "only {} examples ran", ran);
+    }
+
+    #[tokio::test]
+    async fn test_write_budget_refuses_until_reset() {
+        let mut graph = MockGraph::new();
+        graph.add_page("Inbox");
+        let errand = graph.add_block("Inbox", "Errands");
+        graph.add_block(&errand, "Buy milk");
+        let (logseq, mut ctx) = serve(graph).await;
+        ctx.quota = Arc::new(Quota::new(QuotaLimits {
+            pages_created: Some(1),
+            blocks_deleted: Some(1),
+            mutations: None,
+        }));
+
+        call_text(&ctx, "create_page", json!({"name": "First"}))
+            .await
+            .unwrap();
+        let err = call_text(&ctx, "create_page", json!({"name": "Second"}))
+            .await
+            .unwrap_err();
+        assert!(
+            err.message.contains("reset_write_budget"),
+            "{}",
+            err.message
+        );
+        assert!(!logseq.with_graph(|g| g.has_page("Second")));
+
+        // Deleting a block counts its children too
+        let preview = call_text(&ctx, "delete_block", json!({"uuid": errand}))
+            .await
+            .unwrap();
+        let token = preview
+            .split("confirm: \"")
+            .nth(1)
+            .unwrap()
+            .split('"')
+            .next()
+            .unwrap();
+        let args = json!({"uuid": errand, "confirm": token});
+        let err = call_text(&ctx, "delete_block", args).await.unwrap_err();
+        assert!(err.message.contains("blocks deleted"), "{}", err.message);
+        assert_eq!(logseq.with_graph(|g| g.children("Inbox").len()), 1);
+
+        let text = call_text(&ctx, "reset_write_budget", json!({}))
+            .await
+            .unwrap();
+        assert!(text.contains("Pages created: 1 of 1"), "{}", text);
+        call_text(&ctx, "create_page", json!({"name": "Second"}))
+            .await
+            .unwrap();
+        assert!(logseq.with_graph(|g| g.has_page("Second")));
+    }
+
+    #[tokio::test]
+    async fn test_changes_are_recorded_in_the_audit_log() {
+        let mut graph = MockGraph::new();
+        graph.add_page("Inbox");
+        let logseq = MockLogSeq::serve(graph).await.unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("audit.jsonl");
+        let mut ctx = ToolContext::new(logseq.client());
+        ctx.audit = Some(Arc::new(AuditLog::open(&path).unwrap()));
+
+        call_text(
+            &ctx,
+            "create_block",
+            json!({"content": "Hello", "parent": "Inbox"}),
+        )
+        .await
+        .unwrap();
+        call_text(&ctx, "list_pages", json!({})).await.unwrap();
+        let preview = call_text(&ctx, "delete_page", json!({"page_name": "Inbox"}))
+            .await
+            .unwrap();
+        assert!(preview.contains("confirm"), "{}", preview);
+        call_text(
+            &ctx,
+            "update_block",
+            json!({"uuid": "missing", "content": "x"}),
+        )
+        .await
+        .unwrap_err();
+
+        let entries: Vec<AuditEntry> = std::fs::read_to_string(&path)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        let tools: Vec<&str> = entries.iter().map(|e| e.tool.as_str()).collect();
+        assert_eq!(tools, vec!["create_block", "update_block"]);
+
+        let created = &entries[0];
+        assert_eq!(created.outcome, AuditOutcome::Ok);
+        assert_eq!(created.arguments["content"], "Hello");
+        assert_eq!(created.uuids, logseq.with_graph(|g| g.children("Inbox")));
+        assert_eq!(created.request_id.as_ref().unwrap().len(), 12);
+
+        assert_eq!(entries[1].outcome, AuditOutcome::Error);
+        assert!(entries[1].error.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_changes_are_journaled_when_asked() {
+        let mut graph = MockGraph::new();
+        graph.add_page("Inbox");
+        let (_logseq, mut ctx) = serve(graph).await;
+        ctx.journal_activity = true;
+
+        for content in ["Hello", "Again"] {
+            call_text(
+                &ctx,
+                "create_block",
+                json!({"content": content, "parent": "Inbox"}),
+            )
+            .await
+            .unwrap();
+        }
+        call_text(&ctx, "list_pages", json!({})).await.unwrap();
+        call_text(
+            &ctx,
+            "update_block",
+            json!({"uuid": "missing", "content": "x"}),
+        )
+        .await
+        .unwrap_err();
+
+        let date_format = ctx.cache.preferred_date_format().await.unwrap();
+        let today = format_journal_title(chrono::Local::now().date_naive(), &date_format);
+        let tree = ctx.client.get_page_blocks_tree(&today).await.unwrap();
+        let heading = &tree.items[1];
+        assert_eq!(heading.content, "Agent activity");
+        let entries: Vec<&str> = heading
+            .children
+            .iter()
+            .map(|b| b.content.as_str())
+            .collect();
+        assert_eq!(entries.len(), 2, "{:?}", entries);
+        assert!(
+            entries[0].ends_with("`create_block` content: `Hello`, parent: `Inbox`"),
+            "{}",
+            entries[0]
+        );
+        assert!(entries[1].contains("`Again`"));
+    }
+}
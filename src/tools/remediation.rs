@@ -0,0 +1,245 @@
+//! Filling in the data of tool errors so agents can correct themselves
+//!
+//! Whatever a tool fails with, the error's data says what kind of failure it
+//! was (`error`), whether the same call might work later (`retryable`), the
+//! tool, the argument at fault where that can be told, and calls that might
+//! help next (`suggested_calls`): the same call with the page name LogSeq
+//! probably meant, `resolve_page_name` for a page that isn't there,
+//! `health_check` when LogSeq can't be reached, and so on. Anything the
+//! tool already put in the data is kept.
+
+use rmcp::{
+    ErrorData as McpError,
+    model::{ErrorCode, JsonObject},
+};
+use serde_json::{Value, json};
+
+/// Keys of error data that describe the failure rather than repeat an
+/// argument
+const RESERVED: &[&str] = &[
+    "error",
+    "retryable",
+    "retry_after_secs",
+    "tool",
+    "argument",
+    "kind",
+    "name",
+    "suggestions",
+    "suggested_calls",
+    "request_id",
+    "limit",
+    "budget",
+    "status",
+    "graph",
+    "namespace",
+    "deadline_ms",
+    "detail",
+];
+
+/// `error` with its data filled in for a call to `tool` with `arguments`
+pub fn remediate(mut error: McpError, tool: &str, arguments: &JsonObject) -> McpError {
+    let mut data = match error.data.take() {
+        Some(Value::Object(data)) => data,
+        Some(other) => JsonObject::from_iter([("detail".to_string(), other)]),
+        None => JsonObject::new(),
+    };
+
+    let kind = match data.get("error").and_then(Value::as_str) {
+        Some(kind) => kind.to_string(),
+        None => error_kind(&error, &data).to_string(),
+    };
+    data.entry("error").or_insert_with(|| kind.as_str().into());
+    data.entry("retryable")
+        .or_insert_with(|| (kind == "deadline_exceeded").into());
+    data.entry("tool").or_insert_with(|| tool.into());
+    if !data.contains_key("argument")
+        && let Some(argument) = failing_argument(&error, &data, arguments)
+    {
+        data.insert("argument".to_string(), argument.into());
+    }
+
+    let calls = suggested_calls(&kind, tool, &data, arguments);
+    if !calls.is_empty() && !data.contains_key("suggested_calls") {
+        data.insert("suggested_calls".to_string(), Value::Array(calls));
+    }
+    error.data = Some(Value::Object(data));
+    error
+}
+
+/// The kind of an error that didn't say, judged by its code and data
+fn error_kind(error: &McpError, data: &JsonObject) -> &'static str {
+    if data.contains_key("budget") {
+        "quota_exhausted"
+    } else if data.contains_key("limit") {
+        "write_limit"
+    } else if data.contains_key("confirm") {
+        "confirmation_invalid"
+    } else if data.contains_key("deadline_ms") {
+        "deadline_exceeded"
+    } else if error.code == ErrorCode::INVALID_PARAMS {
+        "invalid_arguments"
+    } else if error.code == ErrorCode::INVALID_REQUEST {
+        "invalid_request"
+    } else if error.code == ErrorCode::METHOD_NOT_FOUND {
+        "unknown_tool"
+    } else {
+        "internal_error"
+    }
+}
+
+/// The argument an error is about: one serde names in a parse error, one
+/// holding the name that wasn't found, or one whose key the data repeats
+fn failing_argument(error: &McpError, data: &JsonObject, arguments: &JsonObject) -> Option<String> {
+    for pattern in ["missing field `", "unknown field `", "duplicate field `"] {
+        if let Some((_, rest)) = error.message.split_once(pattern) {
+            return rest.split_once('`').map(|(field, _)| field.to_string());
+        }
+    }
+    let value = data.get("name").or_else(|| data.get("key"));
+    if let Some((key, _)) = arguments
+        .iter()
+        .find(|(_, argument)| value.is_some_and(|value| *argument == value))
+    {
+        return Some(key.clone());
+    }
+    data.keys()
+        .find(|key| !RESERVED.contains(&key.as_str()) && arguments.contains_key(*key))
+        .cloned()
+}
+
+/// Calls that might get past the error
+fn suggested_calls(
+    kind: &str,
+    tool: &str,
+    data: &JsonObject,
+    arguments: &JsonObject,
+) -> Vec<Value> {
+    let mut calls = Vec::new();
+    match kind {
+        "not_found" if data.get("kind").and_then(Value::as_str) == Some("page") => {
+            let name = data.get("name").cloned().unwrap_or_default();
+            let best = data
+                .get("suggestions")
+                .and_then(Value::as_array)
+                .and_then(|suggestions| suggestions.first());
+            let argument = data.get("argument").and_then(Value::as_str);
+            if let (Some(best), Some(argument)) = (best, argument) {
+                let mut retry = arguments.clone();
+                retry.insert(argument.to_string(), best.clone());
+                calls.push(json!({
+                    "tool": tool,
+                    "arguments": retry,
+                    "reason": "retry with the closest existing page name",
+                }));
+            }
+            calls.push(json!({
+                "tool": "resolve_page_name",
+                "arguments": { "name": name },
+                "reason": "find the page's exact name, allowing for case, aliases, and typos",
+            }));
+            calls.push(json!({
+                "tool": "list_pages",
+                "arguments": {},
+                "reason": "see which pages exist",
+            }));
+        }
+        "not_found" => calls.push(json!({
+            "tool": "search",
+            "reason": "search for the block's text to find its current UUID",
+        })),
+        "backend_unavailable" | "unauthorized" | "timeout" => calls.push(json!({
+            "tool": "health_check",
+            "arguments": {},
+            "reason": "check whether LogSeq is reachable and accepts the token",
+        })),
+        "quota_exhausted" | "write_limit" => calls.push(json!({
+            "tool": "reset_write_budget",
+            "arguments": {},
+            "reason": "only once the user has reviewed the changes so far",
+        })),
+        "confirmation_invalid" => {
+            let mut preview = arguments.clone();
+            preview.remove("confirm");
+            calls.push(json!({
+                "tool": tool,
+                "arguments": preview,
+                "reason": "preview the change again for a new confirmation token",
+            }));
+        }
+        _ => {}
+    }
+    calls
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn object(value: Value) -> JsonObject {
+        value.as_object().cloned().unwrap()
+    }
+
+    #[test]
+    fn test_parse_errors_name_the_argument() {
+        let error =
+            McpError::invalid_params("Invalid arguments for search: missing field `query`", None);
+        let data = remediate(error, "search", &JsonObject::new()).data.unwrap();
+        assert_eq!(
+            data,
+            json!({
+                "error": "invalid_arguments",
+                "retryable": false,
+                "tool": "search",
+                "argument": "query",
+            })
+        );
+    }
+
+    #[test]
+    fn test_missing_pages_suggest_the_closest_name() {
+        let error = McpError::invalid_params(
+            "Page not found: Projetcs. Did you mean: Projects?",
+            Some(json!({
+                "error": "not_found",
+                "retryable": false,
+                "kind": "page",
+                "name": "Projetcs",
+                "suggestions": ["Projects"],
+            })),
+        );
+        let arguments = object(json!({"page": "Projetcs", "content": "Hi"}));
+        let data = remediate(error, "append_block_to_page", &arguments)
+            .data
+            .unwrap();
+        assert_eq!(data["argument"], "page");
+        let calls = data["suggested_calls"].as_array().unwrap();
+        assert_eq!(
+            calls[0]["arguments"],
+            json!({"page": "Projects", "content": "Hi"})
+        );
+        assert_eq!(calls[1]["tool"], "resolve_page_name");
+        assert_eq!(calls[1]["arguments"], json!({"name": "Projetcs"}));
+    }
+
+    #[test]
+    fn test_kinds_are_inferred_from_the_data() {
+        let error =
+            McpError::invalid_request("Budget used up", Some(json!({"budget": "mutations"})));
+        let data = remediate(error, "create_block", &JsonObject::new())
+            .data
+            .unwrap();
+        assert_eq!(data["error"], "quota_exhausted");
+        assert_eq!(data["suggested_calls"][0]["tool"], "reset_write_budget");
+
+        let error = McpError::invalid_params(
+            "Invalid due_before 'soon'",
+            Some(json!({"due_before": "soon"})),
+        );
+        let arguments = object(json!({"due_before": "soon"}));
+        let data = remediate(error, "find_scheduled_tasks", &arguments)
+            .data
+            .unwrap();
+        assert_eq!(data["argument"], "due_before");
+        assert!(data.get("suggested_calls").is_none());
+    }
+}
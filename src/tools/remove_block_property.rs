@@ -0,0 +1,93 @@
+//! The `remove_block_property` tool
+
+use super::context::{ToolContext, logseq_error, require_property_key, text_result};
+use super::registry::McpTool;
+use rmcp::{ErrorData as McpError, model::CallToolResult};
+use schemars::JsonSchema;
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct RemoveBlockPropertyArgs {
+    /// The UUID of the block to remove the property from.
+    pub uuid: String,
+    /// The property key to remove, as written before '::' in the block.
+    pub key: String,
+}
+
+pub struct RemoveBlockProperty;
+
+impl McpTool for RemoveBlockProperty {
+    type Args = RemoveBlockPropertyArgs;
+
+    fn name(&self) -> &'static str {
+        "remove_block_property"
+    }
+
+    fn description(&self) -> &'static str {
+        "Remove a property from a block by UUID. Only the property line is deleted; the block's content, other properties, and children are left as they are. Reports when the property was not set."
+    }
+
+    fn idempotent(&self) -> bool {
+        true
+    }
+
+    async fn execute(
+        &self,
+        ctx: &ToolContext,
+        args: RemoveBlockPropertyArgs,
+    ) -> Result<CallToolResult, McpError> {
+        let RemoveBlockPropertyArgs { uuid, key } = args;
+        require_property_key(&key)?;
+        ctx.require_writable(&uuid).await?;
+
+        let current = ctx
+            .client
+            .get_block_property(&uuid, &key)
+            .await
+            .map_err(logseq_error)?;
+        if current.is_none() {
+            return Ok(text_result(format!(
+                "Property '{}' was not set on block {}; nothing removed",
+                key, uuid
+            )));
+        }
+
+        ctx.client
+            .remove_block_property(&uuid, &key)
+            .await
+            .map_err(logseq_error)?;
+
+        Ok(text_result(format!(
+            "Removed property '{}' from block {}",
+            key, uuid
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::logseq::mock::MockGraph;
+    use crate::tools::testing::{call_text, serve};
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_property_is_removed_once() {
+        let mut graph = MockGraph::new();
+        graph.add_page("Projects");
+        let task = graph.add_block("Projects", "Ship it\nstatus:: done");
+        let (logseq, ctx) = serve(graph).await;
+
+        let args = json!({"uuid": task, "key": "status"});
+        let text = call_text(&ctx, "remove_block_property", args.clone())
+            .await
+            .unwrap();
+        assert!(text.starts_with("Removed property 'status'"), "{}", text);
+        logseq.with_graph(|g| assert_eq!(g.block_content(&task), Some("Ship it")));
+
+        let text = call_text(&ctx, "remove_block_property", args)
+            .await
+            .unwrap();
+        assert!(text.ends_with("nothing removed"), "{}", text);
+    }
+}
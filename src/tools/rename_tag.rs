@@ -0,0 +1,237 @@
+//! The `rename_tag` tool
+
+use super::add_tag_to_block::tag_name;
+use super::context::{ToolContext, internal_error, logseq_error, text_result};
+use super::registry::McpTool;
+use crate::intents::InverseOp;
+use crate::logseq::api::SearchOptions;
+use crate::logseq::content::{format_tag, rename_tag};
+use crate::tools::is_properties_block;
+use rmcp::{ErrorData as McpError, model::CallToolResult};
+use schemars::JsonSchema;
+use serde::Deserialize;
+use serde_json::{Value, json};
+use std::collections::HashSet;
+
+/// Search results fetched for the tag at first. The search API has no
+/// offset, so when they fill up it is asked again for twice as many.
+const FIRST_SEARCH_LIMIT: usize = 1000;
+
+/// The most search results checked for the tag in one call
+const MAX_SEARCH_RESULTS: usize = 10_000;
+
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct RenameTagArgs {
+    /// The tag to rename, without '#'.
+    pub from: String,
+    /// Its new name, without '#'. Names with spaces are written as #[[multi word tag]].
+    pub to: String,
+}
+
+pub struct RenameTag;
+
+impl McpTool for RenameTag {
+    type Args = RenameTagArgs;
+
+    fn name(&self) -> &'static str {
+        "rename_tag"
+    }
+
+    fn description(&self) -> &'static str {
+        "Rename a tag across the graph: every #tag, #[[tag]], and [[tag]] reference in blocks found by searching for it, and every 'tags::' property (including pages' tags) naming it, is rewritten to the new name. Names are matched ignoring case. The old tag's page itself is left in place. At most 10000 blocks matching the tag's name are checked per call; if more matched and some were renamed, the result says so and calling it again continues."
+    }
+
+    fn examples(&self) -> Vec<Value> {
+        vec![json!({"from": "wip", "to": "in progress"})]
+    }
+
+    fn idempotent(&self) -> bool {
+        true
+    }
+
+    async fn execute(
+        &self,
+        ctx: &ToolContext,
+        args: RenameTagArgs,
+    ) -> Result<CallToolResult, McpError> {
+        let from = tag_name("from", &args.from)?;
+        let to = tag_name("to", &args.to)?;
+        if from == to {
+            return Err(McpError::invalid_params(
+                "'from' and 'to' are the same tag",
+                Some(serde_json::json!({ "from": from, "to": to })),
+            ));
+        }
+
+        let mut limit = FIRST_SEARCH_LIMIT;
+        let results = loop {
+            let options = SearchOptions {
+                limit: Some(limit),
+                ..Default::default()
+            };
+            let results = ctx
+                .client
+                .search(from, &options)
+                .await
+                .map_err(logseq_error)?;
+            if results.len() < limit || limit == MAX_SEARCH_RESULTS {
+                break results;
+            }
+            limit = (limit * 2).min(MAX_SEARCH_RESULTS);
+        };
+        // Most matches can be plain mentions of the name rather than the
+        // tag, so a full last page only means more work if it found some
+        let capped = results.len() == MAX_SEARCH_RESULTS;
+        let mut candidates: Vec<(String, String)> = results
+            .into_iter()
+            .filter(|r| ctx.can_write_page(&r.page_name))
+            .map(|r| (r.block.uuid, r.block.content))
+            .collect();
+
+        // Search may not index property text, so pages tagged with it are
+        // looked up separately. Their tags live in their first block.
+        let lowered = from.to_lowercase();
+        let usage = ctx.cache.tag_usage().await.map_err(logseq_error)?;
+        for page in usage
+            .iter()
+            .filter(|entry| entry.tags.contains(&lowered))
+            .filter_map(|entry| entry.page.as_deref())
+            .filter(|page| ctx.can_write_page(page))
+        {
+            let blocks = ctx
+                .client
+                .get_page_blocks_tree(page)
+                .await
+                .map_err(logseq_error)?;
+            if let Some(first) = blocks.first().filter(|b| is_properties_block(b)) {
+                candidates.push((first.uuid.clone(), first.content.clone()));
+            }
+        }
+
+        let mut seen = HashSet::new();
+        let changes: Vec<(String, String, String)> = candidates
+            .into_iter()
+            .filter(|(uuid, _)| seen.insert(uuid.clone()))
+            .filter_map(|(uuid, content)| {
+                let renamed = rename_tag(&content, from, to);
+                (renamed != content).then_some((uuid, content, renamed))
+            })
+            .collect();
+        if changes.is_empty() {
+            let mut text = format!("No references to {} were found", format_tag(from));
+            if capped {
+                text.push_str(&format!(
+                    " in the first {} search results",
+                    MAX_SEARCH_RESULTS
+                ));
+            }
+            return Ok(text_result(text));
+        }
+        for (_, _, renamed) in &changes {
+            ctx.charge_write(None, renamed)?;
+        }
+
+        let mut intent = ctx.begin_intent(
+            self.name(),
+            changes
+                .iter()
+                .map(|(uuid, _, _)| format!("rename the tag in block {}", uuid))
+                .collect(),
+        )?;
+        for (i, (uuid, content, renamed)) in changes.iter().enumerate() {
+            ctx.client
+                .update_block(uuid, renamed, None)
+                .await
+                .map_err(|e| {
+                    internal_error(format!(
+                        "Renamed the tag in {} block(s), then failed to update block {}: {}",
+                        i, uuid, e
+                    ))
+                })?;
+            intent.done(
+                i,
+                vec![InverseOp::RestoreContent {
+                    uuid: uuid.clone(),
+                    content: content.clone(),
+                }],
+            );
+        }
+        intent.finish();
+        ctx.cache.invalidate_pages();
+
+        let mut text = format!(
+            "Renamed {} to {} in {} block(s)",
+            format_tag(from),
+            format_tag(to),
+            changes.len()
+        );
+        if let Some(namespace) = &ctx.namespace {
+            text.push_str(&format!(
+                " on pages in {}; references elsewhere were left alone",
+                namespace.prefix()
+            ));
+        }
+        if capped {
+            text.push_str(&format!(
+                "\n\nThe search stopped at {} results, so there may be more; call rename_tag again to continue.",
+                MAX_SEARCH_RESULTS
+            ));
+        }
+        Ok(text_result(text))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::logseq::mock::MockGraph;
+    use crate::tools::testing::{call_text, serve};
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_every_form_of_the_tag_is_renamed() {
+        let mut graph = MockGraph::new();
+        graph.add_page("Notes");
+        let props = graph.add_block("Notes", "tags:: rust, web");
+        let inline = graph.add_block("Notes", "Learning #rust today");
+        let link = graph.add_block("Notes", "See [[Rust]] #[[Big Ideas]]");
+        let (logseq, ctx) = serve(graph).await;
+
+        let args = json!({"from": "rust", "to": "rustlang"});
+        let text = call_text(&ctx, "rename_tag", args).await.unwrap();
+        assert!(text.contains("in 3 block(s)"), "{}", text);
+        logseq.with_graph(|g| {
+            assert_eq!(g.block_content(&props), Some("tags:: rustlang, web"));
+            assert_eq!(g.block_content(&inline), Some("Learning #rustlang today"));
+            assert_eq!(
+                g.block_content(&link),
+                Some("See [[rustlang]] #[[Big Ideas]]")
+            );
+        });
+
+        let args = json!({"from": "rust", "to": "rustlang"});
+        let text = call_text(&ctx, "rename_tag", args).await.unwrap();
+        assert_eq!(text, "No references to #rust were found");
+    }
+
+    #[tokio::test]
+    async fn test_search_is_paged_past_plain_mentions() {
+        let mut graph = MockGraph::new();
+        graph.add_page("Log");
+        for i in 0..1500 {
+            graph.add_block("Log", &format!("Read about rust, part {}", i));
+        }
+        let tagged = graph.add_block("Log", "Finished the book #rust");
+        let (logseq, ctx) = serve(graph).await;
+
+        let args = json!({"from": "rust", "to": "rustlang"});
+        let text = call_text(&ctx, "rename_tag", args).await.unwrap();
+        assert_eq!(text, "Renamed #rust to #rustlang in 1 block(s)");
+        logseq.with_graph(|g| {
+            assert_eq!(
+                g.block_content(&tagged),
+                Some("Finished the book #rustlang")
+            );
+        });
+    }
+}
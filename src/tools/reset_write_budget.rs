@@ -0,0 +1,98 @@
+//! The `reset_write_budget` tool
+
+use super::context::{ToolContext, text_result};
+use super::registry::{McpTool, NoArgs};
+use rmcp::{ErrorData as McpError, model::CallToolResult};
+
+pub struct ResetWriteBudget;
+
+impl McpTool for ResetWriteBudget {
+    type Args = NoArgs;
+
+    fn name(&self) -> &'static str {
+        "reset_write_budget"
+    }
+
+    fn description(&self) -> &'static str {
+        "Reset this session's budgets for pages created, blocks deleted, and changes made, after write tools have started refusing because one ran out. Only call this when the user explicitly asks to: the budgets exist so that a person reviews what has been changed before more changes are made."
+    }
+
+    fn read_only(&self) -> bool {
+        true
+    }
+
+    fn needs_logseq(&self) -> bool {
+        false
+    }
+
+    async fn execute(&self, ctx: &ToolContext, _args: NoArgs) -> Result<CallToolResult, McpError> {
+        let limits = ctx.quota.limits();
+        if limits.is_unlimited() {
+            return Ok(text_result("This server doesn't limit changes per session"));
+        }
+
+        let used = ctx.quota.usage();
+        ctx.quota.reset();
+        let mut text = String::from("Write budget reset. Used before the reset:\n");
+        for (label, used, limit) in [
+            ("Pages created", used.pages_created, limits.pages_created),
+            ("Blocks deleted", used.blocks_deleted, limits.blocks_deleted),
+            ("Changes", used.mutations, limits.mutations),
+        ] {
+            match limit {
+                Some(limit) => text.push_str(&format!("- {}: {} of {}\n", label, used, limit)),
+                None => text.push_str(&format!("- {}: {} (unlimited)\n", label, used)),
+            }
+        }
+        Ok(text_result(text))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::logseq::mock::MockGraph;
+    use crate::quota::{Quota, QuotaLimits};
+    use crate::tools::testing::{call_text, serve};
+    use serde_json::json;
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn test_used_up_budget_is_reset() {
+        let mut graph = MockGraph::new();
+        graph.add_page("Inbox");
+        let (_logseq, mut ctx) = serve(graph).await;
+
+        let text = call_text(&ctx, "reset_write_budget", json!({}))
+            .await
+            .unwrap();
+        assert_eq!(text, "This server doesn't limit changes per session");
+
+        ctx.quota = Arc::new(Quota::new(QuotaLimits {
+            mutations: Some(1),
+            ..QuotaLimits::default()
+        }));
+        let args = json!({"content": "First", "parent": "Inbox"});
+        call_text(&ctx, "create_block", args).await.unwrap();
+        let args = json!({"content": "Second", "parent": "Inbox"});
+        let error = call_text(&ctx, "create_block", args.clone())
+            .await
+            .unwrap_err();
+        assert!(
+            error.message.contains("budget of 1 changes"),
+            "{}",
+            error.message
+        );
+
+        let text = call_text(&ctx, "reset_write_budget", json!({}))
+            .await
+            .unwrap();
+        assert_eq!(
+            text,
+            "Write budget reset. Used before the reset:\n\
+             - Pages created: 0 (unlimited)\n\
+             - Blocks deleted: 0 (unlimited)\n\
+             - Changes: 1 of 1\n"
+        );
+        call_text(&ctx, "create_block", args).await.unwrap();
+    }
+}
@@ -0,0 +1,184 @@
+//! The `resolve_interrupted_operation` tool
+
+use super::context::{ToolContext, internal_error, text_result};
+use super::registry::McpTool;
+use crate::intents::{InverseOp, StepStatus};
+use rmcp::{ErrorData as McpError, model::CallToolResult};
+use schemars::JsonSchema;
+use serde::Deserialize;
+
+/// What to do with an interrupted operation
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Resolution {
+    /// Undo the steps that were done
+    Rollback,
+    /// Forget the operation, leaving the graph as it is
+    Dismiss,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct ResolveInterruptedOperationArgs {
+    /// The id of the operation, as shown by list_interrupted_operations.
+    pub id: String,
+    /// 'rollback' to undo the steps that were done, or 'dismiss' to forget the operation once its remaining steps have been finished some other way (or aren't wanted).
+    pub action: Resolution,
+}
+
+pub struct ResolveInterruptedOperation;
+
+impl McpTool for ResolveInterruptedOperation {
+    type Args = ResolveInterruptedOperationArgs;
+
+    fn name(&self) -> &'static str {
+        "resolve_interrupted_operation"
+    }
+
+    fn description(&self) -> &'static str {
+        "Deal with an operation listed by list_interrupted_operations: 'rollback' undoes its completed steps, most recent first (deleting pages and blocks it created and restoring block content it replaced), and 'dismiss' just forgets it. If a rollback fails, the steps already undone are recorded and calling again carries on from there."
+    }
+
+    fn idempotent(&self) -> bool {
+        true
+    }
+
+    async fn execute(
+        &self,
+        ctx: &ToolContext,
+        args: ResolveInterruptedOperationArgs,
+    ) -> Result<CallToolResult, McpError> {
+        let ResolveInterruptedOperationArgs { id, action } = args;
+        let log = ctx.require_intents()?;
+        let mut intent = log
+            .get(&id)
+            .map_err(internal_error)?
+            .filter(|intent| intent.is_interrupted())
+            .ok_or_else(|| {
+                McpError::invalid_params(
+                    format!(
+                        "No interrupted operation with id {}; use list_interrupted_operations to see them",
+                        id
+                    ),
+                    Some(serde_json::json!({ "id": id })),
+                )
+            })?;
+
+        if action == Resolution::Dismiss {
+            log.remove(&intent.id).map_err(internal_error)?;
+            return Ok(text_result(format!(
+                "Dismissed operation {} ({})",
+                intent.id, intent.tool
+            )));
+        }
+
+        let mut undone = 0;
+        for i in (0..intent.steps.len()).rev() {
+            if intent.steps[i].status != StepStatus::Done {
+                continue;
+            }
+            for op in &intent.steps[i].undo {
+                undo(ctx, op).await.map_err(|e| {
+                    internal_error(format!(
+                        "Rolled back {} step(s), then failed to {}: {}",
+                        undone,
+                        op.describe(),
+                        e
+                    ))
+                })?;
+            }
+            // Record progress so a failed rollback can be retried
+            let step = &mut intent.steps[i];
+            step.status = StepStatus::Pending;
+            step.undo.clear();
+            log.save(&intent).map_err(internal_error)?;
+            undone += 1;
+        }
+        log.remove(&intent.id).map_err(internal_error)?;
+
+        Ok(text_result(format!(
+            "Rolled back {} step(s) of operation {} ({})",
+            undone, intent.id, intent.tool
+        )))
+    }
+}
+
+async fn undo(ctx: &ToolContext, op: &InverseOp) -> anyhow::Result<()> {
+    match op {
+        InverseOp::RemoveBlock { uuid } => ctx.client.remove_block(uuid).await,
+        InverseOp::RestoreContent { uuid, content } => ctx
+            .client
+            .update_block(uuid, content, None)
+            .await
+            .map(|_| ()),
+        InverseOp::DeletePage { name } => {
+            let _guard = ctx.page_locks.lock(name).await;
+            ctx.client.delete_page(name).await?;
+            ctx.cache.invalidate_pages();
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::intents::{IntentLog, InverseOp, RunningIntent};
+    use crate::logseq::mock::{MockGraph, MockLogSeq};
+    use crate::tools::context::ToolContext;
+    use crate::tools::testing::call_text;
+    use serde_json::json;
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn test_interrupted_operation_is_rolled_back() {
+        let mut graph = MockGraph::new();
+        graph.add_page("Half Imported");
+        let fixed = graph.add_block("Half Imported", "Corrected text");
+        let logseq = MockLogSeq::serve(graph).await.unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let mut ctx = ToolContext::new(logseq.client());
+        let log = Arc::new(IntentLog::new(dir.path()));
+        ctx.intents = Some(log.clone());
+
+        // A finished operation leaves nothing to resolve
+        let args = json!({"page": "Imported", "markdown": "# Heading\n\nBody"});
+        call_text(&ctx, "import_markdown", args).await.unwrap();
+        let text = call_text(&ctx, "list_interrupted_operations", json!({}))
+            .await
+            .unwrap();
+        assert_eq!(text, "No operations were interrupted.");
+
+        let steps = vec!["create page".into(), "correct block".into(), "write".into()];
+        let mut running = RunningIntent::begin(Some(&log), "test", None, steps).unwrap();
+        running.done(
+            0,
+            vec![InverseOp::DeletePage {
+                name: "Half Imported".into(),
+            }],
+        );
+        running.done(
+            1,
+            vec![InverseOp::RestoreContent {
+                uuid: fixed.clone(),
+                content: "Corected text".into(),
+            }],
+        );
+        drop(running);
+
+        let text = call_text(&ctx, "list_interrupted_operations", json!({}))
+            .await
+            .unwrap();
+        assert!(text.contains("- [x] correct block"), "{}", text);
+        assert!(text.contains("- [ ] write"), "{}", text);
+        assert!(text.contains("delete page 'Half Imported'"), "{}", text);
+        let id = log.interrupted().unwrap()[0].id.clone();
+
+        let args = json!({"id": id, "action": "rollback"});
+        let text = call_text(&ctx, "resolve_interrupted_operation", args)
+            .await
+            .unwrap();
+        assert!(text.contains("Rolled back 2 step(s)"), "{}", text);
+        assert!(!logseq.with_graph(|g| g.has_page("Half Imported")));
+        assert!(log.interrupted().unwrap().is_empty());
+    }
+}
@@ -0,0 +1,136 @@
+//! The `resolve_page_name` tool
+
+use super::context::{ToolContext, json_result, logseq_error, text_result};
+use super::registry::McpTool;
+use crate::fuzzy;
+use crate::logseq::api::Page;
+use crate::session::OutputFormat;
+use rmcp::{ErrorData as McpError, model::CallToolResult};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct ResolvePageNameArgs {
+    /// The page name to look for, in any capitalisation.
+    pub name: String,
+}
+
+/// What's known about a page name, as returned in JSON
+#[derive(Debug, Serialize)]
+struct Resolution {
+    exists: bool,
+    /// The page's name as LogSeq shows it, when it exists
+    name: Option<String>,
+    /// Close matches, when it doesn't
+    suggestions: Vec<String>,
+}
+
+pub struct ResolvePageName;
+
+impl McpTool for ResolvePageName {
+    type Args = ResolvePageNameArgs;
+
+    fn name(&self) -> &'static str {
+        "resolve_page_name"
+    }
+
+    fn description(&self) -> &'static str {
+        "Check whether a page exists before using it. Returns the page's name exactly as LogSeq has it (so 'project alpha' resolves to 'Project Alpha'), or, if there's no such page, the closest matching page names."
+    }
+
+    fn read_only(&self) -> bool {
+        true
+    }
+
+    async fn execute(
+        &self,
+        ctx: &ToolContext,
+        args: ResolvePageNameArgs,
+    ) -> Result<CallToolResult, McpError> {
+        let wanted = args.name.trim();
+        let pages = ctx.cache.pages().await.map_err(logseq_error)?;
+        let lowered = wanted.to_lowercase();
+        let found = match pages
+            .iter()
+            .find(|p| p.display_name() == wanted)
+            .or_else(|| pages.iter().find(|p| p.name == lowered))
+        {
+            Some(page) => Some(page.display_name().to_string()),
+            // The page list may be cached from before the page was created
+            None => ctx
+                .client
+                .find_page(wanted)
+                .await
+                .map_err(logseq_error)?
+                .map(|page| page.display_name().to_string()),
+        };
+
+        let resolution = match found {
+            Some(name) => Resolution {
+                exists: true,
+                name: Some(name),
+                suggestions: Vec::new(),
+            },
+            None => Resolution {
+                exists: false,
+                name: None,
+                suggestions: fuzzy::suggest(wanted, pages.iter().map(Page::display_name)),
+            },
+        };
+
+        if ctx.session.output_format == OutputFormat::Json {
+            return Ok(json_result(&resolution, "page name"));
+        }
+        Ok(text_result(format_resolution(wanted, &resolution)))
+    }
+}
+
+fn format_resolution(wanted: &str, resolution: &Resolution) -> String {
+    if let Some(name) = &resolution.name {
+        return if name == wanted {
+            format!("✓ Page '{}' exists", name)
+        } else {
+            format!("✓ Page exists as '{}'", name)
+        };
+    }
+    if resolution.suggestions.is_empty() {
+        return format!("✗ No page named '{}', and no similar page names", wanted);
+    }
+    let mut content = format!("✗ No page named '{}'. Closest matches:", wanted);
+    for suggestion in &resolution.suggestions {
+        content.push_str(&format!("\n- {}", suggestion));
+    }
+    content
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::logseq::mock::MockGraph;
+    use crate::tools::testing::{call_text, serve};
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_resolve_page_name_fixes_case_and_suggests_near_misses() {
+        let mut graph = MockGraph::new();
+        graph.add_page("Project Alpha");
+        graph.add_page("Groceries");
+        let (_logseq, ctx) = serve(graph).await;
+
+        let text = call_text(&ctx, "resolve_page_name", json!({"name": "Project Alpha"}))
+            .await
+            .unwrap();
+        assert_eq!(text, "✓ Page 'Project Alpha' exists");
+        let text = call_text(&ctx, "resolve_page_name", json!({"name": "project alpha"}))
+            .await
+            .unwrap();
+        assert_eq!(text, "✓ Page exists as 'Project Alpha'");
+        let text = call_text(&ctx, "resolve_page_name", json!({"name": "Projct Alpha"}))
+            .await
+            .unwrap();
+        assert_eq!(
+            text,
+            "✗ No page named 'Projct Alpha'. Closest matches:\n- Project Alpha"
+        );
+    }
+}
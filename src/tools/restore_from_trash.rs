@@ -0,0 +1,234 @@
+//! The `restore_from_trash` tool
+
+use super::context::{ToolContext, internal_error, logseq_error, text_result};
+use super::registry::McpTool;
+use crate::logseq::api::{Block, CreatePageOptions, InsertBlockOptions};
+use crate::quota::QuotaKind;
+use crate::tools::{count_blocks, is_properties_block};
+use crate::trash::{TrashEntry, TrashedItem};
+use rmcp::{ErrorData as McpError, model::CallToolResult};
+use schemars::JsonSchema;
+use serde::Deserialize;
+use std::collections::VecDeque;
+
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct RestoreFromTrashArgs {
+    /// The id of the trash entry, as shown by list_trash.
+    pub id: String,
+    /// A page name or block UUID to restore the blocks under, instead of their original location. Required to restore a page whose name has since been reused.
+    pub target: Option<String>,
+}
+
+pub struct RestoreFromTrash;
+
+impl McpTool for RestoreFromTrash {
+    type Args = RestoreFromTrashArgs;
+
+    fn name(&self) -> &'static str {
+        "restore_from_trash"
+    }
+
+    fn description(&self) -> &'static str {
+        "Restore a page or block deleted through this server from its trash. A page is recreated with its blocks; a block is added back, with its children, at the end of its former parent block or page. Restored blocks get new UUIDs. Pass 'target' to restore somewhere else."
+    }
+
+    fn destructive(&self) -> bool {
+        false
+    }
+
+    async fn execute(
+        &self,
+        ctx: &ToolContext,
+        args: RestoreFromTrashArgs,
+    ) -> Result<CallToolResult, McpError> {
+        let RestoreFromTrashArgs { id, target } = args;
+        let trash = ctx.require_trash()?;
+        let entry = trash.get(&id).map_err(internal_error)?.ok_or_else(|| {
+            McpError::invalid_params(
+                format!(
+                    "No trash entry with id {}; use list_trash to see what can be restored",
+                    id
+                ),
+                Some(serde_json::json!({ "id": id })),
+            )
+        })?;
+
+        let (location, blocks) = match target {
+            Some(target) => (target, entry.blocks.as_slice()),
+            None => default_location(ctx, &entry).await?,
+        };
+        ctx.require_writable(&location).await?;
+        let _guard = ctx.page_locks.lock(&location).await;
+
+        insert_tree(ctx, &location, blocks).await.map_err(|(restored, e)| {
+            internal_error(format!(
+                "Restored {} of {} block(s) under {} before failing: {}. The entry is still in the trash.",
+                restored,
+                count_blocks(blocks),
+                location,
+                e
+            ))
+        })?;
+        trash.remove(&entry.id).map_err(internal_error)?;
+
+        Ok(text_result(format!(
+            "Restored {} with {} block(s) under {}",
+            entry.item.describe(&entry.blocks),
+            count_blocks(blocks),
+            location
+        )))
+    }
+}
+
+/// Where an entry goes back to without an explicit target, and which of its
+/// blocks need inserting there. Deleted pages are recreated first.
+async fn default_location<'a>(
+    ctx: &ToolContext,
+    entry: &'a TrashEntry,
+) -> Result<(String, &'a [Block]), McpError> {
+    match &entry.item {
+        TrashedItem::Page { name, properties } => {
+            ctx.require_writable_page(name)?;
+            if ctx
+                .client
+                .find_page(name)
+                .await
+                .map_err(logseq_error)?
+                .is_some()
+            {
+                return Err(McpError::invalid_params(
+                    format!(
+                        "A page named '{}' already exists; pass 'target' to restore its blocks elsewhere",
+                        name
+                    ),
+                    Some(serde_json::json!({ "page": name })),
+                ));
+            }
+
+            let properties = properties.clone().filter(|p| !p.is_empty());
+            ctx.charge_quota(QuotaKind::PagesCreated, 1)?;
+            ctx.client
+                .create_page(name, properties.clone(), CreatePageOptions::default())
+                .await
+                .map_err(logseq_error)?;
+            ctx.cache.invalidate_pages();
+
+            // The page's properties live in its first block; createPage has
+            // already written them
+            let blocks = match entry.blocks.split_first() {
+                Some((first, rest)) if properties.is_some() && is_properties_block(first) => rest,
+                _ => &entry.blocks,
+            };
+            Ok((name.clone(), blocks))
+        }
+        TrashedItem::Block { page, parent, .. } => {
+            if let Some(parent) = parent
+                && ctx.client.get_block(parent).await.is_ok()
+            {
+                return Ok((parent.clone(), &entry.blocks));
+            }
+            match page {
+                Some(page) => {
+                    ctx.require_page(page).await?;
+                    Ok((page.clone(), &entry.blocks))
+                }
+                None => Err(McpError::invalid_params(
+                    "The block's original location is unknown; pass 'target' to choose where to restore it",
+                    Some(serde_json::json!({ "id": entry.id })),
+                )),
+            }
+        }
+    }
+}
+
+/// Append `blocks` and their children under `location` (a page name or
+/// block UUID), keeping their order. On failure, returns how many blocks
+/// were inserted.
+async fn insert_tree(
+    ctx: &ToolContext,
+    location: &str,
+    blocks: &[Block],
+) -> Result<(), (usize, anyhow::Error)> {
+    let mut pending: VecDeque<(String, &Block)> = blocks
+        .iter()
+        .map(|block| (location.to_string(), block))
+        .collect();
+    let mut inserted = 0;
+
+    while let Some((parent, block)) = pending.pop_front() {
+        let opts = InsertBlockOptions {
+            parent: Some(parent),
+            ..Default::default()
+        };
+        let created = ctx
+            .client
+            .insert_block(&block.content, opts)
+            .await
+            .map_err(|e| (inserted, e))?;
+        inserted += 1;
+        pending.extend(
+            block
+                .children
+                .iter()
+                .map(|child| (created.uuid.clone(), child)),
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::logseq::mock::{MockGraph, MockLogSeq};
+    use crate::tools::context::ToolContext;
+    use crate::tools::testing::call_text;
+    use crate::trash::Trash;
+    use serde_json::json;
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn test_deleted_page_can_be_restored_from_trash() {
+        let mut graph = MockGraph::new();
+        graph.add_page("Scratch");
+        let parent = graph.add_block("Scratch", "Keep this");
+        graph.add_block(&parent, "And this child");
+        let logseq = MockLogSeq::serve(graph).await.unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let mut ctx = ToolContext::new(logseq.client());
+        ctx.trash = Some(Arc::new(Trash::new(dir.path())));
+
+        let preview = call_text(&ctx, "delete_page", json!({"page_name": "Scratch"}))
+            .await
+            .unwrap();
+        assert!(logseq.with_graph(|g| g.has_page("Scratch")), "preview only");
+        let token = preview
+            .split("confirm: \"")
+            .nth(1)
+            .unwrap()
+            .split('"')
+            .next()
+            .unwrap();
+
+        let args = json!({"page_name": "Scratch", "confirm": token});
+        let text = call_text(&ctx, "delete_page", args).await.unwrap();
+        assert!(!logseq.with_graph(|g| g.has_page("Scratch")));
+        let id = text
+            .split("trash as ")
+            .nth(1)
+            .unwrap()
+            .split('.')
+            .next()
+            .unwrap();
+
+        call_text(&ctx, "restore_from_trash", json!({"id": id}))
+            .await
+            .unwrap();
+        let roots = logseq.with_graph(|g| g.children("Scratch"));
+        assert_eq!(roots.len(), 1);
+        logseq.with_graph(|g| {
+            assert_eq!(g.block_content(&roots[0]), Some("Keep this"));
+            let child = &g.children(&roots[0])[0];
+            assert_eq!(g.block_content(child), Some("And this child"));
+        });
+    }
+}
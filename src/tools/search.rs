@@ -0,0 +1,280 @@
+//! The `search` tool
+
+use super::context::{ToolContext, json_result, logseq_error, text_result};
+use super::registry::McpTool;
+use crate::logseq::api::SearchOptions;
+use crate::logseq::content::search_terms;
+use crate::ranking::{PageHit, RankedHit, SearchWeights, merge, rank_blocks, rank_pages};
+use crate::session::OutputFormat;
+use crate::tools::{format_search_result, prune_depth};
+use chrono::Utc;
+use rmcp::{ErrorData as McpError, model::CallToolResult};
+use schemars::JsonSchema;
+use serde::Deserialize;
+use serde_json::{Value, json};
+
+/// Default number of results returned by `search`
+const DEFAULT_SEARCH_LIMIT: usize = 50;
+
+/// The most results one search includes the children of
+const MAX_EXPAND: usize = 10;
+
+/// Levels of children included under an expanded result
+const EXPAND_DEPTH: usize = 3;
+
+/// The most pages one search returns
+const MAX_PAGE_HITS: usize = 10;
+
+/// How many times `limit` block hits to fetch when only those in a
+/// workspace are kept
+const WORKSPACE_OVERFETCH: usize = 4;
+
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct SearchArgs {
+    /// Search query string. Supports text search across block content. With several words, blocks must contain all of them, in any order, ignoring common words like 'the' or 'about'; put a phrase in "double quotes" to match it exactly.
+    pub query: String,
+    /// Match the query's case exactly. Defaults to false (case-insensitive).
+    pub case_sensitive: Option<bool>,
+    /// Maximum number of results to return. Defaults to 50.
+    #[schemars(range(min = 1))]
+    pub limit: Option<usize>,
+    /// Only search blocks on this page (page name).
+    pub page: Option<String>,
+    /// Also include the child blocks (up to 3 levels) of the top this many results, at most 10, such as the items of a matching checklist or the text under a matching heading. Defaults to 0.
+    #[schemars(range(min = 0, max = 10))]
+    pub expand: Option<usize>,
+    /// How much page titles, properties, block text, and recent changes count towards the ranking. Any left out keep their defaults.
+    pub weights: Option<SearchWeights>,
+    /// Only search pages in this workspace's namespace, instead of the session's workspace. Ignored with 'page'.
+    pub workspace: Option<String>,
+}
+
+pub struct Search;
+
+impl McpTool for Search {
+    type Args = SearchArgs;
+
+    fn name(&self) -> &'static str {
+        "search"
+    }
+
+    fn description(&self) -> &'static str {
+        "Search for content across all pages and blocks in the LogSeq graph using LogSeq's full-text search index. Matching is case-insensitive and results are ranked by relevance, unless 'case_sensitive' is set. Results are capped by 'limit' (default 50) and can be restricted to a single page with 'page', or to the pages of a workspace with 'workspace'. Each result includes a snippet with the matched terms in bold, the containing page name and parent blocks, and the block UUID. Set 'expand' to also get the child blocks of the top results, e.g. a checklist or notes under a matching heading, in the same call. Pages whose name, alias, or properties match are included too, since often the page of that name is what's wanted. Pages and blocks share one list, ranked by how well their title, properties, and text match and how recently they changed, with adjustable 'weights'."
+    }
+
+    fn examples(&self) -> Vec<Value> {
+        vec![json!({"query": "\"launch plan\" budget", "page": "Projects", "expand": 2})]
+    }
+
+    fn read_only(&self) -> bool {
+        true
+    }
+
+    async fn execute(
+        &self,
+        ctx: &ToolContext,
+        args: SearchArgs,
+    ) -> Result<CallToolResult, McpError> {
+        let SearchArgs {
+            query,
+            case_sensitive,
+            limit,
+            page,
+            expand,
+            weights,
+            workspace,
+        } = args;
+        let weights = weights.unwrap_or_default();
+        let now = Utc::now();
+        let limit = limit.unwrap_or(DEFAULT_SEARCH_LIMIT).max(1);
+
+        if let Some(page) = &page {
+            ctx.require_page(page).await?;
+        }
+        // A search within one page is already scoped
+        let workspace = match &page {
+            Some(_) => None,
+            None => ctx.workspace(workspace.as_deref())?,
+        };
+        let options = SearchOptions {
+            case_sensitive: case_sensitive.unwrap_or(false),
+            limit: Some(match workspace {
+                Some(_) => limit.saturating_mul(WORKSPACE_OVERFETCH),
+                None => limit,
+            }),
+            page,
+        };
+
+        // A search within one page isn't looking for pages
+        let mut pages = match &options.page {
+            Some(_) => Vec::new(),
+            None => {
+                let pages = ctx.cache.pages().await.map_err(logseq_error)?;
+                rank_pages(&query, &pages, &weights, now)
+            }
+        };
+        if options.case_sensitive {
+            let terms = search_terms(&query);
+            pages.retain(|hit| {
+                let title = hit.alias.as_deref().unwrap_or(&hit.name);
+                terms.iter().all(|term| title.contains(term.as_str()))
+            });
+        }
+        if let Some(workspace) = workspace {
+            pages.retain(|hit| workspace.contains(&hit.name));
+        }
+        pages.sort_by(|a, b| b.score.total_cmp(&a.score));
+        pages.truncate(MAX_PAGE_HITS);
+
+        let mut results = ctx
+            .client
+            .search(&query, &options)
+            .await
+            .map_err(logseq_error)?;
+        if let Some(workspace) = workspace {
+            results.retain(|result| workspace.contains(&result.page_name));
+        }
+        let mut ranked = merge(pages, rank_blocks(&query, results, &weights, now));
+        ranked.truncate(limit);
+
+        let expand = expand.unwrap_or(0).min(MAX_EXPAND);
+        let blocks = ranked.iter_mut().filter_map(|hit| match hit {
+            RankedHit::Block { result, .. } => Some(result),
+            RankedHit::Page(_) => None,
+        });
+        for result in blocks.take(expand) {
+            // A block deleted since the search just goes without its children
+            match ctx.client.get_block_tree(&result.block.uuid).await {
+                Ok(mut tree) => {
+                    prune_depth(&mut tree.children, EXPAND_DEPTH);
+                    result.block.children = tree.children;
+                }
+                Err(e) => tracing::warn!(
+                    "Could not fetch the children of search result {}: {}",
+                    result.block.uuid,
+                    e
+                ),
+            }
+        }
+
+        Ok(match ctx.session.output_format {
+            OutputFormat::Markdown => text_result(format_ranked(&ranked)),
+            OutputFormat::Json => json_result(&ranked, "search results"),
+        })
+    }
+}
+
+fn format_ranked(hits: &[RankedHit]) -> String {
+    if hits.is_empty() {
+        return "No results found.".to_string();
+    }
+
+    let mut content = format!("Found {} results:\n\n", hits.len());
+    for (i, hit) in hits.iter().enumerate() {
+        match hit {
+            RankedHit::Page(page) => content.push_str(&format_page_hit(i + 1, page)),
+            RankedHit::Block { result, .. } => {
+                content.push_str(&format_search_result(i + 1, result))
+            }
+        }
+    }
+    content
+}
+
+fn format_page_hit(number: usize, page: &PageHit) -> String {
+    let mut content = format!("{}. Page [[{}]]\n", number, page.name);
+    if let Some(alias) = &page.alias {
+        content.push_str(&format!("   Alias: {}\n", alias));
+    }
+    content.push_str(&format!("   UUID: {}\n", page.uuid));
+    content.push_str(&format!("   Score: {:.2}\n\n", page.score));
+    content
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::logseq::mock::MockGraph;
+    use crate::tools::testing::{call_text, serve};
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_search_expands_the_top_results() {
+        let mut graph = MockGraph::new();
+        graph.add_page("Packing");
+        let list = graph.add_block("Packing", "Checklist for the trip");
+        graph.add_block(&list, "Passport");
+        let other = graph.add_block("Packing", "Another checklist");
+        graph.add_block(&other, "Sunscreen");
+        let (_logseq, ctx) = serve(graph).await;
+
+        let args = json!({"query": "checklist", "expand": 1});
+        let text = call_text(&ctx, "search", args).await.unwrap();
+        assert!(text.contains("* Passport"), "{}", text);
+        assert!(!text.contains("Sunscreen"), "{}", text);
+
+        let text = call_text(&ctx, "search", json!({"query": "checklist"}))
+            .await
+            .unwrap();
+        assert!(!text.contains("Passport"), "{}", text);
+    }
+
+    #[tokio::test]
+    async fn test_page_search_matches_every_term_of_a_question() {
+        let mut graph = MockGraph::new();
+        graph.add_page("Rust");
+        graph.add_block("Rust", "Lifetimes in Rust are tricky");
+        graph.add_block("Rust", "Rust async runtimes");
+        graph.add_block("Rust", "Lifetimes of houseplants");
+        let (_logseq, ctx) = serve(graph).await;
+
+        let args = json!({"query": "what are the lifetimes in rust?", "page": "Rust"});
+        let text = call_text(&ctx, "search", args).await.unwrap();
+        assert!(text.contains("**Lifetimes** in **Rust**"), "{}", text);
+        assert!(!text.contains("async"), "{}", text);
+        assert!(!text.contains("houseplants"), "{}", text);
+    }
+
+    #[tokio::test]
+    async fn test_search_ranks_pages_and_blocks_together() {
+        let mut graph = MockGraph::new();
+        graph.add_page("Project Alpha");
+        graph.set_page_property("Project Alpha", "alias", json!(["apollo"]));
+        graph.add_page("Alpha Centauri");
+        graph.add_page("Journal");
+        graph.add_block("Journal", "Kicked off Project Alpha today");
+        let (_logseq, ctx) = serve(graph).await;
+
+        let text = call_text(&ctx, "search", json!({"query": "project alpha"}))
+            .await
+            .unwrap();
+        assert!(
+            text.starts_with("Found 2 results:\n\n1. Page [[Project Alpha]]\n"),
+            "{}",
+            text
+        );
+        assert!(
+            text.contains("2. Kicked off **Project** **Alpha** today"),
+            "{}",
+            text
+        );
+
+        let text = call_text(&ctx, "search", json!({"query": "Apollo"}))
+            .await
+            .unwrap();
+        assert!(
+            text.starts_with("Found 1 results:\n\n1. Page [[Project Alpha]]\n   Alias: apollo\n"),
+            "{}",
+            text
+        );
+
+        // With text weighted over titles, the block comes first
+        let args = json!({"query": "project alpha", "weights": {"title": 0, "content": 10}});
+        let text = call_text(&ctx, "search", args).await.unwrap();
+        assert!(text.contains("1. Kicked off"), "{}", text);
+
+        let args = json!({"query": "alpha", "page": "Journal"});
+        let text = call_text(&ctx, "search", args).await.unwrap();
+        assert!(!text.contains("Page [["), "{}", text);
+    }
+}
@@ -0,0 +1,115 @@
+//! The `set_todo_state` tool
+
+use super::context::{ToolContext, logseq_error, text_result};
+use super::registry::McpTool;
+use rmcp::{ErrorData as McpError, model::CallToolResult};
+use schemars::JsonSchema;
+use serde::Deserialize;
+
+/// Task markers that `set_todo_state` can apply
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, JsonSchema)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum TodoState {
+    Todo,
+    Doing,
+    Done,
+    Later,
+    Now,
+    Waiting,
+    Canceled,
+}
+
+impl TodoState {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            TodoState::Todo => "TODO",
+            TodoState::Doing => "DOING",
+            TodoState::Done => "DONE",
+            TodoState::Later => "LATER",
+            TodoState::Now => "NOW",
+            TodoState::Waiting => "WAITING",
+            TodoState::Canceled => "CANCELED",
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct SetTodoStateArgs {
+    /// The UUID of the task block to update.
+    pub uuid: String,
+    /// The new task marker for the block.
+    pub marker: TodoState,
+}
+
+pub struct SetTodoState;
+
+impl McpTool for SetTodoState {
+    type Args = SetTodoStateArgs;
+
+    fn name(&self) -> &'static str {
+        "set_todo_state"
+    }
+
+    fn description(&self) -> &'static str {
+        "Change the task marker of a block (e.g. TODO → DOING → DONE) by UUID. The marker at the start of the block content is rewritten in place; priority tags like [#A], properties, and children are preserved. Use this to complete or reschedule tasks found with find_incomplete_todos."
+    }
+
+    fn idempotent(&self) -> bool {
+        true
+    }
+
+    async fn execute(
+        &self,
+        ctx: &ToolContext,
+        args: SetTodoStateArgs,
+    ) -> Result<CallToolResult, McpError> {
+        let SetTodoStateArgs { uuid, marker } = args;
+        let marker = marker.as_str();
+        ctx.require_writable(&uuid).await?;
+
+        let (previous, block) = ctx
+            .client
+            .set_block_marker(&uuid, marker)
+            .await
+            .map_err(logseq_error)?;
+
+        Ok(text_result(format!(
+            "Updated block {}: {} → {}",
+            block.uuid,
+            previous.as_deref().unwrap_or("(no marker)"),
+            marker
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::logseq::mock::MockGraph;
+    use crate::tools::testing::{call_text, serve};
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_marker_is_rewritten_in_place() {
+        let mut graph = MockGraph::new();
+        graph.add_page("Projects");
+        let task = graph.add_block("Projects", "TODO [#A] Ship it\nowner:: Sam");
+        let note = graph.add_block("Projects", "Just a note");
+        let (logseq, ctx) = serve(graph).await;
+
+        let args = json!({"uuid": task, "marker": "DONE"});
+        let text = call_text(&ctx, "set_todo_state", args).await.unwrap();
+        assert!(text.ends_with("TODO → DONE"), "{}", text);
+
+        let args = json!({"uuid": note, "marker": "LATER"});
+        let text = call_text(&ctx, "set_todo_state", args).await.unwrap();
+        assert!(text.ends_with("(no marker) → LATER"), "{}", text);
+        logseq.with_graph(|g| {
+            assert_eq!(
+                g.block_content(&task),
+                Some("DONE [#A] Ship it\nowner:: Sam")
+            );
+            assert_eq!(g.block_content(&note), Some("LATER Just a note"));
+        });
+    }
+}
@@ -0,0 +1,119 @@
+//! The `show_ui_message` tool
+
+use super::context::{ToolContext, logseq_error, text_result};
+use super::registry::McpTool;
+use rmcp::{ErrorData as McpError, model::CallToolResult};
+use schemars::JsonSchema;
+use serde::Deserialize;
+use std::time::Duration;
+
+/// The longest a message may stay up
+const MAX_TIMEOUT_MS: u64 = 60_000;
+
+/// How a message is styled in LogSeq
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum MessageLevel {
+    #[default]
+    Success,
+    Warning,
+    Error,
+}
+
+impl MessageLevel {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Success => "success",
+            Self::Warning => "warning",
+            Self::Error => "error",
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct ShowUiMessageArgs {
+    /// The text to show. Keep it to a sentence or two.
+    pub message: String,
+    /// How the message is styled: 'success' (the default), 'warning', or 'error'.
+    #[serde(default)]
+    pub level: MessageLevel,
+    /// Milliseconds before the message disappears, at most 60000. Defaults to LogSeq's own timeout.
+    #[schemars(range(min = 1, max = 60000))]
+    pub timeout_ms: Option<u64>,
+}
+
+pub struct ShowUiMessage;
+
+impl McpTool for ShowUiMessage {
+    type Args = ShowUiMessageArgs;
+
+    fn name(&self) -> &'static str {
+        "show_ui_message"
+    }
+
+    fn description(&self) -> &'static str {
+        "Show a short notification in the LogSeq window the user is looking at, e.g. 'Finished reorganizing your project page'. Use it to flag the end of a long task or something that needs the user's attention, not for every step. Nothing in the graph changes."
+    }
+
+    fn read_only(&self) -> bool {
+        true
+    }
+
+    async fn execute(
+        &self,
+        ctx: &ToolContext,
+        args: ShowUiMessageArgs,
+    ) -> Result<CallToolResult, McpError> {
+        let ShowUiMessageArgs {
+            message,
+            level,
+            timeout_ms,
+        } = args;
+        if message.trim().is_empty() {
+            return Err(McpError::invalid_params("The message is empty", None));
+        }
+        let timeout = timeout_ms.map(|ms| Duration::from_millis(ms.clamp(1, MAX_TIMEOUT_MS)));
+
+        ctx.client
+            .show_msg(message.trim(), level.as_str(), timeout)
+            .await
+            .map_err(logseq_error)?;
+
+        Ok(text_result(format!(
+            "Showed a {} message in LogSeq",
+            level.as_str()
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::logseq::mock::MockGraph;
+    use crate::tools::testing::{call_text, serve};
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_ui_message_is_sent_to_logseq() {
+        let (logseq, ctx) = serve(MockGraph::new()).await;
+
+        let args = json!({"message": "Done tidying", "level": "warning", "timeout_ms": 3000});
+        call_text(&ctx, "show_ui_message", args).await.unwrap();
+        let call = logseq
+            .calls()
+            .into_iter()
+            .find(|call| call.method == "logseq.UI.showMsg")
+            .unwrap();
+        assert_eq!(
+            call.args,
+            vec![
+                json!("Done tidying"),
+                json!("warning"),
+                json!({"timeout": 3000})
+            ]
+        );
+
+        let args = json!({"message": "  "});
+        assert!(call_text(&ctx, "show_ui_message", args).await.is_err());
+    }
+}
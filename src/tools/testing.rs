@@ -0,0 +1,91 @@
+//! Helpers for tool tests that run against the mock LogSeq
+
+use super::context::ToolContext;
+use super::registry::ToolRegistry;
+use crate::logseq::mock::{MockGraph, MockLogSeq};
+use rmcp::{ErrorData as McpError, model::RawContent};
+use serde_json::Value;
+
+/// Serve `graph` from a mock LogSeq, with a context for calling tools on it
+pub async fn serve(graph: MockGraph) -> (MockLogSeq, ToolContext) {
+    let logseq = MockLogSeq::serve(graph).await.unwrap();
+    let ctx = ToolContext::new(logseq.client());
+    (logseq, ctx)
+}
+
+/// Call a builtin tool and return the text of its result
+pub async fn call_text(ctx: &ToolContext, tool: &str, args: Value) -> Result<String, McpError> {
+    let result = ToolRegistry::builtin()
+        .call(ctx, tool, args.as_object().cloned())
+        .await?;
+    Ok(result
+        .content
+        .iter()
+        .flatten()
+        .filter_map(|c| match &c.raw {
+            RawContent::Text(text) => Some(text.text.as_str()),
+            _ => None,
+        })
+        .collect())
+}
+
+/// The confirmation token a destructive tool's preview asks to be sent back
+pub fn confirm_token(preview: &str) -> String {
+    let (_, rest) = preview.split_once("confirm: \"").unwrap();
+    rest.split('"').next().unwrap().to_string()
+}
+
+/// Milliseconds since the epoch at noon local time on `date`, given as
+/// `YYYY-MM-DD`
+pub fn noon(date: &str) -> i64 {
+    chrono::NaiveDate::parse_from_str(date, "%Y-%m-%d")
+        .unwrap()
+        .and_hms_opt(12, 0, 0)
+        .unwrap()
+        .and_local_timezone(chrono::Local)
+        .unwrap()
+        .timestamp_millis()
+}
+
+mod tests {
+    use super::{call_text, serve};
+    use crate::logseq::mock::MockGraph;
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_tools_read_and_write_the_mock_graph() {
+        let mut graph = MockGraph::new();
+        graph.add_page("Projects");
+        let plan = graph.add_block("Projects", "TODO Plan the launch");
+        graph.add_block(&plan, "Budget notes");
+        let (_logseq, ctx) = serve(graph).await;
+
+        let text = call_text(
+            &ctx,
+            "create_block",
+            json!({"content": "DOING Write the announcement", "parent": "Projects"}),
+        )
+        .await
+        .unwrap();
+        assert!(text.starts_with("Created block"), "{}", text);
+
+        let text = call_text(&ctx, "get_page_content", json!({"page_name": "projects"}))
+            .await
+            .unwrap();
+        assert!(text.contains("Plan the launch"), "{}", text);
+        assert!(text.contains("Budget notes"), "{}", text);
+        assert!(text.contains("Write the announcement"), "{}", text);
+
+        let text = call_text(&ctx, "search", json!({"query": "budget"}))
+            .await
+            .unwrap();
+        assert!(text.contains("**Budget** notes"), "{}", text);
+        assert!(text.contains("Page: Projects"), "{}", text);
+
+        let text = call_text(&ctx, "find_incomplete_todos", json!({}))
+            .await
+            .unwrap();
+        assert!(text.contains("Plan the launch"), "{}", text);
+        assert!(text.contains("Write the announcement"), "{}", text);
+    }
+}
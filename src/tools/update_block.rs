@@ -0,0 +1,244 @@
+//! The `update_block` tool
+
+use super::context::{ToolContext, logseq_error, require_property_key, text_result};
+use super::registry::McpTool;
+use crate::logseq::content::{append_text, prepend_text};
+use rmcp::{ErrorData as McpError, model::CallToolResult};
+use schemars::JsonSchema;
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+
+/// How `content` changes the block
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum UpdateMode {
+    /// Replace the block's content
+    #[default]
+    Replace,
+    /// Add a line to the end of the block
+    Append,
+    /// Add text to the start of the block's first line, after any task marker
+    Prepend,
+    /// Set properties only, leaving the content alone
+    PropertiesOnly,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct UpdateBlockArgs {
+    /// The UUID of the block to update. Must be an existing block UUID.
+    pub uuid: String,
+    /// The content in markdown format: the block's new content, or the text to append or prepend. Required unless mode is 'properties_only'.
+    pub content: Option<String>,
+    /// 'replace' (the default) replaces the content; 'append' adds content as a new line at the end; 'prepend' adds one line of content to the start, after any TODO/DONE marker; 'properties_only' sets the given properties and leaves the content alone.
+    pub mode: Option<UpdateMode>,
+    /// Optional block properties to update. These are key-value pairs that define metadata for the block (e.g., {'priority': 'high', 'status': 'todo'}). Required in 'properties_only' mode.
+    pub properties: Option<HashMap<String, Value>>,
+}
+
+pub struct UpdateBlock;
+
+impl McpTool for UpdateBlock {
+    type Args = UpdateBlockArgs;
+
+    fn name(&self) -> &'static str {
+        "update_block"
+    }
+
+    fn description(&self) -> &'static str {
+        "Update an existing block by UUID: replace its content, append or prepend to it, or (with mode 'properties_only') set its properties without touching its content. Appending and prepending keep the block's task marker and properties in place. The block's children are never changed."
+    }
+
+    fn idempotent(&self) -> bool {
+        false
+    }
+
+    async fn execute(
+        &self,
+        ctx: &ToolContext,
+        args: UpdateBlockArgs,
+    ) -> Result<CallToolResult, McpError> {
+        let UpdateBlockArgs {
+            uuid,
+            content,
+            mode,
+            properties,
+        } = args;
+        let mode = mode.unwrap_or_default();
+
+        if mode == UpdateMode::PropertiesOnly {
+            if content.is_some() {
+                return Err(McpError::invalid_params(
+                    "'content' can't be given in 'properties_only' mode; use mode 'replace' to change the content too",
+                    None,
+                ));
+            }
+            return set_properties(ctx, &uuid, properties.unwrap_or_default()).await;
+        }
+        let Some(content) = content else {
+            return Err(McpError::invalid_params(
+                "'content' is required unless mode is 'properties_only'",
+                None,
+            ));
+        };
+        if mode == UpdateMode::Prepend && content.contains('\n') {
+            return Err(McpError::invalid_params(
+                "Only one line can be prepended, as the first line holds the block's task marker and comes before its properties; use mode 'replace' to rewrite the block",
+                None,
+            ));
+        }
+
+        ctx.require_writable(&uuid).await?;
+        let content = match mode {
+            UpdateMode::Append | UpdateMode::Prepend => {
+                let block = ctx.client.get_block(&uuid).await.map_err(logseq_error)?;
+                if mode == UpdateMode::Append {
+                    append_text(&block.content, &content)
+                } else {
+                    prepend_text(&block.content, content.trim())
+                }
+            }
+            _ => content,
+        };
+        ctx.charge_write(None, &content)?;
+        let block = ctx
+            .client
+            .update_block(&uuid, &content, properties)
+            .await
+            .map_err(logseq_error)?;
+
+        Ok(text_result(match mode {
+            UpdateMode::Append => format!("Appended to block with UUID: {}", block.uuid),
+            UpdateMode::Prepend => format!("Prepended to block with UUID: {}", block.uuid),
+            _ => format!("Updated block with UUID: {}", block.uuid),
+        }))
+    }
+}
+
+/// Set a block's properties one by one, leaving its content alone
+async fn set_properties(
+    ctx: &ToolContext,
+    uuid: &str,
+    properties: HashMap<String, Value>,
+) -> Result<CallToolResult, McpError> {
+    if properties.is_empty() {
+        return Err(McpError::invalid_params(
+            "'properties' must set at least one property in 'properties_only' mode",
+            None,
+        ));
+    }
+    let properties: BTreeMap<String, Value> = properties.into_iter().collect();
+    for (key, value) in &properties {
+        require_property_key(key)?;
+        if value.is_null() || value.is_object() {
+            return Err(McpError::invalid_params(
+                format!(
+                    "Property '{}' must be a string, number, boolean, or array; use remove_block_property to clear a property",
+                    key
+                ),
+                None,
+            ));
+        }
+    }
+
+    ctx.require_writable(uuid).await?;
+    ctx.charge_write(None, &Value::from_iter(properties.clone()).to_string())?;
+
+    // LogSeq silently ignores writes to missing blocks, so check first
+    let block = ctx.client.get_block(uuid).await.map_err(logseq_error)?;
+    for (key, value) in &properties {
+        ctx.client
+            .upsert_block_property(&block.uuid, key, value)
+            .await
+            .map_err(logseq_error)?;
+    }
+
+    Ok(text_result(format!(
+        "Set {} on block {}",
+        properties
+            .iter()
+            .map(|(key, value)| format!("'{}' to {}", key, value))
+            .collect::<Vec<_>>()
+            .join(", "),
+        block.uuid
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::logseq::mock::{MockGraph, MockLogSeq};
+    use crate::tools::testing::{call_text, serve};
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_update_block_modes_keep_children() {
+        let mut graph = MockGraph::new();
+        graph.add_page("Work");
+        let uuid = graph.add_block("Work", "TODO Ship release\nowner:: me");
+        let child = graph.add_block(&uuid, "Write changelog");
+        let (logseq, ctx) = serve(graph).await;
+        let content = |logseq: &MockLogSeq| {
+            logseq.with_graph(|g| g.block_content(&uuid).unwrap().to_string())
+        };
+
+        let args = json!({"uuid": uuid, "content": "Blocked on review", "mode": "append"});
+        let text = call_text(&ctx, "update_block", args).await.unwrap();
+        assert_eq!(text, format!("Appended to block with UUID: {}", uuid));
+        assert_eq!(
+            content(&logseq),
+            "TODO Ship release\nowner:: me\nBlocked on review"
+        );
+
+        let args = json!({"uuid": uuid, "content": "[[Alpha]]", "mode": "prepend"});
+        call_text(&ctx, "update_block", args).await.unwrap();
+        assert_eq!(
+            content(&logseq),
+            "TODO [[Alpha]] Ship release\nowner:: me\nBlocked on review"
+        );
+
+        let args = json!({"uuid": uuid, "mode": "properties_only", "properties": {"owner": "sam", "status": "blocked"}});
+        let text = call_text(&ctx, "update_block", args).await.unwrap();
+        assert_eq!(
+            text,
+            format!(
+                "Set 'owner' to \"sam\", 'status' to \"blocked\" on block {}",
+                uuid
+            )
+        );
+        let updated = content(&logseq);
+        assert!(
+            updated.starts_with("TODO [[Alpha]] Ship release\n"),
+            "{}",
+            updated
+        );
+        assert!(updated.contains("owner:: sam"), "{}", updated);
+        assert!(updated.contains("status:: blocked"), "{}", updated);
+        assert!(updated.contains("Blocked on review"), "{}", updated);
+
+        let args = json!({"uuid": uuid, "content": "DONE Ship release"});
+        call_text(&ctx, "update_block", args).await.unwrap();
+        assert_eq!(content(&logseq), "DONE Ship release");
+
+        // No mode touches the block's children
+        assert_eq!(logseq.with_graph(|g| g.children(&uuid)), [child.as_str()]);
+        assert_eq!(
+            logseq.with_graph(|g| g.block_content(&child).map(String::from)),
+            Some("Write changelog".to_string())
+        );
+
+        let args = json!({"uuid": uuid, "mode": "append"});
+        let err = call_text(&ctx, "update_block", args).await.unwrap_err();
+        assert!(
+            err.message.contains("'content' is required"),
+            "{}",
+            err.message
+        );
+        let args = json!({"uuid": uuid, "content": "a\nb", "mode": "prepend"});
+        assert!(call_text(&ctx, "update_block", args).await.is_err());
+        let args = json!({"uuid": uuid, "content": "x", "mode": "properties_only", "properties": {"a": 1}});
+        assert!(call_text(&ctx, "update_block", args).await.is_err());
+        let args = json!({"uuid": uuid, "mode": "properties_only"});
+        assert!(call_text(&ctx, "update_block", args).await.is_err());
+    }
+}
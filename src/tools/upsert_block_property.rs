@@ -0,0 +1,109 @@
+//! The `upsert_block_property` tool
+
+use super::context::{ToolContext, logseq_error, require_property_key, text_result};
+use super::registry::McpTool;
+use rmcp::{ErrorData as McpError, model::CallToolResult};
+use schemars::JsonSchema;
+use serde::Deserialize;
+use serde_json::Value;
+
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct UpsertBlockPropertyArgs {
+    /// The UUID of the block to set the property on.
+    pub uuid: String,
+    /// The property key, as written before '::' in the block (e.g. 'status' for 'status:: done').
+    pub key: String,
+    /// The property value: a string, number, boolean, or an array of strings for multi-value properties like tags.
+    pub value: Value,
+}
+
+pub struct UpsertBlockProperty;
+
+impl McpTool for UpsertBlockProperty {
+    type Args = UpsertBlockPropertyArgs;
+
+    fn name(&self) -> &'static str {
+        "upsert_block_property"
+    }
+
+    fn description(&self) -> &'static str {
+        "Set a property on a block by UUID, adding it if it doesn't exist or replacing its value if it does. Only the property is changed; the block's content, other properties, and children are left as they are. Prefer this over update_block for editing properties."
+    }
+
+    fn idempotent(&self) -> bool {
+        true
+    }
+
+    async fn execute(
+        &self,
+        ctx: &ToolContext,
+        args: UpsertBlockPropertyArgs,
+    ) -> Result<CallToolResult, McpError> {
+        let UpsertBlockPropertyArgs { uuid, key, value } = args;
+        require_property_key(&key)?;
+        if value.is_null() || value.is_object() {
+            return Err(McpError::invalid_params(
+                "Property value must be a string, number, boolean, or array; use remove_block_property to clear a property",
+                None,
+            ));
+        }
+
+        ctx.require_writable(&uuid).await?;
+        ctx.charge_write(None, &value.to_string())?;
+
+        // LogSeq silently ignores writes to missing blocks, so check first
+        ctx.client.get_block(&uuid).await.map_err(logseq_error)?;
+        ctx.client
+            .upsert_block_property(&uuid, &key, &value)
+            .await
+            .map_err(logseq_error)?;
+
+        Ok(text_result(format!(
+            "Set property '{}' on block {} to {}",
+            key, uuid, value
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::logseq::mock::MockGraph;
+    use crate::tools::testing::{call_text, serve};
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_property_is_added_then_replaced() {
+        let mut graph = MockGraph::new();
+        graph.add_page("Projects");
+        let task = graph.add_block("Projects", "Ship it");
+        let (logseq, ctx) = serve(graph).await;
+
+        let args = json!({"uuid": task, "key": "status", "value": "doing"});
+        call_text(&ctx, "upsert_block_property", args)
+            .await
+            .unwrap();
+        let args = json!({"uuid": task, "key": "status", "value": "done"});
+        let text = call_text(&ctx, "upsert_block_property", args)
+            .await
+            .unwrap();
+        assert!(text.ends_with("to \"done\""), "{}", text);
+        logseq.with_graph(|g| {
+            assert_eq!(g.block_content(&task), Some("Ship it\nstatus:: done"));
+        });
+
+        let args = json!({"uuid": task, "key": "status", "value": null});
+        assert!(
+            call_text(&ctx, "upsert_block_property", args)
+                .await
+                .is_err()
+        );
+        let missing = "00000000-0000-0000-0000-000000000000";
+        let args = json!({"uuid": missing, "key": "status", "value": "done"});
+        assert!(
+            call_text(&ctx, "upsert_block_property", args)
+                .await
+                .is_err()
+        );
+    }
+}
@@ -0,0 +1,342 @@
+//! The `write_task_dashboard` tool
+
+use super::context::{ToolContext, internal_error, logseq_error, text_result};
+use super::registry::McpTool;
+use crate::logseq::api::{CreatePageOptions, TodoFilter, TodoItem};
+use crate::logseq::content::INCOMPLETE_MARKERS;
+use crate::logseq::markdown::BatchBlock;
+use crate::logseq::page_name::normalize_page_name;
+use crate::logseq::region::{RegionError, write_region};
+use crate::quota::QuotaKind;
+use crate::tools::{TodoGroupBy, group_todos, journal_day_to_date};
+use chrono::{Days, Local, NaiveDate};
+use rmcp::{ErrorData as McpError, model::CallToolResult};
+use schemars::JsonSchema;
+use serde::Deserialize;
+use serde_json::json;
+
+/// The managed region holding the dashboard; the rest of the page is left
+/// alone
+pub const DASHBOARD_REGION: &str = "task-dashboard";
+
+/// How far ahead the agenda section looks
+const AGENDA_DAYS: u64 = 7;
+
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct WriteTaskDashboardArgs {
+    /// The page to write the dashboard to. It is created if it doesn't exist.
+    pub page: String,
+    /// How to group todos: by 'marker' (default), 'page', 'tag', 'priority', or 'due-week', as in find_incomplete_todos.
+    pub group_by: Option<TodoGroupBy>,
+    /// Add an agenda of todos scheduled in the next 7 days, and overdue ones. Defaults to false.
+    pub include_agenda: Option<bool>,
+    /// Add counts of open todos by status and priority, and of scheduled and overdue ones. Defaults to false.
+    pub include_statistics: Option<bool>,
+    /// Replace the dashboard even where it was edited on the page since the last run, instead of failing on a conflict. Defaults to false.
+    pub overwrite: Option<bool>,
+}
+
+pub struct WriteTaskDashboard;
+
+impl McpTool for WriteTaskDashboard {
+    type Args = WriteTaskDashboardArgs;
+
+    fn name(&self) -> &'static str {
+        "write_task_dashboard"
+    }
+
+    fn description(&self) -> &'static str {
+        "Write the incomplete todo report to a LogSeq page as a living dashboard: a 'Task Dashboard' block whose children reference each todo, grouped as in find_incomplete_todos, optionally with an agenda and statistics. The dashboard sits between '<!-- mcp:begin task-dashboard -->' and '<!-- mcp:end task-dashboard -->' marker blocks; running the tool again updates what's between them and leaves the rest of the page alone. Edits made to the dashboard since the last run are kept; if the update would change the same blocks, it fails with a 'conflict' error listing them, unless 'overwrite' is true."
+    }
+
+    fn idempotent(&self) -> bool {
+        true
+    }
+
+    async fn execute(
+        &self,
+        ctx: &ToolContext,
+        args: WriteTaskDashboardArgs,
+    ) -> Result<CallToolResult, McpError> {
+        let WriteTaskDashboardArgs {
+            page,
+            group_by,
+            include_agenda,
+            include_statistics,
+            overwrite,
+        } = args;
+        let group_by = group_by.unwrap_or_default();
+        let title = normalize_page_name(&page)
+            .map_err(|e| McpError::invalid_params(e.to_string(), None))?;
+        let title = ctx.page_to_create(&title);
+
+        let todos = ctx
+            .client
+            .find_incomplete_todos(&TodoFilter::default())
+            .await
+            .map_err(logseq_error)?;
+        let now = Local::now();
+        let today = now.date_naive();
+        let mut sections = Vec::new();
+        if include_statistics.unwrap_or(false) {
+            sections.push(statistics(&todos, today));
+        }
+        if include_agenda.unwrap_or(false) {
+            sections.push(agenda(&todos, today));
+        }
+        let groups = group_todos(&todos, group_by);
+        // Bold, so a heading such as NOW doesn't make the block a task itself
+        sections.extend(groups.iter().map(|(heading, todos)| BatchBlock {
+            content: format!("**{}** ({})", heading, todos.len()),
+            children: todos.iter().map(|todo| todo_ref(todo)).collect(),
+        }));
+        if todos.is_empty() {
+            sections.push(BatchBlock {
+                content: "No incomplete todos.".to_string(),
+                children: Vec::new(),
+            });
+        }
+        let dashboard = BatchBlock {
+            content: format!("Task Dashboard\nupdated:: {}", now.format("%Y-%m-%d %H:%M")),
+            children: sections,
+        };
+
+        let _guard = ctx.page_locks.lock(&title).await;
+        if ctx
+            .client
+            .find_page(&title)
+            .await
+            .map_err(logseq_error)?
+            .is_none()
+        {
+            ctx.charge_quota(QuotaKind::PagesCreated, 1)?;
+            ctx.client
+                .create_page(&title, None, CreatePageOptions::default())
+                .await
+                .map_err(logseq_error)?;
+            ctx.cache.invalidate_pages();
+        }
+        let base = match (&ctx.region_bases, overwrite.unwrap_or(false)) {
+            (Some(bases), false) => bases
+                .get(&title, DASHBOARD_REGION)
+                .map_err(internal_error)?,
+            _ => None,
+        };
+        let written = write_region(
+            &ctx.client,
+            &title,
+            DASHBOARD_REGION,
+            &[dashboard],
+            base.as_deref(),
+        )
+        .await
+        // Broken markers and conflicting edits are for the user to fix, not a
+        // server failure
+        .map_err(|e| match e.downcast_ref::<RegionError>() {
+            Some(RegionError::Conflict { blocks, .. }) => McpError::invalid_params(
+                format!(
+                    "{}; undo those edits, or pass overwrite: true to replace them",
+                    e
+                ),
+                Some(json!({ "error": "conflict", "page": title, "blocks": blocks })),
+            ),
+            Some(e) => McpError::invalid_params(e.to_string(), Some(json!({ "page": title }))),
+            None => internal_error(e),
+        })?;
+        if let Some(bases) = &ctx.region_bases
+            && let Err(e) = bases.put(&title, DASHBOARD_REGION, written.base)
+        {
+            tracing::warn!("Could not record the dashboard on '{}': {}", title, e);
+        }
+
+        Ok(text_result(format!(
+            "{} the task dashboard on page '{}' with {} todo(s) in {} group(s){}.",
+            if written.created { "Wrote" } else { "Updated" },
+            title,
+            todos.len(),
+            groups.len(),
+            if written.merged {
+                ", keeping edits made on the page"
+            } else {
+                ""
+            }
+        )))
+    }
+}
+
+/// A reference to a todo rather than a copy of it: the dashboard shows its
+/// live content, and doesn't add todos of its own for the next report to find
+fn todo_ref(todo: &TodoItem) -> BatchBlock {
+    BatchBlock {
+        content: format!("(({}))", todo.uuid),
+        children: Vec::new(),
+    }
+}
+
+fn statistics(todos: &[TodoItem], today: NaiveDate) -> BatchBlock {
+    let mut by_status: Vec<(&str, usize)> = INCOMPLETE_MARKERS
+        .iter()
+        .map(|marker| {
+            (
+                *marker,
+                todos.iter().filter(|t| t.marker == *marker).count(),
+            )
+        })
+        .collect();
+    by_status.retain(|(_, count)| *count > 0);
+
+    let mut by_priority: Vec<(String, usize)> = Vec::new();
+    for todo in todos {
+        let priority = todo.priority.clone().unwrap_or_else(|| "none".to_string());
+        match by_priority.iter_mut().find(|(p, _)| *p == priority) {
+            Some((_, count)) => *count += 1,
+            None => by_priority.push((priority, 1)),
+        }
+    }
+
+    let scheduled: Vec<NaiveDate> = todos
+        .iter()
+        .filter_map(|t| t.scheduled.and_then(journal_day_to_date))
+        .collect();
+    let overdue = scheduled.iter().filter(|date| **date < today).count();
+
+    let lines = [
+        format!("Open todos: {}", todos.len()),
+        format!("By status: {}", join_counts(by_status)),
+        format!("By priority: {}", join_counts(by_priority)),
+        format!("Scheduled: {}, overdue: {}", scheduled.len(), overdue),
+    ];
+    BatchBlock {
+        content: "Statistics".to_string(),
+        children: lines
+            .into_iter()
+            .map(|content| BatchBlock {
+                content,
+                children: Vec::new(),
+            })
+            .collect(),
+    }
+}
+
+fn join_counts<S: AsRef<str>>(counts: Vec<(S, usize)>) -> String {
+    if counts.is_empty() {
+        return "none".to_string();
+    }
+    counts
+        .iter()
+        .map(|(label, count)| format!("{} {}", label.as_ref(), count))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Todos scheduled up to `AGENDA_DAYS` ahead, overdue ones included, in date
+/// order
+fn agenda(todos: &[TodoItem], today: NaiveDate) -> BatchBlock {
+    let horizon = today + Days::new(AGENDA_DAYS);
+    let mut upcoming: Vec<(NaiveDate, &TodoItem)> = todos
+        .iter()
+        .filter_map(|todo| {
+            let date = todo.scheduled.and_then(journal_day_to_date)?;
+            (date <= horizon).then_some((date, todo))
+        })
+        .collect();
+    upcoming.sort_by_key(|(date, _)| *date);
+
+    let children = if upcoming.is_empty() {
+        vec![BatchBlock {
+            content: format!("Nothing scheduled in the next {} days", AGENDA_DAYS),
+            children: Vec::new(),
+        }]
+    } else {
+        upcoming
+            .into_iter()
+            .map(|(date, todo)| {
+                let label = if date < today {
+                    format!("Overdue since {}", date.format("%Y-%m-%d"))
+                } else {
+                    date.format("%Y-%m-%d").to_string()
+                };
+                BatchBlock {
+                    content: format!("{}: (({}))", label, todo.uuid),
+                    children: Vec::new(),
+                }
+            })
+            .collect()
+    };
+    BatchBlock {
+        content: format!("Agenda: next {} days", AGENDA_DAYS),
+        children,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::logseq::mock::MockGraph;
+    use crate::tools::testing::{call_text, serve};
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_task_dashboard_is_replaced_in_place() {
+        let mut graph = MockGraph::new();
+        graph.add_page("Work");
+        let ship = graph.add_block("Work", "NOW [#A] Ship it\nSCHEDULED: <2024-01-31 Wed>");
+        graph.add_block("Work", "TODO Write docs");
+        graph.add_page("Dashboard");
+        graph.add_block("Dashboard", "My notes");
+        let (logseq, ctx) = serve(graph).await;
+
+        let args = json!({
+            "page": "Dashboard",
+            "include_agenda": true,
+            "include_statistics": true
+        });
+        let text = call_text(&ctx, "write_task_dashboard", args.clone())
+            .await
+            .unwrap();
+        assert!(text.starts_with("Wrote the task dashboard"), "{}", text);
+        let text = call_text(&ctx, "write_task_dashboard", args).await.unwrap();
+        assert!(text.starts_with("Updated the task dashboard"), "{}", text);
+
+        logseq.with_graph(|g| {
+            let roots = g.children("Dashboard");
+            assert_eq!(roots.len(), 4, "one dashboard after two runs");
+            assert_eq!(g.block_content(&roots[0]), Some("My notes"));
+            assert_eq!(
+                g.block_content(&roots[1]),
+                Some("<!-- mcp:begin task-dashboard -->")
+            );
+            assert!(
+                g.block_content(&roots[2])
+                    .unwrap()
+                    .starts_with("Task Dashboard\nupdated:: ")
+            );
+
+            let sections: Vec<&str> = g
+                .children(&roots[2])
+                .iter()
+                .filter_map(|uuid| g.block_content(uuid))
+                .collect();
+            assert_eq!(
+                sections,
+                vec![
+                    "Statistics",
+                    "Agenda: next 7 days",
+                    "**NOW** (1)",
+                    "**TODO** (1)"
+                ]
+            );
+            let agenda = &g.children(&roots[2])[1];
+            let entry = &g.children(agenda)[0];
+            assert_eq!(
+                g.block_content(entry),
+                Some(format!("Overdue since 2024-01-31: (({}))", ship).as_str())
+            );
+        });
+
+        let text = call_text(&ctx, "find_incomplete_todos", json!({}))
+            .await
+            .unwrap();
+        assert!(text.contains("Found 2 incomplete todos"), "{}", text);
+    }
+}
@@ -0,0 +1,224 @@
+//! Local trash for deleted pages and blocks
+//!
+//! LogSeq's delete APIs are irreversible, so before a page or block is
+//! deleted through the server its block tree is saved here as one JSON file
+//! per deletion. `restore_from_trash` writes it back.
+
+use crate::logseq::api::Block;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+/// What was deleted, and where it lived
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum TrashedItem {
+    Page {
+        name: String,
+        #[serde(default)]
+        properties: Option<HashMap<String, Value>>,
+    },
+    Block {
+        uuid: String,
+        /// Name of the page the block was on, when it could be determined
+        #[serde(default)]
+        page: Option<String>,
+        /// UUID of the parent block, or `None` for a top-level block
+        #[serde(default)]
+        parent: Option<String>,
+    },
+}
+
+impl TrashedItem {
+    /// A short human-readable description, e.g. `page 'Projects'`
+    pub fn describe(&self, blocks: &[Block]) -> String {
+        match self {
+            TrashedItem::Page { name, .. } => format!("page '{}'", name),
+            TrashedItem::Block { uuid, page, .. } => {
+                let first_line = blocks
+                    .first()
+                    .and_then(|block| block.content.lines().next())
+                    .unwrap_or_default();
+                let mut text = format!("block {} (\"{}\")", uuid, first_line);
+                if let Some(page) = page {
+                    text.push_str(&format!(" from page '{}'", page));
+                }
+                text
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrashEntry {
+    pub id: String,
+    pub deleted_at: DateTime<Utc>,
+    #[serde(flatten)]
+    pub item: TrashedItem,
+    /// The deleted block tree: a page's top-level blocks, or the single
+    /// deleted block with its children
+    pub blocks: Vec<Block>,
+}
+
+/// A directory of trashed pages and blocks, one `<id>.json` file each
+#[derive(Debug, Clone)]
+pub struct Trash {
+    dir: PathBuf,
+}
+
+impl Trash {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// Save a deleted item, returning its entry
+    pub fn put(&self, item: TrashedItem, blocks: Vec<Block>) -> io::Result<TrashEntry> {
+        fs::create_dir_all(&self.dir)?;
+
+        let deleted_at = Utc::now();
+        let suffix = uuid::Uuid::new_v4().simple().to_string();
+        let entry = TrashEntry {
+            id: format!("{}-{}", deleted_at.format("%Y%m%dT%H%M%S"), &suffix[..8]),
+            deleted_at,
+            item,
+            blocks,
+        };
+
+        // Write then rename so a crash never leaves a truncated entry behind
+        let path = self.entry_path(&entry.id);
+        let partial = path.with_extension("json.partial");
+        fs::write(&partial, serde_json::to_vec_pretty(&entry)?)?;
+        fs::rename(&partial, &path)?;
+        Ok(entry)
+    }
+
+    /// Every entry in the trash, newest first. Files that can't be read are
+    /// skipped with a warning.
+    pub fn list(&self) -> io::Result<Vec<TrashEntry>> {
+        let dir = match fs::read_dir(&self.dir) {
+            Ok(dir) => dir,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e),
+        };
+
+        let mut entries: Vec<TrashEntry> = Vec::new();
+        for file in dir {
+            let path = file?.path();
+            if path.extension().is_none_or(|ext| ext != "json") {
+                continue;
+            }
+            let entry = fs::read(&path)
+                .map_err(|e| e.to_string())
+                .and_then(|bytes| serde_json::from_slice(&bytes).map_err(|e| e.to_string()));
+            match entry {
+                Ok(entry) => entries.push(entry),
+                Err(e) => tracing::warn!("Skipping unreadable trash entry {:?}: {}", path, e),
+            }
+        }
+        entries.sort_by(|a, b| b.deleted_at.cmp(&a.deleted_at).then(b.id.cmp(&a.id)));
+        Ok(entries)
+    }
+
+    /// Look up an entry by id
+    pub fn get(&self, id: &str) -> io::Result<Option<TrashEntry>> {
+        if !is_valid_id(id) {
+            return Ok(None);
+        }
+        match fs::read(self.entry_path(id)) {
+            Ok(bytes) => Ok(Some(serde_json::from_slice(&bytes)?)),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Permanently remove an entry, e.g. once it has been restored
+    pub fn remove(&self, id: &str) -> io::Result<()> {
+        if !is_valid_id(id) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("invalid trash id: {}", id),
+            ));
+        }
+        fs::remove_file(self.entry_path(id))
+    }
+
+    fn entry_path(&self, id: &str) -> PathBuf {
+        self.dir.join(format!("{}.json", id))
+    }
+}
+
+/// Ids are generated by `put`; anything else (such as a path) is rejected
+fn is_valid_id(id: &str) -> bool {
+    !id.is_empty() && id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tree() -> Vec<Block> {
+        let mut parent = Block::new("a".into(), "Parent\nsecond line".into());
+        parent.children = vec![Block::new("b".into(), "Child".into())];
+        vec![parent]
+    }
+
+    #[test]
+    fn test_put_get_remove_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let trash = Trash::new(dir.path().join("trash"));
+
+        let entry = trash
+            .put(
+                TrashedItem::Block {
+                    uuid: "a".into(),
+                    page: Some("Projects".into()),
+                    parent: None,
+                },
+                tree(),
+            )
+            .unwrap();
+
+        let stored = trash.get(&entry.id).unwrap().unwrap();
+        assert_eq!(stored.blocks[0].children[0].content, "Child");
+        assert_eq!(
+            stored.item.describe(&stored.blocks),
+            "block a (\"Parent\") from page 'Projects'"
+        );
+
+        trash.remove(&entry.id).unwrap();
+        assert!(trash.get(&entry.id).unwrap().is_none());
+        assert!(trash.list().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_list_is_newest_first_and_skips_junk() {
+        let dir = tempfile::tempdir().unwrap();
+        let trash = Trash::new(dir.path());
+        assert!(trash.list().unwrap().is_empty());
+
+        let page = |name: &str| TrashedItem::Page {
+            name: name.into(),
+            properties: None,
+        };
+        let first = trash.put(page("First"), tree()).unwrap();
+        let second = trash.put(page("Second"), vec![]).unwrap();
+        fs::write(dir.path().join("broken.json"), "{").unwrap();
+        fs::write(dir.path().join("notes.txt"), "ignored").unwrap();
+
+        let ids: Vec<String> = trash.list().unwrap().into_iter().map(|e| e.id).collect();
+        assert_eq!(ids, vec![second.id, first.id]);
+    }
+
+    #[test]
+    fn test_rejects_ids_that_are_not_ours() {
+        let dir = tempfile::tempdir().unwrap();
+        let trash = Trash::new(dir.path());
+
+        assert!(trash.get("../secrets").unwrap().is_none());
+        assert!(trash.remove("../secrets").is_err());
+    }
+}
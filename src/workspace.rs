@@ -0,0 +1,156 @@
+//! Named workspaces that give tools a default scope
+//!
+//! A workspace bundles a namespace, tags, a journal heading, and a capture
+//! template under one name, configured as the name followed by a JSON object:
+//! `alpha {"namespace": "Projects/Alpha", "tags": ["alpha"], "heading": "Alpha"}`.
+//! A session selects one with its `workspace` setting, and a call can pick
+//! another with its own `workspace` argument. Within a workspace, `search`
+//! only finds pages in the namespace, `create_page` creates pages in it with
+//! the tags, and `capture` files notes under the heading with the tags,
+//! shaped by the workspace's [capture template](crate::capture_template).
+//!
+//! Unlike the namespace of [`crate::sandbox`], a workspace only sets
+//! defaults: it doesn't stop tools reaching the rest of the graph.
+
+use crate::logseq::content::extract_tags;
+use crate::sandbox::Namespace;
+use crate::tools::add_tag_to_block::with_tag;
+use serde::Deserialize;
+
+/// A workspace's scope, as configured
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Workspace {
+    pub name: String,
+    pub namespace: Namespace,
+    /// Tags added to pages created and notes captured in the workspace
+    pub tags: Vec<String>,
+    /// The journal block notes are captured under, if not the page itself
+    pub heading: Option<String>,
+    /// The capture template notes captured in the workspace use, instead
+    /// of the default one
+    pub template: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct WorkspaceSettings {
+    namespace: Option<String>,
+    #[serde(default)]
+    tags: Vec<String>,
+    heading: Option<String>,
+    template: Option<String>,
+}
+
+impl Workspace {
+    /// Parse a name followed, optionally, by its settings as a JSON object.
+    /// The namespace defaults to the name.
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let text = text.trim();
+        let (name, settings) = match text.split_once(char::is_whitespace) {
+            Some((name, settings)) => (name, settings.trim()),
+            None => (text, ""),
+        };
+        if name.is_empty() || name.starts_with('{') {
+            return Err(format!(
+                "'{}' must start with the workspace's name, such as 'alpha {{\"namespace\": \"Projects/Alpha\"}}'",
+                text
+            ));
+        }
+        let settings: WorkspaceSettings = match settings {
+            "" => serde_json::from_str("{}"),
+            json => serde_json::from_str(json),
+        }
+        .map_err(|e| {
+            format!(
+                "settings of workspace {} must be a JSON object with namespace, tags, heading, and template: {}",
+                name, e
+            )
+        })?;
+
+        let namespace = Namespace::new(settings.namespace.as_deref().unwrap_or(name))
+            .map_err(|e| format!("workspace {} has an invalid namespace: {}", name, e))?;
+        let tags = settings
+            .tags
+            .iter()
+            .map(|tag| tag.trim().trim_start_matches('#').to_string())
+            .filter(|tag| !tag.is_empty())
+            .collect();
+        Ok(Self {
+            name: name.to_string(),
+            namespace,
+            tags,
+            heading: settings
+                .heading
+                .map(|heading| heading.trim().to_string())
+                .filter(|heading| !heading.is_empty()),
+            template: settings
+                .template
+                .map(|template| template.trim().to_string())
+                .filter(|template| !template.is_empty()),
+        })
+    }
+
+    /// Whether `page` is the namespace's own page or one under it
+    pub fn contains(&self, page: &str) -> bool {
+        let own = self.namespace.prefix().trim_end_matches('/');
+        self.namespace.contains(page) || page.trim().eq_ignore_ascii_case(own)
+    }
+
+    /// The name a page called `page` has in the workspace
+    pub fn page_name(&self, page: &str) -> String {
+        self.namespace.qualify(page)
+    }
+
+    /// `content` with whichever of the workspace's tags it doesn't have yet
+    pub fn tag(&self, content: &str) -> String {
+        let present = extract_tags(content);
+        self.tags
+            .iter()
+            .filter(|tag| !present.contains(&tag.to_lowercase()))
+            .fold(content.to_string(), |content, tag| with_tag(&content, tag))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_workspaces_parse_with_defaults() {
+        let workspace = Workspace::parse(
+            r##"alpha {"namespace": "Projects/Alpha", "tags": ["#alpha", "client work"], "heading": "Alpha"}"##,
+        )
+        .unwrap();
+        assert_eq!(workspace.name, "alpha");
+        assert_eq!(workspace.namespace.prefix(), "Projects/Alpha/");
+        assert_eq!(workspace.tags, ["alpha", "client work"]);
+        assert_eq!(workspace.heading.as_deref(), Some("Alpha"));
+        assert!(workspace.template.is_none());
+
+        let bare = Workspace::parse("Reading").unwrap();
+        assert_eq!(bare.namespace.prefix(), "Reading/");
+        assert!(bare.tags.is_empty());
+        assert!(bare.heading.is_none());
+
+        assert!(Workspace::parse(r#"{"namespace": "A"}"#).is_err());
+        assert!(Workspace::parse(r#"alpha {"namspace": "A"}"#).is_err());
+        assert!(Workspace::parse("alpha [1]").is_err());
+    }
+
+    #[test]
+    fn test_workspaces_scope_pages_and_tag_content() {
+        let workspace = Workspace::parse(
+            r#"alpha {"namespace": "Projects/Alpha", "tags": ["alpha", "client work"]}"#,
+        )
+        .unwrap();
+        assert!(workspace.contains("projects/alpha"));
+        assert!(workspace.contains("Projects/Alpha/Notes"));
+        assert!(!workspace.contains("Projects/Beta"));
+        assert_eq!(workspace.page_name("Notes"), "Projects/Alpha/Notes");
+
+        assert_eq!(
+            workspace.tag("Call the printer #Alpha"),
+            "Call the printer #Alpha #[[client work]]"
+        );
+    }
+}
@@ -35,6 +35,9 @@ pub struct McpTestContext {
     pub created_pages: Vec<String>,
     pub created_blocks: Vec<String>,
     pub request_id: u64,
+    /// State directory for the server under test, so its trash doesn't
+    /// mix with the user's
+    pub state_dir: tempfile::TempDir,
 }
 
 impl McpTestContext {
@@ -54,7 +57,8 @@ impl McpTestContext {
         println!("🧪 Starting MCP test context: {}", &test_id[..8]);
 
         // Spawn the MCP server process
-        let mut server_process = Self::spawn_server().await?;
+        let state_dir = tempfile::tempdir()?;
+        let mut server_process = Self::spawn_server(&state_dir).await?;
 
         // Extract stdin and stdout
         let stdin = server_process.stdin.take();
@@ -68,6 +72,7 @@ impl McpTestContext {
             created_pages: Vec::new(),
             created_blocks: Vec::new(),
             request_id: 1,
+            state_dir,
         };
 
         // Initialize the MCP session
@@ -78,7 +83,7 @@ impl McpTestContext {
     }
 
     /// Spawn the MCP server process
-    async fn spawn_server() -> Result<Child> {
+    async fn spawn_server(state_dir: &tempfile::TempDir) -> Result<Child> {
         let mut cmd = Command::new("cargo");
         cmd.args(["run", "--quiet"])
             .stdin(Stdio::piped())
@@ -88,7 +93,8 @@ impl McpTestContext {
                 "LOGSEQ_API_URL",
                 env::var("LOGSEQ_API_URL").unwrap_or_else(|_| "http://localhost:12315".into()),
             )
-            .env("LOGSEQ_API_TOKEN", env::var("LOGSEQ_API_TOKEN")?);
+            .env("LOGSEQ_API_TOKEN", env::var("LOGSEQ_API_TOKEN")?)
+            .env("LOGSEQ_MCP_STATE_DIR", state_dir.path());
 
         let mut child = cmd
             .spawn()
@@ -271,6 +277,23 @@ impl McpTestContext {
             .ok_or_else(|| anyhow::anyhow!("No result in response"))
     }
 
+    /// Call a two-phase destructive tool: request the preview, then repeat the
+    /// call with the confirmation token it returned
+    pub async fn call_confirmed_tool(
+        &mut self,
+        tool_name: &str,
+        arguments: Value,
+    ) -> Result<Value> {
+        let preview = self.call_tool(tool_name, Some(arguments.clone())).await?;
+        let text = preview["content"][0]["text"].as_str().unwrap_or_default();
+        let token = confirmation_token(text)
+            .ok_or_else(|| anyhow::anyhow!("No confirmation token in {}: {}", tool_name, text))?;
+
+        let mut arguments = arguments;
+        arguments["confirm"] = json!(token);
+        self.call_tool(tool_name, Some(arguments)).await
+    }
+
     /// Generate a unique test page name
     pub fn test_page_name(&self, suffix: &str) -> String {
         format!("test-{}-{}", &self.test_id[..8], suffix)
@@ -412,7 +435,9 @@ impl McpTestContext {
                                             "page_name": page_name
                                         });
 
-                                        match self.call_tool("delete_page", Some(delete_args)).await
+                                        match self
+                                            .call_confirmed_tool("delete_page", delete_args)
+                                            .await
                                         {
                                             Ok(_) => {
                                                 println!(
@@ -635,7 +660,7 @@ impl McpTestContext {
                     "page_name": page_name
                 });
 
-                match self.call_tool("delete_page", Some(delete_args)).await {
+                match self.call_confirmed_tool("delete_page", delete_args).await {
                     Ok(result) => {
                         if let Some(is_error) = result.get("isError") {
                             if !is_error.as_bool().unwrap_or(false) {
@@ -690,7 +715,7 @@ impl McpTestContext {
                     "uuid": block_uuid
                 });
 
-                match self.call_tool("delete_block", Some(delete_args)).await {
+                match self.call_confirmed_tool("delete_block", delete_args).await {
                     Ok(result) => {
                         if let Some(is_error) = result.get("isError") {
                             if !is_error.as_bool().unwrap_or(false) {
@@ -745,6 +770,13 @@ impl Drop for McpTestContext {
     }
 }
 
+/// Extract the token from a destructive tool's `confirm: "<token>"` prompt
+fn confirmation_token(text: &str) -> Option<&str> {
+    let start = text.find("confirm: \"")? + "confirm: \"".len();
+    let len = text[start..].find('"')?;
+    Some(&text[start..start + len])
+}
+
 /// Helper to skip tests if integration testing is disabled
 fn should_skip_integration_tests() -> bool {
     env::var("SKIP_INTEGRATION_TESTS").unwrap_or_default() == "1"
@@ -763,21 +795,69 @@ async fn test_mcp_server_startup_and_tools() -> Result<()> {
     let expected_tools = vec![
         "list_pages",
         "get_page_content",
+        "get_page_checksums",
+        "page_stats",
         "create_page",
         "search",
         "create_block",
+        "append_block_to_page",
+        "prepend_block_to_page",
+        "capture",
         "get_page",
+        "resolve_page_name",
+        "get_namespace_tree",
         "get_block",
+        "get_block_children",
         "get_current_page",
         "get_current_block",
+        "get_editor_context",
         "datascript_query",
         "get_current_graph",
+        "list_graphs",
+        "health_check",
         "get_state_from_store",
         "get_user_configs",
+        "show_ui_message",
+        "open_in_logseq",
+        "invalidate_cache",
+        "reset_write_budget",
         "update_block",
+        "get_block_property",
+        "upsert_block_property",
+        "remove_block_property",
+        "set_todo_state",
+        "move_block",
+        "quote_block",
+        "create_block_reference",
+        "get_block_referencing_blocks",
+        "compile_digest",
         "delete_block",
         "delete_page",
         "find_incomplete_todos",
+        "find_scheduled_tasks",
+        "list_flashcards",
+        "get_due_cards",
+        "analyze_tag_cooccurrence",
+        "list_all_tags",
+        "find_broken_links",
+        "find_duplicates",
+        "add_tag_to_block",
+        "rename_tag",
+        "get_writing_stats",
+        "get_graph_stats",
+        "get_on_this_day",
+        "get_recent_changes",
+        "build_context",
+        "export_link_graph",
+        "extract_corpus",
+        "apply_corrections",
+        "list_trash",
+        "restore_from_trash",
+        "list_interrupted_operations",
+        "resolve_interrupted_operation",
+        "create_page_from_template",
+        "import_markdown",
+        "write_task_dashboard",
     ];
 
     for expected_tool in &expected_tools {
@@ -900,6 +980,30 @@ async fn test_mcp_get_page_content() -> Result<()> {
         return Err(anyhow::anyhow!("get_page_content did not return content"));
     }
 
+    // Block UUIDs are included on request so agents can act on blocks
+    let block_content = ctx.test_content("Addressable block");
+    let created = ctx
+        .call_tool(
+            "create_block",
+            Some(json!({"content": block_content, "parent": page_name})),
+        )
+        .await?;
+    if let Some(uuid) = created["content"][0]["text"]
+        .as_str()
+        .and_then(|t| t.strip_prefix("Created block with UUID: "))
+    {
+        ctx.created_blocks.push(uuid.to_string());
+        let args = json!({"page_name": page_name, "include_uuids": true, "max_depth": 1});
+        let result = ctx.call_tool("get_page_content", Some(args)).await?;
+        let text = result["content"][0]["text"].as_str().unwrap_or_default();
+        assert!(
+            text.contains(&format!("id:: {}", uuid)),
+            "UUID should be listed: {}",
+            text
+        );
+        println!("  ✓ get_page_content includes block UUIDs on request");
+    }
+
     ctx.cleanup().await;
     Ok(())
 }
@@ -947,6 +1051,29 @@ async fn test_mcp_search_tool() -> Result<()> {
         println!("  ✓ search returned {} characters of results", text.len());
     }
 
+    // Scoped, case-sensitive search with a limit
+    let scoped_args = json!({
+        "query": search_term.to_uppercase(),
+        "case_sensitive": true,
+        "limit": 5,
+        "page": ctx.created_pages[0]
+    });
+    let result = ctx.call_tool("search", Some(scoped_args)).await?;
+    if let Some(text) = result
+        .get("content")
+        .and_then(|c| c.as_array())
+        .and_then(|c| c.first())
+        .and_then(|c| c.get("raw"))
+        .and_then(|r| r.get("text"))
+        .and_then(|t| t.as_str())
+    {
+        assert!(
+            text.contains("No results found"),
+            "Case-sensitive search should not match a differently-cased term"
+        );
+        println!("  ✓ case-sensitive page-scoped search excluded mismatched case");
+    }
+
     ctx.cleanup().await;
     Ok(())
 }
@@ -1073,7 +1200,7 @@ async fn test_mcp_app_state_tools() -> Result<()> {
     Ok(())
 }
 
-/// Test delete operations (delete_page and delete_block)
+/// Test the two-phase delete_page and delete_block confirmation flow
 #[tokio::test]
 #[ignore]
 async fn test_mcp_delete_operations() -> Result<()> {
@@ -1081,54 +1208,392 @@ async fn test_mcp_delete_operations() -> Result<()> {
 
     println!("🗑️ Testing MCP delete operations");
 
-    // Step 1: Create a test page to delete later
-    println!("1. Creating test page and blocks for deletion");
+    println!("1. Creating test page and block for deletion");
     let page_name = ctx.create_test_page("delete-test", None).await?;
-    println!("   ✓ Created test page: {}", page_name);
+    let content = ctx.test_content("Block to delete");
+    let create_result = ctx
+        .call_tool(
+            "create_block",
+            Some(json!({"content": content, "parent": page_name})),
+        )
+        .await?;
+    let uuid = create_result["content"][0]["text"]
+        .as_str()
+        .and_then(|t| t.strip_prefix("Created block with UUID: "))
+        .map(String::from);
 
-    // Step 2: Try to create a block on the page (may fail due to LogSeq API limitations)
-    println!("2. Attempting to create test block");
+    if let Some(uuid) = uuid {
+        println!("2. Previewing and confirming delete_block");
+        let preview = ctx
+            .call_tool("delete_block", Some(json!({"uuid": uuid})))
+            .await?;
+        let text = preview["content"][0]["text"].as_str().unwrap_or_default();
+        assert!(text.contains("Nothing has been deleted yet"), "{}", text);
+        let token = confirmation_token(text).expect("preview should include a token");
+
+        let wrong = ctx
+            .call_tool(
+                "delete_block",
+                Some(json!({"uuid": uuid, "confirm": "not-a-token"})),
+            )
+            .await;
+        assert!(wrong.is_err(), "an unknown token should be rejected");
 
-    // Use DataScript query to find an existing block we can safely test delete on
-    let datascript_args = json!({
-        "query": "[:find ?uuid :where [?b :block/uuid ?uuid] :limit 1]"
-    });
+        let result = ctx
+            .call_tool(
+                "delete_block",
+                Some(json!({"uuid": uuid, "confirm": token})),
+            )
+            .await?;
+        let text = result["content"][0]["text"].as_str().unwrap_or_default();
+        assert!(text.contains("Successfully deleted block"), "{}", text);
+
+        let reused = ctx
+            .call_tool(
+                "delete_block",
+                Some(json!({"uuid": uuid, "confirm": token})),
+            )
+            .await;
+        assert!(reused.is_err(), "a token should only work once");
+        println!("   ✓ Block deleted only after confirmation");
+    } else {
+        println!("   ⚠ Could not create a block; skipping delete_block checks");
+    }
 
-    match ctx
-        .call_tool("datascript_query", Some(datascript_args))
+    println!("3. Previewing and confirming delete_page");
+    let preview = ctx
+        .call_tool("delete_page", Some(json!({"page_name": page_name})))
+        .await?;
+    let text = preview["content"][0]["text"].as_str().unwrap_or_default();
+    assert!(text.contains("block(s)"), "{}", text);
+    let page = ctx
+        .call_tool("get_page", Some(json!({"name_or_uuid": page_name})))
+        .await;
+    assert!(page.is_ok(), "the preview must not delete the page");
+
+    ctx.call_confirmed_tool("delete_page", json!({"page_name": page_name}))
+        .await?;
+    ctx.created_pages.retain(|p| p != &page_name);
+    println!("   ✓ Page deleted only after confirmation");
+
+    ctx.cleanup().await;
+    Ok(())
+}
+
+/// Test that deleted pages go to the trash and can be restored
+#[tokio::test]
+#[ignore]
+async fn test_mcp_trash_restore() -> Result<()> {
+    let mut ctx = McpTestContext::new().await?;
+
+    println!("♻️ Testing MCP trash and restore");
+
+    let page_name = ctx.create_test_page("trash-test", None).await?;
+    let content = ctx.test_content("Block that survives deletion");
+    ctx.call_tool(
+        "create_block",
+        Some(json!({"content": content, "parent": page_name})),
+    )
+    .await?;
+
+    let result = ctx
+        .call_confirmed_tool("delete_page", json!({"page_name": page_name}))
+        .await?;
+    let text = result["content"][0]["text"].as_str().unwrap_or_default();
+    let id = text
+        .split("Saved to the trash as ")
+        .nth(1)
+        .and_then(|rest| rest.split('.').next())
+        .expect("delete result should name the trash entry")
+        .to_string();
+    println!("   ✓ Page deleted and saved to trash as {}", id);
+
+    let listing = ctx.call_tool("list_trash", None).await?;
+    let text = listing["content"][0]["text"].as_str().unwrap_or_default();
+    assert!(
+        text.contains(&id),
+        "list_trash should show {}: {}",
+        id,
+        text
+    );
+
+    ctx.call_tool("restore_from_trash", Some(json!({"id": id})))
+        .await?;
+    let restored = ctx
+        .call_tool("get_page_content", Some(json!({"page_name": page_name})))
+        .await?;
+    let text = restored["content"][0]["text"].as_str().unwrap_or_default();
+    assert!(
+        text.contains(&content),
+        "restored page is missing its block: {}",
+        text
+    );
+    println!("   ✓ Page restored with its blocks");
+
+    let listing = ctx.call_tool("list_trash", None).await?;
+    let text = listing["content"][0]["text"].as_str().unwrap_or_default();
+    assert!(!text.contains(&id), "restored entries leave the trash");
+
+    ctx.cleanup().await;
+    Ok(())
+}
+
+/// Test that page lookups for missing pages suggest close matches
+#[tokio::test]
+#[ignore]
+async fn test_mcp_page_not_found_suggestions() -> Result<()> {
+    let mut ctx = McpTestContext::new().await?;
+
+    println!("🔎 Testing page-not-found suggestions");
+
+    let page_name = ctx.create_test_page("suggest-me", None).await?;
+    let typo = format!("{}x", page_name);
+
+    let result = ctx
+        .call_tool("get_page", Some(json!({"name_or_uuid": typo})))
+        .await;
+    let error = result.expect_err("Looking up a missing page should fail");
+    let message = error.to_string();
+    assert!(
+        message.contains(&page_name),
+        "Error should suggest the similar page: {}",
+        message
+    );
+    println!("   ✓ Suggested {} for {}", page_name, typo);
+
+    ctx.cleanup().await;
+    Ok(())
+}
+
+/// Test datascript queries with values bound through `:in` inputs
+#[tokio::test]
+#[ignore]
+async fn test_mcp_datascript_query_inputs() -> Result<()> {
+    let mut ctx = McpTestContext::new().await?;
+
+    println!("🗄️ Testing parameterized datascript queries");
+
+    let page_name = ctx.create_test_page("query-inputs", None).await?;
+
+    // The page name goes through an input, quoted as an EDN string
+    let args = json!({
+        "query": "[:find ?uuid :in $ ?name :where [?p :block/name ?name] [?p :block/uuid ?uuid]]",
+        "inputs": [format!("{:?}", page_name.to_lowercase())]
+    });
+    let result = ctx.call_tool("datascript_query", Some(args)).await?;
+    let text = result["content"][0]["text"].as_str().unwrap_or_default();
+    let rows: Value = serde_json::from_str(text)?;
+    assert_eq!(
+        rows.as_array().map(Vec::len),
+        Some(1),
+        "Query should find exactly the test page: {}",
+        text
+    );
+    println!("   ✓ Bound page name through :in");
+
+    // Inputs without an :in clause are rejected rather than silently ignored
+    let args = json!({
+        "query": "[:find ?p :where [?p :block/name]]",
+        "inputs": ["\"unused\""]
+    });
+    let error = ctx
+        .call_tool("datascript_query", Some(args))
         .await
-    {
-        Ok(query_result) => {
-            if let Some(content) = query_result.get("content").and_then(|c| c.as_array())
-                && let Some(first_content) = content.first()
-                && let Some(text) = first_content
-                    .get("raw")
-                    .and_then(|r| r.get("text"))
-                    .and_then(|t| t.as_str())
-                && let Ok(query_data) = serde_json::from_str::<Value>(text)
-                && let Some(results) = query_data.as_array()
-                && let Some(first_result) = results.first()
-                && let Some(result_array) = first_result.as_array()
-                && let Some(uuid) = result_array.first().and_then(|u| u.as_str())
-            {
-                println!("   ⚠ Found existing block UUID for delete test: {}", uuid);
+        .expect_err("Inputs without :in should be rejected");
+    assert!(error.to_string().contains(":in"));
+    println!("   ✓ Rejected inputs without :in");
 
-                // Test delete_block with warning (we won't actually delete)
-                println!("3. Testing delete_block tool availability (not executing)");
-                // We don't actually delete the block to avoid data loss
-                println!("   ⚠ Skipping actual block deletion to prevent data loss");
-            }
-        }
-        Err(e) => {
-            println!("   ⚠ Could not find existing blocks: {}", e);
-        }
+    ctx.cleanup().await;
+    Ok(())
+}
+
+/// Test get/upsert/remove_block_property tools
+#[tokio::test]
+#[ignore]
+async fn test_mcp_block_properties() -> Result<()> {
+    let mut ctx = McpTestContext::new().await?;
+
+    println!("🏷️ Testing MCP block property tools");
+
+    let page_name = ctx.create_test_page("block-properties", None).await?;
+    let content = ctx.test_content("Block with properties");
+    let create_result = ctx
+        .call_tool(
+            "create_block",
+            Some(json!({"content": content, "parent": page_name})),
+        )
+        .await?;
+    let uuid = create_result["content"][0]["text"]
+        .as_str()
+        .and_then(|t| t.strip_prefix("Created block with UUID: "))
+        .map(String::from);
+
+    if let Some(uuid) = uuid {
+        ctx.created_blocks.push(uuid.clone());
+
+        ctx.call_tool(
+            "upsert_block_property",
+            Some(json!({"uuid": uuid, "key": "status", "value": "active"})),
+        )
+        .await?;
+        let result = ctx
+            .call_tool(
+                "get_block_property",
+                Some(json!({"uuid": uuid, "key": "status"})),
+            )
+            .await?;
+        let text = result["content"][0]["text"].as_str().unwrap_or_default();
+        assert!(text.contains("active"), "Unexpected value: {}", text);
+        println!("   ✓ Set and read back status:: active");
+
+        let block = ctx
+            .call_tool("get_block", Some(json!({"uuid": uuid})))
+            .await?;
+        let block_text = block["content"][0]["text"].as_str().unwrap_or_default();
+        assert!(
+            block_text.contains("Block with properties"),
+            "Block content should be preserved"
+        );
+
+        let result = ctx
+            .call_tool(
+                "remove_block_property",
+                Some(json!({"uuid": uuid, "key": "status"})),
+            )
+            .await?;
+        let text = result["content"][0]["text"].as_str().unwrap_or_default();
+        assert!(text.starts_with("Removed property"), "Unexpected result: {}", text);
+
+        let result = ctx
+            .call_tool(
+                "get_block_property",
+                Some(json!({"uuid": uuid, "key": "status"})),
+            )
+            .await?;
+        let text = result["content"][0]["text"].as_str().unwrap_or_default();
+        assert!(text.contains("is not set"), "Property should be gone: {}", text);
+        println!("   ✓ Removed property");
+
+        let error = ctx
+            .call_tool(
+                "upsert_block_property",
+                Some(json!({"uuid": uuid, "key": "two words", "value": "x"})),
+            )
+            .await
+            .expect_err("Keys with whitespace should be rejected");
+        assert!(error.to_string().contains("Invalid property key"));
+        println!("   ✓ Rejected invalid key");
+    } else {
+        println!("   ⚠ Could not create block (API limitation)");
+    }
+
+    ctx.cleanup().await;
+    Ok(())
+}
+
+/// Test set_todo_state tool
+#[tokio::test]
+#[ignore]
+async fn test_mcp_set_todo_state() -> Result<()> {
+    let mut ctx = McpTestContext::new().await?;
+
+    println!("✅ Testing MCP set_todo_state tool");
+
+    let page_name = ctx.create_test_page("todo-state-test", None).await?;
+    let content = format!("TODO [#A] {}", ctx.test_content("Finish the report"));
+    let create_result = ctx
+        .call_tool(
+            "create_block",
+            Some(json!({"content": content, "parent": page_name})),
+        )
+        .await?;
+
+    let uuid = create_result
+        .get("content")
+        .and_then(|c| c.as_array())
+        .and_then(|c| c.first())
+        .and_then(|c| c.get("text"))
+        .and_then(|t| t.as_str())
+        .and_then(|t| t.strip_prefix("Created block with UUID: "))
+        .map(String::from);
+
+    if let Some(uuid) = uuid {
+        ctx.created_blocks.push(uuid.clone());
+
+        let result = ctx
+            .call_tool(
+                "set_todo_state",
+                Some(json!({"uuid": uuid, "marker": "DONE"})),
+            )
+            .await?;
+        let text = result["content"][0]["text"].as_str().unwrap_or_default();
+        assert!(text.contains("TODO → DONE"), "Unexpected result: {}", text);
+        println!("   ✓ {}", text);
+
+        let block = ctx
+            .call_tool("get_block", Some(json!({"uuid": uuid})))
+            .await?;
+        let block_text = block["content"][0]["text"].as_str().unwrap_or_default();
+        assert!(block_text.contains("DONE [#A]"), "Priority should be kept");
+        println!("   ✓ Marker rewritten and priority preserved");
+    } else {
+        println!("   ⚠ Could not create task block (API limitation)");
     }
 
-    // Step 3: Test delete_page functionality (will be cleaned up automatically)
-    println!("4. Testing delete_page tool availability");
-    println!("   ✓ delete_page tool is available and will be tested during cleanup");
+    let invalid = ctx
+        .call_tool(
+            "set_todo_state",
+            Some(json!({"uuid": "00000000-0000-0000-0000-000000000000", "marker": "FINISHED"})),
+        )
+        .await;
+    assert!(invalid.is_err(), "Unknown markers should be rejected");
 
-    println!("   ✓ Delete operations test completed");
+    ctx.cleanup().await;
+    Ok(())
+}
+
+/// Test move_block tool
+#[tokio::test]
+#[ignore]
+async fn test_mcp_move_block() -> Result<()> {
+    let mut ctx = McpTestContext::new().await?;
+
+    println!("🔀 Testing MCP move_block tool");
+
+    let page_name = ctx.create_test_page("move-test", None).await?;
+    let first = ctx
+        .try_create_test_block("First block", Some(page_name.clone()))
+        .await?;
+    let second = ctx
+        .try_create_test_block("Second block", Some(page_name.clone()))
+        .await?;
+
+    if let (Some(first), Some(second)) = (first, second) {
+        println!("1. Moving second block before the first");
+        let move_args = json!({
+            "uuid": second,
+            "target_uuid": first,
+            "before": true
+        });
+        let result = ctx.call_tool("move_block", Some(move_args)).await?;
+        assert_ne!(
+            result.get("isError").and_then(|e| e.as_bool()),
+            Some(true),
+            "move_block should succeed"
+        );
+        println!("   ✓ Block moved");
+
+        println!("2. Moving first block under the second as a child");
+        let move_args = json!({
+            "uuid": first,
+            "target_uuid": second,
+            "children": true
+        });
+        ctx.call_tool("move_block", Some(move_args)).await?;
+        println!("   ✓ Block nested");
+    } else {
+        println!("   ⚠ Could not create blocks to move (API limitation)");
+    }
 
     ctx.cleanup().await;
     Ok(())
@@ -1194,6 +1659,59 @@ async fn test_mcp_find_incomplete_todos() -> Result<()> {
     Ok(())
 }
 
+/// Test find_incomplete_todos pagination
+#[tokio::test]
+#[ignore]
+async fn test_mcp_find_incomplete_todos_pagination() -> Result<()> {
+    let mut ctx = McpTestContext::new().await?;
+
+    println!("📋 Testing find_incomplete_todos pagination");
+
+    let result = ctx
+        .call_tool("find_incomplete_todos", Some(json!({"limit": 1})))
+        .await?;
+    let text = result
+        .get("content")
+        .and_then(|c| c.as_array())
+        .and_then(|c| c.first())
+        .and_then(|c| c.get("text"))
+        .and_then(|t| t.as_str())
+        .unwrap_or_default()
+        .to_string();
+
+    if let Some(cursor_start) = text.find("cursor \"") {
+        let cursor = &text[cursor_start + 8..];
+        let cursor = &cursor[..cursor.find('"').unwrap_or(cursor.len())];
+        println!("   ✓ First page returned next cursor {}", cursor);
+
+        let next = ctx
+            .call_tool(
+                "find_incomplete_todos",
+                Some(json!({"limit": 1, "cursor": cursor})),
+            )
+            .await?;
+        assert_ne!(
+            next.get("isError").and_then(|e| e.as_bool()),
+            Some(true),
+            "Fetching the next page should succeed"
+        );
+        println!("   ✓ Second page fetched");
+    } else {
+        println!("   ⚠ Fewer than two todos in graph; pagination not exercised");
+    }
+
+    let bad_cursor = ctx
+        .call_tool(
+            "find_incomplete_todos",
+            Some(json!({"cursor": "not-a-cursor"})),
+        )
+        .await;
+    assert!(bad_cursor.is_err(), "Invalid cursor should be rejected");
+
+    ctx.cleanup().await;
+    Ok(())
+}
+
 /// Comprehensive end-to-end MCP test
 #[tokio::test]
 #[ignore]